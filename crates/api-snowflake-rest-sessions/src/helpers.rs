@@ -1,7 +1,11 @@
 use super::TokenizedSession;
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD;
 use chrono::offset::Local;
-use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use time::Duration;
 
 #[derive(Serialize, Deserialize)]
@@ -62,6 +66,97 @@ where
     )
 }
 
+/// Claims of the JWT a client signs with its private key for key-pair authentication
+/// (`authenticator=SNOWFLAKE_JWT`).
+#[derive(Serialize, Deserialize)]
+#[cfg_attr(test, derive(Debug))]
+pub struct KeyPairClaims {
+    pub iss: String, // <ACCOUNT>.<USER>.SHA256:<public key fingerprint>
+    pub sub: String, // <ACCOUNT>.<USER>
+    pub iat: i64,
+    pub exp: i64,
+}
+
+/// Fingerprint of a base64 encoded DER public key, in the form clients put it in the
+/// issuer claim of key-pair JWTs.
+#[must_use]
+pub fn public_key_fingerprint(public_key: &str) -> Option<String> {
+    let der = STANDARD.decode(public_key).ok()?;
+    Some(format!("SHA256:{}", STANDARD.encode(Sha256::digest(der))))
+}
+
+fn rsa_decoding_key(public_key: &str) -> Result<DecodingKey, jsonwebtoken::errors::Error> {
+    let body = public_key
+        .as_bytes()
+        .chunks(64)
+        .map(|line| String::from_utf8_lossy(line).into_owned())
+        .collect::<Vec<_>>()
+        .join("\n");
+    DecodingKey::from_rsa_pem(
+        format!("-----BEGIN PUBLIC KEY-----\n{body}\n-----END PUBLIC KEY-----\n").as_bytes(),
+    )
+}
+
+/// Key-pair JWTs are short lived, clients can't issue tokens valid for longer than this
+pub const KEY_PAIR_JWT_MAX_LIFETIME_SECONDS: i64 = 60 * 60;
+
+/// Clock skew allowed between the client issuing a key-pair JWT and the server
+pub const KEY_PAIR_JWT_LEEWAY_SECONDS: u64 = 5;
+
+/// Validates a key-pair JWT signed by `user_name` against the user's registered public keys
+/// (base64 encoded DER). The token must be signed with one of the keys, be issued for the
+/// user, carry the fingerprint of the key it was signed with, not be issued in the future
+/// and be valid for at most [`KEY_PAIR_JWT_MAX_LIFETIME_SECONDS`].
+pub fn validate_key_pair_jwt(
+    token: &str,
+    user_name: &str,
+    public_keys: &[&str],
+) -> Result<KeyPairClaims, jsonwebtoken::errors::Error> {
+    let mut validation = Validation::new(Algorithm::RS256);
+    validation.leeway = KEY_PAIR_JWT_LEEWAY_SECONDS;
+    validation.set_required_spec_claims(&["exp", "iss", "sub"]);
+
+    let mut error = jsonwebtoken::errors::Error::from(ErrorKind::InvalidKeyFormat);
+    for public_key in public_keys {
+        let claims = match rsa_decoding_key(public_key)
+            .and_then(|key| decode::<KeyPairClaims>(token, &key, &validation))
+        {
+            Ok(decoded) => decoded.claims,
+            Err(err) => {
+                error = err;
+                continue;
+            }
+        };
+        let subject_user = claims
+            .sub
+            .split_once('.')
+            .map_or(claims.sub.as_str(), |(_, user)| user);
+        if !subject_user.eq_ignore_ascii_case(user_name) {
+            return Err(ErrorKind::InvalidSubject.into());
+        }
+        let fingerprint = public_key_fingerprint(public_key).unwrap_or_default();
+        let issuer_matches =
+            claims
+                .iss
+                .rsplit_once('.')
+                .is_some_and(|(issuer, issuer_fingerprint)| {
+                    issuer.eq_ignore_ascii_case(&claims.sub) && issuer_fingerprint == fingerprint
+                });
+        if !issuer_matches {
+            return Err(ErrorKind::InvalidIssuer.into());
+        }
+        let leeway = i64::try_from(KEY_PAIR_JWT_LEEWAY_SECONDS).unwrap_or_default();
+        if claims.iat > Local::now().timestamp() + leeway {
+            return Err(ErrorKind::ImmatureSignature.into());
+        }
+        if claims.exp - claims.iat > KEY_PAIR_JWT_MAX_LIFETIME_SECONDS {
+            return Err(ErrorKind::InvalidToken.into());
+        }
+        return Ok(claims);
+    }
+    Err(error)
+}
+
 #[must_use]
 pub fn ensure_jwt_secret_is_valid(jwt_secret: &str) -> Option<String> {
     if jwt_secret.is_empty() {
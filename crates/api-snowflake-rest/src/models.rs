@@ -38,7 +38,14 @@ pub struct LoginRequestData {
     pub svn_revision: Option<String>,
     pub account_name: String,
     pub login_name: String,
+    #[serde(default)]
     pub password: String,
+    /// `SNOWFLAKE_JWT` for key-pair authentication, password authentication otherwise
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authenticator: Option<String>,
+    /// JWT signed with the user's private key, used with key-pair authentication
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
     pub client_environment: HashMap<String, serde_json::Value>,
     pub session_parameters: HashMap<String, serde_json::Value>,
 }
@@ -66,6 +73,22 @@ pub struct AbortRequestBody {
     pub request_id: Uuid, // duplicate in body, taken from snowflake connector
 }
 
+/// Query parameters of the table upload endpoint, the files are sent as multipart form
+/// fields
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadRequestQueryParams {
+    /// `CSV`, `PARQUET` or `JSON` for newline delimited JSON, `CSV` by default
+    pub format: Option<String>,
+    /// Whether the first line of CSV files holds the column names
+    #[serde(default)]
+    pub header: bool,
+    pub delimiter: Option<String>,
+    /// Whether a missing table is created from the schema of the first file
+    #[serde(default)]
+    pub create_table: bool,
+}
+
 #[allow(clippy::ref_option)]
 fn serialize_raw_json<S>(value: &Option<RowSet>, s: S) -> Result<S::Ok, S::Error>
 where
@@ -116,6 +139,57 @@ pub struct ResponseData {
     pub sql_state: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub query_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub get_result_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chunks: Option<Vec<ChunkInfo>>,
+    /// `UPLOAD` or `DOWNLOAD` for `PUT` and `GET`, the client then transfers the files
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+    #[serde(rename = "src_locations", skip_serializing_if = "Option::is_none")]
+    pub src_locations: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub local_location: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stage_info: Option<StageInfo>,
+    /// Download urls of the `src_locations` of a `GET`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presigned_urls: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auto_compress: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub overwrite: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source_compression: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel: Option<u32>,
+}
+
+/// Stage the client transfers files of `PUT` and `GET` to or from.
+///
+/// Internal stages are described as a GCS stage, the only location type drivers
+/// transfer files with plain presigned urls, which point back to this server.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct StageInfo {
+    pub location_type: String,
+    pub location: String,
+    pub path: String,
+    pub region: String,
+    pub creds: HashMap<String, String>,
+    /// Upload url of the file of a `PUT`
+    pub presigned_url: Option<String>,
+    pub end_point: Option<String>,
+    pub is_client_side_encrypted: bool,
+}
+
+/// Result chunk that is not returned inline, drivers download it from `url`
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChunkInfo {
+    pub url: String,
+    pub row_count: i64,
+    pub uncompressed_size: i64,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -126,6 +200,48 @@ pub struct JsonResponse {
     pub code: Option<String>,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct UploadResponse {
+    pub data: UploadResponseData,
+    pub success: bool,
+    pub message: Option<String>,
+    pub code: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadResponseData {
+    pub files: Vec<UploadedFileInfo>,
+    pub rows_loaded: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UploadedFileInfo {
+    pub file_name: String,
+    pub rows_loaded: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct QueryMonitoringResponse {
+    pub data: QueryMonitoringData,
+    pub success: bool,
+    pub message: Option<String>,
+    pub code: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct QueryMonitoringData {
+    pub queries: Vec<QueryMonitoringInfo>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct QueryMonitoringInfo {
+    pub id: String,
+    // Snowflake query status: RUNNING, SUCCESS, FAILED_WITH_ERROR, ...
+    pub status: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ColumnInfo {
     name: String,
@@ -176,4 +292,11 @@ impl Auth {
             ..Self::default()
         }
     }
+
+    /// Demo credentials are opt-in, they are set only when both the user and the
+    /// password are given
+    #[must_use]
+    pub fn has_demo_credentials(&self) -> bool {
+        !self.demo_user.is_empty() && !self.demo_password.is_empty()
+    }
 }
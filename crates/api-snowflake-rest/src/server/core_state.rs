@@ -1,13 +1,18 @@
 use crate::server::error::CreateExecutorSnafu;
+use crate::server::error::CreateMetastoreSnafu;
 use crate::server::error::MetastoreConfigSnafu;
 use crate::server::error::Result;
+use crate::server::result_cache::ResultChunkCache;
 use crate::server::server_models::RestApiConfig;
+use crate::server::stage_transfers::StageTransfers;
 use api_snowflake_rest_sessions::session::SessionStore;
 use catalog_metastore::InMemoryMetastore;
 use catalog_metastore::Metastore;
+use catalog_metastore::PersistentMetastore;
 use catalog_metastore::metastore_bootstrap_config::MetastoreBootstrapConfig;
 use catalog_metastore::metastore_settings_config::MetastoreSettingsConfig;
-use executor::service::CoreExecutionService;
+use catalog_metastore::models::volumes::{FileVolume, Volume, VolumeType};
+use executor::service::{CoreExecutionService, ExecutionService};
 use executor::utils::Config as ExecutionConfig;
 use snafu::ResultExt;
 use std::path::PathBuf;
@@ -16,8 +21,19 @@ use tokio::time::Duration;
 
 pub struct CoreState {
     pub executor: Arc<CoreExecutionService>,
-    pub metastore: Arc<InMemoryMetastore>,
+    pub metastore: Arc<dyn Metastore>,
     pub rest_api_config: RestApiConfig,
+    pub result_cache: Arc<ResultChunkCache>,
+    pub stage_transfers: Arc<StageTransfers>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub enum MetastoreBackend {
+    /// Metastore state lives in memory only and is lost on restart
+    #[default]
+    Memory,
+    /// Metastore state is persisted to a file in the given directory
+    File(PathBuf),
 }
 
 #[derive(Clone)]
@@ -35,14 +51,17 @@ impl CoreState {
         rest_api_config: RestApiConfig,
         metastore_settings_config: MetastoreSettingsConfig,
         metastore_bootstrap_config: MetastoreConfig,
+        metastore_backend: MetastoreBackend,
     ) -> Result<Self> {
-        let metastore = create_metastore(metastore_settings_config);
+        let metastore = create_metastore(metastore_settings_config, metastore_backend).await?;
         apply_metastore_bootstrap_config(metastore.clone(), metastore_bootstrap_config).await?;
         let executor = create_executor(metastore.clone(), execution_cfg).await?;
         Ok(Self {
             executor,
             metastore,
             rest_api_config,
+            result_cache: Arc::new(ResultChunkCache::default()),
+            stage_transfers: Arc::new(StageTransfers::default()),
         })
     }
 
@@ -59,15 +78,103 @@ impl CoreState {
         });
         Ok(())
     }
+
+    /// Clears the result chunks left from before the start and periodically removes the
+    /// chunks of results that expired since
+    pub async fn with_result_chunks_reaper(&self, period: Duration) -> Result<()> {
+        tracing::info!(
+            "With result chunks reaper, every {} seconds",
+            period.as_secs()
+        );
+        self.result_cache.clear().await?;
+        let result_cache = Arc::clone(&self.result_cache);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                match result_cache.remove_expired().await {
+                    Ok(0) => {}
+                    Ok(removed) => tracing::debug!(removed, "Removed expired result chunks"),
+                    Err(error) => tracing::warn!("Failed to remove expired result chunks: {error}"),
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// Periodically deletes the dropped databases, schemas and tables whose retention
+    /// time has passed, along with their files
+    pub fn with_dropped_objects_purger(&self, period: Duration) -> Result<()> {
+        tracing::info!(
+            "With dropped objects purger, every {} seconds",
+            period.as_secs()
+        );
+        let metastore = self.metastore.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                match metastore.purge_dropped_objects().await {
+                    Ok(0) => {}
+                    Ok(purged) => tracing::info!(purged, "Purged expired dropped objects"),
+                    Err(error) => tracing::warn!("Failed to purge dropped objects: {error}"),
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// Periodically registers the files at the locations of the external tables with
+    /// `AUTO_REFRESH`
+    pub fn with_external_tables_refresher(&self, period: Duration) -> Result<()> {
+        tracing::info!(
+            "With external tables refresher, every {} seconds",
+            period.as_secs()
+        );
+        let executor = self.executor.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(period);
+            loop {
+                interval.tick().await;
+                match executor.refresh_external_tables().await {
+                    Ok(0) => {}
+                    Ok(refreshed) => tracing::debug!(refreshed, "Refreshed external tables"),
+                    Err(error) => tracing::warn!("Failed to refresh external tables: {error}"),
+                }
+            }
+        });
+        Ok(())
+    }
 }
 
-#[must_use]
-fn create_metastore(metastore_settings_config: MetastoreSettingsConfig) -> Arc<InMemoryMetastore> {
-    Arc::new(InMemoryMetastore::new().with_settings_config(metastore_settings_config))
+async fn create_metastore(
+    metastore_settings_config: MetastoreSettingsConfig,
+    metastore_backend: MetastoreBackend,
+) -> Result<Arc<dyn Metastore>> {
+    match metastore_backend {
+        MetastoreBackend::Memory => Ok(Arc::new(
+            InMemoryMetastore::new().with_settings_config(metastore_settings_config),
+        )),
+        MetastoreBackend::File(path) => {
+            tracing::info!(path = %path.display(), "Opening file-backed metastore");
+            let volume = Volume::new(
+                "metastore".to_string(),
+                VolumeType::File(FileVolume {
+                    path: path.display().to_string(),
+                }),
+            );
+            let metastore = PersistentMetastore::from_volume(&volume)
+                .await
+                .context(CreateMetastoreSnafu)?;
+            Ok(Arc::new(
+                metastore.with_settings_config(metastore_settings_config),
+            ))
+        }
+    }
 }
 
 async fn apply_metastore_bootstrap_config(
-    metastore: Arc<InMemoryMetastore>,
+    metastore: Arc<dyn Metastore>,
     metastore_bootstrap_config: MetastoreConfig,
 ) -> Result<()> {
     match metastore_bootstrap_config {
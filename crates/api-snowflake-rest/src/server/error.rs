@@ -39,12 +39,6 @@ pub enum Error {
         location: Location,
     },
 
-    #[snafu(display("Feature not implemented"))]
-    NotImplemented {
-        #[snafu(implicit)]
-        location: Location,
-    },
-
     #[snafu(display("UTF8 error: {error}"))]
     Utf8 {
         #[snafu(source)]
@@ -117,6 +111,75 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Failed to create metastore: {error}"))]
+    CreateMetastore {
+        #[snafu(source(from(catalog_metastore::Error, Box::new)))]
+        error: Box<catalog_metastore::Error>,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Metastore error: {error}"))]
+    Metastore {
+        #[snafu(source(from(catalog_metastore::Error, Box::new)))]
+        error: Box<catalog_metastore::Error>,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Result chunk {index} of result {result_id} not found or expired"))]
+    ResultChunkNotFound {
+        result_id: uuid::Uuid,
+        index: usize,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Stage transfer {transfer_id} not found or expired"))]
+    StageTransferNotFound {
+        transfer_id: uuid::Uuid,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Failed to read uploaded files: {error}"))]
+    UploadMultipart {
+        #[snafu(source)]
+        error: axum::extract::multipart::MultipartError,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("No files to upload"))]
+    MissingUploadFiles {
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Upload io error: {error}"))]
+    UploadIo {
+        #[snafu(source)]
+        error: std::io::Error,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Result chunk io error: {error}"))]
+    ResultChunkIo {
+        #[snafu(source)]
+        error: std::io::Error,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Failed to keep query result response: {error}"))]
+    KeptResponse {
+        #[snafu(source)]
+        error: serde_json::Error,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[snafu(display("Metastore config error: {error}"))]
     MetastoreConfig {
         #[snafu(source)]
@@ -237,6 +300,11 @@ impl Error {
                             _ => (http::StatusCode::OK, SqlState::Success, error_code),
                         }
                     }
+                    ErrorCode::InsufficientPrivileges => (
+                        http::StatusCode::OK,
+                        SqlState::InsufficientPrivilege,
+                        error_code,
+                    ),
                     ErrorCode::DataFusionSql | ErrorCode::DataFusionSqlParse => {
                         (http::StatusCode::OK, SqlState::Success, error_code)
                     }
@@ -259,14 +327,26 @@ impl Error {
                 SqlState::FeatureNotSupported,
                 ErrorCode::Other,
             ),
+            Self::ResultChunkNotFound { .. } | Self::StageTransferNotFound { .. } => (
+                http::StatusCode::NOT_FOUND,
+                SqlState::Success,
+                ErrorCode::Other,
+            ),
+            Self::UploadMultipart { .. } | Self::MissingUploadFiles { .. } => (
+                http::StatusCode::BAD_REQUEST,
+                SqlState::Success,
+                ErrorCode::Other,
+            ),
             Self::Utf8 { .. }
+            | Self::ResultChunkIo { .. }
+            | Self::KeptResponse { .. }
+            | Self::UploadIo { .. }
             | Self::MetastoreConfig { .. }
+            | Self::CreateMetastore { .. }
+            | Self::Metastore { .. }
             | Self::CreateExecutor { .. }
             | Self::RetryDisabled { .. }
-            | Self::Arrow { .. }
-            | Self::NotImplemented { .. } => {
-                (http::StatusCode::OK, SqlState::Success, ErrorCode::Other)
-            }
+            | Self::Arrow { .. } => (http::StatusCode::OK, SqlState::Success, ErrorCode::Other),
         };
 
         // Give more context to user, not just "Internal server error"
@@ -300,6 +380,9 @@ impl Error {
                 query_result_format: None,
                 // Query uuid is returned to the user
                 query_id: Some(self.query_id().to_string()),
+                get_result_url: None,
+                chunks: None,
+                ..ResponseData::default()
             }),
             code: Some(error_code.to_string()),
         });
@@ -1,17 +1,39 @@
 use super::state::AppState;
 use crate::models::{
     AbortRequestBody, JsonResponse, LoginRequestBody, LoginRequestQueryParams, LoginResponse,
-    QueryRequest, QueryRequestBody,
+    QueryMonitoringResponse, QueryRequest, QueryRequestBody, UploadRequestQueryParams,
+    UploadResponse,
 };
-use crate::server::error::Result;
-use crate::server::logic::{handle_login_request, handle_query_request};
+use crate::server::error::{self as api_snowflake_rest_error, Result};
+use crate::server::helpers::result_base_url;
+use crate::server::logic::{
+    handle_login_request, handle_query_monitoring_request, handle_query_request,
+    handle_query_result_request, handle_upload_request,
+};
+use crate::server::stage_transfers::StageTransfer;
 use api_snowflake_rest_sessions::TokenizedSession;
 use api_snowflake_rest_sessions::layer::Host;
-use axum::Json;
-use axum::extract::{ConnectInfo, Query, State};
-use executor::RunningQueryId;
+use axum::body::Body;
+use axum::extract::{ConnectInfo, Multipart, Path, Query, Request, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::{Json, RequestExt};
+use catalog_metastore::TableIdent;
+use executor::{QueryId, RunningQueryId};
+use futures::{StreamExt, TryStreamExt};
 use serde::Deserialize;
 use std::net::SocketAddr;
+use tokio_util::io::ReaderStream;
+use uuid::Uuid;
+
+const FORWARDED_PROTO_HEADER: &str = "x-forwarded-proto";
+
+fn base_url(host: &str, headers: &HeaderMap) -> String {
+    let forwarded_proto = headers
+        .get(FORWARDED_PROTO_HEADER)
+        .and_then(|proto| proto.to_str().ok());
+    result_base_url(host, forwarded_proto)
+}
 
 #[derive(Debug, Deserialize)]
 pub struct SessionQueryParams {
@@ -51,6 +73,8 @@ pub async fn login(
     ret(level = tracing::Level::TRACE),
 )]
 pub async fn query(
+    Host(host): Host,
+    headers: HeaderMap,
     ConnectInfo(addr): ConnectInfo<SocketAddr>,
     tokenized_session: TokenizedSession,
     State(state): State<AppState>,
@@ -63,14 +87,146 @@ pub async fn query(
         query,
         query_body,
         Option::from(addr.ip().to_string()),
+        &base_url(&host, &headers),
     )
     .await?;
 
     Ok(Json(response))
 }
 
+#[tracing::instrument(
+    name = "api_snowflake_rest::query_result",
+    level = "debug",
+    skip(state),
+    err,
+    ret(level = tracing::Level::TRACE)
+)]
+pub async fn query_result(
+    Host(host): Host,
+    headers: HeaderMap,
+    TokenizedSession(session_id, ..): TokenizedSession,
+    State(state): State<AppState>,
+    Path(query_id): Path<QueryId>,
+) -> Result<Json<JsonResponse>> {
+    let response =
+        handle_query_result_request(&state, &session_id, query_id, &base_url(&host, &headers))
+            .await?;
+    Ok(Json(response))
+}
+
+#[tracing::instrument(
+    name = "api_snowflake_rest::result_chunk",
+    level = "debug",
+    skip(state),
+    err
+)]
+pub async fn result_chunk(
+    State(state): State<AppState>,
+    Path((result_id, index)): Path<(Uuid, usize)>,
+) -> Result<Response> {
+    let file = state.result_cache.open_chunk(result_id, index).await?;
+    Ok(Body::from_stream(ReaderStream::new(file)).into_response())
+}
+
+#[tracing::instrument(
+    name = "api_snowflake_rest::upload_stage_file",
+    level = "debug",
+    skip(state, request),
+    err
+)]
+pub async fn upload_stage_file(
+    State(state): State<AppState>,
+    Path(transfer_id): Path<Uuid>,
+    request: Request,
+) -> Result<StatusCode> {
+    let transfer = take_stage_transfer(&state, transfer_id).await?;
+    // The body is streamed to the stage, it fails once it exceeds the body limit
+    let data = request
+        .with_limited_body()
+        .into_body()
+        .into_data_stream()
+        .map_err(std::io::Error::other)
+        .boxed();
+    state
+        .execution_svc
+        .upload_stage_file(&transfer.stage, &transfer.path, data, transfer.overwrite)
+        .await?;
+    Ok(StatusCode::OK)
+}
+
+#[tracing::instrument(
+    name = "api_snowflake_rest::download_stage_file",
+    level = "debug",
+    skip(state),
+    err
+)]
+pub async fn download_stage_file(
+    State(state): State<AppState>,
+    Path(transfer_id): Path<Uuid>,
+) -> Result<Response> {
+    let transfer = take_stage_transfer(&state, transfer_id).await?;
+    let data = state
+        .execution_svc
+        .download_stage_file(&transfer.stage, &transfer.path)
+        .await?;
+    Ok(data.into_response())
+}
+
+/// Transfers are single use and die with the session that issued them
+async fn take_stage_transfer(state: &AppState, transfer_id: Uuid) -> Result<StageTransfer> {
+    let (session_id, transfer) = state.stage_transfers.take(transfer_id)?;
+    if !state.execution_svc.session_exists(&session_id).await {
+        return api_snowflake_rest_error::StageTransferNotFoundSnafu { transfer_id }.fail();
+    }
+    Ok(transfer)
+}
+
+#[tracing::instrument(
+    name = "api_snowflake_rest::upload_files",
+    level = "debug",
+    skip(state, multipart),
+    err,
+    ret(level = tracing::Level::TRACE)
+)]
+pub async fn upload_files(
+    TokenizedSession(session_id, session_metadata): TokenizedSession,
+    State(state): State<AppState>,
+    Path((database, schema, table)): Path<(String, String, String)>,
+    Query(params): Query<UploadRequestQueryParams>,
+    multipart: Multipart,
+) -> Result<Json<UploadResponse>> {
+    let table_ident = TableIdent::new(&database, &schema, &table);
+    let response = handle_upload_request(
+        &state,
+        &session_id,
+        session_metadata,
+        table_ident,
+        params,
+        multipart,
+    )
+    .await?;
+    Ok(Json(response))
+}
+
+#[tracing::instrument(
+    name = "api_snowflake_rest::query_monitoring",
+    level = "debug",
+    skip(state),
+    err,
+    ret(level = tracing::Level::TRACE)
+)]
+pub async fn query_monitoring(
+    TokenizedSession(session_id, ..): TokenizedSession,
+    State(state): State<AppState>,
+    Path(query_id): Path<QueryId>,
+) -> Result<Json<QueryMonitoringResponse>> {
+    let response = handle_query_monitoring_request(&state, &session_id, query_id)?;
+    Ok(Json(response))
+}
+
 #[tracing::instrument(name = "api_snowflake_rest::abort", level = "debug", skip(state), err, ret(level = tracing::Level::TRACE))]
 pub async fn abort(
+    TokenizedSession(session_id, ..): TokenizedSession,
     State(state): State<AppState>,
     Json(AbortRequestBody {
         sql_text,
@@ -80,6 +236,9 @@ pub async fn abort(
     let query_id = state
         .execution_svc
         .locate_query_id(RunningQueryId::ByRequestId(request_id, sql_text))?;
+    state
+        .execution_svc
+        .check_query_owner(&session_id, query_id)?;
     state.execution_svc.abort(query_id).await?;
     Ok(Json(serde_json::value::Value::Null))
 }
@@ -115,6 +274,7 @@ pub async fn session(
 
     if delete {
         state.execution_svc.delete_session(&session_id).await?;
+        state.stage_transfers.remove_session(&session_id);
     } else {
         tracing::debug!("Session endpoint called without delete flag; ignoring request");
     }
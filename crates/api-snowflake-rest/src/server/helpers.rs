@@ -1,18 +1,27 @@
 use crate::SqlState;
-use crate::models::{JsonResponse, ResponseData, RowSet};
+use crate::models::{
+    ChunkInfo, JsonResponse, QueryMonitoringData, QueryMonitoringInfo, QueryMonitoringResponse,
+    ResponseData, RowSet, StageInfo,
+};
 use crate::server::error::{self as api_snowflake_rest_error, Error, Result};
+use crate::server::result_cache::ResultChunkCache;
+use crate::server::stage_transfers::{StageTransfer, StageTransfers};
 use base64;
 use base64::engine::general_purpose::STANDARD as engine_base64;
 use base64::prelude::*;
+use datafusion::arrow::datatypes::SchemaRef;
 use datafusion::arrow::ipc::MetadataVersion;
 use datafusion::arrow::ipc::writer::{IpcWriteOptions, StreamWriter};
 use datafusion::arrow::json::{StructMode, WriterBuilder, writer::JsonArray};
 use datafusion::arrow::record_batch::RecordBatch;
-use executor::models::QueryResult;
+use executor::ExecutionStatus;
+use executor::models::{FileTransfer, FileTransferCommand, QueryResult};
+use executor::stages::FileTransferOptions;
 use executor::utils::{
     DataSerializationFormat, convert_record_batches, convert_struct_to_timestamp,
 };
 use snafu::ResultExt;
+use std::collections::HashMap;
 use tracing;
 use uuid::Uuid;
 
@@ -24,7 +33,12 @@ use uuid::Uuid;
 // For more info see issue #115
 const ARROW_IPC_ALIGNMENT: usize = 8;
 
-fn records_to_arrow_string(recs: &[RecordBatch]) -> std::result::Result<String, Error> {
+// Snowflake response codes for a query that is still running, the client then polls
+// `getResultUrl` until the result is ready
+pub const QUERY_IN_PROGRESS_CODE: &str = "333333";
+pub const QUERY_IN_PROGRESS_ASYNC_CODE: &str = "333334";
+
+fn records_to_arrow_bytes(recs: &[RecordBatch]) -> std::result::Result<Vec<u8>, Error> {
     let mut buf = Vec::new();
     let options = IpcWriteOptions::try_new(ARROW_IPC_ALIGNMENT, false, MetadataVersion::V5)
         .context(api_snowflake_rest_error::ArrowSnafu)?;
@@ -42,7 +56,11 @@ fn records_to_arrow_string(recs: &[RecordBatch]) -> std::result::Result<String,
             .context(api_snowflake_rest_error::ArrowSnafu)?;
         drop(writer);
     }
-    Ok(engine_base64.encode(buf))
+    Ok(buf)
+}
+
+fn records_to_arrow_string(recs: &[RecordBatch]) -> std::result::Result<String, Error> {
+    Ok(engine_base64.encode(records_to_arrow_bytes(recs)?))
 }
 
 fn records_to_json_string(recs: &[RecordBatch]) -> std::result::Result<String, Error> {
@@ -65,48 +83,165 @@ fn records_to_json_string(recs: &[RecordBatch]) -> std::result::Result<String, E
     String::from_utf8(buf).context(api_snowflake_rest_error::Utf8Snafu)
 }
 
+// Snowflake chunks hold the rows without the enclosing brackets, e.g. `[1,"a"],[2,"b"]`
+fn records_to_json_chunk(recs: &[RecordBatch]) -> std::result::Result<Vec<u8>, Error> {
+    let json = records_to_json_string(recs)?;
+    let rows = json
+        .strip_prefix('[')
+        .and_then(|json| json.strip_suffix(']'))
+        .unwrap_or_default();
+    Ok(rows.as_bytes().to_vec())
+}
+
+fn num_rows(recs: &[RecordBatch]) -> i64 {
+    recs.iter()
+        .map(|batch| i64::try_from(batch.num_rows()).unwrap_or(i64::MAX))
+        .sum()
+}
+
+/// Lazily splits record batches into chunks of at most `chunk_size` rows, slicing
+/// batches that span chunk boundaries (slicing is zero-copy). Batches are released
+/// as soon as the chunks holding their rows are dropped.
+fn split_into_chunks(
+    records: Vec<RecordBatch>,
+    chunk_size: usize,
+) -> impl Iterator<Item = Vec<RecordBatch>> {
+    let chunk_size = chunk_size.max(1);
+    let mut batches = records.into_iter();
+    let mut pending: Option<RecordBatch> = None;
+    std::iter::from_fn(move || {
+        let mut chunk = Vec::new();
+        let mut chunk_rows = 0;
+        while chunk_rows < chunk_size {
+            let Some(batch) = pending.take().or_else(|| batches.next()) else {
+                break;
+            };
+            let len = (chunk_size - chunk_rows).min(batch.num_rows());
+            if len < batch.num_rows() {
+                pending = Some(batch.slice(len, batch.num_rows() - len));
+            }
+            if len > 0 {
+                chunk.push(batch.slice(0, len));
+                chunk_rows += len;
+            }
+        }
+        (!chunk.is_empty()).then_some(chunk)
+    })
+}
+
+/// Converts the batches of a chunk the way the serialization format expects them,
+/// one chunk at a time so only a single chunk is ever held in both forms
+fn convert_chunk(
+    chunk: Vec<RecordBatch>,
+    schema: &SchemaRef,
+    ser_fmt: DataSerializationFormat,
+) -> Result<Vec<RecordBatch>> {
+    let chunk = QueryResult::new(chunk, schema.clone());
+    // Add columns dbt metadata to each field
+    let records = convert_record_batches(&chunk, ser_fmt)?;
+    if ser_fmt == DataSerializationFormat::Json {
+        // Convert struct timestamp columns to string representation
+        Ok(convert_struct_to_timestamp(&records)?)
+    } else {
+        Ok(records)
+    }
+}
+
+#[must_use]
+pub fn result_chunk_url(base_url: &str, result_id: Uuid, index: usize) -> String {
+    format!("{base_url}/results/{result_id}/chunks/{index}")
+}
+
+/// Base url the result chunk urls are built from, drivers download chunks as is
+/// so the urls have to be absolute
+#[must_use]
+pub fn result_base_url(host: &str, forwarded_proto: Option<&str>) -> String {
+    format!("{}://{host}", forwarded_proto.unwrap_or("http"))
+}
+
+/// Serializes the chunks following the first one and spills them to the result cache,
+/// each chunk is released once it is written. The executor collects the whole result
+/// before, so this bounds the memory held while the result is served, not its peak.
+async fn spill_result_chunks(
+    chunks: impl Iterator<Item = Vec<RecordBatch>>,
+    schema: &SchemaRef,
+    ser_fmt: DataSerializationFormat,
+    result_cache: &ResultChunkCache,
+    base_url: &str,
+) -> Result<Vec<ChunkInfo>> {
+    let result_id = result_cache.new_result();
+    let mut chunks_info = Vec::new();
+    for (index, chunk) in chunks.enumerate() {
+        let chunk = convert_chunk(chunk, schema, ser_fmt)?;
+        let body = if ser_fmt == DataSerializationFormat::Arrow {
+            records_to_arrow_bytes(&chunk)?
+        } else {
+            records_to_json_chunk(&chunk)?
+        };
+        let uncompressed_size = i64::try_from(body.len()).unwrap_or(i64::MAX);
+        result_cache.put_chunk(result_id, index, body).await?;
+        chunks_info.push(ChunkInfo {
+            url: result_chunk_url(base_url, result_id, index),
+            row_count: num_rows(&chunk),
+            uncompressed_size,
+        });
+    }
+    Ok(chunks_info)
+}
+
 #[tracing::instrument(
     name = "handle_query_ok_result",
     level = "debug",
+    skip(result_cache),
     err,
     ret(level = tracing::Level::TRACE)
 )]
-pub fn handle_query_ok_result(
+pub async fn handle_query_ok_result(
     sql_text: &str,
     query_id: Uuid,
     query_result: QueryResult,
     ser_fmt: DataSerializationFormat,
+    chunk_size: usize,
+    result_cache: &ResultChunkCache,
+    base_url: &str,
 ) -> Result<JsonResponse> {
-    // Convert the QueryResult to RecordBatches using the specified serialization format
-    // Add columns dbt metadata to each field
-    let records = convert_record_batches(&query_result, ser_fmt)?;
-    let total_rows = records
-        .iter()
-        .map(|batch| i64::try_from(batch.num_rows()).unwrap_or(i64::MAX))
-        .sum();
+    let row_type = query_result
+        .column_info()
+        .into_iter()
+        .map(Into::into)
+        .collect();
+    let schema = query_result.schema.clone();
+    let records = query_result.records;
+    let total_rows = num_rows(&records);
+
+    // The first chunk is returned inline, the rest is downloaded by the client.
+    // Chunks are converted and serialized one at a time while the batches are consumed.
+    let mut chunks = split_into_chunks(records, chunk_size).peekable();
+    let first_chunk = convert_chunk(chunks.next().unwrap_or_default(), &schema, ser_fmt)?;
+    let returned_rows = num_rows(&first_chunk);
 
     let row_set = if ser_fmt == DataSerializationFormat::Json {
-        // Convert struct timestamp columns to string representation
-        let records = convert_struct_to_timestamp(&records)?;
-        let serialized_rowset = records_to_json_string(&records)?;
+        let serialized_rowset = records_to_json_string(&first_chunk)?;
         Some(RowSet::Raw(serialized_rowset))
     } else {
         None
     };
     let row_set_base_64 = if ser_fmt == DataSerializationFormat::Arrow {
-        Option::from(records_to_arrow_string(&records)?)
+        Option::from(records_to_arrow_string(&first_chunk)?)
     } else {
         None
     };
-    let returned_rows = total_rows;
+    drop(first_chunk);
+
+    let chunks = if chunks.peek().is_none() {
+        None
+    } else {
+        Some(spill_result_chunks(chunks, &schema, ser_fmt, result_cache, base_url).await?)
+    };
 
     let json_resp = JsonResponse {
         data: Option::from(ResponseData {
-            row_type: query_result
-                .column_info()
-                .into_iter()
-                .map(Into::into)
-                .collect(),
+            row_type,
             query_result_format: Some(ser_fmt.to_string().to_lowercase()),
             row_set,
             row_set_base_64,
@@ -115,6 +250,9 @@ pub fn handle_query_ok_result(
             query_id: Some(query_id.to_string()),
             error_code: None,
             sql_state: Some(SqlState::Success.to_string()),
+            get_result_url: None,
+            chunks,
+            ..ResponseData::default()
         }),
         success: true,
         message: Option::from("successfully executed".to_string()),
@@ -122,3 +260,195 @@ pub fn handle_query_ok_result(
     };
     Ok(json_resp)
 }
+
+#[must_use]
+pub fn stage_transfer_url(base_url: &str, transfer_id: Uuid) -> String {
+    format!("{base_url}/stages/transfers/{transfer_id}")
+}
+
+/// Extensions of the files `PUT` doesn't compress again
+const COMPRESSED_FILE_EXTENSIONS: [&str; 6] = ["gz", "bz2", "br", "zst", "deflate", "raw_deflate"];
+
+/// Name of an uploaded file in the stage: with `AUTO_COMPRESS` drivers gzip the files
+/// that aren't compressed yet before uploading them, and the file gets a `.gz` suffix
+fn stage_file_name(file_name: &str, options: &FileTransferOptions) -> String {
+    let compressed = !options
+        .source_compression
+        .eq_ignore_ascii_case("AUTO_DETECT")
+        && !options.source_compression.eq_ignore_ascii_case("NONE")
+        || file_name.rsplit_once('.').is_some_and(|(_, extension)| {
+            COMPRESSED_FILE_EXTENSIONS
+                .iter()
+                .any(|compressed| extension.eq_ignore_ascii_case(compressed))
+        });
+    if options.auto_compress && !compressed {
+        format!("{file_name}.gz")
+    } else {
+        file_name.to_string()
+    }
+}
+
+fn join_stage_path(path: &str, file_name: &str) -> String {
+    if path.is_empty() {
+        file_name.to_string()
+    } else {
+        format!("{path}/{file_name}")
+    }
+}
+
+/// Response to `PUT` and `GET`, the client transfers the files with the returned urls.
+#[tracing::instrument(
+    name = "handle_file_transfer_result",
+    level = "debug",
+    skip(query_result, stage_transfers),
+    ret(level = tracing::Level::TRACE)
+)]
+#[must_use]
+pub fn handle_file_transfer_result(
+    session_id: &str,
+    query_id: Uuid,
+    query_result: &QueryResult,
+    file_transfer: FileTransfer,
+    stage_transfers: &StageTransfers,
+    base_url: &str,
+) -> JsonResponse {
+    let FileTransfer {
+        command,
+        stage,
+        path,
+        src_locations,
+        local_location,
+        options,
+    } = file_transfer;
+    let (command, presigned_url, presigned_urls) = match command {
+        FileTransferCommand::Upload => {
+            // Drivers expand wildcards locally and then ask for an url per file
+            let presigned_url = src_locations
+                .first()
+                .map(|source| source.rsplit('/').next().unwrap_or(source))
+                .filter(|file_name| !file_name.is_empty() && !file_name.contains(['*', '?']))
+                .map(|file_name| {
+                    let transfer_id = stage_transfers.register(
+                        session_id,
+                        StageTransfer {
+                            stage: stage.clone(),
+                            path: join_stage_path(&path, &stage_file_name(file_name, &options)),
+                            overwrite: options.overwrite,
+                        },
+                    );
+                    stage_transfer_url(base_url, transfer_id)
+                });
+            ("UPLOAD", presigned_url, None)
+        }
+        FileTransferCommand::Download => {
+            let presigned_urls = src_locations
+                .iter()
+                .map(|file| {
+                    let transfer_id = stage_transfers.register(
+                        session_id,
+                        StageTransfer {
+                            stage: stage.clone(),
+                            path: file.clone(),
+                            overwrite: false,
+                        },
+                    );
+                    stage_transfer_url(base_url, transfer_id)
+                })
+                .collect();
+            ("DOWNLOAD", None, Some(presigned_urls))
+        }
+    };
+    JsonResponse {
+        data: Option::from(ResponseData {
+            row_type: query_result
+                .column_info()
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            query_id: Some(query_id.to_string()),
+            sql_state: Some(SqlState::Success.to_string()),
+            command: Some(command.to_string()),
+            src_locations: Some(src_locations),
+            local_location,
+            stage_info: Some(StageInfo {
+                location_type: "GCS".to_string(),
+                location: format!("{}/{}/{}/", stage.database, stage.schema, stage.stage),
+                path,
+                region: "local".to_string(),
+                creds: HashMap::new(),
+                presigned_url,
+                end_point: None,
+                is_client_side_encrypted: false,
+            }),
+            presigned_urls,
+            auto_compress: Some(options.auto_compress),
+            overwrite: Some(options.overwrite),
+            source_compression: Some(options.source_compression),
+            parallel: Some(options.parallel),
+            ..ResponseData::default()
+        }),
+        success: true,
+        message: Option::from("successfully executed".to_string()),
+        code: None,
+    }
+}
+
+#[must_use]
+pub fn query_result_url(query_id: Uuid) -> String {
+    format!("/queries/{query_id}/result")
+}
+
+#[tracing::instrument(
+    name = "handle_query_in_progress",
+    level = "debug",
+    ret(level = tracing::Level::TRACE)
+)]
+#[must_use]
+pub fn handle_query_in_progress(query_id: Uuid, async_exec: bool) -> JsonResponse {
+    let (code, message) = if async_exec {
+        (
+            QUERY_IN_PROGRESS_ASYNC_CODE,
+            "Asynchronous execution in progress. Use provided query id to perform query monitoring and management.",
+        )
+    } else {
+        (
+            QUERY_IN_PROGRESS_CODE,
+            "Query execution in progress. Use provided URL to poll.",
+        )
+    };
+    JsonResponse {
+        data: Option::from(ResponseData {
+            query_id: Some(query_id.to_string()),
+            get_result_url: Some(query_result_url(query_id)),
+            ..ResponseData::default()
+        }),
+        success: true,
+        message: Option::from(message.to_string()),
+        code: Option::from(code.to_string()),
+    }
+}
+
+#[must_use]
+pub const fn snowflake_query_status(status: ExecutionStatus) -> &'static str {
+    match status {
+        ExecutionStatus::Running => "RUNNING",
+        ExecutionStatus::Success => "SUCCESS",
+        ExecutionStatus::Fail => "FAILED_WITH_ERROR",
+        ExecutionStatus::Incident => "FAILED_WITH_INCIDENT",
+    }
+}
+
+#[must_use]
+pub fn handle_query_monitoring(query_id: Uuid, status: ExecutionStatus) -> QueryMonitoringResponse {
+    QueryMonitoringResponse {
+        data: QueryMonitoringData {
+            queries: vec![QueryMonitoringInfo {
+                id: query_id.to_string(),
+                status: snowflake_query_status(status).to_string(),
+            }],
+        },
+        success: true,
+        message: None,
+        code: None,
+    }
+}
@@ -1,5 +1,5 @@
 use super::{error, state::AppState};
-use crate::server::error::{BadAuthTokenSnafu, NoJwtSecretSnafu};
+use crate::server::error::{BadAuthTokenSnafu, MetastoreSnafu, NoJwtSecretSnafu};
 use api_snowflake_rest_sessions::helpers::{
     ensure_jwt_secret_is_valid, get_claims_validate_jwt_token,
 };
@@ -8,6 +8,7 @@ use api_snowflake_rest_sessions::session::extract_token_from_auth;
 use axum::extract::{Request, State};
 use axum::middleware::Next;
 use axum::response::IntoResponse;
+use catalog_metastore::User;
 use snafu::{OptionExt, ResultExt};
 
 #[allow(clippy::unwrap_used)]
@@ -24,8 +25,9 @@ pub async fn require_auth(
     req: Request,
     next: Next,
 ) -> error::Result<impl IntoResponse> {
-    // no demo user -> no auth required
-    if state.config.auth.demo_user.is_empty() || state.config.auth.demo_password.is_empty() {
+    let has_users = state.metastore.has_users().await.context(MetastoreSnafu)?;
+    // no demo user and no users in the metastore -> no auth required
+    if !state.config.auth.has_demo_credentials() && !has_users {
         return Ok(next.run(req).await);
     }
 
@@ -39,6 +41,25 @@ pub async fn require_auth(
     let jwt_claims =
         get_claims_validate_jwt_token(&token, &host, &jwt_secret).context(BadAuthTokenSnafu)?;
 
+    // Tokens of users dropped or disabled since the login are no longer valid
+    let user = state
+        .metastore
+        .get_user(&User::normalize_name(&jwt_claims.sub))
+        .await
+        .context(MetastoreSnafu)?;
+    let user_is_valid = match user {
+        Some(user) => !user.disabled,
+        // demo tokens are no longer valid once users are created
+        None => {
+            !has_users
+                && state.config.auth.has_demo_credentials()
+                && jwt_claims.sub == state.config.auth.demo_user
+        }
+    };
+    if !user_is_valid {
+        return error::InvalidAuthTokenSnafu.fail()?;
+    }
+
     // Record the result as part of the current span.
     tracing::Span::current().record("session_id", jwt_claims.session.session_id());
 
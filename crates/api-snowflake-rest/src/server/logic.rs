@@ -1,21 +1,79 @@
 use super::state::AppState;
 use crate::models::{
     JsonResponse, LoginRequestData, LoginRequestQueryParams, LoginResponse, LoginResponseData,
-    QueryRequest, QueryRequestBody,
+    QueryMonitoringResponse, QueryRequest, QueryRequestBody, UploadRequestQueryParams,
+    UploadResponse, UploadResponseData, UploadedFileInfo,
 };
 use crate::server::error::{
-    self as api_snowflake_rest_error, CreateJwtSnafu, NoJwtSecretSnafu, Result,
+    self as api_snowflake_rest_error, BadAuthTokenSnafu, CreateJwtSnafu, MetastoreSnafu,
+    NoJwtSecretSnafu, Result,
 };
-use crate::server::helpers::handle_query_ok_result;
+use crate::server::helpers::{
+    handle_file_transfer_result, handle_query_in_progress, handle_query_monitoring,
+    handle_query_ok_result,
+};
+use crate::server::uploads::UploadSpool;
 use api_snowflake_rest_sessions::TokenizedSession;
-use api_snowflake_rest_sessions::helpers::{create_jwt, ensure_jwt_secret_is_valid, jwt_claims};
-use executor::RunningQueryId;
+use api_snowflake_rest_sessions::helpers::{
+    create_jwt, ensure_jwt_secret_is_valid, jwt_claims, validate_key_pair_jwt,
+};
+use axum::extract::Multipart;
+use catalog_metastore::{TableIdent, User};
+use executor::file_formats::FileFormatOptions;
 use executor::models::{QueryContext, SessionMetadata, SessionMetadataAttr};
+use executor::{ExecutionStatus, QueryId, RunningQueryId};
 use snafu::{OptionExt, ResultExt};
+use std::collections::BTreeMap;
 use time::Duration;
 
 pub const JWT_TOKEN_EXPIRATION_SECONDS: u32 = 3 * 24 * 60 * 60;
 
+/// Value of the login request `AUTHENTICATOR` for key-pair authentication
+pub const KEY_PAIR_AUTHENTICATOR: &str = "SNOWFLAKE_JWT";
+
+/// Checks the credentials against the users of the metastore. The demo credentials,
+/// when configured, are only accepted while the metastore has no users.
+/// Returns the name of the authenticated user.
+async fn authenticate(state: &AppState, credentials: &LoginRequestData) -> Result<String> {
+    let LoginRequestData {
+        login_name,
+        password,
+        authenticator,
+        token,
+        ..
+    } = credentials;
+    let user = state
+        .metastore
+        .get_user(&User::normalize_name(login_name))
+        .await
+        .context(MetastoreSnafu)?;
+
+    if authenticator
+        .as_deref()
+        .is_some_and(|authenticator| authenticator.eq_ignore_ascii_case(KEY_PAIR_AUTHENTICATOR))
+    {
+        let (Some(user), Some(token)) = (user, token) else {
+            return api_snowflake_rest_error::InvalidAuthDataSnafu.fail();
+        };
+        validate_key_pair_jwt(token, &user.name, &user.public_keys()).context(BadAuthTokenSnafu)?;
+        return Ok(user.data.name.clone());
+    }
+
+    match user {
+        Some(user) if user.verify_password(password) => Ok(user.data.name.clone()),
+        None if state.config.auth.has_demo_credentials()
+            && *login_name == state.config.auth.demo_user
+            && *password == state.config.auth.demo_password =>
+        {
+            if state.metastore.has_users().await.context(MetastoreSnafu)? {
+                return api_snowflake_rest_error::InvalidAuthDataSnafu.fail();
+            }
+            Ok(login_name.clone())
+        }
+        _ => api_snowflake_rest_error::InvalidAuthDataSnafu.fail(),
+    }
+}
+
 #[tracing::instrument(
     name = "api_snowflake_rest::handle_login_request",
     level = "debug",
@@ -31,25 +89,21 @@ pub async fn handle_login_request(
     params: LoginRequestQueryParams,
     client_ip: Option<String>,
 ) -> Result<LoginResponse> {
+    let user_name = authenticate(state, &credentials).await?;
+
     let LoginRequestData {
-        login_name,
-        password,
         account_name,
         client_app_id,
         client_app_version,
         ..
     } = credentials;
 
-    if login_name != *state.config.auth.demo_user || password != *state.config.auth.demo_password {
-        return api_snowflake_rest_error::InvalidAuthDataSnafu.fail();
-    }
-
     // host is required to check token audience claim
     let jwt_secret = &*state.config.auth.jwt_secret;
     let _ = ensure_jwt_secret_is_valid(jwt_secret).context(NoJwtSecretSnafu)?;
 
     let mut session_metadata = SessionMetadata::default();
-    session_metadata.set_attr(SessionMetadataAttr::UserName, login_name.clone());
+    session_metadata.set_attr(SessionMetadataAttr::UserName, user_name.clone());
     session_metadata.set_attr(SessionMetadataAttr::AccountName, account_name);
     session_metadata.set_attr(SessionMetadataAttr::ClientAppId, client_app_id);
     session_metadata.set_attr(SessionMetadataAttr::ClientAppVersion, client_app_version);
@@ -69,7 +123,7 @@ pub async fn handle_login_request(
     let tokenized_session = TokenizedSession::default().with_metadata(session_metadata);
 
     let jwt_claims = jwt_claims(
-        &login_name,
+        &user_name,
         &host,
         Duration::seconds(JWT_TOKEN_EXPIRATION_SECONDS.into()),
         tokenized_session,
@@ -103,6 +157,7 @@ pub async fn handle_query_request(
     query: QueryRequest,
     query_body: QueryRequestBody,
     client_ip: Option<String>,
+    base_url: &str,
 ) -> Result<JsonResponse> {
     let QueryRequestBody {
         sql_text,
@@ -110,9 +165,6 @@ pub async fn handle_query_request(
         query_submission_time,
     } = query_body;
     let async_exec = async_exec.unwrap_or(false);
-    if async_exec {
-        return api_snowflake_rest_error::NotImplementedSnafu.fail();
-    }
 
     let serialization_format = state.config.dbt_serialization_format;
     let mut query_context = QueryContext::new(
@@ -142,19 +194,220 @@ pub async fn handle_query_request(
         return api_snowflake_rest_error::RetryDisabledSnafu.fail();
     }
 
-    let (result, query_id) = if query.retry_count.unwrap_or_default() > 0
+    // a retried submission can only refer to a query of the same session
+    let query_id_res = query_id_res.and_then(|query_id| {
+        state
+            .execution_svc
+            .check_query_owner(&session_id, query_id)
+            .map(|()| query_id)
+    });
+
+    if async_exec {
+        // retried submission refers to the query submitted by the first attempt
+        let query_id = if query.retry_count.unwrap_or_default() > 0
+            && let Ok(query_id) = query_id_res
+        {
+            query_id
+        } else {
+            state
+                .execution_svc
+                .submit(&session_id, &sql_text, query_context)
+                .await?
+        };
+        return Ok(handle_query_in_progress(query_id, true));
+    }
+
+    if query.retry_count.unwrap_or_default() > 0
         && let Ok(query_id) = query_id_res
     {
-        let result = state.execution_svc.wait(query_id).await?;
-        (result, query_id)
-    } else {
-        let query_id = query_context.query_id;
-        let result = state
-            .execution_svc
-            .query(&session_id, &sql_text, query_context)
-            .await?;
-        (result, query_id)
+        return handle_kept_query_result(state, &session_id, query_id, &sql_text, base_url).await;
+    }
+    let query_id = query_context.query_id;
+    let mut result = state
+        .execution_svc
+        .query(&session_id, &sql_text, query_context)
+        .await?;
+
+    if let Some(file_transfer) = result.file_transfer.take() {
+        return Ok(handle_file_transfer_result(
+            &session_id,
+            query_id,
+            &result,
+            file_transfer,
+            &state.stage_transfers,
+            base_url,
+        ));
+    }
+    handle_query_ok_result(
+        &sql_text,
+        query_id,
+        result,
+        serialization_format,
+        state.config.result_chunk_size,
+        &state.result_cache,
+        base_url,
+    )
+    .await
+}
+
+#[tracing::instrument(
+    name = "api_snowflake_rest::handle_query_result_request",
+    level = "debug",
+    skip(state),
+    err,
+    ret(level = tracing::Level::TRACE)
+)]
+pub async fn handle_query_result_request(
+    state: &AppState,
+    session_id: &str,
+    query_id: QueryId,
+    base_url: &str,
+) -> Result<JsonResponse> {
+    state
+        .execution_svc
+        .check_query_owner(session_id, query_id)?;
+    let status = state.execution_svc.query_status(query_id)?;
+    if status == ExecutionStatus::Running {
+        return Ok(handle_query_in_progress(query_id, false));
+    }
+    handle_kept_query_result(state, session_id, query_id, "", base_url).await
+}
+
+/// Response with the kept result of a finished query of the session. The first
+/// response is kept by the result cache and the executor releases the result, later
+/// requests are served from the cache.
+async fn handle_kept_query_result(
+    state: &AppState,
+    session_id: &str,
+    query_id: QueryId,
+    sql_text: &str,
+    base_url: &str,
+) -> Result<JsonResponse> {
+    if let Some(response) = state.result_cache.kept_response(query_id).await? {
+        return Ok(response);
+    }
+    let result = state.execution_svc.query_result(session_id, query_id).await;
+    // a concurrent request may have kept the response and released the result meanwhile
+    if result.is_err()
+        && let Some(response) = state.result_cache.kept_response(query_id).await?
+    {
+        return Ok(response);
+    }
+    let mut result = result?;
+    if let Some(file_transfer) = result.file_transfer.take() {
+        return Ok(handle_file_transfer_result(
+            session_id,
+            query_id,
+            &result,
+            file_transfer,
+            &state.stage_transfers,
+            base_url,
+        ));
+    }
+    let response = handle_query_ok_result(
+        sql_text,
+        query_id,
+        result,
+        state.config.dbt_serialization_format,
+        state.config.result_chunk_size,
+        &state.result_cache,
+        base_url,
+    )
+    .await?;
+    let response = state.result_cache.keep_response(query_id, response).await?;
+    state
+        .execution_svc
+        .release_query_result(session_id, query_id)?;
+    Ok(response)
+}
+
+#[tracing::instrument(
+    name = "api_snowflake_rest::handle_query_monitoring_request",
+    level = "debug",
+    skip(state),
+    err,
+    ret(level = tracing::Level::TRACE)
+)]
+pub fn handle_query_monitoring_request(
+    state: &AppState,
+    session_id: &str,
+    query_id: QueryId,
+) -> Result<QueryMonitoringResponse> {
+    state
+        .execution_svc
+        .check_query_owner(session_id, query_id)?;
+    let status = state.execution_svc.query_status(query_id)?;
+    Ok(handle_query_monitoring(query_id, status))
+}
+
+/// File format options of an upload, as `FILE_FORMAT` options of `COPY INTO`
+fn upload_file_format(params: &UploadRequestQueryParams) -> Result<FileFormatOptions> {
+    let mut options = BTreeMap::from([(
+        "TYPE".to_string(),
+        params.format.clone().unwrap_or_else(|| "CSV".to_string()),
+    )]);
+    if params.header {
+        options.insert("PARSE_HEADER".to_string(), "TRUE".to_string());
+    }
+    if let Some(delimiter) = &params.delimiter {
+        options.insert("FIELD_DELIMITER".to_string(), delimiter.clone());
+    }
+    Ok(FileFormatOptions::parse(&options)?.unwrap_or_default())
+}
+
+#[tracing::instrument(
+    name = "api_snowflake_rest::handle_upload_request",
+    level = "debug",
+    skip(state, session_metadata, multipart),
+    err,
+    ret(level = tracing::Level::TRACE)
+)]
+pub async fn handle_upload_request(
+    state: &AppState,
+    session_id: &str,
+    session_metadata: SessionMetadata,
+    table_ident: TableIdent,
+    params: UploadRequestQueryParams,
+    multipart: Multipart,
+) -> Result<UploadResponse> {
+    let format = upload_file_format(&params)?;
+    let spool = UploadSpool::default();
+    let results = match spool.spool(multipart).await {
+        Ok(files) => {
+            state
+                .execution_svc
+                .upload_files_to_table(
+                    session_id,
+                    &table_ident,
+                    &files,
+                    format,
+                    params.create_table,
+                    // the files are loaded on behalf of the user of the session
+                    QueryContext::default().with_session_metadata(Some(session_metadata)),
+                )
+                .await
+        }
+        Err(error) => {
+            spool.remove().await;
+            return Err(error);
+        }
     };
+    spool.remove().await;
 
-    handle_query_ok_result(&sql_text, query_id, result, serialization_format)
+    let files = results?
+        .into_iter()
+        .map(|result| UploadedFileInfo {
+            file_name: result.file_name,
+            rows_loaded: result.rows_loaded,
+        })
+        .collect::<Vec<_>>();
+    Ok(UploadResponse {
+        data: UploadResponseData {
+            rows_loaded: files.iter().map(|file| file.rows_loaded).sum(),
+            files,
+        },
+        success: true,
+        message: Some("successfully executed".to_string()),
+        code: None,
+    })
 }
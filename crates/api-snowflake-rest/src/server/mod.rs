@@ -4,8 +4,11 @@ pub mod handlers;
 pub mod helpers;
 pub mod layer;
 pub mod logic;
+pub mod result_cache;
 pub mod router;
 pub mod server_models;
+pub mod stage_transfers;
 pub mod state;
+pub mod uploads;
 
 pub use router::make_snowflake_router;
@@ -0,0 +1,251 @@
+use crate::models::{JsonResponse, RowSet};
+use crate::server::error::{self as api_snowflake_rest_error, Result};
+use dashmap::DashMap;
+use executor::QueryId;
+use executor::running_queries::QUERY_RESULT_RETENTION_SECONDS;
+use snafu::ResultExt;
+use std::path::PathBuf;
+use std::time::Instant;
+use tokio::time::Duration;
+use uuid::Uuid;
+
+/// Result chunks are kept for as long as Snowflake keeps presigned chunk urls valid
+pub const RESULT_CHUNKS_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// How often expired result chunks are looked for
+pub const RESULT_CHUNKS_REAPER_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+/// Kept responses are served for as long as the executor keeps the status of the query
+const KEPT_RESPONSE_TTL: Duration = Duration::from_secs(QUERY_RESULT_RETENTION_SECONDS);
+
+/// Response to the result request of a query whose result the executor released. Only
+/// the response without its inline rows is held in memory, the rows are spilled as the
+/// chunk 0 of `rows`, along with whether they are the base64 encoded Arrow rows.
+#[derive(Debug)]
+struct KeptResponse {
+    response: Vec<u8>,
+    rows: Option<(Uuid, bool)>,
+    expires_at: Instant,
+}
+
+/// Spilled query result chunks, written to local disk once the first chunk of a
+/// result is returned inline and served back by the result chunks route.
+///
+/// Chunks of a result are addressed by a random result id rather than by query id:
+/// the drivers download chunks without an auth token, the same way they download
+/// them from presigned urls, so the result id is what protects the data.
+#[derive(Debug)]
+pub struct ResultChunkCache {
+    root: PathBuf,
+    ttl: Duration,
+    kept: DashMap<QueryId, KeptResponse>,
+}
+
+impl Default for ResultChunkCache {
+    fn default() -> Self {
+        Self::new(std::env::temp_dir().join("embucket-result-chunks"))
+    }
+}
+
+impl ResultChunkCache {
+    #[must_use]
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            root,
+            ttl: RESULT_CHUNKS_TTL,
+            kept: DashMap::new(),
+        }
+    }
+
+    fn result_dir(&self, result_id: Uuid) -> PathBuf {
+        self.root.join(result_id.to_string())
+    }
+
+    fn chunk_path(&self, result_id: Uuid, index: usize) -> PathBuf {
+        self.result_dir(result_id).join(index.to_string())
+    }
+
+    /// Allocates a result id for spilling chunks, the chunks are removed by
+    /// [`Self::remove_expired`] once they expire
+    #[must_use]
+    pub fn new_result(&self) -> Uuid {
+        Uuid::new_v4()
+    }
+
+    /// Removes all the chunks, the urls of results spilled before a restart are no
+    /// longer handed out so their chunks would never be removed otherwise
+    #[tracing::instrument(name = "ResultChunkCache::clear", level = "debug", skip(self), err)]
+    pub async fn clear(&self) -> Result<()> {
+        match tokio::fs::remove_dir_all(&self.root).await {
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => {
+                Err(error).context(api_snowflake_rest_error::ResultChunkIoSnafu)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Removes the chunks of the results spilled longer than the ttl ago, returns the
+    /// number of results removed
+    #[tracing::instrument(
+        name = "ResultChunkCache::remove_expired",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    pub async fn remove_expired(&self) -> Result<usize> {
+        let mut entries = match tokio::fs::read_dir(&self.root).await {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+            Err(error) => return Err(error).context(api_snowflake_rest_error::ResultChunkIoSnafu),
+        };
+        let mut removed = 0;
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .context(api_snowflake_rest_error::ResultChunkIoSnafu)?
+        {
+            let expired = entry
+                .metadata()
+                .await
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .is_some_and(|age| age >= self.ttl);
+            if !expired {
+                continue;
+            }
+            match tokio::fs::remove_dir_all(entry.path()).await {
+                Ok(()) => removed += 1,
+                Err(error) if error.kind() == std::io::ErrorKind::NotFound => {}
+                Err(error) => tracing::warn!(
+                    path = %entry.path().display(),
+                    "Failed to remove expired result chunks: {error}"
+                ),
+            }
+        }
+        Ok(removed)
+    }
+
+    #[tracing::instrument(
+        name = "ResultChunkCache::put_chunk",
+        level = "debug",
+        skip(self, body),
+        err
+    )]
+    pub async fn put_chunk(&self, result_id: Uuid, index: usize, body: Vec<u8>) -> Result<()> {
+        tokio::fs::create_dir_all(self.result_dir(result_id))
+            .await
+            .context(api_snowflake_rest_error::ResultChunkIoSnafu)?;
+        tokio::fs::write(self.chunk_path(result_id, index), body)
+            .await
+            .context(api_snowflake_rest_error::ResultChunkIoSnafu)
+    }
+
+    #[tracing::instrument(
+        name = "ResultChunkCache::open_chunk",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    pub async fn open_chunk(&self, result_id: Uuid, index: usize) -> Result<tokio::fs::File> {
+        match tokio::fs::File::open(self.chunk_path(result_id, index)).await {
+            Ok(file) => Ok(file),
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                api_snowflake_rest_error::ResultChunkNotFoundSnafu { result_id, index }.fail()
+            }
+            Err(error) => Err(error).context(api_snowflake_rest_error::ResultChunkIoSnafu),
+        }
+    }
+
+    /// Keeps the response to the result request of a query, so the executor can release
+    /// the result and the response is served again from here. Expired responses are
+    /// forgotten.
+    #[tracing::instrument(
+        name = "ResultChunkCache::keep_response",
+        level = "debug",
+        skip(self, response),
+        err
+    )]
+    pub async fn keep_response(
+        &self,
+        query_id: QueryId,
+        mut response: JsonResponse,
+    ) -> Result<JsonResponse> {
+        let now = Instant::now();
+        self.kept.retain(|_, kept| kept.expires_at > now);
+        let rows = take_rows(&mut response);
+        let kept_rows = if let Some((rows, arrow)) = &rows {
+            let rows_id = self.new_result();
+            self.put_chunk(rows_id, 0, rows.as_bytes().to_vec()).await?;
+            Some((rows_id, *arrow))
+        } else {
+            None
+        };
+        self.kept.insert(
+            query_id,
+            KeptResponse {
+                response: serde_json::to_vec(&response)
+                    .context(api_snowflake_rest_error::KeptResponseSnafu)?,
+                rows: kept_rows,
+                expires_at: now + KEPT_RESPONSE_TTL,
+            },
+        );
+        if let Some((rows, arrow)) = rows {
+            put_rows(&mut response, rows, arrow);
+        }
+        Ok(response)
+    }
+
+    /// Response kept for the query, with its inline rows read back
+    #[tracing::instrument(
+        name = "ResultChunkCache::kept_response",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    pub async fn kept_response(&self, query_id: QueryId) -> Result<Option<JsonResponse>> {
+        let Some((response, rows)) = self
+            .kept
+            .get(&query_id)
+            .filter(|kept| kept.expires_at > Instant::now())
+            .map(|kept| (kept.response.clone(), kept.rows))
+        else {
+            return Ok(None);
+        };
+        let mut response: JsonResponse = serde_json::from_slice(&response)
+            .context(api_snowflake_rest_error::KeptResponseSnafu)?;
+        if let Some((rows_id, arrow)) = rows {
+            let rows = tokio::fs::read_to_string(self.chunk_path(rows_id, 0))
+                .await
+                .context(api_snowflake_rest_error::ResultChunkIoSnafu)?;
+            put_rows(&mut response, rows, arrow);
+        }
+        Ok(Some(response))
+    }
+}
+
+/// Takes the inline rows out of the response, along with whether they are the base64
+/// encoded Arrow rows
+fn take_rows(response: &mut JsonResponse) -> Option<(String, bool)> {
+    let data = response.data.as_mut()?;
+    if let Some(rows) = data.row_set_base_64.take() {
+        return Some((rows, true));
+    }
+    match data.row_set.take() {
+        Some(RowSet::Raw(rows)) => Some((rows, false)),
+        row_set => {
+            data.row_set = row_set;
+            None
+        }
+    }
+}
+
+fn put_rows(response: &mut JsonResponse, rows: String, arrow: bool) {
+    if let Some(data) = response.data.as_mut() {
+        if arrow {
+            data.row_set_base_64 = Some(rows);
+        } else {
+            data.row_set = Some(RowSet::Raw(rows));
+        }
+    }
+}
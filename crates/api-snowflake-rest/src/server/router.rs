@@ -1,9 +1,14 @@
-use super::handlers::{abort, login, query, session};
+use super::handlers::{
+    abort, download_stage_file, login, query, query_monitoring, query_result, result_chunk,
+    session, upload_files, upload_stage_file,
+};
 use super::layer::require_auth;
+use super::stage_transfers::STAGE_FILE_MAX_SIZE;
 use super::state::AppState;
 use api_snowflake_rest_sessions::layer::Host;
+use axum::extract::DefaultBodyLimit;
 use axum::middleware;
-use axum::routing::post;
+use axum::routing::{get, post, put};
 use axum::{Extension, Router};
 use tower::ServiceBuilder;
 use tower_http::compression::CompressionLayer;
@@ -13,12 +18,32 @@ pub fn create_auth_router() -> Router<AppState> {
     Router::new()
         .route("/session/v1/login-request", post(login))
         .route("/session", post(session))
+        // Drivers download result chunks without the auth token, same as from presigned urls
+        .route("/results/{result_id}/chunks/{index}", get(result_chunk))
+}
+
+// Files are transferred as is: drivers upload gzip compressed files with a gzip
+// content encoding, which must not be decoded on the way to the stage
+pub fn create_stage_transfer_router() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/stages/transfers/{transfer_id}",
+            put(upload_stage_file).get(download_stage_file),
+        )
+        .layer(DefaultBodyLimit::max(STAGE_FILE_MAX_SIZE))
 }
 
 pub fn create_router() -> Router<AppState> {
     Router::new()
         .route("/queries/v1/query-request", post(query))
         .route("/queries/v1/abort-request", post(abort))
+        .route("/queries/{query_id}/result", get(query_result))
+        .route("/monitoring/queries/{query_id}", get(query_monitoring))
+        // Uploaded files are streamed to disk, there is no limit on their size
+        .route(
+            "/uploads/v1/tables/{database}/{schema}/{table}",
+            post(upload_files).layer(DefaultBodyLimit::disable()),
+        )
 }
 
 pub fn make_snowflake_router(app_state: AppState) -> Router {
@@ -35,9 +60,12 @@ pub fn make_snowflake_router(app_state: AppState) -> Router {
             require_auth,
         ));
     let snowflake_auth_router = create_auth_router()
-        .with_state(app_state)
+        .with_state(app_state.clone())
         .layer(compression_layer)
         .layer(Extension(Host(String::default())));
+    let stage_transfer_router = create_stage_transfer_router().with_state(app_state);
 
-    snowflake_router.merge(snowflake_auth_router)
+    snowflake_router
+        .merge(snowflake_auth_router)
+        .merge(stage_transfer_router)
 }
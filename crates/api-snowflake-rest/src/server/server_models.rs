@@ -1,10 +1,25 @@
 use crate::models::Auth;
 use executor::utils::DataSerializationFormat;
 
-#[derive(Clone, Default)]
+/// Default number of rows per result chunk, the first chunk is returned inline
+/// and the rest are served from the result chunks route
+pub const DEFAULT_RESULT_CHUNK_SIZE: usize = 100_000;
+
+#[derive(Clone)]
 pub struct RestApiConfig {
     pub auth: Auth,
     pub dbt_serialization_format: DataSerializationFormat,
+    pub result_chunk_size: usize,
+}
+
+impl Default for RestApiConfig {
+    fn default() -> Self {
+        Self {
+            auth: Auth::default(),
+            dbt_serialization_format: DataSerializationFormat::default(),
+            result_chunk_size: DEFAULT_RESULT_CHUNK_SIZE,
+        }
+    }
 }
 
 impl RestApiConfig {
@@ -12,9 +27,17 @@ impl RestApiConfig {
         Ok(Self {
             dbt_serialization_format: DataSerializationFormat::try_from(data_format)?,
             auth: Auth::new(jwt_secret),
+            result_chunk_size: DEFAULT_RESULT_CHUNK_SIZE,
         })
     }
     #[must_use]
+    pub fn with_result_chunk_size(self, result_chunk_size: usize) -> Self {
+        Self {
+            result_chunk_size: result_chunk_size.max(1),
+            ..self
+        }
+    }
+    #[must_use]
     pub fn with_demo_credentials(mut self, demo_user: String, demo_password: String) -> Self {
         self.auth = Auth {
             demo_user,
@@ -0,0 +1,93 @@
+use crate::server::error::{self as api_snowflake_rest_error, Result};
+use catalog_metastore::StageIdent;
+use dashmap::DashMap;
+use std::time::Instant;
+use tokio::time::Duration;
+use uuid::Uuid;
+
+/// Transfer urls are handed to the driver right before it transfers the file, so
+/// they only need to outlive a retry or two
+pub const STAGE_TRANSFER_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Largest file accepted by a transfer url
+pub const STAGE_FILE_MAX_SIZE: usize = 5 * 1024 * 1024 * 1024;
+
+/// Stage file a client may upload or download through a transfer url
+#[derive(Debug, Clone)]
+pub struct StageTransfer {
+    pub stage: StageIdent,
+    /// Path of the file inside of the stage
+    pub path: String,
+    pub overwrite: bool,
+}
+
+#[derive(Debug)]
+struct RegisteredTransfer {
+    session_id: String,
+    expires_at: Instant,
+    transfer: StageTransfer,
+}
+
+/// File transfers of `PUT` and `GET` commands in flight.
+///
+/// Drivers transfer internal stage files the same way they do with GCS presigned
+/// urls: without an auth token, so the random transfer id is what authorizes the
+/// request. A transfer id can be used once, only until it expires and only while the
+/// session of the command that issued it exists.
+#[derive(Debug)]
+pub struct StageTransfers {
+    transfers: DashMap<Uuid, RegisteredTransfer>,
+    ttl: Duration,
+}
+
+impl Default for StageTransfers {
+    fn default() -> Self {
+        Self::new(STAGE_TRANSFER_TTL)
+    }
+}
+
+impl StageTransfers {
+    #[must_use]
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            transfers: DashMap::new(),
+            ttl,
+        }
+    }
+
+    /// Registers a transfer of the session, expired transfers are forgotten
+    #[must_use]
+    pub fn register(&self, session_id: &str, transfer: StageTransfer) -> Uuid {
+        let now = Instant::now();
+        self.transfers
+            .retain(|_, registered| registered.expires_at > now);
+        let transfer_id = Uuid::new_v4();
+        self.transfers.insert(
+            transfer_id,
+            RegisteredTransfer {
+                session_id: session_id.to_string(),
+                expires_at: now + self.ttl,
+                transfer,
+            },
+        );
+        transfer_id
+    }
+
+    /// Takes the transfer out, returns it with the id of the session that issued it
+    pub fn take(&self, transfer_id: Uuid) -> Result<(String, StageTransfer)> {
+        self.transfers
+            .remove(&transfer_id)
+            .map(|(_, registered)| registered)
+            .filter(|registered| registered.expires_at > Instant::now())
+            .map(|registered| (registered.session_id, registered.transfer))
+            .ok_or_else(|| {
+                api_snowflake_rest_error::StageTransferNotFoundSnafu { transfer_id }.build()
+            })
+    }
+
+    /// Forgets the transfers of a session that is closed
+    pub fn remove_session(&self, session_id: &str) {
+        self.transfers
+            .retain(|_, registered| registered.session_id != session_id);
+    }
+}
@@ -1,6 +1,9 @@
 use super::server_models::RestApiConfig;
 use crate::server::core_state::CoreState;
+use crate::server::result_cache::ResultChunkCache;
+use crate::server::stage_transfers::StageTransfers;
 use api_snowflake_rest_sessions::session::JwtSecret;
+use catalog_metastore::Metastore;
 use executor::ExecutionAppState;
 use executor::service::ExecutionService;
 use std::sync::Arc;
@@ -8,7 +11,10 @@ use std::sync::Arc;
 #[derive(Clone)]
 pub struct AppState {
     pub execution_svc: Arc<dyn ExecutionService>,
+    pub metastore: Arc<dyn Metastore>,
     pub config: RestApiConfig,
+    pub result_cache: Arc<ResultChunkCache>,
+    pub stage_transfers: Arc<StageTransfers>,
 }
 
 impl ExecutionAppState for AppState {
@@ -27,7 +33,10 @@ impl From<&CoreState> for AppState {
     fn from(core_state: &CoreState) -> Self {
         Self {
             execution_svc: core_state.executor.clone(),
+            metastore: core_state.metastore.clone(),
             config: core_state.rest_api_config.clone(),
+            result_cache: core_state.result_cache.clone(),
+            stage_transfers: core_state.stage_transfers.clone(),
         }
     }
 }
@@ -0,0 +1,88 @@
+use crate::server::error::{self as api_snowflake_rest_error, Result};
+use axum::extract::Multipart;
+use executor::file_formats::UploadedFile;
+use snafu::ResultExt;
+use std::path::PathBuf;
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+
+/// Local directory the files of a table upload are spooled to while they are loaded.
+///
+/// Files are written chunk by chunk as they arrive and read back as a stream by the
+/// executor, so an upload is never held in memory as a whole.
+#[derive(Debug)]
+pub struct UploadSpool {
+    dir: PathBuf,
+}
+
+impl Default for UploadSpool {
+    fn default() -> Self {
+        Self::new(std::env::temp_dir().join("embucket-uploads"))
+    }
+}
+
+impl UploadSpool {
+    #[must_use]
+    pub fn new(root: PathBuf) -> Self {
+        Self {
+            dir: root.join(Uuid::new_v4().to_string()),
+        }
+    }
+
+    /// Writes the file fields of a multipart form to disk, other fields are ignored
+    #[tracing::instrument(
+        name = "UploadSpool::spool",
+        level = "debug",
+        skip(self, multipart),
+        err
+    )]
+    pub async fn spool(&self, mut multipart: Multipart) -> Result<Vec<UploadedFile>> {
+        tokio::fs::create_dir_all(&self.dir)
+            .await
+            .context(api_snowflake_rest_error::UploadIoSnafu)?;
+        let mut files = Vec::new();
+        while let Some(mut field) = multipart
+            .next_field()
+            .await
+            .context(api_snowflake_rest_error::UploadMultipartSnafu)?
+        {
+            let Some(file_name) = field.file_name().map(str::to_string) else {
+                continue;
+            };
+            // Uploaded names are not trusted as paths
+            let path = self.dir.join(files.len().to_string());
+            let mut file = tokio::fs::File::create(&path)
+                .await
+                .context(api_snowflake_rest_error::UploadIoSnafu)?;
+            while let Some(chunk) = field
+                .chunk()
+                .await
+                .context(api_snowflake_rest_error::UploadMultipartSnafu)?
+            {
+                file.write_all(&chunk)
+                    .await
+                    .context(api_snowflake_rest_error::UploadIoSnafu)?;
+            }
+            file.flush()
+                .await
+                .context(api_snowflake_rest_error::UploadIoSnafu)?;
+            files.push(UploadedFile { file_name, path });
+        }
+        if files.is_empty() {
+            return api_snowflake_rest_error::MissingUploadFilesSnafu.fail();
+        }
+        Ok(files)
+    }
+
+    /// Removes the spooled files once they are loaded
+    pub async fn remove(self) {
+        if let Err(error) = tokio::fs::remove_dir_all(&self.dir).await
+            && error.kind() != std::io::ErrorKind::NotFound
+        {
+            tracing::warn!(
+                path = %self.dir.display(),
+                "Failed to remove uploaded files: {error}"
+            );
+        }
+    }
+}
@@ -21,6 +21,7 @@ pub enum SqlState {
     DoesNotExist,
     GenericQueryErrorFromHistory,
     FeatureNotSupported,
+    InsufficientPrivilege,
 }
 
 impl Display for SqlState {
@@ -35,6 +36,7 @@ impl Display for SqlState {
             // As currently we don't save SqlState when save result to history
             Self::GenericQueryErrorFromHistory => "42S03",
             Self::FeatureNotSupported => "0A000",
+            Self::InsufficientPrivilege => "42501",
         };
         write!(f, "{sql_state:05}")
     }
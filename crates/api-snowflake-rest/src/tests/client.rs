@@ -140,6 +140,8 @@ fn login_data(login: &str, passw: &str) -> LoginRequestBody {
             account_name: String::new(),
             login_name: login.to_string(),
             password: passw.to_string(),
+            authenticator: None,
+            token: None,
             client_environment: HashMap::default(),
             session_parameters: HashMap::default(),
         },
@@ -1,10 +1,13 @@
 use super::TEST_JWT_SECRET;
+use super::sql_test_macro::{DEMO_PASSWORD, DEMO_USER};
 use crate::server::core_state::CoreState;
+use crate::server::core_state::MetastoreBackend;
 use crate::server::core_state::MetastoreConfig;
 use crate::server::make_snowflake_router;
 use crate::server::server_models::RestApiConfig;
 use crate::server::state::AppState;
 use catalog_metastore::metastore_settings_config::MetastoreSettingsConfig;
+use catalog_metastore::{ACCOUNTADMIN_ROLE, User};
 use executor::utils::Config as UtilsConfig;
 use std::net::SocketAddr;
 use std::net::TcpListener;
@@ -22,7 +25,6 @@ static INIT: std::sync::Once = std::sync::Once::new();
 pub fn rest_default_cfg(data_format: &str) -> RestApiConfig {
     RestApiConfig::new(data_format, TEST_JWT_SECRET.to_string())
         .expect("Failed to create server config")
-        .with_demo_credentials("embucket".to_string(), "embucket".to_string())
 }
 
 #[allow(clippy::expect_used)]
@@ -38,6 +40,8 @@ pub fn metastore_default_settings_cfg() -> MetastoreSettingsConfig {
         .with_object_store_timeout(1)
 }
 
+/// Runs a test server. Servers configured without demo credentials start with an
+/// `ACCOUNTADMIN` user that logs in with the `DEMO_USER` and `DEMO_PASSWORD` credentials.
 #[allow(clippy::expect_used)]
 pub fn run_test_rest_api_server(
     rest_cfg: Option<RestApiConfig>,
@@ -189,14 +193,29 @@ pub async fn run_test_rest_api_server_with_config(
     setup_tracing();
     tracing::info!("Starting server at {addr}");
 
+    let with_admin_user = !snowflake_rest_cfg.auth.has_demo_credentials();
     let core_state = CoreState::new(
         execution_cfg,
         snowflake_rest_cfg,
         metastore_settings_cfg,
         metastore_cfg,
+        MetastoreBackend::Memory,
     )
     .await
     .expect("Core state creation error");
+    if with_admin_user {
+        let admin = User {
+            roles: vec![ACCOUNTADMIN_ROLE.to_string()],
+            default_role: Some(ACCOUNTADMIN_ROLE.to_string()),
+            ..User::new(DEMO_USER)
+        }
+        .with_password(DEMO_PASSWORD);
+        core_state
+            .metastore
+            .create_user(&admin.name.clone(), admin)
+            .await
+            .expect("Failed to create admin user");
+    }
 
     let app = make_snowflake_router(AppState::from(&core_state))
         .into_make_service_with_connect_info::<SocketAddr>();
@@ -7,6 +7,10 @@ pub mod test_rest_api;
 pub mod test_stress;
 
 pub mod test_gzip_encoding;
+pub mod test_result_chunks;
+pub mod test_stage_transfers;
+pub mod test_uploads;
+pub mod test_users;
 pub use create_test_server::run_test_rest_api_server;
 pub use create_test_server::{executor_default_cfg, rest_default_cfg};
 
@@ -32,6 +32,7 @@ pub fn insta_replace_filters() -> Vec<(&'static str, &'static str)> {
             r"\d{4}-\d{2}-\d{2} \d{2}:\d{2}:\d{2}\.\d{9} UTC",
             "UTC_TIME9",
         ),
+        (r"https?://[^/\s]+/results/", "http://HOST/results/"),
     ]
 }
 
@@ -59,6 +59,8 @@ mod tests {
                 account_name: String::new(),
                 login_name: "embucket".to_string(),
                 password: "embucket".to_string(),
+                authenticator: None,
+                token: None,
                 client_environment: HashMap::default(),
                 session_parameters: HashMap::default(),
             },
@@ -50,6 +50,14 @@ mod compatible {
         ])
     );
 
+    sql_test!(
+        async_exec_select_1,
+        SqlTest::new(&[
+            // Snowflake returns query id right away, the result is polled by the query id
+            "select 1;>",
+        ])
+    );
+
     sql_test!(
         regression_bug_1662_ambiguous_schema,
         SqlTest::new(&[
@@ -191,7 +199,7 @@ mod known_issues {
 mod custom_server {
     use super::*;
     use crate::server::server_models::RestApiConfig;
-    use crate::tests::sql_test_macro::ARROW;
+    use crate::tests::sql_test_macro::{ARROW, JSON};
 
     sql_test!(
         select_date_timestamp_in_arrow_format,
@@ -200,8 +208,7 @@ mod custom_server {
         ])
         .with_server_config(
             RestApiConfig::new(ARROW, TEST_JWT_SECRET.to_string())
-                .expect("Failed to create server config")
-                .with_demo_credentials("embucket".to_string(), "embucket".to_string()),
+                .expect("Failed to create server config"),
         )
     );
 
@@ -210,9 +217,21 @@ mod custom_server {
         SqlTest::new(&["SELECT SLEEP(2)",])
             .with_server_config(
                 RestApiConfig::new(ARROW, TEST_JWT_SECRET.to_string())
-                    .expect("Failed to create server config")
-                    .with_demo_credentials("embucket".to_string(), "embucket".to_string()),
+                    .expect("Failed to create server config"),
             )
             .with_executor_config(executor::utils::Config::default().with_query_timeout(1))
     );
+
+    sql_test!(
+        select_result_chunks,
+        SqlTest::new(&[
+            // first chunk is returned inline, the rest is listed in chunks
+            "SELECT 1 AS a UNION ALL SELECT 2 UNION ALL SELECT 3 ORDER BY a",
+        ])
+        .with_server_config(
+            RestApiConfig::new(JSON, TEST_JWT_SECRET.to_string())
+                .expect("Failed to create server config")
+                .with_result_chunk_size(2),
+        )
+    );
 }
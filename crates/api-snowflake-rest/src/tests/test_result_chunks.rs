@@ -0,0 +1,83 @@
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use crate::models::ResponseData;
+    use crate::server::core_state::MetastoreConfig;
+    use crate::server::result_cache::ResultChunkCache;
+    use crate::tests::create_test_server::run_test_rest_api_server;
+    use crate::tests::rest_default_cfg;
+    use crate::tests::snow_sql::{PASSWORD_KEY, REQUEST_ID_KEY, USER_KEY, snow_sql};
+    use crate::tests::sql_test_macro::{DEMO_PASSWORD, DEMO_USER, JSON};
+    use axum::http;
+    use std::collections::HashMap;
+
+    #[tokio::test]
+    async fn test_download_result_chunks() {
+        let addr = run_test_rest_api_server(
+            Some(rest_default_cfg(JSON).with_result_chunk_size(2)),
+            None,
+            None,
+            MetastoreConfig::None,
+        );
+        let mut params = HashMap::from([
+            (USER_KEY, DEMO_USER.to_string()),
+            (PASSWORD_KEY, DEMO_PASSWORD.to_string()),
+            (REQUEST_ID_KEY, uuid::Uuid::new_v4().to_string()),
+        ]);
+        let (res, _) = snow_sql(
+            &addr,
+            "SELECT * FROM (VALUES (1), (2), (3), (4), (5)) AS t(a) ORDER BY a",
+            &mut params,
+        )
+        .await;
+        let ResponseData {
+            total,
+            returned,
+            chunks,
+            ..
+        } = res.data.expect("No data in response");
+        assert_eq!((total, returned), (Some(5), Some(2)));
+
+        // chunks are downloaded without the auth token
+        let client = reqwest::Client::new();
+        let mut bodies = Vec::new();
+        for chunk in chunks.expect("No chunks in response") {
+            let res = client.get(&chunk.url).send().await.unwrap();
+            assert_eq!(res.status(), http::StatusCode::OK);
+            bodies.push(res.text().await.unwrap());
+        }
+        assert_eq!(bodies, vec!["[3],[4]".to_string(), "[5]".to_string()]);
+
+        let res = client
+            .get(format!(
+                "http://{addr}/results/{}/chunks/0",
+                uuid::Uuid::new_v4()
+            ))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), http::StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_result_chunks_cleared() {
+        let root = std::env::temp_dir().join(format!("result-chunks-{}", uuid::Uuid::new_v4()));
+        let cache = ResultChunkCache::new(root.clone());
+        let result_id = cache.new_result();
+        cache
+            .put_chunk(result_id, 0, b"[1]".to_vec())
+            .await
+            .unwrap();
+        assert!(cache.open_chunk(result_id, 0).await.is_ok());
+
+        // chunks written moments ago haven't expired yet
+        assert_eq!(cache.remove_expired().await.unwrap(), 0);
+        assert!(cache.open_chunk(result_id, 0).await.is_ok());
+
+        cache.clear().await.unwrap();
+        assert!(cache.open_chunk(result_id, 0).await.is_err());
+        assert!(!root.exists());
+        // clearing a missing directory is fine
+        cache.clear().await.unwrap();
+    }
+}
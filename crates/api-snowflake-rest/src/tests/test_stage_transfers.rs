@@ -0,0 +1,161 @@
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use crate::models::{JsonResponse, ResponseData, RowSet};
+    use crate::server::core_state::MetastoreConfig;
+    use crate::tests::create_test_server::run_test_rest_api_server;
+    use crate::tests::rest_default_cfg;
+    use crate::tests::snow_sql::{PASSWORD_KEY, REQUEST_ID_KEY, USER_KEY, snow_sql};
+    use crate::tests::sql_test_macro::{DEMO_PASSWORD, DEMO_USER, JSON};
+    use axum::http;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+    use std::collections::HashMap;
+    use std::io::Write;
+    use std::net::SocketAddr;
+
+    async fn run(addr: &SocketAddr, sql: &str, params: &mut HashMap<&str, String>) -> JsonResponse {
+        params.insert(REQUEST_ID_KEY, uuid::Uuid::new_v4().to_string());
+        let (res, _) = snow_sql(addr, sql, params).await;
+        assert!(res.success, "{sql}: {:?}", res.message);
+        res
+    }
+
+    #[tokio::test]
+    async fn test_put_and_get_internal_stage_files() {
+        let addr = run_test_rest_api_server(
+            Some(rest_default_cfg(JSON)),
+            None,
+            None,
+            MetastoreConfig::DefaultConfig,
+        );
+        let mut params = HashMap::from([
+            (USER_KEY, DEMO_USER.to_string()),
+            (PASSWORD_KEY, DEMO_PASSWORD.to_string()),
+        ]);
+        run(&addr, "CREATE STAGE embucket.public.s", &mut params).await;
+
+        let res = run(
+            &addr,
+            "PUT 'file:///tmp/data.csv' @embucket.public.s/dir AUTO_COMPRESS = FALSE",
+            &mut params,
+        )
+        .await;
+        let ResponseData {
+            command,
+            stage_info,
+            auto_compress,
+            ..
+        } = res.data.expect("No data in response");
+        assert_eq!(command.as_deref(), Some("UPLOAD"));
+        assert_eq!(auto_compress, Some(false));
+        let stage_info = stage_info.expect("No stage info in response");
+        assert_eq!(stage_info.location_type, "GCS");
+        assert_eq!(stage_info.path, "dir");
+
+        // files are transferred without the auth token, same as with presigned urls
+        let client = reqwest::Client::new();
+        let res = client
+            .put(stage_info.presigned_url.expect("No presigned url"))
+            .body("id,name\n1,a\n")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), http::StatusCode::OK);
+
+        let res = run(&addr, "LIST @embucket.public.s", &mut params).await;
+        let Some(RowSet::Parsed(rows)) = res.data.expect("No data in response").row_set else {
+            panic!("No rows in response");
+        };
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0][0], "s/dir/data.csv");
+
+        let res = run(
+            &addr,
+            "GET @embucket.public.s/dir file:///tmp/out PATTERN = '.*[.]csv'",
+            &mut params,
+        )
+        .await;
+        let ResponseData {
+            command,
+            src_locations,
+            local_location,
+            presigned_urls,
+            ..
+        } = res.data.expect("No data in response");
+        assert_eq!(command.as_deref(), Some("DOWNLOAD"));
+        assert_eq!(src_locations, Some(vec!["dir/data.csv".to_string()]));
+        assert_eq!(local_location.as_deref(), Some("/tmp/out"));
+        let presigned_urls = presigned_urls.expect("No presigned urls");
+        assert_eq!(presigned_urls.len(), 1);
+        let res = client.get(&presigned_urls[0]).send().await.unwrap();
+        assert_eq!(res.status(), http::StatusCode::OK);
+        assert_eq!(res.text().await.unwrap(), "id,name\n1,a\n");
+
+        // transfer urls can only be used once
+        let res = client.get(&presigned_urls[0]).send().await.unwrap();
+        assert_eq!(res.status(), http::StatusCode::NOT_FOUND);
+
+        let res = client
+            .get(format!(
+                "http://{addr}/stages/transfers/{}",
+                uuid::Uuid::new_v4()
+            ))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), http::StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_put_auto_compress_by_default() {
+        let addr = run_test_rest_api_server(
+            Some(rest_default_cfg(JSON)),
+            None,
+            None,
+            MetastoreConfig::DefaultConfig,
+        );
+        let mut params = HashMap::from([
+            (USER_KEY, DEMO_USER.to_string()),
+            (PASSWORD_KEY, DEMO_PASSWORD.to_string()),
+        ]);
+        run(&addr, "CREATE STAGE embucket.public.s", &mut params).await;
+
+        let client = reqwest::Client::new();
+        for file in ["data.csv", "other.csv.gz"] {
+            let res = run(
+                &addr,
+                &format!("PUT 'file:///tmp/{file}' @embucket.public.s"),
+                &mut params,
+            )
+            .await;
+            let data = res.data.expect("No data in response");
+            assert_eq!(data.auto_compress, Some(true));
+            let presigned_url = data
+                .stage_info
+                .and_then(|stage_info| stage_info.presigned_url)
+                .expect("No presigned url");
+            // drivers gzip the file before uploading it
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(b"id,name\n1,a\n").unwrap();
+            let res = client
+                .put(presigned_url)
+                .body(encoder.finish().unwrap())
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(res.status(), http::StatusCode::OK);
+        }
+
+        let res = run(&addr, "LIST @embucket.public.s", &mut params).await;
+        let Some(RowSet::Parsed(rows)) = res.data.expect("No data in response").row_set else {
+            panic!("No rows in response");
+        };
+        let mut names: Vec<_> = rows
+            .iter()
+            .filter_map(|row| row[0].as_str().map(str::to_string))
+            .collect();
+        names.sort();
+        assert_eq!(names, ["s/data.csv.gz", "s/other.csv.gz"]);
+    }
+}
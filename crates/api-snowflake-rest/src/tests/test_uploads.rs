@@ -0,0 +1,106 @@
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use crate::models::{JsonResponse, RowSet, UploadResponse};
+    use crate::server::core_state::MetastoreConfig;
+    use crate::tests::create_test_server::run_test_rest_api_server;
+    use crate::tests::rest_default_cfg;
+    use crate::tests::snow_sql::{
+        ACCESS_TOKEN_KEY, PASSWORD_KEY, REQUEST_ID_KEY, USER_KEY, snow_sql,
+    };
+    use crate::tests::sql_test_macro::{DEMO_PASSWORD, DEMO_USER, JSON};
+    use axum::http;
+    use reqwest::multipart::{Form, Part};
+    use std::collections::HashMap;
+    use std::net::SocketAddr;
+
+    async fn run(addr: &SocketAddr, sql: &str, params: &mut HashMap<&str, String>) -> JsonResponse {
+        params.insert(REQUEST_ID_KEY, uuid::Uuid::new_v4().to_string());
+        let (res, _) = snow_sql(addr, sql, params).await;
+        assert!(res.success, "{sql}: {:?}", res.message);
+        res
+    }
+
+    fn csv_files() -> Form {
+        Form::new()
+            .part("file", Part::text("id,name\n1,a\n2,b\n").file_name("a.csv"))
+            .part("file", Part::text("id,name\n3,c\n").file_name("b.csv"))
+    }
+
+    #[tokio::test]
+    async fn test_upload_files_to_table() {
+        let addr = run_test_rest_api_server(
+            Some(rest_default_cfg(JSON)),
+            None,
+            None,
+            MetastoreConfig::DefaultConfig,
+        );
+        let mut params = HashMap::from([
+            (USER_KEY, DEMO_USER.to_string()),
+            (PASSWORD_KEY, DEMO_PASSWORD.to_string()),
+        ]);
+        run(&addr, "SELECT 1", &mut params).await;
+        let access_token = params
+            .get(ACCESS_TOKEN_KEY)
+            .expect("No access token")
+            .clone();
+        let url = format!("http://{addr}/uploads/v1/tables/embucket/public/uploaded");
+        let client = reqwest::Client::new();
+
+        // Uploads require authentication
+        let res = client
+            .post(format!("{url}?header=true&createTable=true"))
+            .multipart(csv_files())
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), http::StatusCode::UNAUTHORIZED);
+
+        let res = client
+            .post(format!("{url}?header=true&createTable=true"))
+            .header(
+                http::header::AUTHORIZATION,
+                format!("Snowflake Token=\"{access_token}\""),
+            )
+            .multipart(csv_files())
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), http::StatusCode::OK);
+        let res: UploadResponse = res.json().await.unwrap();
+        assert!(res.success);
+        assert_eq!(res.data.rows_loaded, 3);
+        assert_eq!(
+            res.data
+                .files
+                .iter()
+                .map(|file| (file.file_name.as_str(), file.rows_loaded))
+                .collect::<Vec<_>>(),
+            vec![("a.csv", 2), ("b.csv", 1)]
+        );
+
+        let res = run(
+            &addr,
+            "SELECT name FROM embucket.public.uploaded ORDER BY id",
+            &mut params,
+        )
+        .await;
+        let Some(RowSet::Parsed(rows)) = res.data.expect("No data in response").row_set else {
+            panic!("No rows in response");
+        };
+        assert_eq!(rows, vec![vec!["a"], vec!["b"], vec!["c"]]);
+
+        // A form without files is rejected
+        let res = client
+            .post(format!("{url}?header=true"))
+            .header(
+                http::header::AUTHORIZATION,
+                format!("Snowflake Token=\"{access_token}\""),
+            )
+            .multipart(Form::new().text("comment", "no files"))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.status(), http::StatusCode::BAD_REQUEST);
+    }
+}
@@ -0,0 +1,383 @@
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use crate::models::{JsonResponse, LoginRequestBody, LoginRequestData, LoginResponse, RowSet};
+    use crate::server::core_state::MetastoreConfig;
+    use crate::server::logic::{JWT_TOKEN_EXPIRATION_SECONDS, KEY_PAIR_AUTHENTICATOR};
+    use crate::server::server_models::RestApiConfig;
+    use crate::tests::TEST_JWT_SECRET;
+    use crate::tests::client::{TestHttpError, http_req_with_headers, login, login_url, query};
+    use crate::tests::create_test_server::run_test_rest_api_server;
+    use crate::tests::rest_default_cfg;
+    use crate::tests::snow_sql::{
+        ACCESS_TOKEN_KEY, PASSWORD_KEY, REQUEST_ID_KEY, USER_KEY, snow_sql,
+    };
+    use crate::tests::sql_test_macro::{DEMO_PASSWORD, DEMO_USER, JSON};
+    use api_snowflake_rest_sessions::TokenizedSession;
+    use api_snowflake_rest_sessions::helpers::{
+        KeyPairClaims, create_jwt, jwt_claims, public_key_fingerprint,
+    };
+    use axum::http;
+    use catalog_metastore::User;
+    use jsonwebtoken::{Algorithm, EncodingKey, Header};
+    use reqwest::header::{self, HeaderMap, HeaderValue};
+    use std::collections::HashMap;
+    use std::net::SocketAddr;
+
+    const PRIVATE_KEY: &str = include_str!("keys/rsa_key.p8");
+    const PUBLIC_KEY: &str = include_str!("keys/rsa_key.pub");
+
+    fn credentials(user: &str, password: &str) -> HashMap<&'static str, String> {
+        HashMap::from([
+            (USER_KEY, user.to_string()),
+            (PASSWORD_KEY, password.to_string()),
+            (REQUEST_ID_KEY, uuid::Uuid::new_v4().to_string()),
+        ])
+    }
+
+    fn demo_cfg() -> RestApiConfig {
+        rest_default_cfg(JSON)
+            .with_demo_credentials(DEMO_USER.to_string(), DEMO_PASSWORD.to_string())
+    }
+
+    fn key_pair_jwt(account: &str, user: &str) -> String {
+        key_pair_jwt_issued(account, user, 0, 60)
+    }
+
+    // Key-pair JWT issued `issued_in` seconds from now and valid for `lifetime` seconds
+    fn key_pair_jwt_issued(account: &str, user: &str, issued_in: i64, lifetime: i64) -> String {
+        let fingerprint = public_key_fingerprint(&User::normalize_public_key(PUBLIC_KEY))
+            .expect("Invalid public key");
+        let iat = i64::try_from(
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+        )
+        .unwrap()
+            + issued_in;
+        let claims = KeyPairClaims {
+            iss: format!("{account}.{user}.{fingerprint}"),
+            sub: format!("{account}.{user}"),
+            iat,
+            exp: iat + lifetime,
+        };
+        jsonwebtoken::encode(
+            &Header::new(Algorithm::RS256),
+            &claims,
+            &EncodingKey::from_rsa_pem(PRIVATE_KEY.as_bytes()).expect("Invalid private key"),
+        )
+        .expect("Failed to sign JWT")
+    }
+
+    async fn login_with_key_pair(
+        addr: &SocketAddr,
+        login_name: &str,
+        token: String,
+    ) -> Result<(HeaderMap, LoginResponse), TestHttpError> {
+        let body = LoginRequestBody {
+            data: LoginRequestData {
+                client_app_id: String::new(),
+                client_app_version: String::new(),
+                svn_revision: None,
+                account_name: "ACCOUNT".to_string(),
+                login_name: login_name.to_string(),
+                password: String::new(),
+                authenticator: Some(KEY_PAIR_AUTHENTICATOR.to_string()),
+                token: Some(token),
+                client_environment: HashMap::default(),
+                session_parameters: HashMap::default(),
+            },
+        };
+        http_req_with_headers::<LoginResponse>(
+            &reqwest::Client::new(),
+            reqwest::Method::POST,
+            HeaderMap::from_iter(vec![(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("application/json"),
+            )]),
+            &login_url(addr, &uuid::Uuid::new_v4().to_string(), None, None),
+            serde_json::json!(body).to_string(),
+        )
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_login_as_store_user() {
+        let addr = run_test_rest_api_server(
+            Some(rest_default_cfg(JSON)),
+            None,
+            None,
+            MetastoreConfig::None,
+        );
+        let (res, _) = snow_sql(
+            &addr,
+            "CREATE USER alice PASSWORD = 'secret'",
+            &mut credentials(DEMO_USER, DEMO_PASSWORD),
+        )
+        .await;
+        assert!(res.success, "{res:?}");
+
+        // user names are case-insensitive
+        let mut alice = credentials("ALICE", "secret");
+        let (res, _) = snow_sql(&addr, "SELECT CURRENT_USER()", &mut alice).await;
+        let row_set = res.data.expect("No data in response").row_set;
+        assert!(
+            matches!(row_set, Some(RowSet::Parsed(rows)) if rows == vec![vec![serde_json::json!("alice")]])
+        );
+
+        let client = reqwest::Client::new();
+        let err = login::<LoginResponse>(&client, &addr, credentials("alice", "wrong"))
+            .await
+            .expect_err("Login with a wrong password should fail");
+        assert_eq!(err.status, http::StatusCode::UNAUTHORIZED);
+
+        let (res, _) = snow_sql(
+            &addr,
+            "ALTER USER alice SET DISABLED = TRUE",
+            &mut credentials(DEMO_USER, DEMO_PASSWORD),
+        )
+        .await;
+        assert!(res.success, "{res:?}");
+        let err = login::<LoginResponse>(&client, &addr, credentials("alice", "secret"))
+            .await
+            .expect_err("Login as a disabled user should fail");
+        assert_eq!(err.status, http::StatusCode::UNAUTHORIZED);
+
+        // tokens issued before the user was disabled are rejected too
+        let err = query::<JsonResponse>(
+            &client,
+            &addr,
+            &alice[ACCESS_TOKEN_KEY],
+            uuid::Uuid::new_v4(),
+            0,
+            "SELECT 1",
+            false,
+        )
+        .await
+        .expect_err("Query with the token of a disabled user should fail");
+        assert_eq!(err.status, http::StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_demo_login_only_without_users() {
+        let addr = run_test_rest_api_server(Some(demo_cfg()), None, None, MetastoreConfig::None);
+        let mut demo_credentials = credentials(DEMO_USER, DEMO_PASSWORD);
+        let (res, _) = snow_sql(&addr, "SELECT 1", &mut demo_credentials).await;
+        assert!(res.success, "{res:?}");
+
+        // the demo user isn't stored, so it only gets the privileges of PUBLIC
+        for sql in ["CREATE ROLE analyst", "USE ROLE accountadmin"] {
+            let (res, _) = snow_sql(&addr, sql, &mut demo_credentials).await;
+            assert!(!res.success, "{sql}: {res:?}");
+        }
+
+        // once users exist, neither the demo credentials nor demo tokens are accepted
+        let addr = run_test_rest_api_server(
+            Some(demo_cfg()),
+            None,
+            None,
+            MetastoreConfig::ConfigJson(
+                r#"{"users": [{"name": "alice", "password": "secret"}]}"#.to_string(),
+            ),
+        );
+        let client = reqwest::Client::new();
+        let err = login::<LoginResponse>(&client, &addr, credentials(DEMO_USER, DEMO_PASSWORD))
+            .await
+            .expect_err("Demo login should fail once users exist");
+        assert_eq!(err.status, http::StatusCode::UNAUTHORIZED);
+
+        let demo_token = create_jwt(
+            &jwt_claims(
+                DEMO_USER,
+                &addr.to_string(),
+                time::Duration::seconds(JWT_TOKEN_EXPIRATION_SECONDS.into()),
+                TokenizedSession::default(),
+            ),
+            TEST_JWT_SECRET,
+        )
+        .expect("Failed to create JWT token");
+        let err = query::<JsonResponse>(
+            &client,
+            &addr,
+            &demo_token,
+            uuid::Uuid::new_v4(),
+            0,
+            "SELECT 1",
+            false,
+        )
+        .await
+        .expect_err("Query with a demo token should fail once users exist");
+        assert_eq!(err.status, http::StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_user_token_rejected() {
+        let addr = run_test_rest_api_server(
+            Some(rest_default_cfg(JSON)),
+            None,
+            None,
+            MetastoreConfig::None,
+        );
+        let mut admin = credentials(DEMO_USER, DEMO_PASSWORD);
+        let (res, _) = snow_sql(&addr, "CREATE USER alice PASSWORD = 'secret'", &mut admin).await;
+        assert!(res.success, "{res:?}");
+
+        let mut alice = credentials("alice", "secret");
+        let (res, _) = snow_sql(&addr, "SELECT 1", &mut alice).await;
+        assert!(res.success, "{res:?}");
+
+        let (res, _) = snow_sql(&addr, "DROP USER alice", &mut admin).await;
+        assert!(res.success, "{res:?}");
+        let err = query::<JsonResponse>(
+            &reqwest::Client::new(),
+            &addr,
+            &alice[ACCESS_TOKEN_KEY],
+            uuid::Uuid::new_v4(),
+            0,
+            "SELECT 1",
+            false,
+        )
+        .await
+        .expect_err("Query with the token of a dropped user should fail");
+        assert_eq!(err.status, http::StatusCode::UNAUTHORIZED);
+
+        // tokens of the other users stay valid
+        let (res, _) = snow_sql(&addr, "SELECT 1", &mut admin).await;
+        assert!(res.success, "{res:?}");
+    }
+
+    #[tokio::test]
+    async fn test_login_with_key_pair() {
+        let addr = run_test_rest_api_server(
+            Some(rest_default_cfg(JSON)),
+            None,
+            None,
+            MetastoreConfig::None,
+        );
+        let (res, _) = snow_sql(
+            &addr,
+            &format!("CREATE USER ci_user RSA_PUBLIC_KEY = '{PUBLIC_KEY}'"),
+            &mut credentials(DEMO_USER, DEMO_PASSWORD),
+        )
+        .await;
+        assert!(res.success, "{res:?}");
+
+        let (_, login_res) =
+            login_with_key_pair(&addr, "ci_user", key_pair_jwt("ACCOUNT", "CI_USER"))
+                .await
+                .expect("Key-pair login failed");
+        let token = login_res.data.expect("No token in response").token;
+        let mut params = credentials("ci_user", "");
+        params.insert(ACCESS_TOKEN_KEY, token);
+        let (res, _) = snow_sql(&addr, "SELECT CURRENT_USER()", &mut params).await;
+        let row_set = res.data.expect("No data in response").row_set;
+        assert!(
+            matches!(row_set, Some(RowSet::Parsed(rows)) if rows == vec![vec![serde_json::json!("ci_user")]])
+        );
+
+        // token signed for another user
+        let err = login_with_key_pair(&addr, "ci_user", key_pair_jwt("ACCOUNT", "OTHER"))
+            .await
+            .expect_err("Key-pair login with a wrong subject should fail");
+        assert_eq!(err.status, http::StatusCode::UNAUTHORIZED);
+
+        // tokens valid for longer than an hour
+        let err = login_with_key_pair(
+            &addr,
+            "ci_user",
+            key_pair_jwt_issued("ACCOUNT", "CI_USER", 0, 2 * 60 * 60),
+        )
+        .await
+        .expect_err("Key-pair login with a long lived token should fail");
+        assert_eq!(err.status, http::StatusCode::UNAUTHORIZED);
+
+        // tokens issued in the future
+        let err = login_with_key_pair(
+            &addr,
+            "ci_user",
+            key_pair_jwt_issued("ACCOUNT", "CI_USER", 10 * 60, 60),
+        )
+        .await
+        .expect_err("Key-pair login with a token issued in the future should fail");
+        assert_eq!(err.status, http::StatusCode::UNAUTHORIZED);
+
+        // user without a registered public key
+        let err = login_with_key_pair(&addr, DEMO_USER, key_pair_jwt("ACCOUNT", DEMO_USER))
+            .await
+            .expect_err("Key-pair login without a public key should fail");
+        assert_eq!(err.status, http::StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_store_user_privileges() {
+        let addr = run_test_rest_api_server(
+            Some(rest_default_cfg(JSON)),
+            None,
+            None,
+            MetastoreConfig::None,
+        );
+        let mut admin = credentials(DEMO_USER, DEMO_PASSWORD);
+        for sql in [
+            "CREATE TABLE embucket.public.roles_test (id INT)",
+            "CREATE ROLE analyst",
+            "GRANT USAGE ON DATABASE embucket TO ROLE analyst",
+            "GRANT USAGE ON SCHEMA embucket.public TO ROLE analyst",
+            "GRANT SELECT ON TABLE embucket.public.roles_test TO ROLE analyst",
+            "CREATE USER alice PASSWORD = 'secret' DEFAULT_ROLE = analyst",
+            "GRANT ROLE analyst TO USER alice",
+        ] {
+            let (res, _) = snow_sql(&addr, sql, &mut admin).await;
+            assert!(res.success, "{sql}: {res:?}");
+        }
+
+        let mut alice = credentials("alice", "secret");
+        let (res, _) = snow_sql(
+            &addr,
+            "SELECT * FROM embucket.public.roles_test",
+            &mut alice,
+        )
+        .await;
+        assert!(res.success, "{res:?}");
+
+        let (res, _) = snow_sql(
+            &addr,
+            "INSERT INTO embucket.public.roles_test VALUES (1)",
+            &mut alice,
+        )
+        .await;
+        assert!(!res.success);
+        let data = res.data.expect("No data in response");
+        assert_eq!(data.sql_state.as_deref(), Some("42501"));
+        assert_eq!(data.error_code.as_deref(), Some("003001"));
+
+        // roles not granted to the user can't be used, nor set as session variables
+        for sql in [
+            "USE ROLE accountadmin",
+            "SET role = 'ACCOUNTADMIN'",
+            "ALTER SESSION SET role = 'ACCOUNTADMIN'",
+        ] {
+            let (res, _) = snow_sql(&addr, sql, &mut alice).await;
+            assert!(!res.success, "{sql}: {res:?}");
+        }
+        let (res, _) = snow_sql(
+            &addr,
+            "INSERT INTO embucket.public.roles_test VALUES (1)",
+            &mut alice,
+        )
+        .await;
+        assert!(!res.success);
+
+        // users can change their own credentials, but not manage users
+        let (res, _) = snow_sql(&addr, "ALTER USER alice SET COMMENT = 'me'", &mut alice).await;
+        assert!(res.success, "{res:?}");
+        for sql in [
+            "ALTER USER alice SET DISABLED = TRUE",
+            "ALTER USER alice SET DEFAULT_ROLE = accountadmin",
+            "DROP USER alice",
+        ] {
+            let (res, _) = snow_sql(&addr, sql, &mut alice).await;
+            assert!(!res.success, "{sql}: {res:?}");
+        }
+    }
+}
@@ -56,6 +56,83 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("User {user} already exists"))]
+    UserAlreadyExists {
+        user: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("User {user} not found"))]
+    UserNotFound {
+        user: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Role {role} already exists"))]
+    RoleAlreadyExists {
+        role: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Role {role} not found"))]
+    RoleNotFound {
+        role: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Stage {stage} already exists"))]
+    StageAlreadyExists {
+        stage: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Stage {stage} not found"))]
+    StageNotFound {
+        stage: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("No dropped {object} to restore within its retention time"))]
+    DroppedObjectNotFound {
+        object: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("External table {table} already exists"))]
+    ExternalTableAlreadyExists {
+        table: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("External table {table} not found"))]
+    ExternalTableNotFound {
+        table: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("File format {file_format} already exists"))]
+    FileFormatAlreadyExists {
+        file_format: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("File format {file_format} not found"))]
+    FileFormatNotFound {
+        file_format: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[snafu(display("Database {db} already exists"))]
     DatabaseAlreadyExists {
         db: String,
@@ -154,6 +231,23 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Failed to serialize metastore state: {error}"))]
+    SerializeMetastoreState {
+        #[snafu(source)]
+        error: serde_json::Error,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Failed to load metastore state from {path}: {error}"))]
+    LoadMetastoreState {
+        path: String,
+        #[snafu(source)]
+        error: serde_json::Error,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[snafu(display("Validation Error: {error}"))]
     Validation {
         #[snafu(source)]
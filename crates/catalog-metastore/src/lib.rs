@@ -3,7 +3,9 @@ pub mod metastore;
 pub mod metastore_bootstrap_config;
 pub mod metastore_settings_config;
 pub mod models;
+pub mod persistent_metastore;
 
 pub use error::Error;
 pub use metastore::*;
 pub use models::*;
+pub use persistent_metastore::PersistentMetastore;
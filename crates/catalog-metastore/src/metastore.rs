@@ -1,23 +1,35 @@
 use crate::error::{self as metastore_error, Result};
-use crate::metastore_settings_config::MetastoreSettingsConfig;
+use crate::metastore_settings_config::{
+    DEFAULT_DATA_RETENTION_TIME_IN_DAYS, MetastoreSettingsConfig,
+};
 use crate::models::{
     RwObject,
     database::{Database, DatabaseIdent},
+    dropped_object::{DroppedObject, DroppedObjectIdent},
+    external_table::ExternalTable,
+    file_format::{FileFormat, FileFormatIdent},
+    role::{Role, RoleIdent},
     schema::{Schema, SchemaIdent},
+    stage::{Stage, StageIdent},
     table::{Table, TableCreateRequest, TableFormat, TableIdent, TableRequirementExt, TableUpdate},
+    table_load::TableLoad,
+    user::{User, UserIdent},
     volumes::{Volume, VolumeIdent},
 };
 use async_trait::async_trait;
 use bytes::Bytes;
-use chrono::Utc;
+use chrono::{Duration, Utc};
 use dashmap::DashMap;
+use futures::TryStreamExt;
 use iceberg_rust::catalog::commit::{TableUpdate as IcebergTableUpdate, apply_table_updates};
+use iceberg_rust::spec::util::strip_prefix;
 use iceberg_rust_spec::{
     schema::Schema as IcebergSchema,
-    table_metadata::{FormatVersion, TableMetadataBuilder},
+    table_metadata::{FormatVersion, TableMetadata, TableMetadataBuilder},
     types::{StructField, Type},
 };
 use object_store::{ObjectStore, PutPayload, path::Path};
+use serde::{Deserialize, Serialize};
 use snafu::ResultExt;
 use std::{collections::HashMap, sync::Arc};
 use tokio::sync::RwLock;
@@ -60,6 +72,8 @@ pub trait Metastore: std::fmt::Debug + Send + Sync {
         ident: &TableIdent,
         table: TableCreateRequest,
     ) -> Result<RwObject<Table>>;
+    /// Registers a table with the given metadata, which may refer to the data files of
+    /// another table, as a zero-copy clone does. Fails if the table already exists.
     async fn register_table(&self, ident: &TableIdent, table: Table) -> Result<RwObject<Table>>;
     async fn get_table(&self, ident: &TableIdent) -> Result<Option<RwObject<Table>>>;
     async fn update_table(
@@ -67,12 +81,101 @@ pub trait Metastore: std::fmt::Debug + Send + Sync {
         ident: &TableIdent,
         update: TableUpdate,
     ) -> Result<RwObject<Table>>;
+    /// Applies updates to several tables atomically: either the requirements of every
+    /// update hold and all of them are applied, or none is.
+    async fn update_tables(
+        &self,
+        updates: Vec<(TableIdent, TableUpdate)>,
+    ) -> Result<Vec<RwObject<Table>>>;
     async fn delete_table(&self, ident: &TableIdent, cascade: bool) -> Result<()>;
+    /// Renames a table, which keeps its metadata and load history
+    async fn rename_table(
+        &self,
+        ident: &TableIdent,
+        new_ident: &TableIdent,
+    ) -> Result<RwObject<Table>>;
+    /// Swaps the names of two tables, along with their load history
+    async fn swap_tables(&self, ident: &TableIdent, other: &TableIdent) -> Result<()>;
     async fn table_object_store(&self, ident: &TableIdent) -> Result<Option<Arc<dyn ObjectStore>>>;
 
     async fn table_exists(&self, ident: &TableIdent) -> Result<bool>;
     async fn url_for_table(&self, ident: &TableIdent) -> Result<String>;
     async fn volume_for_table(&self, ident: &TableIdent) -> Result<Option<RwObject<Volume>>>;
+
+    async fn list_users(&self) -> Result<Vec<RwObject<User>>>;
+    /// Returns true if any users are kept in the metastore
+    async fn has_users(&self) -> Result<bool>;
+    async fn create_user(&self, name: &UserIdent, user: User) -> Result<RwObject<User>>;
+    async fn get_user(&self, name: &UserIdent) -> Result<Option<RwObject<User>>>;
+    /// Updates the user, renaming it when the name of the given user differs from `name`
+    async fn update_user(&self, name: &UserIdent, user: User) -> Result<RwObject<User>>;
+    async fn delete_user(&self, name: &UserIdent) -> Result<()>;
+
+    async fn list_roles(&self) -> Result<Vec<RwObject<Role>>>;
+    async fn create_role(&self, name: &RoleIdent, role: Role) -> Result<RwObject<Role>>;
+    async fn get_role(&self, name: &RoleIdent) -> Result<Option<RwObject<Role>>>;
+    async fn update_role(&self, name: &RoleIdent, role: Role) -> Result<RwObject<Role>>;
+    async fn delete_role(&self, name: &RoleIdent) -> Result<()>;
+
+    async fn list_stages(&self, schema: &SchemaIdent) -> Result<Vec<RwObject<Stage>>>;
+    async fn create_stage(&self, ident: &StageIdent, stage: Stage) -> Result<RwObject<Stage>>;
+    async fn get_stage(&self, ident: &StageIdent) -> Result<Option<RwObject<Stage>>>;
+    async fn update_stage(&self, ident: &StageIdent, stage: Stage) -> Result<RwObject<Stage>>;
+    async fn delete_stage(&self, ident: &StageIdent) -> Result<()>;
+
+    async fn list_file_formats(&self, schema: &SchemaIdent) -> Result<Vec<RwObject<FileFormat>>>;
+    async fn create_file_format(
+        &self,
+        ident: &FileFormatIdent,
+        file_format: FileFormat,
+    ) -> Result<RwObject<FileFormat>>;
+    async fn get_file_format(
+        &self,
+        ident: &FileFormatIdent,
+    ) -> Result<Option<RwObject<FileFormat>>>;
+    async fn update_file_format(
+        &self,
+        ident: &FileFormatIdent,
+        file_format: FileFormat,
+    ) -> Result<RwObject<FileFormat>>;
+    async fn delete_file_format(&self, ident: &FileFormatIdent) -> Result<()>;
+
+    async fn list_external_tables(
+        &self,
+        schema: &SchemaIdent,
+    ) -> Result<Vec<RwObject<ExternalTable>>>;
+    async fn create_external_table(
+        &self,
+        ident: &TableIdent,
+        table: ExternalTable,
+    ) -> Result<RwObject<ExternalTable>>;
+    async fn get_external_table(
+        &self,
+        ident: &TableIdent,
+    ) -> Result<Option<RwObject<ExternalTable>>>;
+    async fn update_external_table(
+        &self,
+        ident: &TableIdent,
+        table: ExternalTable,
+    ) -> Result<RwObject<ExternalTable>>;
+    async fn delete_external_table(&self, ident: &TableIdent) -> Result<()>;
+
+    /// Load metadata of the files copied into the table, oldest first
+    async fn list_table_loads(&self, ident: &TableIdent) -> Result<Vec<TableLoad>>;
+    /// Load metadata of the files copied into all tables of the database, oldest first
+    async fn list_database_loads(&self, database: &DatabaseIdent) -> Result<Vec<TableLoad>>;
+    async fn record_table_loads(&self, ident: &TableIdent, loads: Vec<TableLoad>) -> Result<()>;
+
+    /// Dropped databases, schemas and tables that can still be restored, oldest drop first
+    async fn list_dropped_objects(&self) -> Result<Vec<DroppedObject>>;
+    /// Restores the most recently dropped object with the given name, provided its
+    /// retention time has not passed and no object with that name exists in the meantime
+    async fn undrop(&self, ident: &DroppedObjectIdent) -> Result<()>;
+    /// Deletes the dropped objects whose retention time has passed along with the files
+    /// of their tables and forgets expired table loads, returns how many objects were
+    /// purged
+    async fn purge_dropped_objects(&self) -> Result<usize>;
+
     fn settings_config(&self) -> Option<MetastoreSettingsConfig>;
 }
 
@@ -82,6 +185,38 @@ struct MetastoreState {
     databases: HashMap<DatabaseIdent, RwObject<Database>>,
     schemas: HashMap<(DatabaseIdent, String), RwObject<Schema>>,
     tables: HashMap<(DatabaseIdent, String, String), RwObject<Table>>,
+    users: HashMap<UserIdent, RwObject<User>>,
+    roles: HashMap<RoleIdent, RwObject<Role>>,
+    stages: HashMap<(DatabaseIdent, String, String), RwObject<Stage>>,
+    file_formats: HashMap<(DatabaseIdent, String, String), RwObject<FileFormat>>,
+    external_tables: HashMap<(DatabaseIdent, String, String), RwObject<ExternalTable>>,
+    table_loads: HashMap<(DatabaseIdent, String, String), Vec<TableLoad>>,
+    /// Tombstones of dropped objects, oldest drop first
+    dropped: Vec<DroppedObject>,
+}
+
+/// Serializable copy of the whole metastore state, used by
+/// [`crate::persistent_metastore::PersistentMetastore`] to save and restore it.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct MetastoreSnapshot {
+    pub volumes: Vec<RwObject<Volume>>,
+    pub databases: Vec<RwObject<Database>>,
+    pub schemas: Vec<RwObject<Schema>>,
+    pub tables: Vec<RwObject<Table>>,
+    #[serde(default)]
+    pub users: Vec<RwObject<User>>,
+    #[serde(default)]
+    pub roles: Vec<RwObject<Role>>,
+    #[serde(default)]
+    pub stages: Vec<RwObject<Stage>>,
+    #[serde(default)]
+    pub file_formats: Vec<RwObject<FileFormat>>,
+    #[serde(default)]
+    pub external_tables: Vec<RwObject<ExternalTable>>,
+    #[serde(default)]
+    pub table_loads: Vec<TableLoad>,
+    #[serde(default)]
+    pub dropped_objects: Vec<DroppedObject>,
 }
 
 #[derive(Debug, Default)]
@@ -110,6 +245,88 @@ impl InMemoryMetastore {
         }
     }
 
+    /// Returns a copy of the current state of the metastore.
+    pub async fn snapshot(&self) -> MetastoreSnapshot {
+        let state = self.state.read().await;
+        MetastoreSnapshot {
+            volumes: state.volumes.values().cloned().collect(),
+            databases: state.databases.values().cloned().collect(),
+            schemas: state.schemas.values().cloned().collect(),
+            tables: state.tables.values().cloned().collect(),
+            users: state.users.values().cloned().collect(),
+            roles: state.roles.values().cloned().collect(),
+            stages: state.stages.values().cloned().collect(),
+            file_formats: state.file_formats.values().cloned().collect(),
+            external_tables: state.external_tables.values().cloned().collect(),
+            table_loads: state.table_loads.values().flatten().cloned().collect(),
+            dropped_objects: state.dropped.clone(),
+        }
+    }
+
+    /// Replaces the state of the metastore with the given snapshot.
+    pub async fn restore(&self, snapshot: MetastoreSnapshot) {
+        let mut state = self.state.write().await;
+        *state = MetastoreState {
+            volumes: snapshot
+                .volumes
+                .into_iter()
+                .map(|volume| (volume.ident.clone(), volume))
+                .collect(),
+            databases: snapshot
+                .databases
+                .into_iter()
+                .map(|database| (database.ident.clone(), database))
+                .collect(),
+            schemas: snapshot
+                .schemas
+                .into_iter()
+                .map(|schema| (Self::schema_key(&schema.ident), schema))
+                .collect(),
+            tables: snapshot
+                .tables
+                .into_iter()
+                .map(|table| (Self::table_key(&table.ident), table))
+                .collect(),
+            users: snapshot
+                .users
+                .into_iter()
+                .map(|user| (user.name.clone(), user))
+                .collect(),
+            roles: snapshot
+                .roles
+                .into_iter()
+                .map(|role| (role.name.clone(), role))
+                .collect(),
+            stages: snapshot
+                .stages
+                .into_iter()
+                .map(|stage| (Self::stage_key(&stage.ident), stage))
+                .collect(),
+            file_formats: snapshot
+                .file_formats
+                .into_iter()
+                .map(|file_format| (Self::file_format_key(&file_format.ident), file_format))
+                .collect(),
+            external_tables: snapshot
+                .external_tables
+                .into_iter()
+                .map(|table| (Self::table_key(&table.ident), table))
+                .collect(),
+            table_loads: snapshot.table_loads.into_iter().fold(
+                HashMap::new(),
+                |mut loads, load| {
+                    loads
+                        .entry(Self::table_key(&load.table))
+                        .or_default()
+                        .push(load);
+                    loads
+                },
+            ),
+            dropped: snapshot.dropped_objects,
+        };
+        self.object_store_cache.clear();
+    }
+
     #[instrument(name = "Metastore::metadata_file_name", level = "trace", ret)]
     fn metadata_file_name() -> String {
         format!("{}.metadata.json", Uuid::new_v4())
@@ -135,6 +352,22 @@ impl InMemoryMetastore {
         )
     }
 
+    fn stage_key(ident: &StageIdent) -> (DatabaseIdent, String, String) {
+        (
+            ident.database.to_ascii_lowercase(),
+            ident.schema.to_ascii_lowercase(),
+            ident.stage.to_ascii_lowercase(),
+        )
+    }
+
+    fn file_format_key(ident: &FileFormatIdent) -> (DatabaseIdent, String, String) {
+        (
+            ident.database.to_ascii_lowercase(),
+            ident.schema.to_ascii_lowercase(),
+            ident.file_format.to_ascii_lowercase(),
+        )
+    }
+
     fn database_key(ident: &TableIdent) -> DatabaseIdent {
         ident.database.to_ascii_lowercase()
     }
@@ -378,19 +611,20 @@ impl Metastore for InMemoryMetastore {
             }
             .fail();
         }
-        for key in &schema_keys {
-            state.schemas.remove(key);
-        }
-        let table_keys: Vec<_> = state
-            .tables
-            .keys()
-            .filter(|(db, _, _)| db == name)
-            .cloned()
+        let Some(database) = state.databases.remove(name) else {
+            return Ok(());
+        };
+        let mut dropped = DroppedObject::new(
+            DroppedObjectIdent::Database(name.clone()),
+            self.data_retention_time(),
+        );
+        dropped.database = Some(database);
+        dropped.schemas = schema_keys
+            .iter()
+            .filter_map(|key| state.schemas.remove(key))
             .collect();
-        for key in &table_keys {
-            state.tables.remove(key);
-        }
-        state.databases.remove(name);
+        Self::take_schema_objects(&mut state, name, None, &mut dropped);
+        state.dropped.push(dropped);
         Ok(())
     }
 
@@ -481,10 +715,17 @@ impl Metastore for InMemoryMetastore {
             }
             .fail();
         }
-        for key in tables {
-            state.tables.remove(&key);
-        }
-        state.schemas.remove(&Self::schema_key(ident));
+        let Some(schema) = state.schemas.remove(&Self::schema_key(ident)) else {
+            return Ok(());
+        };
+        let mut dropped = DroppedObject::new(
+            DroppedObjectIdent::Schema(ident.clone()),
+            self.data_retention_time(),
+        );
+        dropped.schemas.push(schema);
+        let (database, schema) = Self::schema_key(ident);
+        Self::take_schema_objects(&mut state, &database, Some(&schema), &mut dropped);
+        state.dropped.push(dropped);
         Ok(())
     }
 
@@ -618,8 +859,59 @@ impl Metastore for InMemoryMetastore {
         skip(self, table),
         err
     )]
-    async fn register_table(&self, ident: &TableIdent, table: Table) -> Result<RwObject<Table>> {
+    async fn register_table(
+        &self,
+        ident: &TableIdent,
+        mut table: Table,
+    ) -> Result<RwObject<Table>> {
+        let volume_ident = match table.volume_ident.clone() {
+            Some(volume_ident) => volume_ident,
+            None => {
+                let state = self.state.read().await;
+                Self::ensure_database(&state, &ident.database)?
+                    .volume
+                    .clone()
+            }
+        };
+        let object_store = self
+            .volume_object_store(&volume_ident)
+            .await?
+            .ok_or_else(|| {
+                metastore_error::VolumeNotFoundSnafu {
+                    volume: volume_ident.clone(),
+                }
+                .build()
+            })?;
+
         let mut state = self.state.write().await;
+        if !state
+            .schemas
+            .contains_key(&Self::schema_key(&ident.clone().into()))
+        {
+            return metastore_error::SchemaNotFoundSnafu {
+                schema: ident.schema.clone(),
+                db: ident.database.clone(),
+            }
+            .fail();
+        }
+        if state.tables.contains_key(&Self::table_key(ident)) {
+            return metastore_error::TableAlreadyExistsSnafu {
+                table: ident.table.clone(),
+                schema: ident.schema.clone(),
+                db: ident.database.clone(),
+            }
+            .fail();
+        }
+
+        // The metadata may refer to data files of another table, which stay where they
+        // are, only the metadata file is written for the registered table
+        table.metadata_location = self
+            .put_metadata(ident, object_store, &table.metadata)
+            .await?;
+        table.ident = ident.clone();
+        table.volume_ident = Some(volume_ident);
+        Self::update_properties_timestamps(&mut table.properties);
+
         let row = RwObject::new(table);
         state.tables.insert(Self::table_key(ident), row.clone());
         Ok(row)
@@ -682,10 +974,167 @@ impl Metastore for InMemoryMetastore {
         Ok(table_entry.clone())
     }
 
+    #[instrument(
+        name = "Metastore::update_tables",
+        level = "debug",
+        skip(self, updates),
+        err
+    )]
+    async fn update_tables(
+        &self,
+        updates: Vec<(TableIdent, TableUpdate)>,
+    ) -> Result<Vec<RwObject<Table>>> {
+        let mut object_stores = Vec::with_capacity(updates.len());
+        for (ident, _) in &updates {
+            let object_store = self.table_object_store(ident).await?.ok_or_else(|| {
+                metastore_error::TableNotFoundSnafu {
+                    table: ident.table.clone(),
+                    schema: ident.schema.clone(),
+                    db: ident.database.clone(),
+                }
+                .build()
+            })?;
+            object_stores.push(object_store);
+        }
+
+        let mut state = self.state.write().await;
+        // Check every requirement and write the new metadata files before touching any
+        // table entry, so a single failed requirement leaves all of the tables intact.
+        let mut staged = Vec::with_capacity(updates.len());
+        for ((ident, mut update), object_store) in updates.into_iter().zip(object_stores) {
+            let table_entry = state.tables.get(&Self::table_key(&ident)).ok_or_else(|| {
+                metastore_error::TableNotFoundSnafu {
+                    table: ident.table.clone(),
+                    schema: ident.schema.clone(),
+                    db: ident.database.clone(),
+                }
+                .build()
+            })?;
+            update
+                .requirements
+                .into_iter()
+                .map(TableRequirementExt::new)
+                .try_for_each(|req| req.assert(&table_entry.metadata))?;
+
+            convert_add_schema_update_to_lowercase(&mut update.updates)?;
+
+            let mut metadata = table_entry.metadata.clone();
+            apply_table_updates(&mut metadata, update.updates)
+                .context(metastore_error::IcebergSnafu)?;
+            let metadata_location = self.put_metadata(&ident, object_store, &metadata).await?;
+            staged.push((ident, metadata, metadata_location));
+        }
+
+        let mut tables = Vec::with_capacity(staged.len());
+        for (ident, metadata, metadata_location) in staged {
+            if let Some(table_entry) = state.tables.get_mut(&Self::table_key(&ident)) {
+                table_entry.data.metadata = metadata;
+                table_entry.data.metadata_location = metadata_location;
+                table_entry.touch();
+                tables.push(table_entry.clone());
+            }
+        }
+        Ok(tables)
+    }
+
     #[instrument(name = "Metastore::delete_table", level = "debug", skip(self), err)]
     async fn delete_table(&self, ident: &TableIdent, _cascade: bool) -> Result<()> {
         let mut state = self.state.write().await;
-        state.tables.remove(&Self::table_key(ident));
+        let key = Self::table_key(ident);
+        let Some(table) = state.tables.remove(&key) else {
+            return Ok(());
+        };
+        let mut dropped = DroppedObject::new(
+            DroppedObjectIdent::Table(ident.clone()),
+            self.data_retention_time(),
+        );
+        dropped.tables.push(table);
+        // Load metadata is not kept for dropped tables, an undropped table loads its files again
+        state.table_loads.remove(&key);
+        state.dropped.push(dropped);
+        Ok(())
+    }
+
+    #[instrument(name = "Metastore::rename_table", level = "debug", skip(self), err)]
+    async fn rename_table(
+        &self,
+        ident: &TableIdent,
+        new_ident: &TableIdent,
+    ) -> Result<RwObject<Table>> {
+        let mut state = self.state.write().await;
+        if !state
+            .schemas
+            .contains_key(&Self::schema_key(&new_ident.clone().into()))
+        {
+            return metastore_error::SchemaNotFoundSnafu {
+                schema: new_ident.schema.clone(),
+                db: new_ident.database.clone(),
+            }
+            .fail();
+        }
+        let new_key = Self::table_key(new_ident);
+        if state.tables.contains_key(&new_key) {
+            return metastore_error::TableAlreadyExistsSnafu {
+                table: new_ident.table.clone(),
+                schema: new_ident.schema.clone(),
+                db: new_ident.database.clone(),
+            }
+            .fail();
+        }
+        let key = Self::table_key(ident);
+        let mut table = state.tables.remove(&key).ok_or_else(|| {
+            metastore_error::TableNotFoundSnafu {
+                table: ident.table.clone(),
+                schema: ident.schema.clone(),
+                db: ident.database.clone(),
+            }
+            .build()
+        })?;
+        table.data.ident = new_ident.clone();
+        table.touch();
+        state.tables.insert(new_key.clone(), table.clone());
+        if let Some(loads) = state.table_loads.remove(&key) {
+            state.table_loads.insert(new_key, loads);
+        }
+        Ok(table)
+    }
+
+    #[instrument(name = "Metastore::swap_tables", level = "debug", skip(self), err)]
+    async fn swap_tables(&self, ident: &TableIdent, other: &TableIdent) -> Result<()> {
+        let mut state = self.state.write().await;
+        let (key, other_key) = (Self::table_key(ident), Self::table_key(other));
+        for (ident, key) in [(ident, &key), (other, &other_key)] {
+            if !state.tables.contains_key(key) {
+                return metastore_error::TableNotFoundSnafu {
+                    table: ident.table.clone(),
+                    schema: ident.schema.clone(),
+                    db: ident.database.clone(),
+                }
+                .fail();
+            }
+        }
+        if key == other_key {
+            return Ok(());
+        }
+        let (Some(mut table), Some(mut other_table)) =
+            (state.tables.remove(&key), state.tables.remove(&other_key))
+        else {
+            return Ok(());
+        };
+        table.data.ident = other.clone();
+        table.touch();
+        other_table.data.ident = ident.clone();
+        other_table.touch();
+        state.tables.insert(other_key.clone(), table);
+        state.tables.insert(key.clone(), other_table);
+        let loads = state.table_loads.remove(&key);
+        let other_loads = state.table_loads.remove(&other_key);
+        if let Some(loads) = loads {
+            state.table_loads.insert(other_key, loads);
+        }
+        if let Some(other_loads) = other_loads {
+            state.table_loads.insert(key, other_loads);
+        }
         Ok(())
     }
 
@@ -742,6 +1191,608 @@ impl Metastore for InMemoryMetastore {
         }
     }
 
+    #[instrument(name = "Metastore::list_users", level = "trace", skip(self), err)]
+    async fn list_users(&self) -> Result<Vec<RwObject<User>>> {
+        let state = self.state.read().await;
+        Ok(state.users.values().cloned().collect())
+    }
+
+    #[instrument(name = "Metastore::has_users", level = "trace", skip(self), err)]
+    async fn has_users(&self) -> Result<bool> {
+        let state = self.state.read().await;
+        Ok(!state.users.is_empty())
+    }
+
+    #[instrument(
+        name = "Metastore::create_user",
+        level = "debug",
+        skip(self, user),
+        err
+    )]
+    async fn create_user(&self, name: &UserIdent, user: User) -> Result<RwObject<User>> {
+        let mut state = self.state.write().await;
+        if state.users.contains_key(name) {
+            return metastore_error::UserAlreadyExistsSnafu { user: name }.fail();
+        }
+        let row = RwObject::new(user);
+        state.users.insert(name.clone(), row.clone());
+        Ok(row)
+    }
+
+    #[instrument(name = "Metastore::get_user", level = "trace", skip(self), err)]
+    async fn get_user(&self, name: &UserIdent) -> Result<Option<RwObject<User>>> {
+        let state = self.state.read().await;
+        Ok(state.users.get(name).cloned())
+    }
+
+    #[instrument(
+        name = "Metastore::update_user",
+        level = "debug",
+        skip(self, user),
+        err
+    )]
+    async fn update_user(&self, name: &UserIdent, user: User) -> Result<RwObject<User>> {
+        let mut state = self.state.write().await;
+        if &user.name != name && state.users.contains_key(&user.name) {
+            return metastore_error::UserAlreadyExistsSnafu { user: user.name }.fail();
+        }
+        let mut entry = state
+            .users
+            .remove(name)
+            .ok_or_else(|| metastore_error::UserNotFoundSnafu { user: name }.build())?;
+        entry.update(user);
+        state.users.insert(entry.name.clone(), entry.clone());
+        Ok(entry)
+    }
+
+    #[instrument(name = "Metastore::delete_user", level = "debug", skip(self), err)]
+    async fn delete_user(&self, name: &UserIdent) -> Result<()> {
+        let mut state = self.state.write().await;
+        state
+            .users
+            .remove(name)
+            .map(|_| ())
+            .ok_or_else(|| metastore_error::UserNotFoundSnafu { user: name }.build())
+    }
+
+    #[instrument(name = "Metastore::list_roles", level = "trace", skip(self), err)]
+    async fn list_roles(&self) -> Result<Vec<RwObject<Role>>> {
+        let state = self.state.read().await;
+        Ok(state.roles.values().cloned().collect())
+    }
+
+    #[instrument(
+        name = "Metastore::create_role",
+        level = "debug",
+        skip(self, role),
+        err
+    )]
+    async fn create_role(&self, name: &RoleIdent, role: Role) -> Result<RwObject<Role>> {
+        let mut state = self.state.write().await;
+        if state.roles.contains_key(name) {
+            return metastore_error::RoleAlreadyExistsSnafu { role: name }.fail();
+        }
+        let row = RwObject::new(role);
+        state.roles.insert(name.clone(), row.clone());
+        Ok(row)
+    }
+
+    #[instrument(name = "Metastore::get_role", level = "trace", skip(self), err)]
+    async fn get_role(&self, name: &RoleIdent) -> Result<Option<RwObject<Role>>> {
+        let state = self.state.read().await;
+        Ok(state.roles.get(name).cloned())
+    }
+
+    #[instrument(
+        name = "Metastore::update_role",
+        level = "debug",
+        skip(self, role),
+        err
+    )]
+    async fn update_role(&self, name: &RoleIdent, role: Role) -> Result<RwObject<Role>> {
+        let mut state = self.state.write().await;
+        let entry = state
+            .roles
+            .get_mut(name)
+            .ok_or_else(|| metastore_error::RoleNotFoundSnafu { role: name }.build())?;
+        entry.update(role);
+        Ok(entry.clone())
+    }
+
+    #[instrument(name = "Metastore::delete_role", level = "debug", skip(self), err)]
+    async fn delete_role(&self, name: &RoleIdent) -> Result<()> {
+        let mut state = self.state.write().await;
+        state
+            .roles
+            .remove(name)
+            .map(|_| ())
+            .ok_or_else(|| metastore_error::RoleNotFoundSnafu { role: name }.build())
+    }
+
+    #[instrument(name = "Metastore::list_stages", level = "trace", skip(self), err)]
+    async fn list_stages(&self, schema: &SchemaIdent) -> Result<Vec<RwObject<Stage>>> {
+        let state = self.state.read().await;
+        let (database, schema) = Self::schema_key(schema);
+        let mut items: Vec<_> = state
+            .stages
+            .iter()
+            .filter(|((db, sch, _), _)| db == &database && sch == &schema)
+            .map(|(_, stage)| stage.clone())
+            .collect();
+        items.sort_by(|a, b| a.ident.stage.cmp(&b.ident.stage));
+        Ok(items)
+    }
+
+    #[instrument(
+        name = "Metastore::create_stage",
+        level = "debug",
+        skip(self, stage),
+        err
+    )]
+    async fn create_stage(&self, ident: &StageIdent, stage: Stage) -> Result<RwObject<Stage>> {
+        let mut state = self.state.write().await;
+        let key = Self::stage_key(ident);
+        if state.stages.contains_key(&key) {
+            return metastore_error::StageAlreadyExistsSnafu {
+                stage: ident.to_string(),
+            }
+            .fail();
+        }
+        Self::ensure_database(&state, &key.0)?;
+        let row = RwObject::new(stage);
+        state.stages.insert(key, row.clone());
+        Ok(row)
+    }
+
+    #[instrument(name = "Metastore::get_stage", level = "trace", skip(self), err)]
+    async fn get_stage(&self, ident: &StageIdent) -> Result<Option<RwObject<Stage>>> {
+        let state = self.state.read().await;
+        Ok(state.stages.get(&Self::stage_key(ident)).cloned())
+    }
+
+    #[instrument(
+        name = "Metastore::update_stage",
+        level = "debug",
+        skip(self, stage),
+        err
+    )]
+    async fn update_stage(&self, ident: &StageIdent, stage: Stage) -> Result<RwObject<Stage>> {
+        let mut state = self.state.write().await;
+        let entry = state
+            .stages
+            .get_mut(&Self::stage_key(ident))
+            .ok_or_else(|| {
+                metastore_error::StageNotFoundSnafu {
+                    stage: ident.to_string(),
+                }
+                .build()
+            })?;
+        entry.update(stage);
+        Ok(entry.clone())
+    }
+
+    #[instrument(name = "Metastore::delete_stage", level = "debug", skip(self), err)]
+    async fn delete_stage(&self, ident: &StageIdent) -> Result<()> {
+        let mut state = self.state.write().await;
+        state
+            .stages
+            .remove(&Self::stage_key(ident))
+            .map(|_| ())
+            .ok_or_else(|| {
+                metastore_error::StageNotFoundSnafu {
+                    stage: ident.to_string(),
+                }
+                .build()
+            })
+    }
+
+    #[instrument(
+        name = "Metastore::list_file_formats",
+        level = "trace",
+        skip(self),
+        err
+    )]
+    async fn list_file_formats(&self, schema: &SchemaIdent) -> Result<Vec<RwObject<FileFormat>>> {
+        let state = self.state.read().await;
+        let (database, schema) = Self::schema_key(schema);
+        let mut items: Vec<_> = state
+            .file_formats
+            .iter()
+            .filter(|((db, sch, _), _)| db == &database && sch == &schema)
+            .map(|(_, file_format)| file_format.clone())
+            .collect();
+        items.sort_by(|a, b| a.ident.file_format.cmp(&b.ident.file_format));
+        Ok(items)
+    }
+
+    #[instrument(
+        name = "Metastore::create_file_format",
+        level = "debug",
+        skip(self, file_format),
+        err
+    )]
+    async fn create_file_format(
+        &self,
+        ident: &FileFormatIdent,
+        file_format: FileFormat,
+    ) -> Result<RwObject<FileFormat>> {
+        let mut state = self.state.write().await;
+        let key = Self::file_format_key(ident);
+        if state.file_formats.contains_key(&key) {
+            return metastore_error::FileFormatAlreadyExistsSnafu {
+                file_format: ident.to_string(),
+            }
+            .fail();
+        }
+        Self::ensure_database(&state, &key.0)?;
+        let row = RwObject::new(file_format);
+        state.file_formats.insert(key, row.clone());
+        Ok(row)
+    }
+
+    #[instrument(name = "Metastore::get_file_format", level = "trace", skip(self), err)]
+    async fn get_file_format(
+        &self,
+        ident: &FileFormatIdent,
+    ) -> Result<Option<RwObject<FileFormat>>> {
+        let state = self.state.read().await;
+        Ok(state
+            .file_formats
+            .get(&Self::file_format_key(ident))
+            .cloned())
+    }
+
+    #[instrument(
+        name = "Metastore::update_file_format",
+        level = "debug",
+        skip(self, file_format),
+        err
+    )]
+    async fn update_file_format(
+        &self,
+        ident: &FileFormatIdent,
+        file_format: FileFormat,
+    ) -> Result<RwObject<FileFormat>> {
+        let mut state = self.state.write().await;
+        let entry = state
+            .file_formats
+            .get_mut(&Self::file_format_key(ident))
+            .ok_or_else(|| {
+                metastore_error::FileFormatNotFoundSnafu {
+                    file_format: ident.to_string(),
+                }
+                .build()
+            })?;
+        entry.update(file_format);
+        Ok(entry.clone())
+    }
+
+    #[instrument(
+        name = "Metastore::delete_file_format",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn delete_file_format(&self, ident: &FileFormatIdent) -> Result<()> {
+        let mut state = self.state.write().await;
+        state
+            .file_formats
+            .remove(&Self::file_format_key(ident))
+            .map(|_| ())
+            .ok_or_else(|| {
+                metastore_error::FileFormatNotFoundSnafu {
+                    file_format: ident.to_string(),
+                }
+                .build()
+            })
+    }
+
+    #[instrument(
+        name = "Metastore::list_external_tables",
+        level = "trace",
+        skip(self),
+        err
+    )]
+    async fn list_external_tables(
+        &self,
+        schema: &SchemaIdent,
+    ) -> Result<Vec<RwObject<ExternalTable>>> {
+        let state = self.state.read().await;
+        let (database, schema) = Self::schema_key(schema);
+        let mut items: Vec<_> = state
+            .external_tables
+            .iter()
+            .filter(|((db, sch, _), _)| db == &database && sch == &schema)
+            .map(|(_, table)| table.clone())
+            .collect();
+        items.sort_by(|a, b| a.ident.table.cmp(&b.ident.table));
+        Ok(items)
+    }
+
+    #[instrument(
+        name = "Metastore::create_external_table",
+        level = "debug",
+        skip(self, table),
+        err
+    )]
+    async fn create_external_table(
+        &self,
+        ident: &TableIdent,
+        table: ExternalTable,
+    ) -> Result<RwObject<ExternalTable>> {
+        let mut state = self.state.write().await;
+        let key = Self::table_key(ident);
+        if state.external_tables.contains_key(&key) || state.tables.contains_key(&key) {
+            return metastore_error::ExternalTableAlreadyExistsSnafu {
+                table: ident.to_string(),
+            }
+            .fail();
+        }
+        Self::ensure_database(&state, &key.0)?;
+        let row = RwObject::new(table);
+        state.external_tables.insert(key, row.clone());
+        Ok(row)
+    }
+
+    #[instrument(
+        name = "Metastore::get_external_table",
+        level = "trace",
+        skip(self),
+        err
+    )]
+    async fn get_external_table(
+        &self,
+        ident: &TableIdent,
+    ) -> Result<Option<RwObject<ExternalTable>>> {
+        let state = self.state.read().await;
+        Ok(state.external_tables.get(&Self::table_key(ident)).cloned())
+    }
+
+    #[instrument(
+        name = "Metastore::update_external_table",
+        level = "debug",
+        skip(self, table),
+        err
+    )]
+    async fn update_external_table(
+        &self,
+        ident: &TableIdent,
+        table: ExternalTable,
+    ) -> Result<RwObject<ExternalTable>> {
+        let mut state = self.state.write().await;
+        let entry = state
+            .external_tables
+            .get_mut(&Self::table_key(ident))
+            .ok_or_else(|| {
+                metastore_error::ExternalTableNotFoundSnafu {
+                    table: ident.to_string(),
+                }
+                .build()
+            })?;
+        entry.update(table);
+        Ok(entry.clone())
+    }
+
+    #[instrument(
+        name = "Metastore::delete_external_table",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn delete_external_table(&self, ident: &TableIdent) -> Result<()> {
+        let mut state = self.state.write().await;
+        state
+            .external_tables
+            .remove(&Self::table_key(ident))
+            .map(|_| ())
+            .ok_or_else(|| {
+                metastore_error::ExternalTableNotFoundSnafu {
+                    table: ident.to_string(),
+                }
+                .build()
+            })
+    }
+
+    #[instrument(name = "Metastore::list_table_loads", level = "trace", skip(self), err)]
+    async fn list_table_loads(&self, ident: &TableIdent) -> Result<Vec<TableLoad>> {
+        let state = self.state.read().await;
+        let now = Utc::now().naive_utc();
+        Ok(state
+            .table_loads
+            .get(&Self::table_key(ident))
+            .into_iter()
+            .flatten()
+            .filter(|load| !load.is_expired(now))
+            .cloned()
+            .collect())
+    }
+
+    #[instrument(
+        name = "Metastore::list_database_loads",
+        level = "trace",
+        skip(self),
+        err
+    )]
+    async fn list_database_loads(&self, database: &DatabaseIdent) -> Result<Vec<TableLoad>> {
+        let state = self.state.read().await;
+        let database = database.to_ascii_lowercase();
+        let now = Utc::now().naive_utc();
+        let mut items: Vec<_> = state
+            .table_loads
+            .iter()
+            .filter(|((db, _, _), _)| db == &database)
+            .flat_map(|(_, loads)| loads.iter())
+            .filter(|load| !load.is_expired(now))
+            .cloned()
+            .collect();
+        items.sort_by(|a, b| a.loaded_at.cmp(&b.loaded_at));
+        Ok(items)
+    }
+
+    #[instrument(
+        name = "Metastore::record_table_loads",
+        level = "debug",
+        skip(self, loads),
+        err
+    )]
+    async fn record_table_loads(&self, ident: &TableIdent, loads: Vec<TableLoad>) -> Result<()> {
+        let mut state = self.state.write().await;
+        let key = Self::table_key(ident);
+        if !state.tables.contains_key(&key) {
+            return metastore_error::TableNotFoundSnafu {
+                table: ident.table.clone(),
+                schema: ident.schema.clone(),
+                db: ident.database.clone(),
+            }
+            .fail();
+        }
+        let now = Utc::now().naive_utc();
+        let table_loads = state.table_loads.entry(key).or_default();
+        table_loads.extend(loads);
+        table_loads.retain(|load| !load.is_expired(now));
+        Ok(())
+    }
+
+    #[instrument(
+        name = "Metastore::list_dropped_objects",
+        level = "trace",
+        skip(self),
+        err
+    )]
+    async fn list_dropped_objects(&self) -> Result<Vec<DroppedObject>> {
+        let state = self.state.read().await;
+        Ok(state
+            .dropped
+            .iter()
+            .filter(|dropped| !dropped.is_expired())
+            .cloned()
+            .collect())
+    }
+
+    #[instrument(name = "Metastore::undrop", level = "debug", skip(self), err)]
+    async fn undrop(&self, ident: &DroppedObjectIdent) -> Result<()> {
+        let mut state = self.state.write().await;
+        let position = state
+            .dropped
+            .iter()
+            .rposition(|dropped| dropped.ident.matches(ident) && !dropped.is_expired())
+            .ok_or_else(|| {
+                metastore_error::DroppedObjectNotFoundSnafu {
+                    object: ident.to_string(),
+                }
+                .build()
+            })?;
+        match ident {
+            DroppedObjectIdent::Database(name) => {
+                if state.databases.contains_key(name) {
+                    return metastore_error::DatabaseAlreadyExistsSnafu { db: name }.fail();
+                }
+                if let Some(database) = &state.dropped[position].database {
+                    Self::ensure_volume(&state, &database.volume)?;
+                }
+            }
+            DroppedObjectIdent::Schema(schema) => {
+                Self::ensure_database(&state, &schema.database)?;
+                if state.schemas.contains_key(&Self::schema_key(schema)) {
+                    return metastore_error::SchemaAlreadyExistsSnafu {
+                        schema: schema.schema.clone(),
+                        db: schema.database.clone(),
+                    }
+                    .fail();
+                }
+            }
+            DroppedObjectIdent::Table(table) => {
+                if !state
+                    .schemas
+                    .contains_key(&Self::schema_key(&table.clone().into()))
+                {
+                    return metastore_error::SchemaNotFoundSnafu {
+                        schema: table.schema.clone(),
+                        db: table.database.clone(),
+                    }
+                    .fail();
+                }
+                if state.tables.contains_key(&Self::table_key(table)) {
+                    return metastore_error::TableAlreadyExistsSnafu {
+                        table: table.table.clone(),
+                        schema: table.schema.clone(),
+                        db: table.database.clone(),
+                    }
+                    .fail();
+                }
+            }
+        }
+
+        let dropped = state.dropped.remove(position);
+        if let Some(database) = dropped.database {
+            state.databases.insert(database.ident.clone(), database);
+        }
+        for schema in dropped.schemas {
+            state
+                .schemas
+                .insert(Self::schema_key(&schema.ident), schema);
+        }
+        for table in dropped.tables {
+            state.tables.insert(Self::table_key(&table.ident), table);
+        }
+        for stage in dropped.stages {
+            state.stages.insert(Self::stage_key(&stage.ident), stage);
+        }
+        for file_format in dropped.file_formats {
+            state
+                .file_formats
+                .insert(Self::file_format_key(&file_format.ident), file_format);
+        }
+        for table in dropped.external_tables {
+            state
+                .external_tables
+                .insert(Self::table_key(&table.ident), table);
+        }
+        Ok(())
+    }
+
+    #[instrument(
+        name = "Metastore::purge_dropped_objects",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn purge_dropped_objects(&self) -> Result<usize> {
+        let (expired, paths_in_use) = {
+            let mut state = self.state.write().await;
+            let (expired, retained): (Vec<_>, Vec<_>) = std::mem::take(&mut state.dropped)
+                .into_iter()
+                .partition(DroppedObject::is_expired);
+            state.dropped = retained;
+            let now = Utc::now().naive_utc();
+            state.table_loads.retain(|_, loads| {
+                loads.retain(|load| !load.is_expired(now));
+                !loads.is_empty()
+            });
+            // Files of a dropped table may still be read by a clone, or share their
+            // location with a table created under the same name since
+            let paths_in_use = state
+                .tables
+                .values()
+                .chain(
+                    state
+                        .dropped
+                        .iter()
+                        .flat_map(|dropped| dropped.tables.iter()),
+                )
+                .flat_map(|table| table_paths(&table.metadata))
+                .collect::<Vec<_>>();
+            (expired, paths_in_use)
+        };
+        for table in expired.iter().flat_map(|dropped| dropped.tables.iter()) {
+            // A failure leaves files behind but must not keep the other objects around
+            if let Err(error) = self.purge_table_files(table, &paths_in_use).await {
+                tracing::warn!(table = %table.ident, "Failed to purge files of dropped table: {error}");
+            }
+        }
+        Ok(expired.len())
+    }
+
     fn settings_config(&self) -> Option<MetastoreSettingsConfig> {
         self.settings_config.clone()
     }
@@ -776,6 +1827,96 @@ impl InMemoryMetastore {
             })
     }
 
+    fn data_retention_time(&self) -> Duration {
+        let days = self
+            .settings_config
+            .as_ref()
+            .map_or(DEFAULT_DATA_RETENTION_TIME_IN_DAYS, |settings| {
+                settings.data_retention_time_in_days
+            });
+        i64::try_from(days)
+            .ok()
+            .and_then(Duration::try_days)
+            .unwrap_or(Duration::MAX)
+    }
+
+    /// Moves the tables, stages, file formats and external tables of a schema, or of
+    /// every schema of the database if `schema` is `None`, into the tombstone of the
+    /// dropped schema or database
+    fn take_schema_objects(
+        state: &mut MetastoreState,
+        database: &str,
+        schema: Option<&str>,
+        dropped: &mut DroppedObject,
+    ) {
+        fn take<V>(
+            entries: &mut HashMap<(DatabaseIdent, String, String), V>,
+            database: &str,
+            schema: Option<&str>,
+        ) -> Vec<V> {
+            let keys: Vec<_> = entries
+                .keys()
+                .filter(|(db, sch, _)| db == database && schema.is_none_or(|schema| sch == schema))
+                .cloned()
+                .collect();
+            keys.iter().filter_map(|key| entries.remove(key)).collect()
+        }
+        dropped.tables = take(&mut state.tables, database, schema);
+        dropped.stages = take(&mut state.stages, database, schema);
+        dropped.file_formats = take(&mut state.file_formats, database, schema);
+        dropped.external_tables = take(&mut state.external_tables, database, schema);
+        take(&mut state.table_loads, database, schema);
+    }
+
+    /// Deletes the metadata files of a dropped table and, unless another table still
+    /// refers to files under its location, everything under its location
+    #[instrument(
+        name = "Metastore::purge_table_files",
+        level = "debug",
+        skip(self, table, paths_in_use),
+        fields(table = %table.ident),
+        err
+    )]
+    async fn purge_table_files(&self, table: &Table, paths_in_use: &[String]) -> Result<()> {
+        let Some(volume_ident) = &table.volume_ident else {
+            return Ok(());
+        };
+        // Nothing to delete once the volume is gone
+        let Some(object_store) = self.volume_object_store(volume_ident).await? else {
+            return Ok(());
+        };
+        let mut files: Vec<Path> = std::iter::once(&table.metadata_location)
+            .chain(
+                table
+                    .metadata
+                    .metadata_log
+                    .iter()
+                    .map(|entry| &entry.metadata_file),
+            )
+            .map(|file| strip_prefix(file))
+            .filter(|file| !file.is_empty())
+            .map(Path::from)
+            .collect();
+        let location = strip_prefix(&table.metadata.location);
+        if !location.is_empty() && !paths_in_use.iter().any(|path| overlaps(path, &location)) {
+            let prefix = Path::from(location);
+            let objects: Vec<_> = object_store
+                .list(Some(&prefix))
+                .map_ok(|object| object.location)
+                .try_collect()
+                .await
+                .context(metastore_error::ObjectStoreSnafu)?;
+            files.extend(objects);
+        }
+        for file in files {
+            match object_store.delete(&file).await {
+                Ok(()) | Err(object_store::Error::NotFound { .. }) => {}
+                Err(error) => return Err(error).context(metastore_error::ObjectStoreSnafu),
+            }
+        }
+        Ok(())
+    }
+
     #[instrument(name = "Metastore::update_properties_timestamps", level = "trace")]
     fn update_properties_timestamps(properties: &mut HashMap<String, String>) {
         let utc_now = Utc::now();
@@ -785,6 +1926,29 @@ impl InMemoryMetastore {
     }
 }
 
+/// Paths of the files a table refers to, relative to its volume: its location and the
+/// manifest lists of its snapshots, which may lie under the location of a cloned table
+fn table_paths(metadata: &TableMetadata) -> Vec<String> {
+    std::iter::once(strip_prefix(&metadata.location))
+        .chain(
+            metadata
+                .snapshots
+                .values()
+                .map(|snapshot| strip_prefix(snapshot.manifest_list())),
+        )
+        .filter(|path| !path.is_empty())
+        .collect()
+}
+
+/// Whether one of the paths is the other or lies under it
+fn overlaps(path: &str, other: &str) -> bool {
+    let under = |path: &str, parent: &str| {
+        path.strip_prefix(parent)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+    };
+    under(path, other) || under(other, path)
+}
+
 fn max_field_id(schema: &IcebergSchema) -> i32 {
     fn recurse(field: &StructField) -> i32 {
         let mut max_id = field.id;
@@ -1,6 +1,6 @@
 use crate::{
-    AwsAccessKeyCredentials, AwsCredentials, Database, Metastore, S3TablesVolume, S3Volume, Schema,
-    SchemaIdent, TableFormat, TableIdent, Volume, VolumeIdent, VolumeType,
+    AwsAccessKeyCredentials, AwsCredentials, Database, Metastore, Role, S3TablesVolume, S3Volume,
+    Schema, SchemaIdent, TableFormat, TableIdent, User, Volume, VolumeIdent, VolumeType,
 };
 use aws_config::meta::credentials::CredentialsProviderChain;
 use aws_config::{BehaviorVersion, Region};
@@ -30,6 +30,10 @@ pub struct MetastoreBootstrapConfig {
     schemas: Vec<SchemaEntry>,
     #[serde(default)]
     tables: Vec<TableEntry>,
+    #[serde(default)]
+    users: Vec<UserEntry>,
+    #[serde(default)]
+    roles: Vec<Role>,
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -64,6 +68,58 @@ struct TableEntry {
     metadata_location: String,
 }
 
+/// User entry, with either a plain `password` that is hashed on load or an
+/// already hashed `password_hash`, and optional public keys for key-pair authentication
+#[derive(Debug, Deserialize, Clone)]
+struct UserEntry {
+    name: String,
+    #[serde(default)]
+    password: Option<String>,
+    #[serde(default)]
+    password_hash: Option<String>,
+    #[serde(default)]
+    rsa_public_key: Option<String>,
+    #[serde(default)]
+    rsa_public_key_2: Option<String>,
+    #[serde(default)]
+    roles: Vec<String>,
+    #[serde(default)]
+    default_role: Option<String>,
+    #[serde(default)]
+    disabled: bool,
+    #[serde(default)]
+    comment: Option<String>,
+}
+
+impl UserEntry {
+    fn user(&self) -> User {
+        let user = User {
+            password_hash: self.password_hash.clone(),
+            rsa_public_key: self
+                .rsa_public_key
+                .as_deref()
+                .map(User::normalize_public_key),
+            rsa_public_key_2: self
+                .rsa_public_key_2
+                .as_deref()
+                .map(User::normalize_public_key),
+            roles: self
+                .roles
+                .iter()
+                .map(|role| Role::normalize_name(role))
+                .collect(),
+            default_role: self.default_role.as_deref().map(Role::normalize_name),
+            disabled: self.disabled,
+            comment: self.comment.clone(),
+            ..User::new(&self.name)
+        };
+        match &self.password {
+            Some(password) => user.with_password(password),
+            None => user,
+        }
+    }
+}
+
 impl TableEntry {
     fn table_ident(&self) -> TableIdent {
         TableIdent::new(&self.database, &self.schema, &self.table)
@@ -136,6 +192,8 @@ impl MetastoreBootstrapConfig {
                 schema: DEFAULT_SCHEMA_NAME.to_string(),
             }],
             tables: vec![],
+            users: vec![],
+            roles: vec![],
         }
     }
 }
@@ -198,6 +256,65 @@ impl MetastoreBootstrapConfig {
             self.apply_table(table, metastore.clone()).await?;
         }
 
+        for role in &self.roles {
+            Self::apply_role(role, metastore.clone()).await?;
+        }
+
+        for user in &self.users {
+            Self::apply_user(user, metastore.clone()).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn apply_role(role: &Role, metastore: Arc<dyn Metastore>) -> Result<(), ConfigError> {
+        // granting again expands ALL into the privileges of the object
+        let mut normalized = Role {
+            comment: role.comment.clone(),
+            ..Role::new(&role.name)
+        };
+        for grant in &role.grants {
+            normalized.grant(grant.privilege, grant.on.clone());
+        }
+        let role = normalized;
+        if metastore
+            .get_role(&role.name)
+            .await
+            .context(MetastoreSnafu)?
+            .is_some()
+        {
+            // grants changed with GRANT/REVOKE are not overwritten on restart
+            tracing::debug!(role = %role.name, "Role already exists, skipping config create");
+            return Ok(());
+        }
+        tracing::info!(role = %role.name, "Creating role from config");
+        metastore
+            .create_role(&role.name.clone(), role)
+            .await
+            .context(MetastoreSnafu)?;
+        Ok(())
+    }
+
+    async fn apply_user(
+        entry: &UserEntry,
+        metastore: Arc<dyn Metastore>,
+    ) -> Result<(), ConfigError> {
+        let user = entry.user();
+        if metastore
+            .get_user(&user.name)
+            .await
+            .context(MetastoreSnafu)?
+            .is_some()
+        {
+            // users changed with ALTER USER are not overwritten on restart
+            tracing::debug!(user = %user.name, "User already exists, skipping config create");
+            return Ok(());
+        }
+        tracing::info!(user = %user.name, "Creating user from config");
+        metastore
+            .create_user(&user.name.clone(), user)
+            .await
+            .context(MetastoreSnafu)?;
         Ok(())
     }
 
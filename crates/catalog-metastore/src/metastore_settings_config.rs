@@ -1,8 +1,23 @@
 use object_store::ClientOptions;
 
-#[derive(Debug, Clone, Default)]
+/// Days dropped databases, schemas and tables are kept for `UNDROP` by default
+pub const DEFAULT_DATA_RETENTION_TIME_IN_DAYS: u64 = 1;
+
+#[derive(Debug, Clone)]
 pub struct MetastoreSettingsConfig {
     pub object_store_client_options: ClientOptions,
+    /// Days a dropped object is kept before its files are purged, `0` purges it on the
+    /// next run of the purger and leaves nothing to `UNDROP`
+    pub data_retention_time_in_days: u64,
+}
+
+impl Default for MetastoreSettingsConfig {
+    fn default() -> Self {
+        Self {
+            object_store_client_options: ClientOptions::default(),
+            data_retention_time_in_days: DEFAULT_DATA_RETENTION_TIME_IN_DAYS,
+        }
+    }
 }
 
 impl MetastoreSettingsConfig {
@@ -12,6 +27,7 @@ impl MetastoreSettingsConfig {
             object_store_client_options: self
                 .object_store_client_options
                 .with_timeout(std::time::Duration::from_secs(timeout_secs)),
+            ..self
         }
     }
 
@@ -21,6 +37,15 @@ impl MetastoreSettingsConfig {
             object_store_client_options: self
                 .object_store_client_options
                 .with_connect_timeout(std::time::Duration::from_secs(timeout_secs)),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn with_data_retention_time_in_days(self, days: u64) -> Self {
+        Self {
+            data_retention_time_in_days: days,
+            ..self
         }
     }
 }
@@ -0,0 +1,93 @@
+use std::fmt::Display;
+
+use chrono::{Duration, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Database, DatabaseIdent, ExternalTable, FileFormat, RwObject, Schema, SchemaIdent, Stage,
+    Table, TableIdent,
+};
+
+/// Name of a dropped database, schema or table
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum DroppedObjectIdent {
+    Database(DatabaseIdent),
+    Schema(SchemaIdent),
+    Table(TableIdent),
+}
+
+impl DroppedObjectIdent {
+    /// Whether both name the same object, names are compared ignoring case
+    #[must_use]
+    pub fn matches(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Database(a), Self::Database(b)) => a.eq_ignore_ascii_case(b),
+            (Self::Schema(a), Self::Schema(b)) => a.normalized() == b.normalized(),
+            (Self::Table(a), Self::Table(b)) => a.normalized() == b.normalized(),
+            _ => false,
+        }
+    }
+}
+
+impl Display for DroppedObjectIdent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Database(database) => write!(f, "database {database}"),
+            Self::Schema(schema) => write!(f, "schema {schema}"),
+            Self::Table(table) => write!(f, "table {table}"),
+        }
+    }
+}
+
+/// Tombstone of a dropped database, schema or table.
+///
+/// It holds everything the drop removed from the metastore, including the tables,
+/// stages and file formats of a dropped schema or database, so `UNDROP` can restore
+/// the object as it was until `retained_until`. After that the purger deletes the files
+/// of its tables and the tombstone itself. The load history of the tables is not kept,
+/// so `COPY INTO` an undropped table loads its files again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DroppedObject {
+    pub ident: DroppedObjectIdent,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub database: Option<RwObject<Database>>,
+    #[serde(default)]
+    pub schemas: Vec<RwObject<Schema>>,
+    #[serde(default)]
+    pub tables: Vec<RwObject<Table>>,
+    #[serde(default)]
+    pub stages: Vec<RwObject<Stage>>,
+    #[serde(default)]
+    pub file_formats: Vec<RwObject<FileFormat>>,
+    #[serde(default)]
+    pub external_tables: Vec<RwObject<ExternalTable>>,
+    pub dropped_at: NaiveDateTime,
+    pub retained_until: NaiveDateTime,
+}
+
+impl DroppedObject {
+    /// Empty tombstone for an object dropped now and kept for `retention`
+    #[must_use]
+    pub fn new(ident: DroppedObjectIdent, retention: Duration) -> Self {
+        let now = Utc::now().naive_utc();
+        Self {
+            ident,
+            database: None,
+            schemas: Vec::new(),
+            tables: Vec::new(),
+            stages: Vec::new(),
+            file_formats: Vec::new(),
+            external_tables: Vec::new(),
+            dropped_at: now,
+            retained_until: now
+                .checked_add_signed(retention)
+                .unwrap_or(NaiveDateTime::MAX),
+        }
+    }
+
+    /// Whether the retention time of the object has passed, it can no longer be restored
+    #[must_use]
+    pub fn is_expired(&self) -> bool {
+        self.retained_until <= Utc::now().naive_utc()
+    }
+}
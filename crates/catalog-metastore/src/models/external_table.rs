@@ -0,0 +1,86 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+use super::{StageIdent, TableIdent};
+
+/// Column of an external table, computed from the `VALUE` of each row or from the
+/// `METADATA$FILENAME` of its file
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, utoipa::ToSchema)]
+pub struct ExternalTableColumn {
+    pub name: String,
+    /// SQL type of the column, such as `VARCHAR` or `NUMBER(38, 0)`
+    pub data_type: String,
+    /// SQL expression of the column, such as `VALUE:c1::VARCHAR`
+    pub expression: String,
+}
+
+/// Data file registered in the metadata of an external table
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, utoipa::ToSchema)]
+pub struct ExternalTableFile {
+    /// Path of the file relative to the stage
+    pub file: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub e_tag: Option<String>,
+    pub size: u64,
+    pub last_modified: NaiveDateTime,
+    pub registered_at: NaiveDateTime,
+    /// Arrow schema of the rows of the file as JSON, inferred when the file is registered
+    /// so that reading the table does not open every file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema: Option<String>,
+}
+
+impl ExternalTableFile {
+    /// Whether this is the registered version of the given file
+    #[must_use]
+    pub fn is_same(&self, file: &str, e_tag: Option<&str>, size: u64) -> bool {
+        self.file == file && self.e_tag.as_deref() == e_tag && self.size == size
+    }
+}
+
+/// External table: a read-only table over the files at a stage location, whose
+/// metadata keeps the list of files it reads
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, utoipa::ToSchema)]
+pub struct ExternalTable {
+    pub ident: TableIdent,
+    pub stage: StageIdent,
+    /// Path inside of the stage, without leading and trailing slashes
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub path: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pattern: Option<String>,
+    /// `FILE_FORMAT` options of the table, keyed by their uppercase names
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub file_format: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub columns: Vec<ExternalTableColumn>,
+    /// Names of the `PARTITION BY` columns, which only depend on the file path
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub partition_by: Vec<String>,
+    /// Whether the files are registered again periodically, in the background
+    pub auto_refresh: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<ExternalTableFile>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+impl ExternalTable {
+    #[must_use]
+    pub fn new(ident: TableIdent, stage: StageIdent, path: String) -> Self {
+        Self {
+            ident,
+            stage,
+            path,
+            pattern: None,
+            file_format: BTreeMap::new(),
+            columns: Vec::new(),
+            partition_by: Vec::new(),
+            auto_refresh: true,
+            files: Vec::new(),
+            comment: None,
+        }
+    }
+}
@@ -0,0 +1,69 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::SchemaIdent;
+
+#[derive(Validate, Debug, Clone, Serialize, Deserialize, PartialEq, Eq, utoipa::ToSchema)]
+/// A file format identifier, file formats live in a schema next to its tables
+pub struct FileFormatIdent {
+    #[validate(length(min = 1))]
+    /// The name of the file format
+    pub file_format: String,
+    #[validate(length(min = 1))]
+    /// The schema the file format belongs to
+    pub schema: String,
+    #[validate(length(min = 1))]
+    /// The database the file format belongs to
+    pub database: String,
+}
+
+impl FileFormatIdent {
+    #[must_use]
+    pub fn new(database: &str, schema: &str, file_format: &str) -> Self {
+        Self {
+            file_format: file_format.to_string(),
+            schema: schema.to_string(),
+            database: database.to_string(),
+        }
+    }
+}
+
+impl From<FileFormatIdent> for SchemaIdent {
+    fn from(ident: FileFormatIdent) -> Self {
+        Self {
+            database: ident.database,
+            schema: ident.schema,
+        }
+    }
+}
+
+impl Display for FileFormatIdent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.database, self.schema, self.file_format)
+    }
+}
+
+/// Named file format: a set of format options referenced by `FORMAT_NAME`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, utoipa::ToSchema)]
+pub struct FileFormat {
+    pub ident: FileFormatIdent,
+    /// Format options such as `TYPE` and `FIELD_DELIMITER`, keyed by their uppercase names
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub options: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+impl FileFormat {
+    #[must_use]
+    pub const fn new(ident: FileFormatIdent, options: BTreeMap<String, String>) -> Self {
+        Self {
+            ident,
+            options,
+            comment: None,
+        }
+    }
+}
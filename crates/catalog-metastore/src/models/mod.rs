@@ -4,13 +4,27 @@ use chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 
 pub mod database;
+pub mod dropped_object;
+pub mod external_table;
+pub mod file_format;
+pub mod role;
 pub mod schema;
+pub mod stage;
 pub mod table;
+pub mod table_load;
+pub mod user;
 pub mod volumes;
 
 pub use database::*;
+pub use dropped_object::*;
+pub use external_table::*;
+pub use file_format::*;
+pub use role::*;
 pub use schema::*;
+pub use stage::*;
 pub use table::*;
+pub use table_load::*;
+pub use user::*;
 
 pub use volumes::*;
 
@@ -0,0 +1,267 @@
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::{DatabaseIdent, SchemaIdent, StageIdent, TableIdent};
+
+/// A role identifier, role names are case-insensitive and kept in lowercase
+pub type RoleIdent = String;
+
+/// System role that is allowed everything, it is not kept in the metastore
+pub const ACCOUNTADMIN_ROLE: &str = "accountadmin";
+/// System role granted to every user
+pub const PUBLIC_ROLE: &str = "public";
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    Hash,
+    strum::Display,
+    strum::EnumString,
+    utoipa::ToSchema,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE", ascii_case_insensitive)]
+pub enum Privilege {
+    Usage,
+    Select,
+    Insert,
+    Update,
+    Delete,
+    Truncate,
+    CreateSchema,
+    CreateTable,
+    CreateView,
+    CreateStage,
+    /// Reading the files of a stage
+    Read,
+    /// Writing and removing the files of a stage
+    Write,
+    Ownership,
+    /// All privileges of the object except ownership, expanded when granted
+    All,
+}
+
+impl Privilege {
+    /// Privileges `ALL` stands for on the object
+    #[must_use]
+    pub const fn all_on(on: &GrantObject) -> &'static [Self] {
+        match on {
+            GrantObject::Database { .. } => &[Self::Usage, Self::CreateSchema],
+            GrantObject::Schema(_) => &[
+                Self::Usage,
+                Self::CreateTable,
+                Self::CreateView,
+                Self::CreateStage,
+            ],
+            GrantObject::Table(_) => &[
+                Self::Select,
+                Self::Insert,
+                Self::Update,
+                Self::Delete,
+                Self::Truncate,
+            ],
+            GrantObject::Stage(_) => &[Self::Read, Self::Write],
+        }
+    }
+}
+
+/// Object a privilege is granted on
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, utoipa::ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GrantObject {
+    Database { database: DatabaseIdent },
+    Schema(SchemaIdent),
+    Table(TableIdent),
+    Stage(StageIdent),
+}
+
+impl GrantObject {
+    #[must_use]
+    pub fn database(database: &str) -> Self {
+        Self::Database {
+            database: database.to_ascii_lowercase(),
+        }
+    }
+
+    #[must_use]
+    pub fn schema(database: &str, schema: &str) -> Self {
+        Self::Schema(SchemaIdent::new(
+            database.to_ascii_lowercase(),
+            schema.to_ascii_lowercase(),
+        ))
+    }
+
+    #[must_use]
+    pub fn table(database: &str, schema: &str, table: &str) -> Self {
+        Self::Table(TableIdent::new(
+            &database.to_ascii_lowercase(),
+            &schema.to_ascii_lowercase(),
+            &table.to_ascii_lowercase(),
+        ))
+    }
+
+    #[must_use]
+    pub fn stage(database: &str, schema: &str, stage: &str) -> Self {
+        Self::Stage(StageIdent::new(
+            &database.to_ascii_lowercase(),
+            &schema.to_ascii_lowercase(),
+            &stage.to_ascii_lowercase(),
+        ))
+    }
+
+    /// Returns true if the object is this object or is contained in it
+    #[must_use]
+    pub fn contains(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Database { database }, Self::Schema(SchemaIdent { database: db, .. }))
+            | (Self::Database { database }, Self::Table(TableIdent { database: db, .. }))
+            | (Self::Database { database }, Self::Stage(StageIdent { database: db, .. })) => {
+                database == db
+            }
+            (Self::Schema(schema), Self::Table(table)) => {
+                schema.database == table.database && schema.schema == table.schema
+            }
+            (Self::Schema(schema), Self::Stage(stage)) => {
+                schema.database == stage.database && schema.schema == stage.schema
+            }
+            _ => self == other,
+        }
+    }
+
+    #[must_use]
+    pub const fn object_type(&self) -> &'static str {
+        match self {
+            Self::Database { .. } => "database",
+            Self::Schema(_) => "schema",
+            Self::Table(_) => "table",
+            Self::Stage(_) => "stage",
+        }
+    }
+}
+
+impl std::fmt::Display for GrantObject {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Database { database } => write!(f, "{database}"),
+            Self::Schema(schema) => write!(f, "{}.{}", schema.database, schema.schema),
+            Self::Table(table) => {
+                write!(f, "{}.{}.{}", table.database, table.schema, table.table)
+            }
+            Self::Stage(stage) => {
+                write!(f, "{}.{}.{}", stage.database, stage.schema, stage.stage)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, utoipa::ToSchema)]
+pub struct Grant {
+    pub privilege: Privilege,
+    pub on: GrantObject,
+}
+
+#[derive(Validate, Debug, Clone, Serialize, Deserialize, PartialEq, Eq, utoipa::ToSchema)]
+pub struct Role {
+    #[validate(length(min = 1))]
+    pub name: RoleIdent,
+    #[serde(default)]
+    pub grants: Vec<Grant>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+impl Role {
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: Self::normalize_name(name),
+            grants: vec![],
+            comment: None,
+        }
+    }
+
+    #[must_use]
+    pub fn normalize_name(name: &str) -> RoleIdent {
+        name.to_ascii_lowercase()
+    }
+
+    /// Checks whether the role has the privilege on the object, ownership implies all
+    /// privileges of the object
+    #[must_use]
+    pub fn allows(&self, privilege: Privilege, on: &GrantObject) -> bool {
+        self.grants.iter().any(|grant| {
+            &grant.on == on
+                && (grant.privilege == privilege || grant.privilege == Privilege::Ownership)
+        })
+    }
+
+    /// Adds the grant, granting the same privilege twice has no effect
+    pub fn grant(&mut self, privilege: Privilege, on: GrantObject) {
+        if privilege == Privilege::All {
+            for privilege in Privilege::all_on(&on) {
+                self.grant(*privilege, on.clone());
+            }
+            return;
+        }
+        let grant = Grant { privilege, on };
+        if !self.grants.contains(&grant) {
+            self.grants.push(grant);
+        }
+    }
+
+    pub fn revoke(&mut self, privilege: Privilege, on: &GrantObject) {
+        self.grants.retain(|grant| {
+            &grant.on != on
+                || match privilege {
+                    Privilege::All => grant.privilege == Privilege::Ownership,
+                    privilege => grant.privilege != privilege,
+                }
+        });
+    }
+
+    /// Revokes every privilege on the object and on the objects it contains
+    pub fn revoke_all_on(&mut self, on: &GrantObject) {
+        self.grants.retain(|grant| !on.contains(&grant.on));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_role_privileges() {
+        let table = GrantObject::table("DB", "Public", "T");
+        let mut role = Role::new("Analyst");
+        assert_eq!(role.name, "analyst");
+        role.grant(Privilege::Select, table.clone());
+        assert!(role.allows(Privilege::Select, &GrantObject::table("db", "public", "t")));
+        assert!(!role.allows(Privilege::Insert, &table));
+
+        role.grant(Privilege::All, table.clone());
+        assert!(role.allows(Privilege::Insert, &table));
+        assert!(!role.allows(Privilege::Ownership, &table));
+
+        role.revoke(Privilege::Select, &table);
+        assert!(!role.allows(Privilege::Select, &table));
+        assert!(role.allows(Privilege::Delete, &table));
+
+        role.revoke(Privilege::All, &table);
+        assert!(role.grants.is_empty());
+
+        role.grant(Privilege::Ownership, table.clone());
+        role.revoke_all_on(&GrantObject::schema("db", "public"));
+        assert!(!role.allows(Privilege::Delete, &table));
+
+        let stage = GrantObject::stage("db", "public", "S");
+        role.grant(Privilege::All, stage.clone());
+        assert!(role.allows(Privilege::Write, &GrantObject::stage("db", "public", "s")));
+        role.revoke_all_on(&GrantObject::database("db"));
+        assert!(!role.allows(Privilege::Read, &stage));
+    }
+}
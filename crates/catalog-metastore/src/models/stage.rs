@@ -0,0 +1,116 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+use validator::Validate;
+
+use super::{SchemaIdent, VolumeIdent};
+
+#[derive(Validate, Debug, Clone, Serialize, Deserialize, PartialEq, Eq, utoipa::ToSchema)]
+/// A stage identifier, stages live in a schema next to its tables
+pub struct StageIdent {
+    #[validate(length(min = 1))]
+    /// The name of the stage
+    pub stage: String,
+    #[validate(length(min = 1))]
+    /// The schema the stage belongs to
+    pub schema: String,
+    #[validate(length(min = 1))]
+    /// The database the stage belongs to
+    pub database: String,
+}
+
+impl StageIdent {
+    #[must_use]
+    pub fn new(database: &str, schema: &str, stage: &str) -> Self {
+        Self {
+            stage: stage.to_string(),
+            schema: schema.to_string(),
+            database: database.to_string(),
+        }
+    }
+
+    #[must_use]
+    pub fn normalized(&self) -> Self {
+        Self {
+            stage: self.stage.to_ascii_lowercase(),
+            schema: self.schema.to_ascii_lowercase(),
+            database: self.database.to_ascii_lowercase(),
+        }
+    }
+}
+
+impl From<StageIdent> for SchemaIdent {
+    fn from(ident: StageIdent) -> Self {
+        Self {
+            database: ident.database,
+            schema: ident.schema,
+        }
+    }
+}
+
+impl Display for StageIdent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.database, self.schema, self.stage)
+    }
+}
+
+/// Where the files of a stage are kept
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, utoipa::ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StageLocation {
+    /// Files under `path` of a metastore volume, using the volume's storage and credentials
+    Volume { volume: VolumeIdent, path: String },
+    /// Internal stage: files uploaded with `PUT`, kept under `path` of the volume of the
+    /// stage's database and removed together with the stage
+    Internal { volume: VolumeIdent, path: String },
+    /// Files under an external `s3://` or `file://` url
+    Url {
+        url: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        endpoint: Option<String>,
+        /// `CREDENTIALS` of the stage, such as `AWS_KEY_ID` and `AWS_SECRET_KEY`
+        #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+        credentials: BTreeMap<String, String>,
+    },
+}
+
+impl Display for StageLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Volume { volume, path } if path.is_empty() => write!(f, "{volume}"),
+            Self::Volume { volume, path } | Self::Internal { volume, path } => {
+                write!(f, "{volume}/{path}")
+            }
+            Self::Url { url, .. } => write!(f, "{url}"),
+        }
+    }
+}
+
+/// Named stage: a location of data files together with the defaults used to load them
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, utoipa::ToSchema)]
+pub struct Stage {
+    pub ident: StageIdent,
+    pub location: StageLocation,
+    /// `FILE_FORMAT` options of the stage, keyed by their uppercase names
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub file_format: BTreeMap<String, String>,
+    /// `COPY_OPTIONS` of the stage, keyed by their uppercase names
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub copy_options: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+impl Stage {
+    #[must_use]
+    pub fn new(ident: StageIdent, location: StageLocation) -> Self {
+        Self {
+            ident,
+            location,
+            file_format: BTreeMap::new(),
+            copy_options: BTreeMap::new(),
+            comment: None,
+        }
+    }
+}
@@ -72,12 +72,12 @@ impl Display for TableIdent {
 #[serde(rename_all = "kebab-case")]
 pub enum TableFormat {
     /*
-    Avro,
-    Orc,
     Delta,
     Json,
     Csv,*/
     Parquet,
+    Avro,
+    Orc,
     Iceberg,
 }
 
@@ -85,6 +85,8 @@ impl Display for TableFormat {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let str = match self {
             Self::Parquet => "parquet".to_string(),
+            Self::Avro => "avro".to_string(),
+            Self::Orc => "orc".to_string(),
             Self::Iceberg => "iceberg".to_string(),
         };
         write!(f, "{str}")
@@ -95,6 +97,8 @@ impl From<String> for TableFormat {
     fn from(value: String) -> Self {
         match value.to_lowercase().as_str() {
             "parquet" => Self::Parquet,
+            "avro" => Self::Avro,
+            "orc" => Self::Orc,
             _ => Self::Iceberg,
         }
     }
@@ -0,0 +1,53 @@
+use chrono::{Duration, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+use super::TableIdent;
+
+/// Status of a file loaded with `COPY INTO` for which no rows were loaded
+pub const LOAD_FAILED: &str = "LOAD_FAILED";
+
+/// Days the load metadata of a file is kept, as with Snowflake a file loaded longer
+/// ago than that is no longer skipped by `COPY INTO`
+pub const TABLE_LOAD_RETENTION_DAYS: i64 = 64;
+
+/// Load metadata of a file copied into a table with `COPY INTO`, used to skip files
+/// that are already loaded and reported by `LOAD_HISTORY` and `COPY_HISTORY`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, utoipa::ToSchema)]
+pub struct TableLoad {
+    pub table: TableIdent,
+    /// Full path of the file, including the stage or external location it was loaded from
+    pub file: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub e_tag: Option<String>,
+    pub size: u64,
+    /// `LOADED`, `PARTIALLY_LOADED` or `LOAD_FAILED`
+    pub status: String,
+    pub rows_parsed: u64,
+    pub rows_loaded: u64,
+    pub error_limit: u64,
+    pub errors_seen: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_error: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_error_line: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_error_column_name: Option<String>,
+    pub loaded_at: NaiveDateTime,
+}
+
+impl TableLoad {
+    /// Whether this load already copied the rows of the given version of the file
+    #[must_use]
+    pub fn is_loaded(&self, file: &str, e_tag: Option<&str>, size: u64) -> bool {
+        self.status != LOAD_FAILED
+            && self.file == file
+            && self.e_tag.as_deref() == e_tag
+            && self.size == size
+    }
+
+    /// Whether the load is older than the load metadata retention and is forgotten
+    #[must_use]
+    pub fn is_expired(&self, now: NaiveDateTime) -> bool {
+        now.signed_duration_since(self.loaded_at) >= Duration::days(TABLE_LOAD_RETENTION_DAYS)
+    }
+}
@@ -0,0 +1,191 @@
+use super::{PUBLIC_ROLE, RoleIdent};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use uuid::Uuid;
+use validator::Validate;
+
+/// A user identifier, user names are case-insensitive and kept in lowercase
+pub type UserIdent = String;
+
+const PASSWORD_HASH_SCHEME: &str = "pbkdf2-sha256";
+const PASSWORD_HASH_ITERATIONS: u32 = 100_000;
+const PASSWORD_HASH_LEN: usize = 32;
+
+#[derive(Validate, Debug, Clone, Serialize, Deserialize, PartialEq, Eq, utoipa::ToSchema)]
+pub struct User {
+    #[validate(length(min = 1))]
+    pub name: UserIdent,
+    /// Salted password hash in the `pbkdf2-sha256$<iterations>$<salt>$<hash>` format,
+    /// users without a password can't log in with one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub password_hash: Option<String>,
+    /// Base64 encoded DER public keys for key-pair authentication, a second key
+    /// allows rotating keys without downtime
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rsa_public_key: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rsa_public_key_2: Option<String>,
+    /// Roles granted to the user, besides the `PUBLIC` role every user has
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<RoleIdent>,
+    /// Role the sessions of the user start with, `PUBLIC` when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_role: Option<RoleIdent>,
+    #[serde(default)]
+    pub disabled: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+impl User {
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: Self::normalize_name(name),
+            password_hash: None,
+            rsa_public_key: None,
+            rsa_public_key_2: None,
+            roles: vec![],
+            default_role: None,
+            disabled: false,
+            comment: None,
+        }
+    }
+
+    #[must_use]
+    pub fn normalize_name(name: &str) -> UserIdent {
+        name.to_ascii_lowercase()
+    }
+
+    #[must_use]
+    pub fn with_password(self, password: &str) -> Self {
+        Self {
+            password_hash: Some(hash_password(password)),
+            ..self
+        }
+    }
+
+    /// Strips the PEM armor and line breaks, keys are kept as a single base64 line
+    #[must_use]
+    pub fn normalize_public_key(public_key: &str) -> String {
+        public_key
+            .lines()
+            .filter(|line| !line.trim_start().starts_with("-----"))
+            .flat_map(str::split_whitespace)
+            .collect()
+    }
+
+    /// Returns true if the role is granted to the user
+    #[must_use]
+    pub fn has_role(&self, role: &str) -> bool {
+        role == PUBLIC_ROLE || self.roles.iter().any(|granted| granted == role)
+    }
+
+    /// Public keys the user can authenticate with, none for disabled users
+    #[must_use]
+    pub fn public_keys(&self) -> Vec<&str> {
+        if self.disabled {
+            return vec![];
+        }
+        [&self.rsa_public_key, &self.rsa_public_key_2]
+            .into_iter()
+            .filter_map(Option::as_deref)
+            .collect()
+    }
+
+    /// Checks the password against the stored hash, disabled users never match
+    #[must_use]
+    pub fn verify_password(&self, password: &str) -> bool {
+        !self.disabled
+            && self
+                .password_hash
+                .as_deref()
+                .is_some_and(|hash| verify_password(password, hash))
+    }
+}
+
+#[must_use]
+pub fn hash_password(password: &str) -> String {
+    let salt = Uuid::new_v4();
+    let hash = pbkdf2_sha256(
+        password.as_bytes(),
+        salt.as_bytes(),
+        PASSWORD_HASH_ITERATIONS,
+    );
+    format!(
+        "{PASSWORD_HASH_SCHEME}${PASSWORD_HASH_ITERATIONS}${}${}",
+        hex::encode(salt.as_bytes()),
+        hex::encode(hash)
+    )
+}
+
+#[must_use]
+pub fn verify_password(password: &str, password_hash: &str) -> bool {
+    let parts = password_hash.split('$').collect::<Vec<_>>();
+    let [scheme, iterations, salt, hash] = parts.as_slice() else {
+        return false;
+    };
+    if *scheme != PASSWORD_HASH_SCHEME {
+        return false;
+    }
+    let (Ok(iterations), Ok(salt), Ok(hash)) = (
+        iterations.parse::<u32>(),
+        hex::decode(salt),
+        hex::decode(hash),
+    ) else {
+        return false;
+    };
+    let computed = pbkdf2_sha256(password.as_bytes(), &salt, iterations);
+    // constant time comparison
+    computed.len() == hash.len()
+        && computed
+            .iter()
+            .zip(hash.iter())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+fn pbkdf2_sha256(password: &[u8], salt: &[u8], iterations: u32) -> [u8; PASSWORD_HASH_LEN] {
+    pbkdf2::pbkdf2_hmac_array::<Sha256, PASSWORD_HASH_LEN>(password, salt, iterations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify_password() {
+        let user = User::new("Alice").with_password("secret");
+        assert_eq!(user.name, "alice");
+        assert!(user.verify_password("secret"));
+        assert!(!user.verify_password("Secret"));
+        assert!(
+            !User {
+                disabled: true,
+                ..user
+            }
+            .verify_password("secret")
+        );
+        assert!(!User::new("bob").verify_password(""));
+    }
+
+    #[test]
+    fn test_pbkdf2_sha256() {
+        // RFC 7914 section 11 test vector
+        assert_eq!(
+            hex::encode(pbkdf2_sha256(b"passwd", b"salt", 1)),
+            "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc"
+        );
+    }
+
+    #[test]
+    fn test_normalize_public_key() {
+        assert_eq!(
+            User::normalize_public_key(
+                "-----BEGIN PUBLIC KEY-----\nMIIBIjAN\nBgkqhkiG\n-----END PUBLIC KEY-----\n"
+            ),
+            "MIIBIjANBgkqhkiG"
+        );
+        assert_eq!(User::normalize_public_key("MIIB IjAN"), "MIIBIjAN");
+    }
+}
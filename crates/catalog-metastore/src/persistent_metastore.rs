@@ -0,0 +1,675 @@
+use crate::error::{self as metastore_error, Result};
+use crate::metastore::{InMemoryMetastore, Metastore, MetastoreSnapshot};
+use crate::metastore_settings_config::MetastoreSettingsConfig;
+use crate::models::{
+    RwObject,
+    database::{Database, DatabaseIdent},
+    dropped_object::{DroppedObject, DroppedObjectIdent},
+    external_table::ExternalTable,
+    file_format::{FileFormat, FileFormatIdent},
+    role::{Role, RoleIdent},
+    schema::{Schema, SchemaIdent},
+    stage::{Stage, StageIdent},
+    table::{Table, TableCreateRequest, TableIdent, TableUpdate},
+    table_load::TableLoad,
+    user::{User, UserIdent},
+    volumes::{Volume, VolumeIdent},
+};
+use async_trait::async_trait;
+use bytes::Bytes;
+use object_store::{ObjectStore, PutPayload, path::Path};
+use snafu::ResultExt;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tracing::instrument;
+
+/// Name of the file the metastore state is kept in, relative to the root of its store.
+pub const METASTORE_STATE_FILE: &str = "metastore.json";
+
+/// Durable metastore: serves every request from an [`InMemoryMetastore`] and writes
+/// the whole state as a JSON document to an object store after each change, so volumes,
+/// databases, schemas and table registrations survive a restart.
+///
+/// The state document is rewritten with a single `put`, which is atomic for both the
+/// local filesystem and S3, so a crash never leaves a partially written state behind.
+/// Note that the metadata of tables created on `memory` volumes is still lost on
+/// restart even though their registrations are kept.
+#[derive(Debug)]
+pub struct PersistentMetastore {
+    inner: InMemoryMetastore,
+    object_store: Arc<dyn ObjectStore>,
+    path: Path,
+    // Serializes changes so that state documents are written in the order the changes happen
+    write_lock: Mutex<()>,
+}
+
+impl PersistentMetastore {
+    /// Opens the metastore kept at `path` of the given object store, starting empty
+    /// if there is no state there yet.
+    pub async fn new(object_store: Arc<dyn ObjectStore>, path: Path) -> Result<Self> {
+        let metastore = Self {
+            inner: InMemoryMetastore::new(),
+            object_store,
+            path,
+            write_lock: Mutex::new(()),
+        };
+        metastore.load().await?;
+        Ok(metastore)
+    }
+
+    /// Opens the metastore kept in the root of a volume.
+    pub async fn from_volume(volume: &Volume) -> Result<Self> {
+        Self::new(volume.get_object_store()?, Path::from(METASTORE_STATE_FILE)).await
+    }
+
+    #[must_use]
+    pub fn with_settings_config(self, settings: MetastoreSettingsConfig) -> Self {
+        Self {
+            inner: self.inner.with_settings_config(settings),
+            ..self
+        }
+    }
+
+    #[instrument(name = "PersistentMetastore::load", level = "debug", skip(self), err)]
+    async fn load(&self) -> Result<()> {
+        let bytes = match self.object_store.get(&self.path).await {
+            Ok(result) => result
+                .bytes()
+                .await
+                .context(metastore_error::ObjectStoreSnafu)?,
+            Err(object_store::Error::NotFound { .. }) => return Ok(()),
+            Err(error) => return Err(error).context(metastore_error::ObjectStoreSnafu),
+        };
+        let snapshot: MetastoreSnapshot =
+            serde_json::from_slice(&bytes).context(metastore_error::LoadMetastoreStateSnafu {
+                path: self.path.to_string(),
+            })?;
+        self.inner.restore(snapshot).await;
+        Ok(())
+    }
+
+    #[instrument(
+        name = "PersistentMetastore::persist",
+        level = "trace",
+        skip(self),
+        err
+    )]
+    async fn persist(&self) -> Result<()> {
+        let snapshot = self.inner.snapshot().await;
+        let bytes =
+            serde_json::to_vec(&snapshot).context(metastore_error::SerializeMetastoreStateSnafu)?;
+        self.object_store
+            .put(&self.path, PutPayload::from_bytes(Bytes::from(bytes)))
+            .await
+            .context(metastore_error::ObjectStoreSnafu)?;
+        Ok(())
+    }
+
+    /// Runs a change against the in-memory state and persists the resulting state.
+    /// If the state cannot be persisted the change is rolled back, so the in-memory
+    /// state never diverges from the persisted one.
+    async fn mutate<T>(&self, change: impl Future<Output = Result<T>> + Send) -> Result<T> {
+        let _guard = self.write_lock.lock().await;
+        let before = self.inner.snapshot().await;
+        let result = change.await?;
+        if let Err(error) = self.persist().await {
+            self.inner.restore(before).await;
+            return Err(error);
+        }
+        Ok(result)
+    }
+}
+
+#[async_trait]
+impl Metastore for PersistentMetastore {
+    async fn list_volumes(&self) -> Result<Vec<RwObject<Volume>>> {
+        self.inner.list_volumes().await
+    }
+
+    async fn create_volume(&self, name: &VolumeIdent, volume: Volume) -> Result<RwObject<Volume>> {
+        self.mutate(self.inner.create_volume(name, volume)).await
+    }
+
+    async fn get_volume(&self, name: &VolumeIdent) -> Result<Option<RwObject<Volume>>> {
+        self.inner.get_volume(name).await
+    }
+
+    async fn update_volume(&self, name: &VolumeIdent, volume: Volume) -> Result<RwObject<Volume>> {
+        self.mutate(self.inner.update_volume(name, volume)).await
+    }
+
+    async fn delete_volume(&self, name: &VolumeIdent, cascade: bool) -> Result<()> {
+        self.mutate(self.inner.delete_volume(name, cascade)).await
+    }
+
+    async fn volume_object_store(
+        &self,
+        name: &VolumeIdent,
+    ) -> Result<Option<Arc<dyn ObjectStore>>> {
+        self.inner.volume_object_store(name).await
+    }
+
+    async fn list_databases(&self) -> Result<Vec<RwObject<Database>>> {
+        self.inner.list_databases().await
+    }
+
+    async fn create_database(
+        &self,
+        name: &DatabaseIdent,
+        database: Database,
+    ) -> Result<RwObject<Database>> {
+        self.mutate(self.inner.create_database(name, database))
+            .await
+    }
+
+    async fn get_database(&self, name: &DatabaseIdent) -> Result<Option<RwObject<Database>>> {
+        self.inner.get_database(name).await
+    }
+
+    async fn update_database(
+        &self,
+        name: &DatabaseIdent,
+        database: Database,
+    ) -> Result<RwObject<Database>> {
+        self.mutate(self.inner.update_database(name, database))
+            .await
+    }
+
+    async fn delete_database(&self, name: &DatabaseIdent, cascade: bool) -> Result<()> {
+        self.mutate(self.inner.delete_database(name, cascade)).await
+    }
+
+    async fn list_schemas(&self, database: &DatabaseIdent) -> Result<Vec<RwObject<Schema>>> {
+        self.inner.list_schemas(database).await
+    }
+
+    async fn create_schema(&self, ident: &SchemaIdent, schema: Schema) -> Result<RwObject<Schema>> {
+        self.mutate(self.inner.create_schema(ident, schema)).await
+    }
+
+    async fn get_schema(&self, ident: &SchemaIdent) -> Result<Option<RwObject<Schema>>> {
+        self.inner.get_schema(ident).await
+    }
+
+    async fn update_schema(&self, ident: &SchemaIdent, schema: Schema) -> Result<RwObject<Schema>> {
+        self.mutate(self.inner.update_schema(ident, schema)).await
+    }
+
+    async fn delete_schema(&self, ident: &SchemaIdent, cascade: bool) -> Result<()> {
+        self.mutate(self.inner.delete_schema(ident, cascade)).await
+    }
+
+    async fn list_tables(&self, schema: &SchemaIdent) -> Result<Vec<RwObject<Table>>> {
+        self.inner.list_tables(schema).await
+    }
+
+    async fn create_table(
+        &self,
+        ident: &TableIdent,
+        table: TableCreateRequest,
+    ) -> Result<RwObject<Table>> {
+        self.mutate(self.inner.create_table(ident, table)).await
+    }
+
+    async fn register_table(&self, ident: &TableIdent, table: Table) -> Result<RwObject<Table>> {
+        self.mutate(self.inner.register_table(ident, table)).await
+    }
+
+    async fn get_table(&self, ident: &TableIdent) -> Result<Option<RwObject<Table>>> {
+        self.inner.get_table(ident).await
+    }
+
+    async fn update_table(
+        &self,
+        ident: &TableIdent,
+        update: TableUpdate,
+    ) -> Result<RwObject<Table>> {
+        self.mutate(self.inner.update_table(ident, update)).await
+    }
+
+    async fn update_tables(
+        &self,
+        updates: Vec<(TableIdent, TableUpdate)>,
+    ) -> Result<Vec<RwObject<Table>>> {
+        self.mutate(self.inner.update_tables(updates)).await
+    }
+
+    async fn delete_table(&self, ident: &TableIdent, cascade: bool) -> Result<()> {
+        self.mutate(self.inner.delete_table(ident, cascade)).await
+    }
+
+    async fn rename_table(
+        &self,
+        ident: &TableIdent,
+        new_ident: &TableIdent,
+    ) -> Result<RwObject<Table>> {
+        self.mutate(self.inner.rename_table(ident, new_ident)).await
+    }
+
+    async fn swap_tables(&self, ident: &TableIdent, other: &TableIdent) -> Result<()> {
+        self.mutate(self.inner.swap_tables(ident, other)).await
+    }
+
+    async fn table_object_store(&self, ident: &TableIdent) -> Result<Option<Arc<dyn ObjectStore>>> {
+        self.inner.table_object_store(ident).await
+    }
+
+    async fn table_exists(&self, ident: &TableIdent) -> Result<bool> {
+        self.inner.table_exists(ident).await
+    }
+
+    async fn url_for_table(&self, ident: &TableIdent) -> Result<String> {
+        self.inner.url_for_table(ident).await
+    }
+
+    async fn volume_for_table(&self, ident: &TableIdent) -> Result<Option<RwObject<Volume>>> {
+        self.inner.volume_for_table(ident).await
+    }
+
+    async fn list_users(&self) -> Result<Vec<RwObject<User>>> {
+        self.inner.list_users().await
+    }
+
+    async fn has_users(&self) -> Result<bool> {
+        self.inner.has_users().await
+    }
+
+    async fn create_user(&self, name: &UserIdent, user: User) -> Result<RwObject<User>> {
+        self.mutate(self.inner.create_user(name, user)).await
+    }
+
+    async fn get_user(&self, name: &UserIdent) -> Result<Option<RwObject<User>>> {
+        self.inner.get_user(name).await
+    }
+
+    async fn update_user(&self, name: &UserIdent, user: User) -> Result<RwObject<User>> {
+        self.mutate(self.inner.update_user(name, user)).await
+    }
+
+    async fn delete_user(&self, name: &UserIdent) -> Result<()> {
+        self.mutate(self.inner.delete_user(name)).await
+    }
+
+    async fn list_roles(&self) -> Result<Vec<RwObject<Role>>> {
+        self.inner.list_roles().await
+    }
+
+    async fn create_role(&self, name: &RoleIdent, role: Role) -> Result<RwObject<Role>> {
+        self.mutate(self.inner.create_role(name, role)).await
+    }
+
+    async fn get_role(&self, name: &RoleIdent) -> Result<Option<RwObject<Role>>> {
+        self.inner.get_role(name).await
+    }
+
+    async fn update_role(&self, name: &RoleIdent, role: Role) -> Result<RwObject<Role>> {
+        self.mutate(self.inner.update_role(name, role)).await
+    }
+
+    async fn delete_role(&self, name: &RoleIdent) -> Result<()> {
+        self.mutate(self.inner.delete_role(name)).await
+    }
+
+    async fn list_stages(&self, schema: &SchemaIdent) -> Result<Vec<RwObject<Stage>>> {
+        self.inner.list_stages(schema).await
+    }
+
+    async fn create_stage(&self, ident: &StageIdent, stage: Stage) -> Result<RwObject<Stage>> {
+        self.mutate(self.inner.create_stage(ident, stage)).await
+    }
+
+    async fn get_stage(&self, ident: &StageIdent) -> Result<Option<RwObject<Stage>>> {
+        self.inner.get_stage(ident).await
+    }
+
+    async fn update_stage(&self, ident: &StageIdent, stage: Stage) -> Result<RwObject<Stage>> {
+        self.mutate(self.inner.update_stage(ident, stage)).await
+    }
+
+    async fn delete_stage(&self, ident: &StageIdent) -> Result<()> {
+        self.mutate(self.inner.delete_stage(ident)).await
+    }
+
+    async fn list_file_formats(&self, schema: &SchemaIdent) -> Result<Vec<RwObject<FileFormat>>> {
+        self.inner.list_file_formats(schema).await
+    }
+
+    async fn create_file_format(
+        &self,
+        ident: &FileFormatIdent,
+        file_format: FileFormat,
+    ) -> Result<RwObject<FileFormat>> {
+        self.mutate(self.inner.create_file_format(ident, file_format))
+            .await
+    }
+
+    async fn get_file_format(
+        &self,
+        ident: &FileFormatIdent,
+    ) -> Result<Option<RwObject<FileFormat>>> {
+        self.inner.get_file_format(ident).await
+    }
+
+    async fn update_file_format(
+        &self,
+        ident: &FileFormatIdent,
+        file_format: FileFormat,
+    ) -> Result<RwObject<FileFormat>> {
+        self.mutate(self.inner.update_file_format(ident, file_format))
+            .await
+    }
+
+    async fn delete_file_format(&self, ident: &FileFormatIdent) -> Result<()> {
+        self.mutate(self.inner.delete_file_format(ident)).await
+    }
+
+    async fn list_external_tables(
+        &self,
+        schema: &SchemaIdent,
+    ) -> Result<Vec<RwObject<ExternalTable>>> {
+        self.inner.list_external_tables(schema).await
+    }
+
+    async fn create_external_table(
+        &self,
+        ident: &TableIdent,
+        table: ExternalTable,
+    ) -> Result<RwObject<ExternalTable>> {
+        self.mutate(self.inner.create_external_table(ident, table))
+            .await
+    }
+
+    async fn get_external_table(
+        &self,
+        ident: &TableIdent,
+    ) -> Result<Option<RwObject<ExternalTable>>> {
+        self.inner.get_external_table(ident).await
+    }
+
+    async fn update_external_table(
+        &self,
+        ident: &TableIdent,
+        table: ExternalTable,
+    ) -> Result<RwObject<ExternalTable>> {
+        self.mutate(self.inner.update_external_table(ident, table))
+            .await
+    }
+
+    async fn delete_external_table(&self, ident: &TableIdent) -> Result<()> {
+        self.mutate(self.inner.delete_external_table(ident)).await
+    }
+
+    async fn list_table_loads(&self, ident: &TableIdent) -> Result<Vec<TableLoad>> {
+        self.inner.list_table_loads(ident).await
+    }
+
+    async fn list_database_loads(&self, database: &DatabaseIdent) -> Result<Vec<TableLoad>> {
+        self.inner.list_database_loads(database).await
+    }
+
+    async fn record_table_loads(&self, ident: &TableIdent, loads: Vec<TableLoad>) -> Result<()> {
+        self.mutate(self.inner.record_table_loads(ident, loads))
+            .await
+    }
+
+    async fn list_dropped_objects(&self) -> Result<Vec<DroppedObject>> {
+        self.inner.list_dropped_objects().await
+    }
+
+    async fn undrop(&self, ident: &DroppedObjectIdent) -> Result<()> {
+        self.mutate(self.inner.undrop(ident)).await
+    }
+
+    async fn purge_dropped_objects(&self) -> Result<usize> {
+        self.mutate(self.inner.purge_dropped_objects()).await
+    }
+
+    fn settings_config(&self) -> Option<MetastoreSettingsConfig> {
+        self.inner.settings_config()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::volumes::VolumeType;
+    use object_store::local::LocalFileSystem;
+    use object_store::memory::InMemory;
+
+    #[tokio::test]
+    async fn test_state_survives_reopen() {
+        let store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let path = Path::from(METASTORE_STATE_FILE);
+        let volume = Volume::new("vol".to_string(), VolumeType::Memory);
+        let schema_ident = SchemaIdent::new("db".to_string(), "public".to_string());
+
+        let metastore = PersistentMetastore::new(store.clone(), path.clone())
+            .await
+            .expect("Failed to open metastore");
+        metastore
+            .create_volume(&"vol".to_string(), volume.clone())
+            .await
+            .expect("Failed to create volume");
+        metastore
+            .create_database(
+                &"db".to_string(),
+                Database {
+                    ident: "db".to_string(),
+                    properties: None,
+                    volume: "vol".to_string(),
+                    should_refresh: false,
+                },
+            )
+            .await
+            .expect("Failed to create database");
+        metastore
+            .create_schema(
+                &schema_ident,
+                Schema {
+                    ident: schema_ident.clone(),
+                    properties: None,
+                },
+            )
+            .await
+            .expect("Failed to create schema");
+
+        let reopened = PersistentMetastore::new(store, path)
+            .await
+            .expect("Failed to reopen metastore");
+        let volume_after = reopened
+            .get_volume(&"vol".to_string())
+            .await
+            .expect("Failed to get volume")
+            .expect("Volume not persisted");
+        assert_eq!(volume_after.data, volume);
+        assert!(
+            reopened
+                .get_database(&"db".to_string())
+                .await
+                .expect("Failed to get database")
+                .is_some()
+        );
+        assert!(
+            reopened
+                .get_schema(&schema_ident)
+                .await
+                .expect("Failed to get schema")
+                .is_some()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_failed_persist_rolls_back_change() {
+        let dir = std::env::temp_dir().join(format!("metastore_rollback_{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).expect("Failed to create directory");
+        let store: Arc<dyn ObjectStore> =
+            Arc::new(LocalFileSystem::new_with_prefix(&dir).expect("Failed to open directory"));
+
+        let metastore = PersistentMetastore::new(store, Path::from(METASTORE_STATE_FILE))
+            .await
+            .expect("Failed to open metastore");
+        metastore
+            .create_volume(
+                &"vol".to_string(),
+                Volume::new("vol".to_string(), VolumeType::Memory),
+            )
+            .await
+            .expect("Failed to create volume");
+
+        // A directory in place of the state document makes every later write fail
+        std::fs::remove_file(dir.join(METASTORE_STATE_FILE)).expect("Failed to remove state");
+        std::fs::create_dir(dir.join(METASTORE_STATE_FILE)).expect("Failed to create directory");
+        let result = metastore
+            .create_database(
+                &"db".to_string(),
+                Database {
+                    ident: "db".to_string(),
+                    properties: None,
+                    volume: "vol".to_string(),
+                    should_refresh: false,
+                },
+            )
+            .await;
+        assert!(result.is_err());
+        assert!(
+            metastore
+                .get_database(&"db".to_string())
+                .await
+                .expect("Failed to get database")
+                .is_none()
+        );
+        assert!(
+            metastore
+                .get_volume(&"vol".to_string())
+                .await
+                .expect("Failed to get volume")
+                .is_some()
+        );
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[tokio::test]
+    async fn test_dropped_schema_survives_reopen() {
+        let store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let path = Path::from(METASTORE_STATE_FILE);
+        let schema_ident = SchemaIdent::new("db".to_string(), "staging".to_string());
+
+        let metastore = PersistentMetastore::new(store.clone(), path.clone())
+            .await
+            .expect("Failed to open metastore");
+        metastore
+            .create_volume(
+                &"vol".to_string(),
+                Volume::new("vol".to_string(), VolumeType::Memory),
+            )
+            .await
+            .expect("Failed to create volume");
+        metastore
+            .create_database(
+                &"db".to_string(),
+                Database {
+                    ident: "db".to_string(),
+                    properties: None,
+                    volume: "vol".to_string(),
+                    should_refresh: false,
+                },
+            )
+            .await
+            .expect("Failed to create database");
+        metastore
+            .create_schema(
+                &schema_ident,
+                Schema {
+                    ident: schema_ident.clone(),
+                    properties: None,
+                },
+            )
+            .await
+            .expect("Failed to create schema");
+        metastore
+            .delete_schema(&schema_ident, true)
+            .await
+            .expect("Failed to drop schema");
+
+        let reopened = PersistentMetastore::new(store, path)
+            .await
+            .expect("Failed to reopen metastore");
+        let dropped = reopened
+            .list_dropped_objects()
+            .await
+            .expect("Failed to list dropped objects");
+        assert_eq!(dropped.len(), 1);
+        assert_eq!(
+            dropped[0].ident,
+            DroppedObjectIdent::Schema(schema_ident.clone())
+        );
+        // Still within the default retention time, nothing to purge
+        assert_eq!(
+            reopened
+                .purge_dropped_objects()
+                .await
+                .expect("Failed to purge dropped objects"),
+            0
+        );
+        reopened
+            .undrop(&DroppedObjectIdent::Schema(schema_ident.clone()))
+            .await
+            .expect("Failed to undrop schema");
+        assert!(
+            reopened
+                .get_schema(&schema_ident)
+                .await
+                .expect("Failed to get schema")
+                .is_some()
+        );
+        assert!(
+            reopened
+                .undrop(&DroppedObjectIdent::Schema(schema_ident))
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_users_survive_reopen() {
+        let store: Arc<dyn ObjectStore> = Arc::new(InMemory::new());
+        let path = Path::from(METASTORE_STATE_FILE);
+
+        let metastore = PersistentMetastore::new(store.clone(), path.clone())
+            .await
+            .expect("Failed to open metastore");
+        let user = User::new("alice").with_password("secret");
+        metastore
+            .create_user(&user.name.clone(), user.clone())
+            .await
+            .expect("Failed to create user");
+        metastore
+            .update_user(
+                &user.name,
+                User {
+                    name: "bob".to_string(),
+                    ..user
+                },
+            )
+            .await
+            .expect("Failed to rename user");
+
+        let reopened = PersistentMetastore::new(store, path)
+            .await
+            .expect("Failed to reopen metastore");
+        assert!(
+            reopened
+                .get_user(&"alice".to_string())
+                .await
+                .expect("Failed to get user")
+                .is_none()
+        );
+        let bob = reopened
+            .get_user(&"bob".to_string())
+            .await
+            .expect("Failed to get user")
+            .expect("User not persisted");
+        assert!(bob.verify_password("secret"));
+    }
+}
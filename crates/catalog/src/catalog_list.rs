@@ -107,8 +107,7 @@ impl EmbucketCatalogList {
         err
     )]
     pub async fn create_catalog(&self, catalog_name: &str, volume_ident: &str) -> Result<()> {
-        let volume = self
-            .metastore
+        self.metastore
             .get_volume(&volume_ident.to_string())
             .await
             .context(MetastoreSnafu)?
@@ -127,20 +126,38 @@ impl EmbucketCatalogList {
             .create_database(&catalog_name.to_owned(), ident)
             .await
             .context(MetastoreSnafu)?;
+        self.register_database(&database).await
+    }
 
+    /// Registers the catalog of a database that already exists in the metastore, such as
+    /// a database restored by `UNDROP`.
+    #[tracing::instrument(
+        name = "EmbucketCatalogList::register_database",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    pub async fn register_database(&self, database: &RwObject<Database>) -> Result<()> {
+        let volume = self
+            .metastore
+            .get_volume(&database.volume)
+            .await
+            .context(MetastoreSnafu)?
+            .context(MissingVolumeSnafu {
+                name: database.volume.clone(),
+            })?;
         let catalog = match &volume.volume {
-            VolumeType::S3(_) | VolumeType::File(_) => self.get_embucket_catalog(&database).await?,
+            VolumeType::S3(_) | VolumeType::File(_) => self.get_embucket_catalog(database).await?,
             VolumeType::Memory => self
-                .get_embucket_catalog(&database)
+                .get_embucket_catalog(database)
                 .await?
                 .with_catalog_type(CatalogType::Memory),
             VolumeType::S3Tables(vol) => {
-                self.s3tables_iceberg_catalog(vol.clone(), &database)
-                    .await?
+                self.s3tables_iceberg_catalog(vol.clone(), database).await?
             }
         };
         self.catalogs
-            .insert(catalog_name.to_owned(), Arc::new(catalog));
+            .insert(database.ident.clone(), Arc::new(catalog));
         Ok(())
     }
 
@@ -42,6 +42,13 @@ pub enum DFExternalError {
         #[snafu(implicit)]
         location: Location,
     },
+    #[snafu(display("Invalid argument {argument} for table function {function}"))]
+    InvalidTableFunctionArgument {
+        function: String,
+        argument: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
     #[snafu(display("Cannot resolve view reference '{reference}'"))]
     CannotResolveViewReference {
         reference: String,
@@ -11,7 +11,9 @@ use super::tables::InformationSchemaTablesBuilder;
 use super::views::InformationSchemaViewBuilder;
 use crate::catalog::CachingCatalog;
 use crate::df_error;
+use crate::error;
 use crate::information_schema::databases::InformationSchemaDatabasesBuilder;
+use crate::information_schema::load_history::InformationSchemaLoadHistoryBuilder;
 use crate::information_schema::navigation_tree::InformationSchemaNavigationTreeBuilder;
 use crate::utils::fetch_table_providers;
 use dashmap::DashMap;
@@ -286,6 +288,39 @@ impl InformationSchemaConfig {
         Ok(())
     }
 
+    /// Construct the `information_schema.load_history` virtual table from the load
+    /// history the metastore keeps for the tables of the catalog
+    pub(crate) async fn make_load_history(
+        &self,
+        builder: &mut InformationSchemaLoadHistoryBuilder,
+    ) -> datafusion_common::Result<(), DataFusionError> {
+        let Some(catalog) = self.catalog_list.catalog(&self.catalog_name) else {
+            return Ok(());
+        };
+        let Some(metastore) = catalog
+            .as_any()
+            .downcast_ref::<CachingCatalog>()
+            .and_then(|catalog| catalog.metastore.clone())
+        else {
+            return Ok(());
+        };
+        let loads = metastore
+            .list_database_loads(&self.catalog_name.to_string())
+            .await
+            .context(error::MetastoreSnafu)
+            .context(df_error::CatalogSnafu)?;
+        let target_schema = self
+            .target_reference
+            .as_ref()
+            .and_then(TableReference::schema);
+        for load in &loads {
+            if target_schema.is_none_or(|schema| schema.eq_ignore_ascii_case(&load.table.schema)) {
+                builder.add_load(load);
+            }
+        }
+        Ok(())
+    }
+
     /// Construct the `information_schema.df_settings` virtual table
     pub(crate) fn make_df_settings(
         config_options: &ConfigOptions,
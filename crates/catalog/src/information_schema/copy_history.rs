@@ -0,0 +1,274 @@
+//! [`CopyHistoryFunc`] that implements the Snowflake [COPY_HISTORY] table function.
+//!
+//! [COPY_HISTORY]: https://docs.snowflake.com/en/sql-reference/functions/copy_history
+
+use crate::df_error;
+use crate::error;
+use async_trait::async_trait;
+use catalog_metastore::{Metastore, TableIdent, TableLoad};
+use datafusion::arrow::array::{
+    Array, AsArray, RecordBatchOptions, StringBuilder, TimestampMillisecondBuilder, UInt64Builder,
+};
+use datafusion::arrow::compute::cast;
+use datafusion::arrow::datatypes::{
+    DataType, Field, Schema, SchemaRef, TimeUnit, TimestampMillisecondType,
+};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::catalog::{Session, TableFunctionImpl, TableProvider};
+use datafusion::datasource::MemTable;
+use datafusion_common::{DFSchema, Result};
+use datafusion_expr::{Expr, TableType};
+use datafusion_physical_plan::ExecutionPlan;
+use functions::session_params::SessionParams;
+use snafu::ResultExt;
+use std::any::Any;
+use std::sync::Arc;
+
+const COPY_HISTORY: &str = "COPY_HISTORY";
+
+/// Returns the load history of a table kept by `COPY INTO`:
+///
+/// ```sql
+/// SELECT * FROM TABLE(information_schema.copy_history(
+///     TABLE_NAME => 'mytable',
+///     START_TIME => DATEADD(hours, -1, CURRENT_TIMESTAMP())
+/// ));
+/// ```
+///
+/// Unqualified table names are resolved with the current database and schema.
+#[derive(Debug)]
+pub struct CopyHistoryFunc {
+    metastore: Arc<dyn Metastore>,
+}
+
+impl CopyHistoryFunc {
+    #[must_use]
+    pub fn new(metastore: Arc<dyn Metastore>) -> Self {
+        Self { metastore }
+    }
+}
+
+fn invalid_argument(argument: &str) -> df_error::DFExternalError {
+    df_error::InvalidTableFunctionArgumentSnafu {
+        function: COPY_HISTORY,
+        argument,
+    }
+    .build()
+}
+
+/// Resolves a `TABLE_NAME` argument with the current database and schema of the session
+pub(crate) fn resolve_table_name(state: &dyn Session, table_name: &str) -> Option<TableIdent> {
+    let options = state.config_options();
+    let params = options.extensions.get::<SessionParams>();
+    let current = |key: &str, default: &str| {
+        params
+            .and_then(|params| params.get_property(key))
+            .unwrap_or_else(|| default.to_string())
+    };
+    let parts: Vec<&str> = table_name
+        .split('.')
+        .map(|part| part.trim_matches('"'))
+        .collect();
+    match parts.as_slice() {
+        [table] => Some(TableIdent::new(
+            &current("database", &options.catalog.default_catalog),
+            &current("schema", &options.catalog.default_schema),
+            table,
+        )),
+        [schema, table] => Some(TableIdent::new(
+            &current("database", &options.catalog.default_catalog),
+            schema,
+            table,
+        )),
+        [database, schema, table] => Some(TableIdent::new(database, schema, table)),
+        _ => None,
+    }
+}
+
+impl TableFunctionImpl for CopyHistoryFunc {
+    fn call(&self, args: &[(Expr, Option<String>)]) -> Result<Arc<dyn TableProvider>> {
+        let mut table_name = None;
+        let mut start_time = None;
+        let mut end_time = None;
+        for (position, (expr, name)) in args.iter().enumerate() {
+            let name = name.as_deref().map(str::to_ascii_uppercase);
+            match (name.as_deref(), position) {
+                (Some("TABLE_NAME"), _) | (None, 0) => table_name = Some(expr),
+                (Some("START_TIME"), _) | (None, 1) => start_time = Some(expr.clone()),
+                (Some("END_TIME"), _) | (None, 2) => end_time = Some(expr.clone()),
+                (name, position) => {
+                    return Err(invalid_argument(name.unwrap_or(&position.to_string())).into());
+                }
+            }
+        }
+        let Some(Expr::Literal(table_name, _)) = table_name else {
+            return Err(invalid_argument("TABLE_NAME").into());
+        };
+        let Some(Some(table_name)) = table_name.try_as_str() else {
+            return Err(invalid_argument("TABLE_NAME").into());
+        };
+        Ok(Arc::new(CopyHistoryTable {
+            metastore: self.metastore.clone(),
+            table_name: table_name.to_string(),
+            start_time,
+            end_time,
+            schema: CopyHistoryTable::schema(),
+        }))
+    }
+}
+
+#[derive(Debug)]
+struct CopyHistoryTable {
+    metastore: Arc<dyn Metastore>,
+    table_name: String,
+    start_time: Option<Expr>,
+    end_time: Option<Expr>,
+    schema: SchemaRef,
+}
+
+impl CopyHistoryTable {
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("file_name", DataType::Utf8, false),
+            Field::new(
+                "last_load_time",
+                DataType::Timestamp(TimeUnit::Millisecond, None),
+                false,
+            ),
+            Field::new("row_count", DataType::UInt64, false),
+            Field::new("row_parsed", DataType::UInt64, false),
+            Field::new("file_size", DataType::UInt64, false),
+            Field::new("first_error_message", DataType::Utf8, true),
+            Field::new("first_error_line_number", DataType::UInt64, true),
+            Field::new("first_error_column_name", DataType::Utf8, true),
+            Field::new("error_count", DataType::UInt64, false),
+            Field::new("error_limit", DataType::UInt64, false),
+            Field::new("status", DataType::Utf8, false),
+            Field::new("table_catalog_name", DataType::Utf8, false),
+            Field::new("table_schema_name", DataType::Utf8, false),
+            Field::new("table_name", DataType::Utf8, false),
+        ]))
+    }
+
+    /// Evaluates a `START_TIME` or `END_TIME` argument to milliseconds since the epoch
+    fn evaluate_time(state: &dyn Session, expr: &Expr) -> Result<Option<i64>> {
+        let expr = state.create_physical_expr(expr.clone(), &DFSchema::empty())?;
+        let batch = RecordBatch::try_new_with_options(
+            Arc::new(Schema::empty()),
+            vec![],
+            &RecordBatchOptions::new().with_row_count(Some(1)),
+        )?;
+        let value = expr.evaluate(&batch)?.into_array(1)?;
+        let value = cast(&value, &DataType::Timestamp(TimeUnit::Millisecond, None))?;
+        let value = value.as_primitive::<TimestampMillisecondType>();
+        Ok(value.is_valid(0).then(|| value.value(0)))
+    }
+
+    fn load_history(
+        &self,
+        loads: &[TableLoad],
+        start: Option<i64>,
+        end: Option<i64>,
+    ) -> Result<RecordBatch> {
+        let mut file_names = StringBuilder::new();
+        let mut last_load_times = TimestampMillisecondBuilder::new();
+        let mut row_counts = UInt64Builder::new();
+        let mut rows_parsed = UInt64Builder::new();
+        let mut file_sizes = UInt64Builder::new();
+        let mut first_error_messages = StringBuilder::new();
+        let mut first_error_line_numbers = UInt64Builder::new();
+        let mut first_error_column_names = StringBuilder::new();
+        let mut error_counts = UInt64Builder::new();
+        let mut error_limits = UInt64Builder::new();
+        let mut statuses = StringBuilder::new();
+        let mut table_catalog_names = StringBuilder::new();
+        let mut table_schema_names = StringBuilder::new();
+        let mut table_names = StringBuilder::new();
+        for load in loads {
+            let loaded_at = load.loaded_at.and_utc().timestamp_millis();
+            if start.is_some_and(|start| loaded_at < start)
+                || end.is_some_and(|end| loaded_at >= end)
+            {
+                continue;
+            }
+            file_names.append_value(&load.file);
+            last_load_times.append_value(loaded_at);
+            row_counts.append_value(load.rows_loaded);
+            rows_parsed.append_value(load.rows_parsed);
+            file_sizes.append_value(load.size);
+            first_error_messages.append_option(load.first_error.as_ref());
+            first_error_line_numbers.append_option(load.first_error_line);
+            first_error_column_names.append_option(load.first_error_column_name.as_ref());
+            error_counts.append_value(load.errors_seen);
+            error_limits.append_value(load.error_limit);
+            statuses.append_value(&load.status);
+            table_catalog_names.append_value(&load.table.database);
+            table_schema_names.append_value(&load.table.schema);
+            table_names.append_value(&load.table.table);
+        }
+        Ok(RecordBatch::try_new(
+            Arc::clone(&self.schema),
+            vec![
+                Arc::new(file_names.finish()),
+                Arc::new(last_load_times.finish()),
+                Arc::new(row_counts.finish()),
+                Arc::new(rows_parsed.finish()),
+                Arc::new(file_sizes.finish()),
+                Arc::new(first_error_messages.finish()),
+                Arc::new(first_error_line_numbers.finish()),
+                Arc::new(first_error_column_names.finish()),
+                Arc::new(error_counts.finish()),
+                Arc::new(error_limits.finish()),
+                Arc::new(statuses.finish()),
+                Arc::new(table_catalog_names.finish()),
+                Arc::new(table_schema_names.finish()),
+                Arc::new(table_names.finish()),
+            ],
+        )?)
+    }
+}
+
+#[async_trait]
+impl TableProvider for CopyHistoryTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Temporary
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let start = match &self.start_time {
+            Some(expr) => Self::evaluate_time(state, expr)?,
+            None => None,
+        };
+        let end = match &self.end_time {
+            Some(expr) => Self::evaluate_time(state, expr)?,
+            None => None,
+        };
+        let loads = self
+            .metastore
+            .list_table_loads(
+                &resolve_table_name(state, &self.table_name)
+                    .ok_or_else(|| invalid_argument("TABLE_NAME"))?,
+            )
+            .await
+            .context(error::MetastoreSnafu)
+            .context(df_error::CatalogSnafu)?;
+        let batch = self.load_history(&loads, start, end)?;
+        MemTable::try_new(Arc::clone(&self.schema), vec![vec![batch]])?
+            .scan(state, projection, filters, limit)
+            .await
+    }
+}
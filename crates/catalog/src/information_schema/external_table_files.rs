@@ -0,0 +1,169 @@
+//! [`ExternalTableFilesFunc`] that implements the Snowflake [EXTERNAL_TABLE_FILES] table function.
+//!
+//! [EXTERNAL_TABLE_FILES]: https://docs.snowflake.com/en/sql-reference/functions/external_table_files
+
+use crate::df_error;
+use crate::error;
+use crate::information_schema::copy_history::resolve_table_name;
+use async_trait::async_trait;
+use catalog_metastore::{ExternalTableFile, Metastore};
+use datafusion::arrow::array::{StringBuilder, TimestampMillisecondBuilder, UInt64Builder};
+use datafusion::arrow::datatypes::{DataType, Field, Schema, SchemaRef, TimeUnit};
+use datafusion::arrow::record_batch::RecordBatch;
+use datafusion::catalog::{Session, TableFunctionImpl, TableProvider};
+use datafusion::datasource::MemTable;
+use datafusion_common::Result;
+use datafusion_expr::{Expr, TableType};
+use datafusion_physical_plan::ExecutionPlan;
+use snafu::ResultExt;
+use std::any::Any;
+use std::sync::Arc;
+
+const EXTERNAL_TABLE_FILES: &str = "EXTERNAL_TABLE_FILES";
+
+/// Returns the files registered in the metadata of an external table:
+///
+/// ```sql
+/// SELECT * FROM TABLE(information_schema.external_table_files(TABLE_NAME => 'mytable'));
+/// ```
+///
+/// Unqualified table names are resolved with the current database and schema.
+#[derive(Debug)]
+pub struct ExternalTableFilesFunc {
+    metastore: Arc<dyn Metastore>,
+}
+
+impl ExternalTableFilesFunc {
+    #[must_use]
+    pub fn new(metastore: Arc<dyn Metastore>) -> Self {
+        Self { metastore }
+    }
+}
+
+fn invalid_argument(argument: &str) -> df_error::DFExternalError {
+    df_error::InvalidTableFunctionArgumentSnafu {
+        function: EXTERNAL_TABLE_FILES,
+        argument,
+    }
+    .build()
+}
+
+impl TableFunctionImpl for ExternalTableFilesFunc {
+    fn call(&self, args: &[(Expr, Option<String>)]) -> Result<Arc<dyn TableProvider>> {
+        let mut table_name = None;
+        for (position, (expr, name)) in args.iter().enumerate() {
+            let name = name.as_deref().map(str::to_ascii_uppercase);
+            match (name.as_deref(), position) {
+                (Some("TABLE_NAME"), _) | (None, 0) => table_name = Some(expr),
+                (name, position) => {
+                    return Err(invalid_argument(name.unwrap_or(&position.to_string())).into());
+                }
+            }
+        }
+        let Some(Expr::Literal(table_name, _)) = table_name else {
+            return Err(invalid_argument("TABLE_NAME").into());
+        };
+        let Some(Some(table_name)) = table_name.try_as_str() else {
+            return Err(invalid_argument("TABLE_NAME").into());
+        };
+        Ok(Arc::new(ExternalTableFilesTable {
+            metastore: self.metastore.clone(),
+            table_name: table_name.to_string(),
+            schema: ExternalTableFilesTable::schema(),
+        }))
+    }
+}
+
+#[derive(Debug)]
+struct ExternalTableFilesTable {
+    metastore: Arc<dyn Metastore>,
+    table_name: String,
+    schema: SchemaRef,
+}
+
+impl ExternalTableFilesTable {
+    fn schema() -> SchemaRef {
+        Arc::new(Schema::new(vec![
+            Field::new("file_name", DataType::Utf8, false),
+            Field::new(
+                "registered_on",
+                DataType::Timestamp(TimeUnit::Millisecond, None),
+                false,
+            ),
+            Field::new("file_size", DataType::UInt64, false),
+            Field::new(
+                "last_modified",
+                DataType::Timestamp(TimeUnit::Millisecond, None),
+                false,
+            ),
+            Field::new("etag", DataType::Utf8, true),
+            Field::new("md5", DataType::Utf8, true),
+        ]))
+    }
+
+    fn files(&self, files: &[ExternalTableFile]) -> Result<RecordBatch> {
+        let mut file_names = StringBuilder::new();
+        let mut registered_on = TimestampMillisecondBuilder::new();
+        let mut file_sizes = UInt64Builder::new();
+        let mut last_modified = TimestampMillisecondBuilder::new();
+        let mut etags = StringBuilder::new();
+        let mut md5s = StringBuilder::new();
+        for file in files {
+            file_names.append_value(&file.file);
+            registered_on.append_value(file.registered_at.and_utc().timestamp_millis());
+            file_sizes.append_value(file.size);
+            last_modified.append_value(file.last_modified.and_utc().timestamp_millis());
+            etags.append_option(file.e_tag.as_ref());
+            // Object stores do not report the MD5 of a file
+            md5s.append_null();
+        }
+        Ok(RecordBatch::try_new(
+            Arc::clone(&self.schema),
+            vec![
+                Arc::new(file_names.finish()),
+                Arc::new(registered_on.finish()),
+                Arc::new(file_sizes.finish()),
+                Arc::new(last_modified.finish()),
+                Arc::new(etags.finish()),
+                Arc::new(md5s.finish()),
+            ],
+        )?)
+    }
+}
+
+#[async_trait]
+impl TableProvider for ExternalTableFilesTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Temporary
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[Expr],
+        limit: Option<usize>,
+    ) -> Result<Arc<dyn ExecutionPlan>> {
+        let ident = resolve_table_name(state, &self.table_name)
+            .ok_or_else(|| invalid_argument("TABLE_NAME"))?;
+        let table = self
+            .metastore
+            .get_external_table(&ident)
+            .await
+            .context(error::MetastoreSnafu)
+            .context(df_error::CatalogSnafu)?
+            .ok_or_else(|| invalid_argument("TABLE_NAME"))?;
+        let batch = self.files(&table.files)?;
+        MemTable::try_new(Arc::clone(&self.schema), vec![vec![batch]])?
+            .scan(state, projection, filters, limit)
+            .await
+    }
+}
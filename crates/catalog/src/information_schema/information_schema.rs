@@ -6,6 +6,7 @@ use crate::information_schema::columns::InformationSchemaColumns;
 use crate::information_schema::config::InformationSchemaConfig;
 use crate::information_schema::databases::InformationSchemaDatabases;
 use crate::information_schema::df_settings::InformationSchemaDfSettings;
+use crate::information_schema::load_history::InformationSchemaLoadHistory;
 use crate::information_schema::navigation_tree::InformationSchemaNavigationTree;
 use crate::information_schema::parameters::InformationSchemaParameters;
 use crate::information_schema::routines::InformationSchemaRoutines;
@@ -28,6 +29,7 @@ pub const VIEWS: &str = "views";
 pub const COLUMNS: &str = "columns";
 pub const SCHEMATA: &str = "schemata";
 pub const DATABASES: &str = "databases";
+pub const LOAD_HISTORY: &str = "load_history";
 
 pub const DF_SETTINGS: &str = "df_settings";
 pub const ROUTINES: &str = "routines";
@@ -62,6 +64,7 @@ impl InformationSchemaProvider {
                     (COLUMNS, InformationSchemaColumns::schema()),
                     (SCHEMATA, InformationSchemata::schema()),
                     (DATABASES, InformationSchemaDatabases::schema()),
+                    (LOAD_HISTORY, InformationSchemaLoadHistory::schema()),
                     (DF_SETTINGS, InformationSchemaDfSettings::schema()),
                     (ROUTINES, InformationSchemaRoutines::schema()),
                     (PARAMETERS, InformationSchemaParameters::schema()),
@@ -106,6 +109,7 @@ impl SchemaProvider for InformationSchemaProvider {
             VIEWS => Arc::new(InformationSchemaViews::new(config)),
             SCHEMATA => Arc::new(InformationSchemata::new(config)),
             DATABASES => Arc::new(InformationSchemaDatabases::new(config)),
+            LOAD_HISTORY => Arc::new(InformationSchemaLoadHistory::new(config)),
             // TODO: Check if non-Snowflake related tables are required
             DF_SETTINGS => Arc::new(InformationSchemaDfSettings::new()),
             ROUTINES => Arc::new(InformationSchemaRoutines::new()),
@@ -0,0 +1,145 @@
+//! [`InformationSchemaLoadHistory`] that implements the SQL [Information Schema Load History] for Snowflake.
+//!
+//! [Information Schema Load History]: https://docs.snowflake.com/en/sql-reference/info-schema/load_history
+
+use crate::information_schema::config::InformationSchemaConfig;
+use catalog_metastore::TableLoad;
+use datafusion::arrow::datatypes::TimeUnit;
+use datafusion::arrow::error::ArrowError;
+use datafusion::arrow::{
+    array::{StringBuilder, TimestampMillisecondBuilder, UInt64Builder},
+    datatypes::{DataType, Field, Schema, SchemaRef},
+    record_batch::RecordBatch,
+};
+use datafusion::execution::TaskContext;
+use datafusion_physical_plan::SendableRecordBatchStream;
+use datafusion_physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion_physical_plan::streaming::PartitionStream;
+use std::fmt::Debug;
+use std::sync::Arc;
+
+#[derive(Debug)]
+pub struct InformationSchemaLoadHistory {
+    schema: SchemaRef,
+    config: InformationSchemaConfig,
+}
+
+impl InformationSchemaLoadHistory {
+    pub fn schema() -> Arc<Schema> {
+        Arc::new(Schema::new(vec![
+            Field::new("schema_name", DataType::Utf8, false),
+            Field::new("file_name", DataType::Utf8, false),
+            Field::new("table_name", DataType::Utf8, false),
+            Field::new(
+                "last_load_time",
+                DataType::Timestamp(TimeUnit::Millisecond, None),
+                false,
+            ),
+            Field::new("status", DataType::Utf8, false),
+            Field::new("row_count", DataType::UInt64, false),
+            Field::new("row_parsed", DataType::UInt64, false),
+            Field::new("first_error_message", DataType::Utf8, true),
+            Field::new("first_error_line_number", DataType::UInt64, true),
+            Field::new("first_error_col_name", DataType::Utf8, true),
+            Field::new("error_count", DataType::UInt64, false),
+            Field::new("error_limit", DataType::UInt64, false),
+        ]))
+    }
+    pub(crate) fn new(config: InformationSchemaConfig) -> Self {
+        let schema = Self::schema();
+        Self { schema, config }
+    }
+
+    fn builder(&self) -> InformationSchemaLoadHistoryBuilder {
+        InformationSchemaLoadHistoryBuilder {
+            schema: Arc::clone(&self.schema),
+            schema_names: StringBuilder::new(),
+            file_names: StringBuilder::new(),
+            table_names: StringBuilder::new(),
+            last_load_times: TimestampMillisecondBuilder::new(),
+            statuses: StringBuilder::new(),
+            row_counts: UInt64Builder::new(),
+            rows_parsed: UInt64Builder::new(),
+            first_error_messages: StringBuilder::new(),
+            first_error_line_numbers: UInt64Builder::new(),
+            first_error_col_names: StringBuilder::new(),
+            error_counts: UInt64Builder::new(),
+            error_limits: UInt64Builder::new(),
+        }
+    }
+}
+
+impl PartitionStream for InformationSchemaLoadHistory {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+
+    fn execute(&self, _ctx: Arc<TaskContext>) -> SendableRecordBatchStream {
+        let mut builder = self.builder();
+        let config = self.config.clone();
+        Box::pin(RecordBatchStreamAdapter::new(
+            Arc::clone(&self.schema),
+            futures::stream::once(async move {
+                config.make_load_history(&mut builder).await?;
+                Ok(builder.finish()?)
+            }),
+        ))
+    }
+}
+
+pub struct InformationSchemaLoadHistoryBuilder {
+    schema: SchemaRef,
+    schema_names: StringBuilder,
+    file_names: StringBuilder,
+    table_names: StringBuilder,
+    last_load_times: TimestampMillisecondBuilder,
+    statuses: StringBuilder,
+    row_counts: UInt64Builder,
+    rows_parsed: UInt64Builder,
+    first_error_messages: StringBuilder,
+    first_error_line_numbers: UInt64Builder,
+    first_error_col_names: StringBuilder,
+    error_counts: UInt64Builder,
+    error_limits: UInt64Builder,
+}
+
+impl InformationSchemaLoadHistoryBuilder {
+    pub(crate) fn add_load(&mut self, load: &TableLoad) {
+        self.schema_names.append_value(&load.table.schema);
+        self.file_names.append_value(&load.file);
+        self.table_names.append_value(&load.table.table);
+        self.last_load_times
+            .append_value(load.loaded_at.and_utc().timestamp_millis());
+        self.statuses.append_value(&load.status);
+        self.row_counts.append_value(load.rows_loaded);
+        self.rows_parsed.append_value(load.rows_parsed);
+        self.first_error_messages
+            .append_option(load.first_error.as_ref());
+        self.first_error_line_numbers
+            .append_option(load.first_error_line);
+        self.first_error_col_names
+            .append_option(load.first_error_column_name.as_ref());
+        self.error_counts.append_value(load.errors_seen);
+        self.error_limits.append_value(load.error_limit);
+    }
+
+    fn finish(&mut self) -> Result<RecordBatch, ArrowError> {
+        RecordBatch::try_new(
+            Arc::clone(&self.schema),
+            vec![
+                Arc::new(self.schema_names.finish()),
+                Arc::new(self.file_names.finish()),
+                Arc::new(self.table_names.finish()),
+                Arc::new(self.last_load_times.finish()),
+                Arc::new(self.statuses.finish()),
+                Arc::new(self.row_counts.finish()),
+                Arc::new(self.rows_parsed.finish()),
+                Arc::new(self.first_error_messages.finish()),
+                Arc::new(self.first_error_line_numbers.finish()),
+                Arc::new(self.first_error_col_names.finish()),
+                Arc::new(self.error_counts.finish()),
+                Arc::new(self.error_limits.finish()),
+            ],
+        )
+    }
+}
@@ -1,9 +1,12 @@
 mod columns;
 mod config;
+pub mod copy_history;
 mod databases;
 mod df_settings;
+pub mod external_table_files;
 #[allow(clippy::module_inception)]
 pub mod information_schema;
+mod load_history;
 mod navigation_tree;
 mod parameters;
 mod routines;
@@ -1,5 +1,6 @@
 use build_info::BuildInfo;
 use executor::utils::{Config as ExecutionConfig, MemPoolType};
+use state_store::StateStoreBackend;
 use std::{env, path::PathBuf};
 
 #[derive(Debug, Clone)]
@@ -34,8 +35,8 @@ impl EnvConfig {
     pub fn from_env() -> Self {
         Self {
             data_format: env_or_default("DATA_FORMAT", "json"),
-            auth_demo_user: env_or_default("AUTH_DEMO_USER", "embucket"),
-            auth_demo_password: env_or_default("AUTH_DEMO_PASSWORD", "embucket"),
+            auth_demo_user: env::var("AUTH_DEMO_USER").unwrap_or_default(),
+            auth_demo_password: env::var("AUTH_DEMO_PASSWORD").unwrap_or_default(),
             sql_parser_dialect: env::var("SQL_PARSER_DIALECT").ok(),
             query_timeout_secs: parse_env("QUERY_TIMEOUT_SECS").unwrap_or(1200),
             max_concurrency_level: parse_env("MAX_CONCURRENCY_LEVEL").unwrap_or(8),
@@ -88,6 +89,7 @@ impl EnvConfig {
             aws_sdk_operation_attempt_timeout_secs: self.aws_sdk_operation_attempt_timeout_secs,
             iceberg_table_timeout_secs: self.iceberg_table_timeout_secs,
             iceberg_catalog_timeout_secs: self.iceberg_catalog_timeout_secs,
+            state_store_backend: StateStoreBackend::DynamoDb,
         }
     }
 }
@@ -2,8 +2,10 @@ mod config;
 
 use crate::config::EnvConfig;
 use api_snowflake_rest::server::core_state::CoreState;
+use api_snowflake_rest::server::core_state::MetastoreBackend;
 use api_snowflake_rest::server::core_state::MetastoreConfig;
 use api_snowflake_rest::server::make_snowflake_router;
+use api_snowflake_rest::server::result_cache::RESULT_CHUNKS_REAPER_INTERVAL;
 use api_snowflake_rest::server::server_models::RestApiConfig as SnowflakeServerConfig;
 use api_snowflake_rest::server::state::AppState;
 use api_snowflake_rest_sessions::session::SESSION_EXPIRATION_SECONDS;
@@ -128,10 +130,14 @@ impl LambdaApp {
             snowflake_cfg,
             metastore_settings_config,
             metastore_cfg,
+            MetastoreBackend::Memory,
         )
         .await?;
         core_state
             .with_session_timeout(tokio::time::Duration::from_secs(SESSION_EXPIRATION_SECONDS))?;
+        core_state
+            .with_result_chunks_reaper(RESULT_CHUNKS_REAPER_INTERVAL)
+            .await?;
 
         let appstate = AppState::from(&core_state);
         let router = make_snowflake_router(appstate);
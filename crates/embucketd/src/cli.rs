@@ -1,5 +1,7 @@
+use api_snowflake_rest::server::core_state::MetastoreBackend;
 use clap::{Parser, ValueEnum};
 use executor::utils::MemPoolType;
+use state_store::StateStoreBackend;
 use std::path::PathBuf;
 use tracing_subscriber::filter::LevelFilter;
 
@@ -14,6 +16,42 @@ pub struct CliOpts {
     )]
     pub metastore_config: Option<PathBuf>,
 
+    #[arg(
+        long,
+        value_enum,
+        env = "METASTORE_BACKEND",
+        default_value = "memory",
+        help = "Metastore backend, can be 'memory' or 'file'"
+    )]
+    pub metastore_backend: MetastoreBackendType,
+
+    #[arg(
+        long,
+        env = "METASTORE_PATH",
+        value_name = "PATH",
+        default_value = "metastore",
+        help = "Directory the 'file' metastore backend keeps its state in"
+    )]
+    pub metastore_path: PathBuf,
+
+    #[arg(
+        long,
+        value_enum,
+        env = "STATESTORE_BACKEND",
+        default_value = "dynamodb",
+        help = "State store backend used with the 'state-store' feature, can be 'dynamodb' or 'file'"
+    )]
+    pub state_store_backend: StateStoreBackendType,
+
+    #[arg(
+        long,
+        env = "STATESTORE_PATH",
+        value_name = "PATH",
+        default_value = "statestore",
+        help = "Directory the 'file' state store backend keeps sessions and query history in"
+    )]
+    pub state_store_path: PathBuf,
+
     #[arg(
         long,
         env = "BUCKET_HOST",
@@ -39,6 +77,14 @@ pub struct CliOpts {
     )]
     pub data_format: Option<String>,
 
+    #[arg(
+        long,
+        env = "RESULT_CHUNK_SIZE",
+        default_value = "100000",
+        help = "Number of rows per query result chunk in Snowflake v1 API"
+    )]
+    pub result_chunk_size: usize,
+
     #[arg(
         long,
         env = "SQL_PARSER_DIALECT",
@@ -104,8 +150,7 @@ pub struct CliOpts {
         long,
         env = "AUTH_DEMO_USER",
         value_parser = clap::builder::NonEmptyStringValueParser::new(),
-        default_value = "embucket",
-        help = "User for auth demo"
+        help = "User for auth demo, only accepted until the first user is created"
     )]
     pub auth_demo_user: Option<String>,
 
@@ -113,8 +158,7 @@ pub struct CliOpts {
         long,
         env = "AUTH_DEMO_PASSWORD",
         value_parser = clap::builder::NonEmptyStringValueParser::new(),
-        default_value = "embucket",
-        help = "Password for auth demo"
+        help = "Password for auth demo, only accepted until the first user is created"
     )]
     pub auth_demo_password: Option<String>,
 
@@ -224,9 +268,51 @@ pub struct CliOpts {
         help = "Object store connect timeout in seconds"
     )]
     pub object_store_connect_timeout_secs: u64,
+
+    #[arg(
+        long,
+        env = "DATA_RETENTION_TIME_IN_DAYS",
+        default_value = "1",
+        help = "Days dropped databases, schemas and tables are kept for UNDROP before their files are purged"
+    )]
+    pub data_retention_time_in_days: u64,
+
+    #[arg(
+        long,
+        env = "DROPPED_OBJECTS_PURGE_INTERVAL_SECS",
+        value_parser = clap::value_parser!(u64).range(1..),
+        default_value = "3600",
+        help = "Interval in seconds between purges of dropped objects past their retention time"
+    )]
+    pub dropped_objects_purge_interval_secs: u64,
+
+    #[arg(
+        long,
+        env = "EXTERNAL_TABLES_REFRESH_INTERVAL_SECS",
+        value_parser = clap::value_parser!(u64).range(1..),
+        default_value = "60",
+        help = "Interval in seconds between refreshes of the external tables with AUTO_REFRESH"
+    )]
+    pub external_tables_refresh_interval_secs: u64,
 }
 
 impl CliOpts {
+    #[must_use]
+    pub fn metastore_backend(&self) -> MetastoreBackend {
+        match self.metastore_backend {
+            MetastoreBackendType::Memory => MetastoreBackend::Memory,
+            MetastoreBackendType::File => MetastoreBackend::File(self.metastore_path.clone()),
+        }
+    }
+
+    #[must_use]
+    pub fn state_store_backend(&self) -> StateStoreBackend {
+        match self.state_store_backend {
+            StateStoreBackendType::Dynamodb => StateStoreBackend::DynamoDb,
+            StateStoreBackendType::File => StateStoreBackend::File(self.state_store_path.clone()),
+        }
+    }
+
     // method resets a secret env
     pub fn jwt_secret(&self) -> String {
         unsafe {
@@ -236,6 +322,18 @@ impl CliOpts {
     }
 }
 
+#[derive(Debug, Clone, ValueEnum)]
+pub enum MetastoreBackendType {
+    Memory,
+    File,
+}
+
+#[derive(Debug, Clone, ValueEnum)]
+pub enum StateStoreBackendType {
+    Dynamodb,
+    File,
+}
+
 #[derive(Debug, Clone, ValueEnum)]
 pub enum TracingLevel {
     Off,
@@ -8,6 +8,7 @@ pub(crate) mod layers;
 use api_snowflake_rest::server::core_state::CoreState;
 use api_snowflake_rest::server::core_state::MetastoreConfig;
 use api_snowflake_rest::server::make_snowflake_router;
+use api_snowflake_rest::server::result_cache::RESULT_CHUNKS_REAPER_INTERVAL;
 use api_snowflake_rest::server::server_models::RestApiConfig;
 use api_snowflake_rest::server::state::AppState;
 use api_snowflake_rest_sessions::session::SESSION_EXPIRATION_SECONDS;
@@ -117,9 +118,10 @@ async fn async_main(
     let snowflake_rest_cfg = RestApiConfig::new(&data_format, opts.jwt_secret())
         .expect("Failed to create snowflake config")
         .with_demo_credentials(
-            opts.auth_demo_user.clone().unwrap(),
-            opts.auth_demo_password.clone().unwrap(),
-        );
+            opts.auth_demo_user.clone().unwrap_or_default(),
+            opts.auth_demo_password.clone().unwrap_or_default(),
+        )
+        .with_result_chunk_size(opts.result_chunk_size);
 
     let execution_cfg = ExecutionConfig {
         embucket_version: BuildInfo::VERSION.to_string(),
@@ -141,6 +143,7 @@ async fn async_main(
         aws_sdk_connect_timeout_secs: opts.aws_sdk_connect_timeout_secs,
         iceberg_table_timeout_secs: opts.iceberg_table_timeout_secs,
         iceberg_catalog_timeout_secs: opts.iceberg_catalog_timeout_secs,
+        state_store_backend: opts.state_store_backend(),
     };
 
     let host = opts.host.clone().unwrap();
@@ -148,7 +151,8 @@ async fn async_main(
 
     let metatore_settings_config = MetastoreSettingsConfig::default()
         .with_object_store_timeout(opts.object_store_timeout_secs)
-        .with_object_store_connect_timeout(opts.object_store_connect_timeout_secs);
+        .with_object_store_connect_timeout(opts.object_store_connect_timeout_secs)
+        .with_data_retention_time_in_days(opts.data_retention_time_in_days);
 
     let metastore_cfg = if let Some(config_path) = &opts.metastore_config {
         MetastoreConfig::ConfigPath(config_path.clone())
@@ -160,12 +164,22 @@ async fn async_main(
         snowflake_rest_cfg,
         metatore_settings_config,
         metastore_cfg,
+        opts.metastore_backend(),
     )
     .await
     .expect("Core state creation error");
 
     core_state
         .with_session_timeout(tokio::time::Duration::from_secs(SESSION_EXPIRATION_SECONDS))?;
+    core_state.with_dropped_objects_purger(tokio::time::Duration::from_secs(
+        opts.dropped_objects_purge_interval_secs,
+    ))?;
+    core_state.with_external_tables_refresher(tokio::time::Duration::from_secs(
+        opts.external_tables_refresh_interval_secs,
+    ))?;
+    core_state
+        .with_result_chunks_reaper(RESULT_CHUNKS_REAPER_INTERVAL)
+        .await?;
 
     let appstate = AppState::from(&core_state);
     let snowflake_router = make_snowflake_router(appstate);
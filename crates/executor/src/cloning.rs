@@ -0,0 +1,159 @@
+use catalog_metastore::{Table as MetastoreTable, TableIdent as MetastoreTableIdent};
+use chrono::Utc;
+use iceberg_rust::spec::table_metadata::TableMetadata;
+use sqlparser::ast::{ObjectName, TableVersion};
+use sqlparser::dialect::SnowflakeDialect;
+use sqlparser::keywords::Keyword;
+use sqlparser::parser::{Parser, ParserError};
+use sqlparser::tokenizer::Token;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+type ParseResult<T> = std::result::Result<T, ParserError>;
+
+/// Kind of object a `CLONE` statement creates
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CloneKind {
+    Table,
+    Schema,
+    Database,
+}
+
+/// `CREATE [OR REPLACE] TABLE | SCHEMA | DATABASE [IF NOT EXISTS] <name>
+/// CLONE <source> [AT | BEFORE (...)]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct CloneStatement {
+    pub kind: CloneKind,
+    pub name: ObjectName,
+    pub source: ObjectName,
+    pub or_replace: bool,
+    pub if_not_exists: bool,
+    /// `AT` or `BEFORE` clause giving the point in the history of the source the
+    /// clone is taken at, the latest state if not given
+    pub version: Option<TableVersion>,
+}
+
+// Cheap check to avoid tokenizing every statement twice
+fn looks_like_clone_statement(sql: &str) -> bool {
+    let words = sql
+        .split_whitespace()
+        .take(12)
+        .map(str::to_ascii_uppercase)
+        .collect::<Vec<_>>();
+    words.first().is_some_and(|word| word == "CREATE") && words.iter().any(|word| word == "CLONE")
+}
+
+/// Parses a `CLONE` statement, returns `None` if the sql is a different statement.
+pub fn parse_clone_statement(sql: &str) -> ParseResult<Option<CloneStatement>> {
+    if !looks_like_clone_statement(sql) {
+        return Ok(None);
+    }
+    let dialect = SnowflakeDialect;
+    let mut parser = Parser::new(&dialect).try_with_sql(sql)?;
+    if !parser.parse_keyword(Keyword::CREATE) {
+        return Ok(None);
+    }
+    let or_replace = parser.parse_keywords(&[Keyword::OR, Keyword::REPLACE]);
+    let _transient = parser.parse_keyword(Keyword::TRANSIENT);
+    let kind =
+        match parser.parse_one_of_keywords(&[Keyword::TABLE, Keyword::SCHEMA, Keyword::DATABASE]) {
+            Some(Keyword::TABLE) => CloneKind::Table,
+            Some(Keyword::SCHEMA) => CloneKind::Schema,
+            Some(Keyword::DATABASE) => CloneKind::Database,
+            _ => return Ok(None),
+        };
+    let if_not_exists = parser.parse_keywords(&[Keyword::IF, Keyword::NOT, Keyword::EXISTS]);
+    let name = parser.parse_object_name(false)?;
+    if !parser.parse_keyword(Keyword::CLONE) {
+        return Ok(None);
+    }
+    let source = parser.parse_object_name(false)?;
+    let version = if matches!(
+        parser.peek_token().token,
+        Token::Word(ref word) if matches!(word.keyword, Keyword::AT | Keyword::BEFORE)
+    ) {
+        Some(TableVersion::Function(parser.parse_expr()?))
+    } else {
+        None
+    };
+    while parser.consume_token(&Token::SemiColon) {}
+    parser.expect_token(&Token::EOF)?;
+    Ok(Some(CloneStatement {
+        kind,
+        name,
+        source,
+        or_replace,
+        if_not_exists,
+        version,
+    }))
+}
+
+/// Table registered for a zero-copy clone of `source`: a copy of its metadata with a
+/// table uuid and location of its own, whose snapshots keep referring to the data files
+/// of the source until the clone diverges.
+#[must_use]
+pub fn cloned_table(source: &MetastoreTable, ident: &MetastoreTableIdent) -> MetastoreTable {
+    let mut metadata = source.metadata.clone();
+    metadata.table_uuid = Uuid::new_v4();
+    metadata.location = cloned_location(&metadata.location, &source.ident, ident);
+    metadata.last_updated_ms = Utc::now().timestamp_millis();
+    // The metadata files of the source are not part of the history of the clone
+    metadata.metadata_log.clear();
+    MetastoreTable {
+        ident: ident.clone(),
+        metadata_location: String::new(),
+        properties: source.properties.clone(),
+        volume_ident: source.volume_ident.clone(),
+        volume_location: source
+            .volume_location
+            .as_deref()
+            .map(|location| cloned_location(location, &source.ident, ident)),
+        is_temporary: false,
+        format: source.format.clone(),
+        metadata,
+    }
+}
+
+/// Makes `snapshot_id` the current snapshot of the main branch of a clone taken at an
+/// earlier point, dropping the snapshots that are not its ancestors along with the
+/// other branches and tags. `None` leaves the clone without snapshots, as the source
+/// was before its first one.
+pub fn pin_snapshot(metadata: &mut TableMetadata, snapshot_id: Option<i64>) {
+    let mut ancestors = HashSet::new();
+    let mut next = snapshot_id;
+    while let Some(id) = next {
+        ancestors.insert(id);
+        next = metadata
+            .snapshots
+            .get(&id)
+            .and_then(|snapshot| *snapshot.parent_snapshot_id());
+    }
+    metadata.current_snapshot_id = snapshot_id;
+    metadata.snapshots.retain(|id, _| ancestors.contains(id));
+    metadata
+        .snapshot_log
+        .retain(|entry| ancestors.contains(&entry.snapshot_id));
+    metadata.refs.retain(|name, _| name == "main");
+    match snapshot_id {
+        Some(snapshot_id) => {
+            if let Some(main) = metadata.refs.get_mut("main") {
+                main.snapshot_id = snapshot_id;
+            }
+        }
+        None => metadata.refs.clear(),
+    }
+}
+
+/// Location of the clone: the location of the source with its table path replaced,
+/// or the default table path if the location of the source does not end with it
+fn cloned_location(
+    location: &str,
+    source: &MetastoreTableIdent,
+    ident: &MetastoreTableIdent,
+) -> String {
+    let source_path = format!("{}/{}/{}", source.database, source.schema, source.table);
+    let path = format!("{}/{}/{}", ident.database, ident.schema, ident.table);
+    location
+        .strip_suffix(&source_path)
+        .map_or(path.clone(), |prefix| format!("{prefix}{path}"))
+}
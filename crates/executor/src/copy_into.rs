@@ -0,0 +1,510 @@
+use crate::error::{self as ex_error, Result};
+use catalog_metastore::{TableIdent, TableLoad};
+use chrono::NaiveDateTime;
+use datafusion::arrow::array::{Array, AsArray, Int64Array, RecordBatch, StringArray};
+use datafusion::arrow::compute::{cast, filter_record_batch, is_null};
+use datafusion::arrow::datatypes::{DataType, Field, Schema as ArrowSchema, SchemaRef};
+use datafusion::datasource::listing::ListingTableUrl;
+use datafusion::error::DataFusionError;
+use datafusion::execution::{SendableRecordBatchStream, TaskContext};
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::streaming::PartitionStream;
+use futures::StreamExt;
+use object_store::ObjectMeta;
+use snafu::ResultExt;
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+/// Column holding the error of a row read by `COPY INTO`, NULL if it can be loaded
+pub const COPY_ERROR_COLUMN: &str = "__copy_error";
+/// Column holding the name of the column the error of a row was found in
+pub const COPY_ERROR_COLUMN_NAME_COLUMN: &str = "__copy_error_column_name";
+
+/// File loaded by `COPY INTO`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CopyFile {
+    /// Name of the file in the results
+    pub name: String,
+    pub url: ListingTableUrl,
+    /// `ETag` and size identify the version of the file in the load history
+    pub e_tag: Option<String>,
+    pub size: u64,
+}
+
+impl CopyFile {
+    #[must_use]
+    pub fn new(name: String, url: ListingTableUrl, meta: &ObjectMeta) -> Self {
+        Self {
+            name,
+            url,
+            e_tag: meta.e_tag.clone(),
+            size: meta.size,
+        }
+    }
+}
+
+/// What `COPY INTO` does with files that have errors, set by `ON_ERROR`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnError {
+    /// Loads the rows without errors
+    Continue,
+    /// Skips a file once it has `limit` errors, `SKIP_FILE` stands for a limit of 1
+    SkipFile { limit: usize },
+    /// Skips a file once `percent` of its rows have errors
+    SkipFilePercent { percent: usize },
+    /// Fails the statement on the first error, nothing is loaded
+    #[default]
+    AbortStatement,
+}
+
+/// `VALIDATION_MODE` of `COPY INTO`: the files are checked rather than loaded
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationMode {
+    /// Returns the errors found in the files
+    ReturnErrors,
+    /// Returns the first rows if they have no errors
+    ReturnRows(usize),
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CopyOptions {
+    pub on_error: OnError,
+    pub validation_mode: Option<ValidationMode>,
+    /// Loads files again even if the load history has them as loaded
+    pub force: bool,
+}
+
+pub fn invalid_option(option: &str, value: &str) -> ex_error::Error {
+    ex_error::InvalidCopyOptionSnafu {
+        option: option.to_string(),
+        value: value.to_string(),
+    }
+    .build()
+}
+
+/// Parses a `TRUE` or `FALSE` copy option
+pub fn bool_option(option: &str, value: &str) -> Result<bool> {
+    match value.to_ascii_uppercase().as_str() {
+        "TRUE" => Ok(true),
+        "FALSE" => Ok(false),
+        _ => Err(invalid_option(option, value)),
+    }
+}
+
+impl CopyOptions {
+    /// Parses the copy options keyed by their uppercase names, options other than
+    /// `ON_ERROR` and `FORCE` are ignored
+    pub fn parse(
+        options: &BTreeMap<String, String>,
+        validation_mode: Option<&str>,
+    ) -> Result<Self> {
+        let on_error = options
+            .get("ON_ERROR")
+            .map(|value| OnError::parse(value))
+            .transpose()?
+            .unwrap_or_default();
+        let validation_mode = validation_mode.map(ValidationMode::parse).transpose()?;
+        let force = options
+            .get("FORCE")
+            .map(|value| bool_option("FORCE", value))
+            .transpose()?
+            .unwrap_or_default();
+        Ok(Self {
+            on_error,
+            validation_mode,
+            force,
+        })
+    }
+}
+
+impl OnError {
+    fn parse(value: &str) -> Result<Self> {
+        let upper = value.to_ascii_uppercase();
+        let on_error = match upper.as_str() {
+            "CONTINUE" => Self::Continue,
+            "SKIP_FILE" => Self::SkipFile { limit: 1 },
+            "ABORT_STATEMENT" => Self::AbortStatement,
+            _ => {
+                let limit = upper
+                    .strip_prefix("SKIP_FILE_")
+                    .ok_or_else(|| invalid_option("ON_ERROR", value))?;
+                match limit.strip_suffix('%') {
+                    Some(percent) => Self::SkipFilePercent {
+                        percent: percent.parse().context(ex_error::ParseCopyOptionSnafu {
+                            option: "ON_ERROR",
+                            value,
+                        })?,
+                    },
+                    None => Self::SkipFile {
+                        limit: limit.parse().context(ex_error::ParseCopyOptionSnafu {
+                            option: "ON_ERROR",
+                            value,
+                        })?,
+                    },
+                }
+            }
+        };
+        Ok(on_error)
+    }
+
+    /// Number of errors at which a file with `rows_parsed` rows is no longer loaded
+    #[must_use]
+    pub const fn error_limit(&self, rows_parsed: usize) -> usize {
+        match self {
+            Self::Continue => rows_parsed,
+            Self::SkipFile { limit } => *limit,
+            Self::SkipFilePercent { percent } => rows_parsed.saturating_mul(*percent).div_ceil(100),
+            Self::AbortStatement => 1,
+        }
+    }
+}
+
+impl ValidationMode {
+    fn parse(value: &str) -> Result<Self> {
+        let upper = value.to_ascii_uppercase();
+        match upper.as_str() {
+            // Only the first error of past loads is kept, so all errors are the errors
+            // of these files
+            "RETURN_ERRORS" | "RETURN_ALL_ERRORS" => Ok(Self::ReturnErrors),
+            _ => upper
+                .strip_prefix("RETURN_")
+                .and_then(|rows| rows.strip_suffix("_ROWS"))
+                .and_then(|rows| rows.parse().ok())
+                .map(Self::ReturnRows)
+                .ok_or_else(|| invalid_option("VALIDATION_MODE", value)),
+        }
+    }
+}
+
+/// Error found while loading a file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadError {
+    pub error: String,
+    /// Line of the file, `None` if the file could not be read at all
+    pub line: Option<usize>,
+    pub column_name: Option<String>,
+    /// Row of the file, counted from 1
+    pub row: Option<usize>,
+}
+
+impl LoadError {
+    /// Error of a file that could not be read
+    #[must_use]
+    pub fn file(error: &impl std::fmt::Display) -> Self {
+        Self {
+            error: error.to_string(),
+            line: None,
+            column_name: None,
+            row: None,
+        }
+    }
+}
+
+/// Rows and errors of a file, counted as its rows stream into the table
+#[derive(Debug, Default)]
+pub struct LoadCounts {
+    pub rows_parsed: usize,
+    pub rows_loaded: usize,
+    pub errors_seen: usize,
+    pub first_error: Option<LoadError>,
+}
+
+impl LoadCounts {
+    /// Whether the file has too many errors to be loaded at all
+    #[must_use]
+    pub const fn skipped(&self, on_error: OnError) -> bool {
+        self.errors_seen > 0 && self.errors_seen >= on_error.error_limit(self.rows_parsed)
+    }
+}
+
+/// Result of loading a single file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileLoad {
+    pub file: CopyFile,
+    pub rows_parsed: usize,
+    pub rows_loaded: usize,
+    pub error_limit: usize,
+    pub errors_seen: usize,
+    pub first_error: Option<LoadError>,
+}
+
+impl FileLoad {
+    #[must_use]
+    pub const fn status(&self) -> &'static str {
+        if self.errors_seen == 0 {
+            "LOADED"
+        } else if self.rows_loaded == 0 {
+            "LOAD_FAILED"
+        } else {
+            "PARTIALLY_LOADED"
+        }
+    }
+
+    /// Load history entry of the file, kept in the metastore
+    #[must_use]
+    pub fn table_load(&self, table: &TableIdent, loaded_at: NaiveDateTime) -> TableLoad {
+        let first_error = self.first_error.as_ref();
+        TableLoad {
+            table: table.clone(),
+            file: self.file.name.clone(),
+            e_tag: self.file.e_tag.clone(),
+            size: self.file.size,
+            status: self.status().to_string(),
+            rows_parsed: unsigned_count(self.rows_parsed),
+            rows_loaded: unsigned_count(self.rows_loaded),
+            error_limit: unsigned_count(self.error_limit),
+            errors_seen: unsigned_count(self.errors_seen),
+            first_error: first_error.map(|error| error.error.clone()),
+            first_error_line: first_error.and_then(|error| error.line).map(unsigned_count),
+            first_error_column_name: first_error.and_then(|error| error.column_name.clone()),
+            loaded_at,
+        }
+    }
+}
+
+/// Splits a batch read by `COPY INTO` into the rows to load and the errors of the
+/// other rows. `rows_before` is the number of rows of the file in previous batches and
+/// `header_lines` the number of lines before the first row of the file.
+pub fn split_rows(
+    batch: &RecordBatch,
+    schema: &SchemaRef,
+    rows_before: usize,
+    header_lines: usize,
+) -> Result<(RecordBatch, Vec<LoadError>)> {
+    let columns = schema.fields().len();
+    let error = cast(batch.column(columns), &DataType::Utf8).context(ex_error::ArrowSnafu)?;
+    let column_name =
+        cast(batch.column(columns + 1), &DataType::Utf8).context(ex_error::ArrowSnafu)?;
+    let (error, column_name) = (error.as_string::<i32>(), column_name.as_string::<i32>());
+    let errors = (0..batch.num_rows())
+        .filter(|row| error.is_valid(*row))
+        .map(|row| {
+            let number = rows_before + row + 1;
+            LoadError {
+                error: error.value(row).to_string(),
+                line: Some(header_lines + number),
+                column_name: column_name
+                    .is_valid(row)
+                    .then(|| column_name.value(row).to_string()),
+                row: Some(number),
+            }
+        })
+        .collect();
+
+    let rows = batch
+        .project(&(0..columns).collect::<Vec<_>>())
+        .context(ex_error::ArrowSnafu)?;
+    let rows = filter_record_batch(&rows, &is_null(error).context(ex_error::ArrowSnafu)?)
+        .context(ex_error::ArrowSnafu)?;
+    let rows = RecordBatch::try_new(schema.clone(), rows.columns().to_vec())
+        .context(ex_error::ArrowSnafu)?;
+    Ok((rows, errors))
+}
+
+/// Streams the rows of a file that have no errors, counting rows and errors in `counts`.
+///
+/// The stream fails on the first error with `ABORT_STATEMENT`, and once it ends if the
+/// file is to be skipped, so that none of the rows of the file are committed.
+pub fn load_stream(
+    input: SendableRecordBatchStream,
+    schema: SchemaRef,
+    header_lines: usize,
+    on_error: OnError,
+    counts: Arc<Mutex<LoadCounts>>,
+) -> SendableRecordBatchStream {
+    let end_counts = Arc::clone(&counts);
+    let rows_schema = Arc::clone(&schema);
+    let rows = input.map(move |batch| {
+        let batch = batch?;
+        #[allow(clippy::unwrap_used)]
+        let mut counts = counts.lock().unwrap();
+        let (rows, errors) = split_rows(&batch, &rows_schema, counts.rows_parsed, header_lines)
+            .map_err(|error| DataFusionError::External(Box::new(error)))?;
+        counts.rows_parsed += batch.num_rows();
+        counts.rows_loaded += rows.num_rows();
+        counts.errors_seen += errors.len();
+        if counts.first_error.is_none() {
+            counts.first_error = errors.into_iter().next();
+        }
+        if on_error == OnError::AbortStatement && counts.first_error.is_some() {
+            return Err(DataFusionError::Execution(
+                "File has errors, the load is aborted".to_string(),
+            ));
+        }
+        Ok(rows)
+    });
+    let end = futures::stream::once(async move {
+        #[allow(clippy::unwrap_used)]
+        let skipped = end_counts.lock().unwrap().skipped(on_error);
+        skipped.then(|| {
+            Err(DataFusionError::Execution(
+                "File has too many errors, it is skipped".to_string(),
+            ))
+        })
+    })
+    .filter_map(futures::future::ready);
+    Box::pin(RecordBatchStreamAdapter::new(schema, rows.chain(end)))
+}
+
+/// Errors of all the rows of a file, for `VALIDATION_MODE = RETURN_ERRORS`
+pub async fn file_errors(
+    mut input: SendableRecordBatchStream,
+    schema: &SchemaRef,
+    header_lines: usize,
+) -> Result<Vec<LoadError>> {
+    let mut rows_parsed = 0;
+    let mut errors = Vec::new();
+    while let Some(batch) = input.next().await {
+        let batch = batch.context(ex_error::DataFusionSnafu)?;
+        let (_, batch_errors) = split_rows(&batch, schema, rows_parsed, header_lines)?;
+        rows_parsed += batch.num_rows();
+        errors.extend(batch_errors);
+    }
+    Ok(errors)
+}
+
+/// Partition of a table scan handing out a stream that is already open, so that the
+/// rows of a file can be inserted as they are read
+pub struct OnceStream {
+    schema: SchemaRef,
+    stream: Mutex<Option<SendableRecordBatchStream>>,
+}
+
+impl OnceStream {
+    #[must_use]
+    pub fn new(stream: SendableRecordBatchStream) -> Self {
+        Self {
+            schema: stream.schema(),
+            stream: Mutex::new(Some(stream)),
+        }
+    }
+}
+
+impl std::fmt::Debug for OnceStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OnceStream")
+            .field("schema", &self.schema)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartitionStream for OnceStream {
+    fn schema(&self) -> &SchemaRef {
+        &self.schema
+    }
+
+    fn execute(&self, _ctx: Arc<TaskContext>) -> SendableRecordBatchStream {
+        #[allow(clippy::unwrap_used)]
+        let stream = self.stream.lock().unwrap().take();
+        stream.unwrap_or_else(|| {
+            Box::pin(RecordBatchStreamAdapter::new(
+                Arc::clone(&self.schema),
+                futures::stream::once(async {
+                    Err(DataFusionError::Execution(
+                        "The stream was already read".to_string(),
+                    ))
+                }),
+            ))
+        })
+    }
+}
+
+fn count(value: usize) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
+
+fn unsigned_count(value: usize) -> u64 {
+    u64::try_from(value).unwrap_or(u64::MAX)
+}
+
+/// Result set of `COPY INTO` with a row per loaded file, as returned by Snowflake
+pub fn load_results(loads: &[FileLoad]) -> Result<(RecordBatch, SchemaRef)> {
+    let schema = Arc::new(ArrowSchema::new(vec![
+        Field::new("file", DataType::Utf8, false),
+        Field::new("status", DataType::Utf8, false),
+        Field::new("rows_parsed", DataType::Int64, false),
+        Field::new("rows_loaded", DataType::Int64, false),
+        Field::new("error_limit", DataType::Int64, false),
+        Field::new("errors_seen", DataType::Int64, false),
+        Field::new("first_error", DataType::Utf8, true),
+        Field::new("first_error_line", DataType::Int64, true),
+        Field::new("first_error_column_name", DataType::Utf8, true),
+    ]));
+    let first_errors = loads
+        .iter()
+        .map(|load| load.first_error.as_ref())
+        .collect::<Vec<_>>();
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(StringArray::from_iter_values(
+                loads.iter().map(|load| &load.file.name),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                loads.iter().map(FileLoad::status),
+            )),
+            Arc::new(Int64Array::from_iter_values(
+                loads.iter().map(|load| count(load.rows_parsed)),
+            )),
+            Arc::new(Int64Array::from_iter_values(
+                loads.iter().map(|load| count(load.rows_loaded)),
+            )),
+            Arc::new(Int64Array::from_iter_values(
+                loads.iter().map(|load| count(load.error_limit)),
+            )),
+            Arc::new(Int64Array::from_iter_values(
+                loads.iter().map(|load| count(load.errors_seen)),
+            )),
+            Arc::new(StringArray::from_iter(
+                first_errors
+                    .iter()
+                    .map(|error| error.map(|error| &error.error)),
+            )),
+            Arc::new(Int64Array::from_iter(
+                first_errors
+                    .iter()
+                    .map(|error| error.and_then(|error| error.line).map(count)),
+            )),
+            Arc::new(StringArray::from_iter(
+                first_errors
+                    .iter()
+                    .map(|error| error.and_then(|error| error.column_name.as_ref())),
+            )),
+        ],
+    )
+    .context(ex_error::ArrowSnafu)?;
+    Ok((batch, schema))
+}
+
+/// Result set of `VALIDATION_MODE = RETURN_ERRORS` with a row per error
+pub fn validation_errors(errors: &[(String, LoadError)]) -> Result<(RecordBatch, SchemaRef)> {
+    let schema = Arc::new(ArrowSchema::new(vec![
+        Field::new("error", DataType::Utf8, false),
+        Field::new("file", DataType::Utf8, false),
+        Field::new("line", DataType::Int64, true),
+        Field::new("column_name", DataType::Utf8, true),
+        Field::new("row_number", DataType::Int64, true),
+    ]));
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(StringArray::from_iter_values(
+                errors.iter().map(|(_, error)| &error.error),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                errors.iter().map(|(file, _)| file),
+            )),
+            Arc::new(Int64Array::from_iter(
+                errors.iter().map(|(_, error)| error.line.map(count)),
+            )),
+            Arc::new(StringArray::from_iter(
+                errors.iter().map(|(_, error)| error.column_name.as_ref()),
+            )),
+            Arc::new(Int64Array::from_iter(
+                errors.iter().map(|(_, error)| error.row.map(count)),
+            )),
+        ],
+    )
+    .context(ex_error::ArrowSnafu)?;
+    Ok((batch, schema))
+}
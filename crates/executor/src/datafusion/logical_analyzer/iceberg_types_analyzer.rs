@@ -1,4 +1,4 @@
-use datafusion::arrow::datatypes::DataType;
+use datafusion::arrow::datatypes::{DataType, TimeUnit};
 use datafusion::error::Result as DFResult;
 use datafusion::logical_expr::LogicalPlan;
 use datafusion::optimizer::AnalyzerRule;
@@ -10,17 +10,19 @@ use datafusion_expr::{Cast, Expr};
 #[derive(Debug)]
 pub struct IcebergTypesAnalyzer;
 
-/// Rewrites expressions in the logical plan to cast `UInt64` columns to `Int64`
-/// for compatibility with the Apache Iceberg format.
+/// Rewrites expressions in the logical plan to cast columns of types Apache Iceberg
+/// has no counterpart for to the Iceberg compatible types, see `iceberg_type`.
 ///
 /// Currently, Apache Iceberg does not support the `UInt64` data type. When creating
 /// a table, Iceberg coerces such fields to `Int64`. This mismatch leads to schema
 /// validation errors during insert or write operations if the input data retains
-/// the `UInt64` type.
+/// the `UInt64` type. The same holds for the other types of `iceberg_type`, which
+/// Avro and ORC files are read with: small integers, half floats, 64 bit dates and
+/// times and timestamps other than microsecond ones.
 ///
-/// This rule transforms the logical plan by replacing any reference to `UInt64`
-/// columns with an explicit `CAST(... AS Int64)` expression. This ensures the input
-/// data conforms to the table schema expected by Iceberg.
+/// This rule transforms the logical plan by replacing any reference to such columns
+/// with an explicit `CAST(...)` expression. This ensures the input data conforms to
+/// the table schema expected by Iceberg.
 ///
 /// ⚠️ Note: Converting from `UInt64` to `Int64` may result in **integer overflow**
 /// if the original values exceed the maximum value of `Int64` (i.e., values > `i64::MAX`).
@@ -55,11 +57,11 @@ fn analyze_internal(plan: LogicalPlan) -> DFResult<Transformed<LogicalPlan>> {
         let transformed_expr = expr.transform_up(|e| {
             if let Expr::Column(col) = &e
                 && let Ok(field) = schema.field_with_unqualified_name(&col.name)
-                && field.data_type() == &DataType::UInt64
+                && let Some(data_type) = iceberg_type(field.data_type())
             {
                 let casted = Expr::Cast(Cast {
                     expr: Box::new(Expr::Column(col.clone())),
-                    data_type: DataType::Int64,
+                    data_type,
                 })
                 .alias(col.name.clone());
 
@@ -73,3 +75,26 @@ fn analyze_internal(plan: LogicalPlan) -> DFResult<Transformed<LogicalPlan>> {
 
     Ok(new_plan)
 }
+
+/// Iceberg compatible type of a column of a type Iceberg does not support, `None` if
+/// the type is supported
+fn iceberg_type(data_type: &DataType) -> Option<DataType> {
+    match data_type {
+        DataType::Int8 | DataType::Int16 | DataType::UInt8 | DataType::UInt16 => {
+            Some(DataType::Int32)
+        }
+        DataType::UInt32 | DataType::UInt64 => Some(DataType::Int64),
+        DataType::Float16 => Some(DataType::Float32),
+        DataType::Date64 => Some(DataType::Date32),
+        DataType::Time32(_) | DataType::Time64(TimeUnit::Nanosecond) => {
+            Some(DataType::Time64(TimeUnit::Microsecond))
+        }
+        DataType::Timestamp(unit, tz) if *unit != TimeUnit::Microsecond => {
+            Some(DataType::Timestamp(TimeUnit::Microsecond, tz.clone()))
+        }
+        DataType::Dictionary(_, value_type) => {
+            Some(iceberg_type(value_type).unwrap_or_else(|| value_type.as_ref().clone()))
+        }
+        _ => None,
+    }
+}
@@ -3,8 +3,9 @@ use datafusion::{
         array::{Array, ArrayRef, BooleanArray, RecordBatch, StringArray, downcast_array},
         compute::{
             filter, filter_record_batch,
+            kernels::boolean::not,
             kernels::cmp::{distinct, eq},
-            or, or_kleene,
+            or, or_kleene, prep_null_mask_filter,
         },
         datatypes::Schema,
     },
@@ -45,6 +46,9 @@ pub(crate) static DATA_FILE_PATH_COLUMN: &str = "__data_file_path";
 pub(crate) static MANIFEST_FILE_PATH_COLUMN: &str = "__manifest_file_path";
 pub(crate) static MERGE_UPDATED_COLUMN: &str = "__merge_row_updated";
 pub(crate) static MERGE_INSERTED_COLUMN: &str = "__merge_row_inserted";
+pub(crate) static MERGE_DELETED_COLUMN: &str = "__merge_row_deleted";
+pub(crate) static UPDATE_ROW_ID_COLUMN: &str = "__update_row_id";
+pub(crate) static UPDATE_ROW_MATCH_COLUMN: &str = "__update_row_match";
 static THREAD_FILE_RATIO: usize = 4;
 
 #[derive(Debug)]
@@ -155,6 +159,7 @@ impl ExecutionPlan for MergeIntoCOWSinkExec {
         let matching_files: Arc<Mutex<Option<ManifestAndDataFiles>>> = Arc::default();
         let updated_rows: Arc<AtomicI64> = Arc::new(AtomicI64::new(0));
         let inserted_rows: Arc<AtomicI64> = Arc::new(AtomicI64::new(0));
+        let deleted_rows: Arc<AtomicI64> = Arc::new(AtomicI64::new(0));
 
         // `Count` metrics that surface in `EXPLAIN ANALYZE` as
         // `metrics=[updated_rows=…, inserted_rows=…, deleted_rows=…]` on this
@@ -179,6 +184,7 @@ impl ExecutionPlan for MergeIntoCOWSinkExec {
             input_batches,
             updated_rows.clone(),
             inserted_rows.clone(),
+            deleted_rows.clone(),
         );
 
         let stream = futures::stream::once({
@@ -187,6 +193,7 @@ impl ExecutionPlan for MergeIntoCOWSinkExec {
             let schema = schema.clone();
             let updated_rows = Arc::clone(&updated_rows);
             let inserted_rows = Arc::clone(&inserted_rows);
+            let deleted_rows = Arc::clone(&deleted_rows);
             let updated_rows_metric = updated_rows_metric.clone();
             let inserted_rows_metric = inserted_rows_metric.clone();
             let deleted_rows_metric = deleted_rows_metric.clone();
@@ -220,23 +227,22 @@ impl ExecutionPlan for MergeIntoCOWSinkExec {
                     })?
                 };
 
-                if !datafiles.is_empty() {
-                    // Commit transaction on Iceberg table
-                    if matching_files.is_empty() {
-                        table
-                            .new_transaction(branch.as_deref())
-                            .append_data(datafiles)
-                            .commit()
-                            .await
-                            .context(error::IcebergSnafu)?;
-                    } else {
-                        table
-                            .new_transaction(branch.as_deref())
-                            .overwrite(datafiles, matching_files)
-                            .commit()
-                            .await
-                            .context(error::IcebergSnafu)?;
-                    }
+                // Commit transaction on Iceberg table. Matching files are overwritten even
+                // when no datafiles were written, since all of their rows may be deleted.
+                if !matching_files.is_empty() {
+                    table
+                        .new_transaction(branch.as_deref())
+                        .overwrite(datafiles, matching_files)
+                        .commit()
+                        .await
+                        .context(error::IcebergSnafu)?;
+                } else if !datafiles.is_empty() {
+                    table
+                        .new_transaction(branch.as_deref())
+                        .append_data(datafiles)
+                        .commit()
+                        .await
+                        .context(error::IcebergSnafu)?;
                 }
                 // Refresh the cached table with the latest snapshot so subsequent scans
                 // see the results of this MERGE operation.
@@ -246,8 +252,7 @@ impl ExecutionPlan for MergeIntoCOWSinkExec {
                 // Return a one-row result for DML, so clients don't render "No data result" on success.
                 let updated = updated_rows.load(Ordering::Relaxed);
                 let inserted = inserted_rows.load(Ordering::Relaxed);
-                // MERGE DELETE is not supported yet
-                let deleted = 0i64;
+                let deleted = deleted_rows.load(Ordering::Relaxed);
 
                 // Publish per-clause counts to the `MetricsSet` so
                 // `EXPLAIN ANALYZE` shows them on the MergeIntoSinkExec line.
@@ -290,15 +295,17 @@ impl ExecutionPlan for MergeIntoCOWSinkExec {
 }
 
 pin_project! {
-    /// Stream wrapper that counts per-action MERGE rows (insert/update markers) and projects away
-    /// auxiliary merge columns before writing to data files.
+    /// Stream wrapper that counts per-action MERGE rows (insert/update/delete markers), drops
+    /// deleted rows and projects away auxiliary merge columns before writing to data files.
     pub struct MergeCOWCountAndProjectStream {
         projection_indices: Vec<usize>,
         projected_schema: Arc<Schema>,
         updated_idx: Option<usize>,
         inserted_idx: Option<usize>,
+        deleted_idx: Option<usize>,
         updated_rows: Arc<AtomicI64>,
         inserted_rows: Arc<AtomicI64>,
+        deleted_rows: Arc<AtomicI64>,
 
         #[pin]
         input: SendableRecordBatchStream,
@@ -310,11 +317,13 @@ impl MergeCOWCountAndProjectStream {
         input: SendableRecordBatchStream,
         updated_rows: Arc<AtomicI64>,
         inserted_rows: Arc<AtomicI64>,
+        deleted_rows: Arc<AtomicI64>,
     ) -> Self {
         let input_schema = input.schema();
 
         let updated_idx = input_schema.index_of(MERGE_UPDATED_COLUMN).ok();
         let inserted_idx = input_schema.index_of(MERGE_INSERTED_COLUMN).ok();
+        let deleted_idx = input_schema.index_of(MERGE_DELETED_COLUMN).ok();
 
         // Drop auxiliary columns so we only write table columns to parquet
         let projection_indices: Vec<usize> = input_schema
@@ -328,6 +337,7 @@ impl MergeCOWCountAndProjectStream {
                     && name != MANIFEST_FILE_PATH_COLUMN
                     && name != MERGE_UPDATED_COLUMN
                     && name != MERGE_INSERTED_COLUMN
+                    && name != MERGE_DELETED_COLUMN
                 {
                     Some(i)
                 } else {
@@ -348,8 +358,10 @@ impl MergeCOWCountAndProjectStream {
             projected_schema,
             updated_idx,
             inserted_idx,
+            deleted_idx,
             updated_rows,
             inserted_rows,
+            deleted_rows,
             input,
         }
     }
@@ -396,6 +408,17 @@ impl Stream for MergeCOWCountAndProjectStream {
                             "Failed to project MERGE record batch: {e}"
                         ))
                     })?;
+
+                // Deleted rows are counted and left out of the rewritten data files
+                if let Some(deleted_idx) = *project.deleted_idx
+                    && let Some(col) = batch.columns().get(deleted_idx)
+                {
+                    let deleted = downcast_array::<BooleanArray>(col.as_ref());
+                    let n = usize_to_i64_saturating(count_true_and_valid(&deleted));
+                    project.deleted_rows.fetch_add(n, Ordering::Relaxed);
+                    let keep = not(&prep_null_mask_filter(&deleted))?;
+                    return Poll::Ready(Some(Ok(filter_record_batch(&projected, &keep)?)));
+                }
                 Poll::Ready(Some(Ok(projected)))
             }
             Poll::Ready(Some(Err(e))) => Poll::Ready(Some(Err(e))),
@@ -1,4 +1,4 @@
-use crate::models::QueryContext;
+use crate::models::{QueryContext, SessionMetadataAttr};
 use crate::query_types::QueryId;
 use crate::running_queries::RunningQueries;
 use datafusion::arrow::array::{ListArray, ListBuilder, StringBuilder};
@@ -21,6 +21,8 @@ pub struct SessionContextExprRewriter {
     pub query_context: QueryContext,
     pub recent_queries: Arc<RwLock<VecDeque<QueryId>>>,
     pub running_queries: Arc<dyn RunningQueries>,
+    pub current_transaction: Option<i64>,
+    pub last_transaction: Option<i64>,
 }
 
 impl SessionContextExprRewriter {
@@ -97,6 +99,12 @@ impl TreeNodeRewriter for ExprRewriter<'_> {
                 "current_version" => Some(utf8_val(&self.rewriter.version)),
                 "current_client" => Some(utf8_val(format!("Embucket {}", &self.rewriter.version))),
                 "current_session" => Some(utf8_val(&self.rewriter.session_id)),
+                "current_transaction" => Some(ScalarValue::Utf8(
+                    self.rewriter.current_transaction.map(|id| id.to_string()),
+                )),
+                "last_transaction" => Some(ScalarValue::Utf8(
+                    self.rewriter.last_transaction.map(|id| id.to_string()),
+                )),
                 "last_query_id" => {
                     let index = match fun.args.first() {
                         Some(Expr::Literal(value, _)) => value.clone().try_into().unwrap_or(-1),
@@ -104,6 +112,14 @@ impl TreeNodeRewriter for ExprRewriter<'_> {
                     };
                     Some(self.rewriter.last_query_id(index)?)
                 }
+                "current_user" => Some(utf8_val(
+                    self.rewriter
+                        .query_context
+                        .session_metadata
+                        .as_ref()
+                        .and_then(|metadata| metadata.attr(SessionMetadataAttr::UserName))
+                        .unwrap_or_default(),
+                )),
                 "current_ip_address" => Some(utf8_val(
                     self.rewriter
                         .query_context
@@ -253,6 +253,210 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("File format option {option} is not supported for {format} files"))]
+    UnsupportedFileFormatOption {
+        option: String,
+        format: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Invalid value '{value}' for copy option {option}"))]
+    InvalidCopyOption {
+        option: String,
+        value: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Invalid value '{value}' for copy option {option}"))]
+    ParseCopyOption {
+        option: String,
+        value: String,
+        #[snafu(source)]
+        error: std::num::ParseIntError,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display(
+        "{error}\n  File '{file}'{}",
+        line.map(|line| format!(", line {line}")).unwrap_or_default()
+    ))]
+    CopyIntoFileFailed {
+        file: String,
+        line: Option<usize>,
+        error: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("{source}\n  File '{file}'"))]
+    CopyIntoFileRead {
+        file: String,
+        #[snafu(source(from(Error, Box::new)))]
+        source: Box<Error>,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display(
+        "Files already existing at the unload destination: {file}. Use overwrite option to force unloading."
+    ))]
+    UnloadFileExists {
+        file: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Failed to write unloaded file {file}: {error}"))]
+    UnloadFileWrite {
+        file: String,
+        #[snafu(source)]
+        error: std::io::Error,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display(
+        "COPY INTO <location> unloads to stages and to locations of volumes only, not to {url}"
+    ))]
+    UnloadLocationNotAllowed {
+        url: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Parquet error: {error}"))]
+    Parquet {
+        #[snafu(source)]
+        error: datafusion::parquet::errors::ParquetError,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("ORC error: {error}"))]
+    Orc {
+        #[snafu(source)]
+        error: orc_rust::error::OrcError,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Invalid argument {argument} for INFER_SCHEMA: {reason}"))]
+    InvalidInferSchemaArgument {
+        argument: String,
+        reason: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Invalid argument {argument} for INFER_SCHEMA: {reason}"))]
+    ParseInferSchemaArgument {
+        argument: String,
+        reason: String,
+        #[snafu(source)]
+        error: std::num::ParseIntError,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Invalid template for table {table}: {reason}"))]
+    InvalidTableTemplate {
+        table: String,
+        reason: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Invalid template for table {table}: {reason}"))]
+    ParseTableTemplate {
+        table: String,
+        reason: String,
+        #[snafu(source)]
+        error: serde_json::Error,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Invalid external table {table}: {reason}"))]
+    InvalidExternalTable {
+        table: String,
+        reason: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Invalid partitioning of table {table}: {reason}"))]
+    InvalidPartitionSpec {
+        table: String,
+        reason: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Invalid partitioning of table {table}: {error}"))]
+    PartitionSpecIcebergSpec {
+        table: String,
+        #[snafu(source(from(iceberg_rust::spec::error::Error, Box::new)))]
+        error: Box<iceberg_rust::spec::error::Error>,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Time travel data is not available for table {table}: {reason}"))]
+    InvalidTimeTravel {
+        table: String,
+        reason: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Invalid change to table {table}: {reason}"))]
+    InvalidSchemaChange {
+        table: String,
+        reason: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Invalid change to table {table}: {error}"))]
+    SchemaChangeIcebergSpec {
+        table: String,
+        #[snafu(source(from(iceberg_rust::spec::error::Error, Box::new)))]
+        error: Box<iceberg_rust::spec::error::Error>,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Invalid value '{value}' for file format option {option}"))]
+    InvalidFileFormatOption {
+        option: String,
+        value: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Invalid value '{value}' for file format option {option}"))]
+    ParseFileFormatOption {
+        option: String,
+        value: String,
+        #[snafu(source)]
+        error: std::num::ParseIntError,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Invalid value '{value}' for file format option {option}"))]
+    TokenizeFileFormatOption {
+        option: String,
+        value: String,
+        #[snafu(source)]
+        error: sqlparser::tokenizer::TokenizerError,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[snafu(display("Cannot refresh catalog list: {source}"))]
     RefreshCatalogList {
         #[snafu(source(from(CatalogError, Box::new)))]
@@ -300,6 +504,15 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Invalid URL {url}: {error}"))]
+    InvalidUrl {
+        url: String,
+        #[snafu(source)]
+        error: url::ParseError,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[cfg(feature = "dedicated-executor")]
     #[snafu(display("Threaded Job error: {error}: {backtrace}"))]
     JobError {
@@ -375,6 +588,27 @@ pub enum Error {
         #[snafu(implicit)]
         location: Location,
     },
+    #[snafu(display("Insufficient privileges to operate on {object_type} '{object_name}'"))]
+    InsufficientPrivileges {
+        object_type: String,
+        object_name: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Requested role '{role}' is not assigned to the executing user"))]
+    RoleNotAssigned {
+        role: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("The role of the session can only be changed with USE ROLE"))]
+    RoleSessionVariable {
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[snafu(display("Only primitive statements are supported"))]
     OnlyPrimitiveStatements {
         #[snafu(implicit)]
@@ -486,6 +720,35 @@ pub enum Error {
         #[snafu(implicit)]
         location: Location,
     },
+    #[snafu(display("Only UPDATE statements are supported"))]
+    OnlyUpdateStatements {
+        #[snafu(implicit)]
+        location: Location,
+    },
+    #[snafu(display("UPDATE statement target must be a single table"))]
+    UpdateTargetMustBeTable {
+        #[snafu(implicit)]
+        location: Location,
+    },
+    #[snafu(display("Only DELETE statements are supported"))]
+    OnlyDeleteStatements {
+        #[snafu(implicit)]
+        location: Location,
+    },
+    #[snafu(display("DELETE statement target must be a single table"))]
+    DeleteTargetMustBeTable {
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Transaction {id} could not be committed: {error}"))]
+    TransactionCommit {
+        id: i64,
+        #[snafu(source(from(catalog_metastore::error::Error, Box::new)))]
+        error: Box<catalog_metastore::error::Error>,
+        #[snafu(implicit)]
+        location: Location,
+    },
 
     #[snafu(display("LogicalPlan Extension {name} requires exactly {expected} child(ren)"))]
     LogicalExtensionChildCount {
@@ -534,8 +797,49 @@ pub enum Error {
         location: Location,
     },
 
-    #[snafu(display("Stages are currently not supported"))]
-    StagesNotSupported {
+    #[snafu(display("Stage {stage} not found"))]
+    StageNotFound {
+        stage: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Stage location {url} overlaps the data of table {table}"))]
+    StageOverlapsTable {
+        url: String,
+        table: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("File format {file_format} not found"))]
+    FileFormatNotFound {
+        file_format: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Failed to write stage file {path}: {error}"))]
+    StageFileWrite {
+        path: String,
+        #[snafu(source)]
+        error: std::io::Error,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("PUT and GET are only supported for internal stages, {stage} is external"))]
+    ExternalStageFileTransfer {
+        stage: String,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
+    #[snafu(display("Invalid pattern '{pattern}': {error}"))]
+    InvalidPattern {
+        pattern: String,
+        #[snafu(source)]
+        error: regex::Error,
         #[snafu(implicit)]
         location: Location,
     },
@@ -563,6 +867,14 @@ pub enum Error {
         location: Location,
     },
 
+    // Failed result of a finished query, kept to be fetched again
+    #[snafu(display("{source}"))]
+    KeptQueryResult {
+        source: std::sync::Arc<Error>,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[snafu(display("Query {query_id} isn't running"))]
     QueryIsntRunning {
         query_id: QueryId,
@@ -570,6 +882,13 @@ pub enum Error {
         location: Location,
     },
 
+    #[snafu(display("Result of query {query_id} was already released"))]
+    QueryResultReleased {
+        query_id: QueryId,
+        #[snafu(implicit)]
+        location: Location,
+    },
+
     #[snafu(display("Query with request_id {request_id} isn't running"))]
     QueryByRequestIdIsntRunning {
         request_id: uuid::Uuid,
@@ -647,10 +966,10 @@ pub enum Error {
 impl Error {
     #[must_use]
     pub fn query_id(&self) -> QueryId {
-        if let Self::QueryExecution { query_id, .. } = self {
-            *query_id
-        } else {
-            QueryId::default()
+        match self {
+            Self::QueryExecution { query_id, .. } => *query_id,
+            Self::KeptQueryResult { source, .. } => source.query_id(),
+            _ => QueryId::default(),
         }
     }
     #[must_use]
@@ -699,6 +1018,9 @@ pub enum ObjectType {
     Database,
     Schema,
     Table,
+    Stage,
+    FileFormat,
+    ExternalTable,
 }
 
 impl Display for ObjectType {
@@ -708,6 +1030,9 @@ impl Display for ObjectType {
             Self::Database => write!(f, "database"),
             Self::Schema => write!(f, "schema"),
             Self::Table => write!(f, "table"),
+            Self::Stage => write!(f, "stage"),
+            Self::FileFormat => write!(f, "file format"),
+            Self::ExternalTable => write!(f, "external table"),
         }
     }
 }
@@ -35,6 +35,7 @@ pub enum ErrorCode {
     DataFusionSqlParse,
     DataFusionSql,
     EntityNotFound(Entity, OperationOn),
+    InsufficientPrivileges,
     Other,
     UnsupportedFeature,
     Timeout,
@@ -51,12 +52,14 @@ impl Display for ErrorCode {
             Self::Timeout => 630,
             Self::Cancelled => 684,
             Self::HistoricalQueryError => 1001,
+            Self::InsufficientPrivileges => 3001,
             Self::DataFusionSqlParse => 1003,
             Self::DataFusionSql => 2003,
             Self::EntityNotFound(entity, operation) => match (entity, operation) {
                 (Entity::Table, OperationOn::Table(..))
                 | (Entity::Schema, OperationOn::Table(..))
-                | (Entity::Database, OperationOn::Table(..)) => 2003,
+                | (Entity::Database, OperationOn::Table(..))
+                | (Entity::Stage | Entity::FileFormat, _) => 2003,
                 _ => 2043,
             },
             _ => 10001,
@@ -0,0 +1,665 @@
+use crate::error::{self as ex_error, Result};
+use crate::file_formats::{FileFormatOptions, parse_format_options, rewrite_file};
+use crate::query::UserQuery;
+use crate::stages::StageReference;
+use async_trait::async_trait;
+use catalog_metastore::ExternalTableFile;
+use chrono::NaiveDateTime;
+use datafusion::arrow::array::{RecordBatch, StringArray};
+use datafusion::arrow::datatypes::{DataType, Field, Schema as ArrowSchema, SchemaRef};
+use datafusion::catalog::{Session, TableProvider};
+use datafusion::datasource::default_table_source::provider_as_source;
+use datafusion::datasource::listing::{
+    ListingOptions, ListingTable, ListingTableConfig, ListingTableUrl,
+};
+use datafusion::functions_window::expr_fn::row_number;
+use datafusion::physical_plan::empty::EmptyExec;
+use datafusion_common::{Column, DataFusionError, Result as DFResult, project_schema};
+use datafusion_expr::expr_rewriter::unnormalize_col;
+use datafusion_expr::utils::conjunction;
+use datafusion_expr::{
+    Expr as DFExpr, LogicalPlan, LogicalPlanBuilder, TableProviderFilterPushDown, TableType, lit,
+};
+use datafusion_physical_plan::ExecutionPlan;
+use object_store::ObjectMeta;
+use snafu::ResultExt;
+use sqlparser::ast::{Expr, Ident, ObjectName, visit_expressions};
+use sqlparser::dialect::SnowflakeDialect;
+use sqlparser::keywords::Keyword;
+use sqlparser::parser::{Parser, ParserError};
+use sqlparser::tokenizer::Token;
+use std::any::Any;
+use std::collections::BTreeMap;
+use std::ops::ControlFlow;
+use std::sync::Arc;
+
+type ParseResult<T> = std::result::Result<T, ParserError>;
+
+/// Column of an external table holding each row as an object
+pub const VALUE_COLUMN: &str = "value";
+/// Pseudo-column with the path of the file a row was read from, relative to the stage
+pub const FILENAME_COLUMN: &str = "metadata$filename";
+/// Pseudo-column with the number of a row in its file, starting at 1
+pub const FILE_ROW_NUMBER_COLUMN: &str = "metadata$file_row_number";
+
+/// Column definition of `CREATE EXTERNAL TABLE`: `<name> <type> AS <expr>`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalColumnDef {
+    pub name: Ident,
+    pub data_type: String,
+    pub expression: Expr,
+}
+
+/// External table statement: `CREATE`, `ALTER` or `DROP EXTERNAL TABLE` over a stage.
+///
+/// These are parsed here rather than by the SQL parser, which only knows the
+/// `CREATE EXTERNAL TABLE ... STORED AS` form of DataFusion. That form is left to the
+/// SQL parser.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExternalTableStatement {
+    Create {
+        name: ObjectName,
+        or_replace: bool,
+        if_not_exists: bool,
+        columns: Vec<ExternalColumnDef>,
+        partition_by: Vec<Ident>,
+        location: StageReference,
+        pattern: Option<String>,
+        /// `FILE_FORMAT` options keyed by their uppercase names
+        file_format: BTreeMap<String, String>,
+        auto_refresh: bool,
+        refresh_on_create: bool,
+        comment: Option<String>,
+    },
+    /// `ALTER EXTERNAL TABLE ... REFRESH ['<path>']`, the path is relative to the location
+    /// of the table
+    Refresh {
+        name: ObjectName,
+        if_exists: bool,
+        path: Option<String>,
+    },
+    SetAutoRefresh {
+        name: ObjectName,
+        if_exists: bool,
+        auto_refresh: bool,
+    },
+    Drop {
+        name: ObjectName,
+        if_exists: bool,
+    },
+}
+
+// Cheap check to avoid tokenizing every statement twice
+fn looks_like_external_table_statement(sql: &str) -> bool {
+    let words = sql
+        .split_whitespace()
+        .take(6)
+        .map(str::to_ascii_uppercase)
+        .collect::<Vec<_>>();
+    matches!(
+        words.first().map(String::as_str),
+        Some("CREATE" | "ALTER" | "DROP")
+    ) && words
+        .windows(2)
+        .any(|pair| pair[0] == "EXTERNAL" && pair[1] == "TABLE")
+}
+
+/// Consumes the next word if it is `word`, for words that are no keywords of the parser
+fn parse_word(parser: &mut Parser, word: &str) -> bool {
+    match parser.peek_token().token {
+        Token::Word(next) if next.value.eq_ignore_ascii_case(word) => {
+            parser.next_token();
+            true
+        }
+        _ => false,
+    }
+}
+
+fn parse_bool(parser: &mut Parser) -> ParseResult<bool> {
+    parser.expect_token(&Token::Eq)?;
+    if parser.parse_keyword(Keyword::TRUE) {
+        Ok(true)
+    } else if parser.parse_keyword(Keyword::FALSE) {
+        Ok(false)
+    } else {
+        parser.expected("TRUE or FALSE", parser.peek_token())
+    }
+}
+
+fn parse_string(parser: &mut Parser) -> ParseResult<String> {
+    parser.expect_token(&Token::Eq)?;
+    parser.parse_literal_string()
+}
+
+/// Parses the stage reference of `LOCATION`. The tokenizer splits a reference such as
+/// `@my_stage/path/` into several tokens, which are joined back up to the next
+/// whitespace.
+fn parse_stage_location(parser: &mut Parser) -> ParseResult<StageReference> {
+    let mut location = String::new();
+    loop {
+        match parser.peek_token_no_skip().token {
+            Token::Whitespace(_) if location.is_empty() => {}
+            Token::Whitespace(_) | Token::EOF | Token::SemiColon => break,
+            token => location.push_str(&token.to_string()),
+        }
+        parser.next_token_no_skip();
+    }
+    StageReference::parse(&location)
+}
+
+/// Parses a column definition, returns `None` if the column has no `AS <expr>` as in
+/// the DataFusion form of the statement
+fn parse_column(parser: &mut Parser) -> ParseResult<Option<ExternalColumnDef>> {
+    let name = parser.parse_identifier()?;
+    let data_type = parser.parse_data_type()?.to_string();
+    if !parser.parse_keyword(Keyword::AS) {
+        return Ok(None);
+    }
+    Ok(Some(ExternalColumnDef {
+        name,
+        data_type,
+        expression: parser.parse_expr()?,
+    }))
+}
+
+fn parse_create(parser: &mut Parser) -> ParseResult<Option<ExternalTableStatement>> {
+    let or_replace = parser.parse_keywords(&[Keyword::OR, Keyword::REPLACE]);
+    if !parser.parse_keywords(&[Keyword::EXTERNAL, Keyword::TABLE]) {
+        return Ok(None);
+    }
+    let if_not_exists = parser.parse_keywords(&[Keyword::IF, Keyword::NOT, Keyword::EXISTS]);
+    let name = parser.parse_object_name(false)?;
+
+    let mut columns = Vec::new();
+    if parser.consume_token(&Token::LParen) && !parser.consume_token(&Token::RParen) {
+        loop {
+            let Some(column) = parse_column(parser)? else {
+                return Ok(None);
+            };
+            columns.push(column);
+            if !parser.consume_token(&Token::Comma) {
+                break;
+            }
+        }
+        parser.expect_token(&Token::RParen)?;
+    }
+
+    let mut partition_by = Vec::new();
+    let mut location = None;
+    let mut pattern = None;
+    let mut file_format = BTreeMap::new();
+    let mut auto_refresh = true;
+    let mut refresh_on_create = true;
+    let mut comment = None;
+    loop {
+        while parser.consume_token(&Token::SemiColon) {}
+        if parser.peek_token().token == Token::EOF {
+            break;
+        }
+        if parser.parse_keywords(&[Keyword::PARTITION, Keyword::BY]) {
+            parser.expect_token(&Token::LParen)?;
+            partition_by = parser.parse_comma_separated(Parser::parse_identifier)?;
+            parser.expect_token(&Token::RParen)?;
+            continue;
+        }
+        // `WITH LOCATION` is the same as `LOCATION`
+        let _ = parser.parse_keyword(Keyword::WITH);
+        let option = parser.parse_identifier()?.value.to_ascii_uppercase();
+        // `STORED AS` and `LOCATION '<path>'` belong to the DataFusion form
+        if parser.peek_token().token != Token::Eq {
+            return Ok(None);
+        }
+        match option.as_str() {
+            "LOCATION" => {
+                parser.expect_token(&Token::Eq)?;
+                location = Some(parse_stage_location(parser)?);
+            }
+            "PATTERN" => pattern = Some(parse_string(parser)?),
+            "FILE_FORMAT" => {
+                parser.expect_token(&Token::Eq)?;
+                parser.expect_token(&Token::LParen)?;
+                file_format = parse_format_options(parser)?;
+                parser.expect_token(&Token::RParen)?;
+            }
+            "AUTO_REFRESH" => auto_refresh = parse_bool(parser)?,
+            "REFRESH_ON_CREATE" => refresh_on_create = parse_bool(parser)?,
+            "COMMENT" => comment = Some(parse_string(parser)?),
+            _ => {
+                return Err(ParserError::ParserError(format!(
+                    "Unsupported option: {option}"
+                )));
+            }
+        }
+    }
+    let Some(location) = location else {
+        return Err(ParserError::ParserError(
+            "LOCATION is required for external tables".to_string(),
+        ));
+    };
+    Ok(Some(ExternalTableStatement::Create {
+        name,
+        or_replace,
+        if_not_exists,
+        columns,
+        partition_by,
+        location,
+        pattern,
+        file_format,
+        auto_refresh,
+        refresh_on_create,
+        comment,
+    }))
+}
+
+/// Parses an external table statement, returns `None` if the sql is a different
+/// statement.
+pub fn parse_external_table_statement(sql: &str) -> ParseResult<Option<ExternalTableStatement>> {
+    if !looks_like_external_table_statement(sql) {
+        return Ok(None);
+    }
+    let dialect = SnowflakeDialect;
+    let mut parser = Parser::new(&dialect).try_with_sql(sql)?;
+
+    if parser.parse_keyword(Keyword::CREATE) {
+        return parse_create(&mut parser);
+    }
+    let statement = if parser.parse_keyword(Keyword::ALTER) {
+        if !parser.parse_keywords(&[Keyword::EXTERNAL, Keyword::TABLE]) {
+            return Ok(None);
+        }
+        let if_exists = parser.parse_keywords(&[Keyword::IF, Keyword::EXISTS]);
+        let name = parser.parse_object_name(false)?;
+        if parse_word(&mut parser, "REFRESH") {
+            let path = match parser.peek_token().token {
+                Token::SingleQuotedString(_) => Some(parser.parse_literal_string()?),
+                _ => None,
+            };
+            ExternalTableStatement::Refresh {
+                name,
+                if_exists,
+                path,
+            }
+        } else if parser.parse_keyword(Keyword::SET) && parse_word(&mut parser, "AUTO_REFRESH") {
+            ExternalTableStatement::SetAutoRefresh {
+                name,
+                if_exists,
+                auto_refresh: parse_bool(&mut parser)?,
+            }
+        } else {
+            return parser.expected("REFRESH or SET AUTO_REFRESH", parser.peek_token());
+        }
+    } else if parser.parse_keyword(Keyword::DROP) {
+        if !parser.parse_keywords(&[Keyword::EXTERNAL, Keyword::TABLE]) {
+            return Ok(None);
+        }
+        let if_exists = parser.parse_keywords(&[Keyword::IF, Keyword::EXISTS]);
+        ExternalTableStatement::Drop {
+            name: parser.parse_object_name(false)?,
+            if_exists,
+        }
+    } else {
+        return Ok(None);
+    };
+
+    while parser.consume_token(&Token::SemiColon) {}
+    parser.expect_token(&Token::EOF)?;
+    Ok(Some(statement))
+}
+
+/// Checks that the expression of a partition column only depends on the file path
+pub fn check_partition_expression(table: &str, column: &ExternalColumnDef) -> Result<()> {
+    let references_other_columns = visit_expressions(&column.expression, |expr| match expr {
+        Expr::Identifier(ident) if ident.value.eq_ignore_ascii_case(FILENAME_COLUMN) => {
+            ControlFlow::Continue(())
+        }
+        Expr::Identifier(_) | Expr::CompoundIdentifier(_) => ControlFlow::Break(()),
+        _ => ControlFlow::Continue(()),
+    });
+    if references_other_columns.is_break() {
+        return ex_error::InvalidExternalTableSnafu {
+            table: table.to_string(),
+            reason: format!(
+                "partition column {} can only be computed from METADATA$FILENAME",
+                column.name
+            ),
+        }
+        .fail();
+    }
+    Ok(())
+}
+
+/// Status of a file registered by a refresh
+pub const REGISTERED_NEW: &str = "REGISTERED_NEW";
+/// Status of a registered file that changed since it was registered
+pub const REGISTERED_UPDATE: &str = "REGISTERED_UPDATE";
+/// Status of a registered file that no longer exists
+pub const UNREGISTERED: &str = "UNREGISTERED";
+
+/// Change made to the files of an external table by a refresh
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileRefresh {
+    pub file: String,
+    pub status: &'static str,
+}
+
+/// Registers the listed files in the metadata of an external table. Registered files
+/// under `prefix` that are not listed are unregistered, other registered files are kept.
+/// New and changed files have no schema until it is inferred. Returns the refreshed
+/// files of the table and the changes made.
+#[must_use]
+pub fn refresh_files(
+    registered: &[ExternalTableFile],
+    listed: &[ObjectMeta],
+    prefix: &str,
+    now: NaiveDateTime,
+) -> (Vec<ExternalTableFile>, Vec<FileRefresh>) {
+    let mut files = Vec::with_capacity(listed.len());
+    let mut changes = Vec::new();
+    for file in registered {
+        if !file.file.starts_with(prefix) {
+            files.push(file.clone());
+        } else if !listed
+            .iter()
+            .any(|meta| meta.location.as_ref() == file.file)
+        {
+            changes.push(FileRefresh {
+                file: file.file.clone(),
+                status: UNREGISTERED,
+            });
+        }
+    }
+    for meta in listed {
+        let location = meta.location.as_ref();
+        let previous = registered.iter().find(|file| file.file == location);
+        let (registered_at, schema) = match previous {
+            Some(file) if file.is_same(location, meta.e_tag.as_deref(), meta.size) => {
+                (file.registered_at, file.schema.clone())
+            }
+            Some(_) => {
+                changes.push(FileRefresh {
+                    file: location.to_string(),
+                    status: REGISTERED_UPDATE,
+                });
+                (now, None)
+            }
+            None => {
+                changes.push(FileRefresh {
+                    file: location.to_string(),
+                    status: REGISTERED_NEW,
+                });
+                (now, None)
+            }
+        };
+        files.push(ExternalTableFile {
+            file: location.to_string(),
+            e_tag: meta.e_tag.clone(),
+            size: meta.size,
+            last_modified: meta.last_modified.naive_utc(),
+            registered_at,
+            schema,
+        });
+    }
+    files.sort_by(|a, b| a.file.cmp(&b.file));
+    (files, changes)
+}
+
+/// Result of `ALTER EXTERNAL TABLE ... REFRESH`: a row for each file whose registration
+/// changed
+pub fn refresh_results(changes: &[FileRefresh]) -> Result<(RecordBatch, SchemaRef)> {
+    let schema = Arc::new(ArrowSchema::new(vec![
+        Field::new("file", DataType::Utf8, false),
+        Field::new("status", DataType::Utf8, false),
+        Field::new("description", DataType::Utf8, false),
+    ]));
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(StringArray::from_iter_values(
+                changes.iter().map(|change| &change.file),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                changes.iter().map(|change| change.status),
+            )),
+            Arc::new(StringArray::from_iter_values(changes.iter().map(
+                |change| {
+                    if change.status == UNREGISTERED {
+                        "File unregistered successfully."
+                    } else {
+                        "File registered successfully."
+                    }
+                },
+            ))),
+        ],
+    )
+    .context(ex_error::ArrowSnafu)?;
+    Ok((batch, schema))
+}
+
+/// Table provider of an external table. Each registered file is read by a plan of its
+/// own, in which `METADATA$FILENAME` is a literal: filters on the partition columns are
+/// folded to constants by the optimizer, which drops the files they rule out before
+/// any file is opened.
+#[derive(Debug)]
+pub struct ExternalTableProvider {
+    schema: SchemaRef,
+    files: Vec<LogicalPlan>,
+}
+
+impl ExternalTableProvider {
+    /// `files` are the plans of the registered files, with the columns of `schema`
+    #[must_use]
+    pub const fn new(schema: SchemaRef, files: Vec<LogicalPlan>) -> Self {
+        Self { schema, files }
+    }
+}
+
+#[async_trait]
+impl TableProvider for ExternalTableProvider {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    fn supports_filters_pushdown(
+        &self,
+        filters: &[&DFExpr],
+    ) -> DFResult<Vec<TableProviderFilterPushDown>> {
+        Ok(vec![TableProviderFilterPushDown::Inexact; filters.len()])
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        filters: &[DFExpr],
+        _limit: Option<usize>,
+    ) -> DFResult<Arc<dyn ExecutionPlan>> {
+        // The filters are qualified with the name of the table, the file plans are not
+        let filter = conjunction(filters.iter().cloned().map(unnormalize_col));
+        let mut plans = self.files.iter().map(|plan| {
+            let builder = LogicalPlanBuilder::from(plan.clone());
+            match &filter {
+                Some(filter) => builder.filter(filter.clone()),
+                None => Ok(builder),
+            }
+        });
+        let Some(first) = plans.next() else {
+            let schema = project_schema(&self.schema, projection)?;
+            return Ok(Arc::new(EmptyExec::new(schema)));
+        };
+        let mut builder = first?;
+        for plan in plans {
+            builder = builder.union(plan?.build()?)?;
+        }
+        if let Some(projection) = projection {
+            builder = builder.select(projection.iter().copied())?;
+        }
+        state.create_physical_plan(&builder.build()?).await
+    }
+}
+
+/// Unlike DataFusion, Snowflake reads files regardless of their extension. A single
+/// partition keeps the rows in the order of the file for their row numbers.
+fn file_listing_options(
+    format: &FileFormatOptions,
+    url: &ListingTableUrl,
+) -> Result<ListingOptions> {
+    Ok(
+        ListingOptions::new(format.listing_format(url.prefix().as_ref())?)
+            .with_file_extension("")
+            .with_target_partitions(1),
+    )
+}
+
+/// Schema of the rows of a file read by an external table, `format` is the format of
+/// the file after its rewrite. CSV values are read as text, the columns are named c1,
+/// c2, ... in the objects.
+pub async fn infer_file_schema(
+    state: &dyn Session,
+    format: &FileFormatOptions,
+    url: &ListingTableUrl,
+) -> Result<SchemaRef> {
+    let schema = file_listing_options(format, url)?
+        .infer_schema(state, url)
+        .await
+        .context(ex_error::DataFusionSnafu)?;
+    if !matches!(format, FileFormatOptions::Csv(_)) {
+        return Ok(schema);
+    }
+    Ok(Arc::new(ArrowSchema::new(
+        (1..=schema.fields().len())
+            .map(|position| Field::new(format!("c{position}"), DataType::Utf8, true))
+            .collect::<Vec<_>>(),
+    )))
+}
+
+/// Rows of a file of an external table: `VALUE` holds each row as an object, next to
+/// `METADATA$FILE_ROW_NUMBER`. The file is only opened once a query scans it, so the
+/// files that the filters rule out are never read.
+#[derive(Debug)]
+pub struct ExternalFileTable {
+    url: ListingTableUrl,
+    format: FileFormatOptions,
+    /// Schema cached when the file was registered, inferred on scan if there is none
+    file_schema: Option<SchemaRef>,
+    schema: SchemaRef,
+}
+
+impl ExternalFileTable {
+    #[must_use]
+    pub fn new(
+        url: ListingTableUrl,
+        format: FileFormatOptions,
+        file_schema: Option<SchemaRef>,
+    ) -> Self {
+        Self {
+            url,
+            format,
+            file_schema,
+            schema: Arc::new(ArrowSchema::new(vec![
+                Field::new(VALUE_COLUMN, DataType::Utf8, true),
+                Field::new(FILE_ROW_NUMBER_COLUMN, DataType::UInt64, true),
+            ])),
+        }
+    }
+
+    async fn plan(&self, state: &dyn Session) -> Result<LogicalPlan> {
+        let mut url = self.url.clone();
+        let mut format = self.format.clone();
+        if let Some(rewrite) = format.rewrite() {
+            url = rewrite_file(state.runtime_env(), &url, format.compression(), rewrite).await?;
+            format.rewritten(rewrite);
+        }
+        let schema = match &self.file_schema {
+            Some(schema) => Arc::clone(schema),
+            None => infer_file_schema(state, &format, &url).await?,
+        };
+        let config = ListingTableConfig::new(url.clone())
+            .with_listing_options(file_listing_options(&format, &url)?)
+            .with_schema(Arc::clone(&schema));
+        let provider = ListingTable::try_new(config).context(ex_error::DataFusionSnafu)?;
+        let mut input = LogicalPlanBuilder::scan(
+            "external_file",
+            provider_as_source(Arc::new(provider)),
+            None,
+        )
+        .context(ex_error::DataFusionSnafu)?
+        .build()
+        .context(ex_error::DataFusionSnafu)?;
+        if let FileFormatOptions::Csv(csv) = &format
+            && csv.converts_values()
+        {
+            input = UserQuery::convert_text_values(input, &schema, csv)?;
+        }
+
+        let object = state
+            .scalar_functions()
+            .get("object_construct")
+            .ok_or_else(|| DataFusionError::Plan("Missing function object_construct".to_string()))
+            .context(ex_error::DataFusionSnafu)?
+            .call(
+                schema
+                    .fields()
+                    .iter()
+                    .flat_map(|field| {
+                        [
+                            lit(field.name().clone()),
+                            DFExpr::Column(Column::from_name(field.name())),
+                        ]
+                    })
+                    .collect(),
+            );
+        LogicalPlanBuilder::from(input)
+            .window(vec![row_number().alias(FILE_ROW_NUMBER_COLUMN)])
+            .context(ex_error::DataFusionSnafu)?
+            .project(vec![
+                object.alias(VALUE_COLUMN),
+                DFExpr::Column(Column::from_name(FILE_ROW_NUMBER_COLUMN)),
+            ])
+            .context(ex_error::DataFusionSnafu)?
+            .build()
+            .context(ex_error::DataFusionSnafu)
+    }
+}
+
+#[async_trait]
+impl TableProvider for ExternalFileTable {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn schema(&self) -> SchemaRef {
+        Arc::clone(&self.schema)
+    }
+
+    fn table_type(&self) -> TableType {
+        TableType::Base
+    }
+
+    async fn scan(
+        &self,
+        state: &dyn Session,
+        projection: Option<&Vec<usize>>,
+        _filters: &[DFExpr],
+        _limit: Option<usize>,
+    ) -> DFResult<Arc<dyn ExecutionPlan>> {
+        let plan = self
+            .plan(state)
+            .await
+            .map_err(|error| DataFusionError::External(Box::new(error)))?;
+        let plan = match projection {
+            Some(projection) => LogicalPlanBuilder::from(plan)
+                .select(projection.iter().copied())?
+                .build()?,
+            None => plan,
+        };
+        state.create_physical_plan(&plan).await
+    }
+}
@@ -0,0 +1,719 @@
+use crate::error::{self as ex_error, Result};
+use bytes::Bytes;
+use datafusion::arrow::array::RecordBatch;
+use datafusion::arrow::csv::reader::Format;
+use datafusion::arrow::datatypes::SchemaRef;
+use datafusion::datasource::avro_to_arrow::ReaderBuilder as AvroReaderBuilder;
+use datafusion::datasource::file_format::FileFormat;
+use datafusion::datasource::file_format::avro::AvroFormat;
+use datafusion::datasource::file_format::csv::CsvFormat;
+use datafusion::datasource::file_format::file_compression_type::FileCompressionType;
+use datafusion::datasource::file_format::json::JsonFormat;
+use datafusion::datasource::file_format::parquet::ParquetFormat;
+use datafusion::datasource::listing::ListingTableUrl;
+use datafusion::execution::runtime_env::RuntimeEnv;
+use datafusion::parquet::arrow::ArrowWriter;
+use datafusion_common::DataFusionError;
+use futures::{StreamExt, TryStreamExt};
+use object_store::ObjectStore;
+use object_store::memory::InMemory;
+use orc_rust::ArrowReaderBuilder;
+use snafu::ResultExt;
+use sqlparser::ast::ObjectName;
+use sqlparser::dialect::SnowflakeDialect;
+use sqlparser::keywords::Keyword;
+use sqlparser::parser::{Parser, ParserError};
+use sqlparser::tokenizer::{Token, Tokenizer};
+use std::collections::BTreeMap;
+use std::io::Cursor;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::Arc;
+use url::Url;
+use uuid::Uuid;
+
+type ParseResult<T> = std::result::Result<T, ParserError>;
+
+/// File format statement: `CREATE FILE FORMAT` or `DROP FILE FORMAT`.
+///
+/// These are parsed here rather than by the SQL parser, which has no AST for them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileFormatStatement {
+    Create {
+        name: ObjectName,
+        or_replace: bool,
+        if_not_exists: bool,
+        /// Format options keyed by their uppercase names, lists are kept as `('a', 'b')`
+        options: BTreeMap<String, String>,
+        comment: Option<String>,
+    },
+    Drop {
+        name: ObjectName,
+        if_exists: bool,
+    },
+}
+
+// Cheap check to avoid tokenizing every statement twice
+fn looks_like_file_format_statement(sql: &str) -> bool {
+    let words = sql
+        .split_whitespace()
+        .take(6)
+        .map(str::to_ascii_uppercase)
+        .collect::<Vec<_>>();
+    matches!(words.first().map(String::as_str), Some("CREATE" | "DROP"))
+        && words
+            .windows(2)
+            .any(|pair| pair[0] == "FILE" && pair[1] == "FORMAT")
+}
+
+/// Parses a file format statement, returns `None` if the sql is a different statement.
+pub fn parse_file_format_statement(sql: &str) -> ParseResult<Option<FileFormatStatement>> {
+    if !looks_like_file_format_statement(sql) {
+        return Ok(None);
+    }
+    let dialect = SnowflakeDialect;
+    let mut parser = Parser::new(&dialect).try_with_sql(sql)?;
+
+    let statement = if parser.parse_keyword(Keyword::CREATE) {
+        let or_replace = parser.parse_keywords(&[Keyword::OR, Keyword::REPLACE]);
+        // Temporary file formats are kept like permanent ones
+        let _ = parser.parse_one_of_keywords(&[Keyword::TEMP, Keyword::TEMPORARY]);
+        if !parser.parse_keywords(&[Keyword::FILE, Keyword::FORMAT]) {
+            return Ok(None);
+        }
+        let if_not_exists = parser.parse_keywords(&[Keyword::IF, Keyword::NOT, Keyword::EXISTS]);
+        let name = parser.parse_object_name(false)?;
+        let mut options = parse_format_options(&mut parser)?;
+        let comment = options.remove("COMMENT");
+        FileFormatStatement::Create {
+            name,
+            or_replace,
+            if_not_exists,
+            options,
+            comment,
+        }
+    } else if parser.parse_keyword(Keyword::DROP) {
+        if !parser.parse_keywords(&[Keyword::FILE, Keyword::FORMAT]) {
+            return Ok(None);
+        }
+        let if_exists = parser.parse_keywords(&[Keyword::IF, Keyword::EXISTS]);
+        FileFormatStatement::Drop {
+            name: parser.parse_object_name(false)?,
+            if_exists,
+        }
+    } else {
+        return Ok(None);
+    };
+
+    while parser.consume_token(&Token::SemiColon) {}
+    parser.expect_token(&Token::EOF)?;
+    Ok(Some(statement))
+}
+
+/// Parses `KEY = value` format options, where a value can also be a list such as
+/// `NULL_IF = ('', 'NULL')`
+pub fn parse_format_options(parser: &mut Parser) -> ParseResult<BTreeMap<String, String>> {
+    let mut options = BTreeMap::new();
+    while matches!(parser.peek_token().token, Token::Word(_)) {
+        let key = parser.parse_identifier()?.value.to_ascii_uppercase();
+        parser.expect_token(&Token::Eq)?;
+        let value = if parser.consume_token(&Token::LParen) {
+            let values = if parser.consume_token(&Token::RParen) {
+                Vec::new()
+            } else {
+                let values = parser.parse_comma_separated(parse_option_value)?;
+                parser.expect_token(&Token::RParen)?;
+                values
+            };
+            format_option_list(&values)
+        } else {
+            parse_option_value(parser)?
+        };
+        options.insert(key, value);
+    }
+    Ok(options)
+}
+
+fn parse_option_value(parser: &mut Parser) -> ParseResult<String> {
+    let token = parser.next_token();
+    match token.token {
+        Token::SingleQuotedString(value) => Ok(value),
+        Token::Word(word) => Ok(word.value),
+        Token::Number(number, _) => Ok(number),
+        _ => parser.expected("an option value", token),
+    }
+}
+
+/// Formats a list option value the way it is written in SQL
+#[must_use]
+pub fn format_option_list(values: &[String]) -> String {
+    let values = values
+        .iter()
+        .map(|value| format!("'{}'", value.replace('\\', "\\\\").replace('\'', "''")))
+        .collect::<Vec<_>>();
+    format!("({})", values.join(", "))
+}
+
+/// Values of an option that may be a list such as `('', 'NULL')` or a single value
+fn option_list(option: &str, value: &str) -> Result<Vec<String>> {
+    let Some(list) = value
+        .trim()
+        .strip_prefix('(')
+        .and_then(|list| list.strip_suffix(')'))
+    else {
+        return Ok(vec![value.to_string()]);
+    };
+    let tokens = Tokenizer::new(&SnowflakeDialect, list)
+        .tokenize()
+        .context(ex_error::TokenizeFileFormatOptionSnafu { option, value })?;
+    let mut values = Vec::new();
+    for token in tokens {
+        match token {
+            Token::SingleQuotedString(item) | Token::Number(item, _) => values.push(item),
+            Token::Word(word) => values.push(word.value),
+            Token::Comma | Token::Whitespace(_) => {}
+            _ => return Err(invalid_option(option, value)),
+        }
+    }
+    Ok(values)
+}
+
+/// Name of the file format referenced by `FORMAT_NAME`
+pub fn parse_format_name(name: &str) -> ParseResult<ObjectName> {
+    let dialect = SnowflakeDialect;
+    let mut parser = Parser::new(&dialect).try_with_sql(name)?;
+    let name = parser.parse_object_name(false)?;
+    parser.expect_token(&Token::EOF)?;
+    Ok(name)
+}
+
+fn invalid_option(option: &str, value: &str) -> ex_error::Error {
+    ex_error::InvalidFileFormatOptionSnafu {
+        option: option.to_string(),
+        value: value.to_string(),
+    }
+    .build()
+}
+
+fn bool_option(option: &str, value: &str) -> Result<bool> {
+    match value.to_ascii_uppercase().as_str() {
+        "TRUE" => Ok(true),
+        "FALSE" => Ok(false),
+        _ => Err(invalid_option(option, value)),
+    }
+}
+
+/// Single byte option such as `FIELD_DELIMITER = '|'`, `NONE` is returned as `None`.
+///
+/// Besides plain characters this accepts the escape sequences, octal (`'\\001'`) and
+/// hex (`0x1F`) values Snowflake accepts.
+fn byte_option(option: &str, value: &str) -> Result<Option<u8>> {
+    if value.eq_ignore_ascii_case("NONE") {
+        return Ok(None);
+    }
+    let byte = match value.as_bytes() {
+        [byte] => Some(*byte),
+        [b'\\', b't'] => Some(b'\t'),
+        [b'\\', b'n'] => Some(b'\n'),
+        [b'\\', b'r'] => Some(b'\r'),
+        [b'\\', b'\\'] => Some(b'\\'),
+        [b'\\', b'\''] => Some(b'\''),
+        [b'\\', b'"'] => Some(b'"'),
+        [b'\\', octal @ ..] if !octal.is_empty() && octal.len() <= 3 => {
+            u8::from_str_radix(&value[1..], 8).ok()
+        }
+        [b'0', b'x' | b'X', ..] => u8::from_str_radix(&value[2..], 16).ok(),
+        _ => None,
+    };
+    byte.map(Some).ok_or_else(|| invalid_option(option, value))
+}
+
+/// Format options of `COPY INTO`, parsed from the `FILE_FORMAT` options of the statement,
+/// of the stage or of a named file format
+#[derive(Debug, Clone)]
+pub enum FileFormatOptions {
+    Csv(CsvFileFormat),
+    Json(JsonFileFormat),
+    Parquet,
+    Avro,
+    /// ORC files are converted to Parquet before they are read
+    Orc,
+}
+
+#[derive(Debug, Clone)]
+#[allow(clippy::struct_excessive_bools)]
+pub struct CsvFileFormat {
+    pub compression: String,
+    /// Number of lines skipped at the start of each file
+    pub skip_header: usize,
+    /// Column names are taken from the first line of the files
+    pub parse_header: bool,
+    pub field_delimiter: u8,
+    /// `None` stands for `\n` and `\r\n` line endings
+    pub record_delimiter: Option<u8>,
+    pub quote: u8,
+    pub escape: Option<u8>,
+    /// Values loaded as NULL. Unlike Snowflake, `\N` is only one of them when listed
+    pub null_if: Vec<String>,
+    pub empty_field_as_null: bool,
+    pub trim_space: bool,
+    pub date_format: Option<String>,
+    pub timestamp_format: Option<String>,
+    pub error_on_column_count_mismatch: bool,
+}
+
+impl Default for CsvFileFormat {
+    fn default() -> Self {
+        Self {
+            compression: "AUTO".to_string(),
+            skip_header: 0,
+            parse_header: false,
+            field_delimiter: b',',
+            record_delimiter: None,
+            quote: b'"',
+            escape: None,
+            null_if: Vec::new(),
+            empty_field_as_null: true,
+            trim_space: false,
+            date_format: None,
+            timestamp_format: None,
+            error_on_column_count_mismatch: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct JsonFileFormat {
+    pub compression: String,
+    /// Files hold a single JSON array whose elements are loaded as rows
+    pub strip_outer_array: bool,
+}
+
+impl Default for JsonFileFormat {
+    fn default() -> Self {
+        Self {
+            compression: "AUTO".to_string(),
+            strip_outer_array: false,
+        }
+    }
+}
+
+// Formats of `DATE_FORMAT` and `TIMESTAMP_FORMAT` other than `AUTO`
+fn format_option(value: &str) -> Option<String> {
+    (!value.eq_ignore_ascii_case("AUTO")).then(|| value.to_string())
+}
+
+impl Default for FileFormatOptions {
+    fn default() -> Self {
+        Self::Csv(CsvFileFormat::default())
+    }
+}
+
+impl FileFormatOptions {
+    /// Parses format options keyed by their uppercase names, returns `None` if there
+    /// are no options. `FORMAT_NAME` has to be resolved before.
+    pub fn parse(options: &BTreeMap<String, String>) -> Result<Option<Self>> {
+        if options.is_empty() {
+            return Ok(None);
+        }
+        // CSV is the default type in Snowflake
+        let format_type = options.get("TYPE").map_or("CSV", String::as_str);
+        let mut format = match format_type.to_ascii_uppercase().as_str() {
+            "CSV" => Self::Csv(CsvFileFormat::default()),
+            "JSON" => Self::Json(JsonFileFormat::default()),
+            "PARQUET" => Self::Parquet,
+            "AVRO" => Self::Avro,
+            "ORC" => Self::Orc,
+            _ => {
+                return ex_error::UnsupportedFileFormatSnafu {
+                    format: format_type.to_string(),
+                }
+                .fail();
+            }
+        };
+        for (option, value) in options {
+            match (&mut format, option.as_str()) {
+                (_, "TYPE") => {}
+                (Self::Csv(csv), "COMPRESSION") => csv.compression = value.to_ascii_uppercase(),
+                (Self::Json(json), "COMPRESSION") => {
+                    json.compression = value.to_ascii_uppercase();
+                }
+                (Self::Csv(csv), "SKIP_HEADER") => {
+                    csv.skip_header = value
+                        .parse()
+                        .context(ex_error::ParseFileFormatOptionSnafu { option, value })?;
+                }
+                (Self::Csv(csv), "PARSE_HEADER") => csv.parse_header = bool_option(option, value)?,
+                (Self::Csv(csv), "FIELD_DELIMITER") => {
+                    csv.field_delimiter =
+                        byte_option(option, value)?.ok_or_else(|| invalid_option(option, value))?;
+                }
+                (Self::Csv(csv), "RECORD_DELIMITER") => {
+                    csv.record_delimiter = if value == "\r\n" || value == "\\r\\n" {
+                        None
+                    } else {
+                        Some(
+                            byte_option(option, value)?
+                                .ok_or_else(|| invalid_option(option, value))?,
+                        )
+                    };
+                }
+                (Self::Csv(csv), "FIELD_OPTIONALLY_ENCLOSED_BY") => {
+                    csv.quote = byte_option(option, value)?.unwrap_or(b'"');
+                }
+                (Self::Csv(csv), "ESCAPE") => csv.escape = byte_option(option, value)?,
+                (Self::Csv(csv), "NULL_IF") => csv.null_if = option_list(option, value)?,
+                (Self::Csv(csv), "EMPTY_FIELD_AS_NULL") => {
+                    csv.empty_field_as_null = bool_option(option, value)?;
+                }
+                (Self::Csv(csv), "TRIM_SPACE") => csv.trim_space = bool_option(option, value)?,
+                (Self::Csv(csv), "DATE_FORMAT") => csv.date_format = format_option(value),
+                (Self::Csv(csv), "TIMESTAMP_FORMAT") => csv.timestamp_format = format_option(value),
+                (Self::Csv(csv), "ERROR_ON_COLUMN_COUNT_MISMATCH") => {
+                    csv.error_on_column_count_mismatch = bool_option(option, value)?;
+                }
+                (Self::Json(json), "STRIP_OUTER_ARRAY") => {
+                    json.strip_outer_array = bool_option(option, value)?;
+                }
+                // Parquet, Avro and ORC compression is read from the files
+                (Self::Parquet | Self::Avro | Self::Orc, "COMPRESSION") => {}
+                _ => {
+                    return ex_error::UnsupportedFileFormatOptionSnafu {
+                        option: option.clone(),
+                        format: format_type.to_ascii_uppercase(),
+                    }
+                    .fail();
+                }
+            }
+        }
+        Ok(Some(format))
+    }
+
+    /// Name of the format as given by `TYPE`
+    #[must_use]
+    pub const fn name(&self) -> &'static str {
+        match self {
+            Self::Csv(_) => "CSV",
+            Self::Json(_) => "JSON",
+            Self::Parquet => "PARQUET",
+            Self::Avro => "AVRO",
+            Self::Orc => "ORC",
+        }
+    }
+
+    /// Whether the schema is inferred from the files rather than taken from the table
+    #[must_use]
+    pub const fn infers_schema(&self) -> bool {
+        match self {
+            Self::Csv(csv) => csv.parse_header,
+            Self::Json(_) | Self::Parquet | Self::Avro | Self::Orc => true,
+        }
+    }
+
+    /// Number of lines at the start of each file before the first row
+    #[must_use]
+    pub const fn header_lines(&self) -> usize {
+        match self {
+            Self::Csv(csv) if csv.parse_header => 1,
+            Self::Csv(csv) => csv.skip_header,
+            Self::Json(_) | Self::Parquet | Self::Avro | Self::Orc => 0,
+        }
+    }
+
+    /// Rewrite the files need before they can be read, for the options the readers
+    /// do not support themselves
+    #[must_use]
+    pub const fn rewrite(&self) -> Option<FileRewrite> {
+        match self {
+            // The reader skips a single header line
+            Self::Csv(csv) if csv.skip_header > 1 => Some(FileRewrite::SkipLines {
+                lines: csv.skip_header,
+                terminator: csv.record_delimiter,
+            }),
+            Self::Json(json) if json.strip_outer_array => Some(FileRewrite::StripOuterArray),
+            Self::Orc => Some(FileRewrite::OrcToParquet),
+            _ => None,
+        }
+    }
+
+    /// Compression of the files, `AUTO` unless set
+    #[must_use]
+    pub fn compression(&self) -> &str {
+        match self {
+            Self::Csv(csv) => &csv.compression,
+            Self::Json(json) => &json.compression,
+            Self::Parquet | Self::Avro | Self::Orc => "AUTO",
+        }
+    }
+
+    /// Adjusts the options to files the rewrite was applied to, which are stored
+    /// uncompressed
+    pub fn rewritten(&mut self, rewrite: FileRewrite) {
+        match (self, rewrite) {
+            (Self::Csv(csv), FileRewrite::SkipLines { .. }) => {
+                csv.skip_header = 0;
+                csv.compression = "NONE".to_string();
+            }
+            (Self::Json(json), FileRewrite::StripOuterArray) => {
+                json.strip_outer_array = false;
+                json.compression = "NONE".to_string();
+            }
+            (format @ Self::Orc, FileRewrite::OrcToParquet) => *format = Self::Parquet,
+            _ => {}
+        }
+    }
+
+    /// Format to read the files with, `path` of a file picks the compression of `AUTO`
+    pub fn listing_format(&self, path: &str) -> Result<Arc<dyn FileFormat>> {
+        Ok(match self {
+            Self::Csv(csv) => {
+                let format = CsvFormat::default()
+                    .with_has_header(csv.parse_header || csv.skip_header > 0)
+                    .with_delimiter(csv.field_delimiter)
+                    .with_quote(csv.quote)
+                    .with_escape(csv.escape)
+                    .with_terminator(csv.record_delimiter)
+                    .with_truncated_rows(!csv.error_on_column_count_mismatch)
+                    .with_file_compression_type(compression_type(&csv.compression, path)?);
+                // Empty fields are told apart from NULLs while converting the values
+                let format = if csv.converts_values() {
+                    format.with_null_regex(Some(NEVER_NULL_REGEX.to_string()))
+                } else {
+                    format
+                };
+                Arc::new(format)
+            }
+            Self::Json(json) => Arc::new(
+                JsonFormat::default()
+                    .with_file_compression_type(compression_type(&json.compression, path)?),
+            ),
+            Self::Parquet => Arc::new(ParquetFormat::default()),
+            Self::Avro => Arc::new(AvroFormat),
+            // There is no ORC reader, the files are rewritten to Parquet instead
+            Self::Orc => {
+                return ex_error::UnsupportedFileFormatSnafu {
+                    format: self.name(),
+                }
+                .fail();
+            }
+        })
+    }
+}
+
+/// Rewrite of file contents applied before the files are read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileRewrite {
+    /// Drops the first lines of the file, `terminator` of `None` stands for `\n`
+    SkipLines {
+        lines: usize,
+        terminator: Option<u8>,
+    },
+    /// Turns a JSON array into newline delimited JSON
+    StripOuterArray,
+    /// Converts an ORC file to a Parquet file with the same schema
+    OrcToParquet,
+}
+
+impl FileRewrite {
+    /// Applies the rewrite to the uncompressed contents of a file
+    pub fn apply(&self, data: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Self::SkipLines { lines, terminator } => {
+                let Some(last_line) = lines.checked_sub(1) else {
+                    return Ok(data.to_vec());
+                };
+                let terminator = terminator.unwrap_or(b'\n');
+                let start = data
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, byte)| **byte == terminator)
+                    .nth(last_line)
+                    .map_or(data.len(), |(position, _)| position + 1);
+                Ok(data[start..].to_vec())
+            }
+            Self::StripOuterArray => {
+                let value: serde_json::Value =
+                    serde_json::from_slice(data).context(ex_error::SerdeParseSnafu)?;
+                // A file that is not an array is loaded as a single row
+                let rows = match value {
+                    serde_json::Value::Array(rows) => rows,
+                    value => vec![value],
+                };
+                let mut output = Vec::with_capacity(data.len());
+                for row in rows {
+                    serde_json::to_writer(&mut output, &row).context(ex_error::SerdeParseSnafu)?;
+                    output.push(b'\n');
+                }
+                Ok(output)
+            }
+            Self::OrcToParquet => {
+                let (schema, batches) = read_orc(Bytes::copy_from_slice(data))?;
+                let mut output = Vec::with_capacity(data.len());
+                let mut writer = ArrowWriter::try_new(&mut output, schema, None)
+                    .context(ex_error::ParquetSnafu)?;
+                for batch in &batches {
+                    writer.write(batch).context(ex_error::ParquetSnafu)?;
+                }
+                writer.close().context(ex_error::ParquetSnafu)?;
+                Ok(output)
+            }
+        }
+    }
+}
+
+/// Copies a file to memory with the rewrite applied, returns the url of the copy.
+/// The copy is stored uncompressed.
+pub async fn rewrite_file(
+    runtime_env: &RuntimeEnv,
+    url: &ListingTableUrl,
+    compression: &str,
+    rewrite: FileRewrite,
+) -> Result<ListingTableUrl> {
+    let store = runtime_env
+        .object_store(url.object_store())
+        .context(ex_error::DataFusionSnafu)?;
+    let stream = store
+        .get(url.prefix())
+        .await
+        .context(ex_error::ObjectStoreSnafu)?
+        .into_stream()
+        .map_err(DataFusionError::from)
+        .boxed();
+    let chunks: Vec<Bytes> = compression_type(compression, url.prefix().as_ref())?
+        .convert_stream(stream)
+        .context(ex_error::DataFusionSnafu)?
+        .try_collect()
+        .await
+        .context(ex_error::DataFusionSnafu)?;
+    let data = rewrite.apply(&chunks.concat())?;
+
+    let rewritten_store = Arc::new(InMemory::new());
+    rewritten_store
+        .put(url.prefix(), data.into())
+        .await
+        .context(ex_error::ObjectStoreSnafu)?;
+    let base_url = format!("rewritten://{}/", Uuid::new_v4().simple());
+    let rewritten_url = ListingTableUrl::parse(format!("{base_url}{}", url.prefix()))
+        .context(ex_error::DataFusionSnafu)?;
+    let base_url = Url::parse(&base_url).context(ex_error::InvalidUrlSnafu { url: base_url })?;
+    runtime_env.register_object_store(&base_url, rewritten_store);
+    Ok(rewritten_url)
+}
+
+/// Format of the data uploaded with `ExecutionService::upload_data_to_table`
+#[derive(Debug, Clone)]
+pub enum UploadFormat {
+    Csv(Format),
+    Avro,
+    Orc,
+}
+
+/// File uploaded with `ExecutionService::upload_files_to_table`, spooled to local disk
+/// so that it is read as a stream rather than held in memory
+#[derive(Debug, Clone)]
+pub struct UploadedFile {
+    /// Name of the file as uploaded, which picks the compression of `AUTO`
+    pub file_name: String,
+    pub path: PathBuf,
+}
+
+/// Rows loaded from an uploaded file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UploadedFileResult {
+    pub file_name: String,
+    pub rows_loaded: usize,
+}
+
+/// Reads all rows of an ORC file, along with its schema
+pub fn read_orc(data: Bytes) -> Result<(SchemaRef, Vec<RecordBatch>)> {
+    let reader = ArrowReaderBuilder::try_new(data)
+        .context(ex_error::OrcSnafu)?
+        .build();
+    let schema = reader.schema();
+    let batches = reader
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .context(ex_error::ArrowSnafu)?;
+    Ok((schema, batches))
+}
+
+/// Reads all rows of an Avro file, along with its schema
+pub fn read_avro(data: Bytes) -> Result<(SchemaRef, Vec<RecordBatch>)> {
+    let reader = AvroReaderBuilder::new()
+        .read_schema()
+        .build(Cursor::new(data))
+        .context(ex_error::DataFusionSnafu)?;
+    let schema = reader.schema();
+    let batches = reader
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .context(ex_error::ArrowSnafu)?;
+    Ok((schema, batches))
+}
+
+// Regex no value matches, `^` only matches at the start of the value
+const NEVER_NULL_REGEX: &str = "a^";
+
+impl CsvFileFormat {
+    /// Whether the values are loaded as text and converted afterwards: the CSV reader
+    /// does not apply `NULL_IF`, `EMPTY_FIELD_AS_NULL`, `TRIM_SPACE` and custom date
+    /// and timestamp formats itself
+    #[must_use]
+    pub fn converts_values(&self) -> bool {
+        !self.null_if.is_empty()
+            || !self.empty_field_as_null
+            || self.trim_space
+            || self.date_format.is_some()
+            || self.timestamp_format.is_some()
+    }
+
+    /// Values loaded as NULL, including the empty value when `EMPTY_FIELD_AS_NULL` is set
+    #[must_use]
+    pub fn null_values(&self) -> Vec<String> {
+        let mut values = self.null_if.clone();
+        if self.empty_field_as_null && !values.iter().any(String::is_empty) {
+            values.push(String::new());
+        }
+        values
+    }
+}
+
+/// Compression of `COMPRESSION`, `AUTO` picks it by the extension of `path`
+pub fn compression_type(compression: &str, path: &str) -> Result<FileCompressionType> {
+    let compression = match compression {
+        "AUTO" => compression_extension(path).map_or("", |(_, compression)| compression),
+        "NONE" => "",
+        "BZ2" => "BZIP2",
+        compression => compression,
+    };
+    FileCompressionType::from_str(compression).context(ex_error::DataFusionSnafu)
+}
+
+/// File extensions of the supported compressions
+const COMPRESSION_EXTENSIONS: [(&str, &str); 4] = [
+    (".gz", "GZIP"),
+    (".bz2", "BZIP2"),
+    (".xz", "XZ"),
+    (".zst", "ZSTD"),
+];
+
+/// Compression extension of a file name, along with the matching compression
+#[must_use]
+fn compression_extension(path: &str) -> Option<(&'static str, &'static str)> {
+    COMPRESSION_EXTENSIONS
+        .into_iter()
+        .find(|(extension, _)| path.to_ascii_lowercase().ends_with(extension))
+}
+
+/// Compression of files unloaded with `COPY INTO <location>`, where `AUTO` stands for
+/// gzip like in Snowflake. Returned along with the extension it adds to the file names.
+pub fn unload_compression(compression: &str) -> Result<(FileCompressionType, &'static str)> {
+    let compression = match compression {
+        "AUTO" => "GZIP",
+        "BZ2" => "BZIP2",
+        compression => compression,
+    };
+    let extension = COMPRESSION_EXTENSIONS
+        .into_iter()
+        .find(|(_, name)| *name == compression)
+        .map_or("", |(extension, _)| extension);
+    Ok((compression_type(compression, "")?, extension))
+}
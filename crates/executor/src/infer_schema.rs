@@ -0,0 +1,459 @@
+use crate::error::{self as ex_error, Result};
+use crate::file_formats::FileFormatOptions;
+use datafusion::arrow::datatypes::{DataType, SchemaRef};
+use serde_json::Value as JsonValue;
+use snafu::ResultExt;
+use sqlparser::ast::{
+    Expr, FunctionArg, FunctionArgExpr, Ident, ObjectName, Query, Value, ValueWithSpan,
+};
+use sqlparser::dialect::SnowflakeDialect;
+use sqlparser::keywords::Keyword;
+use sqlparser::parser::{Parser, ParserError};
+use sqlparser::tokenizer::Token;
+
+type ParseResult<T> = std::result::Result<T, ParserError>;
+
+/// Name of the `INFER_SCHEMA` table function
+pub const INFER_SCHEMA_FUNCTION: &str = "infer_schema";
+
+/// Arguments of the `INFER_SCHEMA` table function
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InferSchemaArgs {
+    /// Stage reference such as `@my_stage/path/`
+    pub location: String,
+    /// Name of the file format the files are read with
+    pub file_format: String,
+    /// Files relative to the location, all files of the location if not set
+    pub files: Option<Vec<String>>,
+    /// Column names are uppercased and matched between files regardless of case
+    pub ignore_case: bool,
+    /// Maximum number of files whose schemas are read
+    pub max_file_count: Option<usize>,
+}
+
+fn invalid_argument(argument: &str, reason: &str) -> ex_error::Error {
+    ex_error::InvalidInferSchemaArgumentSnafu {
+        argument: argument.to_string(),
+        reason: reason.to_string(),
+    }
+    .build()
+}
+
+fn string_argument(argument: &str, expr: &Expr) -> Result<String> {
+    match expr {
+        Expr::Value(ValueWithSpan {
+            value: Value::SingleQuotedString(value),
+            ..
+        }) => Ok(value.clone()),
+        _ => Err(invalid_argument(argument, "expected a string")),
+    }
+}
+
+impl InferSchemaArgs {
+    /// Parses the named arguments of the function, `LOCATION` and `FILE_FORMAT` are
+    /// required
+    pub fn parse(args: &[FunctionArg]) -> Result<Self> {
+        let mut location = None;
+        let mut file_format = None;
+        let mut files = None;
+        let mut ignore_case = false;
+        let mut max_file_count = None;
+        for arg in args {
+            let FunctionArg::Named {
+                name,
+                arg: FunctionArgExpr::Expr(expr),
+                ..
+            } = arg
+            else {
+                return Err(invalid_argument(
+                    &arg.to_string(),
+                    "expected a named argument",
+                ));
+            };
+            let argument = name.value.to_ascii_uppercase();
+            match argument.as_str() {
+                "LOCATION" => location = Some(string_argument(&argument, expr)?),
+                "FILE_FORMAT" => file_format = Some(string_argument(&argument, expr)?),
+                "FILES" => {
+                    files = Some(match expr {
+                        Expr::Tuple(values) => values
+                            .iter()
+                            .map(|value| string_argument(&argument, value))
+                            .collect::<Result<Vec<_>>>()?,
+                        Expr::Nested(value) => vec![string_argument(&argument, value)?],
+                        value => vec![string_argument(&argument, value)?],
+                    });
+                }
+                "IGNORE_CASE" => {
+                    ignore_case = match expr {
+                        Expr::Value(ValueWithSpan {
+                            value: Value::Boolean(value),
+                            ..
+                        }) => *value,
+                        _ => return Err(invalid_argument(&argument, "expected a boolean")),
+                    };
+                }
+                "MAX_FILE_COUNT" => {
+                    max_file_count = match expr {
+                        Expr::Value(ValueWithSpan {
+                            value: Value::Number(value, _),
+                            ..
+                        }) => Some(value.parse().context(
+                            ex_error::ParseInferSchemaArgumentSnafu {
+                                argument: &argument,
+                                reason: "expected a positive number",
+                            },
+                        )?),
+                        _ => return Err(invalid_argument(&argument, "expected a number")),
+                    };
+                }
+                _ => return Err(invalid_argument(&argument, "unknown argument")),
+            }
+        }
+        Ok(Self {
+            location: location
+                .ok_or_else(|| invalid_argument("LOCATION", "the argument is required"))?,
+            file_format: file_format
+                .ok_or_else(|| invalid_argument("FILE_FORMAT", "the argument is required"))?,
+            files,
+            ignore_case,
+            max_file_count,
+        })
+    }
+}
+
+/// Column found by `INFER_SCHEMA` in the staged files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InferredColumn {
+    pub name: String,
+    /// Snowflake type of the column, such as `NUMBER(38, 0)`
+    pub data_type: String,
+    pub nullable: bool,
+    /// Expression reading the column from a staged file, such as `$1:id::NUMBER(38, 0)`
+    pub expression: String,
+    /// Files the column was found in
+    pub filenames: Vec<String>,
+}
+
+/// Snowflake type of the column a value of an Arrow type is loaded to
+#[must_use]
+pub fn snowflake_type(data_type: &DataType) -> String {
+    match data_type {
+        DataType::Boolean => "BOOLEAN".to_string(),
+        DataType::Int8
+        | DataType::Int16
+        | DataType::Int32
+        | DataType::Int64
+        | DataType::UInt8
+        | DataType::UInt16
+        | DataType::UInt32
+        | DataType::UInt64 => "NUMBER(38, 0)".to_string(),
+        DataType::Decimal128(precision, scale) | DataType::Decimal256(precision, scale) => {
+            format!("NUMBER({precision}, {scale})")
+        }
+        DataType::Float16 | DataType::Float32 | DataType::Float64 => "REAL".to_string(),
+        DataType::Date32 | DataType::Date64 => "DATE".to_string(),
+        DataType::Time32(_) | DataType::Time64(_) => "TIME".to_string(),
+        DataType::Timestamp(_, None) => "TIMESTAMP_NTZ".to_string(),
+        DataType::Timestamp(_, Some(_)) => "TIMESTAMP_TZ".to_string(),
+        DataType::Binary
+        | DataType::LargeBinary
+        | DataType::BinaryView
+        | DataType::FixedSizeBinary(_) => "BINARY".to_string(),
+        DataType::List(_)
+        | DataType::LargeList(_)
+        | DataType::ListView(_)
+        | DataType::LargeListView(_)
+        | DataType::FixedSizeList(_, _)
+        | DataType::Struct(_)
+        | DataType::Map(_, _) => "VARIANT".to_string(),
+        _ => "TEXT".to_string(),
+    }
+}
+
+/// Merges the schemas of the files into columns in the order they are first found. A
+/// column missing from any of the files is nullable, and a column whose type differs
+/// between files is read as TEXT.
+///
+/// CSV columns are named by their position unless the names are parsed from the header.
+#[must_use]
+pub fn infer_columns(
+    format: &FileFormatOptions,
+    files: &[(String, SchemaRef)],
+    ignore_case: bool,
+) -> Vec<InferredColumn> {
+    let positional = matches!(format, FileFormatOptions::Csv(csv) if !csv.parse_header);
+    // Columns along with their position in the first file they are found in
+    let mut columns: Vec<(InferredColumn, usize)> = Vec::new();
+    for (file, schema) in files {
+        for (index, field) in schema.fields().iter().enumerate() {
+            let name = if positional {
+                format!("c{}", index + 1)
+            } else if ignore_case {
+                field.name().to_uppercase()
+            } else {
+                field.name().clone()
+            };
+            let data_type = snowflake_type(field.data_type());
+            if let Some((column, _)) = columns.iter_mut().find(|(column, _)| column.name == name) {
+                if column.data_type != data_type {
+                    column.data_type = "TEXT".to_string();
+                }
+                column.nullable |= field.is_nullable();
+                column.filenames.push(file.clone());
+            } else {
+                columns.push((
+                    InferredColumn {
+                        name,
+                        data_type,
+                        nullable: field.is_nullable(),
+                        expression: String::new(),
+                        filenames: vec![file.clone()],
+                    },
+                    index + 1,
+                ));
+            }
+        }
+    }
+    columns
+        .into_iter()
+        .map(|(mut column, position)| {
+            column.nullable |= column.filenames.len() < files.len();
+            column.expression = if matches!(format, FileFormatOptions::Csv(_)) {
+                format!("${position}::{}", column.data_type)
+            } else {
+                format!("$1:{}::{}", json_path_key(&column.name), column.data_type)
+            };
+            column
+        })
+        .collect()
+}
+
+// Keys of a semi-structured path are quoted unless they are plain identifiers
+fn json_path_key(name: &str) -> String {
+    let plain = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if plain {
+        name.to_string()
+    } else {
+        Ident::with_quote('"', name).to_string()
+    }
+}
+
+/// Query returning the rows of `INFER_SCHEMA` for the columns, used in place of the
+/// table function
+pub fn infer_schema_query(columns: &[InferredColumn]) -> ParseResult<Box<Query>> {
+    let sql = if columns.is_empty() {
+        "SELECT CAST(NULL AS TEXT) AS column_name, CAST(NULL AS TEXT) AS type, \
+         CAST(NULL AS BOOLEAN) AS nullable, CAST(NULL AS TEXT) AS expression, \
+         CAST(NULL AS TEXT) AS filenames, CAST(NULL AS BIGINT) AS order_id WHERE FALSE"
+            .to_string()
+    } else {
+        let rows = columns
+            .iter()
+            .enumerate()
+            .map(|(order_id, column)| {
+                format!(
+                    "({}, {}, {}, {}, {}, {order_id})",
+                    Value::SingleQuotedString(column.name.clone()),
+                    Value::SingleQuotedString(column.data_type.clone()),
+                    Value::Boolean(column.nullable),
+                    Value::SingleQuotedString(column.expression.clone()),
+                    Value::SingleQuotedString(column.filenames.join(",")),
+                )
+            })
+            .collect::<Vec<_>>();
+        format!(
+            "SELECT column1 AS column_name, column2 AS type, column3 AS nullable, \
+             column4 AS expression, column5 AS filenames, column6 AS order_id \
+             FROM (VALUES {})",
+            rows.join(", ")
+        )
+    };
+    Parser::new(&SnowflakeDialect)
+        .try_with_sql(&sql)?
+        .parse_query()
+}
+
+/// `CREATE TABLE ... USING TEMPLATE (<query>)`, which the SQL parser has no AST for
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateTableStatement {
+    pub name: ObjectName,
+    pub or_replace: bool,
+    pub if_not_exists: bool,
+    /// Query returning the column definitions as an array of objects, usually built
+    /// with `ARRAY_AGG(OBJECT_CONSTRUCT(*))` over the result of `INFER_SCHEMA`
+    pub template: Box<Query>,
+}
+
+// Cheap check to avoid tokenizing every statement twice
+fn looks_like_template_table_statement(sql: &str) -> bool {
+    let words = sql
+        .split_whitespace()
+        .take(10)
+        .map(str::to_ascii_uppercase)
+        .collect::<Vec<_>>();
+    words.first().is_some_and(|word| word == "CREATE")
+        && words
+            .windows(2)
+            .any(|pair| pair[0] == "USING" && pair[1].starts_with("TEMPLATE"))
+}
+
+/// Parses a `CREATE TABLE ... USING TEMPLATE` statement, returns `None` if the sql is
+/// a different statement.
+pub fn parse_template_table_statement(sql: &str) -> ParseResult<Option<TemplateTableStatement>> {
+    if !looks_like_template_table_statement(sql) {
+        return Ok(None);
+    }
+    let dialect = SnowflakeDialect;
+    let mut parser = Parser::new(&dialect).try_with_sql(sql)?;
+    if !parser.parse_keyword(Keyword::CREATE) {
+        return Ok(None);
+    }
+    let or_replace = parser.parse_keywords(&[Keyword::OR, Keyword::REPLACE]);
+    if !parser.parse_keyword(Keyword::TABLE) {
+        return Ok(None);
+    }
+    let if_not_exists = parser.parse_keywords(&[Keyword::IF, Keyword::NOT, Keyword::EXISTS]);
+    let name = parser.parse_object_name(false)?;
+    if !parser.parse_keyword(Keyword::USING)
+        || !parser
+            .parse_identifier()?
+            .value
+            .eq_ignore_ascii_case("TEMPLATE")
+    {
+        return Ok(None);
+    }
+    parser.expect_token(&Token::LParen)?;
+    let template = parser.parse_query()?;
+    parser.expect_token(&Token::RParen)?;
+    while parser.consume_token(&Token::SemiColon) {}
+    parser.expect_token(&Token::EOF)?;
+    Ok(Some(TemplateTableStatement {
+        name,
+        or_replace,
+        if_not_exists,
+        template,
+    }))
+}
+
+/// Column definition of a `USING TEMPLATE` result
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateColumn {
+    pub name: String,
+    pub data_type: String,
+    pub nullable: bool,
+}
+
+fn invalid_template(table: &str, reason: &str) -> ex_error::Error {
+    ex_error::InvalidTableTemplateSnafu {
+        table: table.to_string(),
+        reason: reason.to_string(),
+    }
+    .build()
+}
+
+// Keys of the template objects are matched regardless of case, like in Snowflake
+fn template_key<'a>(
+    object: &'a serde_json::Map<String, JsonValue>,
+    key: &str,
+) -> Option<&'a JsonValue> {
+    object
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(key))
+        .map(|(_, value)| value)
+}
+
+/// Column definitions of a template, which is a JSON array of objects with
+/// `COLUMN_NAME`, `TYPE` and optional `NULLABLE` and `ORDER_ID` keys. The columns are
+/// ordered by `ORDER_ID` if it is set.
+pub fn template_columns(table: &str, template: &str) -> Result<Vec<TemplateColumn>> {
+    let value: JsonValue =
+        serde_json::from_str(template).context(ex_error::ParseTableTemplateSnafu {
+            table,
+            reason: "expected an array of objects",
+        })?;
+    let JsonValue::Array(items) = value else {
+        return Err(invalid_template(table, "expected an array of objects"));
+    };
+    let mut columns = Vec::with_capacity(items.len());
+    for item in items {
+        // Objects aggregated into an array may be kept as JSON text
+        let item = match item {
+            JsonValue::String(text) => {
+                serde_json::from_str(&text).context(ex_error::ParseTableTemplateSnafu {
+                    table,
+                    reason: "expected an array of objects",
+                })?
+            }
+            item => item,
+        };
+        let JsonValue::Object(object) = item else {
+            return Err(invalid_template(table, "expected an array of objects"));
+        };
+        let name = template_key(&object, "COLUMN_NAME")
+            .and_then(JsonValue::as_str)
+            .ok_or_else(|| invalid_template(table, "COLUMN_NAME is missing"))?;
+        let data_type = template_key(&object, "TYPE")
+            .and_then(JsonValue::as_str)
+            .ok_or_else(|| invalid_template(table, "TYPE is missing"))?;
+        let nullable = match template_key(&object, "NULLABLE") {
+            None | Some(JsonValue::Null) => true,
+            Some(JsonValue::Bool(nullable)) => *nullable,
+            Some(JsonValue::String(nullable)) => !nullable.eq_ignore_ascii_case("false"),
+            Some(_) => return Err(invalid_template(table, "NULLABLE is not a boolean")),
+        };
+        let order_id = template_key(&object, "ORDER_ID").and_then(JsonValue::as_i64);
+        columns.push((
+            order_id,
+            TemplateColumn {
+                name: name.to_string(),
+                data_type: data_type.to_string(),
+                nullable,
+            },
+        ));
+    }
+    if columns.is_empty() {
+        return Err(invalid_template(table, "the template has no columns"));
+    }
+    // Stable, so columns without an ORDER_ID keep the order of the array
+    columns.sort_by_key(|(order_id, _)| order_id.unwrap_or(i64::MAX));
+    Ok(columns.into_iter().map(|(_, column)| column).collect())
+}
+
+/// `CREATE TABLE` statement with the columns of a template
+#[must_use]
+pub fn template_table_sql(
+    statement: &TemplateTableStatement,
+    columns: &[TemplateColumn],
+) -> String {
+    let columns = columns
+        .iter()
+        .map(|column| {
+            format!(
+                "{} {}{}",
+                Ident::with_quote('"', column.name.as_str()),
+                column.data_type,
+                if column.nullable { "" } else { " NOT NULL" }
+            )
+        })
+        .collect::<Vec<_>>();
+    format!(
+        "CREATE {}TABLE {}{} ({})",
+        if statement.or_replace {
+            "OR REPLACE "
+        } else {
+            ""
+        },
+        if statement.if_not_exists {
+            "IF NOT EXISTS "
+        } else {
+            ""
+        },
+        statement.name,
+        columns.join(", ")
+    )
+}
@@ -1,18 +1,32 @@
 pub use catalog;
+pub mod cloning;
+pub mod copy_into;
 pub mod datafusion;
 #[cfg(feature = "dedicated-executor")]
 pub mod dedicated_executor;
 pub mod error;
 pub mod error_code;
+pub mod external_tables;
+pub mod file_formats;
+pub mod infer_schema;
 pub mod models;
+pub mod partitioning;
 pub mod query;
 pub mod query_task_result;
 pub mod query_types;
+pub mod roles;
 pub mod running_queries;
+pub mod schema_evolution;
 pub mod service;
 pub mod session;
 pub mod snowflake_error;
+pub mod stages;
+pub mod time_travel;
 pub mod tracing;
+pub mod transaction;
+pub mod undrop;
+pub mod unload;
+pub mod users;
 pub mod utils;
 
 #[cfg(test)]
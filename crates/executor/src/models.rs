@@ -1,4 +1,6 @@
 use crate::query_types::QueryId;
+use crate::stages::FileTransferOptions;
+use catalog_metastore::StageIdent;
 use datafusion::arrow::array::RecordBatch;
 use datafusion::arrow::datatypes::{DataType, Field, Schema as ArrowSchema, TimeUnit};
 use datafusion_common::arrow::datatypes::Schema;
@@ -93,6 +95,8 @@ pub struct QueryResult {
     pub schema: Arc<ArrowSchema>,
     /// Execution plan metrics collected after running the query.
     pub metrics: Vec<QueryMetric>,
+    /// Set for `PUT` and `GET`, whose files are transferred by the client
+    pub file_transfer: Option<FileTransfer>,
 }
 
 impl QueryResult {
@@ -102,6 +106,7 @@ impl QueryResult {
             records,
             schema,
             metrics: Vec::new(),
+            file_transfer: None,
         }
     }
 
@@ -115,6 +120,15 @@ impl QueryResult {
             records,
             schema,
             metrics,
+            file_transfer: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_file_transfer(self, file_transfer: FileTransfer) -> Self {
+        Self {
+            file_transfer: Some(file_transfer),
+            ..self
         }
     }
     #[must_use]
@@ -123,6 +137,27 @@ impl QueryResult {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileTransferCommand {
+    Upload,
+    Download,
+}
+
+/// Files of an internal stage to be transferred by the client: Snowflake drivers upload
+/// and download the files of `PUT` and `GET` themselves, the server only tells them where
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileTransfer {
+    pub command: FileTransferCommand,
+    pub stage: StageIdent,
+    /// Directory of the stage the files are uploaded to or downloaded from
+    pub path: String,
+    /// Local files to upload, or the paths of the stage files to download
+    pub src_locations: Vec<String>,
+    /// Local directory the files are downloaded to
+    pub local_location: Option<String>,
+    pub options: FileTransferOptions,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct QueryMetric {
     pub node_id: usize,
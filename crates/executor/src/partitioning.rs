@@ -0,0 +1,467 @@
+use crate::error::{self as ex_error, Result};
+use catalog_metastore::TableUpdate as MetastoreTableUpdate;
+use iceberg_rust::catalog::commit::{TableRequirement, TableUpdate as IcebergTableUpdate};
+use iceberg_rust::spec::partition::{PartitionField, PartitionSpec, Transform};
+use iceberg_rust::spec::schema::Schema;
+use iceberg_rust::spec::table_metadata::TableMetadata;
+use iceberg_rust::spec::types::{PrimitiveType, Type};
+use snafu::ResultExt;
+use sqlparser::ast::{
+    DataType as SqlDataType, Expr, Function, FunctionArg, FunctionArgExpr, FunctionArguments,
+    ObjectName, Value, ValueWithSpan,
+};
+use sqlparser::dialect::SnowflakeDialect;
+use sqlparser::keywords::Keyword;
+use sqlparser::parser::{Parser, ParserError};
+use sqlparser::tokenizer::{Token, Tokenizer};
+
+type ParseResult<T> = std::result::Result<T, ParserError>;
+
+/// Iceberg assigns partition field ids starting after this one
+const PARTITION_DATA_ID_START: i32 = 999;
+
+/// Partitioning statement the SQL parser does not know
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PartitionStatement {
+    /// `CREATE TABLE ... PARTITION BY (<transforms>)`, with the SQL of the statement
+    /// without the `PARTITION BY` clause
+    CreateTable {
+        sql: String,
+        partition_by: Vec<Expr>,
+    },
+    /// `ALTER TABLE ... SET PARTITION SPEC (<transforms>)`, an empty list of transforms
+    /// makes the table unpartitioned
+    SetPartitionSpec {
+        name: ObjectName,
+        if_exists: bool,
+        partition_by: Vec<Expr>,
+    },
+}
+
+// Cheap check to avoid tokenizing every statement twice
+fn looks_like_partition_statement(sql: &str) -> bool {
+    let upper = sql.to_ascii_uppercase();
+    let mut words = upper.split_whitespace();
+    match words.next() {
+        Some("CREATE") => upper.contains("PARTITION") && words.take(4).any(|word| word == "TABLE"),
+        Some("ALTER") => upper.contains("PARTITION") && upper.contains("SPEC"),
+        _ => false,
+    }
+}
+
+fn parse_transforms(parser: &mut Parser) -> ParseResult<Vec<Expr>> {
+    parser.expect_token(&Token::LParen)?;
+    if parser.consume_token(&Token::RParen) {
+        return Ok(vec![]);
+    }
+    let transforms = parser.parse_comma_separated(Parser::parse_expr)?;
+    parser.expect_token(&Token::RParen)?;
+    Ok(transforms)
+}
+
+fn parse_set_partition_spec(sql: &str) -> ParseResult<Option<PartitionStatement>> {
+    let dialect = SnowflakeDialect;
+    let mut parser = Parser::new(&dialect).try_with_sql(sql)?;
+    if !parser.parse_keywords(&[Keyword::ALTER, Keyword::TABLE]) {
+        return Ok(None);
+    }
+    let if_exists = parser.parse_keywords(&[Keyword::IF, Keyword::EXISTS]);
+    let name = parser.parse_object_name(false)?;
+    if !parser.parse_keywords(&[Keyword::SET, Keyword::PARTITION]) {
+        return Ok(None);
+    }
+    let token = parser.next_token();
+    if !matches!(&token.token, Token::Word(word) if word.value.eq_ignore_ascii_case("SPEC")) {
+        return parser.expected("SPEC", token);
+    }
+    let partition_by = parse_transforms(&mut parser)?;
+    while parser.consume_token(&Token::SemiColon) {}
+    parser.expect_token(&Token::EOF)?;
+    Ok(Some(PartitionStatement::SetPartitionSpec {
+        name,
+        if_exists,
+        partition_by,
+    }))
+}
+
+/// Removes the `PARTITION BY` clause following the column definitions of a
+/// `CREATE TABLE`. The clause is looked for outside of parentheses and before the
+/// `AS` of a query, so window functions of the query are left alone.
+fn parse_create_table(sql: &str) -> ParseResult<Option<PartitionStatement>> {
+    let dialect = SnowflakeDialect;
+    let tokens = Tokenizer::new(&dialect, sql).tokenize()?;
+    let is_word = |token: &Token, keyword: Keyword| matches!(token, Token::Word(word) if word.keyword == keyword);
+    let significant = |from: usize| {
+        (from..tokens.len()).find(|&index| !matches!(tokens[index], Token::Whitespace(_)))
+    };
+
+    let mut depth = 0usize;
+    let mut index = 0;
+    while index < tokens.len() {
+        match &tokens[index] {
+            Token::LParen => depth += 1,
+            Token::RParen => depth = depth.saturating_sub(1),
+            token if depth == 0 && is_word(token, Keyword::AS) => return Ok(None),
+            token if depth == 0 && is_word(token, Keyword::PARTITION) => {
+                let Some(by) =
+                    significant(index + 1).filter(|&by| is_word(&tokens[by], Keyword::BY))
+                else {
+                    return Ok(None);
+                };
+                let Some(start) =
+                    significant(by + 1).filter(|&start| tokens[start] == Token::LParen)
+                else {
+                    return Ok(None);
+                };
+                let mut end = start;
+                let mut clause_depth = 0usize;
+                for (offset, token) in tokens[start..].iter().enumerate() {
+                    match token {
+                        Token::LParen => clause_depth += 1,
+                        Token::RParen => {
+                            clause_depth -= 1;
+                            if clause_depth == 0 {
+                                end = start + offset;
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                if end == start {
+                    return Err(ParserError::ParserError(
+                        "Expected ) to close PARTITION BY".to_string(),
+                    ));
+                }
+                let clause = tokens[start..=end]
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<String>();
+                let mut parser = Parser::new(&dialect).try_with_sql(&clause)?;
+                let partition_by = parse_transforms(&mut parser)?;
+                let sql = tokens[..index]
+                    .iter()
+                    .chain(&tokens[end + 1..])
+                    .map(ToString::to_string)
+                    .collect();
+                return Ok(Some(PartitionStatement::CreateTable { sql, partition_by }));
+            }
+            _ => {}
+        }
+        index += 1;
+    }
+    Ok(None)
+}
+
+/// Parses a partitioning statement, returns `None` if the sql is a different statement
+/// or a `CREATE TABLE` without `PARTITION BY`.
+pub fn parse_partition_statement(sql: &str) -> ParseResult<Option<PartitionStatement>> {
+    if !looks_like_partition_statement(sql) {
+        return Ok(None);
+    }
+    if sql.trim_start().to_ascii_uppercase().starts_with("ALTER") {
+        parse_set_partition_spec(sql)
+    } else {
+        parse_create_table(sql)
+    }
+}
+
+/// Partition field of a table: the transform of the values of a column
+#[derive(Debug, Clone)]
+pub struct PartitionTransform {
+    pub column: String,
+    pub transform: Transform,
+}
+
+fn invalid_partition(table: &str, reason: String) -> ex_error::Error {
+    ex_error::InvalidPartitionSpecSnafu {
+        table: table.to_string(),
+        reason,
+    }
+    .build()
+}
+
+fn column_name(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Identifier(ident) => Some(ident.value.clone()),
+        Expr::CompoundIdentifier(idents) => idents.last().map(|ident| ident.value.clone()),
+        _ => None,
+    }
+}
+
+fn number(expr: &Expr) -> Option<u32> {
+    match expr {
+        Expr::Value(ValueWithSpan {
+            value: Value::Number(value, _),
+            ..
+        }) => value.parse().ok(),
+        _ => None,
+    }
+}
+
+fn string(expr: &Expr) -> Option<String> {
+    match expr {
+        Expr::Value(ValueWithSpan {
+            value: Value::SingleQuotedString(value),
+            ..
+        }) => Some(value.to_ascii_lowercase()),
+        _ => None,
+    }
+}
+
+fn function_args(function: &Function) -> Vec<&Expr> {
+    match &function.args {
+        FunctionArguments::List(list) => list
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                FunctionArg::Unnamed(FunctionArgExpr::Expr(expr)) => Some(expr),
+                _ => None,
+            })
+            .collect(),
+        _ => vec![],
+    }
+}
+
+fn time_transform(unit: &str) -> Option<Transform> {
+    match unit {
+        "year" | "years" => Some(Transform::Year),
+        "month" | "months" => Some(Transform::Month),
+        "day" | "days" => Some(Transform::Day),
+        "hour" | "hours" => Some(Transform::Hour),
+        _ => None,
+    }
+}
+
+/// Transform of an expression of `PARTITION BY`: a column, `identity(col)`,
+/// `year(col)`, `month(col)`, `day(col)`, `hour(col)`, `bucket(n, col)` or
+/// `truncate(n, col)`. With `clustering`, the Snowflake clustering keys these
+/// transforms correspond to are recognized too: `col::date`, `to_date(col)`,
+/// `date_trunc('<unit>', col)` and `left(col, n)` or `substr(col, 1, n)`. A bare
+/// column is no clustering transform, as it would make a partition of every value.
+fn transform(expr: &Expr, clustering: bool) -> Option<PartitionTransform> {
+    let identity = |column: String| PartitionTransform {
+        column,
+        transform: Transform::Identity,
+    };
+    let of = |transform: Transform, expr: &Expr| {
+        column_name(expr).map(|column| PartitionTransform { column, transform })
+    };
+    match expr {
+        Expr::Identifier(_) | Expr::CompoundIdentifier(_) if !clustering => {
+            column_name(expr).map(identity)
+        }
+        Expr::Nested(expr) => transform(expr, clustering),
+        Expr::Cast {
+            expr,
+            data_type: SqlDataType::Date,
+            ..
+        } if clustering => of(Transform::Day, expr),
+        Expr::Function(function) => {
+            let name = function.name.to_string().to_ascii_lowercase();
+            match (name.as_str(), function_args(function).as_slice()) {
+                ("identity", [column]) if !clustering => of(Transform::Identity, column),
+                ("bucket", [count, column]) => of(Transform::Bucket(number(count)?), column),
+                ("truncate", [width, column]) => of(Transform::Truncate(number(width)?), column),
+                (unit, [column]) if time_transform(unit).is_some() => {
+                    of(time_transform(unit)?, column)
+                }
+                ("to_date" | "date", [column]) if clustering => of(Transform::Day, column),
+                ("date_trunc" | "trunc", [unit, column]) if clustering => {
+                    of(time_transform(&string(unit)?)?, column)
+                }
+                ("left", [column, width]) if clustering => {
+                    of(Transform::Truncate(number(width)?), column)
+                }
+                ("substr" | "substring", [column, start, width])
+                    if clustering && number(start) == Some(1) =>
+                {
+                    of(Transform::Truncate(number(width)?), column)
+                }
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Transforms of `PARTITION BY`, any expression that is no transform is an error
+pub fn partition_transforms(table: &str, partition_by: &[Expr]) -> Result<Vec<PartitionTransform>> {
+    partition_by
+        .iter()
+        .map(|expr| {
+            transform(expr, false).ok_or_else(|| {
+                invalid_partition(table, format!("unsupported partition transform {expr}"))
+            })
+        })
+        .collect()
+}
+
+/// Transforms of the Snowflake clustering keys of `CLUSTER BY`. Clustering keys are a
+/// hint for the layout of the data, only keys that are a transform of a column make
+/// partition fields and the others, bare columns among them, are skipped.
+#[must_use]
+pub fn clustering_transforms(cluster_by: &[Expr]) -> Vec<PartitionTransform> {
+    cluster_by
+        .iter()
+        .filter_map(|expr| transform(expr, true))
+        .collect()
+}
+
+fn supports(transform: &Transform, field_type: &Type) -> bool {
+    let Type::Primitive(primitive) = field_type else {
+        return false;
+    };
+    match transform {
+        Transform::Identity => true,
+        Transform::Year | Transform::Month | Transform::Day => matches!(
+            primitive,
+            PrimitiveType::Date | PrimitiveType::Timestamp | PrimitiveType::Timestamptz
+        ),
+        Transform::Hour => matches!(
+            primitive,
+            PrimitiveType::Timestamp | PrimitiveType::Timestamptz
+        ),
+        Transform::Bucket(_) => !matches!(
+            primitive,
+            PrimitiveType::Boolean | PrimitiveType::Float | PrimitiveType::Double
+        ),
+        Transform::Truncate(_) => matches!(
+            primitive,
+            PrimitiveType::Int
+                | PrimitiveType::Long
+                | PrimitiveType::Decimal { .. }
+                | PrimitiveType::String
+                | PrimitiveType::Binary
+        ),
+        _ => false,
+    }
+}
+
+fn field_name(column: &str, transform: &Transform) -> String {
+    let suffix = match transform {
+        Transform::Identity => return column.to_string(),
+        Transform::Year => "year",
+        Transform::Month => "month",
+        Transform::Day => "day",
+        Transform::Hour => "hour",
+        Transform::Bucket(_) => "bucket",
+        Transform::Truncate(_) => "trunc",
+        _ => "void",
+    };
+    format!("{column}_{suffix}")
+}
+
+/// Builds the partition spec of the transforms over the columns of the schema. Fields
+/// of earlier specs of the table keep their ids, new fields get ids after the last
+/// partition id of the table.
+pub fn partition_spec(
+    table: &str,
+    schema: &Schema,
+    transforms: &[PartitionTransform],
+    spec_id: i32,
+    earlier_fields: &[&PartitionField],
+    last_partition_id: i32,
+) -> Result<PartitionSpec> {
+    let mut last_partition_id = earlier_fields
+        .iter()
+        .map(|field| *field.field_id())
+        .fold(last_partition_id.max(PARTITION_DATA_ID_START), i32::max);
+    let mut fields: Vec<PartitionField> = Vec::with_capacity(transforms.len());
+    for PartitionTransform { column, transform } in transforms {
+        let source = schema
+            .fields()
+            .iter()
+            .find(|field| field.name.eq_ignore_ascii_case(column))
+            .ok_or_else(|| invalid_partition(table, format!("column '{column}' does not exist")))?;
+        if !supports(transform, &source.field_type) {
+            return Err(invalid_partition(
+                table,
+                format!(
+                    "transform {transform:?} is not supported for column '{column}' of type {}",
+                    source.field_type
+                ),
+            ));
+        }
+        if fields
+            .iter()
+            .any(|field| *field.source_id() == source.id && field.transform() == transform)
+        {
+            return Err(invalid_partition(
+                table,
+                format!("column '{column}' is partitioned by {transform:?} more than once"),
+            ));
+        }
+        let field_id = if let Some(earlier) = earlier_fields
+            .iter()
+            .find(|field| *field.source_id() == source.id && field.transform() == transform)
+        {
+            *earlier.field_id()
+        } else {
+            last_partition_id += 1;
+            last_partition_id
+        };
+        fields.push(PartitionField::new(
+            source.id,
+            field_id,
+            &field_name(&source.name, transform),
+            transform.clone(),
+        ));
+    }
+
+    let mut builder = PartitionSpec::builder();
+    builder.with_spec_id(spec_id);
+    for field in fields {
+        builder.with_partition_field(field);
+    }
+    builder
+        .build()
+        .context(ex_error::PartitionSpecIcebergSpecSnafu { table })
+}
+
+/// Update adding the partition spec of the transforms to a table and making it the
+/// default spec new data files are written with, `None` if the table already has
+/// this spec. Data files written before keep their partitioning.
+pub fn partition_spec_update(
+    table: &str,
+    metadata: &TableMetadata,
+    transforms: &[PartitionTransform],
+) -> Result<Option<MetastoreTableUpdate>> {
+    let schema = metadata
+        .current_schema(None)
+        .context(ex_error::PartitionSpecIcebergSpecSnafu { table })?;
+    let earlier_fields = metadata
+        .partition_specs
+        .values()
+        .flat_map(|spec| spec.fields())
+        .collect::<Vec<_>>();
+    let spec_id = metadata
+        .partition_specs
+        .keys()
+        .max()
+        .map_or(0, |spec_id| spec_id + 1);
+    let spec = partition_spec(
+        table,
+        schema,
+        transforms,
+        spec_id,
+        &earlier_fields,
+        metadata.last_partition_id,
+    )?;
+    if metadata
+        .partition_specs
+        .get(&metadata.default_spec_id)
+        .is_some_and(|default_spec| default_spec.fields() == spec.fields())
+    {
+        return Ok(None);
+    }
+    Ok(Some(MetastoreTableUpdate {
+        requirements: vec![TableRequirement::AssertDefaultSpecId {
+            default_spec_id: metadata.default_spec_id,
+        }],
+        updates: vec![
+            IcebergTableUpdate::AddSpec { spec },
+            IcebergTableUpdate::SetDefaultSpec { spec_id },
+        ],
+    }))
+}
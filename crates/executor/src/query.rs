@@ -6,46 +6,98 @@ use super::error::{
     self as ex_error, Error, InvalidColumnIdentifierSnafu, MergeSourceNotSupportedSnafu,
     ObjectType as ExistingObjectType, Result,
 };
-use super::running_queries::RunningQueries;
+use super::running_queries::{RunningQueries, StatementTimeRange};
 use super::session::UserSession;
 use super::utils::{NormalizedIdent, is_logical_plan_effectively_empty};
+use crate::cloning::{
+    CloneKind, CloneStatement, cloned_table, parse_clone_statement, pin_snapshot,
+};
+use crate::copy_into::{
+    COPY_ERROR_COLUMN, COPY_ERROR_COLUMN_NAME_COLUMN, CopyFile, CopyOptions, FileLoad, LoadCounts,
+    LoadError, OnError, OnceStream, ValidationMode, file_errors, load_results, load_stream,
+    split_rows, validation_errors,
+};
 use crate::datafusion::logical_plan::merge::MergeIntoCOWSink;
 use crate::datafusion::physical_optimizer::runtime_physical_optimizer_rules;
 use crate::datafusion::physical_plan::merge::{
-    DATA_FILE_PATH_COLUMN, MANIFEST_FILE_PATH_COLUMN, MERGE_INSERTED_COLUMN, MERGE_UPDATED_COLUMN,
-    SOURCE_EXISTS_COLUMN, TARGET_EXISTS_COLUMN,
+    DATA_FILE_PATH_COLUMN, MANIFEST_FILE_PATH_COLUMN, MERGE_DELETED_COLUMN, MERGE_INSERTED_COLUMN,
+    MERGE_UPDATED_COLUMN, SOURCE_EXISTS_COLUMN, TARGET_EXISTS_COLUMN, UPDATE_ROW_ID_COLUMN,
+    UPDATE_ROW_MATCH_COLUMN,
 };
 use crate::datafusion::rewriters::session_context::SessionContextExprRewriter;
 use crate::error::{OperationOn, OperationType};
-use crate::models::{QueryContext, QueryMetric, QueryResult, metrics_set_to_json};
-use crate::query_types::{DdlStType, DmlStType, MiscStType, QueryStats, QueryType};
+use crate::external_tables::{
+    ExternalColumnDef, ExternalFileTable, ExternalTableProvider, ExternalTableStatement,
+    FILE_ROW_NUMBER_COLUMN, FILENAME_COLUMN, FileRefresh, VALUE_COLUMN, check_partition_expression,
+    infer_file_schema, parse_external_table_statement, refresh_files, refresh_results,
+};
+use crate::file_formats::{
+    CsvFileFormat, FileFormatOptions, FileFormatStatement, FileRewrite,
+    parse_file_format_statement, parse_format_name, rewrite_file,
+};
+use crate::infer_schema::{
+    INFER_SCHEMA_FUNCTION, InferSchemaArgs, InferredColumn, TemplateTableStatement, infer_columns,
+    infer_schema_query, parse_template_table_statement, template_columns, template_table_sql,
+};
+use crate::models::{
+    FileTransfer, FileTransferCommand, QueryContext, QueryMetric, QueryResult, SessionMetadataAttr,
+    metrics_set_to_json,
+};
+use crate::partitioning::{
+    PartitionStatement, PartitionTransform, clustering_transforms, parse_partition_statement,
+    partition_spec, partition_spec_update, partition_transforms,
+};
+use crate::query_types::{DdlStType, DmlStType, MiscStType, QueryId, QueryStats, QueryType};
+use crate::roles::{GrantOn, RoleStatement, parse_role_statement};
+use crate::schema_evolution::{SchemaEvolution, table_comment_update};
+use crate::stages::{
+    FileTransferOptions, StageReference, StageStatement, parse_stage_statement,
+    volume_stage_object_store,
+};
+use crate::time_travel::{
+    TimeTravel, TimeTravelPoint, invalid_time_travel, not_created_yet, snapshot_at,
+    take_time_travel_tables,
+};
+use crate::transaction::Transaction;
+use crate::undrop::{UndropStatement, parse_undrop_statement};
+use crate::unload::{UNLOAD_PARTITION_COLUMN, UnloadOptions, UnloadWriter, unload_results};
+use crate::users::{AlterUserAction, UserStatement, parse_user_statement};
+use catalog::catalog::CatalogType;
+use catalog::schema::CachingSchema;
 use catalog::table::{CachingTable, IcebergTableBuilder};
 use catalog_metastore::{
-    AwsAccessKeyCredentials, AwsCredentials, FileVolume, Metastore, S3TablesVolume, S3Volume,
-    TableCreateRequest as MetastoreTableCreateRequest, TableFormat as MetastoreTableFormat,
-    TableIdent as MetastoreTableIdent, Volume, VolumeType,
-    models::volumes::create_object_store_from_url,
+    ACCOUNTADMIN_ROLE, AwsAccessKeyCredentials, AwsCredentials, DroppedObjectIdent, ExternalTable,
+    ExternalTableColumn, ExternalTableFile, FileFormat as MetastoreFileFormat, FileFormatIdent,
+    FileVolume, GrantObject, Metastore, PUBLIC_ROLE, Privilege, Role, RoleIdent, S3TablesVolume,
+    S3Volume, SchemaIdent as MetastoreSchemaIdent, Stage, StageIdent, StageLocation,
+    Table as MetastoreTable, TableCreateRequest as MetastoreTableCreateRequest,
+    TableFormat as MetastoreTableFormat, TableIdent as MetastoreTableIdent, User, Volume,
+    VolumeIdent, VolumeType, models::volumes::create_object_store_from_url,
+};
+use chrono::Utc;
+use datafusion::arrow::array::{Array, AsArray, Int64Array, RecordBatch, StringArray};
+use datafusion::arrow::compute::can_cast_types;
+use datafusion::arrow::datatypes::{
+    DECIMAL128_MAX_PRECISION, DataType, Field, Schema as ArrowSchema, SchemaRef, TimeUnit,
 };
-use datafusion::arrow::array::{Int64Array, RecordBatch, StringArray};
-use datafusion::arrow::datatypes::{DataType, Field, Schema as ArrowSchema, SchemaRef};
 use datafusion::arrow::datatypes::{Fields, SchemaBuilder};
 use datafusion::catalog::TableProvider;
+use datafusion::catalog::streaming::StreamingTable;
 use datafusion::catalog::{CatalogProvider, SchemaProvider};
-use datafusion::datasource::DefaultTableSource;
 use datafusion::datasource::default_table_source::provider_as_source;
-use datafusion::datasource::file_format::FileFormat;
-use datafusion::datasource::file_format::csv::CsvFormat;
-use datafusion::datasource::file_format::file_compression_type::FileCompressionType;
-use datafusion::datasource::file_format::json::JsonFormat;
-use datafusion::datasource::file_format::parquet::ParquetFormat;
+use datafusion::datasource::empty::EmptyTable;
 use datafusion::datasource::listing::{
     ListingOptions, ListingTable, ListingTableConfig, ListingTableUrl,
 };
+use datafusion::datasource::{DefaultTableSource, MemTable, ViewTable};
 use datafusion::execution::session_state::{SessionContextProvider, SessionState};
-use datafusion::logical_expr::{self, col};
+use datafusion::execution::{FunctionRegistry, SendableRecordBatchStream};
+use datafusion::functions::string::expr_fn::{btrim, concat};
+use datafusion::functions_window::expr_fn::row_number;
+use datafusion::logical_expr::{self, cast, col};
 use datafusion::logical_expr::{LogicalPlan, TableSource};
 use datafusion::physical_optimizer::PhysicalOptimizerRule;
-use datafusion::prelude::{CsvReadOptions, DataFrame};
+use datafusion::prelude::DataFrame;
 use datafusion::scalar::ScalarValue;
 use datafusion::sql::parser::{CreateExternalTable, Statement as DFStatement};
 use datafusion::sql::planner::SqlToRel;
@@ -55,6 +107,7 @@ use datafusion::sql::sqlparser::ast::{
 };
 use datafusion::sql::statement::object_name_to_string;
 use datafusion_common::config::ConfigOptions;
+use datafusion_common::tree_node::{Transformed, TreeNode};
 use datafusion_common::{
     Column, DFSchema, DataFusionError, ParamValues, ResolvedTableReference, SchemaReference,
     TableReference, ToDFSchema, plan_datafusion_err,
@@ -62,10 +115,11 @@ use datafusion_common::{
 use datafusion_expr::conditional_expressions::CaseBuilder;
 use datafusion_expr::logical_plan::dml::{DmlStatement, InsertOp, WriteOp};
 use datafusion_expr::planner::ContextProvider;
+use datafusion_expr::utils::split_conjunction;
 use datafusion_expr::{
-    BinaryExpr, CreateMemoryTable, DdlStatement, Expr as DFExpr, ExprSchemable, Extension,
-    JoinType, LogicalPlanBuilder, Operator, Projection, SubqueryAlias, TryCast, and,
-    build_join_schema, is_null, lit, or, when,
+    BinaryExpr, CreateMemoryTable, DdlStatement, Expr as DFExpr, ExprFunctionExt, ExprSchemable,
+    Extension, JoinType, LogicalPlanBuilder, Operator, Projection, SubqueryAlias, TryCast, and,
+    build_join_schema, is_null, is_true, lit, or, when,
 };
 use datafusion_iceberg::DataFusionTable;
 use datafusion_iceberg::table::DataFusionTableConfigBuilder;
@@ -78,38 +132,46 @@ use functions::visitors::{
     table_functions_cte_relation, timestamp, top_limit,
     unimplemented::functions_checker::visit as unimplemented_functions_checker,
 };
+use futures::{StreamExt, TryStreamExt};
 use iceberg_rust::catalog::create::CreateTableBuilder;
 use iceberg_rust::catalog::tabular::Tabular;
 use iceberg_rust::error::Error as IcebergError;
 use iceberg_rust::spec::arrow::schema::new_fields_with_ids;
+use iceberg_rust::spec::partition::Transform;
 use iceberg_rust::spec::schema::Schema;
-use iceberg_rust::spec::snapshot::Snapshot;
-use iceberg_rust::spec::table_metadata::TableMetadata;
 use iceberg_rust::spec::types::StructType;
 use iceberg_rust::spec::values::Value as IcebergValue;
-use iceberg_rust::table::manifest_list::snapshot_partition_bounds;
-use object_store::aws::{AmazonS3Builder, resolve_bucket_region};
-use object_store::{ClientOptions, ObjectStore};
+use iceberg_rust::table::Table as IcebergTable;
+use object_store::aws::resolve_bucket_region;
+use object_store::path::Path as ObjectPath;
+use object_store::prefix::PrefixStore;
+use object_store::{ClientOptions, ObjectMeta, ObjectStore};
+use regex::Regex;
 use snafu::{OptionExt, ResultExt, location};
 use sqlparser::ast::helpers::key_value_options::KeyValueOptions;
 use sqlparser::ast::helpers::stmt_data_loading::StageParamsObject;
 use sqlparser::ast::{
-    AlterTableOperation, AssignmentTarget, CloudProviderParams, MergeAction, MergeClause,
-    MergeClauseKind, MergeInsertKind, ObjectNamePart, ObjectType, PivotValueSource, ShowObjects,
-    ShowStatementFilter, ShowStatementIn, ShowStatementInParentType as ShowType,
-    ShowStatementInParentType, TruncateTableTarget, Use, Value, visit_relations_mut,
+    AlterColumnOperation, AlterTableOperation, AssignmentTarget, CloudProviderParams, ColumnOption,
+    CommentObject, CopyIntoSnowflakeKind, Delete, FromTable, Function, FunctionArg,
+    FunctionArgExpr, FunctionArgumentList, FunctionArguments, Insert, MergeAction, MergeClause,
+    MergeClauseKind, MergeInsertKind, ObjectNamePart, ObjectType, PivotValueSource,
+    RenameTableNameKind, ShowObjects, ShowStatementFilter, ShowStatementIn,
+    ShowStatementInParentType as ShowType, ShowStatementInParentType, TableAlias, TableObject,
+    TableWithJoins, TruncateTableTarget, UpdateTableFromKind, Use, Value, WrappedCollection,
+    visit_expressions, visit_expressions_mut, visit_relations_mut,
 };
 use std::collections::hash_map::Entry;
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Write;
 use std::ops::ControlFlow;
 use std::result::Result as StdResult;
 use std::str::FromStr;
-use std::sync::Arc;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use tracing::Instrument;
 use tracing_attributes::instrument;
 use url::Url;
+use uuid::Uuid;
 
 pub struct UserQuery {
     pub metastore: Arc<dyn Metastore>,
@@ -119,6 +181,11 @@ pub struct UserQuery {
     pub raw_statement: Option<DFStatement>,
     pub session: Arc<UserSession>,
     pub query_context: QueryContext,
+    /// Set for queries issued by the executor on behalf of an already authorized query
+    pub authorized: bool,
+    /// `PARTITION BY` of a `CREATE TABLE`, which is removed from the raw query since
+    /// the SQL parser does not know it
+    pub partition_by: Vec<Expr>,
 }
 
 impl UserQuery {
@@ -134,6 +201,8 @@ impl UserQuery {
             raw_statement: None,
             session,
             query_context,
+            authorized: false,
+            partition_by: Vec::new(),
         }
     }
 
@@ -187,7 +256,103 @@ impl UserQuery {
         Ok(())
     }
 
+    /// Commits the open transaction of the session, if any. The transaction is closed
+    /// even if the commit fails, in which case none of its changes are applied.
+    #[instrument(
+        name = "UserQuery::commit_transaction",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn commit_transaction(&self) -> Result<()> {
+        if let Some(transaction) = self.session.transactions.end() {
+            transaction.commit(&self.metastore).await?;
+        }
+        Ok(())
+    }
+
+    /// Swaps an Iceberg table of an Embucket database for its transaction-scoped provider
+    /// while the session has an open transaction, so the statement reads the state of the
+    /// transaction and its commits are buffered until `COMMIT`.
+    async fn transaction_table_provider(
+        &self,
+        table_ref: &ResolvedTableReference,
+        provider: Arc<dyn TableProvider>,
+    ) -> Result<Arc<dyn TableProvider>> {
+        let Some(transaction) = self.session.transactions.current() else {
+            return Ok(provider);
+        };
+        self.enlisted_table_provider(&transaction, table_ref, provider)
+            .await
+    }
+
+    /// Swaps an Iceberg table of an Embucket database for its provider scoped to
+    /// `transaction`, other tables are returned unchanged
+    async fn enlisted_table_provider(
+        &self,
+        transaction: &Transaction,
+        table_ref: &ResolvedTableReference,
+        provider: Arc<dyn TableProvider>,
+    ) -> Result<Arc<dyn TableProvider>> {
+        let transactional = self
+            .session
+            .ctx
+            .state()
+            .catalog_list()
+            .as_any()
+            .downcast_ref::<EmbucketCatalogList>()
+            .and_then(|catalog_list| catalog_list.catalogs.get(table_ref.catalog.as_ref()))
+            .is_some_and(|catalog| matches!(catalog.catalog_type, CatalogType::Embucket));
+        if !transactional {
+            return Ok(provider);
+        }
+
+        if let Some(caching_table) = provider.as_any().downcast_ref::<CachingTable>()
+            && let Some(table) = caching_table
+                .table
+                .as_any()
+                .downcast_ref::<DataFusionTable>()
+        {
+            let table = transaction.table_provider(table_ref, table).await?;
+            return Ok(Arc::new(CachingTable::new_with_schema(
+                caching_table.name.clone(),
+                caching_table.schema(),
+                Arc::new(table),
+            )));
+        }
+        if let Some(table) = provider.as_any().downcast_ref::<DataFusionTable>() {
+            return Ok(Arc::new(
+                transaction.table_provider(table_ref, table).await?,
+            ));
+        }
+        Ok(provider)
+    }
+
     #[instrument(name = "UserQuery::create_catalog", level = "debug", skip(self), err)]
+    /// Registers the catalog of a database restored by `UNDROP`
+    async fn register_catalog(&self, catalog: &str) -> Result<()> {
+        let database = self
+            .metastore
+            .get_database(&catalog.to_string())
+            .await
+            .context(ex_error::MetastoreSnafu)?
+            .context(ex_error::DatabaseNotFoundSnafu { db: catalog })?;
+        if let Some(catalog_list_impl) = self
+            .session
+            .ctx
+            .state()
+            .catalog_list()
+            .as_any()
+            .downcast_ref::<EmbucketCatalogList>()
+        {
+            catalog_list_impl
+                .register_database(&database)
+                .await
+                .context(ex_error::CreateDatabaseSnafu)?;
+        }
+        Ok(())
+    }
+
     async fn create_catalog(&self, catalog: &str, volume: &str) -> Result<()> {
         if let Some(catalog_list_impl) = self
             .session
@@ -216,6 +381,12 @@ impl UserQuery {
             query_context: self.query_context.clone(),
             recent_queries: self.session.recent_queries.clone(),
             running_queries: self.running_queries.clone(),
+            current_transaction: self
+                .session
+                .transactions
+                .current()
+                .map(|transaction| transaction.id),
+            last_transaction: self.session.transactions.last_id(),
         }
     }
 
@@ -274,6 +445,7 @@ impl UserQuery {
                 // match DDL statements:
                 Statement::AlterSession { .. } => save(QueryType::Ddl(DdlStType::AlterSession)),
                 Statement::AlterTable { .. } => save(QueryType::Ddl(DdlStType::AlterTable)),
+                Statement::Comment { .. } => save(QueryType::Ddl(DdlStType::Comment)),
                 Statement::CreateTable { .. } => save(QueryType::Ddl(DdlStType::CreateTable)),
                 Statement::CreateView { .. } => save(QueryType::Ddl(DdlStType::CreateView)),
                 Statement::CreateDatabase { .. } => save(QueryType::Ddl(DdlStType::CreateDatabase)),
@@ -282,6 +454,10 @@ impl UserQuery {
                 }
                 Statement::CreateSchema { .. } => save(QueryType::Ddl(DdlStType::CreateSchema)),
                 Statement::CreateStage { .. } => save(QueryType::Ddl(DdlStType::CreateStage)),
+                Statement::CopyIntoSnowflake {
+                    kind: CopyIntoSnowflakeKind::Location,
+                    ..
+                } => save(QueryType::Dml(DmlStType::Unload)),
                 Statement::CopyIntoSnowflake { .. } => {
                     save(QueryType::Ddl(DdlStType::CopyIntoSnowflake));
                 }
@@ -321,7 +497,7 @@ impl UserQuery {
         } else if let DFStatement::Explain(..) = statement {
             save(QueryType::Misc(MiscStType::Explain));
         } else if let DFStatement::CopyTo(..) = statement {
-            save(QueryType::Misc(MiscStType::CopyTo));
+            save(QueryType::Dml(DmlStType::Unload));
         }
     }
 
@@ -337,6 +513,61 @@ impl UserQuery {
         err
     )]
     pub async fn execute(&mut self) -> Result<QueryResult> {
+        if let Some(statement) =
+            parse_role_statement(&self.raw_query).context(ex_error::SqlParserSnafu)?
+        {
+            return self.execute_role_statement(statement).await;
+        }
+        if let Some(statement) =
+            parse_user_statement(&self.raw_query).context(ex_error::SqlParserSnafu)?
+        {
+            return self.execute_user_statement(statement).await;
+        }
+        if let Some(statement) =
+            parse_stage_statement(&self.raw_query).context(ex_error::SqlParserSnafu)?
+        {
+            return self.execute_stage_statement(statement).await;
+        }
+        if let Some(statement) =
+            parse_file_format_statement(&self.raw_query).context(ex_error::SqlParserSnafu)?
+        {
+            return self.execute_file_format_statement(statement).await;
+        }
+        if let Some(statement) =
+            parse_external_table_statement(&self.raw_query).context(ex_error::SqlParserSnafu)?
+        {
+            return Box::pin(self.execute_external_table_statement(statement)).await;
+        }
+        if let Some(statement) =
+            parse_template_table_statement(&self.raw_query).context(ex_error::SqlParserSnafu)?
+        {
+            return Box::pin(self.create_table_using_template(statement)).await;
+        }
+        if let Some(statement) =
+            parse_clone_statement(&self.raw_query).context(ex_error::SqlParserSnafu)?
+        {
+            return Box::pin(self.execute_clone_statement(statement)).await;
+        }
+        if let Some(statement) =
+            parse_undrop_statement(&self.raw_query).context(ex_error::SqlParserSnafu)?
+        {
+            return Box::pin(self.execute_undrop_statement(statement)).await;
+        }
+        match parse_partition_statement(&self.raw_query).context(ex_error::SqlParserSnafu)? {
+            Some(PartitionStatement::CreateTable { sql, partition_by }) => {
+                self.raw_query = sql;
+                self.partition_by = partition_by;
+            }
+            Some(PartitionStatement::SetPartitionSpec {
+                name,
+                if_exists,
+                partition_by,
+            }) => {
+                return Box::pin(self.set_partition_spec(name, if_exists, partition_by)).await;
+            }
+            None => {}
+        }
+
         let statement = self.parse_query().context(ex_error::DataFusionSnafu)?;
         self.query = statement.to_string();
         self.raw_statement = Some(statement.clone());
@@ -353,6 +584,20 @@ impl UserQuery {
         // 4. Single place to rewrite-optimize-adjust logical plan
         // etc
         if let DFStatement::Statement(s) = statement {
+            // DDL statements implicitly commit the open transaction, as in Snowflake
+            if matches!(
+                *s,
+                Statement::CreateTable { .. }
+                    | Statement::CreateView { .. }
+                    | Statement::CreateDatabase { .. }
+                    | Statement::CreateSchema { .. }
+                    | Statement::AlterTable { .. }
+                    | Statement::Comment { .. }
+                    | Statement::Drop { .. }
+                    | Statement::Truncate { .. }
+            ) {
+                self.commit_transaction().await?;
+            }
             match *s {
                 Statement::AlterSession {
                     set,
@@ -418,6 +663,12 @@ impl UserQuery {
                     // We support only CSV uploads for now
                     return Box::pin(self.create_stage_query(*s)).await;
                 }
+                Statement::CopyIntoSnowflake {
+                    kind: CopyIntoSnowflakeKind::Location,
+                    ..
+                } => {
+                    return Box::pin(self.copy_into_location_query(*s)).await;
+                }
                 Statement::CopyIntoSnowflake { .. } => {
                     return Box::pin(self.copy_into_snowflake_query(*s)).await;
                 }
@@ -429,10 +680,40 @@ impl UserQuery {
                 } => {
                     return Box::pin(self.alter_table(name, operations, if_exists)).await;
                 }
-                Statement::StartTransaction { .. }
-                | Statement::Commit { .. }
-                | Statement::Rollback { .. }
-                | Statement::Update { .. } => return self.status_response(),
+                Statement::Comment {
+                    object_type,
+                    object_name,
+                    comment,
+                    if_exists,
+                } => {
+                    return Box::pin(self.comment_query(
+                        object_type,
+                        object_name,
+                        comment,
+                        if_exists,
+                    ))
+                    .await;
+                }
+                Statement::StartTransaction { .. } => {
+                    self.session.transactions.begin();
+                    return self.status_response();
+                }
+                Statement::Commit { .. } => {
+                    self.commit_transaction().await?;
+                    return self.status_response();
+                }
+                Statement::Rollback { savepoint, .. } => {
+                    if savepoint.is_some() {
+                        return ex_error::NotSupportedStatementSnafu {
+                            statement: "ROLLBACK TO SAVEPOINT".to_string(),
+                        }
+                        .fail();
+                    }
+                    self.session.transactions.rollback();
+                    return self.status_response();
+                }
+                Statement::Update { .. } => return Box::pin(self.update_query(*s)).await,
+                Statement::Delete { .. } => return Box::pin(self.delete_query(*s)).await,
                 Statement::Insert { .. } => {
                     return Box::pin(self.execute_with_custom_plan(&self.query)).await;
                 }
@@ -472,15 +753,26 @@ impl UserQuery {
             // plan ourselves, then wrap it in the equivalent `LogicalPlan::Explain`
             // / `LogicalPlan::Analyze` that DataFusion's SQL path would have
             // produced. This lets callers actually inspect the plan and see
-            // physical-level metrics via `EXPLAIN ANALYZE MERGE`.
+            // physical-level metrics via `EXPLAIN ANALYZE MERGE`. `UPDATE` and
+            // `DELETE` are planned on the same copy-on-write sink, so they are
+            // routed the same way.
             if let DFStatement::Statement(inner) = explain.statement.as_ref()
-                && matches!(inner.as_ref(), Statement::Merge { .. })
+                && matches!(
+                    inner.as_ref(),
+                    Statement::Merge { .. } | Statement::Update { .. } | Statement::Delete(_)
+                )
             {
                 let analyze = explain.analyze;
                 let verbose = explain.verbose;
                 let format = explain.format.clone();
-                let merge_stmt = (**inner).clone();
-                let merge_plan = Box::pin(self.merge_to_logical_plan(merge_stmt)).await?;
+                let dml_stmt = (**inner).clone();
+                let merge_plan = match dml_stmt {
+                    Statement::Update { .. } => {
+                        Box::pin(self.update_to_logical_plan(dml_stmt)).await?
+                    }
+                    Statement::Delete(_) => Box::pin(self.delete_to_logical_plan(dml_stmt)).await?,
+                    _ => Box::pin(self.merge_to_logical_plan(dml_stmt)).await?,
+                };
                 let merge_plan = Arc::new(merge_plan);
                 let schema = datafusion_expr::LogicalPlan::explain_schema()
                     .to_dfschema_ref()
@@ -526,7 +818,7 @@ impl UserQuery {
         &mut self,
         mut subquery: Box<Query>,
     ) -> Result<QueryResult> {
-        self.traverse_and_update_query(subquery.as_mut()).await;
+        self.traverse_and_update_query(subquery.as_mut()).await?;
         Box::pin(self.execute_with_custom_plan(&subquery.to_string())).await
     }
 
@@ -546,7 +838,12 @@ impl UserQuery {
             Use::Schema(n) => ("schema", n.to_string()),
             Use::Database(n) => ("database", n.to_string()),
             Use::Warehouse(n) => ("warehouse", n.to_string()),
-            Use::Role(n) => ("role", n.to_string()),
+            Use::Role(n) => {
+                let role = Role::normalize_name(&object_name_to_string(&n));
+                self.use_role(&role).await?;
+                self.session.set_role(role);
+                return self.status_response();
+            }
             Use::Object(n) => ("object", n.to_string()),
             Use::SecondaryRoles(sr) => ("secondary_roles", sr.to_string()),
             Use::Default => ("", String::new()),
@@ -704,145 +1001,564 @@ impl UserQuery {
                     schema: &resolved.schema.to_string(),
                     db: &resolved.catalog.to_string(),
                 })?;
-        if !if_exists {
-            schema
-                .table(&resolved.table)
-                .await
-                .context(ex_error::DataFusionSnafu)?
-                .context(ex_error::TableNotFoundInSchemaInDatabaseSnafu {
-                    operation_on: OperationOn::Table(OperationType::Alter),
-                    table: &resolved.table.to_string(),
-                    schema: &resolved.schema.to_string(),
-                    db: &resolved.catalog.to_string(),
-                })?;
-        }
-        self.status_response()
-    }
-
-    #[instrument(name = "UserQuery::drop_query", level = "trace", skip(self), err)]
-    #[allow(clippy::too_many_lines)]
-    pub async fn drop_query(&self, statement: Statement) -> Result<QueryResult> {
-        let Statement::Drop {
-            object_type,
-            names,
-            cascade,
-            if_exists,
-            ..
-        } = statement.clone()
-        else {
-            return ex_error::OnlyDropStatementsSnafu.fail();
-        };
-
-        // DROP DATABASE is a special case, since it is not a part of iceberg catalog
-        if object_type == ObjectType::Database {
-            if let Some(database) = names.first() {
-                self.drop_catalog(&object_name_to_string(database), cascade)
-                    .await?;
+        let table = schema
+            .table(&resolved.table)
+            .await
+            .context(ex_error::DataFusionSnafu)?;
+        if table.is_none() {
+            if if_exists {
                 return self.status_response();
             }
-            let database_name = names
-                .iter()
-                .map(ToString::to_string)
-                .collect::<Vec<_>>()
-                .join(", ");
-            return ex_error::InvalidDatabaseIdentifierSnafu {
-                ident: database_name,
+            return ex_error::TableNotFoundInSchemaInDatabaseSnafu {
+                operation_on: OperationOn::Table(OperationType::Alter),
+                table: resolved.table.to_string(),
+                schema: resolved.schema.to_string(),
+                db: resolved.catalog.to_string(),
             }
             .fail();
         }
+        self.authorize(Privilege::Ownership, &table_grant_object(&resolved))
+            .await?;
 
-        let mut plan = self.sql_statement_to_plan(statement).await?;
-        let table_ref = match &mut plan {
-            LogicalPlan::Ddl(ddl) => match ddl {
-                DdlStatement::DropTable(t) => {
-                    let resolved = self.resolve_table_ref(t.name.clone());
-                    t.name = resolved.clone().into();
-                    resolved
-                }
-                DdlStatement::DropView(v) => {
-                    let resolved = self.resolve_table_ref(v.name.clone());
-                    v.name = resolved.clone().into();
-                    resolved
-                }
-                DdlStatement::DropCatalogSchema(s) => {
-                    let resolved = self.resolve_schema_ref(s.name.clone());
-                    s.name = resolved;
-                    self.schema_ref_to_resolved(s.name.clone())
+        if let [operation] = operations.as_slice() {
+            match operation {
+                AlterTableOperation::RenameTable {
+                    table_name:
+                        RenameTableNameKind::As(new_name) | RenameTableNameKind::To(new_name),
+                } => return self.rename_table(&resolved, new_name).await,
+                AlterTableOperation::SwapWith { table_name } => {
+                    return self.swap_tables(&resolved, table_name).await;
                 }
-                _ => return ex_error::OnlyDropStatementsSnafu.fail(),
-            },
-            _ => return ex_error::OnlyDropStatementsSnafu.fail(),
-        };
-
-        let catalog_name = table_ref.catalog.as_ref();
-        let schema_name = table_ref.schema.to_string();
-
-        // Inject more information to the error
-        let catalog = self.get_catalog(catalog_name).map_err(|_| {
-            ex_error::CatalogNotFoundSnafu {
-                operation_on: match object_type {
-                    ObjectType::Table | ObjectType::View => OperationOn::Table(OperationType::Drop),
-                    ObjectType::Schema => OperationOn::Schema(OperationType::Drop),
-                    ObjectType::Database => OperationOn::Database(OperationType::Drop),
-                    _ => OperationOn::Unknown,
-                },
-                catalog: catalog_name,
+                _ => {}
             }
-            .build()
-        })?;
+        }
 
-        // Check schema/table existence
-        match object_type {
-            ObjectType::Table | ObjectType::View => {
-                if let Some(schema) = catalog.schema(&schema_name) {
-                    if !if_exists
-                        && schema
-                            .table(&table_ref.table)
-                            .await
-                            .context(ex_error::DataFusionSnafu)?
-                            .is_none()
-                    {
-                        ex_error::TableNotFoundInSchemaInDatabaseSnafu {
-                            operation_on: OperationOn::Table(OperationType::Drop),
-                            table: table_ref.table.to_string(),
-                            schema: schema_name,
-                            db: catalog_name.to_string(),
+        let ident = MetastoreTableIdent::new(&resolved.catalog, &resolved.schema, &resolved.table);
+        let mut evolution = self.schema_evolution(&ident).await?;
+        let state = self.session.ctx.state();
+        let planner_context = SessionContextProvider {
+            state: &state,
+            tables: HashMap::new(),
+        };
+        let planner = SqlToRel::new(&planner_context);
+        for operation in operations {
+            match operation {
+                AlterTableOperation::AddColumn {
+                    if_not_exists,
+                    column_def,
+                    ..
+                } => {
+                    let comment = column_def.options.iter().find_map(|option| {
+                        if let ColumnOption::Comment(comment) = &option.option {
+                            Some(comment.clone())
+                        } else {
+                            None
                         }
-                        .fail()?;
+                    });
+                    let schema = planner
+                        .build_schema(vec![column_def])
+                        .context(ex_error::DataFusionSnafu)?;
+                    for field in schema.fields() {
+                        evolution.add_column(field, comment.clone(), if_not_exists)?;
                     }
-                } else {
-                    return ex_error::SchemaNotFoundInDatabaseSnafu {
-                        operation_on: OperationOn::Table(OperationType::Drop),
-                        schema: schema_name,
-                        db: catalog_name.to_string(),
+                }
+                AlterTableOperation::DropColumn {
+                    column_names,
+                    if_exists,
+                    ..
+                } => {
+                    for column in column_names {
+                        evolution.drop_column(&column.value, if_exists)?;
                     }
-                    .fail();
                 }
-            }
-            ObjectType::Schema if !if_exists && catalog.schema(&schema_name).is_none() => {
-                return ex_error::SchemaNotFoundInDatabaseSnafu {
-                    operation_on: OperationOn::Table(OperationType::Drop),
-                    schema: schema_name,
-                    db: catalog_name.to_string(),
+                AlterTableOperation::RenameColumn {
+                    old_column_name,
+                    new_column_name,
+                } => {
+                    evolution.rename_column(&old_column_name.value, &new_column_name.value)?;
+                }
+                AlterTableOperation::AlterColumn { column_name, op } => match op {
+                    AlterColumnOperation::SetNotNull => {
+                        // Iceberg only allows a required column if no row holds a null
+                        if evolution.contains(&column_name.value)
+                            && self.has_nulls(&resolved, &column_name.value).await?
+                        {
+                            return ex_error::InvalidSchemaChangeSnafu {
+                                table: resolved.table.to_string(),
+                                reason: format!(
+                                    "column '{}' contains null values",
+                                    column_name.value
+                                ),
+                            }
+                            .fail();
+                        }
+                        evolution.set_required(&column_name.value, true)?;
+                    }
+                    AlterColumnOperation::DropNotNull => {
+                        evolution.set_required(&column_name.value, false)?;
+                    }
+                    AlterColumnOperation::SetDataType { data_type, .. } => {
+                        let data_type = planner
+                            .convert_data_type(&data_type)
+                            .context(ex_error::DataFusionSnafu)?;
+                        let field = Field::new(column_name.value.clone(), data_type, true);
+                        evolution.set_type(&column_name.value, &field)?;
+                    }
+                    op => {
+                        return ex_error::NotSupportedStatementSnafu {
+                            statement: format!("ALTER TABLE ... ALTER COLUMN {op}"),
+                        }
+                        .fail();
+                    }
+                },
+                operation => {
+                    return ex_error::NotSupportedStatementSnafu {
+                        statement: format!("ALTER TABLE ... {operation}"),
+                    }
+                    .fail();
                 }
-                .fail();
             }
-            _ => {}
         }
-        self.execute_logical_plan(plan).await?;
+        if let Some(update) = evolution.into_update()? {
+            self.metastore
+                .update_table(&ident, update)
+                .await
+                .context(ex_error::MetastoreSnafu)?;
+            self.invalidate_table(&resolved);
+        }
         self.status_response()
     }
 
-    #[allow(clippy::redundant_else, clippy::too_many_lines)]
+    /// `ALTER TABLE ... SET PARTITION SPEC`. Data files written from now on are
+    /// partitioned by the new spec, the existing ones keep their partitioning.
     #[instrument(
-        name = "UserQuery::create_table_query",
+        name = "UserQuery::set_partition_spec",
         level = "trace",
         skip(self),
         err
     )]
-    pub async fn create_table_query(&self, statement: Statement) -> Result<QueryResult> {
-        let Statement::CreateTable(mut create_table_statement) = statement.clone() else {
-            return ex_error::OnlyCreateTableStatementsSnafu.fail();
+    async fn set_partition_spec(
+        &self,
+        name: ObjectName,
+        if_exists: bool,
+        partition_by: Vec<Expr>,
+    ) -> Result<QueryResult> {
+        self.running_queries.update_stats(
+            self.query_context.query_id,
+            &QueryStats::default().with_query_type(QueryType::Ddl(DdlStType::AlterTable)),
+        );
+        // DDL statements implicitly commit the open transaction, as in Snowflake
+        self.commit_transaction().await?;
+
+        let resolved = self.resolve_table_ref(&self.resolve_table_object_name(name.0)?);
+        let ident = MetastoreTableIdent::new(&resolved.catalog, &resolved.schema, &resolved.table);
+        let Some(table) = self
+            .metastore
+            .get_table(&ident)
+            .await
+            .context(ex_error::MetastoreSnafu)?
+        else {
+            if if_exists {
+                return self.status_response();
+            }
+            return ex_error::TableNotFoundInSchemaInDatabaseSnafu {
+                operation_on: OperationOn::Table(OperationType::Alter),
+                table: resolved.table.to_string(),
+                schema: resolved.schema.to_string(),
+                db: resolved.catalog.to_string(),
+            }
+            .fail();
+        };
+        self.authorize(Privilege::Ownership, &table_grant_object(&resolved))
+            .await?;
+
+        let transforms = partition_transforms(&resolved.table, &partition_by)?;
+        if let Some(update) = partition_spec_update(&resolved.table, &table.metadata, &transforms)?
+        {
+            self.metastore
+                .update_table(&ident, update)
+                .await
+                .context(ex_error::MetastoreSnafu)?;
+            self.invalidate_table(&resolved);
+        }
+        self.status_response()
+    }
+
+    /// `COMMENT ON TABLE` and `COMMENT ON COLUMN`. The comment of a column is the doc
+    /// of its field in the Iceberg schema, so it is committed as a new schema.
+    #[instrument(name = "UserQuery::comment_query", level = "trace", skip(self), err)]
+    pub async fn comment_query(
+        &self,
+        object_type: CommentObject,
+        object_name: ObjectName,
+        comment: Option<String>,
+        if_exists: bool,
+    ) -> Result<QueryResult> {
+        let mut parts = object_name.0;
+        let column = match object_type {
+            CommentObject::Table => None,
+            CommentObject::Column => parts
+                .pop()
+                .and_then(|part| part.as_ident().map(|ident| ident.value.clone())),
+            object_type => {
+                return ex_error::NotSupportedStatementSnafu {
+                    statement: format!("COMMENT ON {object_type}"),
+                }
+                .fail();
+            }
+        };
+        let resolved = self.resolve_table_ref(&self.resolve_table_object_name(parts)?);
+        let ident = MetastoreTableIdent::new(&resolved.catalog, &resolved.schema, &resolved.table);
+        if !self
+            .metastore
+            .table_exists(&ident)
+            .await
+            .context(ex_error::MetastoreSnafu)?
+        {
+            if if_exists {
+                return self.status_response();
+            }
+            return ex_error::TableNotFoundInSchemaInDatabaseSnafu {
+                operation_on: OperationOn::Table(OperationType::Alter),
+                table: resolved.table.to_string(),
+                schema: resolved.schema.to_string(),
+                db: resolved.catalog.to_string(),
+            }
+            .fail();
+        }
+        self.authorize(Privilege::Ownership, &table_grant_object(&resolved))
+            .await?;
+
+        let update = if let Some(column) = column {
+            let mut evolution = self.schema_evolution(&ident).await?;
+            evolution.set_comment(&column, comment)?;
+            evolution.into_update()?
+        } else {
+            Some(table_comment_update(comment))
+        };
+        if let Some(update) = update {
+            self.metastore
+                .update_table(&ident, update)
+                .await
+                .context(ex_error::MetastoreSnafu)?;
+            self.invalidate_table(&resolved);
+        }
+        self.status_response()
+    }
+
+    /// Schema evolution of a table of the metastore. Tables of other catalogs, such as
+    /// S3 Tables, are not evolved by Embucket.
+    async fn schema_evolution(&self, ident: &MetastoreTableIdent) -> Result<SchemaEvolution> {
+        let Some(table) = self
+            .metastore
+            .get_table(ident)
+            .await
+            .context(ex_error::MetastoreSnafu)?
+        else {
+            return ex_error::NotSupportedStatementSnafu {
+                statement: format!("ALTER TABLE on {ident}, which is not an Embucket table"),
+            }
+            .fail();
+        };
+        SchemaEvolution::new(&ident.table, &table.metadata)
+    }
+
+    async fn has_nulls(&self, table: &ResolvedTableReference, column: &str) -> Result<bool> {
+        let result = self
+            .execute_sql(&format!(
+                "SELECT COUNT(*) FROM {}.{}.{} WHERE {} IS NULL",
+                Ident::with_quote('"', table.catalog.as_ref()),
+                Ident::with_quote('"', table.schema.as_ref()),
+                Ident::with_quote('"', table.table.as_ref()),
+                Ident::with_quote('"', column.to_ascii_lowercase())
+            ))
+            .await?;
+        Ok(result
+            .records
+            .first()
+            .and_then(|batch| batch.column(0).as_any().downcast_ref::<Int64Array>())
+            .is_some_and(|nulls| !nulls.is_empty() && nulls.value(0) > 0))
+    }
+
+    /// `ALTER TABLE ... RENAME TO`, which may move the table to another schema of its
+    /// database. The grants on the table follow it.
+    async fn rename_table(
+        &self,
+        table: &ResolvedTableReference,
+        new_name: &ObjectName,
+    ) -> Result<QueryResult> {
+        let new_table =
+            self.resolve_table_ref(&self.resolve_table_object_name(new_name.0.clone())?);
+        if new_table.catalog != table.catalog {
+            return ex_error::NotSupportedStatementSnafu {
+                statement: "ALTER TABLE ... RENAME TO a table of another database".to_string(),
+            }
+            .fail();
+        }
+        if new_table.schema != table.schema {
+            self.authorize(
+                Privilege::CreateTable,
+                &GrantObject::schema(&new_table.catalog, &new_table.schema),
+            )
+            .await?;
+        }
+        self.metastore
+            .rename_table(
+                &MetastoreTableIdent::new(&table.catalog, &table.schema, &table.table),
+                &MetastoreTableIdent::new(&new_table.catalog, &new_table.schema, &new_table.table),
+            )
+            .await
+            .context(ex_error::MetastoreSnafu)?;
+        self.invalidate_table(table);
+        self.invalidate_table(&new_table);
+        self.move_table_grants(&[(table_grant_object(table), table_grant_object(&new_table))])
+            .await?;
+        self.status_response()
+    }
+
+    /// `ALTER TABLE ... SWAP WITH`: the tables exchange their names and their grants
+    async fn swap_tables(
+        &self,
+        table: &ResolvedTableReference,
+        other_name: &ObjectName,
+    ) -> Result<QueryResult> {
+        let other = self.resolve_table_ref(&self.resolve_table_object_name(other_name.0.clone())?);
+        if other.catalog != table.catalog {
+            return ex_error::NotSupportedStatementSnafu {
+                statement: "ALTER TABLE ... SWAP WITH a table of another database".to_string(),
+            }
+            .fail();
+        }
+        self.authorize(Privilege::Ownership, &table_grant_object(&other))
+            .await?;
+        self.metastore
+            .swap_tables(
+                &MetastoreTableIdent::new(&table.catalog, &table.schema, &table.table),
+                &MetastoreTableIdent::new(&other.catalog, &other.schema, &other.table),
+            )
+            .await
+            .context(ex_error::MetastoreSnafu)?;
+        self.invalidate_table(table);
+        self.invalidate_table(&other);
+        let (table_object, other_object) = (table_grant_object(table), table_grant_object(&other));
+        self.move_table_grants(&[
+            (table_object.clone(), other_object.clone()),
+            (other_object, table_object),
+        ])
+        .await?;
+        self.status_response()
+    }
+
+    /// Moves the grants of roles on tables to other tables, all at once so that tables
+    /// can exchange their grants.
+    async fn move_table_grants(&self, moves: &[(GrantObject, GrantObject)]) -> Result<()> {
+        for role in self
+            .metastore
+            .list_roles()
+            .await
+            .context(ex_error::MetastoreSnafu)?
+        {
+            let mut role = role.data;
+            let mut moved = false;
+            for grant in &mut role.grants {
+                if let Some((_, to)) = moves.iter().find(|(from, _)| *from == grant.on) {
+                    grant.on = to.clone();
+                    moved = true;
+                }
+            }
+            if moved {
+                self.metastore
+                    .update_role(&role.name.clone(), role)
+                    .await
+                    .context(ex_error::MetastoreSnafu)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Drops the cached provider of a table whose metadata changed outside of the
+    /// provider, so the next statement loads the table from the metastore again.
+    fn invalidate_table(&self, table: &ResolvedTableReference) {
+        if let Ok(schema) = self.schema_for_ref(table.clone())
+            && let Some(schema) = schema.as_any().downcast_ref::<CachingSchema>()
+        {
+            schema.tables_cache.remove(table.table.as_ref());
+        }
+    }
+
+    #[instrument(name = "UserQuery::drop_query", level = "trace", skip(self), err)]
+    #[allow(clippy::too_many_lines)]
+    pub async fn drop_query(&self, statement: Statement) -> Result<QueryResult> {
+        let Statement::Drop {
+            object_type,
+            names,
+            cascade,
+            if_exists,
+            ..
+        } = statement.clone()
+        else {
+            return ex_error::OnlyDropStatementsSnafu.fail();
+        };
+
+        // DROP DATABASE is a special case, since it is not a part of iceberg catalog
+        if object_type == ObjectType::Database {
+            if let Some(database) = names.first() {
+                let database = object_name_to_string(database);
+                let grant_object = GrantObject::database(&database);
+                if self.get_catalog(&database).is_ok() {
+                    self.authorize(Privilege::Ownership, &grant_object).await?;
+                }
+                self.drop_catalog(&database, cascade).await?;
+                self.revoke_grants_on(&grant_object).await?;
+                return self.status_response();
+            }
+            let database_name = names
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            return ex_error::InvalidDatabaseIdentifierSnafu {
+                ident: database_name,
+            }
+            .fail();
+        }
+
+        if object_type == ObjectType::Stage {
+            for name in &names {
+                let ident = self.resolve_stage_ident(name)?;
+                self.authorize_schema_usage(&ident.database, &ident.schema)
+                    .await?;
+                if let Some(stage) = self
+                    .metastore
+                    .get_stage(&ident)
+                    .await
+                    .context(ex_error::MetastoreSnafu)?
+                {
+                    let grant_object = stage_grant_object(&ident);
+                    self.authorize(Privilege::Ownership, &grant_object).await?;
+                    // Files of internal stages belong to the stage
+                    if matches!(stage.location, StageLocation::Internal { .. }) {
+                        let store = self.stage_object_store(&stage).await?;
+                        for file in list_stage_files(&store, "", None).await? {
+                            store
+                                .delete(&file.location)
+                                .await
+                                .context(ex_error::ObjectStoreSnafu)?;
+                        }
+                    }
+                    self.metastore
+                        .delete_stage(&ident)
+                        .await
+                        .context(ex_error::MetastoreSnafu)?;
+                    self.revoke_grants_on(&grant_object).await?;
+                } else if !if_exists {
+                    return ex_error::StageNotFoundSnafu {
+                        stage: ident.to_string(),
+                    }
+                    .fail();
+                }
+            }
+            return self.status_response();
+        }
+
+        let mut plan = self.sql_statement_to_plan(statement).await?;
+        let table_ref = match &mut plan {
+            LogicalPlan::Ddl(ddl) => match ddl {
+                DdlStatement::DropTable(t) => {
+                    let resolved = self.resolve_table_ref(t.name.clone());
+                    t.name = resolved.clone().into();
+                    resolved
+                }
+                DdlStatement::DropView(v) => {
+                    let resolved = self.resolve_table_ref(v.name.clone());
+                    v.name = resolved.clone().into();
+                    resolved
+                }
+                DdlStatement::DropCatalogSchema(s) => {
+                    let resolved = self.resolve_schema_ref(s.name.clone());
+                    s.name = resolved;
+                    self.schema_ref_to_resolved(s.name.clone())
+                }
+                _ => return ex_error::OnlyDropStatementsSnafu.fail(),
+            },
+            _ => return ex_error::OnlyDropStatementsSnafu.fail(),
+        };
+
+        let catalog_name = table_ref.catalog.as_ref();
+        let schema_name = table_ref.schema.to_string();
+
+        // Inject more information to the error
+        let catalog = self.get_catalog(catalog_name).map_err(|_| {
+            ex_error::CatalogNotFoundSnafu {
+                operation_on: match object_type {
+                    ObjectType::Table | ObjectType::View => OperationOn::Table(OperationType::Drop),
+                    ObjectType::Schema => OperationOn::Schema(OperationType::Drop),
+                    ObjectType::Database => OperationOn::Database(OperationType::Drop),
+                    _ => OperationOn::Unknown,
+                },
+                catalog: catalog_name,
+            }
+            .build()
+        })?;
+
+        // Check schema/table existence
+        match object_type {
+            ObjectType::Table | ObjectType::View => {
+                if let Some(schema) = catalog.schema(&schema_name) {
+                    if !if_exists
+                        && schema
+                            .table(&table_ref.table)
+                            .await
+                            .context(ex_error::DataFusionSnafu)?
+                            .is_none()
+                    {
+                        ex_error::TableNotFoundInSchemaInDatabaseSnafu {
+                            operation_on: OperationOn::Table(OperationType::Drop),
+                            table: table_ref.table.to_string(),
+                            schema: schema_name,
+                            db: catalog_name.to_string(),
+                        }
+                        .fail()?;
+                    }
+                } else {
+                    return ex_error::SchemaNotFoundInDatabaseSnafu {
+                        operation_on: OperationOn::Table(OperationType::Drop),
+                        schema: schema_name,
+                        db: catalog_name.to_string(),
+                    }
+                    .fail();
+                }
+            }
+            ObjectType::Schema if !if_exists && catalog.schema(&schema_name).is_none() => {
+                return ex_error::SchemaNotFoundInDatabaseSnafu {
+                    operation_on: OperationOn::Table(OperationType::Drop),
+                    schema: schema_name,
+                    db: catalog_name.to_string(),
+                }
+                .fail();
+            }
+            _ => {}
+        }
+        let grant_object = match object_type {
+            ObjectType::Schema => GrantObject::schema(catalog_name, &schema_name),
+            _ => table_grant_object(&table_ref),
+        };
+        self.authorize(Privilege::Ownership, &grant_object).await?;
+        self.execute_logical_plan(plan).await?;
+        self.revoke_grants_on(&grant_object).await?;
+        self.status_response()
+    }
+
+    #[allow(clippy::redundant_else, clippy::too_many_lines)]
+    #[instrument(
+        name = "UserQuery::create_table_query",
+        level = "trace",
+        skip(self),
+        err
+    )]
+    pub async fn create_table_query(&self, statement: Statement) -> Result<QueryResult> {
+        let Statement::CreateTable(mut create_table_statement) = statement.clone() else {
+            return ex_error::OnlyCreateTableStatementsSnafu.fail();
         };
         // Guard: if both column list and query are absent, treat as SQL parse error (syntax)
         // e.g., "create table foo" should map to Snowflake-like syntax error
@@ -873,11 +1589,25 @@ impl UserQuery {
         create_table_statement.catalog = None;
         create_table_statement.catalog_sync = None;
         create_table_statement.storage_serialization_policy = None;
-        create_table_statement.cluster_by = None;
+        // Clustering keys become the partition spec, unless there is a `PARTITION BY`
+        let cluster_by = match create_table_statement.cluster_by.take() {
+            Some(WrappedCollection::NoWrapping(keys) | WrappedCollection::Parentheses(keys)) => {
+                keys
+            }
+            None => Vec::new(),
+        };
 
         let if_not_exists = create_table_statement.if_not_exists;
         let or_replace = create_table_statement.or_replace;
 
+        let new_table_ref = self.resolve_table_ref(&new_table_ident);
+        self.authorize(
+            Privilege::CreateTable,
+            &GrantObject::schema(&new_table_ref.catalog, &new_table_ref.schema),
+        )
+        .await?;
+        let created = !self.table_exists(&new_table_ref);
+
         let df_stmt =
             DFStatement::Statement(Box::new(Statement::CreateTable(create_table_statement)));
         let mut plan = Box::pin(self.get_custom_logical_plan(df_stmt)).await?;
@@ -911,6 +1641,11 @@ impl UserQuery {
             .build()
         })?;
 
+        let partition_by = if self.partition_by.is_empty() {
+            clustering_transforms(&cluster_by)
+        } else {
+            partition_transforms(&table_name, &self.partition_by)?
+        };
         let table_provider: Option<Arc<dyn TableProvider>> = self.create_iceberg_table_provider(
             table_ref,
             schema_provider.clone(),
@@ -918,12 +1653,17 @@ impl UserQuery {
             if_not_exists,
             or_replace,
             plan.clone(),
+            &partition_by,
         )?;
         if let Some(provider) = table_provider {
             schema_provider
                 .register_table(table_name, provider)
                 .context(ex_error::DataFusionSnafu)?;
         }
+        if created {
+            self.grant_ownership(table_grant_object(&new_table_ref))
+                .await?;
+        }
 
         // Insert data to new table
         // Since we don't execute logical plan, and we don't transform it to physical plan and
@@ -989,6 +1729,7 @@ impl UserQuery {
         if_not_exists: bool,
         or_replace: bool,
         plan: LogicalPlan,
+        partition_by: &[PartitionTransform],
     ) -> Result<Option<Arc<dyn TableProvider>>> {
         // Check if table already exists, if exists and CREATE OR REPLACE - drop it
         if schema_provider.table_exist(&table_ref.table) {
@@ -1039,6 +1780,16 @@ impl UserQuery {
             .context(ex_error::DataFusionSnafu)?;
 
         let mut builder = CreateTableBuilder::default();
+        if !partition_by.is_empty() {
+            builder.with_partition_spec(partition_spec(
+                &table_ref.table,
+                &schema,
+                partition_by,
+                0,
+                &[],
+                0,
+            )?);
+        }
         builder
             .with_name(table_ref.table.to_string())
             .with_schema(schema)
@@ -1114,14 +1865,9 @@ impl UserQuery {
         self.created_entity_response()
     }
 
-    /// This is experimental CREATE STAGE support
-    /// Current limitations
-    /// TODO
-    /// - Prepare object storage depending on the URL. Currently we support only s3 public buckets    ///   with public access with default eu-central-1 region
-    /// - Parse credentials from specified config
-    /// - We don't need to create table in case we have common shared session context.
-    ///   CSV is registered as a table which can referenced from SQL statements executed against this context
-    /// - Revisit this with the new metastore approach
+    /// Creates a named stage: a location of data files on a metastore volume
+    /// (`STORAGE_INTEGRATION = <volume>`) or under an external `URL`, together with the
+    /// file format and copy options used when loading from it.
     #[instrument(
         name = "UserQuery::create_stage_query",
         level = "trace",
@@ -1130,108 +1876,1301 @@ impl UserQuery {
     )]
     pub async fn create_stage_query(&self, statement: Statement) -> Result<QueryResult> {
         let Statement::CreateStage {
+            or_replace,
+            if_not_exists,
             name,
             stage_params,
             file_format,
+            copy_options,
+            comment,
             ..
         } = statement
         else {
             return ex_error::OnlyCreateStageStatementsSnafu.fail();
         };
 
-        let table_name = match name.0.last() {
-            Some(ObjectNamePart::Identifier(ident)) => ident.value.clone(),
-            _ => {
-                return ex_error::InvalidTableIdentifierSnafu {
-                    ident: name.to_string(),
+        let ident = self.resolve_stage_ident(&name)?;
+        self.authorize_schema_usage(&ident.database, &ident.schema)
+            .await?;
+        self.authorize(
+            Privilege::CreateStage,
+            &GrantObject::schema(&ident.database, &ident.schema),
+        )
+        .await?;
+        let location = self.stage_location(&ident, stage_params).await?;
+        self.authorize_stage_location(&location).await?;
+        let stage = Stage {
+            file_format: key_value_options_map(&file_format),
+            copy_options: key_value_options_map(&copy_options),
+            comment,
+            ..Stage::new(ident.clone(), location)
+        };
+
+        let exists = self
+            .metastore
+            .get_stage(&ident)
+            .await
+            .context(ex_error::MetastoreSnafu)?
+            .is_some();
+        match (exists, or_replace, if_not_exists) {
+            (true, true, _) => {
+                self.authorize(Privilege::Ownership, &stage_grant_object(&ident))
+                    .await?;
+                self.metastore
+                    .update_stage(&ident, stage)
+                    .await
+                    .context(ex_error::MetastoreSnafu)?;
+            }
+            (true, false, true) => {}
+            (true, false, false) => {
+                return ex_error::ObjectAlreadyExistsSnafu {
+                    r#type: ExistingObjectType::Stage,
+                    name: ident.to_string(),
                 }
                 .fail();
             }
-        };
+            (false, ..) => {
+                self.metastore
+                    .create_stage(&ident, stage)
+                    .await
+                    .context(ex_error::MetastoreSnafu)?;
+                self.grant_ownership(stage_grant_object(&ident)).await?;
+            }
+        }
+        self.status_response()
+    }
 
-        let skip_header = file_format.options.iter().any(|option| {
-            option.option_name.eq_ignore_ascii_case("skip_header")
-                && option.value.eq_ignore_ascii_case("1")
-        });
+    /// Stages on volumes and on local files reach data that is not reachable through
+    /// grants, such as the files of other tables, so only `ACCOUNTADMIN`, which owns the
+    /// volumes, can create them. No stage may point into the data of a table.
+    async fn authorize_stage_location(&self, location: &StageLocation) -> Result<()> {
+        let url = match location {
+            StageLocation::Internal { .. } => return Ok(()),
+            StageLocation::Volume { volume, path } => {
+                self.authorize_account().await?;
+                let volume = self
+                    .metastore
+                    .get_volume(volume)
+                    .await
+                    .context(ex_error::MetastoreSnafu)?
+                    .context(ex_error::VolumeNotFoundSnafu {
+                        volume: volume.clone(),
+                    })?;
+                format!("{}/{path}", volume.prefix())
+            }
+            StageLocation::Url { url, .. } => {
+                if Url::parse(url).is_ok_and(|url| url.scheme() == "file") {
+                    self.authorize_account().await?;
+                }
+                url.clone()
+            }
+        };
+        // Locations of tables on volumes are relative to the volume
+        let volume_prefixes = self
+            .metastore
+            .list_volumes()
+            .await
+            .context(ex_error::MetastoreSnafu)?
+            .into_iter()
+            .map(|volume| (volume.ident.clone(), volume.prefix()))
+            .collect::<HashMap<_, _>>();
+        for database in self
+            .metastore
+            .list_databases()
+            .await
+            .context(ex_error::MetastoreSnafu)?
+        {
+            for schema in self
+                .metastore
+                .list_schemas(&database.ident)
+                .await
+                .context(ex_error::MetastoreSnafu)?
+            {
+                for table in self
+                    .metastore
+                    .list_tables(&schema.ident)
+                    .await
+                    .context(ex_error::MetastoreSnafu)?
+                {
+                    let location = &table.metadata.location;
+                    let table_url = if location.contains("://") {
+                        location.clone()
+                    } else if let Some(prefix) = table
+                        .volume_ident
+                        .as_ref()
+                        .and_then(|volume| volume_prefixes.get(volume))
+                    {
+                        format!("{prefix}/{location}")
+                    } else {
+                        continue;
+                    };
+                    if locations_overlap(&url, &table_url) {
+                        return ex_error::StageOverlapsTableSnafu {
+                            url,
+                            table: table.ident.to_string(),
+                        }
+                        .fail();
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Volume and path of the external location of `COPY INTO <location>`. As it
+    /// writes files, it unloads only under the location of a volume, the one named by
+    /// `STORAGE_INTEGRATION` or else the volume whose location contains the url, and
+    /// never to local files.
+    async fn unload_volume_location(
+        &self,
+        url: &str,
+        storage_integration: Option<String>,
+    ) -> Result<(VolumeIdent, String)> {
+        let not_allowed = || {
+            ex_error::UnloadLocationNotAllowedSnafu {
+                url: url.to_string(),
+            }
+            .build()
+        };
+        if Url::parse(url)
+            .context(ex_error::InvalidUrlSnafu { url })?
+            .scheme()
+            == "file"
+        {
+            return Err(not_allowed());
+        }
+        self.metastore
+            .list_volumes()
+            .await
+            .context(ex_error::MetastoreSnafu)?
+            .iter()
+            .filter(|volume| {
+                storage_integration
+                    .as_ref()
+                    .is_none_or(|name| *name == volume.ident)
+            })
+            .filter_map(|volume| {
+                let path = url.strip_prefix(&volume.prefix())?;
+                (path.is_empty() || path.starts_with('/')).then(|| {
+                    (
+                        volume.ident.clone(),
+                        path.trim_start_matches('/').to_string(),
+                    )
+                })
+            })
+            .min_by_key(|(_, path)| path.len())
+            .ok_or_else(not_allowed)
+    }
+
+    async fn stage_location(
+        &self,
+        ident: &StageIdent,
+        stage_params: StageParamsObject,
+    ) -> Result<StageLocation> {
+        if let Some(volume_ident) = stage_params.storage_integration {
+            let volume = self
+                .metastore
+                .get_volume(&volume_ident)
+                .await
+                .context(ex_error::MetastoreSnafu)?
+                .context(ex_error::VolumeNotFoundSnafu {
+                    volume: volume_ident.clone(),
+                })?;
+            // The url of a volume stage is only used to pick a directory of the volume
+            let path = stage_params.url.map_or_else(String::new, |url| {
+                url.strip_prefix(&volume.prefix()).map_or_else(
+                    || Url::parse(&url).map_or(url.clone(), |url| url.path().to_string()),
+                    ToString::to_string,
+                )
+            });
+            return Ok(StageLocation::Volume {
+                volume: volume_ident,
+                path: path.trim_matches('/').to_string(),
+            });
+        }
+
+        let Some(url) = stage_params.url else {
+            // Internal stages keep their files on the volume of their database
+            let database = self
+                .metastore
+                .get_database(&ident.database)
+                .await
+                .context(ex_error::MetastoreSnafu)?
+                .context(ex_error::DatabaseNotFoundSnafu {
+                    db: ident.database.clone(),
+                })?;
+            return Ok(StageLocation::Internal {
+                volume: database.volume.clone(),
+                path: format!(
+                    "{}/{}/.stages/{}",
+                    ident.database, ident.schema, ident.stage
+                ),
+            });
+        };
+        let parsed = Url::parse(&url).context(ex_error::InvalidUrlSnafu { url: &url })?;
+        if !matches!(parsed.scheme(), "s3" | "file") {
+            return ex_error::UnsupportedUrlSchemeSnafu {
+                scheme: parsed.scheme().to_string(),
+                url,
+            }
+            .fail();
+        }
+        Ok(StageLocation::Url {
+            url,
+            endpoint: stage_params.endpoint,
+            credentials: key_value_options_map(&stage_params.credentials),
+        })
+    }
+
+    fn resolve_stage_ident(&self, name: &ObjectName) -> Result<StageIdent> {
+        let ident = self.resolve_table_object_name(name.0.clone())?;
+        let resolved = self.resolve_table_ref(&ident);
+        Ok(StageIdent::new(
+            &resolved.catalog,
+            &resolved.schema,
+            &resolved.table,
+        ))
+    }
+
+    /// Checks that the schema of a stage or file format exists and can be used by the
+    /// current role
+    async fn authorize_schema_usage(&self, database: &str, schema: &str) -> Result<()> {
+        self.get_catalog(database)?.schema(schema).context(
+            ex_error::SchemaNotFoundInDatabaseSnafu {
+                operation_on: OperationOn::Unknown,
+                schema: schema.to_string(),
+                db: database.to_string(),
+            },
+        )?;
+        self.authorize(Privilege::Usage, &GrantObject::schema(database, schema))
+            .await
+    }
+
+    /// Loads a stage the current role has the privilege on
+    async fn load_stage(&self, ident: &StageIdent, privilege: Privilege) -> Result<Stage> {
+        self.authorize_schema_usage(&ident.database, &ident.schema)
+            .await?;
+        let stage = self.get_stage(ident).await?;
+        self.authorize(privilege, &stage_grant_object(ident))
+            .await?;
+        Ok(stage)
+    }
+
+    /// Loads a stage without checking privileges on it, for objects that were
+    /// authorized to use the stage when they were created
+    async fn get_stage(&self, ident: &StageIdent) -> Result<Stage> {
+        self.metastore
+            .get_stage(ident)
+            .await
+            .context(ex_error::MetastoreSnafu)?
+            .map(|stage| stage.data)
+            .context(ex_error::StageNotFoundSnafu {
+                stage: ident.to_string(),
+            })
+    }
+
+    async fn execute_file_format_statement(
+        &self,
+        statement: FileFormatStatement,
+    ) -> Result<QueryResult> {
+        let query_type = match &statement {
+            FileFormatStatement::Create { .. } => DdlStType::CreateFileFormat,
+            FileFormatStatement::Drop { .. } => DdlStType::DropFileFormat,
+        };
+        self.running_queries.update_stats(
+            self.query_context.query_id,
+            &QueryStats::default().with_query_type(QueryType::Ddl(query_type)),
+        );
+
+        match statement {
+            FileFormatStatement::Create {
+                name,
+                or_replace,
+                if_not_exists,
+                options,
+                comment,
+            } => {
+                let ident = self.resolve_file_format_ident(&name)?;
+                self.authorize_schema_usage(&ident.database, &ident.schema)
+                    .await?;
+                // Invalid options are rejected when the file format is created
+                FileFormatOptions::parse(&options)?;
+                let file_format = MetastoreFileFormat {
+                    comment,
+                    ..MetastoreFileFormat::new(ident.clone(), options)
+                };
+
+                let exists = self
+                    .metastore
+                    .get_file_format(&ident)
+                    .await
+                    .context(ex_error::MetastoreSnafu)?
+                    .is_some();
+                match (exists, or_replace, if_not_exists) {
+                    (true, true, _) => {
+                        self.metastore
+                            .update_file_format(&ident, file_format)
+                            .await
+                            .context(ex_error::MetastoreSnafu)?;
+                    }
+                    (true, false, true) => {}
+                    (true, false, false) => {
+                        return ex_error::ObjectAlreadyExistsSnafu {
+                            r#type: ExistingObjectType::FileFormat,
+                            name: ident.to_string(),
+                        }
+                        .fail();
+                    }
+                    (false, ..) => {
+                        self.metastore
+                            .create_file_format(&ident, file_format)
+                            .await
+                            .context(ex_error::MetastoreSnafu)?;
+                    }
+                }
+            }
+            FileFormatStatement::Drop { name, if_exists } => {
+                let ident = self.resolve_file_format_ident(&name)?;
+                self.authorize_schema_usage(&ident.database, &ident.schema)
+                    .await?;
+                let exists = self
+                    .metastore
+                    .get_file_format(&ident)
+                    .await
+                    .context(ex_error::MetastoreSnafu)?
+                    .is_some();
+                if exists {
+                    self.metastore
+                        .delete_file_format(&ident)
+                        .await
+                        .context(ex_error::MetastoreSnafu)?;
+                } else if !if_exists {
+                    return ex_error::FileFormatNotFoundSnafu {
+                        file_format: ident.to_string(),
+                    }
+                    .fail();
+                }
+            }
+        }
+        self.status_response()
+    }
+
+    fn resolve_file_format_ident(&self, name: &ObjectName) -> Result<FileFormatIdent> {
+        let ident = self.resolve_table_object_name(name.0.clone())?;
+        let resolved = self.resolve_table_ref(&ident);
+        Ok(FileFormatIdent::new(
+            &resolved.catalog,
+            &resolved.schema,
+            &resolved.table,
+        ))
+    }
+
+    async fn load_file_format(&self, ident: &FileFormatIdent) -> Result<MetastoreFileFormat> {
+        self.authorize_schema_usage(&ident.database, &ident.schema)
+            .await?;
+        self.metastore
+            .get_file_format(ident)
+            .await
+            .context(ex_error::MetastoreSnafu)?
+            .map(|file_format| file_format.data)
+            .context(ex_error::FileFormatNotFoundSnafu {
+                file_format: ident.to_string(),
+            })
+    }
+
+    /// Replaces `FORMAT_NAME` with the options of the named file format, other options
+    /// given along with it take precedence
+    async fn resolve_file_format(
+        &self,
+        mut options: BTreeMap<String, String>,
+    ) -> Result<BTreeMap<String, String>> {
+        let Some(format_name) = options.remove("FORMAT_NAME") else {
+            return Ok(options);
+        };
+        let name = parse_format_name(&format_name).context(ex_error::SqlParserSnafu)?;
+        let mut file_format = self
+            .load_file_format(&self.resolve_file_format_ident(&name)?)
+            .await?
+            .options;
+        file_format.extend(options);
+        Ok(file_format)
+    }
+
+    #[allow(clippy::too_many_lines)]
+    async fn execute_external_table_statement(
+        &self,
+        statement: ExternalTableStatement,
+    ) -> Result<QueryResult> {
+        let query_type = match &statement {
+            ExternalTableStatement::Create { .. } => DdlStType::CreateExternalTable,
+            ExternalTableStatement::Refresh { .. }
+            | ExternalTableStatement::SetAutoRefresh { .. } => DdlStType::AlterExternalTable,
+            ExternalTableStatement::Drop { .. } => DdlStType::DropExternalTable,
+        };
+        self.running_queries.update_stats(
+            self.query_context.query_id,
+            &QueryStats::default().with_query_type(QueryType::Ddl(query_type)),
+        );
+        // DDL statements implicitly commit the open transaction, as in Snowflake
+        self.commit_transaction().await?;
+
+        match statement {
+            ExternalTableStatement::Create {
+                name,
+                or_replace,
+                if_not_exists,
+                columns,
+                partition_by,
+                location,
+                pattern,
+                file_format,
+                auto_refresh,
+                refresh_on_create,
+                comment,
+            } => {
+                let table_ref = self.resolve_table_ref(&self.resolve_table_object_name(name.0)?);
+                let ident = MetastoreTableIdent::new(
+                    &table_ref.catalog,
+                    &table_ref.schema,
+                    &table_ref.table,
+                );
+                self.authorize(
+                    Privilege::CreateTable,
+                    &GrantObject::schema(&table_ref.catalog, &table_ref.schema),
+                )
+                .await?;
+                let stage = self
+                    .load_stage(&self.resolve_stage_ident(&location.name)?, Privilege::Read)
+                    .await?;
+                // The file format of the stage is used unless the table has its own
+                let file_format = if file_format.is_empty() {
+                    stage.file_format.clone()
+                } else {
+                    file_format
+                };
+                FileFormatOptions::parse(&self.resolve_file_format(file_format.clone()).await?)?;
+                file_pattern(pattern.as_deref())?;
+
+                let columns = columns
+                    .into_iter()
+                    .map(|column| ExternalColumnDef {
+                        name: self.normalize_ident(column.name.clone()),
+                        ..column
+                    })
+                    .collect::<Vec<_>>();
+                let partition_by = partition_by
+                    .into_iter()
+                    .map(|column| self.normalize_ident(column).value)
+                    .collect::<Vec<_>>();
+                for partition_column in &partition_by {
+                    let column = columns
+                        .iter()
+                        .find(|column| column.name.value == *partition_column)
+                        .context(ex_error::InvalidExternalTableSnafu {
+                            table: ident.to_string(),
+                            reason: format!("partition column {partition_column} is not defined"),
+                        })?;
+                    check_partition_expression(&ident.to_string(), column)?;
+                }
+                let table = ExternalTable {
+                    pattern,
+                    file_format,
+                    columns: columns
+                        .into_iter()
+                        .map(|column| ExternalTableColumn {
+                            name: column.name.value,
+                            data_type: column.data_type,
+                            expression: column.expression.to_string(),
+                        })
+                        .collect(),
+                    partition_by,
+                    auto_refresh,
+                    comment,
+                    ..ExternalTable::new(ident.clone(), stage.ident.clone(), location.path)
+                };
+                // Invalid column expressions are rejected when the table is created
+                self.external_table_schema(&table)?;
+
+                let exists = self
+                    .metastore
+                    .get_external_table(&ident)
+                    .await
+                    .context(ex_error::MetastoreSnafu)?
+                    .is_some();
+                if !exists
+                    && self
+                        .metastore
+                        .table_exists(&ident)
+                        .await
+                        .context(ex_error::MetastoreSnafu)?
+                {
+                    return ex_error::ObjectAlreadyExistsSnafu {
+                        r#type: ExistingObjectType::Table,
+                        name: ident.to_string(),
+                    }
+                    .fail();
+                }
+                let mut table = match (exists, or_replace, if_not_exists) {
+                    (true, true, _) => {
+                        self.metastore
+                            .update_external_table(&ident, table)
+                            .await
+                            .context(ex_error::MetastoreSnafu)?
+                            .data
+                    }
+                    (true, false, true) => return self.status_response(),
+                    (true, false, false) => {
+                        return ex_error::ObjectAlreadyExistsSnafu {
+                            r#type: ExistingObjectType::ExternalTable,
+                            name: ident.to_string(),
+                        }
+                        .fail();
+                    }
+                    (false, ..) => {
+                        let table = self
+                            .metastore
+                            .create_external_table(&ident, table)
+                            .await
+                            .context(ex_error::MetastoreSnafu)?
+                            .data;
+                        self.grant_ownership(table_grant_object(&table_ref)).await?;
+                        table
+                    }
+                };
+                if refresh_on_create {
+                    self.refresh_external_table(&mut table, None).await?;
+                }
+                self.status_response()
+            }
+            ExternalTableStatement::Refresh {
+                name,
+                if_exists,
+                path,
+            } => {
+                let Some(mut table) = self.load_external_table(&name, if_exists).await? else {
+                    return self.status_response();
+                };
+                let changes = self
+                    .refresh_external_table(&mut table, path.as_deref())
+                    .await?;
+                let (batch, schema) = refresh_results(&changes)?;
+                Ok(QueryResult::new(vec![batch], schema))
+            }
+            ExternalTableStatement::SetAutoRefresh {
+                name,
+                if_exists,
+                auto_refresh,
+            } => {
+                if let Some(table) = self.load_external_table(&name, if_exists).await? {
+                    self.metastore
+                        .update_external_table(
+                            &table.ident.clone(),
+                            ExternalTable {
+                                auto_refresh,
+                                ..table
+                            },
+                        )
+                        .await
+                        .context(ex_error::MetastoreSnafu)?;
+                }
+                self.status_response()
+            }
+            ExternalTableStatement::Drop { name, if_exists } => {
+                if let Some(table) = self.load_external_table(&name, if_exists).await? {
+                    self.metastore
+                        .delete_external_table(&table.ident)
+                        .await
+                        .context(ex_error::MetastoreSnafu)?;
+                    self.revoke_grants_on(&GrantObject::table(
+                        &table.ident.database,
+                        &table.ident.schema,
+                        &table.ident.table,
+                    ))
+                    .await?;
+                }
+                self.status_response()
+            }
+        }
+    }
+
+    /// External table altered or dropped by the current role, `None` if it does not
+    /// exist and `if_exists` is set
+    async fn load_external_table(
+        &self,
+        name: &ObjectName,
+        if_exists: bool,
+    ) -> Result<Option<ExternalTable>> {
+        let table_ref = self.resolve_table_ref(&self.resolve_table_object_name(name.0.clone())?);
+        let ident =
+            MetastoreTableIdent::new(&table_ref.catalog, &table_ref.schema, &table_ref.table);
+        let Some(table) = self
+            .metastore
+            .get_external_table(&ident)
+            .await
+            .context(ex_error::MetastoreSnafu)?
+        else {
+            if if_exists {
+                return Ok(None);
+            }
+            return ex_error::TableNotFoundInSchemaInDatabaseSnafu {
+                operation_on: OperationOn::Table(OperationType::Alter),
+                table: table_ref.table.to_string(),
+                schema: table_ref.schema.to_string(),
+                db: table_ref.catalog.to_string(),
+            }
+            .fail();
+        };
+        self.authorize(Privilege::Ownership, &table_grant_object(&table_ref))
+            .await?;
+        Ok(Some(table.data))
+    }
+
+    /// Registers the files at the location of an external table in its metadata, `path`
+    /// limits the refresh to the files under it
+    async fn refresh_external_table(
+        &self,
+        table: &mut ExternalTable,
+        path: Option<&str>,
+    ) -> Result<Vec<FileRefresh>> {
+        let stage = self.get_stage(&table.stage).await?;
+        let store = self.stage_object_store(&stage).await?;
+        let prefix = join_stage_path(&table.path, path.unwrap_or_default());
+        let listed = list_stage_files(&store, &prefix, table.pattern.as_deref()).await?;
+        let (mut files, changes) =
+            refresh_files(&table.files, &listed, &prefix, Utc::now().naive_utc());
+        // Schemas are inferred once, when the files are registered, not on every read
+        let infer_schemas = files.iter().any(|file| file.schema.is_none());
+        if infer_schemas {
+            let base_url = stage_object_store_url(&stage.ident)?;
+            self.session.ctx.register_object_store(&base_url, store);
+            let file_format = self.resolve_file_format(table.file_format.clone()).await?;
+            let format = FileFormatOptions::parse(&file_format)?.unwrap_or_default();
+            for file in files.iter_mut().filter(|file| file.schema.is_none()) {
+                file.schema = Some(
+                    self.external_file_schema(&format, &base_url, &file.file)
+                        .await?,
+                );
+            }
+        }
+        if !changes.is_empty() || infer_schemas {
+            table.files = files;
+            self.metastore
+                .update_external_table(&table.ident.clone(), table.clone())
+                .await
+                .context(ex_error::MetastoreSnafu)?;
+        }
+        Ok(changes)
+    }
+
+    /// Table provider of an external table, `None` if there is no external table with
+    /// the name. Only the registered files are read, reads never refresh the table.
+    async fn external_table_provider(
+        &self,
+        table_ref: &ResolvedTableReference,
+    ) -> Result<Option<Arc<dyn TableProvider>>> {
+        let ident =
+            MetastoreTableIdent::new(&table_ref.catalog, &table_ref.schema, &table_ref.table);
+        let Some(table) = self
+            .metastore
+            .get_external_table(&ident)
+            .await
+            .context(ex_error::MetastoreSnafu)?
+        else {
+            return Ok(None);
+        };
+        let table = table.data;
+        let stage = self.get_stage(&table.stage).await?;
+        let base_url = stage_object_store_url(&stage.ident)?;
+        self.session
+            .ctx
+            .register_object_store(&base_url, self.stage_object_store(&stage).await?);
+        let file_format = self.resolve_file_format(table.file_format.clone()).await?;
+        let format = FileFormatOptions::parse(&file_format)?.unwrap_or_default();
+        let files = table
+            .files
+            .iter()
+            .map(|file| {
+                self.external_file_plan(
+                    &table,
+                    Self::external_file_source(&format, &base_url, file)?,
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Some(Arc::new(ExternalTableProvider::new(
+            self.external_table_schema(&table)?,
+            files,
+        ))))
+    }
+
+    /// Schema of an external table: its columns followed by `VALUE` and the `METADATA$`
+    /// pseudo-columns. Columns are nullable, whichever file they are read from.
+    fn external_table_schema(&self, table: &ExternalTable) -> Result<SchemaRef> {
+        let source_schema = Arc::new(ArrowSchema::new(vec![
+            Field::new(VALUE_COLUMN, DataType::Utf8, true),
+            Field::new(FILENAME_COLUMN, DataType::Utf8, true),
+            Field::new(FILE_ROW_NUMBER_COLUMN, DataType::UInt64, true),
+        ]));
+        let source = MemTable::try_new(source_schema, vec![]).context(ex_error::DataFusionSnafu)?;
+        let source =
+            LogicalPlanBuilder::scan("external_file", provider_as_source(Arc::new(source)), None)
+                .context(ex_error::DataFusionSnafu)?
+                .build()
+                .context(ex_error::DataFusionSnafu)?;
+        let plan = self.external_file_plan(table, source)?;
+        Ok(Arc::new(ArrowSchema::new(
+            plan.schema()
+                .fields()
+                .iter()
+                .map(|field| field.as_ref().clone().with_nullable(true))
+                .collect::<Vec<_>>(),
+        )))
+    }
+
+    /// Rows of a file of an external table, next to the `METADATA$` pseudo-columns of the
+    /// file. The file is only read if the plan still scans it once optimized.
+    fn external_file_source(
+        format: &FileFormatOptions,
+        base_url: &Url,
+        file: &ExternalTableFile,
+    ) -> Result<LogicalPlan> {
+        let url = ListingTableUrl::parse(format!("{base_url}{}", file.file))
+            .context(ex_error::DataFusionSnafu)?;
+        // A schema that can't be read back is inferred again when the file is read
+        let schema = file
+            .schema
+            .as_deref()
+            .and_then(|schema| serde_json::from_str::<ArrowSchema>(schema).ok())
+            .map(Arc::new);
+        let source = ExternalFileTable::new(url, format.clone(), schema);
+        LogicalPlanBuilder::scan("external_file", provider_as_source(Arc::new(source)), None)
+            .context(ex_error::DataFusionSnafu)?
+            .project(vec![
+                DFExpr::Column(Column::from_name(VALUE_COLUMN)),
+                lit(file.file.clone()).alias(FILENAME_COLUMN),
+                DFExpr::Column(Column::from_name(FILE_ROW_NUMBER_COLUMN)),
+            ])
+            .context(ex_error::DataFusionSnafu)?
+            .build()
+            .context(ex_error::DataFusionSnafu)
+    }
+
+    /// Infers the schema of the rows of a file of an external table, serialized for the
+    /// metadata of the table
+    async fn external_file_schema(
+        &self,
+        format: &FileFormatOptions,
+        base_url: &Url,
+        file: &str,
+    ) -> Result<String> {
+        let mut url = ListingTableUrl::parse(format!("{base_url}{file}"))
+            .context(ex_error::DataFusionSnafu)?;
+        let mut format = format.clone();
+        if let Some(rewrite) = format.rewrite() {
+            url = self
+                .rewrite_file(&url, format.compression(), rewrite)
+                .await?;
+            format.rewritten(rewrite);
+        }
+        let schema = infer_file_schema(&self.session.ctx.state(), &format, &url).await?;
+        serde_json::to_string(schema.as_ref()).context(ex_error::SerdeParseSnafu)
+    }
+
+    /// Plans the columns of an external table over the rows of `source`. The column
+    /// expressions are SQL, planned as a query over the table itself.
+    fn external_file_plan(
+        &self,
+        table: &ExternalTable,
+        source: LogicalPlan,
+    ) -> Result<LogicalPlan> {
+        let mut projection = table
+            .columns
+            .iter()
+            .map(|column| {
+                format!(
+                    "CAST(({}) AS {}) AS {}",
+                    column.expression,
+                    column.data_type,
+                    Ident::with_quote('"', &column.name)
+                )
+            })
+            .collect::<Vec<_>>();
+        projection.extend(
+            [VALUE_COLUMN, FILENAME_COLUMN, FILE_ROW_NUMBER_COLUMN]
+                .map(|column| Ident::with_quote('"', column).to_string()),
+        );
+        let source_name = ObjectName::from(vec![
+            Ident::with_quote('"', &table.ident.database),
+            Ident::with_quote('"', &table.ident.schema),
+            Ident::with_quote('"', &table.ident.table),
+        ]);
+        let mut statement = self
+            .sql_to_df_statement(&format!(
+                "SELECT {} FROM {source_name}",
+                projection.join(", ")
+            ))
+            .context(ex_error::DataFusionSnafu)?;
+        Self::postprocess_query_statement_with_validation(&mut statement)?;
+        let DFStatement::Statement(statement) = statement else {
+            return ex_error::OnlySQLStatementsSnafu.fail();
+        };
+
+        let source_ref = ResolvedTableReference {
+            catalog: Arc::from(table.ident.database.as_str()),
+            schema: Arc::from(table.ident.schema.as_str()),
+            table: Arc::from(table.ident.table.as_str()),
+        };
+        let source = provider_as_source(Arc::new(ViewTable::new(source, None)));
+        let ctx_provider = SessionContextProvider {
+            state: &self.session.ctx.state(),
+            tables: HashMap::from([(source_ref, source)]),
+        };
+        SqlToRel::new(&ctx_provider)
+            .sql_statement_to_plan(*statement)
+            .context(ex_error::DataFusionSnafu)
+    }
+
+    /// Files at an external location, which is either a file or a directory. Like for
+    /// stages, `FILES` are relative to the location and `PATTERN` matches the whole
+    /// path of the file.
+    async fn list_location_files(
+        &self,
+        url: &ListingTableUrl,
+        names: Option<&[String]>,
+        pattern: Option<&str>,
+    ) -> Result<Vec<CopyFile>> {
+        let pattern = file_pattern(pattern)?;
+        let state = self.session.ctx.state();
+        let store = state
+            .runtime_env()
+            .object_store(url.object_store())
+            .context(ex_error::DataFusionSnafu)?;
+        let mut files: Vec<ObjectMeta> = url
+            .list_all_files(&state, store.as_ref(), "")
+            .await
+            .context(ex_error::DataFusionSnafu)?
+            .try_collect()
+            .await
+            .context(ex_error::DataFusionSnafu)?;
+        if let Some(names) = names {
+            let names = names
+                .iter()
+                .map(|name| join_stage_path(url.prefix().as_ref(), name))
+                .collect::<HashSet<_>>();
+            files.retain(|file| names.contains(file.location.as_ref()));
+        }
+        if let Some(pattern) = pattern {
+            files.retain(|file| pattern.is_match(file.location.as_ref()));
+        }
+        files.sort_by(|a, b| a.location.cmp(&b.location));
+        files
+            .iter()
+            .map(|file| {
+                let name = format!("{}{}", url.object_store().as_str(), file.location);
+                Ok(CopyFile::new(
+                    name.clone(),
+                    ListingTableUrl::parse(&name).context(ex_error::DataFusionSnafu)?,
+                    file,
+                ))
+            })
+            .collect()
+    }
+
+    /// Copies a file to memory with the rewrite applied, see [`rewrite_file`]
+    async fn rewrite_file(
+        &self,
+        url: &ListingTableUrl,
+        compression: &str,
+        rewrite: FileRewrite,
+    ) -> Result<ListingTableUrl> {
+        rewrite_file(&self.session.ctx.runtime_env(), url, compression, rewrite).await
+    }
+
+    /// Applies `TRIM_SPACE`, `NULL_IF` and `EMPTY_FIELD_AS_NULL` to the values of CSV
+    /// files read as text
+    pub(crate) fn convert_text_values(
+        input: LogicalPlan,
+        target_schema: &SchemaRef,
+        csv: &CsvFileFormat,
+    ) -> Result<LogicalPlan> {
+        let input_schema = input.schema().clone();
+        let null_values = csv.null_values().into_iter().map(lit).collect::<Vec<_>>();
+        let mut projections = Vec::with_capacity(target_schema.fields().len());
+        for field in target_schema.fields() {
+            let (reference, input_field) = get_field(&input_schema, field.name())?;
+            let mut value = DFExpr::Column(Column::new(reference.cloned(), input_field.name()));
+            if csv.trim_space {
+                value = btrim(vec![value]);
+            }
+            if !null_values.is_empty() {
+                value = when(
+                    value.clone().in_list(null_values.clone(), false),
+                    lit(ScalarValue::Utf8(None)),
+                )
+                .otherwise(value)
+                .context(ex_error::DataFusionSnafu)?;
+            }
+            projections.push(value.alias(field.name()));
+        }
+        let projection =
+            Projection::try_new(projections, Arc::new(input)).context(ex_error::DataFusionSnafu)?;
+        Ok(LogicalPlan::Projection(projection))
+    }
 
-        let field_optionally_enclosed_by = file_format
-            .options
-            .iter()
-            .find_map(|option| {
-                if option
-                    .option_name
-                    .eq_ignore_ascii_case("field_optionally_enclosed_by")
-                {
-                    Some(option.value.as_bytes()[0])
+    /// Converts the rows read by `COPY INTO` to the columns of the table. Two more
+    /// columns hold the error of each row that cannot be loaded and the column it
+    /// was found in.
+    fn load_projection(
+        &self,
+        input: LogicalPlan,
+        target_schema: &SchemaRef,
+        csv: Option<&CsvFileFormat>,
+    ) -> Result<LogicalPlan> {
+        let input_schema = input.schema().clone();
+        let mut projections = Vec::with_capacity(target_schema.fields().len() + 2);
+        let mut conditions = Vec::new();
+        let mut errors = Vec::new();
+        let mut error_columns = Vec::new();
+        for field in target_schema.fields() {
+            let (reference, input_field) = get_field(&input_schema, field.name())?;
+            let value = DFExpr::Column(Column::new(reference.cloned(), input_field.name()));
+            let conversion = csv
+                .filter(|_| input_field.data_type() == &DataType::Utf8)
+                .and_then(|csv| match field.data_type() {
+                    DataType::Date32 | DataType::Date64 => csv
+                        .date_format
+                        .as_ref()
+                        .map(|format| ("try_to_date", format)),
+                    DataType::Timestamp(_, None) => csv
+                        .timestamp_format
+                        .as_ref()
+                        .map(|format| ("try_to_timestamp_ntz", format)),
+                    DataType::Timestamp(_, Some(_)) => csv
+                        .timestamp_format
+                        .as_ref()
+                        .map(|format| ("try_to_timestamp_tz", format)),
+                    _ => None,
+                });
+            let converted = if let Some((function, format)) = conversion {
+                self.session
+                    .ctx
+                    .udf(function)
+                    .context(ex_error::DataFusionSnafu)?
+                    .call(vec![value.clone(), lit(format.clone())])
+            } else {
+                value.clone()
+            };
+            let converted =
+                DFExpr::TryCast(TryCast::new(Box::new(converted), field.data_type().clone()));
+
+            let error = if can_cast_types(input_field.data_type(), &DataType::Utf8) {
+                concat(vec![
+                    lit("Value '"),
+                    DFExpr::TryCast(TryCast::new(Box::new(value.clone()), DataType::Utf8)),
+                    lit(format!("' cannot be converted to {}", field.data_type())),
+                ])
+            } else {
+                lit(format!(
+                    "Value cannot be converted to {}",
+                    field.data_type()
+                ))
+            };
+            conditions.push(and(value.is_not_null(), converted.clone().is_null()));
+            errors.push(error);
+            error_columns.push(lit(field.name().clone()));
+            if !field.is_nullable() {
+                conditions.push(converted.clone().is_null());
+                errors.push(lit("NULL result in a non-nullable column"));
+                error_columns.push(lit(field.name().clone()));
+            }
+            projections.push(converted.alias(field.name()));
+        }
+
+        for (name, values) in [
+            (COPY_ERROR_COLUMN, errors),
+            (COPY_ERROR_COLUMN_NAME_COLUMN, error_columns),
+        ] {
+            let value = if conditions.is_empty() {
+                lit(ScalarValue::Utf8(None))
+            } else {
+                CaseBuilder::new(
+                    None,
+                    conditions.clone(),
+                    values,
+                    Some(Box::new(lit(ScalarValue::Utf8(None)))),
+                )
+                .end()
+                .context(ex_error::DataFusionSnafu)?
+            };
+            projections.push(cast(value, DataType::Utf8).alias(name));
+        }
+        let projection =
+            Projection::try_new(projections, Arc::new(input)).context(ex_error::DataFusionSnafu)?;
+        Ok(LogicalPlan::Projection(projection))
+    }
+
+    /// Reads a file loaded by `COPY INTO` with the columns of `load_projection`
+    async fn read_copy_file(
+        &self,
+        url: &ListingTableUrl,
+        format: &FileFormatOptions,
+        into_provider: &Arc<dyn TableProvider>,
+        alias: Option<&str>,
+    ) -> Result<SendableRecordBatchStream> {
+        let mut url = url.clone();
+        let mut format = format.clone();
+        if let Some(rewrite) = format.rewrite() {
+            url = self
+                .rewrite_file(&url, format.compression(), rewrite)
+                .await?;
+            format.rewritten(rewrite);
+        }
+
+        let config = self
+            .build_listing_table_config(&format, into_provider, vec![url])
+            .await?;
+        let table_provider = ListingTable::try_new(config).context(ex_error::DataFusionSnafu)?;
+        let builder = LogicalPlanBuilder::scan(
+            "external_location",
+            Arc::new(DefaultTableSource::new(Arc::new(table_provider))),
+            None,
+        )
+        .context(ex_error::DataFusionSnafu)?;
+        let builder = if let Some(alias) = alias {
+            builder.alias(alias).context(ex_error::DataFusionSnafu)?
+        } else {
+            builder
+        };
+        let input = builder.build().context(ex_error::DataFusionSnafu)?;
+
+        let target_schema = into_provider.schema();
+        let csv = match &format {
+            FileFormatOptions::Csv(csv) => Some(csv),
+            _ => None,
+        };
+        let input = match csv {
+            Some(csv) if csv.converts_values() => {
+                Self::convert_text_values(input, &target_schema, csv)?
+            }
+            _ => input,
+        };
+        let plan = self.load_projection(input, &target_schema, csv)?;
+        self.session
+            .ctx
+            .execute_logical_plan(plan)
+            .await
+            .context(ex_error::DataFusionSnafu)?
+            .execute_stream()
+            .await
+            .context(ex_error::DataFusionSnafu)
+    }
+
+    /// Object store rooted at the location of the stage
+    async fn stage_object_store(&self, stage: &Stage) -> Result<Arc<dyn ObjectStore>> {
+        match &stage.location {
+            StageLocation::Volume { volume, path } | StageLocation::Internal { volume, path } => {
+                volume_stage_object_store(self.metastore.as_ref(), volume, path).await
+            }
+            StageLocation::Url {
+                url,
+                endpoint,
+                credentials,
+            } => {
+                let parsed = Url::parse(url).context(ex_error::InvalidUrlSnafu { url })?;
+                let store = self
+                    .object_store_for_url(&parsed, endpoint.clone(), credentials)
+                    .await?;
+                let prefix = parsed.path().trim_matches('/');
+                if prefix.is_empty() {
+                    Ok(store)
                 } else {
-                    None
+                    Ok(Arc::new(PrefixStore::new(store, prefix)))
                 }
-            })
-            .unwrap_or(b'"');
+            }
+        }
+    }
 
-        let file_path = stage_params.url.unwrap_or_default();
-        let url = Url::parse(file_path.as_str()).map_err(|_| {
-            ex_error::InvalidFilePathSnafu {
-                path: file_path.clone(),
+    async fn load_internal_stage(
+        &self,
+        reference: &StageReference,
+        privilege: Privilege,
+    ) -> Result<Stage> {
+        let stage = self
+            .load_stage(&self.resolve_stage_ident(&reference.name)?, privilege)
+            .await?;
+        if !matches!(stage.location, StageLocation::Internal { .. }) {
+            return ex_error::ExternalStageFileTransferSnafu {
+                stage: stage.ident.to_string(),
             }
-            .build()
-        })?;
-        let bucket = url.host_str().unwrap_or_default();
-        // TODO Replace this with the new metastore volume approach
-        let s3 = AmazonS3Builder::from_env()
-            // TODO Get region automatically from the Volume
-            .with_region("eu-central-1")
-            .with_bucket_name(bucket)
-            .build()
-            .map_err(|_| {
-                ex_error::InvalidBucketIdentifierSnafu {
-                    ident: bucket.to_string(),
-                }
-                .build()
-            })?;
+            .fail();
+        }
+        Ok(stage)
+    }
+
+    /// `PUT` only checks the target stage, the client uploads the files itself
+    async fn put_files_query(
+        &self,
+        source: String,
+        reference: StageReference,
+        options: FileTransferOptions,
+    ) -> Result<QueryResult> {
+        self.running_queries.update_stats(
+            self.query_context.query_id,
+            &QueryStats::default().with_query_type(QueryType::Misc(MiscStType::Put)),
+        );
+        let stage = self
+            .load_internal_stage(&reference, Privilege::Write)
+            .await?;
+        let schema = Arc::new(ArrowSchema::new(vec![
+            Field::new("source", DataType::Utf8, false),
+            Field::new("target", DataType::Utf8, false),
+            Field::new("source_size", DataType::Int64, false),
+            Field::new("target_size", DataType::Int64, false),
+            Field::new("source_compression", DataType::Utf8, false),
+            Field::new("target_compression", DataType::Utf8, false),
+            Field::new("status", DataType::Utf8, false),
+            Field::new("message", DataType::Utf8, false),
+        ]));
+        Ok(
+            QueryResult::new(Vec::new(), schema).with_file_transfer(FileTransfer {
+                command: FileTransferCommand::Upload,
+                stage: stage.ident,
+                path: reference.path,
+                src_locations: vec![source],
+                local_location: None,
+                options,
+            }),
+        )
+    }
 
-        self.session.ctx.register_object_store(&url, Arc::new(s3));
+    /// `GET` lists the stage files to download, the client downloads them itself
+    async fn get_files_query(
+        &self,
+        reference: StageReference,
+        target: String,
+        pattern: Option<String>,
+        options: FileTransferOptions,
+    ) -> Result<QueryResult> {
+        self.running_queries.update_stats(
+            self.query_context.query_id,
+            &QueryStats::default().with_query_type(QueryType::Misc(MiscStType::Get)),
+        );
+        let stage = self
+            .load_internal_stage(&reference, Privilege::Read)
+            .await?;
+        let store = self.stage_object_store(&stage).await?;
+        let files = list_stage_files(&store, &reference.path, pattern.as_deref()).await?;
+        let schema = Arc::new(ArrowSchema::new(vec![
+            Field::new("file", DataType::Utf8, false),
+            Field::new("size", DataType::Int64, false),
+            Field::new("status", DataType::Utf8, false),
+            Field::new("message", DataType::Utf8, false),
+        ]));
+        Ok(
+            QueryResult::new(Vec::new(), schema).with_file_transfer(FileTransfer {
+                command: FileTransferCommand::Download,
+                stage: stage.ident,
+                path: reference.path,
+                src_locations: files.iter().map(|file| file.location.to_string()).collect(),
+                local_location: Some(target),
+                options,
+            }),
+        )
+    }
 
-        // Read CSV file to get default schema
-        let csv_data = self
-            .session
-            .ctx
-            .read_csv(
-                file_path.clone(),
-                CsvReadOptions::new()
-                    .has_header(skip_header)
-                    .quote(field_optionally_enclosed_by),
-            )
-            .await
-            .context(ex_error::DataFusionSnafu)?;
+    #[instrument(
+        name = "UserQuery::execute_stage_statement",
+        level = "trace",
+        skip(self),
+        err
+    )]
+    async fn execute_stage_statement(&self, statement: StageStatement) -> Result<QueryResult> {
+        let (reference, pattern, query_type) = match statement {
+            StageStatement::List { stage, pattern } => (stage, pattern, MiscStType::List),
+            StageStatement::Remove { stage, pattern } => (stage, pattern, MiscStType::Remove),
+            StageStatement::Put {
+                source,
+                stage,
+                options,
+            } => return self.put_files_query(source, stage, options).await,
+            StageStatement::Get {
+                stage,
+                target,
+                pattern,
+                options,
+            } => return self.get_files_query(stage, target, pattern, options).await,
+        };
+        self.running_queries.update_stats(
+            self.query_context.query_id,
+            &QueryStats::default().with_query_type(QueryType::Misc(query_type.clone())),
+        );
 
-        let fields = csv_data
-            .schema()
+        // Removing files writes to the stage, listing them only reads it
+        let privilege = if matches!(query_type, MiscStType::Remove) {
+            Privilege::Write
+        } else {
+            Privilege::Read
+        };
+        let stage = self
+            .load_stage(&self.resolve_stage_ident(&reference.name)?, privilege)
+            .await?;
+        let store = self.stage_object_store(&stage).await?;
+        let files = list_stage_files(&store, &reference.path, pattern.as_deref()).await?;
+        let names = files
             .iter()
-            .map(|(_, field)| {
-                let data_type = if matches!(field.data_type(), DataType::Null) {
-                    DataType::Utf8
-                } else {
-                    field.data_type().clone()
-                };
-                Field::new(field.name(), data_type, field.is_nullable())
-            })
+            .map(|file| stage_file_name(&stage, &file.location))
             .collect::<Vec<_>>();
 
-        // Register CSV file with filled missing datatype with default Utf8
-        self.session
-            .ctx
-            .register_csv(
-                table_name,
-                file_path,
-                CsvReadOptions::new()
-                    .has_header(skip_header)
-                    .quote(field_optionally_enclosed_by)
-                    .schema(&ArrowSchema::new(fields)),
+        if matches!(query_type, MiscStType::Remove) {
+            for file in &files {
+                store
+                    .delete(&file.location)
+                    .await
+                    .context(ex_error::ObjectStoreSnafu)?;
+            }
+            let schema = Arc::new(ArrowSchema::new(vec![
+                Field::new("name", DataType::Utf8, false),
+                Field::new("result", DataType::Utf8, false),
+            ]));
+            let batch = RecordBatch::try_new(
+                schema.clone(),
+                vec![
+                    Arc::new(StringArray::from(names)),
+                    Arc::new(StringArray::from(vec!["removed"; files.len()])),
+                ],
             )
-            .await
-            .context(ex_error::DataFusionSnafu)?;
-        self.status_response()
+            .context(ex_error::ArrowSnafu)?;
+            return Ok(QueryResult::new(vec![batch], schema));
+        }
+
+        let schema = Arc::new(ArrowSchema::new(vec![
+            Field::new("name", DataType::Utf8, false),
+            Field::new("size", DataType::Int64, false),
+            Field::new("md5", DataType::Utf8, true),
+            Field::new("last_modified", DataType::Utf8, false),
+        ]));
+        let batch = RecordBatch::try_new(
+            schema.clone(),
+            vec![
+                Arc::new(StringArray::from(names)),
+                Arc::new(Int64Array::from_iter_values(
+                    files
+                        .iter()
+                        .map(|file| i64::try_from(file.size).unwrap_or(i64::MAX)),
+                )),
+                Arc::new(StringArray::from_iter(files.iter().map(|file| {
+                    file.e_tag
+                        .as_ref()
+                        .map(|e_tag| e_tag.trim_matches('"').to_string())
+                }))),
+                Arc::new(StringArray::from_iter_values(files.iter().map(|file| {
+                    file.last_modified
+                        .format("%a, %d %b %Y %H:%M:%S GMT")
+                        .to_string()
+                }))),
+            ],
+        )
+        .context(ex_error::ArrowSnafu)?;
+        Ok(QueryResult::new(vec![batch], schema))
     }
 
     #[instrument(
@@ -1240,13 +3179,18 @@ impl UserQuery {
         skip(self),
         err
     )]
+    #[allow(clippy::too_many_lines)]
     pub async fn copy_into_snowflake_query(&self, statement: Statement) -> Result<QueryResult> {
         let Statement::CopyIntoSnowflake {
             into,
             from_obj,
             from_obj_alias,
             stage_params,
+            files,
+            pattern,
             file_format,
+            copy_options,
+            validation_mode,
             ..
         } = statement
         else {
@@ -1257,18 +3201,24 @@ impl UserQuery {
         };
 
         let insert_into = self.resolve_table_object_name(into.0)?;
+        self.authorize(
+            Privilege::Insert,
+            &table_grant_object(&self.resolve_table_ref(&insert_into)),
+        )
+        .await?;
+        let insert_reference: TableReference = (&insert_into).into();
 
-        // Check if this copies from an external location
-        if let Some(location) = get_external_location(&from_obj) {
-            let insert_reference: TableReference = (&insert_into).into();
-
-            let into_provider = self
-                .session
-                .ctx
-                .table_provider(insert_reference.clone())
-                .await
-                .context(ex_error::DataFusionSnafu)?;
+        let into_provider = self
+            .session
+            .ctx
+            .table_provider(insert_reference.clone())
+            .await
+            .context(ex_error::DataFusionSnafu)?;
 
+        // Check if this copies from an external location, otherwise it copies from a stage
+        let (files, file_format, copy_options) = if let Some(location) =
+            get_external_location(&from_obj)
+        {
             let url = ListingTableUrl::parse(&location.value).context(ex_error::DataFusionSnafu)?;
 
             let object_store = self
@@ -1278,51 +3228,599 @@ impl UserQuery {
             self.session
                 .ctx
                 .register_object_store(url.object_store().as_ref(), object_store);
+            (
+                self.list_location_files(&url, files.as_deref(), pattern.as_deref())
+                    .await?,
+                key_value_options_map(&file_format),
+                key_value_options_map(&copy_options),
+            )
+        } else {
+            // The parser splits `@stage/file.csv` on dots, the stage reference is parsed
+            // from the joined name instead
+            let reference =
+                StageReference::parse(&from_obj.to_string()).context(ex_error::SqlParserSnafu)?;
+            let stage = self
+                .load_stage(&self.resolve_stage_ident(&reference.name)?, Privilege::Read)
+                .await?;
+            let store = self.stage_object_store(&stage).await?;
+            let mut stage_files =
+                list_stage_files(&store, &reference.path, pattern.as_deref()).await?;
+            if let Some(files) = files {
+                let files = files
+                    .iter()
+                    .map(|file| join_stage_path(&reference.path, file))
+                    .collect::<HashSet<_>>();
+                stage_files.retain(|file| files.contains(file.location.as_ref()));
+            }
+            let base_url = stage_object_store_url(&stage.ident)?;
+            self.session.ctx.register_object_store(&base_url, store);
+            let files = stage_files
+                .iter()
+                .map(|file| {
+                    Ok(CopyFile::new(
+                        stage_file_name(&stage, &file.location),
+                        ListingTableUrl::parse(format!("{base_url}{}", file.location))
+                            .context(ex_error::DataFusionSnafu)?,
+                        file,
+                    ))
+                })
+                .collect::<Result<Vec<_>>>()?;
+            // Options of the COPY statement take precedence over the defaults of the stage
+            let file_format = if file_format.options.is_empty() {
+                stage.file_format
+            } else {
+                key_value_options_map(&file_format)
+            };
+            let mut stage_copy_options = stage.copy_options;
+            stage_copy_options.extend(key_value_options_map(&copy_options));
+            (files, file_format, stage_copy_options)
+        };
+
+        let file_format = self.resolve_file_format(file_format).await?;
+        let format = FileFormatOptions::parse(&file_format)?.unwrap_or_default();
+        let copy_options = CopyOptions::parse(&copy_options, validation_mode.as_deref())?;
+
+        // Files loaded before are skipped unless they changed since, or FORCE is set
+        let table_ident: MetastoreTableIdent = insert_into.clone().into();
+        let files = if copy_options.force {
+            files
+        } else {
+            let past_loads = self
+                .metastore
+                .list_table_loads(&table_ident)
+                .await
+                .context(ex_error::MetastoreSnafu)?;
+            files
+                .into_iter()
+                .filter(|file| {
+                    !past_loads
+                        .iter()
+                        .any(|load| load.is_loaded(&file.name, file.e_tag.as_deref(), file.size))
+                })
+                .collect::<Vec<_>>()
+        };
+        if files.is_empty() {
+            return self.created_entity_response();
+        }
+
+        let header_lines = format.header_lines();
+        let alias = from_obj_alias.as_ref().map(ToString::to_string);
+        match copy_options.validation_mode {
+            Some(ValidationMode::ReturnErrors) => {
+                let target_schema = into_provider.schema();
+                let mut errors_found = Vec::new();
+                for file in files {
+                    let errors = match self
+                        .read_copy_file(&file.url, &format, &into_provider, alias.as_deref())
+                        .await
+                    {
+                        Ok(stream) => file_errors(stream, &target_schema, header_lines).await,
+                        Err(error) => Err(error),
+                    };
+                    match errors {
+                        Ok(errors) => errors_found
+                            .extend(errors.into_iter().map(|error| (file.name.clone(), error))),
+                        Err(error) => errors_found.push((file.name, LoadError::file(&error))),
+                    }
+                }
+                let (batch, schema) = validation_errors(&errors_found)?;
+                Ok(QueryResult::new(vec![batch], schema))
+            }
+            Some(ValidationMode::ReturnRows(limit)) => {
+                let target_schema = into_provider.schema();
+                let mut rows_found = Vec::new();
+                let mut returned = 0;
+                for file in files {
+                    if returned >= limit {
+                        break;
+                    }
+                    let mut stream = self
+                        .read_copy_file(&file.url, &format, &into_provider, alias.as_deref())
+                        .await
+                        .context(ex_error::CopyIntoFileReadSnafu { file: &file.name })?;
+                    // Only errors within the returned rows fail the validation
+                    let remaining = limit - returned;
+                    let mut rows_parsed = 0;
+                    while returned < limit
+                        && let Some(batch) = stream.next().await
+                    {
+                        let batch = batch
+                            .context(ex_error::DataFusionSnafu)
+                            .context(ex_error::CopyIntoFileReadSnafu { file: &file.name })?;
+                        let (rows, errors) =
+                            split_rows(&batch, &target_schema, rows_parsed, header_lines)?;
+                        rows_parsed += batch.num_rows();
+                        if let Some(error) = errors.into_iter().next()
+                            && error.row.is_some_and(|row| row <= remaining)
+                        {
+                            return ex_error::CopyIntoFileFailedSnafu {
+                                file: file.name.clone(),
+                                line: error.line,
+                                error: error.error,
+                            }
+                            .fail();
+                        }
+                        let rows = rows.slice(0, (limit - returned).min(rows.num_rows()));
+                        returned += rows.num_rows();
+                        rows_found.push(rows);
+                    }
+                }
+                Ok(QueryResult::new(rows_found, target_schema))
+            }
+            None => {
+                // Files are inserted one by one as they are read. Unless the session has a
+                // transaction open, the inserts are buffered in a transaction of the
+                // statement and committed together once all the files are loaded.
+                let session_transaction = self.session.transactions.current();
+                let transaction = session_transaction
+                    .clone()
+                    .unwrap_or_else(|| Arc::new(Transaction::new()));
+                let into_provider = self
+                    .enlisted_table_provider(
+                        &transaction,
+                        &self.resolve_table_ref(&insert_into),
+                        into_provider,
+                    )
+                    .await?;
+                let target_schema = into_provider.schema();
+                let on_error = copy_options.on_error;
+                let mut loads = Vec::with_capacity(files.len());
+                for file in files {
+                    let counts = Arc::new(Mutex::new(LoadCounts::default()));
+                    let loaded = match self
+                        .read_copy_file(&file.url, &format, &into_provider, alias.as_deref())
+                        .await
+                    {
+                        Ok(stream) => {
+                            let stream = load_stream(
+                                stream,
+                                target_schema.clone(),
+                                header_lines,
+                                on_error,
+                                counts.clone(),
+                            );
+                            self.insert_stream(stream, &insert_reference, &into_provider)
+                                .await
+                        }
+                        Err(error) => Err(error),
+                    };
+                    #[allow(clippy::unwrap_used)]
+                    let counts = std::mem::take(&mut *counts.lock().unwrap());
+                    let load = match loaded {
+                        Ok(()) => FileLoad {
+                            file,
+                            rows_parsed: counts.rows_parsed,
+                            rows_loaded: counts.rows_loaded,
+                            error_limit: on_error.error_limit(counts.rows_parsed),
+                            errors_seen: counts.errors_seen,
+                            first_error: counts.first_error,
+                        },
+                        Err(error) if on_error == OnError::AbortStatement => {
+                            let error = counts
+                                .first_error
+                                .unwrap_or_else(|| LoadError::file(&error));
+                            return ex_error::CopyIntoFileFailedSnafu {
+                                file: file.name,
+                                line: error.line,
+                                error: error.error,
+                            }
+                            .fail();
+                        }
+                        // None of the rows of a skipped file are committed
+                        Err(_) if counts.skipped(on_error) => FileLoad {
+                            file,
+                            rows_parsed: counts.rows_parsed,
+                            rows_loaded: 0,
+                            error_limit: on_error.error_limit(counts.rows_parsed),
+                            errors_seen: counts.errors_seen,
+                            first_error: counts.first_error,
+                        },
+                        Err(error) => FileLoad {
+                            file,
+                            rows_parsed: 0,
+                            rows_loaded: 0,
+                            error_limit: on_error.error_limit(0),
+                            errors_seen: 1,
+                            first_error: Some(LoadError::file(&error)),
+                        },
+                    };
+                    loads.push(load);
+                }
+                if session_transaction.is_none() {
+                    transaction.commit(&self.metastore).await?;
+                }
+
+                if self
+                    .metastore
+                    .table_exists(&table_ident)
+                    .await
+                    .context(ex_error::MetastoreSnafu)?
+                {
+                    let loaded_at = Utc::now().naive_utc();
+                    self.metastore
+                        .record_table_loads(
+                            &table_ident,
+                            loads
+                                .iter()
+                                .map(|load| load.table_load(&table_ident, loaded_at))
+                                .collect(),
+                        )
+                        .await
+                        .context(ex_error::MetastoreSnafu)?;
+                }
+                let (batch, schema) = load_results(&loads)?;
+                Ok(QueryResult::new(vec![batch], schema))
+            }
+        }
+    }
+
+    /// Appends the rows of a stream to a table, the stream is read as the rows are
+    /// written
+    async fn insert_stream(
+        &self,
+        stream: SendableRecordBatchStream,
+        table: &TableReference,
+        provider: &Arc<dyn TableProvider>,
+    ) -> Result<()> {
+        let source =
+            StreamingTable::try_new(stream.schema(), vec![Arc::new(OnceStream::new(stream))])
+                .context(ex_error::DataFusionSnafu)?;
+        let input =
+            LogicalPlanBuilder::scan("copy_source", provider_as_source(Arc::new(source)), None)
+                .context(ex_error::DataFusionSnafu)?
+                .build()
+                .context(ex_error::DataFusionSnafu)?;
+        let plan = LogicalPlanBuilder::insert_into(
+            input,
+            table.clone(),
+            Arc::new(DefaultTableSource::new(Arc::clone(provider))),
+            InsertOp::Append,
+        )
+        .context(ex_error::DataFusionSnafu)?
+        .build()
+        .context(ex_error::DataFusionSnafu)?;
+        self.execute_logical_plan(plan).await?;
+        Ok(())
+    }
+
+    /// `COPY INTO <location>` unloads the rows of a query or a table to files in a stage
+    /// or at an external location
+    #[instrument(
+        name = "UserQuery::copy_into_location_query",
+        level = "trace",
+        skip(self),
+        err
+    )]
+    pub async fn copy_into_location_query(&self, statement: Statement) -> Result<QueryResult> {
+        let Statement::CopyIntoSnowflake {
+            into,
+            from_obj,
+            from_query,
+            stage_params,
+            file_format,
+            copy_options,
+            partition,
+            ..
+        } = statement
+        else {
+            return ex_error::OnlyCopyIntoStatementsSnafu.fail();
+        };
+        let query = match (from_query, from_obj) {
+            (Some(query), _) => query.to_string(),
+            (None, Some(table)) => format!("SELECT * FROM {table}"),
+            (None, None) => return ex_error::FromObjectRequiredForCopyIntoStatementsSnafu.fail(),
+        };
 
-            let config = self
-                .build_listing_table_config(file_format, &into_provider, url)
+        // Check if this unloads to an external location, otherwise it unloads to a stage
+        let (store, path, file_format, copy_options) = if let Some(location) =
+            get_external_location(&into)
+        {
+            let (volume, path) = self
+                .unload_volume_location(&location.value, stage_params.storage_integration)
+                .await?;
+            // Writing to a volume is like writing through a stage on the volume
+            self.authorize_stage_location(&StageLocation::Volume {
+                volume: volume.clone(),
+                path: path.trim_end_matches('/').to_string(),
+            })
+            .await?;
+            let store = volume_stage_object_store(self.metastore.as_ref(), &volume, "").await?;
+            (
+                store,
+                path,
+                key_value_options_map(&file_format),
+                key_value_options_map(&copy_options),
+            )
+        } else {
+            let location = into.to_string();
+            let reference = StageReference::parse(&location).context(ex_error::SqlParserSnafu)?;
+            let stage = self
+                .load_stage(
+                    &self.resolve_stage_ident(&reference.name)?,
+                    Privilege::Write,
+                )
                 .await?;
+            let store = self.stage_object_store(&stage).await?;
+            // The trailing slash tells a directory from a prefix of the file names
+            let path = if location.ends_with('/') && !reference.path.is_empty() {
+                format!("{}/", reference.path)
+            } else {
+                reference.path
+            };
+            let file_format = if file_format.options.is_empty() {
+                stage.file_format
+            } else {
+                key_value_options_map(&file_format)
+            };
+            let mut stage_copy_options = stage.copy_options;
+            stage_copy_options.extend(key_value_options_map(&copy_options));
+            (store, path, file_format, stage_copy_options)
+        };
+
+        let file_format = self.resolve_file_format(file_format).await?;
+        let format = FileFormatOptions::parse(&file_format)?.unwrap_or_default();
+        let options = UnloadOptions::parse(&copy_options)?;
+        if options.single && partition.is_some() {
+            return ex_error::NotSupportedStatementSnafu {
+                statement: "COPY INTO <location> with PARTITION BY and SINGLE = TRUE".to_string(),
+            }
+            .fail();
+        }
+        let mut writer = UnloadWriter::try_new(store, path, format, options, partition.is_some())?;
+
+        let query = match &partition {
+            Some(partition) => format!(
+                "SELECT *, CAST({partition} AS VARCHAR) AS {UNLOAD_PARTITION_COLUMN} FROM ({query})"
+            ),
+            None => query,
+        };
+        let plan = self.custom_plan(&query).await?;
+        let stream = self
+            .session
+            .ctx
+            .execute_logical_plan(plan)
+            .await
+            .context(ex_error::DataFusionSnafu)?
+            .execute_stream()
+            .await
+            .context(ex_error::DataFusionSnafu)?;
+        writer.write_stream(stream).await?;
+
+        let (batch, schema) = unload_results(
+            writer.rows_unloaded,
+            writer.input_bytes,
+            writer.output_bytes,
+        )?;
+        Ok(QueryResult::new(vec![batch], schema))
+    }
 
-            let table_provider =
-                ListingTable::try_new(config).context(ex_error::DataFusionSnafu)?;
+    /// Reads the schemas of the staged files of `INFER_SCHEMA`. Like for `COPY INTO`,
+    /// the path of the location is a prefix of the file paths.
+    async fn infer_schema(&self, args: InferSchemaArgs) -> Result<Vec<InferredColumn>> {
+        let reference = StageReference::parse(&args.location).context(ex_error::SqlParserSnafu)?;
+        let stage = self
+            .load_stage(&self.resolve_stage_ident(&reference.name)?, Privilege::Read)
+            .await?;
+        let store = self.stage_object_store(&stage).await?;
+        let mut files = list_stage_files(&store, &reference.path, None).await?;
+        if let Some(names) = &args.files {
+            let names = names
+                .iter()
+                .map(|file| join_stage_path(&reference.path, file))
+                .collect::<HashSet<_>>();
+            files.retain(|file| names.contains(file.location.as_ref()));
+        }
+        if let Some(max_file_count) = args.max_file_count {
+            files.truncate(max_file_count);
+        }
+        let base_url = stage_object_store_url(&stage.ident)?;
+        self.session.ctx.register_object_store(&base_url, store);
+
+        let file_format = self
+            .resolve_file_format(BTreeMap::from([(
+                "FORMAT_NAME".to_string(),
+                args.file_format.clone(),
+            )]))
+            .await?;
+        let format = FileFormatOptions::parse(&file_format)?.unwrap_or_default();
+
+        let state = self.session.ctx.state();
+        let mut schemas = Vec::with_capacity(files.len());
+        for file in &files {
+            let mut url = ListingTableUrl::parse(format!("{base_url}{}", file.location))
+                .context(ex_error::DataFusionSnafu)?;
+            let mut file_format = format.clone();
+            if let Some(rewrite) = file_format.rewrite() {
+                url = self
+                    .rewrite_file(&url, file_format.compression(), rewrite)
+                    .await?;
+                file_format.rewritten(rewrite);
+            }
+            // Unlike DataFusion, Snowflake reads files regardless of their extension
+            let schema = ListingOptions::new(file_format.listing_format(url.prefix().as_ref())?)
+                .with_file_extension("")
+                .infer_schema(&state, &url)
+                .await
+                .context(ex_error::DataFusionSnafu)?;
+            schemas.push((file.location.to_string(), schema));
+        }
+        Ok(infer_columns(&format, &schemas, args.ignore_case))
+    }
+
+    /// Replaces the `INFER_SCHEMA` table function with a subquery returning its rows
+    async fn replace_infer_schema_function(&self, relation: &mut TableFactor) -> Result<()> {
+        let TableFactor::Function {
+            name, args, alias, ..
+        } = relation
+        else {
+            return Ok(());
+        };
+        if !name
+            .0
+            .last()
+            .is_some_and(|part| part.to_string().eq_ignore_ascii_case(INFER_SCHEMA_FUNCTION))
+        {
+            return Ok(());
+        }
+        let columns = self.infer_schema(InferSchemaArgs::parse(args)?).await?;
+        let alias = alias.clone().unwrap_or_else(|| TableAlias {
+            name: Ident::new(INFER_SCHEMA_FUNCTION),
+            columns: vec![],
+        });
+        *relation = TableFactor::Derived {
+            lateral: false,
+            subquery: infer_schema_query(&columns).context(ex_error::SqlParserSnafu)?,
+            alias: Some(alias),
+        };
+        Ok(())
+    }
 
-            let builder = LogicalPlanBuilder::scan(
-                "external_location",
-                Arc::new(DefaultTableSource::new(Arc::new(table_provider))),
-                None,
+    /// Expands `OBJECT_CONSTRUCT(*)` in the projection to the names and values of the
+    /// columns of the FROM clause, the planner has no wildcard function arguments
+    async fn expand_object_construct_wildcard(
+        &self,
+        select: &mut sqlparser::ast::Select,
+    ) -> Result<()> {
+        let is_wildcard_object = |expr: &Expr| {
+            matches!(
+                expr,
+                Expr::Function(Function {
+                    name,
+                    args: FunctionArguments::List(FunctionArgumentList { args, .. }),
+                    ..
+                }) if name.to_string().eq_ignore_ascii_case("object_construct")
+                    && matches!(args.as_slice(), [FunctionArg::Unnamed(FunctionArgExpr::Wildcard)])
             )
-            .context(ex_error::DataFusionSnafu)?;
-
-            let builder = if let Some(alias) = from_obj_alias {
-                builder
-                    .alias(alias.to_string())
-                    .context(ex_error::DataFusionSnafu)?
+        };
+        let found = visit_expressions(&select.projection, |expr| {
+            if is_wildcard_object(expr) {
+                ControlFlow::Break(())
             } else {
-                builder
-            };
+                ControlFlow::Continue(())
+            }
+        });
+        if found.is_continue() || select.from.is_empty() {
+            return Ok(());
+        }
 
-            let input = builder.build().context(ex_error::DataFusionSnafu)?;
+        let from = select
+            .from
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+        let statement = self
+            .sql_to_df_statement(&format!("SELECT * FROM {from}"))
+            .context(ex_error::DataFusionSnafu)?;
+        let plan = self.get_custom_logical_plan(statement).await?;
+        let pairs = plan
+            .schema()
+            .fields()
+            .iter()
+            .flat_map(|field| {
+                [
+                    Expr::Value(Value::SingleQuotedString(field.name().clone()).with_empty_span()),
+                    Expr::Identifier(Ident::with_quote('"', field.name())),
+                ]
+            })
+            .map(|expr| FunctionArg::Unnamed(FunctionArgExpr::Expr(expr)))
+            .collect::<Vec<_>>();
+        let _ = visit_expressions_mut(&mut select.projection, |expr| {
+            if is_wildcard_object(expr)
+                && let Expr::Function(Function {
+                    args: FunctionArguments::List(list),
+                    ..
+                }) = expr
+            {
+                list.args.clone_from(&pairs);
+            }
+            ControlFlow::<()>::Continue(())
+        });
+        Ok(())
+    }
 
-            let input = if input.schema().as_arrow() == &*into_provider.schema() {
-                input
-            } else {
-                cast_input_to_target_schema(Arc::new(input), &into_provider.schema())?
-            };
+    /// `CREATE TABLE ... USING TEMPLATE` creates a table with the columns returned by
+    /// the template query, such as the columns `INFER_SCHEMA` found in staged files
+    async fn create_table_using_template(
+        &self,
+        statement: TemplateTableStatement,
+    ) -> Result<QueryResult> {
+        self.running_queries.update_stats(
+            self.query_context.query_id,
+            &QueryStats::default().with_query_type(QueryType::Ddl(DdlStType::CreateTable)),
+        );
+        let table = statement.name.to_string();
+        let mut template =
+            DFStatement::Statement(Box::new(Statement::Query(statement.template.clone())));
+        Self::postprocess_query_statement_with_validation(&mut template)?;
+        let DFStatement::Statement(template) = template else {
+            return ex_error::InvalidTableTemplateSnafu {
+                table,
+                reason: "expected a query",
+            }
+            .fail();
+        };
+        let Statement::Query(mut template) = *template else {
+            return ex_error::InvalidTableTemplateSnafu {
+                table,
+                reason: "expected a query",
+            }
+            .fail();
+        };
+        self.traverse_and_update_query(template.as_mut()).await?;
+        let records = self
+            .execute_with_custom_plan(&template.to_string())
+            .await?
+            .records;
+        let value = records
+            .iter()
+            .find(|batch| batch.num_rows() > 0 && batch.num_columns() > 0)
+            .map(|batch| datafusion::arrow::compute::cast(batch.column(0), &DataType::Utf8))
+            .transpose()
+            .context(ex_error::ArrowSnafu)?;
+        let Some(value) = value
+            .as_ref()
+            .map(|value| value.as_string::<i32>())
+            .filter(|value| value.is_valid(0))
+            .map(|value| value.value(0))
+        else {
+            return ex_error::InvalidTableTemplateSnafu {
+                table,
+                reason: "the template returned no value",
+            }
+            .fail();
+        };
+        let columns = template_columns(&table, value)?;
 
-            let plan = LogicalPlanBuilder::insert_into(
-                input,
-                insert_reference,
-                Arc::new(DefaultTableSource::new(into_provider)),
-                InsertOp::Append,
-            )
-            .context(ex_error::DataFusionSnafu)?
-            .build()
+        self.commit_transaction().await?;
+        let mut create = self
+            .sql_to_df_statement(&template_table_sql(&statement, &columns))
             .context(ex_error::DataFusionSnafu)?;
-
-            self.execute_logical_plan(plan).await
-        } else {
-            ex_error::StagesNotSupportedSnafu.fail()
-        }
+        Self::postprocess_query_statement_with_validation(&mut create)?;
+        let DFStatement::Statement(create) = create else {
+            return ex_error::OnlyCreateTableStatementsSnafu.fail();
+        };
+        Box::pin(self.create_table_query(*create)).await
     }
 
     #[instrument(name = "UserQuery::merge_query", level = "trace", skip(self), err)]
@@ -1364,7 +3862,7 @@ impl UserQuery {
 
         // Create a LogicalPlan for the source table
 
-        let (source_plan, target_filter) = match source {
+        let (source_plan, source_table) = match source {
             TableFactor::Table {
                 name: source_ident,
                 alias: source_alias,
@@ -1373,14 +3871,16 @@ impl UserQuery {
                 let source_ident = self.resolve_table_object_name(source_ident.0)?;
 
                 let source_provider = self.get_caching_table_provider(&source_ident).await?;
+                self.authorize(
+                    Privilege::Select,
+                    &table_grant_object(&self.resolve_table_ref(&source_ident)),
+                )
+                .await?;
 
-                let target_filter = if let Some(table) =
-                    source_provider.as_any().downcast_ref::<DataFusionTable>()
-                {
-                    target_filter_expression(table).await?
-                } else {
-                    None
-                };
+                let source_table = source_provider
+                    .as_any()
+                    .downcast_ref::<DataFusionTable>()
+                    .cloned();
 
                 let source_table_source: Arc<dyn TableSource> =
                     Arc::new(DefaultTableSource::new(source_provider));
@@ -1406,7 +3906,7 @@ impl UserQuery {
                 .with_column(SOURCE_EXISTS_COLUMN, lit(true))
                 .context(ex_error::DataFusionLogicalPlanMergeSourceSnafu)?
                 .into_unoptimized_plan();
-                Ok((source_plan, target_filter))
+                Ok((source_plan, source_table))
             }
             TableFactor::Derived {
                 lateral: _,
@@ -1464,6 +3964,15 @@ impl UserQuery {
         };
 
         let target_ident = self.resolve_table_object_name(target_ident.0)?;
+        let target_object = table_grant_object(&self.resolve_table_ref(&target_ident));
+        for clause in &clauses {
+            let privilege = match clause.action {
+                MergeAction::Insert(_) => Privilege::Insert,
+                MergeAction::Update { .. } => Privilege::Update,
+                MergeAction::Delete => Privilege::Delete,
+            };
+            self.authorize(privilege, &target_object).await?;
+        }
 
         let target_table = self
             .get_iceberg_table_provider(
@@ -1486,18 +3995,8 @@ impl UserQuery {
             target_table_source.clone(),
         );
 
-        let plan = if let Some(target_filter) = target_filter {
-            LogicalPlanBuilder::scan_with_filters(
-                &target_ident,
-                target_table_source,
-                None,
-                vec![target_filter],
-            )
-            .context(ex_error::DataFusionLogicalPlanMergeTargetSnafu)?
-        } else {
-            LogicalPlanBuilder::scan(&target_ident, target_table_source, None)
-                .context(ex_error::DataFusionLogicalPlanMergeTargetSnafu)?
-        };
+        let plan = LogicalPlanBuilder::scan(&target_ident, target_table_source, None)
+            .context(ex_error::DataFusionLogicalPlanMergeTargetSnafu)?;
         let plan = if let Some(target_alias) = target_alias {
             plan.alias(target_alias.name.to_string())
                 .context(ex_error::DataFusionLogicalPlanMergeTargetSnafu)?
@@ -1542,28 +4041,492 @@ impl UserQuery {
             clauses,
         )?;
 
+        // Prune the target data files by the partition bounds of the source table
+        let target_filter = if let Some(source_table) = &source_table {
+            target_filter_expression(
+                source_table,
+                &target_table,
+                &on_expr,
+                &source_schema,
+                &target_schema,
+            )
+            .await?
+        } else {
+            None
+        };
+        let target_plan = if let Some(target_filter) = target_filter {
+            with_scan_filter(target_plan, target_filter)?
+        } else {
+            target_plan
+        };
+
         let join_plan = LogicalPlanBuilder::new(target_plan)
             .join_on(source_plan, JoinType::Full, [on_expr; 1])
             .context(ex_error::DataFusionLogicalPlanMergeJoinSnafu)?
             .project(merge_clause_projection)
             .context(ex_error::DataFusionLogicalPlanMergeJoinSnafu)?
             .build()
-            .context(ex_error::DataFusionLogicalPlanMergeJoinSnafu)?;
+            .context(ex_error::DataFusionLogicalPlanMergeJoinSnafu)?;
+
+        let merge_into_plan = MergeIntoCOWSink::new(
+            Arc::new(join_plan),
+            target_table,
+            has_insert,
+            has_update,
+            has_delete,
+        )
+        .context(ex_error::DataFusionSnafu)?;
+
+        Ok(LogicalPlan::Extension(Extension {
+            node: Arc::new(merge_into_plan),
+        }))
+    }
+
+    #[instrument(name = "UserQuery::update_query", level = "trace", skip(self), err)]
+    pub async fn update_query(&self, statement: Statement) -> Result<QueryResult> {
+        let plan = self.update_to_logical_plan(statement).await?;
+        self.execute_logical_plan(plan).await
+    }
+
+    /// Builds the copy-on-write logical plan for an `UPDATE` statement.
+    ///
+    /// The target is scanned together with its data and manifest file path columns.
+    /// Without a `FROM` clause the `WHERE` predicate is evaluated against the target
+    /// alone, otherwise the target is left-joined with the `FROM` relations on it and
+    /// only one joined row is kept per target row, so a target row matching several
+    /// source rows is updated once instead of being duplicated. Every assigned column
+    /// is projected as `CASE WHEN <matched> THEN <value> ELSE <column> END` and the
+    /// matched flag is exposed as `__source_exists`, so the `MergeIntoCOWSink` rewrites
+    /// only the data files that contain updated rows.
+    #[allow(clippy::too_many_lines)]
+    #[instrument(
+        name = "UserQuery::update_to_logical_plan",
+        level = "trace",
+        skip(self),
+        err
+    )]
+    pub async fn update_to_logical_plan(&self, statement: Statement) -> Result<LogicalPlan> {
+        // Fully qualify the target, the FROM relations and any subqueries up front,
+        // so every table source below is resolved against the same names.
+        let mut df_statement = DFStatement::Statement(Box::new(statement));
+        self.update_statement_references(&mut df_statement)?;
+        let df_session_state = self.session.ctx.state();
+        let tables = self
+            .table_references_for_statement(&df_statement, &df_session_state)
+            .await?;
+
+        let DFStatement::Statement(statement) = df_statement else {
+            return ex_error::OnlyUpdateStatementsSnafu.fail();
+        };
+        let Statement::Update {
+            table,
+            assignments,
+            from,
+            selection,
+            ..
+        } = *statement
+        else {
+            return ex_error::OnlyUpdateStatementsSnafu.fail();
+        };
+        if !table.joins.is_empty() {
+            return ex_error::UpdateTargetMustBeTableSnafu.fail();
+        }
+        let TableFactor::Table {
+            name: target_ident,
+            alias: target_alias,
+            ..
+        } = table.relation
+        else {
+            return ex_error::UpdateTargetMustBeTableSnafu.fail();
+        };
+
+        let mut session_context_provider = SessionContextProvider {
+            state: &df_session_state,
+            tables,
+        };
+        let mut planner_context = datafusion::sql::planner::PlannerContext::new();
+
+        // Create a LogicalPlan for the target table
+        let target_ident = self.resolve_table_object_name(target_ident.0)?;
+        let (target_table, target_plan) = self
+            .copy_on_write_target_plan(&target_ident, target_alias, &mut session_context_provider)
+            .await?;
+        let target_schema = target_plan.schema().clone();
+
+        let from = from
+            .map(|from| match from {
+                UpdateTableFromKind::BeforeSet(tables) | UpdateTableFromKind::AfterSet(tables) => {
+                    tables
+                }
+            })
+            .unwrap_or_default();
+
+        // Join with the FROM relations (if any) and compute the matched flag
+        let (input_plan, matched) = if from.is_empty() {
+            let matched = if let Some(selection) = selection {
+                let predicate = SqlToRel::new(&session_context_provider)
+                    .sql_to_expr(selection, &target_schema, &mut planner_context)
+                    .context(ex_error::DataFusionSnafu)?;
+                is_true(predicate)
+            } else {
+                lit(true)
+            };
+            (target_plan, matched)
+        } else {
+            let from_query = format!(
+                "SELECT * FROM {}",
+                from.iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            let from_statement = self
+                .sql_to_df_statement(&from_query)
+                .context(ex_error::DataFusionSnafu)?;
+            let source_plan = Box::pin(self.get_custom_logical_plan(from_statement)).await?;
+            let source_plan = DataFrame::new(df_session_state.clone(), source_plan)
+                .with_column(SOURCE_EXISTS_COLUMN, lit(true))
+                .context(ex_error::DataFusionSnafu)?
+                .into_unoptimized_plan();
+
+            // Number the target rows within their data file, so the rows of the join for
+            // a target row matching several source rows can be told apart and only one of
+            // them kept. Both windows are partitioned by the data file, so they don't
+            // gather the whole target into a single partition.
+            let data_file_path = DFExpr::Column(Column::from(
+                target_schema
+                    .qualified_field_with_unqualified_name(DATA_FILE_PATH_COLUMN)
+                    .context(ex_error::DataFusionSnafu)?,
+            ));
+            let row_id = row_number()
+                .partition_by(vec![data_file_path.clone()])
+                .build()
+                .context(ex_error::DataFusionSnafu)?
+                .alias(UPDATE_ROW_ID_COLUMN);
+            let target_plan = LogicalPlanBuilder::from(target_plan)
+                .window(vec![row_id])
+                .context(ex_error::DataFusionSnafu)?
+                .build()
+                .context(ex_error::DataFusionSnafu)?;
+            let join_schema =
+                build_join_schema(target_plan.schema(), source_plan.schema(), &JoinType::Left)
+                    .context(ex_error::DataFusionSnafu)?;
+            let on_expr = if let Some(selection) = selection {
+                SqlToRel::new(&session_context_provider)
+                    .sql_to_expr(selection, &join_schema, &mut planner_context)
+                    .context(ex_error::DataFusionSnafu)?
+            } else {
+                lit(true)
+            };
+            // A target row is updated once, from one of the source rows it matches,
+            // as in Snowflake with ERROR_ON_NONDETERMINISTIC_UPDATE = FALSE
+            let row_match = row_number()
+                .partition_by(vec![data_file_path, col(UPDATE_ROW_ID_COLUMN)])
+                .build()
+                .context(ex_error::DataFusionSnafu)?
+                .alias(UPDATE_ROW_MATCH_COLUMN);
+            let join_plan = LogicalPlanBuilder::new(target_plan)
+                .join_on(source_plan, JoinType::Left, [on_expr; 1])
+                .context(ex_error::DataFusionSnafu)?
+                .window(vec![row_match])
+                .context(ex_error::DataFusionSnafu)?
+                .filter(col(UPDATE_ROW_MATCH_COLUMN).eq(lit(1_u64)))
+                .context(ex_error::DataFusionSnafu)?
+                .build()
+                .context(ex_error::DataFusionSnafu)?;
+            (join_plan, is_true(col(SOURCE_EXISTS_COLUMN)))
+        };
+        let input_schema = input_plan.schema().clone();
+
+        let sql_planner = SqlToRel::new(&session_context_provider);
+        let mut updates: HashMap<String, DFExpr> = HashMap::new();
+        for assignment in assignments {
+            let AssignmentTarget::ColumnName(column) = assignment.target else {
+                return ex_error::NotSupportedStatementSnafu {
+                    statement: "UPDATE with tuple assignment".to_string(),
+                }
+                .fail();
+            };
+            let column_name = column
+                .0
+                .last()
+                .and_then(ObjectNamePart::as_ident)
+                .map(|ident| self.normalize_ident(ident.clone()).value)
+                .context(ex_error::InvalidColumnIdentifierSnafu {
+                    ident: column.to_string(),
+                })?;
+            let value = sql_planner
+                .sql_to_expr(assignment.value, &input_schema, &mut planner_context)
+                .context(ex_error::DataFusionSnafu)?;
+            updates.insert(column_name, value);
+        }
+
+        let mut projection = Vec::with_capacity(target_schema.fields().len() + 2);
+        for (qualifier, field) in target_schema.iter() {
+            let name = field.name();
+            let column = DFExpr::Column(Column::new(qualifier.cloned(), name));
+            let assigned = updates
+                .keys()
+                .find(|key| *key == name || key.eq_ignore_ascii_case(name))
+                .cloned();
+            let expr = match assigned.and_then(|key| updates.remove(&key)) {
+                Some(value) => when(
+                    matched.clone(),
+                    cast_if_necessary(value, field.data_type(), &input_schema),
+                )
+                .otherwise(column)
+                .context(ex_error::DataFusionSnafu)?
+                .alias(name),
+                None => column,
+            };
+            projection.push(expr);
+        }
+        if let Some(name) = updates.into_keys().next() {
+            return ex_error::MissingOrInvalidColumnSnafu { name }.fail();
+        }
+        projection.push(matched.clone().alias(SOURCE_EXISTS_COLUMN));
+        projection.push(matched.alias(MERGE_UPDATED_COLUMN));
+
+        let update_plan = LogicalPlanBuilder::from(input_plan)
+            .project(projection)
+            .context(ex_error::DataFusionSnafu)?
+            .build()
+            .context(ex_error::DataFusionSnafu)?;
+
+        let update_sink =
+            MergeIntoCOWSink::new(Arc::new(update_plan), target_table, false, true, false)
+                .context(ex_error::DataFusionSnafu)?;
+
+        Ok(LogicalPlan::Extension(Extension {
+            node: Arc::new(update_sink),
+        }))
+    }
+
+    #[instrument(name = "UserQuery::delete_query", level = "trace", skip(self), err)]
+    pub async fn delete_query(&self, statement: Statement) -> Result<QueryResult> {
+        let plan = self.delete_to_logical_plan(statement).await?;
+        self.execute_logical_plan(plan).await
+    }
+
+    /// Builds the copy-on-write logical plan for a `DELETE` statement.
+    ///
+    /// Without a `USING` clause the `WHERE` predicate is evaluated against the target
+    /// alone. Otherwise the target is mark-joined with the `USING` relations, so every
+    /// target row is flagged at most once no matter how many source rows it matches.
+    /// When `USING` names a single partitioned Iceberg table, its partition bounds prune
+    /// the target data files the same way `MERGE` does. The flag is exposed as
+    /// both `__source_exists` and `__merge_row_deleted`: the `MergeIntoCOWSink` then
+    /// rewrites only the data files containing deleted rows and leaves those rows out.
+    #[instrument(
+        name = "UserQuery::delete_to_logical_plan",
+        level = "trace",
+        skip(self),
+        err
+    )]
+    pub async fn delete_to_logical_plan(&self, statement: Statement) -> Result<LogicalPlan> {
+        let mut df_statement = DFStatement::Statement(Box::new(statement));
+        self.update_statement_references(&mut df_statement)?;
+        let df_session_state = self.session.ctx.state();
+        let tables = self
+            .table_references_for_statement(&df_statement, &df_session_state)
+            .await?;
+
+        let DFStatement::Statement(statement) = df_statement else {
+            return ex_error::OnlyDeleteStatementsSnafu.fail();
+        };
+        let Statement::Delete(Delete {
+            tables: multi_tables,
+            from,
+            using,
+            selection,
+            returning,
+            order_by,
+            limit,
+        }) = *statement
+        else {
+            return ex_error::OnlyDeleteStatementsSnafu.fail();
+        };
+        if !multi_tables.is_empty()
+            || returning.is_some()
+            || !order_by.is_empty()
+            || limit.is_some()
+        {
+            return ex_error::NotSupportedStatementSnafu {
+                statement: "DELETE with multiple tables, RETURNING, ORDER BY or LIMIT".to_string(),
+            }
+            .fail();
+        }
+        let (FromTable::WithFromKeyword(mut from) | FromTable::WithoutKeyword(mut from)) = from;
+        let target = match from.pop() {
+            Some(target) if from.is_empty() && target.joins.is_empty() => target,
+            _ => return ex_error::DeleteTargetMustBeTableSnafu.fail(),
+        };
+        let TableFactor::Table {
+            name: target_ident,
+            alias: target_alias,
+            ..
+        } = target.relation
+        else {
+            return ex_error::DeleteTargetMustBeTableSnafu.fail();
+        };
+        let using = using.unwrap_or_default();
+
+        let mut session_context_provider = SessionContextProvider {
+            state: &df_session_state,
+            tables,
+        };
+        let mut planner_context = datafusion::sql::planner::PlannerContext::new();
+
+        let source_table = match using.as_slice() {
+            [
+                TableWithJoins {
+                    relation: TableFactor::Table { name, .. },
+                    joins,
+                },
+            ] if joins.is_empty() => {
+                let source_ident = self.resolve_table_object_name(name.0.clone())?;
+                let source_provider = self.get_caching_table_provider(&source_ident).await?;
+                source_provider
+                    .as_any()
+                    .downcast_ref::<DataFusionTable>()
+                    .cloned()
+            }
+            _ => None,
+        };
+
+        let target_ident = self.resolve_table_object_name(target_ident.0)?;
+        let (target_table, target_plan) = self
+            .copy_on_write_target_plan(&target_ident, target_alias, &mut session_context_provider)
+            .await?;
+        let target_schema = target_plan.schema().clone();
+
+        // Mark the target rows to delete
+        let (input_plan, deleted) = if using.is_empty() {
+            let deleted = if let Some(selection) = selection {
+                let predicate = SqlToRel::new(&session_context_provider)
+                    .sql_to_expr(selection, &target_schema, &mut planner_context)
+                    .context(ex_error::DataFusionSnafu)?;
+                is_true(predicate)
+            } else {
+                lit(true)
+            };
+            (target_plan, deleted)
+        } else {
+            let using_query = format!(
+                "SELECT * FROM {}",
+                using
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            );
+            let using_statement = self
+                .sql_to_df_statement(&using_query)
+                .context(ex_error::DataFusionSnafu)?;
+            let source_plan = Box::pin(self.get_custom_logical_plan(using_statement)).await?;
+
+            let join_schema =
+                build_join_schema(&target_schema, source_plan.schema(), &JoinType::Left)
+                    .context(ex_error::DataFusionSnafu)?;
+            let on_expr = if let Some(selection) = selection {
+                SqlToRel::new(&session_context_provider)
+                    .sql_to_expr(selection, &join_schema, &mut planner_context)
+                    .context(ex_error::DataFusionSnafu)?
+            } else {
+                lit(true)
+            };
+            // Prune the target data files by the partition bounds of a single USING table
+            let target_filter = if let Some(source_table) = &source_table {
+                target_filter_expression(
+                    source_table,
+                    &target_table,
+                    &on_expr,
+                    source_plan.schema(),
+                    &target_schema,
+                )
+                .await?
+            } else {
+                None
+            };
+            let target_plan = if let Some(target_filter) = target_filter {
+                with_scan_filter(target_plan, target_filter)?
+            } else {
+                target_plan
+            };
+            let join_plan = LogicalPlanBuilder::new(target_plan)
+                .join_on(source_plan, JoinType::LeftMark, [on_expr; 1])
+                .context(ex_error::DataFusionSnafu)?
+                .build()
+                .context(ex_error::DataFusionSnafu)?;
+            // The mark column is appended after all of the target columns
+            let mark = join_plan
+                .schema()
+                .qualified_field(target_schema.fields().len());
+            let deleted = is_true(DFExpr::Column(Column::from(mark)));
+            (join_plan, deleted)
+        };
+
+        let mut projection = target_schema
+            .iter()
+            .map(|(qualifier, field)| DFExpr::Column(Column::new(qualifier.cloned(), field.name())))
+            .collect::<Vec<_>>();
+        projection.push(deleted.clone().alias(SOURCE_EXISTS_COLUMN));
+        projection.push(deleted.alias(MERGE_DELETED_COLUMN));
+
+        let delete_plan = LogicalPlanBuilder::from(input_plan)
+            .project(projection)
+            .context(ex_error::DataFusionSnafu)?
+            .build()
+            .context(ex_error::DataFusionSnafu)?;
 
-        let merge_into_plan = MergeIntoCOWSink::new(
-            Arc::new(join_plan),
-            target_table,
-            has_insert,
-            has_update,
-            has_delete,
-        )
-        .context(ex_error::DataFusionSnafu)?;
+        let delete_sink =
+            MergeIntoCOWSink::new(Arc::new(delete_plan), target_table, false, false, true)
+                .context(ex_error::DataFusionSnafu)?;
 
         Ok(LogicalPlan::Extension(Extension {
-            node: Arc::new(merge_into_plan),
+            node: Arc::new(delete_sink),
         }))
     }
 
+    /// Plans the scan of an `UPDATE` or `DELETE` target including its data and manifest
+    /// file path columns, and registers the target with the context provider.
+    async fn copy_on_write_target_plan(
+        &self,
+        target_ident: &NormalizedIdent,
+        target_alias: Option<TableAlias>,
+        session_context_provider: &mut SessionContextProvider<'_>,
+    ) -> Result<(DataFusionTable, LogicalPlan)> {
+        let target_table = self
+            .get_iceberg_table_provider(
+                target_ident,
+                Some(
+                    DataFusionTableConfigBuilder::default()
+                        .enable_data_file_path_column(true)
+                        .enable_manifest_file_path_column(true)
+                        .build()
+                        .context(ex_error::IcebergSnafu)?,
+                ),
+            )
+            .await?;
+        let target_table_source: Arc<dyn TableSource> =
+            Arc::new(DefaultTableSource::new(Arc::new(target_table.clone())));
+        session_context_provider.tables.insert(
+            self.resolve_table_ref(target_ident),
+            target_table_source.clone(),
+        );
+
+        let plan = LogicalPlanBuilder::scan(target_ident, target_table_source, None)
+            .context(ex_error::DataFusionSnafu)?;
+        let plan = if let Some(target_alias) = target_alias {
+            plan.alias(target_alias.name.to_string())
+                .context(ex_error::DataFusionSnafu)?
+        } else {
+            plan
+        };
+        let target_plan = plan.build().context(ex_error::DataFusionSnafu)?;
+        Ok((target_table, target_plan))
+    }
+
     #[instrument(name = "UserQuery::create_database", level = "trace", skip(self), err)]
     pub async fn create_database(
         &self,
@@ -1571,6 +4534,7 @@ impl UserQuery {
         if_not_exists: bool,
         external_volume: Option<String>,
     ) -> Result<QueryResult> {
+        self.authorize_account().await?;
         let catalog_name = object_name_to_string(&db_name);
         if external_volume.is_none() {
             return ex_error::ExternalVolumeRequiredForCreateDatabaseSnafu { name: catalog_name }
@@ -1616,6 +4580,7 @@ impl UserQuery {
         storage_locations: Vec<CloudProviderParams>,
         if_not_exists: bool,
     ) -> Result<QueryResult> {
+        self.authorize_account().await?;
         let ident = object_name_to_string(&name);
 
         if let Ok(Some(_)) = self.metastore.get_volume(&ident).await {
@@ -1713,13 +4678,24 @@ impl UserQuery {
     #[instrument(name = "UserQuery::create_view", level = "trace", skip(self), err)]
     pub async fn create_view(&self, statement: Statement) -> Result<QueryResult> {
         let mut plan = self.sql_statement_to_plan(statement).await?;
-        match &mut plan {
+        let view_ref = match &mut plan {
             LogicalPlan::Ddl(DdlStatement::CreateView(cv)) => {
                 cv.temporary = false;
+                self.resolve_table_ref(cv.name.clone())
             }
             _ => return ex_error::OnlyCreateViewStatementsSnafu.fail(),
+        };
+        self.authorize(
+            Privilege::CreateView,
+            &GrantObject::schema(&view_ref.catalog, &view_ref.schema),
+        )
+        .await?;
+        let created = !self.table_exists(&view_ref);
+        let result = self.execute_logical_plan(plan).await?;
+        if created {
+            self.grant_ownership(table_grant_object(&view_ref)).await?;
         }
-        self.execute_logical_plan(plan).await
+        Ok(result)
     }
 
     #[instrument(name = "UserQuery::create_schema", level = "trace", skip(self), err)]
@@ -1734,6 +4710,11 @@ impl UserQuery {
             _ => return ex_error::OnlyCreateSchemaStatementsSnafu.fail(),
         };
 
+        self.authorize(
+            Privilege::CreateSchema,
+            &GrantObject::database(&schema_ref.catalog),
+        )
+        .await?;
         let catalog = self.get_catalog(&schema_ref.catalog)?;
         if catalog.schema(&schema_ref.schema).is_some() {
             if if_not_exists {
@@ -1746,9 +4727,358 @@ impl UserQuery {
             .fail();
         }
         self.execute_logical_plan(plan).await?;
+        self.grant_ownership(GrantObject::schema(&schema_ref.catalog, &schema_ref.schema))
+            .await?;
+        self.created_entity_response()
+    }
+
+    /// Creates a zero-copy clone of a table, or of every table of a schema or database.
+    /// The clones are registered with metadata of their own that refers to the data
+    /// files of their sources, so nothing is copied until a clone and its source diverge.
+    #[allow(clippy::too_many_lines)]
+    #[instrument(
+        name = "UserQuery::execute_clone_statement",
+        level = "trace",
+        skip(self),
+        err
+    )]
+    async fn execute_clone_statement(&self, statement: CloneStatement) -> Result<QueryResult> {
+        let query_type = match statement.kind {
+            CloneKind::Table => DdlStType::CreateTable,
+            CloneKind::Schema => DdlStType::CreateSchema,
+            CloneKind::Database => DdlStType::CreateDatabase,
+        };
+        self.running_queries.update_stats(
+            self.query_context.query_id,
+            &QueryStats::default().with_query_type(QueryType::Ddl(query_type)),
+        );
+        // DDL statements implicitly commit the open transaction, as in Snowflake
+        self.commit_transaction().await?;
+
+        let source_name = statement.source.to_string();
+        let point = match &statement.version {
+            Some(version) => {
+                let time_travel = TimeTravel::parse(&source_name, version)?;
+                let timestamp_ms = self
+                    .time_travel_timestamp(&source_name, &time_travel)
+                    .await?;
+                Some((timestamp_ms, time_travel.before))
+            }
+            None => None,
+        };
+        match statement.kind {
+            CloneKind::Table => {
+                let source =
+                    self.resolve_table_ref(&self.resolve_table_object_name(statement.source.0)?);
+                let target =
+                    self.resolve_table_ref(&self.resolve_table_object_name(statement.name.0)?);
+                self.authorize(
+                    Privilege::CreateTable,
+                    &GrantObject::schema(&target.catalog, &target.schema),
+                )
+                .await?;
+                if self.table_exists(&target) {
+                    if statement.if_not_exists {
+                        return self.created_entity_response();
+                    }
+                    if !statement.or_replace {
+                        return ex_error::ObjectAlreadyExistsSnafu {
+                            r#type: ExistingObjectType::Table,
+                            name: target.table.to_string(),
+                        }
+                        .fail();
+                    }
+                    self.drop_cloned_object("TABLE", &target.to_string())
+                        .await?;
+                }
+                let source_table = self
+                    .metastore
+                    .get_table(&MetastoreTableIdent::new(
+                        &source.catalog,
+                        &source.schema,
+                        &source.table,
+                    ))
+                    .await
+                    .context(ex_error::MetastoreSnafu)?
+                    .context(ex_error::TableNotFoundInSchemaInDatabaseSnafu {
+                        operation_on: OperationOn::Table(OperationType::Create),
+                        table: source.table.to_string(),
+                        schema: source.schema.to_string(),
+                        db: source.catalog.to_string(),
+                    })?;
+                if let Some((timestamp_ms, _)) = point
+                    && source_table.created_at.and_utc().timestamp_millis() > timestamp_ms
+                {
+                    return Err(not_created_yet(&source.to_string()));
+                }
+                self.clone_table(&source, &target, &source_table, point)
+                    .await?;
+            }
+            CloneKind::Schema => {
+                let source = self.schema_ref_to_resolved(self.resolve_schema_name(&source_name)?);
+                let target = self
+                    .schema_ref_to_resolved(self.resolve_schema_name(&statement.name.to_string())?);
+                if self
+                    .get_catalog(&target.catalog)?
+                    .schema(&target.schema)
+                    .is_some()
+                {
+                    if statement.if_not_exists {
+                        return self.created_entity_response();
+                    }
+                    if !statement.or_replace {
+                        return ex_error::ObjectAlreadyExistsSnafu {
+                            r#type: ExistingObjectType::Schema,
+                            name: target.schema.to_string(),
+                        }
+                        .fail();
+                    }
+                    self.drop_cloned_object(
+                        "SCHEMA",
+                        &format!("{}.{}", target.catalog, target.schema),
+                    )
+                    .await?;
+                }
+                self.clone_schema(&source, &target, point).await?;
+            }
+            CloneKind::Database => {
+                self.authorize_account().await?;
+                let source = object_name_to_string(&statement.source);
+                let target = object_name_to_string(&statement.name);
+                let database = self
+                    .metastore
+                    .get_database(&source)
+                    .await
+                    .context(ex_error::MetastoreSnafu)?
+                    .context(ex_error::DatabaseNotFoundSnafu { db: source.clone() })?;
+                if self.get_catalog(&target).is_ok() {
+                    if statement.if_not_exists {
+                        return self.created_entity_response();
+                    }
+                    if !statement.or_replace {
+                        return ex_error::ObjectAlreadyExistsSnafu {
+                            r#type: ExistingObjectType::Database,
+                            name: target,
+                        }
+                        .fail();
+                    }
+                    self.drop_cloned_object("DATABASE", &target).await?;
+                }
+                self.create_catalog(&target, &database.volume).await?;
+                for schema in self
+                    .metastore
+                    .list_schemas(&source)
+                    .await
+                    .context(ex_error::MetastoreSnafu)?
+                {
+                    let schema_ref = |catalog: &str| ResolvedTableReference {
+                        catalog: Arc::from(catalog),
+                        schema: Arc::from(schema.ident.schema.as_str()),
+                        table: Arc::from(""),
+                    };
+                    self.clone_schema(&schema_ref(&source), &schema_ref(&target), point)
+                        .await?;
+                }
+            }
+        }
         self.created_entity_response()
     }
 
+    /// Drops the object a `CREATE OR REPLACE ... CLONE` replaces
+    async fn drop_cloned_object(&self, object_type: &str, name: &str) -> Result<()> {
+        // The tables of a schema or database go along with it
+        let cascade = if object_type == "TABLE" {
+            ""
+        } else {
+            " CASCADE"
+        };
+        let statement = self
+            .sql_to_df_statement(&format!("DROP {object_type} IF EXISTS {name}{cascade}"))
+            .context(ex_error::DataFusionSnafu)?;
+        let DFStatement::Statement(statement) = statement else {
+            return ex_error::OnlyDropStatementsSnafu.fail();
+        };
+        Box::pin(self.drop_query(*statement)).await?;
+        Ok(())
+    }
+
+    /// Creates the target schema if it does not exist yet and clones every table of the
+    /// source schema into it, leaving out the tables created after the point in time of
+    /// the clone.
+    async fn clone_schema(
+        &self,
+        source: &ResolvedTableReference,
+        target: &ResolvedTableReference,
+        point: Option<(i64, bool)>,
+    ) -> Result<()> {
+        let create = self
+            .sql_to_df_statement(&format!(
+                "CREATE SCHEMA IF NOT EXISTS {}.{}",
+                target.catalog, target.schema
+            ))
+            .context(ex_error::DataFusionSnafu)?;
+        let DFStatement::Statement(create) = create else {
+            return ex_error::OnlyCreateSchemaStatementsSnafu.fail();
+        };
+        Box::pin(self.create_schema(*create)).await?;
+
+        let tables = self
+            .metastore
+            .list_tables(&MetastoreSchemaIdent::new(
+                source.catalog.to_string(),
+                source.schema.to_string(),
+            ))
+            .await
+            .context(ex_error::MetastoreSnafu)?;
+        for table in tables {
+            if point.is_some_and(|(timestamp_ms, _)| {
+                table.created_at.and_utc().timestamp_millis() > timestamp_ms
+            }) {
+                continue;
+            }
+            let table_ref = |schema: &ResolvedTableReference| ResolvedTableReference {
+                table: Arc::from(table.ident.table.as_str()),
+                ..schema.clone()
+            };
+            self.clone_table(&table_ref(source), &table_ref(target), &table, point)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Registers a clone of a table of the metastore, taken at the snapshot current at
+    /// `point` if given
+    async fn clone_table(
+        &self,
+        source: &ResolvedTableReference,
+        target: &ResolvedTableReference,
+        source_table: &MetastoreTable,
+        point: Option<(i64, bool)>,
+    ) -> Result<()> {
+        self.authorize(Privilege::Select, &table_grant_object(source))
+            .await?;
+        let ident = MetastoreTableIdent::new(&target.catalog, &target.schema, &target.table);
+        let mut table = cloned_table(source_table, &ident);
+        if let Some((timestamp_ms, before)) = point {
+            pin_snapshot(
+                &mut table.metadata,
+                snapshot_at(&source_table.metadata, timestamp_ms, before),
+            );
+        }
+        self.metastore
+            .register_table(&ident, table)
+            .await
+            .context(ex_error::MetastoreSnafu)?;
+        self.invalidate_table(target);
+        self.grant_ownership(table_grant_object(target)).await
+    }
+
+    /// Restores a dropped table, schema or database, along with everything it contained,
+    /// from the tombstone the metastore keeps until its retention time passes. The grants
+    /// on the object were revoked when it was dropped, so the current role owns it again.
+    #[instrument(
+        name = "UserQuery::execute_undrop_statement",
+        level = "trace",
+        skip(self),
+        err
+    )]
+    async fn execute_undrop_statement(&self, statement: UndropStatement) -> Result<QueryResult> {
+        self.running_queries.update_stats(
+            self.query_context.query_id,
+            &QueryStats::default().with_query_type(QueryType::Ddl(DdlStType::Undrop)),
+        );
+        // DDL statements implicitly commit the open transaction, as in Snowflake
+        self.commit_transaction().await?;
+
+        let grant_object = match statement.object_type {
+            ObjectType::Table => {
+                let table_ref =
+                    self.resolve_table_ref(&self.resolve_table_object_name(statement.name.0)?);
+                self.authorize(
+                    Privilege::CreateTable,
+                    &GrantObject::schema(&table_ref.catalog, &table_ref.schema),
+                )
+                .await?;
+                self.metastore
+                    .undrop(&DroppedObjectIdent::Table(MetastoreTableIdent::new(
+                        &table_ref.catalog,
+                        &table_ref.schema,
+                        &table_ref.table,
+                    )))
+                    .await
+                    .context(ex_error::MetastoreSnafu)?;
+                self.invalidate_table(&table_ref);
+                table_grant_object(&table_ref)
+            }
+            ObjectType::Schema => {
+                let schema_ref = self
+                    .schema_ref_to_resolved(self.resolve_schema_name(&statement.name.to_string())?);
+                self.authorize(
+                    Privilege::CreateSchema,
+                    &GrantObject::database(&schema_ref.catalog),
+                )
+                .await?;
+                self.metastore
+                    .undrop(&DroppedObjectIdent::Schema(MetastoreSchemaIdent::new(
+                        schema_ref.catalog.to_string(),
+                        schema_ref.schema.to_string(),
+                    )))
+                    .await
+                    .context(ex_error::MetastoreSnafu)?;
+                GrantObject::schema(&schema_ref.catalog, &schema_ref.schema)
+            }
+            _ => {
+                self.authorize_account().await?;
+                let database = object_name_to_string(&statement.name);
+                self.metastore
+                    .undrop(&DroppedObjectIdent::Database(database.clone()))
+                    .await
+                    .context(ex_error::MetastoreSnafu)?;
+                self.register_catalog(&database).await?;
+                GrantObject::database(&database)
+            }
+        };
+        self.grant_ownership(grant_object).await?;
+        self.status_response()
+    }
+
+    /// Rows of the tables of a database, or of one of its schemas, dropped within their
+    /// retention time, as `SELECT`s matching the columns of `SHOW TABLES HISTORY`
+    async fn dropped_tables_rows(
+        &self,
+        catalog: &str,
+        schema: Option<&str>,
+    ) -> Result<Vec<String>> {
+        let quote = |value: &str| format!("'{}'", value.replace('\'', "''"));
+        let mut rows = Vec::new();
+        for dropped in self
+            .metastore
+            .list_dropped_objects()
+            .await
+            .context(ex_error::MetastoreSnafu)?
+        {
+            let DroppedObjectIdent::Table(ident) = &dropped.ident else {
+                continue;
+            };
+            if !ident.database.eq_ignore_ascii_case(catalog)
+                || schema.is_some_and(|schema| !ident.schema.eq_ignore_ascii_case(schema))
+            {
+                continue;
+            }
+            for table in &dropped.tables {
+                rows.push(format!(
+                    "SELECT {}::TIMESTAMP, {}, 'TABLE', {}, {}, {}::TIMESTAMP",
+                    quote(&table.created_at.to_string()),
+                    quote(&table.ident.table),
+                    quote(&table.ident.database),
+                    quote(&table.ident.schema),
+                    quote(&dropped.dropped_at.to_string()),
+                ));
+            }
+        }
+        Ok(rows)
+    }
+
     #[allow(clippy::too_many_lines)]
     pub async fn show_query(&self, statement: Statement) -> Result<QueryResult> {
         let query = match statement {
@@ -1794,6 +5124,42 @@ impl UserQuery {
                 }
                 apply_show_filters(sql, &filters)
             }
+            // Dropped tables that can still be restored are listed along with the
+            // existing ones, with the time they were dropped
+            Statement::ShowTables {
+                history: true,
+                show_options,
+                ..
+            } => {
+                let reference =
+                    self.resolve_show_in_name(show_options.show_in, ShowType::Schema)?;
+                let catalog: String = reference
+                    .catalog()
+                    .map_or_else(|| self.current_database(), ToString::to_string);
+                let schema = reference.schema().filter(|s| !s.is_empty());
+                let mut sql = format!(
+                    "SELECT
+                        NULL::TIMESTAMP as created_on,
+                        table_name as name,
+                        table_type as kind,
+                        table_catalog as database_name,
+                        table_schema as schema_name,
+                        NULL::TIMESTAMP as dropped_on
+                    FROM {catalog}.information_schema.tables
+                    WHERE table_type = 'TABLE'"
+                );
+                for row in self.dropped_tables_rows(&catalog, schema).await? {
+                    let _ = write!(sql, " UNION ALL {row}");
+                }
+                let mut filters = Vec::new();
+                if let Some(filter) = build_starts_with_filter(show_options.starts_with, "name") {
+                    filters.push(filter);
+                }
+                if let Some(schema) = schema {
+                    filters.push(format!("schema_name = '{schema}'"));
+                }
+                apply_show_filters(format!("SELECT * FROM ({sql})"), &filters)
+            }
             Statement::ShowTables { show_options, .. } => {
                 let reference =
                     self.resolve_show_in_name(show_options.show_in, ShowType::Schema)?;
@@ -2044,12 +5410,18 @@ impl UserQuery {
         };
 
         let object_name = self.resolve_table_object_name(first_table.name.0)?;
+        self.authorize(
+            Privilege::Truncate,
+            &table_grant_object(&self.resolve_table_ref(&object_name)),
+        )
+        .await?;
         let mut query = self.session.query(
             format!(
                 "CREATE OR REPLACE TABLE {object_name} as (SELECT * FROM {object_name} WHERE FALSE)",
             ),
             QueryContext::default(),
         );
+        query.authorized = true;
         query.execute().await
     }
 
@@ -2245,13 +5617,26 @@ impl UserQuery {
         }
     }
 
-    pub async fn sql_statement_to_plan(&self, statement: Statement) -> Result<LogicalPlan> {
-        let tables = self
+    pub async fn sql_statement_to_plan(&self, mut statement: Statement) -> Result<LogicalPlan> {
+        let time_travel_tables = take_time_travel_tables(&mut statement)?;
+        let mut tables = self
             .table_references_for_statement(
                 &DFStatement::Statement(Box::new(statement.clone())),
                 &self.session.ctx.state(),
             )
             .await?;
+        for time_travel_table in time_travel_tables {
+            let placeholder = self.resolve_table_ref(TableReference::parse_str(
+                &time_travel_table.placeholder.to_string(),
+            ));
+            let table_ref = self.resolve_table_ref(TableReference::parse_str(
+                &time_travel_table.table.to_string(),
+            ));
+            let provider = self
+                .time_travel_table_provider(&table_ref, &time_travel_table.time_travel)
+                .await?;
+            tables.insert(placeholder, provider_as_source(provider));
+        }
         let ctx_provider = SessionContextProvider {
             state: &self.session.ctx.state(),
             tables,
@@ -2437,6 +5822,12 @@ impl UserQuery {
         err
     )]
     pub async fn execute_with_custom_plan(&self, query: &str) -> Result<QueryResult> {
+        let plan = self.custom_plan(query).await?;
+        self.execute_logical_plan(plan).await
+    }
+
+    /// Plans a query with the session parameters in place of their references
+    async fn custom_plan(&self, query: &str) -> Result<LogicalPlan> {
         let statement = self
             .sql_to_df_statement(query)
             .context(ex_error::DataFusionSnafu)?;
@@ -2470,7 +5861,7 @@ impl UserQuery {
             // The call to `with_param_values` replaces the parameter references with actual values.
             .with_param_values(session_params)
             .context(ex_error::DataFusionSnafu)?;
-        self.execute_logical_plan(plan).await
+        Ok(plan)
     }
 
     async fn execute_scalar_query(&self, query_str: &str) -> Result<ScalarValue> {
@@ -2481,34 +5872,194 @@ impl UserQuery {
         {
             return ex_error::UnexpectedSubqueryResultSnafu.fail();
         }
-
-        let column = res.records[0].column(0);
-        ScalarValue::try_from_array(column, 0).context(ex_error::DataFusionSnafu)
+
+        let column = res.records[0].column(0);
+        ScalarValue::try_from_array(column, 0).context(ex_error::DataFusionSnafu)
+    }
+
+    async fn table_references_for_statement(
+        &self,
+        statement: &DFStatement,
+        state: &SessionState,
+    ) -> Result<HashMap<ResolvedTableReference, Arc<dyn TableSource>>> {
+        let mut tables = HashMap::new();
+
+        let references = state
+            .resolve_table_references(statement)
+            .context(ex_error::DataFusionSnafu)?;
+        let write_target = self.write_target(statement)?;
+        for reference in references {
+            let resolved = self.resolve_table_ref(reference);
+            if let Entry::Vacant(v) = tables.entry(resolved.clone())
+                && let Ok(schema) = self.schema_for_ref(resolved.clone())
+                && let Some(table) = match schema
+                    .table(&resolved.table)
+                    .await
+                    .context(ex_error::DataFusionSnafu)?
+                {
+                    Some(table) => Some(table),
+                    // External tables are kept by the metastore rather than the catalogs
+                    None => self.external_table_provider(&resolved).await?,
+                }
+            {
+                let privilege = match &write_target {
+                    Some((target, privilege)) if *target == resolved => *privilege,
+                    _ => Some(Privilege::Select),
+                };
+                if let Some(privilege) = privilege
+                    && *resolved.schema != *INFORMATION_SCHEMA
+                {
+                    self.authorize(privilege, &table_grant_object(&resolved))
+                        .await?;
+                }
+                let table = self.transaction_table_provider(&resolved, table).await?;
+                v.insert(provider_as_source(table));
+            }
+        }
+        Ok(tables)
+    }
+
+    /// Provider of an Iceberg table reading the snapshot an `AT` or `BEFORE` clause
+    /// points to. A table that existed at that point but had no snapshot yet reads as
+    /// empty.
+    async fn time_travel_table_provider(
+        &self,
+        table_ref: &ResolvedTableReference,
+        time_travel: &TimeTravel,
+    ) -> Result<Arc<dyn TableProvider>> {
+        let table_name = table_ref.to_string();
+        let provider = self
+            .schema_for_ref(table_ref.clone())
+            .context(ex_error::DataFusionSnafu)?
+            .table(&table_ref.table)
+            .await
+            .context(ex_error::DataFusionSnafu)?
+            .ok_or_else(|| invalid_time_travel(&table_name, "table does not exist".to_string()))?;
+        self.authorize(Privilege::Select, &table_grant_object(table_ref))
+            .await?;
+
+        let table = provider
+            .as_any()
+            .downcast_ref::<CachingTable>()
+            .map_or(&provider, |caching_table| &caching_table.table)
+            .as_any()
+            .downcast_ref::<DataFusionTable>()
+            .ok_or_else(|| {
+                invalid_time_travel(
+                    &table_name,
+                    "time travel is only supported for Iceberg tables".to_string(),
+                )
+            })?;
+        #[allow(clippy::unwrap_used)]
+        let tabular = table.tabular.read().unwrap().clone();
+        let Tabular::Table(iceberg_table) = tabular else {
+            return Err(invalid_time_travel(
+                &table_name,
+                "time travel is only supported for Iceberg tables".to_string(),
+            ));
+        };
+
+        let timestamp_ms = self.time_travel_timestamp(&table_name, time_travel).await?;
+        if let Some(snapshot_id) =
+            snapshot_at(iceberg_table.metadata(), timestamp_ms, time_travel.before)
+        {
+            return Ok(Arc::new(DataFusionTable {
+                snapshot_range: (None, Some(snapshot_id)),
+                ..table.clone()
+            }));
+        }
+        // Without a snapshot the table either was empty or did not exist yet
+        let created_at = self
+            .metastore
+            .get_table(&MetastoreTableIdent::new(
+                &table_ref.catalog,
+                &table_ref.schema,
+                &table_ref.table,
+            ))
+            .await
+            .context(ex_error::MetastoreSnafu)?
+            .map(|table| table.created_at.and_utc().timestamp_millis());
+        if created_at.is_some_and(|created_at| created_at <= timestamp_ms) {
+            return Ok(Arc::new(EmptyTable::new(table.schema.clone())));
+        }
+        Err(not_created_yet(&table_name))
+    }
+
+    /// Milliseconds since the epoch a time travel clause points to, which can not be
+    /// in the future
+    async fn time_travel_timestamp(&self, table: &str, time_travel: &TimeTravel) -> Result<i64> {
+        let evaluate = |expr: &Expr, data_type: DataType| {
+            let query = format!("SELECT {expr}");
+            async move {
+                // Boxed, since planning the query resolves time travel clauses again
+                let value: std::pin::Pin<
+                    Box<dyn Future<Output = Result<ScalarValue>> + Send + '_>,
+                > = Box::pin(self.execute_scalar_query(&query));
+                value
+                    .await?
+                    .cast_to(&data_type)
+                    .context(ex_error::DataFusionSnafu)
+            }
+        };
+        let timestamp_ms = match &time_travel.point {
+            TimeTravelPoint::Timestamp(expr) => {
+                // Naive timestamps are taken as UTC, zoned ones keep their instant
+                let data_type = DataType::Timestamp(TimeUnit::Millisecond, Some("UTC".into()));
+                match evaluate(expr, data_type).await? {
+                    ScalarValue::TimestampMillisecond(Some(timestamp_ms), _) => Ok(timestamp_ms),
+                    _ => Err(invalid_time_travel(
+                        table,
+                        format!("invalid timestamp {expr}"),
+                    )),
+                }
+            }
+            TimeTravelPoint::Offset(expr) => match evaluate(expr, DataType::Int64).await? {
+                ScalarValue::Int64(Some(seconds)) => Ok(Utc::now()
+                    .timestamp_millis()
+                    .saturating_add(seconds.saturating_mul(1000))),
+                _ => Err(invalid_time_travel(table, format!("invalid offset {expr}"))),
+            },
+            TimeTravelPoint::Statement(expr) => {
+                let query_id = match evaluate(expr, DataType::Utf8).await? {
+                    ScalarValue::Utf8(Some(query_id)) => Uuid::parse_str(&query_id).ok(),
+                    _ => None,
+                }
+                .ok_or_else(|| invalid_time_travel(table, format!("invalid query id {expr}")))?;
+                let range = self.statement_time_range(query_id).await?.ok_or_else(|| {
+                    invalid_time_travel(table, format!("statement {query_id} is not known"))
+                })?;
+                let point = if time_travel.before {
+                    range.started_at
+                } else {
+                    range.finished_at.ok_or_else(|| {
+                        invalid_time_travel(table, format!("statement {query_id} has not finished"))
+                    })?
+                };
+                Ok(point.timestamp_millis())
+            }
+        }?;
+        if timestamp_ms > Utc::now().timestamp_millis() {
+            return Err(invalid_time_travel(
+                table,
+                "the requested time is in the future".to_string(),
+            ));
+        }
+        Ok(timestamp_ms)
     }
 
-    async fn table_references_for_statement(
-        &self,
-        statement: &DFStatement,
-        state: &SessionState,
-    ) -> Result<HashMap<ResolvedTableReference, Arc<dyn TableSource>>> {
-        let mut tables = HashMap::new();
-
-        let references = state
-            .resolve_table_references(statement)
-            .context(ex_error::DataFusionSnafu)?;
-        for reference in references {
-            let resolved = self.resolve_table_ref(reference);
-            if let Entry::Vacant(v) = tables.entry(resolved.clone())
-                && let Ok(schema) = self.schema_for_ref(resolved.clone())
-                && let Some(table) = schema
-                    .table(&resolved.table)
-                    .await
-                    .context(ex_error::DataFusionSnafu)?
-            {
-                v.insert(provider_as_source(table));
+    /// Start and end time of a statement. Running and recently finished statements are
+    /// known to the running queries, the others are looked up in the query history.
+    async fn statement_time_range(&self, query_id: QueryId) -> Result<Option<StatementTimeRange>> {
+        if let Some(range) = self.running_queries.time_range(query_id) {
+            return Ok(Some(range));
+        }
+        cfg_if::cfg_if! {
+            if #[cfg(feature = "state-store-query")] {
+                self.session.persisted_time_range(query_id).await
+            } else {
+                Ok(None)
             }
         }
-        Ok(tables)
     }
 
     /// Resolves a [`TableReference`] to a [`ResolvedTableReference`]
@@ -2679,16 +6230,22 @@ impl UserQuery {
     fn traverse_and_update_query<'a>(
         &'a self,
         query: &'a mut Query,
-    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = ()> + Send + 'a>> {
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<()>> + Send + 'a>> {
         Box::pin(async move {
             fn process_set_expr<'a>(
                 this: &'a UserQuery,
                 set_expr: &'a mut sqlparser::ast::SetExpr,
-            ) -> std::pin::Pin<Box<dyn Future<Output = ()> + Send + 'a>> {
+            ) -> std::pin::Pin<Box<dyn Future<Output = Result<()>> + Send + 'a>> {
                 Box::pin(async move {
                     match set_expr {
                         sqlparser::ast::SetExpr::Select(select) => {
                             for table_with_joins in &mut select.from {
+                                this.replace_infer_schema_function(&mut table_with_joins.relation)
+                                    .await?;
+                                for join in &mut table_with_joins.joins {
+                                    this.replace_infer_schema_function(&mut join.relation)
+                                        .await?;
+                                }
                                 if let TableFactor::Pivot {
                                     table,
                                     value_column,
@@ -2704,27 +6261,30 @@ impl UserQuery {
                                     .await;
                                 }
                             }
+                            this.expand_object_construct_wildcard(select).await?;
                         }
 
                         sqlparser::ast::SetExpr::Query(inner_query) => {
-                            this.traverse_and_update_query(inner_query).await;
+                            this.traverse_and_update_query(inner_query).await?;
                         }
                         sqlparser::ast::SetExpr::SetOperation { left, right, .. } => {
-                            process_set_expr(this, left).await;
-                            process_set_expr(this, right).await;
+                            process_set_expr(this, left).await?;
+                            process_set_expr(this, right).await?;
                         }
                         _ => {}
                     }
+                    Ok(())
                 })
             }
 
-            process_set_expr(self, &mut query.body).await;
+            process_set_expr(self, &mut query.body).await?;
 
             if let Some(with) = &mut query.with {
                 for cte in &mut with.cte_tables {
-                    self.traverse_and_update_query(&mut cte.query).await;
+                    self.traverse_and_update_query(&mut cte.query).await?;
                 }
             }
+            Ok(())
         })
     }
 
@@ -2822,84 +6382,622 @@ impl UserQuery {
                     ObjectNamePart::Identifier(Ident::new(self.current_schema())),
                 );
             }
-            2 => {
-                table_ident.insert(
-                    0,
-                    ObjectNamePart::Identifier(Ident::new(self.current_database())),
-                );
+            2 => {
+                table_ident.insert(
+                    0,
+                    ObjectNamePart::Identifier(Ident::new(self.current_database())),
+                );
+            }
+            3 => {}
+            _ => {
+                return ex_error::InvalidTableIdentifierSnafu {
+                    ident: table_ident
+                        .iter()
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                        .join("."),
+                }
+                .fail();
+            }
+        }
+
+        let normalized_idents = table_ident
+            .into_iter()
+            .map(|part| match part {
+                ObjectNamePart::Identifier(ident) => self.normalize_ident(ident),
+                ObjectNamePart::Function(_) => Ident::new(String::new()),
+            })
+            .filter(|ident| !ident.value.is_empty())
+            .collect();
+        Ok(NormalizedIdent(normalized_idents))
+    }
+
+    // Fill in the database if missing and normalize the identifiers for ObjectNamePart
+    pub fn resolve_schema_name(&self, ident: &str) -> Result<SchemaReference> {
+        let parts: Vec<String> = ident
+            .split('.')
+            .map(std::string::ToString::to_string)
+            .collect();
+        match parts.len() {
+            1 => Ok(SchemaReference::Full {
+                catalog: Arc::from(self.current_database()),
+                schema: Arc::from(
+                    self.normalize_ident(Ident::new(parts[0].clone()))
+                        .to_string(),
+                ),
+            }),
+            2 => Ok(SchemaReference::Full {
+                catalog: Arc::from(
+                    self.normalize_ident(Ident::new(parts[0].clone()))
+                        .to_string(),
+                ),
+                schema: Arc::from(
+                    self.normalize_ident(Ident::new(parts[1].clone()))
+                        .to_string(),
+                ),
+            }),
+            _ => ex_error::InvalidSchemaIdentifierSnafu { ident }.fail(),
+        }
+    }
+
+    fn normalize_ident(&self, ident: Ident) -> Ident {
+        match ident.quote_style {
+            Some(qs) => Ident::with_quote(qs, ident.value),
+            None => Ident::new(self.session.ident_normalizer.normalize(ident)),
+        }
+    }
+
+    pub fn created_entity_response(&self) -> Result<QueryResult> {
+        let schema = Arc::new(ArrowSchema::new(vec![Field::new(
+            "count",
+            DataType::Int64,
+            false,
+        )]));
+        Ok(QueryResult::new(
+            vec![
+                RecordBatch::try_new(schema.clone(), vec![Arc::new(Int64Array::from(vec![0]))])
+                    .context(ex_error::ArrowSnafu)?,
+            ],
+            schema,
+        ))
+    }
+
+    #[instrument(
+        name = "UserQuery::execute_user_statement",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn execute_user_statement(&self, statement: UserStatement) -> Result<QueryResult> {
+        let query_type = match &statement {
+            UserStatement::Create { .. } => DdlStType::CreateUser,
+            UserStatement::Alter { .. } => DdlStType::AlterUser,
+            UserStatement::Drop { .. } => DdlStType::DropUser,
+        };
+        self.running_queries.update_stats(
+            self.query_context.query_id,
+            &QueryStats::default().with_query_type(QueryType::Ddl(query_type)),
+        );
+        self.authorize_user_statement(&statement).await?;
+
+        match statement {
+            UserStatement::Create {
+                name,
+                or_replace,
+                if_not_exists,
+                properties,
+            } => {
+                let exists = self
+                    .metastore
+                    .get_user(&name)
+                    .await
+                    .context(ex_error::MetastoreSnafu)?
+                    .is_some();
+                let user = properties.apply(User::new(&name));
+                if exists && or_replace {
+                    self.metastore
+                        .update_user(&name, user)
+                        .await
+                        .context(ex_error::MetastoreSnafu)?;
+                } else if !(exists && if_not_exists) {
+                    self.metastore
+                        .create_user(&name, user)
+                        .await
+                        .context(ex_error::MetastoreSnafu)?;
+                }
+            }
+            UserStatement::Alter {
+                name,
+                if_exists,
+                action,
+            } => {
+                let user = self
+                    .metastore
+                    .get_user(&name)
+                    .await
+                    .context(ex_error::MetastoreSnafu)?;
+                match user {
+                    Some(user) => {
+                        self.metastore
+                            .update_user(&name, action.apply(user.data))
+                            .await
+                            .context(ex_error::MetastoreSnafu)?;
+                    }
+                    None if if_exists => {}
+                    None => {
+                        return Err(
+                            catalog_metastore::error::UserNotFoundSnafu { user: name }.build()
+                        )
+                        .context(ex_error::MetastoreSnafu);
+                    }
+                }
+            }
+            UserStatement::Drop { name, if_exists } => {
+                let exists = self
+                    .metastore
+                    .get_user(&name)
+                    .await
+                    .context(ex_error::MetastoreSnafu)?
+                    .is_some();
+                if exists || !if_exists {
+                    self.metastore
+                        .delete_user(&name)
+                        .await
+                        .context(ex_error::MetastoreSnafu)?;
+                }
+            }
+        }
+        self.status_response()
+    }
+
+    #[allow(clippy::too_many_lines)]
+    #[instrument(
+        name = "UserQuery::execute_role_statement",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn execute_role_statement(&self, statement: RoleStatement) -> Result<QueryResult> {
+        let query_type = match &statement {
+            RoleStatement::Create { .. } => DdlStType::CreateRole,
+            RoleStatement::Drop { .. } => DdlStType::DropRole,
+            RoleStatement::Grant { .. } | RoleStatement::GrantRole { .. } => DdlStType::Grant,
+            RoleStatement::Revoke { .. } | RoleStatement::RevokeRole { .. } => DdlStType::Revoke,
+        };
+        self.running_queries.update_stats(
+            self.query_context.query_id,
+            &QueryStats::default().with_query_type(QueryType::Ddl(query_type)),
+        );
+
+        match statement {
+            RoleStatement::Create {
+                name,
+                or_replace,
+                if_not_exists,
+                comment,
+            } => {
+                self.authorize_account().await?;
+                let exists = self
+                    .metastore
+                    .get_role(&name)
+                    .await
+                    .context(ex_error::MetastoreSnafu)?
+                    .is_some();
+                let role = Role {
+                    comment,
+                    ..Role::new(&name)
+                };
+                if exists && or_replace {
+                    self.metastore
+                        .update_role(&name, role)
+                        .await
+                        .context(ex_error::MetastoreSnafu)?;
+                } else if !(exists && if_not_exists) {
+                    self.metastore
+                        .create_role(&name, role)
+                        .await
+                        .context(ex_error::MetastoreSnafu)?;
+                }
+            }
+            RoleStatement::Drop { name, if_exists } => {
+                self.authorize_account().await?;
+                let exists = self
+                    .metastore
+                    .get_role(&name)
+                    .await
+                    .context(ex_error::MetastoreSnafu)?
+                    .is_some();
+                if exists || !if_exists {
+                    self.metastore
+                        .delete_role(&name)
+                        .await
+                        .context(ex_error::MetastoreSnafu)?;
+                }
+                // Users keep no references to the dropped role
+                for user in self
+                    .metastore
+                    .list_users()
+                    .await
+                    .context(ex_error::MetastoreSnafu)?
+                {
+                    let mut user = user.data;
+                    if user.roles.contains(&name) || user.default_role.as_ref() == Some(&name) {
+                        user.roles.retain(|role| *role != name);
+                        user.default_role = user.default_role.filter(|role| *role != name);
+                        self.metastore
+                            .update_user(&user.name.clone(), user)
+                            .await
+                            .context(ex_error::MetastoreSnafu)?;
+                    }
+                }
+            }
+            RoleStatement::Grant {
+                privileges,
+                on,
+                role,
+            } => {
+                let on = self.resolve_grant_on(on).await?;
+                self.authorize(Privilege::Ownership, &on).await?;
+                let mut role = self.load_role(&role).await?;
+                for privilege in privileges {
+                    role.grant(privilege, on.clone());
+                }
+                self.metastore
+                    .update_role(&role.name.clone(), role)
+                    .await
+                    .context(ex_error::MetastoreSnafu)?;
+            }
+            RoleStatement::Revoke {
+                privileges,
+                on,
+                role,
+            } => {
+                let on = self.resolve_grant_on(on).await?;
+                self.authorize(Privilege::Ownership, &on).await?;
+                let mut role = self.load_role(&role).await?;
+                for privilege in privileges {
+                    role.revoke(privilege, &on);
+                }
+                self.metastore
+                    .update_role(&role.name.clone(), role)
+                    .await
+                    .context(ex_error::MetastoreSnafu)?;
+            }
+            RoleStatement::GrantRole { role, user } => {
+                self.authorize_account().await?;
+                let role = self.load_role(&role).await?;
+                let mut user = self.load_user(&user).await?;
+                if !user.has_role(&role.name) {
+                    user.roles.push(role.name);
+                }
+                self.metastore
+                    .update_user(&user.name.clone(), user)
+                    .await
+                    .context(ex_error::MetastoreSnafu)?;
+            }
+            RoleStatement::RevokeRole { role, user } => {
+                self.authorize_account().await?;
+                let mut user = self.load_user(&user).await?;
+                user.roles.retain(|granted| *granted != role);
+                self.metastore
+                    .update_user(&user.name.clone(), user)
+                    .await
+                    .context(ex_error::MetastoreSnafu)?;
+            }
+        }
+        self.status_response()
+    }
+
+    /// Loads a role for an update, the `PUBLIC` role is created on first use
+    async fn load_role(&self, name: &RoleIdent) -> Result<Role> {
+        match self
+            .metastore
+            .get_role(name)
+            .await
+            .context(ex_error::MetastoreSnafu)?
+        {
+            Some(role) => Ok(role.data),
+            None if name == PUBLIC_ROLE => Ok(self
+                .metastore
+                .create_role(name, Role::new(PUBLIC_ROLE))
+                .await
+                .context(ex_error::MetastoreSnafu)?
+                .data),
+            None => Err(catalog_metastore::error::RoleNotFoundSnafu { role: name }.build())
+                .context(ex_error::MetastoreSnafu),
+        }
+    }
+
+    async fn load_user(&self, name: &str) -> Result<User> {
+        match self
+            .metastore
+            .get_user(&name.to_string())
+            .await
+            .context(ex_error::MetastoreSnafu)?
+        {
+            Some(user) => Ok(user.data),
+            None => Err(catalog_metastore::error::UserNotFoundSnafu { user: name }.build())
+                .context(ex_error::MetastoreSnafu),
+        }
+    }
+
+    /// Resolves the object of a `GRANT` or `REVOKE` against the current database and
+    /// schema, the object must exist
+    async fn resolve_grant_on(&self, on: GrantOn) -> Result<GrantObject> {
+        match on {
+            GrantOn::Database(name) => {
+                let database = object_name_to_string(&name).to_ascii_lowercase();
+                self.get_catalog(&database)?;
+                Ok(GrantObject::database(&database))
+            }
+            GrantOn::Schema(name) => {
+                let schema_ref =
+                    self.schema_ref_to_resolved(self.resolve_schema_name(&name.to_string())?);
+                let schema_ref = normalize_resolved_ref(&schema_ref);
+                self.get_catalog(&schema_ref.catalog)?
+                    .schema(&schema_ref.schema)
+                    .context(ex_error::SchemaNotFoundInDatabaseSnafu {
+                        operation_on: OperationOn::Schema(OperationType::Unknown),
+                        schema: schema_ref.schema.to_string(),
+                        db: schema_ref.catalog.to_string(),
+                    })?;
+                Ok(GrantObject::schema(&schema_ref.catalog, &schema_ref.schema))
+            }
+            GrantOn::Table(name) => {
+                let ident = self.resolve_table_object_name(name.0)?;
+                let table_ref = self.resolve_table_ref(&ident);
+                if !self.table_exists(&table_ref) {
+                    return ex_error::TableNotFoundInSchemaInDatabaseSnafu {
+                        operation_on: OperationOn::Table(OperationType::Unknown),
+                        table: table_ref.table.to_string(),
+                        schema: table_ref.schema.to_string(),
+                        db: table_ref.catalog.to_string(),
+                    }
+                    .fail();
+                }
+                Ok(table_grant_object(&table_ref))
+            }
+            GrantOn::Stage(name) => {
+                let ident = self.resolve_stage_ident(&name)?;
+                self.metastore
+                    .get_stage(&ident)
+                    .await
+                    .context(ex_error::MetastoreSnafu)?
+                    .context(ex_error::StageNotFoundSnafu {
+                        stage: ident.to_string(),
+                    })?;
+                Ok(stage_grant_object(&ident))
+            }
+        }
+    }
+
+    fn table_exists(&self, table: &ResolvedTableReference) -> bool {
+        self.schema_for_ref(table.clone())
+            .is_ok_and(|schema| schema.table_exist(&table.table))
+    }
+
+    async fn current_user(&self) -> Result<Option<User>> {
+        let Some(name) = self
+            .query_context
+            .session_metadata
+            .as_ref()
+            .and_then(|metadata| metadata.attr(SessionMetadataAttr::UserName))
+        else {
+            return Ok(None);
+        };
+        Ok(self
+            .metastore
+            .get_user(&User::normalize_name(&name))
+            .await
+            .context(ex_error::MetastoreSnafu)?
+            .map(|user| user.data))
+    }
+
+    /// Role the query runs with, `None` when the query is not restricted.
+    ///
+    /// Sessions of the users kept in the metastore start with the default role of the
+    /// user and internal sessions run as `ACCOUNTADMIN` until a `USE ROLE` statement.
+    /// Any other session, such as a demo login, runs as `PUBLIC`.
+    async fn current_role(&self) -> Result<Option<RoleIdent>> {
+        if self.authorized {
+            return Ok(None);
+        }
+        let role = match self.session.role() {
+            Some(role) => role,
+            None if self.session.is_internal() => return Ok(None),
+            None => self
+                .current_user()
+                .await?
+                .and_then(|user| user.default_role)
+                .unwrap_or_else(|| PUBLIC_ROLE.to_string()),
+        };
+        Ok((role != ACCOUNTADMIN_ROLE).then_some(role))
+    }
+
+    /// `USE ROLE` only switches to existing roles, store users also need the role
+    /// granted. Sessions without a store user can only use `PUBLIC`, unless internal.
+    async fn use_role(&self, role: &RoleIdent) -> Result<()> {
+        let exists = role == ACCOUNTADMIN_ROLE
+            || role == PUBLIC_ROLE
+            || self
+                .metastore
+                .get_role(role)
+                .await
+                .context(ex_error::MetastoreSnafu)?
+                .is_some();
+        let granted = match self.current_user().await? {
+            Some(user) => user.has_role(role),
+            None => self.session.is_internal() || role == PUBLIC_ROLE,
+        };
+        if !exists || !granted {
+            return ex_error::RoleNotAssignedSnafu { role }.fail();
+        }
+        Ok(())
+    }
+
+    /// Checks that the current role, or the `PUBLIC` role, has the privilege on the object.
+    /// Access to a schema also needs `USAGE` on its database, and access to a table
+    /// `USAGE` on both its database and schema.
+    async fn authorize(&self, privilege: Privilege, on: &GrantObject) -> Result<()> {
+        let Some(role) = self.current_role().await? else {
+            return Ok(());
+        };
+        let mut roles = Vec::new();
+        for name in [role, PUBLIC_ROLE.to_string()] {
+            if let Some(role) = self
+                .metastore
+                .get_role(&name)
+                .await
+                .context(ex_error::MetastoreSnafu)?
+            {
+                roles.push(role.data);
+            }
+        }
+        let mut required = match on {
+            GrantObject::Database { .. } => vec![],
+            GrantObject::Schema(schema) => {
+                vec![(Privilege::Usage, GrantObject::database(&schema.database))]
             }
-            3 => {}
-            _ => {
-                return ex_error::InvalidTableIdentifierSnafu {
-                    ident: table_ident
-                        .iter()
-                        .map(ToString::to_string)
-                        .collect::<Vec<_>>()
-                        .join("."),
+            GrantObject::Stage(stage) => vec![
+                (Privilege::Usage, GrantObject::database(&stage.database)),
+                (
+                    Privilege::Usage,
+                    GrantObject::schema(&stage.database, &stage.schema),
+                ),
+            ],
+            GrantObject::Table(table) => vec![
+                (Privilege::Usage, GrantObject::database(&table.database)),
+                (
+                    Privilege::Usage,
+                    GrantObject::schema(&table.database, &table.schema),
+                ),
+            ],
+        };
+        required.push((privilege, on.clone()));
+        for (privilege, on) in required {
+            if !roles.iter().any(|role| role.allows(privilege, &on)) {
+                return ex_error::InsufficientPrivilegesSnafu {
+                    object_type: on.object_type(),
+                    object_name: on.to_string(),
                 }
                 .fail();
             }
         }
+        Ok(())
+    }
 
-        let normalized_idents = table_ident
-            .into_iter()
-            .map(|part| match part {
-                ObjectNamePart::Identifier(ident) => self.normalize_ident(ident),
-                ObjectNamePart::Function(_) => Ident::new(String::new()),
-            })
-            .filter(|ident| !ident.value.is_empty())
-            .collect();
-        Ok(NormalizedIdent(normalized_idents))
+    /// Account level statements, such as creating databases, volumes, users and roles,
+    /// are only allowed to `ACCOUNTADMIN`
+    async fn authorize_account(&self) -> Result<()> {
+        if self.current_role().await?.is_some() {
+            let account = self
+                .query_context
+                .session_metadata
+                .as_ref()
+                .and_then(|metadata| metadata.attr(SessionMetadataAttr::AccountName))
+                .unwrap_or_else(|| "embucket".to_string());
+            return ex_error::InsufficientPrivilegesSnafu {
+                object_type: "account",
+                object_name: account,
+            }
+            .fail();
+        }
+        Ok(())
     }
 
-    // Fill in the database if missing and normalize the identifiers for ObjectNamePart
-    pub fn resolve_schema_name(&self, ident: &str) -> Result<SchemaReference> {
-        let parts: Vec<String> = ident
-            .split('.')
-            .map(std::string::ToString::to_string)
-            .collect();
-        match parts.len() {
-            1 => Ok(SchemaReference::Full {
-                catalog: Arc::from(self.current_database()),
-                schema: Arc::from(
-                    self.normalize_ident(Ident::new(parts[0].clone()))
-                        .to_string(),
-                ),
-            }),
-            2 => Ok(SchemaReference::Full {
-                catalog: Arc::from(
-                    self.normalize_ident(Ident::new(parts[0].clone()))
-                        .to_string(),
-                ),
-                schema: Arc::from(
-                    self.normalize_ident(Ident::new(parts[1].clone()))
-                        .to_string(),
-                ),
-            }),
-            _ => ex_error::InvalidSchemaIdentifierSnafu { ident }.fail(),
+    /// Users are managed by `ACCOUNTADMIN`, users themselves can only change their own
+    /// credentials and comment
+    async fn authorize_user_statement(&self, statement: &UserStatement) -> Result<()> {
+        if let UserStatement::Alter {
+            name,
+            action: AlterUserAction::Set(properties),
+            ..
+        } = statement
+            && properties.default_role.is_none()
+            && properties.disabled.is_none()
+            && self
+                .current_user()
+                .await?
+                .is_some_and(|user| &user.name == name)
+        {
+            return Ok(());
         }
+        self.authorize_account().await
     }
 
-    fn normalize_ident(&self, ident: Ident) -> Ident {
-        match ident.quote_style {
-            Some(qs) => Ident::with_quote(qs, ident.value),
-            None => Ident::new(self.session.ident_normalizer.normalize(ident)),
+    /// Makes the current role the owner of a newly created object
+    async fn grant_ownership(&self, on: GrantObject) -> Result<()> {
+        let Some(role) = self.current_role().await? else {
+            return Ok(());
+        };
+        let mut role = self.load_role(&role).await?;
+        role.grant(Privilege::Ownership, on);
+        self.metastore
+            .update_role(&role.name.clone(), role)
+            .await
+            .context(ex_error::MetastoreSnafu)?;
+        Ok(())
+    }
+
+    /// Drops the grants on a dropped object and on the objects it contained
+    async fn revoke_grants_on(&self, on: &GrantObject) -> Result<()> {
+        for role in self
+            .metastore
+            .list_roles()
+            .await
+            .context(ex_error::MetastoreSnafu)?
+        {
+            let mut role = role.data;
+            let grants = role.grants.len();
+            role.revoke_all_on(on);
+            if role.grants.len() != grants {
+                self.metastore
+                    .update_role(&role.name.clone(), role)
+                    .await
+                    .context(ex_error::MetastoreSnafu)?;
+            }
         }
+        Ok(())
     }
 
-    pub fn created_entity_response(&self) -> Result<QueryResult> {
-        let schema = Arc::new(ArrowSchema::new(vec![Field::new(
-            "count",
-            DataType::Int64,
-            false,
-        )]));
-        Ok(QueryResult::new(
-            vec![
-                RecordBatch::try_new(schema.clone(), vec![Arc::new(Int64Array::from(vec![0]))])
-                    .context(ex_error::ArrowSnafu)?,
-            ],
-            schema,
-        ))
+    /// Table the statement writes to, with the privilege the write needs. Tables that
+    /// are created or dropped are authorized by the statement itself.
+    fn write_target(
+        &self,
+        statement: &DFStatement,
+    ) -> Result<Option<(ResolvedTableReference, Option<Privilege>)>> {
+        let DFStatement::Statement(statement) = statement else {
+            return Ok(None);
+        };
+        let (name, privilege) = match statement.as_ref() {
+            Statement::Insert(Insert {
+                table: TableObject::TableName(name),
+                ..
+            }) => (name, Some(Privilege::Insert)),
+            Statement::Update {
+                table:
+                    TableWithJoins {
+                        relation: TableFactor::Table { name, .. },
+                        ..
+                    },
+                ..
+            } => (name, Some(Privilege::Update)),
+            Statement::Delete(Delete {
+                from: FromTable::WithFromKeyword(from) | FromTable::WithoutKeyword(from),
+                ..
+            }) => match from.first().map(|table| &table.relation) {
+                Some(TableFactor::Table { name, .. }) => (name, Some(Privilege::Delete)),
+                _ => return Ok(None),
+            },
+            Statement::CreateTable(create_table) => (&create_table.name, None),
+            Statement::Drop { names, .. } => match names.first() {
+                Some(name) => (name, None),
+                None => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        let ident = self.resolve_table_object_name(name.0.clone())?;
+        Ok(Some((self.resolve_table_ref(&ident), privilege)))
     }
 
     pub fn status_response(&self) -> Result<QueryResult> {
@@ -2992,7 +7090,8 @@ impl UserQuery {
             })?
             .table
             .clone();
-        Ok(target_provider)
+        self.transaction_table_provider(&self.resolve_table_ref(target_ident), target_provider)
+            .await
     }
 
     async fn get_object_store_from_stage_params(
@@ -3000,107 +7099,110 @@ impl UserQuery {
         stage_params: StageParamsObject,
         url: &ListingTableUrl,
     ) -> Result<Arc<dyn ObjectStore + 'static>> {
-        match (&stage_params.storage_integration, &stage_params.credentials) {
-            (Some(volume), _) => {
-                let volume = self
-                    .metastore
-                    .get_volume(volume)
-                    .await
-                    .context(ex_error::MetastoreSnafu)?
-                    .context(ex_error::VolumeNotFoundSnafu { volume })?;
-                Ok(volume
-                    .get_object_store()
-                    .context(ex_error::MetastoreSnafu)?)
-            }
-            (None, credentials) if !credentials.options.is_empty() => {
-                // Create object store from credentials
-                let access_key_id = get_kv_option(credentials, "AWS_KEY_ID");
-                let secret_access_key = get_kv_option(credentials, "AWS_SECRET_KEY");
-                let session_token = get_kv_option(credentials, "AWS_SESSION_TOKEN");
-
-                if let (Some(access_key), Some(secret_key)) = (access_key_id, secret_access_key) {
-                    let object_store_url = url.object_store();
-                    let bucket = object_store_url
-                        .as_str()
-                        .trim_start_matches("s3://")
-                        .trim_end_matches('/');
-
-                    let region = resolve_bucket_region(bucket, &ClientOptions::default())
-                        .await
-                        .context(ex_error::ObjectStoreSnafu)?;
+        if let Some(volume) = &stage_params.storage_integration {
+            let volume = self
+                .metastore
+                .get_volume(volume)
+                .await
+                .context(ex_error::MetastoreSnafu)?
+                .context(ex_error::VolumeNotFoundSnafu { volume })?;
+            return volume.get_object_store().context(ex_error::MetastoreSnafu);
+        }
+        self.object_store_for_url(
+            url.as_ref(),
+            stage_params.endpoint,
+            &key_value_options_map(&stage_params.credentials),
+        )
+        .await
+    }
 
-                    let credentials = if let Some(token) = session_token {
-                        Some(AwsCredentials::Token(token.to_string()))
-                    } else {
-                        Some(AwsCredentials::AccessKey(AwsAccessKeyCredentials {
-                            aws_access_key_id: access_key.to_string(),
-                            aws_secret_access_key: secret_key.to_string(),
-                            aws_session_token: None,
-                        }))
-                    };
+    /// Object store for an external url, using the `AWS_KEY_ID` and `AWS_SECRET_KEY`
+    /// (or `AWS_SESSION_TOKEN`) credentials if given
+    async fn object_store_for_url(
+        &self,
+        url: &Url,
+        endpoint: Option<String>,
+        credentials: &BTreeMap<String, String>,
+    ) -> Result<Arc<dyn ObjectStore + 'static>> {
+        let access_key_id = credentials.get("AWS_KEY_ID");
+        let secret_access_key = credentials.get("AWS_SECRET_KEY");
+        let session_token = credentials.get("AWS_SESSION_TOKEN");
 
-                    let s3_volume = S3Volume {
-                        region: Some(region),
-                        bucket: Some(bucket.to_string()),
-                        endpoint: stage_params.endpoint.clone(),
-                        credentials,
-                        client_options: None, // could set timeouts here
-                    }
-                    .with_client_options(self.metastore.settings_config().map(Into::into));
+        if let (Some(access_key), Some(secret_key)) = (access_key_id, secret_access_key) {
+            let bucket = url.host_str().unwrap_or_default();
 
-                    let s3 = s3_volume
-                        .get_s3_builder()
-                        .build()
-                        .context(ex_error::ObjectStoreSnafu)?;
-                    Ok(Arc::new(s3))
-                } else {
-                    // Fall through to URL-based object store creation
-                    Ok(create_object_store_from_url(
-                        url.as_str(),
-                        stage_params.endpoint,
-                        self.metastore
-                            .settings_config()
-                            .map(|config| config.object_store_client_options),
-                    )
-                    .await
-                    .context(ex_error::MetastoreSnafu)?)
-                }
+            let region = resolve_bucket_region(bucket, &ClientOptions::default())
+                .await
+                .context(ex_error::ObjectStoreSnafu)?;
+
+            let credentials = if let Some(token) = session_token {
+                Some(AwsCredentials::Token(token.clone()))
+            } else {
+                Some(AwsCredentials::AccessKey(AwsAccessKeyCredentials {
+                    aws_access_key_id: access_key.clone(),
+                    aws_secret_access_key: secret_key.clone(),
+                    aws_session_token: None,
+                }))
+            };
+
+            let s3_volume = S3Volume {
+                region: Some(region),
+                bucket: Some(bucket.to_string()),
+                endpoint,
+                credentials,
+                client_options: None, // could set timeouts here
             }
-            // No stage params or credentials - create from URL
-            _ => create_object_store_from_url(
+            .with_client_options(self.metastore.settings_config().map(Into::into));
+
+            let s3 = s3_volume
+                .get_s3_builder()
+                .build()
+                .context(ex_error::ObjectStoreSnafu)?;
+            Ok(Arc::new(s3))
+        } else {
+            create_object_store_from_url(
                 url.as_str(),
-                stage_params.endpoint,
+                endpoint,
                 self.metastore
                     .settings_config()
                     .map(|config| config.object_store_client_options),
             )
             .await
-            .context(ex_error::MetastoreSnafu),
+            .context(ex_error::MetastoreSnafu)
         }
     }
 
     async fn build_listing_table_config(
         &self,
-        file_format: KeyValueOptions,
+        format: &FileFormatOptions,
         into_provider: &Arc<dyn TableProvider>,
-        url: ListingTableUrl,
+        urls: Vec<ListingTableUrl>,
     ) -> Result<ListingTableConfig> {
-        let config = ListingTableConfig::new(url.clone());
-        let config = if let Some((format, infer_schema)) = create_file_format(&file_format)? {
-            let options = ListingOptions::new(format);
-            let schema = if infer_schema {
-                options
-                    .infer_schema(&self.session.ctx.state(), &url)
-                    .await
-                    .context(ex_error::DataFusionSnafu)?
-            } else {
-                into_provider.schema()
-            };
-            config.with_listing_options(options).with_schema(schema)
-        } else {
-            config
+        let first_url = urls.first().cloned();
+        let config = ListingTableConfig::new_with_multi_paths(urls);
+        let path = first_url.as_ref().map_or("", |url| url.prefix().as_ref());
+        // Unlike DataFusion, Snowflake loads files regardless of their extension
+        let options = ListingOptions::new(format.listing_format(path)?).with_file_extension("");
+        let schema = match (format.infers_schema(), first_url) {
+            (true, Some(url)) => options
+                .infer_schema(&self.session.ctx.state(), &url)
+                .await
+                .context(ex_error::DataFusionSnafu)?,
+            _ => into_provider.schema(),
+        };
+        // CSV values are read as text and converted afterwards, so that a value that
+        // cannot be converted fails its row rather than the whole file
+        let schema = match format {
+            FileFormatOptions::Csv(_) => Arc::new(ArrowSchema::new(
+                schema
+                    .fields()
+                    .iter()
+                    .map(|field| Field::new(field.name(), DataType::Utf8, true))
+                    .collect::<Vec<_>>(),
+            )),
+            _ => schema,
         };
-        Ok(config)
+        Ok(config.with_listing_options(options).with_schema(schema))
     }
 }
 
@@ -3364,59 +7466,98 @@ fn merge_clause_expression(merge_clause: &MergeClause) -> Result<DFExpr> {
 /// Constructs a filter expression for the target table based on the partition column bounds
 /// of the source table.
 ///
-/// This function analyzes the partition structure of the source table and creates
-/// a `DataFusion` expression that filters data based on the minimum and maximum
-/// values of the partition columns. This is used for query optimization to prune
-/// partitions that don't contain relevant data.
+/// The filter is pushed into the target scan, where it only prunes the data files that
+/// can't contain rows matching the source, so it must be constant per data file.
 ///
 /// # Arguments
 ///
-/// * `table` - The `DataFusion` table wrapper for the Iceberg table
+/// * `source` - The `DataFusion` table wrapper for the Iceberg source table
+/// * `target` - The `DataFusion` table wrapper for the Iceberg target table
+/// * `on` - The predicate joining the target with the source
+/// * `source_schema` - The schema of the source plan the predicate is planned against
+/// * `target_schema` - The schema of the target plan the predicate is planned against
 ///
 /// # Returns
 ///
-/// * `Ok(Some(expr))` - A filter expression combining bounds for all partition columns
-/// * `Ok(None)` - If no snapshot exists, no partition bounds available, or no partition fields
+/// * `Ok(Some(expr))` - A filter expression combining bounds for all bounded target columns
+/// * `Ok(None)` - If the source has no identity partition bounds or no column can be bounded
 /// * `Err` - If there's an error accessing table metadata or building the expression
 ///
 /// # Behavior
 ///
-/// The function creates range conditions (column >= min AND column <= max) for each
-/// partition column and combines them with AND operators. Only works with Iceberg tables;
+/// A target column gets range conditions (column >= min AND column <= max) from the bounds
+/// of a source partition column only when the predicate requires the two to be equal and
+/// every partition spec of the target partitions by the column itself (identity transform).
+/// The conditions are combined with AND operators. Only works with Iceberg tables;
 /// returns an error for other table types.
 async fn target_filter_expression(
-    table: &DataFusionTable,
+    source: &DataFusionTable,
+    target: &DataFusionTable,
+    on: &datafusion_expr::Expr,
+    source_schema: &DFSchema,
+    target_schema: &DFSchema,
 ) -> Result<Option<datafusion_expr::Expr>> {
-    #[allow(clippy::unwrap_used)]
-    let value = table.tabular.read().unwrap().clone();
-    let table = match value {
-        Tabular::Table(table) => Ok(table),
-        _ => MergeSourceNotSupportedSnafu.fail(),
-    }?;
-    let object_store = table.object_store();
-    let table_metadata = table.metadata();
-    let Some(current_snapshot) = table_metadata
-        .current_snapshot(None)
-        .map_err(IcebergError::from)
-        .context(ex_error::IcebergSnafu)?
-    else {
+    // (target column, source column) pairs the predicate requires to be equal
+    let equal_columns = split_conjunction(on)
+        .into_iter()
+        .filter_map(|expr| match expr {
+            datafusion_expr::Expr::BinaryExpr(BinaryExpr {
+                left,
+                op: Operator::Eq,
+                right,
+            }) => match (left.as_ref(), right.as_ref()) {
+                (datafusion_expr::Expr::Column(left), datafusion_expr::Expr::Column(right)) => {
+                    if target_schema.has_column(left) && source_schema.has_column(right) {
+                        Some((left.name.clone(), right.name.clone()))
+                    } else if target_schema.has_column(right) && source_schema.has_column(left) {
+                        Some((right.name.clone(), left.name.clone()))
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if equal_columns.is_empty() {
         return Ok(None);
-    };
-    let Some(partition_column_bounds) =
-        partition_column_bounds(current_snapshot, table_metadata, object_store).await?
-    else {
+    }
+
+    let source_bounds = partition_column_bounds(&iceberg_table(source)?).await?;
+    if source_bounds.is_empty() {
         return Ok(None);
-    };
-    let partition_fields = table
-        .metadata()
-        .partition_fields(*current_snapshot.snapshot_id())
+    }
+
+    let target = iceberg_table(target)?;
+    let target_metadata = target.metadata();
+    let target_fields = target_metadata
+        .current_schema(None)
         .map_err(IcebergError::from)
-        .context(ex_error::IcebergSnafu)?;
-    let expr = partition_fields.iter().zip(partition_column_bounds).fold(
-        None,
-        |acc, (column, [min, max])| {
-            let column_expr = col(column.source_name());
-            let expr = and(
+        .context(ex_error::IcebergSnafu)?
+        .fields();
+    let identity_partitioned = |column: &str| {
+        target_fields
+            .iter()
+            .find(|field| field.name == column)
+            .is_some_and(|field| {
+                target_metadata.partition_specs.values().all(|spec| {
+                    spec.fields().iter().any(|partition_field| {
+                        *partition_field.source_id() == field.id
+                            && *partition_field.transform() == Transform::Identity
+                    })
+                })
+            })
+    };
+
+    let expr = equal_columns
+        .into_iter()
+        .filter(|(target_column, _)| identity_partitioned(target_column))
+        .filter_map(|(target_column, source_column)| {
+            let [min, max] = source_bounds.get(&source_column)?.clone();
+            let (min, max) = (lit(min), lit(max));
+            let column_expr = datafusion_expr::Expr::Column(Column::new_unqualified(target_column));
+            Some(and(
                 datafusion_expr::Expr::BinaryExpr(BinaryExpr::new(
                     Box::new(column_expr.clone()),
                     Operator::GtEq,
@@ -3427,83 +7568,163 @@ async fn target_filter_expression(
                     Operator::LtEq,
                     Box::new(max),
                 )),
-            );
-            if let Some(acc) = acc {
-                Some(and(acc, expr))
-            } else {
-                Some(expr)
-            }
-        },
-    );
+            ))
+        })
+        .reduce(and);
     Ok(expr)
 }
 
-/// Retrieves partition column bounds from an Iceberg table snapshot.
+/// Returns the Iceberg table behind the `DataFusion` table wrapper, or an error for
+/// other table types.
+fn iceberg_table(table: &DataFusionTable) -> Result<IcebergTable> {
+    #[allow(clippy::unwrap_used)]
+    let value = table.tabular.read().unwrap().clone();
+    match value {
+        Tabular::Table(table) => Ok(table),
+        _ => MergeSourceNotSupportedSnafu.fail(),
+    }
+}
+
+/// Adds a filter to the table scan of the plan. The scan only uses it to prune the data
+/// files it reads, so the filter must be constant per data file.
+fn with_scan_filter(plan: LogicalPlan, filter: datafusion_expr::Expr) -> Result<LogicalPlan> {
+    plan.transform_up(|plan| match plan {
+        LogicalPlan::TableScan(mut scan) => {
+            scan.filters.push(filter.clone());
+            Ok(Transformed::yes(LogicalPlan::TableScan(scan)))
+        }
+        plan => Ok(Transformed::no(plan)),
+    })
+    .map(|transformed| transformed.data)
+    .context(ex_error::DataFusionSnafu)
+}
+
+/// Retrieves the bounds of the identity partition columns of an Iceberg table.
 ///
-/// This function extracts the minimum and maximum bounds for partition columns
-/// from the given snapshot and converts them into `DataFusion` expressions for
-/// query optimization.
+/// The bounds come from the partition summaries of the manifests of the current
+/// snapshot. All data files of a manifest are written with the same partition spec,
+/// which may be an older one than the default spec, so only the identity fields of
+/// that spec are used: bucket, truncate and time transforms hold transformed values
+/// that can't be compared with the column.
 ///
 /// # Arguments
 ///
-/// * `current_snapshot` - The Iceberg table snapshot to extract bounds from
-/// * `table_metadata` - Metadata about the table structure and partitioning
-/// * `object_store` - Object store for accessing partition metadata
+/// * `table` - The Iceberg table to extract bounds from
 ///
 /// # Returns
 ///
-/// * `Ok(Some(bounds))` - Vector of min/max expression pairs for each partition column
-/// * `Ok(None)` - If no partition bounds are available or bounds are empty
-/// * `Err` - If there's an error accessing snapshot or converting values
+/// * `Ok(bounds)` - Min/max values by column, for the columns every manifest has bounds for
+/// * `Err` - If there's an error reading the manifest list or converting values
 async fn partition_column_bounds(
-    current_snapshot: &Snapshot,
-    table_metadata: &TableMetadata,
-    object_store: Arc<dyn ObjectStore>,
-) -> Result<Option<Vec<[datafusion_expr::Expr; 2]>>> {
-    let Some(bounds) = snapshot_partition_bounds(current_snapshot, table_metadata, object_store)
+    table: &IcebergTable,
+) -> Result<HashMap<String, [ScalarValue; 2]>> {
+    let table_metadata = table.metadata();
+    let schema = table_metadata
+        .current_schema(None)
+        .map_err(IcebergError::from)
+        .context(ex_error::IcebergSnafu)?;
+    let manifests = table
+        .manifests(None, None)
         .await
-        .context(ex_error::IcebergSnafu)?
-    else {
-        return Ok(None);
-    };
-    let bounds = bounds
-        .min
-        .iter()
-        .zip(bounds.max.iter())
-        .map(|(min, max)| Ok([value_to_literal(min)?, value_to_literal(max)?]))
-        .collect::<Result<Vec<_>>>()?;
-    if bounds.is_empty() {
-        Ok(None)
-    } else {
-        Ok(Some(bounds))
+        .context(ex_error::IcebergSnafu)?;
+
+    let mut bounds: Option<HashMap<String, [ScalarValue; 2]>> = None;
+    for manifest in manifests {
+        let Some(spec) = table_metadata
+            .partition_specs
+            .get(&manifest.partition_spec_id)
+        else {
+            return Ok(HashMap::new());
+        };
+        let mut manifest_bounds = HashMap::new();
+        for (field, summary) in spec
+            .fields()
+            .iter()
+            .zip(manifest.partitions.unwrap_or_default())
+        {
+            if *field.transform() != Transform::Identity {
+                continue;
+            }
+            let (Some(column), Some(min), Some(max)) = (
+                schema
+                    .fields()
+                    .iter()
+                    .find(|column| column.id == *field.source_id()),
+                summary.lower_bound,
+                summary.upper_bound,
+            ) else {
+                continue;
+            };
+            manifest_bounds.insert(
+                column.name.clone(),
+                [value_to_scalar(&min)?, value_to_scalar(&max)?],
+            );
+        }
+        bounds = Some(match bounds {
+            None => manifest_bounds,
+            Some(bounds) => bounds
+                .into_iter()
+                .filter_map(|(column, [min, max])| {
+                    let [manifest_min, manifest_max] = manifest_bounds.remove(&column)?;
+                    let min = if min.partial_cmp(&manifest_min)?.is_gt() {
+                        manifest_min
+                    } else {
+                        min
+                    };
+                    let max = if max.partial_cmp(&manifest_max)?.is_lt() {
+                        manifest_max
+                    } else {
+                        max
+                    };
+                    Some((column, [min, max]))
+                })
+                .collect(),
+        });
     }
+    Ok(bounds.unwrap_or_default())
 }
 
-/// Converts an `IcebergValue` to `DataFusion`on literal expression.
+/// Converts an `IcebergValue` to a `DataFusion` scalar value.
 ///
 /// # Arguments
 /// * `value` - The `IcebergValue` to convert
 ///
 /// # Returns
-/// A `DataFusion` expression representing the literal value, or an error if the value type is not supported.
+/// A `DataFusion` scalar value of the matching type, or an error if the value type is not supported.
 ///
 /// # Supported Types
 /// * All primitive types: Boolean, Int, `LongInt`, Float, Double, Date, Time, Timestamp, `TimestampTZ`, String, UUID, Fixed, Binary, Decimal
 /// * Complex types (Struct, List, Map) are not currently supported
-fn value_to_literal(value: &IcebergValue) -> Result<datafusion_expr::Expr> {
+fn value_to_scalar(value: &IcebergValue) -> Result<ScalarValue> {
     match value {
-        IcebergValue::Boolean(b) => Ok(lit(*b)),
-        IcebergValue::Int(i) => Ok(lit(*i)),
-        IcebergValue::LongInt(l) => Ok(lit(*l)),
-        IcebergValue::Float(f) => Ok(lit(f.0)),
-        IcebergValue::Double(d) => Ok(lit(d.0)),
-        IcebergValue::Date(d) => Ok(lit(*d)),
-        IcebergValue::Time(t) => Ok(lit(*t)),
-        IcebergValue::Timestamp(ts) | IcebergValue::TimestampTZ(ts) => Ok(lit(*ts)),
-        IcebergValue::String(s) => Ok(lit(s.as_str())),
-        IcebergValue::UUID(u) => Ok(lit(u.to_string())),
-        IcebergValue::Fixed(_, data) | IcebergValue::Binary(data) => Ok(lit(data.clone())),
-        IcebergValue::Decimal(d) => Ok(lit(d.to_string())),
+        IcebergValue::Boolean(b) => Ok(ScalarValue::from(*b)),
+        IcebergValue::Int(i) => Ok(ScalarValue::from(*i)),
+        IcebergValue::LongInt(l) => Ok(ScalarValue::from(*l)),
+        IcebergValue::Float(f) => Ok(ScalarValue::from(f.0)),
+        IcebergValue::Double(d) => Ok(ScalarValue::from(d.0)),
+        IcebergValue::Date(d) => Ok(ScalarValue::Date32(Some(*d))),
+        IcebergValue::Time(t) => Ok(ScalarValue::Time64Microsecond(Some(*t))),
+        IcebergValue::Timestamp(ts) => Ok(ScalarValue::TimestampMicrosecond(Some(*ts), None)),
+        IcebergValue::TimestampTZ(ts) => Ok(ScalarValue::TimestampMicrosecond(
+            Some(*ts),
+            Some("+00:00".into()),
+        )),
+        IcebergValue::String(s) => Ok(ScalarValue::from(s.as_str())),
+        IcebergValue::UUID(u) => Ok(ScalarValue::from(u.to_string())),
+        IcebergValue::Fixed(_, data) | IcebergValue::Binary(data) => {
+            Ok(ScalarValue::Binary(Some(data.clone())))
+        }
+        // The scale of a `rust_decimal::Decimal` is at most 28
+        #[allow(
+            clippy::as_conversions,
+            clippy::cast_possible_truncation,
+            clippy::cast_possible_wrap
+        )]
+        IcebergValue::Decimal(d) => Ok(ScalarValue::Decimal128(
+            Some(d.mantissa()),
+            DECIMAL128_MAX_PRECISION,
+            d.scale() as i8,
+        )),
         IcebergValue::Struct(_) | IcebergValue::List(_) | IcebergValue::Map(_) => {
             ex_error::UnsupportedIcebergValueTypeSnafu {
                 value_type: format!("{value:?}"),
@@ -3665,52 +7886,103 @@ pub fn get_kv_option<'a>(options: &'a KeyValueOptions, key: &str) -> Option<&'a
         .map(|opt| opt.value.as_str())
 }
 
-fn create_file_format(
-    file_format: &KeyValueOptions,
-) -> Result<Option<(Arc<dyn FileFormat>, bool)>> {
-    if let Some(format_type) = get_kv_option(file_format, "type") {
-        if format_type.eq_ignore_ascii_case("parquet") {
-            Ok(Some((Arc::new(ParquetFormat::default()), true)))
-        } else if format_type.eq_ignore_ascii_case("csv") {
-            let infer_schema = get_kv_option(file_format, "parse_header")
-                .is_some_and(|x| x.to_lowercase() == "true");
-            let has_header =
-                get_kv_option(file_format, "skip_header").is_some_and(|x| x.to_lowercase() == "1");
-
-            let mut csv_format = CsvFormat::default().with_has_header(has_header);
-
-            if let Some(compression) = get_kv_option(file_format, "compression") {
-                csv_format = csv_format.with_file_compression_type(
-                    FileCompressionType::from_str(compression)
-                        .context(ex_error::DataFusionSnafu)?,
-                );
-            }
+/// Collects `KEY = value` options into a map keyed by the uppercase option names
+fn key_value_options_map(options: &KeyValueOptions) -> BTreeMap<String, String> {
+    options
+        .options
+        .iter()
+        .map(|option| {
+            (
+                option.option_name.to_ascii_uppercase(),
+                option.value.clone(),
+            )
+        })
+        .collect()
+}
 
-            // Handle field_delimiter parameter
-            let csv_format = if let Some(delimiter) = get_kv_option(file_format, "field_delimiter")
-            {
-                if delimiter.len() == 1 {
-                    csv_format.with_delimiter(delimiter.as_bytes()[0])
-                } else {
-                    csv_format
-                }
-            } else {
-                csv_format
-            };
+/// Lists the files of a stage starting with `path`, which like in Snowflake is a prefix
+/// rather than a directory, optionally filtered by a `PATTERN` regex matching the whole
+/// path of the file in the stage.
+async fn list_stage_files(
+    store: &Arc<dyn ObjectStore>,
+    path: &str,
+    pattern: Option<&str>,
+) -> Result<Vec<ObjectMeta>> {
+    let pattern = file_pattern(pattern)?;
+    let parent = path
+        .rsplit_once('/')
+        .map(|(parent, _)| ObjectPath::from(parent));
+    let mut files: Vec<ObjectMeta> = store
+        .list(parent.as_ref())
+        .try_collect()
+        .await
+        .context(ex_error::ObjectStoreSnafu)?;
+    files.retain(|file| {
+        let location = file.location.as_ref();
+        location.starts_with(path) && pattern.as_ref().is_none_or(|re| re.is_match(location))
+    });
+    files.sort_by(|a, b| a.location.cmp(&b.location));
+    Ok(files)
+}
 
-            Ok(Some((Arc::new(csv_format), infer_schema)))
-        } else if format_type.eq_ignore_ascii_case("json") {
-            Ok(Some((Arc::new(JsonFormat::default()), true)))
-        } else {
-            ex_error::UnsupportedFileFormatSnafu {
-                format: format_type,
-            }
-            .fail()
-        }
+/// Compiles a `PATTERN` regex, which has to match the whole path of a file
+fn file_pattern(pattern: Option<&str>) -> Result<Option<Regex>> {
+    pattern
+        .map(|pattern| {
+            Regex::new(&format!("^(?:{pattern})$"))
+                .context(ex_error::InvalidPatternSnafu { pattern })
+        })
+        .transpose()
+}
+
+fn join_stage_path(path: &str, file: &str) -> String {
+    let file = file.trim_start_matches('/');
+    if path.is_empty() {
+        file.to_string()
     } else {
-        Ok(None)
+        format!("{path}/{file}")
+    }
+}
+
+/// Name of a stage file as shown by `LIST`: the full url for external stages and the
+/// path prefixed with the stage name for volume stages
+fn stage_file_name(stage: &Stage, location: &ObjectPath) -> String {
+    match &stage.location {
+        StageLocation::Url { url, .. } => format!("{}/{location}", url.trim_end_matches('/')),
+        StageLocation::Volume { .. } | StageLocation::Internal { .. } => {
+            format!("{}/{location}", stage.ident.stage)
+        }
     }
 }
+
+/// Url under which the object store of a stage is registered in the session context
+fn stage_object_store_url(ident: &StageIdent) -> Result<Url> {
+    let host = ident
+        .to_string()
+        .bytes()
+        .fold(String::new(), |mut host, byte| {
+            let _ = write!(host, "{byte:02x}");
+            host
+        });
+    let url = format!("stage://{host}/");
+    Url::parse(&url).context(ex_error::InvalidUrlSnafu { url })
+}
+
+fn table_grant_object(table: &ResolvedTableReference) -> GrantObject {
+    GrantObject::table(&table.catalog, &table.schema, &table.table)
+}
+
+fn stage_grant_object(stage: &StageIdent) -> GrantObject {
+    GrantObject::stage(&stage.database, &stage.schema, &stage.stage)
+}
+
+/// Returns true if one of the locations is inside of the other one
+fn locations_overlap(location: &str, other: &str) -> bool {
+    let location = format!("{}/", location.trim_end_matches('/'));
+    let other = format!("{}/", other.trim_end_matches('/'));
+    location.starts_with(&other) || other.starts_with(&location)
+}
+
 fn normalize_resolved_ref(table_ref: &ResolvedTableReference) -> ResolvedTableReference {
     ResolvedTableReference {
         catalog: Arc::from(table_ref.catalog.to_ascii_lowercase()),
@@ -103,6 +103,7 @@ impl ExecutionTaskResult {
                     DmlStType::Insert => query.set_rows_inserted(rows_count),
                     DmlStType::Update => query.set_rows_updated(rows_count),
                     DmlStType::Delete | DmlStType::Truncate => query.set_rows_deleted(rows_count),
+                    DmlStType::Unload => query.set_rows_unloaded(rows_count),
                     DmlStType::Merge => {
                         // merge has 2 columns, currently map values to insert/select rows counts
                         query.set_rows_inserted(rows_count);
@@ -26,6 +26,7 @@ pub enum DmlStType {
     Delete,
     Truncate,
     Merge,
+    Unload,
 }
 
 #[derive(Debug, Clone, strum::Display)]
@@ -38,6 +39,7 @@ pub enum DdlStType {
     CreateVolume,
     CreateSchema,
     CreateStage,
+    CreateFileFormat,
     CopyIntoSnowflake,
     DropTable,
     DropView,
@@ -45,9 +47,21 @@ pub enum DdlStType {
     DropSchema,
     DropDatabase,
     DropStage,
+    DropFileFormat,
+    DropExternalTable,
     AlterTable,
+    AlterExternalTable,
     AlterSession,
+    Comment,
+    CreateUser,
+    AlterUser,
+    DropUser,
+    CreateRole,
+    DropRole,
+    Grant,
+    Revoke,
     Drop,
+    Undrop,
 }
 
 #[derive(Debug, Clone, strum::Display)]
@@ -70,7 +84,10 @@ pub enum MiscStType {
     ExplainTable,
     Explain,
     Analyze,
-    CopyTo,
+    List,
+    Remove,
+    Put,
+    Get,
 }
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,209 @@
+use catalog_metastore::{Privilege, Role, User};
+use sqlparser::ast::ObjectName;
+use sqlparser::dialect::SnowflakeDialect;
+use sqlparser::keywords::Keyword;
+use sqlparser::parser::{Parser, ParserError};
+use sqlparser::tokenizer::Token;
+use std::str::FromStr;
+
+/// Access control statement: `CREATE ROLE`, `DROP ROLE`, `GRANT` or `REVOKE`.
+///
+/// These are parsed here rather than by the SQL parser, since its AST has no room for
+/// the Snowflake privileges and grant targets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RoleStatement {
+    Create {
+        name: String,
+        or_replace: bool,
+        if_not_exists: bool,
+        comment: Option<String>,
+    },
+    Drop {
+        name: String,
+        if_exists: bool,
+    },
+    Grant {
+        privileges: Vec<Privilege>,
+        on: GrantOn,
+        role: String,
+    },
+    Revoke {
+        privileges: Vec<Privilege>,
+        on: GrantOn,
+        role: String,
+    },
+    GrantRole {
+        role: String,
+        user: String,
+    },
+    RevokeRole {
+        role: String,
+        user: String,
+    },
+}
+
+/// Object named in a `GRANT` or `REVOKE`, resolved against the current database and
+/// schema when the statement is executed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GrantOn {
+    Database(ObjectName),
+    Schema(ObjectName),
+    Table(ObjectName),
+    Stage(ObjectName),
+}
+
+fn parse_privilege(parser: &mut Parser) -> Result<Privilege, ParserError> {
+    if parser.parse_keyword(Keyword::ALL) {
+        let _ = parser.parse_keyword(Keyword::PRIVILEGES);
+        return Ok(Privilege::All);
+    }
+    if parser.parse_keyword(Keyword::CREATE) {
+        return if parser.parse_keyword(Keyword::SCHEMA) {
+            Ok(Privilege::CreateSchema)
+        } else if parser.parse_keyword(Keyword::TABLE) {
+            Ok(Privilege::CreateTable)
+        } else if parser.parse_keyword(Keyword::VIEW) {
+            Ok(Privilege::CreateView)
+        } else if parser.parse_keyword(Keyword::STAGE) {
+            Ok(Privilege::CreateStage)
+        } else {
+            parser.expected("SCHEMA, TABLE, VIEW or STAGE", parser.peek_token())
+        };
+    }
+    let ident = parser.parse_identifier()?;
+    match Privilege::from_str(&ident.value) {
+        Ok(
+            Privilege::CreateSchema
+            | Privilege::CreateTable
+            | Privilege::CreateView
+            | Privilege::CreateStage,
+        )
+        | Err(_) => Err(ParserError::ParserError(format!(
+            "Unsupported privilege: {ident}"
+        ))),
+        Ok(privilege) => Ok(privilege),
+    }
+}
+
+fn parse_grant_on(parser: &mut Parser) -> Result<GrantOn, ParserError> {
+    parser.expect_keyword_is(Keyword::ON)?;
+    if parser.parse_keyword(Keyword::DATABASE) {
+        Ok(GrantOn::Database(parser.parse_object_name(false)?))
+    } else if parser.parse_keyword(Keyword::SCHEMA) {
+        Ok(GrantOn::Schema(parser.parse_object_name(false)?))
+    } else if parser.parse_keyword(Keyword::STAGE) {
+        Ok(GrantOn::Stage(parser.parse_object_name(false)?))
+    } else {
+        let _ = parser.parse_keyword(Keyword::TABLE);
+        Ok(GrantOn::Table(parser.parse_object_name(false)?))
+    }
+}
+
+fn parse_role_name(parser: &mut Parser) -> Result<String, ParserError> {
+    Ok(Role::normalize_name(&parser.parse_identifier()?.value))
+}
+
+fn parse_grantee_role(parser: &mut Parser) -> Result<String, ParserError> {
+    let _ = parser.parse_keyword(Keyword::ROLE);
+    parse_role_name(parser)
+}
+
+fn parse_grantee_user(parser: &mut Parser) -> Result<String, ParserError> {
+    parser.expect_keyword_is(Keyword::USER)?;
+    Ok(User::normalize_name(&parser.parse_identifier()?.value))
+}
+
+// Cheap check to avoid tokenizing every statement twice
+fn looks_like_role_statement(sql: &str) -> bool {
+    let words = sql
+        .split_whitespace()
+        .take(4)
+        .map(str::to_ascii_uppercase)
+        .collect::<Vec<_>>();
+    match words.first().map(String::as_str) {
+        Some("GRANT" | "REVOKE") => true,
+        Some("CREATE" | "DROP") => words.iter().any(|word| word == "ROLE"),
+        _ => false,
+    }
+}
+
+/// Parses an access control statement, returns `None` if the sql is a different statement.
+pub fn parse_role_statement(sql: &str) -> Result<Option<RoleStatement>, ParserError> {
+    if !looks_like_role_statement(sql) {
+        return Ok(None);
+    }
+    let dialect = SnowflakeDialect;
+    let mut parser = Parser::new(&dialect).try_with_sql(sql)?;
+
+    let statement = if parser.parse_keyword(Keyword::CREATE) {
+        let or_replace = parser.parse_keywords(&[Keyword::OR, Keyword::REPLACE]);
+        if !parser.parse_keyword(Keyword::ROLE) {
+            return Ok(None);
+        }
+        let if_not_exists = parser.parse_keywords(&[Keyword::IF, Keyword::NOT, Keyword::EXISTS]);
+        let name = parse_role_name(&mut parser)?;
+        let comment = if parser.parse_keyword(Keyword::COMMENT) {
+            parser.expect_token(&Token::Eq)?;
+            Some(parser.parse_literal_string()?)
+        } else {
+            None
+        };
+        RoleStatement::Create {
+            name,
+            or_replace,
+            if_not_exists,
+            comment,
+        }
+    } else if parser.parse_keyword(Keyword::DROP) {
+        if !parser.parse_keyword(Keyword::ROLE) {
+            return Ok(None);
+        }
+        let if_exists = parser.parse_keywords(&[Keyword::IF, Keyword::EXISTS]);
+        RoleStatement::Drop {
+            name: parse_role_name(&mut parser)?,
+            if_exists,
+        }
+    } else if parser.parse_keyword(Keyword::GRANT) {
+        if parser.parse_keyword(Keyword::ROLE) {
+            let role = parse_role_name(&mut parser)?;
+            parser.expect_keyword_is(Keyword::TO)?;
+            RoleStatement::GrantRole {
+                role,
+                user: parse_grantee_user(&mut parser)?,
+            }
+        } else {
+            let privileges = parser.parse_comma_separated(parse_privilege)?;
+            let on = parse_grant_on(&mut parser)?;
+            parser.expect_keyword_is(Keyword::TO)?;
+            RoleStatement::Grant {
+                privileges,
+                on,
+                role: parse_grantee_role(&mut parser)?,
+            }
+        }
+    } else if parser.parse_keyword(Keyword::REVOKE) {
+        if parser.parse_keyword(Keyword::ROLE) {
+            let role = parse_role_name(&mut parser)?;
+            parser.expect_keyword_is(Keyword::FROM)?;
+            RoleStatement::RevokeRole {
+                role,
+                user: parse_grantee_user(&mut parser)?,
+            }
+        } else {
+            let privileges = parser.parse_comma_separated(parse_privilege)?;
+            let on = parse_grant_on(&mut parser)?;
+            parser.expect_keyword_is(Keyword::FROM)?;
+            RoleStatement::Revoke {
+                privileges,
+                on,
+                role: parse_grantee_role(&mut parser)?,
+            }
+        }
+    } else {
+        return Ok(None);
+    };
+
+    while parser.consume_token(&Token::SemiColon) {}
+    parser.expect_token(&Token::EOF)?;
+    Ok(Some(statement))
+}
@@ -1,9 +1,13 @@
-use super::error::{self as ex_error, Result};
+use super::error::{self as ex_error, Error, Result};
 use super::models::QueryResult;
 use crate::query_types::{ExecutionStatus, QueryId, QueryStats};
+use chrono::{DateTime, Utc};
 use dashmap::DashMap;
+use lru::LruCache;
 use snafu::{OptionExt, ResultExt};
-use std::sync::Arc;
+use std::num::NonZeroUsize;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use tokio::sync::watch;
 use tokio::task::JoinHandle;
 use tokio_util::sync::CancellationToken;
@@ -15,6 +19,8 @@ use uuid::Uuid;
 pub struct RunningQuery {
     pub query_id: QueryId,
     pub request_id: Option<Uuid>,
+    // session that submitted the query, only it can fetch the result or abort the query
+    pub session_id: Option<String>,
     // save result handle here, so when query finishes caller will retrieve handle
     // by removing RunningQuery from registry and will get result using result handle
     pub result_handle: Option<JoinHandle<Result<QueryResult>>>,
@@ -23,6 +29,32 @@ pub struct RunningQuery {
     tx: watch::Sender<Option<ExecutionStatus>>,
     rx: watch::Receiver<Option<ExecutionStatus>>,
     pub query_stats: QueryStats,
+    pub started_at: DateTime<Utc>,
+}
+
+/// Start and end time of a statement, `AT(STATEMENT => ...)` and
+/// `BEFORE(STATEMENT => ...)` time travel clauses refer to these
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StatementTimeRange {
+    pub started_at: DateTime<Utc>,
+    // None while the statement is still running
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+// Number of finished statements whose time range is kept after they leave the registry
+const FINISHED_QUERIES_CAPACITY: usize = 1024;
+
+/// Seconds the result of a finished query is kept, so the session that submitted it
+/// can fetch it again, or for the first time if it was submitted asynchronously
+pub const QUERY_RESULT_RETENTION_SECONDS: u64 = 600;
+
+// Result of a finished query kept for the session that submitted it
+#[derive(Debug, Clone)]
+struct KeptResult {
+    session_id: Option<String>,
+    status: ExecutionStatus,
+    result: std::result::Result<QueryResult, Arc<Error>>,
+    expires_at: Instant,
 }
 
 #[derive(Debug, Clone)]
@@ -38,11 +70,13 @@ impl RunningQuery {
         Self {
             query_id,
             request_id: None,
+            session_id: None,
             cancellation_token: CancellationToken::new(),
             result_handle: None,
             tx,
             rx,
             query_stats: QueryStats::default(),
+            started_at: Utc::now(),
         }
     }
     #[must_use]
@@ -50,6 +84,14 @@ impl RunningQuery {
         Self { request_id, ..self }
     }
 
+    #[must_use]
+    pub fn with_session_id(self, session_id: impl Into<String>) -> Self {
+        Self {
+            session_id: Some(session_id.into()),
+            ..self
+        }
+    }
+
     #[must_use]
     pub fn with_result_handle(self, result_handle: JoinHandle<Result<QueryResult>>) -> Self {
         Self {
@@ -111,6 +153,10 @@ pub struct RunningQueriesRegistry {
     queries: Arc<DashMap<QueryId, RunningQuery>>,
     // <request_id, QueryId> To associate request_id with query_id
     requests_ids: Arc<DashMap<Uuid, QueryId>>,
+    // <query_id, StatementTimeRange> of recently finished queries
+    finished: Arc<Mutex<LruCache<QueryId, StatementTimeRange>>>,
+    // <query_id, KeptResult> of finished queries whose result can still be fetched
+    results: Arc<DashMap<QueryId, KeptResult>>,
 }
 
 impl Default for RunningQueriesRegistry {
@@ -125,6 +171,10 @@ impl RunningQueriesRegistry {
         Self {
             queries: Arc::new(DashMap::new()),
             requests_ids: Arc::new(DashMap::new()),
+            finished: Arc::new(Mutex::new(LruCache::new(
+                NonZeroUsize::new(FINISHED_QUERIES_CAPACITY).unwrap_or(NonZeroUsize::MIN),
+            ))),
+            results: Arc::new(DashMap::new()),
         }
     }
 
@@ -158,10 +208,29 @@ pub trait RunningQueries: Send + Sync {
     fn remove(&self, query_id: QueryId) -> Result<RunningQuery>;
     fn abort(&self, query_id: QueryId) -> Result<()>;
     fn notify_query_finished(&self, query_id: QueryId, status: ExecutionStatus) -> Result<()>;
+    fn status(&self, query_id: QueryId) -> Result<ExecutionStatus>;
+    /// Fails as if the query did not exist unless it was submitted by the session
+    fn check_owner(&self, query_id: QueryId, session_id: &str) -> Result<()>;
+    /// Keeps the result of a finished query for [`QUERY_RESULT_RETENTION_SECONDS`] and
+    /// returns it, failed results are returned as [`Error::KeptQueryResult`]
+    fn keep_result(
+        &self,
+        query_id: QueryId,
+        session_id: Option<String>,
+        status: ExecutionStatus,
+        result: Result<QueryResult>,
+    ) -> Result<QueryResult>;
+    fn kept_result(&self, query_id: QueryId) -> Option<Result<QueryResult>>;
+    /// Drops the records of a kept result, its status and owner stay until it expires
+    /// and fetching it again fails with [`Error::QueryResultReleased`]
+    fn release_result(&self, query_id: QueryId);
+    /// Drops the kept results that expired, returns how many were dropped
+    fn evict_expired_results(&self) -> usize;
     fn locate_query_id(&self, running_query_id: RunningQueryId) -> Result<QueryId>;
     fn count(&self) -> usize;
     fn cloned_stats(&self, query_id: QueryId) -> Option<QueryStats>;
     fn update_stats(&self, query_id: QueryId, stats: &QueryStats);
+    fn time_range(&self, query_id: QueryId) -> Option<StatementTimeRange>;
 }
 
 impl RunningQueries for RunningQueriesRegistry {
@@ -213,9 +282,106 @@ impl RunningQueries for RunningQueriesRegistry {
             .get(&query_id)
             .context(ex_error::QueryIsntRunningSnafu { query_id })?;
         let _ = running_query.notify_query_finished(status);
+        if let Ok(mut finished) = self.finished.lock() {
+            finished.put(
+                query_id,
+                StatementTimeRange {
+                    started_at: running_query.started_at,
+                    finished_at: Some(Utc::now()),
+                },
+            );
+        }
         Ok(())
     }
 
+    #[tracing::instrument(
+        name = "RunningQueriesRegistry::status",
+        level = "trace",
+        skip(self),
+        ret
+    )]
+    fn status(&self, query_id: QueryId) -> Result<ExecutionStatus> {
+        if let Some(running_query) = self.queries.get(&query_id) {
+            // no status is posted until the query finishes
+            let status = *running_query.rx.borrow();
+            return Ok(status.unwrap_or(ExecutionStatus::Running));
+        }
+        self.results
+            .get(&query_id)
+            .filter(|kept| kept.expires_at > Instant::now())
+            .map(|kept| kept.status)
+            .context(ex_error::QueryIsntRunningSnafu { query_id })
+    }
+
+    fn check_owner(&self, query_id: QueryId, session_id: &str) -> Result<()> {
+        let owner = if let Some(running_query) = self.queries.get(&query_id) {
+            running_query.session_id.clone()
+        } else {
+            self.results
+                .get(&query_id)
+                .context(ex_error::QueryIsntRunningSnafu { query_id })?
+                .session_id
+                .clone()
+        };
+        // Queries of other sessions are reported as missing, not to reveal they exist
+        if owner.as_deref() == Some(session_id) {
+            Ok(())
+        } else {
+            ex_error::QueryIsntRunningSnafu { query_id }.fail()
+        }
+    }
+
+    #[tracing::instrument(
+        name = "RunningQueriesRegistry::keep_result",
+        level = "trace",
+        skip(self, result)
+    )]
+    fn keep_result(
+        &self,
+        query_id: QueryId,
+        session_id: Option<String>,
+        status: ExecutionStatus,
+        result: Result<QueryResult>,
+    ) -> Result<QueryResult> {
+        self.evict_expired_results();
+        let result = result.map_err(Arc::new);
+        self.results.insert(
+            query_id,
+            KeptResult {
+                session_id,
+                status,
+                result: result.clone(),
+                expires_at: Instant::now() + Duration::from_secs(QUERY_RESULT_RETENTION_SECONDS),
+            },
+        );
+        result.context(ex_error::KeptQueryResultSnafu)
+    }
+
+    fn kept_result(&self, query_id: QueryId) -> Option<Result<QueryResult>> {
+        let kept = self
+            .results
+            .get(&query_id)
+            .filter(|kept| kept.expires_at > Instant::now())?;
+        Some(kept.result.clone().context(ex_error::KeptQueryResultSnafu))
+    }
+
+    fn release_result(&self, query_id: QueryId) {
+        if let Some(mut kept) = self.results.get_mut(&query_id)
+            && kept.result.is_ok()
+        {
+            kept.result = Err(Arc::new(
+                ex_error::QueryResultReleasedSnafu { query_id }.build(),
+            ));
+        }
+    }
+
+    fn evict_expired_results(&self) -> usize {
+        let now = Instant::now();
+        let count = self.results.len();
+        self.results.retain(|_, kept| kept.expires_at > now);
+        count - self.results.len()
+    }
+
     #[tracing::instrument(
         name = "RunningQueriesRegistry::locate_query_id",
         level = "trace",
@@ -256,4 +422,18 @@ impl RunningQueries for RunningQueriesRegistry {
             running_query.update_query_stats(stats);
         }
     }
+
+    fn time_range(&self, query_id: QueryId) -> Option<StatementTimeRange> {
+        if let Ok(mut finished) = self.finished.lock()
+            && let Some(range) = finished.get(&query_id)
+        {
+            return Some(*range);
+        }
+        self.queries
+            .get(&query_id)
+            .map(|running_query| StatementTimeRange {
+                started_at: running_query.started_at,
+                finished_at: None,
+            })
+    }
 }
@@ -0,0 +1,281 @@
+use crate::error::{self as ex_error, Result};
+use catalog_metastore::TableUpdate as MetastoreTableUpdate;
+use datafusion::arrow::datatypes::{Field, Fields};
+use iceberg_rust::catalog::commit::{TableRequirement, TableUpdate as IcebergTableUpdate};
+use iceberg_rust::spec::arrow::schema::new_fields_with_ids;
+use iceberg_rust::spec::schema::Schema;
+use iceberg_rust::spec::table_metadata::TableMetadata;
+use iceberg_rust::spec::types::{PrimitiveType, StructField, StructType, Type};
+use snafu::ResultExt;
+use std::collections::HashMap;
+
+/// Table property holding the comment of a table
+pub const COMMENT_PROPERTY: &str = "comment";
+
+/// Schema changes of `ALTER TABLE` applied to the current schema of an Iceberg table.
+///
+/// Columns keep their field ids across renames and type changes, new columns get
+/// ids after the last column id of the table, so data files written with an older
+/// schema stay readable. The changes are committed as a new schema that becomes the
+/// current one, see [`SchemaEvolution::into_update`].
+#[derive(Debug, Clone)]
+pub struct SchemaEvolution {
+    table: String,
+    current_schema_id: i32,
+    next_schema_id: i32,
+    last_column_id: i32,
+    has_snapshots: bool,
+    /// Columns the partition fields of the table are computed from, in any of its specs
+    /// since data files written with earlier specs keep their partitioning
+    partition_source_ids: Vec<i32>,
+    fields: Vec<StructField>,
+    changed: bool,
+}
+
+impl SchemaEvolution {
+    pub fn new(table: &str, metadata: &TableMetadata) -> Result<Self> {
+        let schema = metadata
+            .current_schema(None)
+            .context(ex_error::SchemaChangeIcebergSpecSnafu { table })?;
+        Ok(Self {
+            table: table.to_string(),
+            current_schema_id: metadata.current_schema_id,
+            next_schema_id: metadata.schemas.keys().max().map_or(0, |id| id + 1),
+            last_column_id: metadata.last_column_id,
+            has_snapshots: !metadata.snapshots.is_empty(),
+            partition_source_ids: metadata
+                .partition_specs
+                .values()
+                .flat_map(|spec| spec.fields())
+                .map(|field| *field.source_id())
+                .collect(),
+            fields: schema.fields().iter().cloned().collect(),
+            changed: false,
+        })
+    }
+
+    #[must_use]
+    pub fn contains(&self, column: &str) -> bool {
+        self.position(column).is_some()
+    }
+
+    /// Adds a column converted from its Arrow field. A `NOT NULL` column can only be
+    /// added to a table without data, since there are no values for existing rows.
+    pub fn add_column(
+        &mut self,
+        field: &Field,
+        comment: Option<String>,
+        if_not_exists: bool,
+    ) -> Result<()> {
+        if self.contains(field.name()) {
+            if if_not_exists {
+                return Ok(());
+            }
+            return Err(invalid_change(
+                &self.table,
+                format!("column '{}' already exists", field.name()),
+            ));
+        }
+        if !field.is_nullable() && self.has_snapshots {
+            return Err(invalid_change(
+                &self.table,
+                format!(
+                    "cannot add NOT NULL column '{}' to a table with data",
+                    field.name()
+                ),
+            ));
+        }
+        let mut last_column_id = self.last_column_id;
+        let mut column = self.struct_field(field, &mut last_column_id)?;
+        column.doc = comment;
+        self.last_column_id = last_column_id;
+        self.fields.push(column);
+        self.changed = true;
+        Ok(())
+    }
+
+    pub fn drop_column(&mut self, column: &str, if_exists: bool) -> Result<()> {
+        let Some(position) = self.position(column) else {
+            if if_exists {
+                return Ok(());
+            }
+            return Err(self.column_not_found(column));
+        };
+        if self.fields.len() == 1 {
+            return Err(invalid_change(
+                &self.table,
+                format!("cannot drop column '{column}', a table needs at least one column"),
+            ));
+        }
+        if self
+            .partition_source_ids
+            .contains(&self.fields[position].id)
+        {
+            return Err(invalid_change(
+                &self.table,
+                format!("cannot drop column '{column}', the table is partitioned by it"),
+            ));
+        }
+        self.fields.remove(position);
+        self.changed = true;
+        Ok(())
+    }
+
+    pub fn rename_column(&mut self, column: &str, new_name: &str) -> Result<()> {
+        let position = self.field_position(column)?;
+        if self
+            .position(new_name)
+            .is_some_and(|other| other != position)
+        {
+            return Err(invalid_change(
+                &self.table,
+                format!("column '{new_name}' already exists"),
+            ));
+        }
+        self.fields[position].name = new_name.to_string();
+        self.changed = true;
+        Ok(())
+    }
+
+    /// Makes a column required or optional. Whether a column made required holds
+    /// no nulls is left to the caller, who can query the table.
+    pub fn set_required(&mut self, column: &str, required: bool) -> Result<()> {
+        let position = self.field_position(column)?;
+        if self.fields[position].required != required {
+            self.fields[position].required = required;
+            self.changed = true;
+        }
+        Ok(())
+    }
+
+    /// Changes the type of a column to the type of the Arrow field. Only the type
+    /// promotions of the Iceberg spec are allowed, which existing values can be read
+    /// as without rewriting the data files.
+    pub fn set_type(&mut self, column: &str, field: &Field) -> Result<()> {
+        let position = self.field_position(column)?;
+        let target = self.struct_field(field, &mut 0)?.field_type;
+        let current = &self.fields[position].field_type;
+        if current == &target {
+            return Ok(());
+        }
+        if !is_promotion(current, &target) {
+            return Err(invalid_change(
+                &self.table,
+                format!("cannot change column '{column}' from type {current} to {target}"),
+            ));
+        }
+        self.fields[position].field_type = target;
+        self.changed = true;
+        Ok(())
+    }
+
+    pub fn set_comment(&mut self, column: &str, comment: Option<String>) -> Result<()> {
+        let position = self.field_position(column)?;
+        if self.fields[position].doc != comment {
+            self.fields[position].doc = comment;
+            self.changed = true;
+        }
+        Ok(())
+    }
+
+    /// Returns the update adding the changed schema and making it the current one,
+    /// or `None` if nothing changed. The commit fails if the current schema of the
+    /// table changed in the meantime.
+    pub fn into_update(self) -> Result<Option<MetastoreTableUpdate>> {
+        if !self.changed {
+            return Ok(None);
+        }
+        let mut builder = Schema::builder();
+        builder.with_schema_id(self.next_schema_id);
+        builder.with_identifier_field_ids(vec![]);
+        for field in self.fields {
+            builder.with_struct_field(field);
+        }
+        let schema = builder
+            .build()
+            .context(ex_error::SchemaChangeIcebergSpecSnafu { table: &self.table })?;
+        Ok(Some(MetastoreTableUpdate {
+            requirements: vec![TableRequirement::AssertCurrentSchemaId {
+                current_schema_id: self.current_schema_id,
+            }],
+            updates: vec![
+                IcebergTableUpdate::AddSchema {
+                    schema,
+                    last_column_id: Some(self.last_column_id),
+                },
+                IcebergTableUpdate::SetCurrentSchema {
+                    schema_id: self.next_schema_id,
+                },
+            ],
+        }))
+    }
+
+    fn position(&self, column: &str) -> Option<usize> {
+        self.fields
+            .iter()
+            .position(|field| field.name.eq_ignore_ascii_case(column))
+    }
+
+    fn field_position(&self, column: &str) -> Result<usize> {
+        self.position(column)
+            .ok_or_else(|| self.column_not_found(column))
+    }
+
+    fn column_not_found(&self, column: &str) -> ex_error::Error {
+        invalid_change(&self.table, format!("column '{column}' does not exist"))
+    }
+
+    fn struct_field(&self, field: &Field, last_column_id: &mut i32) -> Result<StructField> {
+        let fields = new_fields_with_ids(&Fields::from(vec![field.clone()]), last_column_id);
+        let struct_type = StructType::try_from(&fields)
+            .context(ex_error::SchemaChangeIcebergSpecSnafu { table: &self.table })?;
+        struct_type
+            .iter()
+            .next()
+            .cloned()
+            .ok_or_else(|| invalid_change(&self.table, format!("invalid column {}", field.name())))
+    }
+}
+
+/// Update setting the comment of a table, kept in its `comment` property, or removing
+/// it if there is none
+#[must_use]
+pub fn table_comment_update(comment: Option<String>) -> MetastoreTableUpdate {
+    let update = match comment {
+        Some(comment) => IcebergTableUpdate::SetProperties {
+            updates: HashMap::from([(COMMENT_PROPERTY.to_string(), comment)]),
+        },
+        None => IcebergTableUpdate::RemoveProperties {
+            removals: vec![COMMENT_PROPERTY.to_string()],
+        },
+    };
+    MetastoreTableUpdate {
+        requirements: vec![],
+        updates: vec![update],
+    }
+}
+
+/// Type promotions allowed by the Iceberg spec: `int` to `long`, `float` to `double`
+/// and widening the precision of a decimal with the same scale.
+fn is_promotion(from: &Type, to: &Type) -> bool {
+    match (from, to) {
+        (Type::Primitive(PrimitiveType::Int), Type::Primitive(PrimitiveType::Long))
+        | (Type::Primitive(PrimitiveType::Float), Type::Primitive(PrimitiveType::Double)) => true,
+        (
+            Type::Primitive(PrimitiveType::Decimal { precision, scale }),
+            Type::Primitive(PrimitiveType::Decimal {
+                precision: to_precision,
+                scale: to_scale,
+            }),
+        ) => scale == to_scale && precision <= to_precision,
+        _ => false,
+    }
+}
+
+fn invalid_change(table: &str, reason: String) -> ex_error::Error {
+    ex_error::InvalidSchemaChangeSnafu {
+        table: table.to_string(),
+        reason,
+    }
+    .build()
+}
@@ -1,10 +1,15 @@
 use bytes::{Buf, Bytes};
-use datafusion::arrow::array::RecordBatch;
+use datafusion::arrow::array::{AsArray, RecordBatch};
+use datafusion::arrow::compute::cast;
 use datafusion::arrow::csv::ReaderBuilder;
-use datafusion::arrow::csv::reader::Format;
-use datafusion::catalog::CatalogProvider;
+use datafusion::arrow::datatypes::{DataType, UInt64Type};
+use datafusion::catalog::streaming::StreamingTable;
+use datafusion::catalog::{CatalogProvider, TableProvider};
 use datafusion::catalog::{MemoryCatalogProvider, MemorySchemaProvider};
 use datafusion::common::runtime::set_join_set_tracer;
+use datafusion::datasource::listing::{
+    ListingOptions, ListingTable, ListingTableConfig, ListingTableUrl,
+};
 use datafusion::datasource::memory::MemTable;
 use datafusion::execution::DiskManager;
 use datafusion::execution::disk_manager::DiskManagerMode;
@@ -12,15 +17,22 @@ use datafusion::execution::memory_pool::{
     FairSpillPool, GreedyMemoryPool, MemoryPool, TrackConsumersPool,
 };
 use datafusion::execution::runtime_env::{RuntimeEnv, RuntimeEnvBuilder};
+use datafusion::physical_plan::stream::RecordBatchStreamAdapter;
+use datafusion::physical_plan::streaming::PartitionStream;
 use datafusion_common::TableReference;
-use snafu::ResultExt;
-#[cfg(feature = "state-store-query")]
-use state_store::ExecutionStatus;
+use futures::stream::BoxStream;
+use futures::{StreamExt, TryStreamExt};
+use object_store::ObjectStore;
+use object_store::buffered::BufWriter;
+use object_store::path::Path as ObjectPath;
+use snafu::{OptionExt, ResultExt};
+use sqlparser::ast::{Ident, ObjectName};
 use std::num::NonZeroUsize;
-use std::sync::atomic::Ordering;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::vec;
 use std::{collections::HashMap, sync::Arc};
 use time::{Duration as DateTimeDuration, OffsetDateTime};
+use tokio::io::AsyncWriteExt;
 use tokio::task;
 use tokio_util::sync::CancellationToken;
 
@@ -30,16 +42,23 @@ use super::models::SessionMetadataAttr;
 use super::models::{QueryContext, QueryResult};
 use super::running_queries::{RunningQueries, RunningQueriesRegistry, RunningQuery};
 use super::session::UserSession;
+use crate::copy_into::OnceStream;
+use crate::file_formats::{
+    FileFormatOptions, UploadFormat, UploadedFile, UploadedFileResult, read_avro, read_orc,
+};
 use crate::query_task_result::ExecutionTaskResult;
-use crate::query_types::QueryId;
+use crate::query_types::{ExecutionStatus, QueryId};
 use crate::running_queries::RunningQueryId;
 use crate::session::{SESSION_INACTIVITY_EXPIRATION_SECONDS, to_unix};
+use crate::stages::volume_stage_object_store;
 use crate::tracing::SpanTracer;
 use crate::utils::{Config, MemPoolType};
 use catalog::catalog_list::EmbucketCatalogList;
-use catalog_metastore::{InMemoryMetastore, Metastore, TableIdent as MetastoreTableIdent};
+use catalog_metastore::{
+    InMemoryMetastore, Metastore, StageIdent, StageLocation, TableIdent as MetastoreTableIdent,
+};
 #[cfg(feature = "state-store")]
-use state_store::{DynamoDbStateStore, StateStore, models::Query};
+use state_store::{StateStore, models::Query};
 use tokio::sync::RwLock;
 use tokio::time::Duration;
 use tracing::Instrument;
@@ -52,6 +71,8 @@ pub const TIMEOUT_DISCARD_INTERVAL_SECONDS: u64 = 60;
 #[async_trait::async_trait]
 pub trait ExecutionService: Send + Sync {
     async fn create_session(&self, session_id: &str) -> Result<Arc<UserSession>>;
+    /// Creates a session for the executor's own queries, see `UserSession::with_internal`
+    async fn create_internal_session(&self, session_id: &str) -> Result<Arc<UserSession>>;
     async fn update_session_expiry(&self, session_id: &str) -> Result<bool>;
     async fn delete_expired_sessions(&self) -> Result<()>;
     async fn get_session(&self, session_id: &str) -> Result<Arc<UserSession>>;
@@ -71,6 +92,52 @@ pub trait ExecutionService: Send + Sync {
     /// and the `Err` variant contains an `Error`.
     fn locate_query_id(&self, running_query_id: RunningQueryId) -> Result<QueryId>;
 
+    /// Returns the execution status of a submitted query without waiting for it.
+    ///
+    /// # Arguments
+    ///
+    /// * `query_id` - The id of the submitted query.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` of type `ExecutionStatus`. The `Ok` variant is `Running` until the
+    /// query finishes. The `Err` variant is returned when the query is unknown, its
+    /// result was consumed with `wait` or its kept result expired.
+    fn query_status(&self, query_id: QueryId) -> Result<ExecutionStatus>;
+
+    /// Checks the query was submitted by the session, queries of other sessions are
+    /// reported as unknown.
+    ///
+    /// # Arguments
+    ///
+    /// * `session_id` - The ID of the user session.
+    /// * `query_id` - The id of the submitted query.
+    fn check_query_owner(&self, session_id: &str, query_id: QueryId) -> Result<()>;
+
+    /// Returns the result of a query submitted by the session, waiting for it to finish.
+    /// Unlike `wait` the result is kept, so it can be fetched again until it expires.
+    ///
+    /// # Arguments
+    ///
+    /// * `session_id` - The ID of the user session.
+    /// * `query_id` - The id of the submitted query.
+    ///
+    /// # Returns
+    ///
+    /// A `Result` of type `QueryResult`. The `Ok` variant contains the query result,
+    /// and the `Err` variant contains the error of the query or of the lookup.
+    async fn query_result(&self, session_id: &str, query_id: QueryId) -> Result<QueryResult>;
+
+    /// Releases the records of a kept query result once the caller stored them
+    /// elsewhere. Only the status of the query is kept until the result expires,
+    /// fetching the result again fails.
+    ///
+    /// # Arguments
+    ///
+    /// * `session_id` - The ID of the user session.
+    /// * `query_id` - The id of the submitted query.
+    fn release_query_result(&self, session_id: &str, query_id: QueryId) -> Result<()>;
+
     /// Aborts a query by `query_id`.
     ///
     /// # Arguments
@@ -140,9 +207,41 @@ pub trait ExecutionService: Send + Sync {
         table_ident: &MetastoreTableIdent,
         data: Bytes,
         file_name: &str,
-        format: Format,
+        format: UploadFormat,
+        query_context: QueryContext,
     ) -> Result<usize>;
 
+    /// Loads files uploaded to local disk into a table with a single insert, either all
+    /// the files are loaded or none. A missing table is created from the schema of the
+    /// first file if `create_table` is set. The queries loading the files run with
+    /// `query_context`. Returns the rows loaded from each file.
+    async fn upload_files_to_table(
+        &self,
+        session_id: &str,
+        table_ident: &MetastoreTableIdent,
+        files: &[UploadedFile],
+        format: FileFormatOptions,
+        create_table: bool,
+        query_context: QueryContext,
+    ) -> Result<Vec<UploadedFileResult>>;
+
+    /// Writes a file uploaded with `PUT` to an internal stage as it is received, an
+    /// existing file is only replaced if `overwrite` is set.
+    async fn upload_stage_file(
+        &self,
+        stage: &StageIdent,
+        path: &str,
+        data: BoxStream<'static, std::io::Result<Bytes>>,
+        overwrite: bool,
+    ) -> Result<()>;
+
+    /// Reads a file of an internal stage requested with `GET`.
+    async fn download_stage_file(&self, stage: &StageIdent, path: &str) -> Result<Bytes>;
+
+    /// Registers the files at the locations of the external tables with `AUTO_REFRESH`.
+    /// Returns the number of tables refreshed.
+    async fn refresh_external_tables(&self) -> Result<usize>;
+
     async fn timeout_signal(&self, interval: Duration, idle_timeout: Duration) -> ();
 }
 
@@ -158,6 +257,46 @@ pub struct CoreExecutionService {
 }
 
 impl CoreExecutionService {
+    /// Returns the session with the id, creating it if it doesn't exist yet
+    async fn open_session(&self, session_id: &str, internal: bool) -> Result<Arc<UserSession>> {
+        {
+            let sessions = self.df_sessions.read().await;
+            if let Some(session) = sessions.get(session_id) {
+                return Ok(session.clone());
+            }
+        }
+        let user_session: Arc<UserSession> = Arc::new(
+            UserSession::new(
+                self.metastore.clone(),
+                self.queries.clone(),
+                self.config.clone(),
+                self.catalog_list.clone(),
+                self.runtime_env.clone(),
+                session_id,
+                #[cfg(feature = "state-store")]
+                self.state_store.clone(),
+            )
+            .await?
+            .with_internal(internal),
+        );
+        {
+            tracing::trace!("Acquiring write lock for df_sessions");
+            let mut sessions = self.df_sessions.write().await;
+            tracing::trace!("Acquired write lock for df_sessions");
+            sessions.insert(session_id.to_string(), user_session.clone());
+
+            #[cfg(feature = "state-store-persist-session-oncreate")]
+            self.state_store
+                .put_new_session(session_id)
+                .await
+                .context(ex_error::StateStoreSnafu)?;
+
+            // Record the result as part of the current span.
+            tracing::Span::current().record("new_sessions_count", sessions.len());
+        }
+        Ok(user_session)
+    }
+
     #[cfg(feature = "state-store")]
     pub async fn new_test_executor(
         metastore: Arc<dyn Metastore>,
@@ -191,7 +330,9 @@ impl CoreExecutionService {
         let catalog_list = Self::catalog_list(metastore.clone(), &config).await?;
         let runtime_env = Self::runtime_env(&config, catalog_list.clone())?;
         #[cfg(feature = "state-store")]
-        let state_store = DynamoDbStateStore::new_from_env()
+        let state_store = config
+            .state_store_backend
+            .open()
             .await
             .context(ex_error::StateStoreSnafu)?;
         Ok(Self {
@@ -202,7 +343,7 @@ impl CoreExecutionService {
             runtime_env,
             queries: Arc::new(RunningQueriesRegistry::new()),
             #[cfg(feature = "state-store")]
-            state_store: Arc::new(state_store),
+            state_store,
         })
     }
 
@@ -281,6 +422,27 @@ impl CoreExecutionService {
         rt_builder.build_arc().context(ex_error::DataFusionSnafu)
     }
 
+    async fn internal_stage_object_store(
+        &self,
+        ident: &StageIdent,
+    ) -> Result<Arc<dyn ObjectStore>> {
+        let stage = self
+            .metastore
+            .get_stage(ident)
+            .await
+            .context(ex_error::MetastoreSnafu)?
+            .context(ex_error::StageNotFoundSnafu {
+                stage: ident.to_string(),
+            })?;
+        let StageLocation::Internal { volume, path } = &stage.location else {
+            return ex_error::ExternalStageFileTransferSnafu {
+                stage: ident.to_string(),
+            }
+            .fail();
+        };
+        volume_stage_object_store(self.metastore.as_ref(), volume, path).await
+    }
+
     fn initialize_datafusion_tracer() {
         let _ = set_join_set_tracer(&SpanTracer);
     }
@@ -296,41 +458,18 @@ impl ExecutionService for CoreExecutionService {
         err
     )]
     async fn create_session(&self, session_id: &str) -> Result<Arc<UserSession>> {
-        {
-            let sessions = self.df_sessions.read().await;
-            if let Some(session) = sessions.get(session_id) {
-                return Ok(session.clone());
-            }
-        }
-        let user_session: Arc<UserSession> = Arc::new(
-            UserSession::new(
-                self.metastore.clone(),
-                self.queries.clone(),
-                self.config.clone(),
-                self.catalog_list.clone(),
-                self.runtime_env.clone(),
-                session_id,
-                #[cfg(feature = "state-store")]
-                self.state_store.clone(),
-            )
-            .await?,
-        );
-        {
-            tracing::trace!("Acquiring write lock for df_sessions");
-            let mut sessions = self.df_sessions.write().await;
-            tracing::trace!("Acquired write lock for df_sessions");
-            sessions.insert(session_id.to_string(), user_session.clone());
-
-            #[cfg(feature = "state-store-persist-session-oncreate")]
-            self.state_store
-                .put_new_session(session_id)
-                .await
-                .context(ex_error::StateStoreSnafu)?;
+        self.open_session(session_id, false).await
+    }
 
-            // Record the result as part of the current span.
-            tracing::Span::current().record("new_sessions_count", sessions.len());
-        }
-        Ok(user_session)
+    #[tracing::instrument(
+        name = "ExecutionService::create_internal_session",
+        level = "debug",
+        skip(self),
+        fields(new_sessions_count),
+        err
+    )]
+    async fn create_internal_session(&self, session_id: &str) -> Result<Arc<UserSession>> {
+        self.open_session(session_id, true).await
     }
 
     #[tracing::instrument(
@@ -370,6 +509,7 @@ impl ExecutionService for CoreExecutionService {
         let mut sessions = self.df_sessions.write().await;
 
         let old_sessions_count = sessions.len();
+        self.queries.evict_expired_results();
 
         sessions.retain(|session_id, session| {
             let expiry = session.expiry.load(Ordering::Relaxed);
@@ -490,6 +630,50 @@ impl ExecutionService for CoreExecutionService {
         self.queries.locate_query_id(running_query_id)
     }
 
+    #[tracing::instrument(name = "ExecutionService::query_status", level = "debug", skip(self))]
+    fn query_status(&self, query_id: QueryId) -> Result<ExecutionStatus> {
+        self.queries.status(query_id)
+    }
+
+    #[tracing::instrument(
+        name = "ExecutionService::check_query_owner",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    fn check_query_owner(&self, session_id: &str, query_id: QueryId) -> Result<()> {
+        self.queries.check_owner(query_id, session_id)
+    }
+
+    #[tracing::instrument(
+        name = "ExecutionService::query_result",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn query_result(&self, session_id: &str, query_id: QueryId) -> Result<QueryResult> {
+        self.queries.check_owner(query_id, session_id)?;
+        if let Some(result) = self.queries.kept_result(query_id) {
+            return result;
+        }
+        let status = self.queries.wait_query_finished(query_id).await?;
+        let result = self.wait(query_id).await;
+        self.queries
+            .keep_result(query_id, Some(session_id.to_string()), status, result)
+    }
+
+    #[tracing::instrument(
+        name = "ExecutionService::release_query_result",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    fn release_query_result(&self, session_id: &str, query_id: QueryId) -> Result<()> {
+        self.queries.check_owner(query_id, session_id)?;
+        self.queries.release_result(query_id);
+        Ok(())
+    }
+
     #[tracing::instrument(
         name = "ExecutionService::abort",
         level = "debug",
@@ -671,20 +855,31 @@ impl ExecutionService for CoreExecutionService {
                 // as RunningQuery contains result handle that caller should consume.
                 queries_registry.notify_query_finished(query_id, execution_result.execution_status)?;
 
-                // Discard results after short timeout, to prevent memory leaks
+                // Move results nobody waited for out of the registry after short timeout,
+                // they are kept for the submitting session until they expire
+                let execution_status = execution_result.execution_status;
                 tokio::spawn(async move {
                     tokio::time::sleep(Duration::from_secs(TIMEOUT_DISCARD_INTERVAL_SECONDS)).await;
                     let running_query = queries_registry.remove(query_id);
                     if let Ok(RunningQuery {
                         result_handle: Some(result_handle),
+                        session_id,
                         ..
                     }) = running_query
                     {
                         tracing::debug!(
-                            "Discard execution result '{:?}' for query {query_id}",
-                            execution_result.execution_status
+                            "Keep execution result '{execution_status:?}' for query {query_id}"
+                        );
+                        let result = result_handle
+                            .await
+                            .context(ex_error::AsyncResultTaskJoinSnafu { query_id })
+                            .and_then(|result| result);
+                        let _ = queries_registry.keep_result(
+                            query_id,
+                            session_id,
+                            execution_status,
+                            result,
                         );
-                        let _ = result_handle.await;
                     }
                 });
 
@@ -697,6 +892,7 @@ impl ExecutionService for CoreExecutionService {
         self.queries.add(
             RunningQuery::new(query_id)
                 .with_request_id(request_id)
+                .with_session_id(session_id)
                 .with_result_handle(handle)
                 .with_cancellation_token(query_token),
         );
@@ -717,7 +913,8 @@ impl ExecutionService for CoreExecutionService {
         table_ident: &MetastoreTableIdent,
         data: Bytes,
         file_name: &str,
-        format: Format,
+        format: UploadFormat,
+        query_context: QueryContext,
     ) -> Result<usize> {
         // TODO: is there a way to avoid temp table approach altogether?
         // File upload works as follows:
@@ -725,100 +922,302 @@ impl ExecutionService for CoreExecutionService {
         // 2. Create a temporary table in memory
         // 3. Use Execution service to insert data into the target table from the temporary table
         // 4. Drop the temporary table
+        let user_session = self.get_session(session_id).await?;
+        let source_table = register_upload_source(&user_session)?;
+        let target_table = upload_target(table_ident);
+        // If target table already exists, we need to insert into it
+        // otherwise, we need to create it
+        let exists = user_session
+            .ctx
+            .table_exist(target_table.clone())
+            .context(ex_error::DataFusionSnafu)?;
 
-        // use unique name to support simultaneous uploads
-        let unique_id = Uuid::new_v4().to_string().replace('-', "_");
-        let user_session = {
-            let sessions = self.df_sessions.read().await;
-            sessions
-                .get(session_id)
-                .ok_or_else(|| {
-                    ex_error::MissingDataFusionSessionSnafu {
-                        id: session_id.to_string(),
-                    }
-                    .build()
-                })?
-                .clone()
+        let (schema, batches) = match format {
+            UploadFormat::Csv(format) => {
+                let schema = if exists {
+                    let table = user_session
+                        .ctx
+                        .table(target_table)
+                        .await
+                        .context(ex_error::DataFusionSnafu)?;
+                    table.schema().as_arrow().to_owned()
+                } else {
+                    let (schema, _) = format
+                        .infer_schema(data.clone().reader(), None)
+                        .context(ex_error::ArrowSnafu)?;
+                    schema
+                };
+                let schema = Arc::new(schema);
+
+                // Here we create an arrow CSV reader that infers the schema from the entire
+                // dataset (as `None` is passed for the number of rows) and then builds a
+                // record batch
+                // TODO: This partially duplicates what Datafusion does with
+                // `CsvFormat::infer_schema`
+                let csv = ReaderBuilder::new(schema.clone())
+                    .with_format(format)
+                    .build_buffered(data.reader())
+                    .context(ex_error::ArrowSnafu)?;
+
+                let batches: std::result::Result<Vec<_>, _> = csv.collect();
+                (schema, batches.context(ex_error::ArrowSnafu)?)
+            }
+            // Avro and ORC files carry their schema, the insert casts the values to the
+            // types of an existing table
+            UploadFormat::Avro => read_avro(data)?,
+            UploadFormat::Orc => read_orc(data)?,
         };
 
-        let source_table =
-            TableReference::full("tmp_db", "tmp_schema", format!("tmp_table_{unique_id}"));
-        let target_table = TableReference::full(
-            table_ident.database.clone(),
-            table_ident.schema.clone(),
-            table_ident.table.clone(),
-        );
-        let inmem_catalog = MemoryCatalogProvider::new();
-        inmem_catalog
-            .register_schema(
-                source_table.schema().unwrap_or_default(),
-                Arc::new(MemorySchemaProvider::new()),
-            )
-            .context(ex_error::DataFusionSnafu)?;
-        user_session.ctx.register_catalog(
-            source_table.catalog().unwrap_or_default(),
-            Arc::new(inmem_catalog),
-        );
-        // If target table already exists, we need to insert into it
-        // otherwise, we need to create it
+        let table = MemTable::try_new(schema, vec![batches]).context(ex_error::DataFusionSnafu)?;
+        load_upload_source(
+            &user_session,
+            table_ident,
+            &source_table,
+            Arc::new(table),
+            exists,
+            query_context,
+        )
+        .await
+    }
+
+    #[tracing::instrument(
+        name = "ExecutionService::upload_files_to_table",
+        level = "debug",
+        skip(self, files),
+        err,
+        ret
+    )]
+    async fn upload_files_to_table(
+        &self,
+        session_id: &str,
+        table_ident: &MetastoreTableIdent,
+        files: &[UploadedFile],
+        format: FileFormatOptions,
+        create_table: bool,
+        query_context: QueryContext,
+    ) -> Result<Vec<UploadedFileResult>> {
+        // Rewriting a file means reading all of it into memory
+        if format.rewrite().is_some() {
+            return ex_error::UploadFailedSnafu {
+                message: format!(
+                    "{} files with these format options can only be loaded from a stage",
+                    format.name()
+                ),
+            }
+            .fail();
+        }
+        let user_session = self.get_session(session_id).await?;
+        let source_table = register_upload_source(&user_session)?;
+        let target_table = upload_target(table_ident);
         let exists = user_session
             .ctx
             .table_exist(target_table.clone())
             .context(ex_error::DataFusionSnafu)?;
+        if !exists && !create_table {
+            return ex_error::TableNotFoundSnafu {
+                schema: table_ident.schema.clone(),
+                table: table_ident.table.clone(),
+            }
+            .fail();
+        }
+        let Some(first_file) = files.first() else {
+            return Ok(Vec::new());
+        };
 
+        let listing_options = |file: &UploadedFile| -> Result<(ListingTableUrl, ListingOptions)> {
+            let url = ListingTableUrl::parse(file.path.to_string_lossy())
+                .context(ex_error::DataFusionSnafu)?;
+            let options = ListingOptions::new(format.listing_format(&file.file_name)?)
+                .with_file_extension("");
+            Ok((url, options))
+        };
+        // Files are read with the schema of an existing table: CSV columns by position,
+        // the columns of other formats by name. A new table takes the schema of the first
+        // file.
         let schema = if exists {
-            let table = user_session
+            user_session
                 .ctx
-                .table(target_table)
+                .table_provider(target_table)
                 .await
-                .context(ex_error::DataFusionSnafu)?;
-            table.schema().as_arrow().to_owned()
+                .context(ex_error::DataFusionSnafu)?
+                .schema()
         } else {
-            let (schema, _) = format
-                .infer_schema(data.clone().reader(), None)
-                .context(ex_error::ArrowSnafu)?;
-            schema
+            let (url, options) = listing_options(first_file)?;
+            options
+                .infer_schema(&user_session.ctx.state(), &url)
+                .await
+                .context(ex_error::DataFusionSnafu)?
         };
-        let schema = Arc::new(schema);
-
-        // Here we create an arrow CSV reader that infers the schema from the entire dataset
-        // (as `None` is passed for the number of rows) and then builds a record batch
-        // TODO: This partially duplicates what Datafusion does with `CsvFormat::infer_schema`
-        let csv = ReaderBuilder::new(schema.clone())
-            .with_format(format)
-            .build_buffered(data.reader())
-            .context(ex_error::ArrowSnafu)?;
 
-        let batches: std::result::Result<Vec<_>, _> = csv.collect();
-        let batches = batches.context(ex_error::ArrowSnafu)?;
-
-        let rows_loaded = batches
+        // The files are loaded by a single insert, so that either all of them or none are
+        // loaded. Each file is a partition of the source that counts the rows read from it.
+        let mut partitions: Vec<Arc<dyn PartitionStream>> = Vec::with_capacity(files.len());
+        let mut rows_read = Vec::with_capacity(files.len());
+        for file in files {
+            let (url, options) = listing_options(file)?;
+            let config = ListingTableConfig::new(url)
+                .with_listing_options(options)
+                .with_schema(schema.clone());
+            let source = ListingTable::try_new(config).context(ex_error::DataFusionSnafu)?;
+            let stream = user_session
+                .ctx
+                .read_table(Arc::new(source))
+                .context(ex_error::DataFusionSnafu)?
+                .execute_stream()
+                .await
+                .context(ex_error::DataFusionSnafu)?;
+            let rows = Arc::new(AtomicUsize::new(0));
+            let file_rows = Arc::clone(&rows);
+            let stream = RecordBatchStreamAdapter::new(
+                stream.schema(),
+                stream.inspect_ok(move |batch| {
+                    file_rows.fetch_add(batch.num_rows(), Ordering::Relaxed);
+                }),
+            );
+            partitions.push(Arc::new(OnceStream::new(Box::pin(stream))));
+            rows_read.push(rows);
+        }
+        let source =
+            StreamingTable::try_new(schema, partitions).context(ex_error::DataFusionSnafu)?;
+        load_upload_source(
+            &user_session,
+            table_ident,
+            &source_table,
+            Arc::new(source),
+            exists,
+            query_context,
+        )
+        .await?;
+        Ok(files
             .iter()
-            .map(|batch: &RecordBatch| batch.num_rows())
-            .sum();
-
-        let table = MemTable::try_new(schema, vec![batches]).context(ex_error::DataFusionSnafu)?;
-        user_session
-            .ctx
-            .register_table(source_table.clone(), Arc::new(table))
-            .context(ex_error::DataFusionSnafu)?;
+            .zip(rows_read)
+            .map(|(file, rows)| UploadedFileResult {
+                file_name: file.file_name.clone(),
+                rows_loaded: rows.load(Ordering::Relaxed),
+            })
+            .collect())
+    }
 
-        let table = source_table.clone();
-        let query = if exists {
-            format!("INSERT INTO {table_ident} SELECT * FROM {table}")
-        } else {
-            format!("CREATE TABLE {table_ident} AS SELECT * FROM {table}")
-        };
+    #[tracing::instrument(
+        name = "ExecutionService::upload_stage_file",
+        level = "debug",
+        skip(self, data),
+        err
+    )]
+    async fn upload_stage_file(
+        &self,
+        stage: &StageIdent,
+        path: &str,
+        mut data: BoxStream<'static, std::io::Result<Bytes>>,
+        overwrite: bool,
+    ) -> Result<()> {
+        let store = self.internal_stage_object_store(stage).await?;
+        let location = ObjectPath::from(path);
+        if !overwrite {
+            match store.head(&location).await {
+                Ok(_) => return Ok(()),
+                Err(object_store::Error::NotFound { .. }) => {}
+                Err(error) => return Err(error).context(ex_error::ObjectStoreSnafu),
+            }
+        }
+        // Parts are uploaded as they fill up, a failed upload leaves no file behind
+        let mut writer = BufWriter::new(store, location);
+        let written = async {
+            while let Some(chunk) = data.next().await {
+                let chunk = chunk.context(ex_error::StageFileWriteSnafu { path })?;
+                writer
+                    .put(chunk)
+                    .await
+                    .context(ex_error::ObjectStoreSnafu)?;
+            }
+            writer
+                .shutdown()
+                .await
+                .context(ex_error::StageFileWriteSnafu { path })
+        }
+        .await;
+        if written.is_err()
+            && let Err(error) = writer.abort().await
+        {
+            tracing::warn!("Failed to abort the upload of stage file {path}: {error}");
+        }
+        written
+    }
 
-        let mut query = user_session.query(&query, QueryContext::default());
-        Box::pin(query.execute()).await?;
+    #[tracing::instrument(
+        name = "ExecutionService::download_stage_file",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn download_stage_file(&self, stage: &StageIdent, path: &str) -> Result<Bytes> {
+        let store = self.internal_stage_object_store(stage).await?;
+        store
+            .get(&ObjectPath::from(path))
+            .await
+            .context(ex_error::ObjectStoreSnafu)?
+            .bytes()
+            .await
+            .context(ex_error::ObjectStoreSnafu)
+    }
 
-        user_session
-            .ctx
-            .deregister_table(source_table)
-            .context(ex_error::DataFusionSnafu)?;
+    #[tracing::instrument(
+        name = "ExecutionService::refresh_external_tables",
+        level = "debug",
+        skip(self),
+        err
+    )]
+    async fn refresh_external_tables(&self) -> Result<usize> {
+        let mut tables = Vec::new();
+        for database in self
+            .metastore
+            .list_databases()
+            .await
+            .context(ex_error::MetastoreSnafu)?
+        {
+            for schema in self
+                .metastore
+                .list_schemas(&database.ident)
+                .await
+                .context(ex_error::MetastoreSnafu)?
+            {
+                tables.extend(
+                    self.metastore
+                        .list_external_tables(&schema.ident)
+                        .await
+                        .context(ex_error::MetastoreSnafu)?
+                        .into_iter()
+                        .filter(|table| table.auto_refresh)
+                        .map(|table| table.data.ident),
+                );
+            }
+        }
+        if tables.is_empty() {
+            return Ok(0);
+        }
 
-        Ok(rows_loaded)
+        let session_id = format!("external_tables_refresh_{}", Uuid::new_v4().simple());
+        let user_session = self.create_internal_session(&session_id).await?;
+        let mut refreshed = 0;
+        for ident in tables {
+            let name = ObjectName::from(
+                [&ident.database, &ident.schema, &ident.table]
+                    .map(|part| Ident::with_quote('"', part))
+                    .to_vec(),
+            );
+            let mut query = user_session.query(
+                format!("ALTER EXTERNAL TABLE {name} REFRESH"),
+                QueryContext::default(),
+            );
+            match Box::pin(query.execute()).await {
+                Ok(_) => refreshed += 1,
+                // A table that fails to refresh, e.g. as its stage was dropped, doesn't
+                // hold up the others
+                Err(error) => tracing::warn!("Failed to refresh external table {ident}: {error}"),
+            }
+        }
+        self.delete_session(&session_id).await?;
+        Ok(refreshed)
     }
 
     async fn timeout_signal(&self, interval: Duration, idle_timeout: Duration) -> () {
@@ -853,6 +1252,120 @@ impl ExecutionService for CoreExecutionService {
     }
 }
 
+/// Registers the in-memory catalog of the temporary table uploaded data is read from
+/// while it is loaded, the table name is unique to support simultaneous uploads
+fn register_upload_source(user_session: &UserSession) -> Result<TableReference> {
+    let unique_id = Uuid::new_v4().to_string().replace('-', "_");
+    let source_table =
+        TableReference::full("tmp_db", "tmp_schema", format!("tmp_table_{unique_id}"));
+    let inmem_catalog = MemoryCatalogProvider::new();
+    inmem_catalog
+        .register_schema(
+            source_table.schema().unwrap_or_default(),
+            Arc::new(MemorySchemaProvider::new()),
+        )
+        .context(ex_error::DataFusionSnafu)?;
+    user_session.ctx.register_catalog(
+        source_table.catalog().unwrap_or_default(),
+        Arc::new(inmem_catalog),
+    );
+    Ok(source_table)
+}
+
+fn upload_target(table_ident: &MetastoreTableIdent) -> TableReference {
+    TableReference::full(
+        table_ident.database.clone(),
+        table_ident.schema.clone(),
+        table_ident.table.clone(),
+    )
+}
+
+/// Name of a table in SQL. The parts are quoted, as they name the table exactly.
+fn quoted_table_name(table: &TableReference) -> ObjectName {
+    ObjectName::from(
+        table
+            .to_vec()
+            .iter()
+            .map(|part| Ident::with_quote('"', part))
+            .collect::<Vec<_>>(),
+    )
+}
+
+/// Inserts the rows of `source` into the target table with a single `INSERT`, creating
+/// the table from the schema of the source first unless it `exists`. The source is
+/// read once, under the name of the registered upload source. Returns the number of
+/// rows loaded.
+async fn load_upload_source(
+    user_session: &Arc<UserSession>,
+    table_ident: &MetastoreTableIdent,
+    source_table: &TableReference,
+    source: Arc<dyn TableProvider>,
+    exists: bool,
+    query_context: QueryContext,
+) -> Result<usize> {
+    let target = quoted_table_name(&upload_target(table_ident));
+    let source_name = quoted_table_name(source_table);
+    if !exists {
+        let empty =
+            MemTable::try_new(source.schema(), vec![vec![]]).context(ex_error::DataFusionSnafu)?;
+        execute_upload_query(
+            user_session,
+            source_table,
+            Arc::new(empty),
+            format!("CREATE TABLE {target} AS SELECT * FROM {source_name}"),
+            query_context.clone(),
+        )
+        .await?;
+    }
+    let records = execute_upload_query(
+        user_session,
+        source_table,
+        source,
+        format!("INSERT INTO {target} SELECT * FROM {source_name}"),
+        query_context,
+    )
+    .await?;
+    inserted_rows(&records)
+}
+
+/// Runs a query with `source` registered as the upload source for its duration
+async fn execute_upload_query(
+    user_session: &Arc<UserSession>,
+    source_table: &TableReference,
+    source: Arc<dyn TableProvider>,
+    query: String,
+    query_context: QueryContext,
+) -> Result<Vec<RecordBatch>> {
+    user_session
+        .ctx
+        .register_table(source_table.clone(), source)
+        .context(ex_error::DataFusionSnafu)?;
+    let mut query = user_session.query(query, query_context);
+    let result = Box::pin(query.execute()).await;
+    user_session
+        .ctx
+        .deregister_table(source_table.clone())
+        .context(ex_error::DataFusionSnafu)?;
+    Ok(result?.records)
+}
+
+/// Rows inserted by an `INSERT` statement, from the `count` column of its result
+fn inserted_rows(records: &[RecordBatch]) -> Result<usize> {
+    let mut rows = 0;
+    for batch in records {
+        let Some(count) = batch.column_by_name("count") else {
+            continue;
+        };
+        let count = cast(count, &DataType::UInt64).context(ex_error::ArrowSnafu)?;
+        rows += count
+            .as_primitive::<UInt64Type>()
+            .iter()
+            .flatten()
+            .sum::<u64>();
+    }
+    Ok(usize::try_from(rows).unwrap_or(usize::MAX))
+}
+
 //Test environment
 #[allow(clippy::expect_used)]
 pub async fn make_test_execution_svc() -> Arc<CoreExecutionService> {
@@ -14,8 +14,13 @@ use crate::models::QueryContext;
 use crate::query::UserQuery;
 use crate::query_types::QueryId;
 use crate::running_queries::RunningQueries;
+#[cfg(feature = "state-store-query")]
+use crate::running_queries::StatementTimeRange;
+use crate::transaction::SessionTransactions;
 use crate::utils::Config;
 use catalog::catalog_list::{DEFAULT_CATALOG, EmbucketCatalogList};
+use catalog::information_schema::copy_history::CopyHistoryFunc;
+use catalog::information_schema::external_table_files::ExternalTableFilesFunc;
 use catalog_metastore::Metastore;
 #[cfg(feature = "state-store")]
 use chrono::{TimeZone, Utc};
@@ -66,6 +71,13 @@ pub struct UserSession {
     pub recent_queries: Arc<RwLock<VecDeque<QueryId>>>,
     pub session_id: String,
     pub attrs: DashMap<String, String>,
+    pub transactions: SessionTransactions,
+    /// Role switched to with `USE ROLE`, kept apart from the session variables so that
+    /// `SET` and `ALTER SESSION` can't change it
+    role: RwLock<Option<String>>,
+    /// Set for the sessions the executor opens itself, such as the one refreshing
+    /// external tables
+    internal: bool,
 }
 
 impl UserSession {
@@ -140,6 +152,14 @@ impl UserSession {
         register_udfs(&mut ctx, &session_params_arc).context(ex_error::RegisterUDFSnafu)?;
         register_udafs(&mut ctx).context(ex_error::RegisterUDAFSnafu)?;
         register_udtfs(&ctx);
+        ctx.register_udtf(
+            "copy_history",
+            Arc::new(CopyHistoryFunc::new(metastore.clone())),
+        );
+        ctx.register_udtf(
+            "external_table_files",
+            Arc::new(ExternalTableFilesFunc::new(metastore.clone())),
+        );
         register_json_udfs(&mut ctx).context(ex_error::RegisterUDFSnafu)?;
         //register_geo_native(&ctx);
         //register_geo_udfs(&ctx);
@@ -163,6 +183,9 @@ impl UserSession {
             recent_queries: Arc::new(RwLock::new(VecDeque::new())),
             session_id: session_id.to_string(),
             attrs: DashMap::new(),
+            transactions: SessionTransactions::default(),
+            role: RwLock::new(None),
+            internal: false,
         };
         Ok(session)
     }
@@ -254,12 +277,43 @@ impl UserSession {
         UserQuery::new(self.clone(), query.into(), query_context)
     }
 
+    /// Role switched to with `USE ROLE`, `None` until the session switches roles
+    #[must_use]
+    pub fn role(&self) -> Option<String> {
+        self.role.read().ok().and_then(|role| role.clone())
+    }
+
+    /// Switches the role of the session, the role must be checked by the caller
+    pub fn set_role(&self, role: String) {
+        if let Ok(mut guard) = self.role.write() {
+            *guard = Some(role);
+        }
+    }
+
+    /// Marks the session as internal to the executor. Internal sessions run as
+    /// `ACCOUNTADMIN` until they switch roles, other sessions without a user kept in the
+    /// metastore run as `PUBLIC`.
+    #[must_use]
+    pub fn with_internal(self, internal: bool) -> Self {
+        Self { internal, ..self }
+    }
+
+    #[must_use]
+    pub const fn is_internal(&self) -> bool {
+        self.internal
+    }
+
     #[allow(clippy::unused_async)]
     pub async fn set_session_variable(
         &self,
         set: bool,
         params: HashMap<String, SessionProperty>,
     ) -> Result<()> {
+        // The role only changes through `USE ROLE`, which checks it is granted
+        if params.keys().any(|key| key.eq_ignore_ascii_case("role")) {
+            return ex_error::RoleSessionVariableSnafu.fail();
+        }
+
         #[cfg(feature = "state-store")]
         self.set_session_state_params(set, params.clone()).await?;
 
@@ -327,6 +381,23 @@ impl UserSession {
             }
         }
     }
+
+    /// Start and end time of a statement from the persisted query history, `None` if
+    /// the history has no such statement
+    #[cfg(feature = "state-store-query")]
+    pub async fn persisted_time_range(
+        &self,
+        query_id: QueryId,
+    ) -> Result<Option<StatementTimeRange>> {
+        match self.state_store.get_query(&query_id.to_string()).await {
+            Ok(query) => Ok(Some(StatementTimeRange {
+                started_at: query.start_time,
+                finished_at: query.end_time,
+            })),
+            Err(state_store::Error::NotFound) => Ok(None),
+            Err(error) => Err(error).context(ex_error::StateStoreSnafu),
+        }
+    }
 }
 
 #[must_use]
@@ -33,6 +33,11 @@ pub enum SnowflakeError {
         error: SqlCompilationError,
         error_code: ErrorCode,
     },
+    #[snafu(display("SQL access control error:\n{error}"))]
+    SqlAccessControl {
+        error: String,
+        error_code: ErrorCode,
+    },
     #[snafu(display("{message}"))]
     Custom {
         message: String,
@@ -49,6 +54,7 @@ impl SnowflakeError {
     pub const fn error_code(&self) -> ErrorCode {
         match self {
             Self::SqlCompilation { error_code, .. } => *error_code,
+            Self::SqlAccessControl { error_code, .. } => *error_code,
             Self::Custom { error_code, .. } => *error_code,
         }
     }
@@ -56,7 +62,7 @@ impl SnowflakeError {
     pub fn unhandled_location(&self) -> String {
         match self {
             Self::Custom { location, .. } => location.to_string(),
-            Self::SqlCompilation { .. } => String::new(),
+            Self::SqlCompilation { .. } | Self::SqlAccessControl { .. } => String::new(),
         }
     }
 }
@@ -78,6 +84,9 @@ pub enum Entity {
     Database,
     Schema,
     Table,
+    Stage,
+    #[strum(serialize = "File format")]
+    FileFormat,
 }
 
 #[derive(Snafu, Debug)]
@@ -188,6 +197,7 @@ pub fn executor_error(error: &Error) -> SnowflakeError {
         | Error::DropDatabase { source, .. }
         | Error::CreateDatabase { source, .. } => catalog_error(source, &[]),
         Error::QueryExecution { source, .. } => executor_error(source),
+        Error::KeptQueryResult { source, .. } => executor_error(source),
         Error::TableNotFoundInSchemaInDatabase {
             operation_on,
             table,
@@ -217,6 +227,24 @@ pub fn executor_error(error: &Error) -> SnowflakeError {
             .build(),
             error_code: ErrorCode::EntityNotFound(Entity::Schema, *operation_on),
         },
+        Error::StageNotFound { stage, .. } => SnowflakeError::SqlCompilation {
+            error: EntityDoesntExistSnafu {
+                operation_on: OperationOn::Unknown,
+                entity_name: stage,
+                entity_type: Entity::Stage,
+            }
+            .build(),
+            error_code: ErrorCode::EntityNotFound(Entity::Stage, OperationOn::Unknown),
+        },
+        Error::FileFormatNotFound { file_format, .. } => SnowflakeError::SqlCompilation {
+            error: EntityDoesntExistSnafu {
+                operation_on: OperationOn::Unknown,
+                entity_name: file_format,
+                entity_type: Entity::FileFormat,
+            }
+            .build(),
+            error_code: ErrorCode::EntityNotFound(Entity::FileFormat, OperationOn::Unknown),
+        },
         Error::DatabaseNotFound { db: catalog, .. } => {
             SnowflakeError::SqlCompilation {
                 error: EntityDoesntExistSnafu {
@@ -245,11 +273,17 @@ pub fn executor_error(error: &Error) -> SnowflakeError {
             .build(),
             error_code: ErrorCode::EntityNotFound(Entity::Database, *operation_on),
         },
+        Error::InsufficientPrivileges { .. } => SnowflakeError::SqlAccessControl {
+            error: message,
+            error_code: ErrorCode::InsufficientPrivileges,
+        },
         Error::NotSupportedStatement { statement, .. } => SnowflakeError::SqlCompilation {
             error: CompilationUnsupportedFeatureSnafu { error: statement }.build(),
             error_code: ErrorCode::UnsupportedFeature,
         },
         Error::Arrow { .. }
+        | Error::Parquet { .. }
+        | Error::Orc { .. }
         | Error::SerdeParse { .. }
         | Error::CatalogListDowncast { .. }
         | Error::CatalogDownCast { .. }
@@ -0,0 +1,314 @@
+use crate::error::{self as ex_error, Result};
+use catalog_metastore::{Metastore, VolumeIdent};
+use object_store::ObjectStore;
+use object_store::prefix::PrefixStore;
+use snafu::{OptionExt, ResultExt};
+use sqlparser::ast::ObjectName;
+use sqlparser::dialect::SnowflakeDialect;
+use sqlparser::parser::{Parser, ParserError};
+use sqlparser::tokenizer::Token;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+type ParseResult<T> = std::result::Result<T, ParserError>;
+
+/// Stage file command: `LIST`, `REMOVE`, `PUT` or `GET`.
+///
+/// These are parsed here rather than by the SQL parser, which splits stage paths
+/// containing dots into separate identifiers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StageStatement {
+    List {
+        stage: StageReference,
+        pattern: Option<String>,
+    },
+    Remove {
+        stage: StageReference,
+        pattern: Option<String>,
+    },
+    /// `PUT file://<path> @stage`, the files are uploaded by the client
+    Put {
+        source: String,
+        stage: StageReference,
+        options: FileTransferOptions,
+    },
+    /// `GET @stage file://<directory>`, the files are downloaded by the client
+    Get {
+        stage: StageReference,
+        target: String,
+        pattern: Option<String>,
+        options: FileTransferOptions,
+    },
+}
+
+/// Options of `PUT` and `GET`, passed on to the client doing the transfer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileTransferOptions {
+    pub parallel: u32,
+    pub auto_compress: bool,
+    pub source_compression: String,
+    pub overwrite: bool,
+}
+
+impl Default for FileTransferOptions {
+    fn default() -> Self {
+        Self {
+            parallel: 4,
+            auto_compress: true,
+            source_compression: "AUTO_DETECT".to_string(),
+            overwrite: false,
+        }
+    }
+}
+
+/// Reference to files of a named stage: `@[database.][schema.]stage[/path]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StageReference {
+    pub name: ObjectName,
+    /// Path inside of the stage, without leading and trailing slashes
+    pub path: String,
+}
+
+impl StageReference {
+    /// Parses a stage reference such as `@my_stage/path/file.csv`.
+    pub fn parse(reference: &str) -> ParseResult<Self> {
+        let Some(reference) = reference.strip_prefix('@') else {
+            return Err(ParserError::ParserError(format!(
+                "Expected a stage reference, found: {reference}"
+            )));
+        };
+        if reference.starts_with('~') || reference.starts_with('%') {
+            return Err(ParserError::ParserError(format!(
+                "User and table stages are not supported: @{reference}"
+            )));
+        }
+        // The name ends at the first slash outside of a quoted identifier
+        let mut quoted = false;
+        let name_end = reference
+            .char_indices()
+            .find(|&(_, c)| {
+                if c == '"' {
+                    quoted = !quoted;
+                }
+                c == '/' && !quoted
+            })
+            .map_or(reference.len(), |(index, _)| index);
+        let (name, path) = reference.split_at(name_end);
+        let invalid_name = || ParserError::ParserError(format!("Invalid stage name: @{name}"));
+        let mut parser = Parser::new(&SnowflakeDialect).try_with_sql(name)?;
+        // ParserError has no source, so the message of the parser is kept in its text
+        let name = parser.parse_object_name(false).map_err(|error| {
+            ParserError::ParserError(format!("Invalid stage name: @{name}: {error}"))
+        })?;
+        if parser.peek_token().token != Token::EOF
+            || name.0.is_empty()
+            || name.0.len() > 3
+            || name.0.iter().any(|part| part.to_string().is_empty())
+        {
+            return Err(invalid_name());
+        }
+        Ok(Self {
+            name,
+            path: path.trim_matches('/').to_string(),
+        })
+    }
+}
+
+// Cheap check to avoid tokenizing every statement twice
+fn looks_like_stage_statement(sql: &str) -> bool {
+    let mut words = sql.split_whitespace();
+    match words.next().map(str::to_ascii_uppercase).as_deref() {
+        Some("LIST" | "LS" | "REMOVE" | "RM" | "GET") => {
+            words.next().is_some_and(|word| word.starts_with('@'))
+        }
+        Some("PUT") => words.next().is_some_and(|word| {
+            word.get(..8)
+                .is_some_and(|scheme| scheme.eq_ignore_ascii_case("'file://"))
+                || word
+                    .get(..7)
+                    .is_some_and(|scheme| scheme.eq_ignore_ascii_case("file://"))
+        }),
+        _ => false,
+    }
+}
+
+/// Splits off the next whitespace separated argument, which may be single quoted
+fn next_argument(sql: &str) -> ParseResult<(String, &str)> {
+    let sql = sql.trim_start();
+    if let Some(quoted) = sql.strip_prefix('\'') {
+        let end = quoted.find('\'').ok_or_else(|| {
+            ParserError::ParserError(format!("Unterminated string literal: '{quoted}"))
+        })?;
+        return Ok((quoted[..end].to_string(), &quoted[end + 1..]));
+    }
+    let end = sql
+        .find(|c: char| c.is_whitespace() || c == ';')
+        .unwrap_or(sql.len());
+    Ok((sql[..end].to_string(), &sql[end..]))
+}
+
+/// Local path of a `PUT` or `GET` argument, which has to be a `file://` url
+fn local_path(argument: &str) -> ParseResult<String> {
+    argument
+        .get(..7)
+        .filter(|scheme| scheme.eq_ignore_ascii_case("file://"))
+        .map(|_| argument[7..].to_string())
+        .ok_or_else(|| {
+            ParserError::ParserError(format!("Expected a file:// location, found: {argument}"))
+        })
+}
+
+/// Parses `KEY = value` options, keyed by their uppercase names
+fn parse_options(sql: &str) -> ParseResult<BTreeMap<String, String>> {
+    let dialect = SnowflakeDialect;
+    let mut parser = Parser::new(&dialect).try_with_sql(sql)?;
+    let mut options = BTreeMap::new();
+    loop {
+        while parser.consume_token(&Token::SemiColon) {}
+        if parser.peek_token().token == Token::EOF {
+            return Ok(options);
+        }
+        let key = parser.parse_identifier()?.value.to_ascii_uppercase();
+        parser.expect_token(&Token::Eq)?;
+        let value = match parser.next_token().token {
+            Token::SingleQuotedString(value) => value,
+            Token::Word(word) => word.value,
+            Token::Number(number, _) => number,
+            token => {
+                return Err(ParserError::ParserError(format!(
+                    "Expected a value for {key}, found: {token}"
+                )));
+            }
+        };
+        options.insert(key, value);
+    }
+}
+
+fn take_bool(options: &mut BTreeMap<String, String>, key: &str, default: bool) -> bool {
+    options
+        .remove(key)
+        .map_or(default, |value| value.eq_ignore_ascii_case("TRUE"))
+}
+
+fn file_transfer_options(
+    options: &mut BTreeMap<String, String>,
+) -> ParseResult<FileTransferOptions> {
+    let defaults = FileTransferOptions::default();
+    let parallel = match options.remove("PARALLEL") {
+        Some(parallel) => parallel
+            .parse()
+            .ok()
+            .filter(|parallel| (1..=99).contains(parallel))
+            .ok_or_else(|| {
+                ParserError::ParserError(format!("Invalid value for PARALLEL: {parallel}"))
+            })?,
+        None => defaults.parallel,
+    };
+    Ok(FileTransferOptions {
+        parallel,
+        auto_compress: take_bool(options, "AUTO_COMPRESS", defaults.auto_compress),
+        source_compression: options
+            .remove("SOURCE_COMPRESSION")
+            .map_or(defaults.source_compression, |compression| {
+                compression.to_ascii_uppercase()
+            }),
+        overwrite: take_bool(options, "OVERWRITE", defaults.overwrite),
+    })
+}
+
+/// Parses a stage file command, returns `None` if the sql is a different statement.
+pub fn parse_stage_statement(sql: &str) -> ParseResult<Option<StageStatement>> {
+    if !looks_like_stage_statement(sql) {
+        return Ok(None);
+    }
+    let (command, rest) = next_argument(sql)?;
+    let (first, rest) = next_argument(rest)?;
+    let command = command.to_ascii_uppercase();
+
+    let mut options;
+    let statement = match command.as_str() {
+        "PUT" => {
+            let (stage, rest) = next_argument(rest)?;
+            options = parse_options(rest)?;
+            StageStatement::Put {
+                source: local_path(&first)?,
+                stage: StageReference::parse(&stage)?,
+                options: file_transfer_options(&mut options)?,
+            }
+        }
+        "GET" => {
+            let (target, rest) = next_argument(rest)?;
+            options = parse_options(rest)?;
+            StageStatement::Get {
+                stage: StageReference::parse(&first)?,
+                target: local_path(&target)?,
+                pattern: options.remove("PATTERN"),
+                options: file_transfer_options(&mut options)?,
+            }
+        }
+        "LIST" | "LS" => {
+            options = parse_options(rest)?;
+            StageStatement::List {
+                stage: StageReference::parse(&first)?,
+                pattern: options.remove("PATTERN"),
+            }
+        }
+        _ => {
+            options = parse_options(rest)?;
+            StageStatement::Remove {
+                stage: StageReference::parse(&first)?,
+                pattern: options.remove("PATTERN"),
+            }
+        }
+    };
+    if let Some(option) = options.keys().next() {
+        return Err(ParserError::ParserError(format!(
+            "Unsupported option: {option}"
+        )));
+    }
+    Ok(Some(statement))
+}
+
+/// Object store of the files under `path` of a volume
+pub async fn volume_stage_object_store(
+    metastore: &dyn Metastore,
+    volume: &VolumeIdent,
+    path: &str,
+) -> Result<Arc<dyn ObjectStore>> {
+    let store = metastore
+        .volume_object_store(volume)
+        .await
+        .context(ex_error::MetastoreSnafu)?
+        .context(ex_error::VolumeNotFoundSnafu {
+            volume: volume.clone(),
+        })?;
+    if path.is_empty() {
+        Ok(store)
+    } else {
+        Ok(Arc::new(PrefixStore::new(store, path)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StageReference;
+
+    #[test]
+    #[allow(clippy::unwrap_used)]
+    fn test_parse_stage_reference() {
+        let reference = StageReference::parse("@db.public.my_stage/path/file.csv").unwrap();
+        assert_eq!(reference.name.to_string(), "db.public.my_stage");
+        assert_eq!(reference.path, "path/file.csv");
+
+        // dots and slashes inside of quoted identifiers are part of the name
+        let reference = StageReference::parse(r#"@"my.db"."a/b".stage/dir/"#).unwrap();
+        assert_eq!(reference.name.0.len(), 3);
+        assert_eq!(reference.name.to_string(), r#""my.db"."a/b".stage"#);
+        assert_eq!(reference.path, "dir");
+
+        assert!(StageReference::parse("@a.b.c.d").is_err());
+        assert!(StageReference::parse("@db..stage").is_err());
+        assert!(StageReference::parse("@/path").is_err());
+        assert!(StageReference::parse("my_stage").is_err());
+    }
+}
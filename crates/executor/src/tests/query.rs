@@ -143,7 +143,8 @@ pub async fn create_df_session() -> Arc<UserSession> {
             state_store,
         )
         .await
-        .expect("Failed to create user session"),
+        .expect("Failed to create user session")
+        .with_internal(true),
     );
 
     for q in TABLE_SETUP.split(';') {
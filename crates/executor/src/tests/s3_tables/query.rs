@@ -50,7 +50,8 @@ pub async fn create_s3_tables_df_session() -> Arc<UserSession> {
             state_store,
         )
         .await
-        .expect("Failed to create user session"),
+        .expect("Failed to create user session")
+        .with_internal(true),
     )
 }
 
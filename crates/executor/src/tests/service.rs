@@ -1,4 +1,6 @@
-use crate::models::{QueryContext, QueryResult};
+use crate::ExecutionStatus;
+use crate::file_formats::{FileFormatOptions, UploadFormat, UploadedFile};
+use crate::models::{QueryContext, QueryResult, SessionMetadata, SessionMetadataAttr};
 use crate::service::{CoreExecutionService, ExecutionService};
 use crate::utils::Config;
 use catalog_metastore::InMemoryMetastore;
@@ -8,10 +10,27 @@ use catalog_metastore::{
     Volume as MetastoreVolume,
 };
 use catalog_metastore::{FileVolume, Metastore, VolumeType};
+use datafusion::arrow::array::{Array, RecordBatch};
+use datafusion::arrow::util::display::array_value_to_string;
 use datafusion::{arrow::csv::reader::Format, assert_batches_eq};
+use futures::TryStreamExt;
 use futures::future::join_all;
+use std::collections::{BTreeMap, BTreeSet};
 use std::sync::Arc;
 
+#[allow(clippy::expect_used)]
+fn column_values(records: &[RecordBatch], name: &str) -> Vec<String> {
+    records
+        .iter()
+        .flat_map(|batch| {
+            let column = batch.column_by_name(name).expect("Missing column");
+            (0..column.len())
+                .map(|row| array_value_to_string(column, row).expect("Failed to format value"))
+                .collect::<Vec<_>>()
+        })
+        .collect()
+}
+
 #[tokio::test]
 #[allow(clippy::expect_used)]
 async fn test_execute_always_returns_schema() {
@@ -21,7 +40,7 @@ async fn test_execute_always_returns_schema() {
         .expect("Failed to create execution service");
 
     execution_svc
-        .create_session("test_session_id")
+        .create_internal_session("test_session_id")
         .await
         .expect("Failed to create session");
 
@@ -95,11 +114,11 @@ async fn test_service_upload_file() {
 
     let session_id = "test_session_id";
     execution_svc
-        .create_session(session_id)
+        .create_internal_session(session_id)
         .await
         .expect("Failed to create session");
 
-    let csv_format = Format::default().with_header(true);
+    let csv_format = UploadFormat::Csv(Format::default().with_header(true));
     let rows_loaded = execution_svc
         .upload_data_to_table(
             session_id,
@@ -107,6 +126,7 @@ async fn test_service_upload_file() {
             data.clone().into(),
             file_name,
             csv_format.clone(),
+            QueryContext::default(),
         )
         .await
         .expect("Failed to upload file");
@@ -133,7 +153,14 @@ async fn test_service_upload_file() {
     );
 
     let rows_loaded = execution_svc
-        .upload_data_to_table(session_id, &table_ident, data.into(), file_name, csv_format)
+        .upload_data_to_table(
+            session_id,
+            &table_ident,
+            data.into(),
+            file_name,
+            csv_format,
+            QueryContext::default(),
+        )
         .await
         .expect("Failed to upload file");
     assert_eq!(rows_loaded, 3);
@@ -163,22 +190,13 @@ async fn test_service_upload_file() {
 }
 
 #[tokio::test]
-async fn test_service_create_table_file_volume() {
+#[allow(clippy::expect_used)]
+async fn test_service_upload_files_to_table() {
     let metastore = Arc::new(InMemoryMetastore::new());
-
-    // Create a temporary directory for the file volume
-    let temp_dir = std::env::temp_dir().join("test_file_volume");
-    let _ = std::fs::create_dir_all(&temp_dir);
-    let temp_path = temp_dir.to_str().expect("Failed to convert path to string");
     metastore
         .create_volume(
             &"test_volume".to_string(),
-            MetastoreVolume::new(
-                "test_volume".to_string(),
-                VolumeType::File(FileVolume {
-                    path: temp_path.to_string(),
-                }),
-            ),
+            MetastoreVolume::new("test_volume".to_string(), VolumeType::Memory),
         )
         .await
         .expect("Failed to create volume");
@@ -209,229 +227,2358 @@ async fn test_service_create_table_file_volume() {
         .await
         .expect("Failed to create schema");
 
+    let temp_dir = std::env::temp_dir().join("test_service_upload_files_to_table");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    std::fs::create_dir_all(&temp_dir).expect("Failed to create upload directory");
+    let files = [
+        ("a.csv", "id,name\n1,a\n2,b\n"),
+        ("b.csv", "id,name\n3,c\n"),
+    ]
+    .map(|(file_name, content)| {
+        let path = temp_dir.join(file_name);
+        std::fs::write(&path, content).expect("Failed to write file");
+        UploadedFile {
+            file_name: file_name.to_string(),
+            path,
+        }
+    });
+    let format = FileFormatOptions::parse(&BTreeMap::from([
+        ("TYPE".to_string(), "CSV".to_string()),
+        ("PARSE_HEADER".to_string(), "TRUE".to_string()),
+    ]))
+    .expect("Failed to parse file format")
+    .expect("Missing file format");
     let table_ident = MetastoreTableIdent {
         database: "embucket".to_string(),
         schema: "public".to_string(),
-        table: "target_table".to_string(),
+        table: "uploaded".to_string(),
     };
+
     let execution_svc = CoreExecutionService::new(metastore, Arc::new(Config::default()))
         .await
         .expect("Failed to create execution service");
-
     let session_id = "test_session_id";
     execution_svc
-        .create_session(session_id)
+        .create_internal_session(session_id)
         .await
         .expect("Failed to create session");
 
-    let create_table_sql = format!(
-        "CREATE TABLE {table_ident} (id INT, name STRING, value FLOAT) as VALUES (1, 'test1', 100.0), (2, 'test2', 200.0), (3, 'test3', 300.0)"
+    // The table is only created when asked to
+    assert!(
+        execution_svc
+            .upload_files_to_table(
+                session_id,
+                &table_ident,
+                &files,
+                format.clone(),
+                false,
+                QueryContext::default(),
+            )
+            .await
+            .is_err()
     );
-    let QueryResult { records, .. } = execution_svc
-        .query(session_id, &create_table_sql, QueryContext::default())
+    let results = execution_svc
+        .upload_files_to_table(
+            session_id,
+            &table_ident,
+            &files,
+            format.clone(),
+            true,
+            QueryContext::default(),
+        )
         .await
-        .expect("Failed to create table");
-
-    assert_batches_eq!(
-        &[
-            "+-------+",
-            "| count |",
-            "+-------+",
-            "| 3     |",
-            "+-------+",
-        ],
-        &records
+        .expect("Failed to upload files");
+    assert_eq!(
+        results
+            .iter()
+            .map(|result| (result.file_name.as_str(), result.rows_loaded))
+            .collect::<Vec<_>>(),
+        vec![("a.csv", 2), ("b.csv", 1)]
     );
+    // Files are appended to the existing table
+    let results = execution_svc
+        .upload_files_to_table(
+            session_id,
+            &table_ident,
+            &files[1..],
+            format.clone(),
+            false,
+            QueryContext::default(),
+        )
+        .await
+        .expect("Failed to upload files");
+    assert_eq!(results[0].rows_loaded, 1);
 
-    let insert_sql = format!(
-        "INSERT INTO {table_ident} (id, name, value) VALUES (4, 'test4', 400.0), (5, 'test5', 500.0)"
-    );
     let QueryResult { records, .. } = execution_svc
-        .query(session_id, &insert_sql, QueryContext::default())
+        .query(
+            session_id,
+            "SELECT name FROM uploaded ORDER BY id, name",
+            QueryContext::default(),
+        )
         .await
-        .expect("Failed to insert data");
+        .expect("Failed to execute query");
+    assert_eq!(column_values(&records, "name"), vec!["a", "b", "c", "c"]);
 
-    assert_batches_eq!(
-        &[
-            "+-------+",
-            "| count |",
-            "+-------+",
-            "| 2     |",
-            "+-------+",
-        ],
-        &records
+    // Files are loaded together, a file that fails leaves out the others as well
+    let bad_path = temp_dir.join("bad.csv");
+    std::fs::write(&bad_path, "id,name\nx,d\n").expect("Failed to write file");
+    let bad_files = [
+        files[0].clone(),
+        UploadedFile {
+            file_name: "bad.csv".to_string(),
+            path: bad_path,
+        },
+    ];
+    assert!(
+        execution_svc
+            .upload_files_to_table(
+                session_id,
+                &table_ident,
+                &bad_files,
+                format.clone(),
+                false,
+                QueryContext::default(),
+            )
+            .await
+            .is_err()
     );
-}
-
-#[tokio::test(flavor = "multi_thread")]
-#[allow(clippy::expect_used)]
-async fn test_max_concurrency_level() {
-    use tokio::sync::Barrier;
-
-    let metastore = Arc::new(InMemoryMetastore::new());
-    let execution_svc = Arc::new(
-        CoreExecutionService::new(
-            metastore.clone(),
-            Arc::new(Config::default().with_max_concurrency_level(2)),
+    let QueryResult { records, .. } = execution_svc
+        .query(
+            session_id,
+            "SELECT COUNT(*) AS rows FROM uploaded",
+            QueryContext::default(),
         )
         .await
-        .expect("Failed to create execution service"),
-    );
+        .expect("Failed to execute query");
+    assert_eq!(column_values(&records, "rows"), vec!["4"]);
 
-    let _session = execution_svc
-        .create_session("test_session_id")
+    // The table is named exactly as given
+    let table_ident = MetastoreTableIdent {
+        table: "Uploaded".to_string(),
+        ..table_ident
+    };
+    let results = execution_svc
+        .upload_files_to_table(
+            session_id,
+            &table_ident,
+            &files,
+            format,
+            true,
+            QueryContext::default(),
+        )
         .await
-        .expect("Failed to create session");
-
-    let barrier = Arc::new(Barrier::new(3)); // wait for 3 threads: 2 queries + main thread
-
-    // Reserve 2 permitted slots for the queries
-    for _ in 0..2 {
-        let svc = execution_svc.clone();
-        let barrier = barrier.clone();
-        tokio::spawn(async move {
-            let _ = svc
-                .submit(
-                    "test_session_id",
-                    "SELECT sleep(2)",
-                    QueryContext::default(),
-                )
-                .await;
-            barrier.wait().await;
-        });
-        // add delay as miliseconds granularity used for query_id is not enough
-        tokio::time::sleep(std::time::Duration::from_millis(2)).await;
-    }
-
-    let res = execution_svc
+        .expect("Failed to upload files");
+    assert_eq!(results.len(), 2);
+    let QueryResult { records, .. } = execution_svc
         .query(
-            "test_session_id",
-            "SELECT sleep(3)",
+            session_id,
+            "SELECT COUNT(*) AS rows FROM \"Uploaded\"",
             QueryContext::default(),
         )
-        .await;
-    assert!(
-        res.is_err(),
-        "Expected concurrency limit error but got {res:?}"
-    );
-
-    // Pass the barrier to allow the first two queries to finish
-    barrier.wait().await;
+        .await
+        .expect("Failed to execute query");
+    assert_eq!(column_values(&records, "rows"), vec!["3"]);
+    let _ = std::fs::remove_dir_all(&temp_dir);
 }
 
-#[tokio::test(flavor = "multi_thread")]
-#[allow(clippy::expect_used)]
-async fn test_max_concurrency_level2() {
+#[tokio::test]
+#[allow(clippy::expect_used, clippy::too_many_lines)]
+async fn test_service_alter_table_schema_evolution() {
     let metastore = Arc::new(InMemoryMetastore::new());
-    let execution_svc = Arc::new(
-        CoreExecutionService::new(
-            metastore.clone(),
-            Arc::new(Config::default().with_max_concurrency_level(2)),
+    metastore
+        .create_volume(
+            &"test_volume".to_string(),
+            MetastoreVolume::new("test_volume".to_string(), VolumeType::Memory),
         )
         .await
-        .expect("Failed to create execution service"),
-    );
+        .expect("Failed to create volume");
+    metastore
+        .create_database(
+            &"embucket".to_string(),
+            MetastoreDatabase {
+                ident: "embucket".to_string(),
+                properties: None,
+                volume: "test_volume".to_string(),
+                should_refresh: false,
+            },
+        )
+        .await
+        .expect("Failed to create database");
+    let schema_ident = MetastoreSchemaIdent {
+        database: "embucket".to_string(),
+        schema: "public".to_string(),
+    };
+    metastore
+        .create_schema(
+            &schema_ident.clone(),
+            MetastoreSchema {
+                ident: schema_ident,
+                properties: None,
+            },
+        )
+        .await
+        .expect("Failed to create schema");
 
-    let _session = execution_svc
-        .create_session("test_session_id")
+    let execution_svc = CoreExecutionService::new(metastore.clone(), Arc::new(Config::default()))
+        .await
+        .expect("Failed to create execution service");
+    let session_id = "test_session_id";
+    execution_svc
+        .create_internal_session(session_id)
         .await
         .expect("Failed to create session");
+    let run = |query: &'static str| {
+        let execution_svc = &execution_svc;
+        async move {
+            execution_svc
+                .query(session_id, query, QueryContext::default())
+                .await
+        }
+    };
 
-    for _ in 0..2 {
-        let _ = execution_svc
-            .submit(
-                "test_session_id",
-                "SELECT sleep(2)",
-                QueryContext::default(),
-            )
-            .await;
-        // add delay as miliseconds granularity used for query_id is not enough
-        tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    for query in [
+        "CREATE TABLE t (id INT, amount FLOAT, name VARCHAR)",
+        "INSERT INTO t VALUES (1, 1.5, 'a'), (2, 2.5, NULL)",
+        "ALTER TABLE t ADD COLUMN note VARCHAR COMMENT 'free text'",
+        "ALTER TABLE t RENAME COLUMN name TO label",
+        "ALTER TABLE t ALTER COLUMN id SET DATA TYPE BIGINT",
+        "ALTER TABLE t ALTER COLUMN id SET NOT NULL",
+        "ALTER TABLE t DROP COLUMN amount",
+        "INSERT INTO t VALUES (3, 'c', 'new')",
+        "COMMENT ON TABLE t IS 'evolved'",
+    ] {
+        run(query).await.expect("Failed to execute query");
     }
+    let QueryResult { records, .. } = run("SELECT id, label, note FROM t ORDER BY id")
+        .await
+        .expect("Failed to execute query");
+    assert_eq!(column_values(&records, "id"), vec!["1", "2", "3"]);
+    assert_eq!(column_values(&records, "label"), vec!["a", "", "c"]);
+    assert_eq!(column_values(&records, "note"), vec!["", "", "new"]);
 
-    let res = execution_svc
-        .query("test_session_id", "SELECT 1", QueryContext::default())
-        .await;
+    let table = metastore
+        .get_table(&MetastoreTableIdent::new("embucket", "public", "t"))
+        .await
+        .expect("Failed to get table")
+        .expect("Missing table");
+    let schema = table
+        .metadata
+        .current_schema(None)
+        .expect("Missing current schema");
+    let note = schema.fields().get_name("note").expect("Missing column");
+    assert_eq!(note.doc.as_deref(), Some("free text"));
     assert!(
-        res.is_err(),
-        "Expected concurrency limit error but got {res:?}"
+        schema
+            .fields()
+            .get_name("id")
+            .expect("Missing column")
+            .required
     );
-}
-
-#[tokio::test(flavor = "multi_thread")]
-#[allow(clippy::expect_used)]
-#[allow(clippy::items_after_statements)]
-async fn test_parallel_run() {
-    const MAX_CONCURRENCY_LEVEL: usize = 10;
-    let metastore = Arc::new(InMemoryMetastore::new());
-    let execution_svc = Arc::new(
-        CoreExecutionService::new(
-            metastore.clone(),
-            Arc::new(Config::default().with_max_concurrency_level(MAX_CONCURRENCY_LEVEL)),
-        )
-        .await
-        .expect("Failed to create execution service"),
+    assert_eq!(
+        table.metadata.properties.get("comment").map(String::as_str),
+        Some("evolved")
     );
 
-    let _ = execution_svc
-        .create_session("test_session_id")
+    // Only widening type changes are allowed, and no required column over nulls
+    for query in [
+        "ALTER TABLE t ALTER COLUMN id SET DATA TYPE INT",
+        "ALTER TABLE t ALTER COLUMN label SET NOT NULL",
+        "ALTER TABLE t DROP COLUMN missing",
+        "ALTER TABLE t ADD COLUMN label INT",
+    ] {
+        assert!(run(query).await.is_err(), "{query} should fail");
+    }
+    run("ALTER TABLE t DROP COLUMN IF EXISTS missing")
         .await
-        .expect("Failed to create session");
-
-    async fn exec_query(
-        execution_svc: Arc<dyn ExecutionService>,
-        sql: &str,
-    ) -> crate::Result<QueryResult> {
-        execution_svc
-            .query("test_session_id", sql, QueryContext::default())
-            .await
+        .expect("Failed to execute query");
+    // Nulls are looked up in columns whose names need quoting as well
+    for query in [
+        "CREATE TABLE quoted (\"say \"\"hi\"\"\" INT) AS VALUES (1)",
+        "ALTER TABLE quoted ALTER COLUMN \"say \"\"hi\"\"\" SET NOT NULL",
+    ] {
+        run(query).await.expect("Failed to execute query");
     }
 
-    let mut futures = Vec::new();
-    for _ in 0..MAX_CONCURRENCY_LEVEL {
-        let future = tokio::task::spawn(exec_query(execution_svc.clone(), "SELECT 1"));
-        futures.push(future);
+    // Renamed and swapped tables keep their data
+    for query in [
+        "CREATE TABLE other (x INT) AS VALUES (42)",
+        "ALTER TABLE t RENAME TO renamed",
+        "ALTER TABLE renamed SWAP WITH other",
+    ] {
+        run(query).await.expect("Failed to execute query");
     }
-
-    let results = tokio::time::timeout(std::time::Duration::from_secs(5), join_all(futures))
+    assert!(run("SELECT * FROM t").await.is_err());
+    let QueryResult { records, .. } = run("SELECT x FROM renamed")
         .await
-        .expect("Test timed out")
-        .into_iter()
-        .map(|r| r.expect("Task panicked"))
-        // .map(|_| Ok::<String, Error>(String::from("OK")))
-        .collect::<Vec<_>>();
-    let fails_count = results.iter().filter(|r| r.is_err()).count();
-    eprintln!("queries results: {results:?}");
-    assert_eq!(0, fails_count);
+        .expect("Failed to execute query");
+    assert_eq!(column_values(&records, "x"), vec!["42"]);
+    let QueryResult { records, .. } = run("SELECT COUNT(*) AS rows FROM other")
+        .await
+        .expect("Failed to execute query");
+    assert_eq!(column_values(&records, "rows"), vec!["3"]);
 }
 
-#[tokio::test(flavor = "multi_thread")]
+#[tokio::test]
 #[allow(clippy::expect_used)]
-async fn test_query_timeout() {
+async fn test_service_partitioned_tables() {
     let metastore = Arc::new(InMemoryMetastore::new());
+    metastore
+        .create_volume(
+            &"test_volume".to_string(),
+            MetastoreVolume::new("test_volume".to_string(), VolumeType::Memory),
+        )
+        .await
+        .expect("Failed to create volume");
+    metastore
+        .create_database(
+            &"embucket".to_string(),
+            MetastoreDatabase {
+                ident: "embucket".to_string(),
+                properties: None,
+                volume: "test_volume".to_string(),
+                should_refresh: false,
+            },
+        )
+        .await
+        .expect("Failed to create database");
+    let schema_ident = MetastoreSchemaIdent {
+        database: "embucket".to_string(),
+        schema: "public".to_string(),
+    };
+    metastore
+        .create_schema(
+            &schema_ident.clone(),
+            MetastoreSchema {
+                ident: schema_ident,
+                properties: None,
+            },
+        )
+        .await
+        .expect("Failed to create schema");
+
+    let execution_svc = CoreExecutionService::new(metastore.clone(), Arc::new(Config::default()))
+        .await
+        .expect("Failed to create execution service");
+    let session_id = "test_session_id";
+    execution_svc
+        .create_internal_session(session_id)
+        .await
+        .expect("Failed to create session");
+    let run = |query: &'static str| {
+        let execution_svc = &execution_svc;
+        async move {
+            execution_svc
+                .query(session_id, query, QueryContext::default())
+                .await
+        }
+    };
+    let partition_fields = |table: &'static str| {
+        let metastore = metastore.clone();
+        async move {
+            let table = metastore
+                .get_table(&MetastoreTableIdent::new("embucket", "public", table))
+                .await
+                .expect("Failed to get table")
+                .expect("Missing table");
+            table
+                .metadata
+                .partition_specs
+                .get(&table.metadata.default_spec_id)
+                .map(|spec| {
+                    spec.fields()
+                        .iter()
+                        .map(|field| field.name().clone())
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default()
+        }
+    };
+    // Partitions of the data files of a table, from the `<field>=<value>` parts of
+    // their paths
+    let data_file_partitions = |table: &'static str| {
+        let metastore = metastore.clone();
+        async move {
+            let store = metastore
+                .table_object_store(&MetastoreTableIdent::new("embucket", "public", table))
+                .await
+                .expect("Failed to get object store")
+                .expect("Missing object store");
+            let files: Vec<_> = store
+                .list(None)
+                .try_collect()
+                .await
+                .expect("Failed to list files");
+            files
+                .into_iter()
+                .map(|file| file.location.to_string())
+                .filter(|path| path.contains(&format!("{table}/")) && path.ends_with(".parquet"))
+                .map(|path| {
+                    path.split('/')
+                        .filter(|part| part.contains('='))
+                        .map(ToString::to_string)
+                        .collect::<Vec<_>>()
+                })
+                .collect::<BTreeSet<_>>()
+        }
+    };
+
+    for query in [
+        "CREATE TABLE events (id INT, name VARCHAR, ts TIMESTAMP)
+            PARTITION BY (day(ts), bucket(4, id), truncate(2, name))",
+        "INSERT INTO events VALUES
+            (1, 'alpha', '2024-01-01 10:00:00'),
+            (2, 'beta', '2024-01-02 11:00:00'),
+            (3, 'gamma', '2024-01-02 12:00:00')",
+        "MERGE INTO events USING (SELECT 2 AS id, 'delta' AS name) s ON events.id = s.id
+            WHEN MATCHED THEN UPDATE SET name = s.name",
+    ] {
+        run(query).await.expect("Failed to execute query");
+    }
+    assert_eq!(
+        partition_fields("events").await,
+        vec!["ts_day", "id_bucket", "name_trunc"]
+    );
+    let QueryResult { records, .. } =
+        run("SELECT name FROM events WHERE ts >= '2024-01-02' ORDER BY id")
+            .await
+            .expect("Failed to execute query");
+    assert_eq!(column_values(&records, "name"), vec!["delta", "gamma"]);
+    // Data files are written to a path per partition, days are counted from 1970-01-01
+    let partitions = data_file_partitions("events").await;
+    assert!(partitions.iter().all(|partition| {
+        partition.len() == 3
+            && partition[0].starts_with("ts_day=")
+            && partition[1].starts_with("id_bucket=")
+            && partition[2].starts_with("name_trunc=")
+    }));
+    let values = |field: &str| {
+        partitions
+            .iter()
+            .flatten()
+            .filter_map(|part| {
+                part.strip_prefix(&format!("{field}="))
+                    .map(ToString::to_string)
+            })
+            .collect::<BTreeSet<_>>()
+    };
+    assert_eq!(
+        values("ts_day"),
+        BTreeSet::from(["19723".to_string(), "19724".to_string()])
+    );
+    assert!(
+        ["al", "de", "ga"]
+            .iter()
+            .all(|value| values("name_trunc").contains(*value))
+    );
+
+    // Partition evolution keeps the rows written with the earlier spec
+    run("ALTER TABLE events SET PARTITION SPEC (month(ts))")
+        .await
+        .expect("Failed to execute query");
+    assert_eq!(partition_fields("events").await, vec!["ts_month"]);
+    run("INSERT INTO events VALUES (4, 'epsilon', '2024-02-01 00:00:00')")
+        .await
+        .expect("Failed to execute query");
+    let QueryResult { records, .. } = run("SELECT COUNT(*) AS rows FROM events")
+        .await
+        .expect("Failed to execute query");
+    assert_eq!(column_values(&records, "rows"), vec!["4"]);
+    assert!(
+        data_file_partitions("events")
+            .await
+            .contains(&vec!["ts_month=649".to_string()])
+    );
+
+    // Snowflake clustering keys that are transforms of a column map to partition
+    // fields, bare columns don't
+    for query in [
+        "CREATE TABLE clustered (id INT, ts TIMESTAMP) CLUSTER BY (ts::DATE, id)",
+        "INSERT INTO clustered VALUES
+            (1, '2024-01-01 10:00:00'),
+            (2, '2024-01-02 11:00:00'),
+            (3, '2024-01-02 12:00:00')",
+    ] {
+        run(query).await.expect("Failed to execute query");
+    }
+    assert_eq!(partition_fields("clustered").await, vec!["ts_day"]);
+    assert_eq!(
+        data_file_partitions("clustered").await,
+        BTreeSet::from([
+            vec!["ts_day=19723".to_string()],
+            vec!["ts_day=19724".to_string()],
+        ])
+    );
+    // Columns of a partition field can't be dropped
+    assert!(run("ALTER TABLE clustered DROP COLUMN ts").await.is_err());
+    run("ALTER TABLE clustered DROP COLUMN id")
+        .await
+        .expect("Failed to execute query");
+
+    assert!(
+        run("CREATE TABLE invalid (id INT) PARTITION BY (day(id))")
+            .await
+            .is_err()
+    );
+    assert!(
+        run("ALTER TABLE events SET PARTITION SPEC (missing)")
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+#[allow(clippy::expect_used)]
+async fn test_service_time_travel() {
+    let metastore = Arc::new(InMemoryMetastore::new());
+    metastore
+        .create_volume(
+            &"test_volume".to_string(),
+            MetastoreVolume::new("test_volume".to_string(), VolumeType::Memory),
+        )
+        .await
+        .expect("Failed to create volume");
+    metastore
+        .create_database(
+            &"embucket".to_string(),
+            MetastoreDatabase {
+                ident: "embucket".to_string(),
+                properties: None,
+                volume: "test_volume".to_string(),
+                should_refresh: false,
+            },
+        )
+        .await
+        .expect("Failed to create database");
+    let schema_ident = MetastoreSchemaIdent {
+        database: "embucket".to_string(),
+        schema: "public".to_string(),
+    };
+    metastore
+        .create_schema(
+            &schema_ident.clone(),
+            MetastoreSchema {
+                ident: schema_ident,
+                properties: None,
+            },
+        )
+        .await
+        .expect("Failed to create schema");
+
+    let execution_svc = CoreExecutionService::new(metastore.clone(), Arc::new(Config::default()))
+        .await
+        .expect("Failed to create execution service");
+    let session_id = "test_session_id";
+    execution_svc
+        .create_internal_session(session_id)
+        .await
+        .expect("Failed to create session");
+    let run = |query: String| {
+        let execution_svc = &execution_svc;
+        async move {
+            let query_id = execution_svc
+                .submit(session_id, &query, QueryContext::default())
+                .await?;
+            execution_svc
+                .wait(query_id)
+                .await
+                .map(|result| (query_id, result))
+        }
+    };
+    let count = |query: String| async move {
+        let (_, QueryResult { records, .. }) = run(query).await.expect("Failed to execute query");
+        column_values(&records, "rows")
+    };
+
+    run("CREATE TABLE orders (id INT, status VARCHAR)".to_string())
+        .await
+        .expect("Failed to execute query");
+    let (first_insert, _) = run("INSERT INTO orders VALUES (1, 'new'), (2, 'new')".to_string())
+        .await
+        .expect("Failed to execute query");
+    let (bad_load, _) = run("UPDATE orders SET status = 'lost'".to_string())
+        .await
+        .expect("Failed to execute query");
+
+    assert_eq!(
+        count(format!(
+            "SELECT COUNT(*) AS rows FROM orders BEFORE(STATEMENT => '{first_insert}')"
+        ))
+        .await,
+        vec!["0"]
+    );
+    assert_eq!(
+        count(format!(
+            "SELECT COUNT(*) AS rows FROM orders AT(STATEMENT => '{first_insert}')"
+        ))
+        .await,
+        vec!["2"]
+    );
+    assert_eq!(
+        count(format!(
+            "SELECT COUNT(*) AS rows FROM orders BEFORE(STATEMENT => '{bad_load}')
+                WHERE orders.status = 'new'"
+        ))
+        .await,
+        vec!["2"]
+    );
+    assert_eq!(
+        count(
+            "SELECT COUNT(*) AS rows FROM orders AT(TIMESTAMP => CURRENT_TIMESTAMP())
+                WHERE status = 'lost'"
+                .to_string()
+        )
+        .await,
+        vec!["2"]
+    );
+    // The current and a historical version of a table in one query
+    assert_eq!(
+        count(format!(
+            "SELECT COUNT(*) AS rows FROM orders
+                JOIN orders BEFORE(STATEMENT => '{bad_load}') AS previous
+                ON orders.id = previous.id AND orders.status <> previous.status"
+        ))
+        .await,
+        vec!["2"]
+    );
+
+    // Recovering from the bad load
+    run(format!(
+        "CREATE TABLE restored AS SELECT * FROM orders BEFORE(STATEMENT => '{bad_load}')"
+    ))
+    .await
+    .expect("Failed to execute query");
+    assert_eq!(
+        count("SELECT COUNT(*) AS rows FROM restored WHERE status = 'new'".to_string()).await,
+        vec!["2"]
+    );
+
+    for query in [
+        "SELECT * FROM orders AT(OFFSET => -86400)",
+        "SELECT * FROM orders AT(OFFSET => 86400)",
+        "SELECT * FROM orders AT(STATEMENT => 'not-a-query-id')",
+        "SELECT * FROM orders BEFORE(STATEMENT => '00000000-0000-0000-0000-000000000000')",
+    ] {
+        assert!(run(query.to_string()).await.is_err(), "{query} should fail");
+    }
+}
+
+#[tokio::test]
+#[allow(clippy::expect_used)]
+async fn test_service_zero_copy_clone() {
+    let metastore = Arc::new(InMemoryMetastore::new());
+    metastore
+        .create_volume(
+            &"test_volume".to_string(),
+            MetastoreVolume::new("test_volume".to_string(), VolumeType::Memory),
+        )
+        .await
+        .expect("Failed to create volume");
+    metastore
+        .create_database(
+            &"embucket".to_string(),
+            MetastoreDatabase {
+                ident: "embucket".to_string(),
+                properties: None,
+                volume: "test_volume".to_string(),
+                should_refresh: false,
+            },
+        )
+        .await
+        .expect("Failed to create database");
+    let schema_ident = MetastoreSchemaIdent {
+        database: "embucket".to_string(),
+        schema: "public".to_string(),
+    };
+    metastore
+        .create_schema(
+            &schema_ident.clone(),
+            MetastoreSchema {
+                ident: schema_ident,
+                properties: None,
+            },
+        )
+        .await
+        .expect("Failed to create schema");
+
+    let execution_svc = CoreExecutionService::new(metastore.clone(), Arc::new(Config::default()))
+        .await
+        .expect("Failed to create execution service");
+    let session_id = "test_session_id";
+    execution_svc
+        .create_internal_session(session_id)
+        .await
+        .expect("Failed to create session");
+    let run = |query: String| {
+        let execution_svc = &execution_svc;
+        async move {
+            let query_id = execution_svc
+                .submit(session_id, &query, QueryContext::default())
+                .await?;
+            execution_svc
+                .wait(query_id)
+                .await
+                .map(|result| (query_id, result))
+        }
+    };
+    let count = |table: &'static str| async move {
+        let (_, QueryResult { records, .. }) = run(format!("SELECT COUNT(*) AS rows FROM {table}"))
+            .await
+            .expect("Failed to execute query");
+        column_values(&records, "rows")
+    };
+    let current_snapshot = |database: &'static str, schema: &'static str, table: &'static str| {
+        let metastore = metastore.clone();
+        async move {
+            metastore
+                .get_table(&MetastoreTableIdent::new(database, schema, table))
+                .await
+                .expect("Failed to get table")
+                .expect("Missing table")
+                .metadata
+                .current_snapshot_id
+        }
+    };
+
+    run("CREATE SCHEMA prod".to_string())
+        .await
+        .expect("Failed to execute query");
+    run("CREATE TABLE prod.orders (id INT, status VARCHAR)".to_string())
+        .await
+        .expect("Failed to execute query");
+    let (load, _) = run("INSERT INTO prod.orders VALUES (1, 'new'), (2, 'new')".to_string())
+        .await
+        .expect("Failed to execute query");
+
+    // A clone shares the snapshots and data files of its source until they diverge
+    run("CREATE TABLE orders_copy CLONE prod.orders".to_string())
+        .await
+        .expect("Failed to execute query");
+    assert_eq!(count("orders_copy").await, vec!["2"]);
+    assert_eq!(
+        current_snapshot("embucket", "public", "orders_copy").await,
+        current_snapshot("embucket", "prod", "orders").await
+    );
+    run("INSERT INTO orders_copy VALUES (3, 'new')".to_string())
+        .await
+        .expect("Failed to execute query");
+    assert_eq!(count("orders_copy").await, vec!["3"]);
+    assert_eq!(count("prod.orders").await, vec!["2"]);
+
+    run(format!(
+        "CREATE TABLE orders_before_load CLONE prod.orders BEFORE(STATEMENT => '{load}')"
+    ))
+    .await
+    .expect("Failed to execute query");
+    assert_eq!(count("orders_before_load").await, vec!["0"]);
+
+    run("CREATE SCHEMA dev CLONE prod".to_string())
+        .await
+        .expect("Failed to execute query");
+    assert_eq!(count("dev.orders").await, vec!["2"]);
+
+    run("CREATE DATABASE embucket_dev CLONE embucket".to_string())
+        .await
+        .expect("Failed to execute query");
+    assert_eq!(count("embucket_dev.prod.orders").await, vec!["2"]);
+    assert_eq!(count("embucket_dev.public.orders_copy").await, vec!["3"]);
+
+    assert!(
+        run("CREATE TABLE orders_copy CLONE prod.orders".to_string())
+            .await
+            .is_err()
+    );
+    run("CREATE TABLE IF NOT EXISTS orders_copy CLONE prod.orders".to_string())
+        .await
+        .expect("Failed to execute query");
+    assert_eq!(count("orders_copy").await, vec!["3"]);
+    run("CREATE OR REPLACE TABLE orders_copy CLONE prod.orders".to_string())
+        .await
+        .expect("Failed to execute query");
+    assert_eq!(count("orders_copy").await, vec!["2"]);
+}
+
+#[tokio::test]
+async fn test_service_undrop() {
+    let metastore = Arc::new(InMemoryMetastore::new());
+    metastore
+        .create_volume(
+            &"test_volume".to_string(),
+            MetastoreVolume::new("test_volume".to_string(), VolumeType::Memory),
+        )
+        .await
+        .expect("Failed to create volume");
+    metastore
+        .create_database(
+            &"embucket".to_string(),
+            MetastoreDatabase {
+                ident: "embucket".to_string(),
+                properties: None,
+                volume: "test_volume".to_string(),
+                should_refresh: false,
+            },
+        )
+        .await
+        .expect("Failed to create database");
+    let schema_ident = MetastoreSchemaIdent {
+        database: "embucket".to_string(),
+        schema: "public".to_string(),
+    };
+    metastore
+        .create_schema(
+            &schema_ident.clone(),
+            MetastoreSchema {
+                ident: schema_ident,
+                properties: None,
+            },
+        )
+        .await
+        .expect("Failed to create schema");
+
+    let execution_svc = CoreExecutionService::new(metastore.clone(), Arc::new(Config::default()))
+        .await
+        .expect("Failed to create execution service");
+    let session_id = "test_session_id";
+    execution_svc
+        .create_internal_session(session_id)
+        .await
+        .expect("Failed to create session");
+    let run = |query: &str| {
+        let execution_svc = &execution_svc;
+        let query = query.to_string();
+        async move {
+            let query_id = execution_svc
+                .submit(session_id, &query, QueryContext::default())
+                .await?;
+            execution_svc.wait(query_id).await
+        }
+    };
+    let count = |table: &'static str| async move {
+        let QueryResult { records, .. } = run(&format!("SELECT COUNT(*) AS rows FROM {table}"))
+            .await
+            .expect("Failed to execute query");
+        column_values(&records, "rows")
+    };
+
+    run("CREATE SCHEMA prod")
+        .await
+        .expect("Failed to execute query");
+    run("CREATE TABLE prod.orders (id INT, status VARCHAR)")
+        .await
+        .expect("Failed to execute query");
+    run("INSERT INTO prod.orders VALUES (1, 'new'), (2, 'new')")
+        .await
+        .expect("Failed to execute query");
+
+    run("DROP TABLE prod.orders")
+        .await
+        .expect("Failed to execute query");
+    assert!(run("SELECT * FROM prod.orders").await.is_err());
+    let QueryResult { records, .. } = run("SHOW TABLES HISTORY IN SCHEMA prod")
+        .await
+        .expect("Failed to execute query");
+    assert_eq!(column_values(&records, "name"), vec!["orders"]);
+    assert_ne!(column_values(&records, "dropped_on"), vec![""]);
+
+    run("UNDROP TABLE prod.orders")
+        .await
+        .expect("Failed to execute query");
+    assert_eq!(count("prod.orders").await, vec!["2"]);
+    // Nothing left to restore, and the name is taken by the restored table
+    assert!(run("UNDROP TABLE prod.orders").await.is_err());
+
+    // A table recreated with the name of a dropped one blocks restoring it
+    run("DROP TABLE prod.orders")
+        .await
+        .expect("Failed to execute query");
+    run("CREATE TABLE prod.orders (id INT)")
+        .await
+        .expect("Failed to execute query");
+    assert!(run("UNDROP TABLE prod.orders").await.is_err());
+    run("ALTER TABLE prod.orders RENAME TO prod.orders_new")
+        .await
+        .expect("Failed to execute query");
+    run("UNDROP TABLE prod.orders")
+        .await
+        .expect("Failed to execute query");
+    assert_eq!(count("prod.orders").await, vec!["2"]);
+
+    run("DROP SCHEMA prod CASCADE")
+        .await
+        .expect("Failed to execute query");
+    assert!(run("SELECT * FROM prod.orders").await.is_err());
+    run("UNDROP SCHEMA prod")
+        .await
+        .expect("Failed to execute query");
+    assert_eq!(count("prod.orders").await, vec!["2"]);
+    assert_eq!(count("prod.orders_new").await, vec!["0"]);
+
+    run("CREATE DATABASE archive EXTERNAL_VOLUME = 'test_volume'")
+        .await
+        .expect("Failed to execute query");
+    run("CREATE SCHEMA archive.history")
+        .await
+        .expect("Failed to execute query");
+    run("CREATE TABLE archive.history.events AS SELECT 1 AS id")
+        .await
+        .expect("Failed to execute query");
+    run("DROP DATABASE archive CASCADE")
+        .await
+        .expect("Failed to execute query");
+    assert!(run("SELECT * FROM archive.history.events").await.is_err());
+    run("UNDROP DATABASE archive")
+        .await
+        .expect("Failed to execute query");
+    assert_eq!(count("archive.history.events").await, vec!["1"]);
+    assert!(run("UNDROP DATABASE archive").await.is_err());
+}
+
+#[tokio::test]
+async fn test_service_create_table_file_volume() {
+    let metastore = Arc::new(InMemoryMetastore::new());
+
+    // Create a temporary directory for the file volume
+    let temp_dir = std::env::temp_dir().join("test_file_volume");
+    let _ = std::fs::create_dir_all(&temp_dir);
+    let temp_path = temp_dir.to_str().expect("Failed to convert path to string");
+    metastore
+        .create_volume(
+            &"test_volume".to_string(),
+            MetastoreVolume::new(
+                "test_volume".to_string(),
+                VolumeType::File(FileVolume {
+                    path: temp_path.to_string(),
+                }),
+            ),
+        )
+        .await
+        .expect("Failed to create volume");
+    metastore
+        .create_database(
+            &"embucket".to_string(),
+            MetastoreDatabase {
+                ident: "embucket".to_string(),
+                properties: None,
+                volume: "test_volume".to_string(),
+                should_refresh: false,
+            },
+        )
+        .await
+        .expect("Failed to create database");
+    let schema_ident = MetastoreSchemaIdent {
+        database: "embucket".to_string(),
+        schema: "public".to_string(),
+    };
+    metastore
+        .create_schema(
+            &schema_ident.clone(),
+            MetastoreSchema {
+                ident: schema_ident,
+                properties: None,
+            },
+        )
+        .await
+        .expect("Failed to create schema");
+
+    let table_ident = MetastoreTableIdent {
+        database: "embucket".to_string(),
+        schema: "public".to_string(),
+        table: "target_table".to_string(),
+    };
+    let execution_svc = CoreExecutionService::new(metastore, Arc::new(Config::default()))
+        .await
+        .expect("Failed to create execution service");
+
+    let session_id = "test_session_id";
+    execution_svc
+        .create_internal_session(session_id)
+        .await
+        .expect("Failed to create session");
+
+    let create_table_sql = format!(
+        "CREATE TABLE {table_ident} (id INT, name STRING, value FLOAT) as VALUES (1, 'test1', 100.0), (2, 'test2', 200.0), (3, 'test3', 300.0)"
+    );
+    let QueryResult { records, .. } = execution_svc
+        .query(session_id, &create_table_sql, QueryContext::default())
+        .await
+        .expect("Failed to create table");
+
+    assert_batches_eq!(
+        &[
+            "+-------+",
+            "| count |",
+            "+-------+",
+            "| 3     |",
+            "+-------+",
+        ],
+        &records
+    );
+
+    let insert_sql = format!(
+        "INSERT INTO {table_ident} (id, name, value) VALUES (4, 'test4', 400.0), (5, 'test5', 500.0)"
+    );
+    let QueryResult { records, .. } = execution_svc
+        .query(session_id, &insert_sql, QueryContext::default())
+        .await
+        .expect("Failed to insert data");
+
+    assert_batches_eq!(
+        &[
+            "+-------+",
+            "| count |",
+            "+-------+",
+            "| 2     |",
+            "+-------+",
+        ],
+        &records
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[allow(clippy::expect_used)]
+async fn test_max_concurrency_level() {
+    use tokio::sync::Barrier;
+
+    let metastore = Arc::new(InMemoryMetastore::new());
+    let execution_svc = Arc::new(
+        CoreExecutionService::new(
+            metastore.clone(),
+            Arc::new(Config::default().with_max_concurrency_level(2)),
+        )
+        .await
+        .expect("Failed to create execution service"),
+    );
+
+    let _session = execution_svc
+        .create_internal_session("test_session_id")
+        .await
+        .expect("Failed to create session");
+
+    let barrier = Arc::new(Barrier::new(3)); // wait for 3 threads: 2 queries + main thread
+
+    // Reserve 2 permitted slots for the queries
+    for _ in 0..2 {
+        let svc = execution_svc.clone();
+        let barrier = barrier.clone();
+        tokio::spawn(async move {
+            let _ = svc
+                .submit(
+                    "test_session_id",
+                    "SELECT sleep(2)",
+                    QueryContext::default(),
+                )
+                .await;
+            barrier.wait().await;
+        });
+        // add delay as miliseconds granularity used for query_id is not enough
+        tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    }
+
+    let res = execution_svc
+        .query(
+            "test_session_id",
+            "SELECT sleep(3)",
+            QueryContext::default(),
+        )
+        .await;
+    assert!(
+        res.is_err(),
+        "Expected concurrency limit error but got {res:?}"
+    );
+
+    // Pass the barrier to allow the first two queries to finish
+    barrier.wait().await;
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[allow(clippy::expect_used)]
+async fn test_max_concurrency_level2() {
+    let metastore = Arc::new(InMemoryMetastore::new());
+    let execution_svc = Arc::new(
+        CoreExecutionService::new(
+            metastore.clone(),
+            Arc::new(Config::default().with_max_concurrency_level(2)),
+        )
+        .await
+        .expect("Failed to create execution service"),
+    );
+
+    let _session = execution_svc
+        .create_internal_session("test_session_id")
+        .await
+        .expect("Failed to create session");
+
+    for _ in 0..2 {
+        let _ = execution_svc
+            .submit(
+                "test_session_id",
+                "SELECT sleep(2)",
+                QueryContext::default(),
+            )
+            .await;
+        // add delay as miliseconds granularity used for query_id is not enough
+        tokio::time::sleep(std::time::Duration::from_millis(2)).await;
+    }
+
+    let res = execution_svc
+        .query("test_session_id", "SELECT 1", QueryContext::default())
+        .await;
+    assert!(
+        res.is_err(),
+        "Expected concurrency limit error but got {res:?}"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[allow(clippy::expect_used)]
+#[allow(clippy::items_after_statements)]
+async fn test_parallel_run() {
+    const MAX_CONCURRENCY_LEVEL: usize = 10;
+    let metastore = Arc::new(InMemoryMetastore::new());
+    let execution_svc = Arc::new(
+        CoreExecutionService::new(
+            metastore.clone(),
+            Arc::new(Config::default().with_max_concurrency_level(MAX_CONCURRENCY_LEVEL)),
+        )
+        .await
+        .expect("Failed to create execution service"),
+    );
+
+    let _ = execution_svc
+        .create_internal_session("test_session_id")
+        .await
+        .expect("Failed to create session");
+
+    async fn exec_query(
+        execution_svc: Arc<dyn ExecutionService>,
+        sql: &str,
+    ) -> crate::Result<QueryResult> {
+        execution_svc
+            .query("test_session_id", sql, QueryContext::default())
+            .await
+    }
+
+    let mut futures = Vec::new();
+    for _ in 0..MAX_CONCURRENCY_LEVEL {
+        let future = tokio::task::spawn(exec_query(execution_svc.clone(), "SELECT 1"));
+        futures.push(future);
+    }
+
+    let results = tokio::time::timeout(std::time::Duration::from_secs(5), join_all(futures))
+        .await
+        .expect("Test timed out")
+        .into_iter()
+        .map(|r| r.expect("Task panicked"))
+        // .map(|_| Ok::<String, Error>(String::from("OK")))
+        .collect::<Vec<_>>();
+    let fails_count = results.iter().filter(|r| r.is_err()).count();
+    eprintln!("queries results: {results:?}");
+    assert_eq!(0, fails_count);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[allow(clippy::expect_used)]
+async fn test_query_timeout() {
+    let metastore = Arc::new(InMemoryMetastore::new());
+    let execution_svc = Arc::new(
+        CoreExecutionService::new(
+            metastore.clone(),
+            Arc::new(Config::default().with_query_timeout(1)),
+        )
+        .await
+        .expect("Failed to create execution service"),
+    );
+
+    let _session = execution_svc
+        .create_internal_session("test_session_id")
+        .await
+        .expect("Failed to create session");
+
+    let res = execution_svc
+        .query(
+            "test_session_id",
+            "SELECT sleep(3)",
+            QueryContext::default(),
+        )
+        .await;
+    assert!(
+        res.is_err(),
+        "Expected query execution exceeded timeout error but got {res:?}"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+#[allow(clippy::expect_used)]
+async fn test_query_status() {
+    let metastore = Arc::new(InMemoryMetastore::new());
+    let execution_svc = CoreExecutionService::new(metastore, Arc::new(Config::default()))
+        .await
+        .expect("Failed to create execution service");
+
+    let _session = execution_svc
+        .create_internal_session("test_session_id")
+        .await
+        .expect("Failed to create session");
+
+    let query_id = execution_svc
+        .submit(
+            "test_session_id",
+            "SELECT sleep(1)",
+            QueryContext::default(),
+        )
+        .await
+        .expect("Failed to submit query");
+    assert_eq!(
+        execution_svc
+            .query_status(query_id)
+            .expect("Failed to get query status"),
+        ExecutionStatus::Running
+    );
+
+    // poll the status the way async REST clients do
+    let status = tokio::time::timeout(std::time::Duration::from_secs(5), async {
+        loop {
+            let status = execution_svc
+                .query_status(query_id)
+                .expect("Failed to get query status");
+            if status != ExecutionStatus::Running {
+                break status;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        }
+    })
+    .await
+    .expect("Test timed out");
+    assert_eq!(status, ExecutionStatus::Success);
+
+    execution_svc
+        .wait(query_id)
+        .await
+        .expect("Failed to get query result");
+    // result is consumed and the query is gone from the registry
+    assert!(execution_svc.query_status(query_id).is_err());
+}
+
+#[tokio::test]
+#[allow(clippy::expect_used)]
+async fn test_query_result_owner() {
+    let metastore = Arc::new(InMemoryMetastore::new());
+    let execution_svc = CoreExecutionService::new(metastore, Arc::new(Config::default()))
+        .await
+        .expect("Failed to create execution service");
+    for session_id in ["owner_session_id", "other_session_id"] {
+        execution_svc
+            .create_internal_session(session_id)
+            .await
+            .expect("Failed to create session");
+    }
+
+    let query_id = execution_svc
+        .submit("owner_session_id", "SELECT 1 AS a", QueryContext::default())
+        .await
+        .expect("Failed to submit query");
+
+    // another session can neither see nor fetch the query
+    assert!(
+        execution_svc
+            .check_query_owner("other_session_id", query_id)
+            .is_err()
+    );
+    assert!(
+        execution_svc
+            .query_result("other_session_id", query_id)
+            .await
+            .is_err()
+    );
+
+    // the result is kept, so it can be fetched more than once
+    for _ in 0..2 {
+        let QueryResult { records, .. } = execution_svc
+            .query_result("owner_session_id", query_id)
+            .await
+            .expect("Failed to get query result");
+        assert_eq!(records.iter().map(RecordBatch::num_rows).sum::<usize>(), 1);
+    }
+    assert_eq!(
+        execution_svc
+            .query_status(query_id)
+            .expect("Failed to get query status"),
+        ExecutionStatus::Success
+    );
+    assert!(
+        execution_svc
+            .query_result("other_session_id", query_id)
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+#[allow(clippy::expect_used)]
+async fn test_session_without_stored_user_runs_as_public() {
+    let (execution_svc, session_id) = test_service().await;
+    execution_svc
+        .query(
+            session_id,
+            "CREATE TABLE embucket.public.t (id INT)",
+            QueryContext::default(),
+        )
+        .await
+        .expect("Failed to create table");
+
+    // a login whose user isn't kept in the metastore, such as the demo user
+    execution_svc
+        .create_session("login_session_id")
+        .await
+        .expect("Failed to create session");
+    let mut session_metadata = SessionMetadata::default();
+    session_metadata.set_attr(SessionMetadataAttr::UserName, "embucket".to_string());
+    let query_context =
+        || QueryContext::default().with_session_metadata(Some(session_metadata.clone()));
+
+    for query in [
+        "SELECT * FROM embucket.public.t",
+        "CREATE ROLE analyst",
+        "USE ROLE accountadmin",
+    ] {
+        assert!(
+            execution_svc
+                .query("login_session_id", query, query_context())
+                .await
+                .is_err(),
+            "{query} should be denied to PUBLIC"
+        );
+    }
+    execution_svc
+        .query("login_session_id", "SELECT 1", query_context())
+        .await
+        .expect("Failed to run a query needing no privileges");
+}
+
+/// Execution service over an in-memory metastore with the `embucket.public` schema on
+/// a memory volume, and a session created in it
+#[allow(clippy::expect_used)]
+async fn test_service() -> (Arc<CoreExecutionService>, &'static str) {
+    let metastore = Arc::new(InMemoryMetastore::new());
+    metastore
+        .create_volume(
+            &"test_volume".to_string(),
+            MetastoreVolume::new("test_volume".to_string(), VolumeType::Memory),
+        )
+        .await
+        .expect("Failed to create volume");
+    metastore
+        .create_database(
+            &"embucket".to_string(),
+            MetastoreDatabase {
+                ident: "embucket".to_string(),
+                properties: None,
+                volume: "test_volume".to_string(),
+                should_refresh: false,
+            },
+        )
+        .await
+        .expect("Failed to create database");
+    let schema_ident = MetastoreSchemaIdent {
+        database: "embucket".to_string(),
+        schema: "public".to_string(),
+    };
+    metastore
+        .create_schema(
+            &schema_ident.clone(),
+            MetastoreSchema {
+                ident: schema_ident,
+                properties: None,
+            },
+        )
+        .await
+        .expect("Failed to create schema");
+
     let execution_svc = Arc::new(
-        CoreExecutionService::new(
-            metastore.clone(),
-            Arc::new(Config::default().with_query_timeout(1)),
+        CoreExecutionService::new(metastore, Arc::new(Config::default()))
+            .await
+            .expect("Failed to create execution service"),
+    );
+    let session_id = "test_session_id";
+    execution_svc
+        .create_internal_session(session_id)
+        .await
+        .expect("Failed to create session");
+    (execution_svc, session_id)
+}
+
+#[tokio::test]
+#[allow(clippy::expect_used)]
+async fn test_service_copy_into_from_stage() {
+    let temp_dir = std::env::temp_dir().join("test_stage_files");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    std::fs::create_dir_all(temp_dir.join("data")).expect("Failed to create stage directory");
+    std::fs::write(temp_dir.join("data/first.csv"), "1,a\n2,b\n").expect("Failed to write file");
+    std::fs::write(temp_dir.join("data/second.csv"), "3,c\n").expect("Failed to write file");
+    std::fs::write(temp_dir.join("data/notes.txt"), "not a csv").expect("Failed to write file");
+    let stage_url = format!("file://{}", temp_dir.display());
+
+    let (execution_svc, session_id) = test_service().await;
+
+    for sql in [
+        "CREATE TABLE embucket.public.t (id INT, name STRING)".to_string(),
+        format!("CREATE STAGE embucket.public.s URL = '{stage_url}' FILE_FORMAT = (TYPE = CSV)"),
+    ] {
+        execution_svc
+            .query(session_id, &sql, QueryContext::default())
+            .await
+            .expect("Failed to run setup query");
+    }
+
+    let QueryResult { records, .. } = execution_svc
+        .query(
+            session_id,
+            "LIST @s/data PATTERN = '.*[.]csv'",
+            QueryContext::default(),
+        )
+        .await
+        .expect("Failed to list stage");
+    let names = records[0]
+        .column_by_name("name")
+        .expect("Missing name column")
+        .as_any()
+        .downcast_ref::<datafusion::arrow::array::StringArray>()
+        .expect("Name column is not a string")
+        .iter()
+        .flatten()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            format!("{stage_url}/data/first.csv"),
+            format!("{stage_url}/data/second.csv"),
+        ]
+    );
+
+    let QueryResult { records, .. } = execution_svc
+        .query(
+            session_id,
+            "COPY INTO t FROM @s/data PATTERN = '.*[.]csv'",
+            QueryContext::default(),
+        )
+        .await
+        .expect("Failed to copy from stage");
+    assert_eq!(
+        column_values(&records, "file"),
+        vec![
+            format!("{stage_url}/data/first.csv"),
+            format!("{stage_url}/data/second.csv"),
+        ]
+    );
+    assert_eq!(column_values(&records, "rows_loaded"), vec!["2", "1"]);
+
+    let QueryResult { records, .. } = execution_svc
+        .query(
+            session_id,
+            "REMOVE @s/data/notes.txt",
+            QueryContext::default(),
+        )
+        .await
+        .expect("Failed to remove from stage");
+    assert_eq!(records[0].num_rows(), 1);
+    assert!(!temp_dir.join("data/notes.txt").exists());
+    assert!(temp_dir.join("data/first.csv").exists());
+    let _ = std::fs::remove_dir_all(&temp_dir);
+}
+
+#[tokio::test]
+#[allow(clippy::expect_used, clippy::too_many_lines)]
+async fn test_service_copy_into_with_file_formats() {
+    let temp_dir = std::env::temp_dir().join("test_file_format_files");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    std::fs::create_dir_all(&temp_dir).expect("Failed to create stage directory");
+    std::fs::write(
+        temp_dir.join("people.txt"),
+        "# exported people\nid|name|born\n1| a |31/01/2024\n2|NULL|\n",
+    )
+    .expect("Failed to write file");
+    std::fs::write(
+        temp_dir.join("rows.json"),
+        r#"[{"id": 3, "name": "c"}, {"id": 4, "name": "d"}]"#,
+    )
+    .expect("Failed to write file");
+    let stage_url = format!("file://{}", temp_dir.display());
+
+    let (execution_svc, session_id) = test_service().await;
+
+    for sql in [
+        "CREATE TABLE embucket.public.people (id INT, name STRING, born DATE)".to_string(),
+        "CREATE TABLE embucket.public.events (id INT, name STRING)".to_string(),
+        format!("CREATE STAGE embucket.public.s URL = '{stage_url}'"),
+        "CREATE FILE FORMAT embucket.public.pipes TYPE = CSV FIELD_DELIMITER = '|' \
+         SKIP_HEADER = 2 NULL_IF = ('NULL') TRIM_SPACE = TRUE DATE_FORMAT = 'DD/MM/YYYY'"
+            .to_string(),
+    ] {
+        execution_svc
+            .query(session_id, &sql, QueryContext::default())
+            .await
+            .expect("Failed to run setup query");
+    }
+
+    for sql in [
+        "COPY INTO people FROM @s/people.txt FILE_FORMAT = (FORMAT_NAME = 'pipes')",
+        "COPY INTO events FROM @s/rows.json FILE_FORMAT = (TYPE = JSON STRIP_OUTER_ARRAY = TRUE)",
+    ] {
+        execution_svc
+            .query(session_id, sql, QueryContext::default())
+            .await
+            .expect("Failed to copy from stage");
+    }
+
+    let QueryResult { records, .. } = execution_svc
+        .query(
+            session_id,
+            "SELECT * FROM people ORDER BY id",
+            QueryContext::default(),
+        )
+        .await
+        .expect("Failed to select");
+    assert_batches_eq!(
+        &[
+            "+----+------+------------+",
+            "| id | name | born       |",
+            "+----+------+------------+",
+            "| 1  | a    | 2024-01-31 |",
+            "| 2  |      |            |",
+            "+----+------+------------+",
+        ],
+        &records
+    );
+
+    let QueryResult { records, .. } = execution_svc
+        .query(
+            session_id,
+            "SELECT * FROM events ORDER BY id",
+            QueryContext::default(),
+        )
+        .await
+        .expect("Failed to select");
+    assert_batches_eq!(
+        &[
+            "+----+------+",
+            "| id | name |",
+            "+----+------+",
+            "| 3  | c    |",
+            "| 4  | d    |",
+            "+----+------+",
+        ],
+        &records
+    );
+    let _ = std::fs::remove_dir_all(&temp_dir);
+}
+
+#[tokio::test]
+#[allow(clippy::expect_used, clippy::too_many_lines)]
+async fn test_service_copy_into_on_error() {
+    let temp_dir = std::env::temp_dir().join("test_copy_on_error_files");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    std::fs::create_dir_all(&temp_dir).expect("Failed to create stage directory");
+    std::fs::write(temp_dir.join("bad.csv"), "3,c\nx,d\n5,e\n").expect("Failed to write file");
+    std::fs::write(temp_dir.join("good.csv"), "1,a\n2,b\n").expect("Failed to write file");
+    let stage_url = format!("file://{}", temp_dir.display());
+
+    let (execution_svc, session_id) = test_service().await;
+
+    for sql in [
+        "CREATE TABLE embucket.public.t (id INT NOT NULL, name STRING)".to_string(),
+        format!("CREATE STAGE embucket.public.s URL = '{stage_url}' FILE_FORMAT = (TYPE = CSV)"),
+    ] {
+        execution_svc
+            .query(session_id, &sql, QueryContext::default())
+            .await
+            .expect("Failed to run setup query");
+    }
+    let count_rows = async || {
+        let QueryResult { records, .. } = execution_svc
+            .query(
+                session_id,
+                "SELECT COUNT(*) AS n FROM t",
+                QueryContext::default(),
+            )
+            .await
+            .expect("Failed to count rows");
+        column_values(&records, "n")
+    };
+
+    let QueryResult { records, .. } = execution_svc
+        .query(
+            session_id,
+            "COPY INTO t FROM @s VALIDATION_MODE = RETURN_ERRORS",
+            QueryContext::default(),
+        )
+        .await
+        .expect("Failed to validate files");
+    assert_eq!(
+        column_values(&records, "error"),
+        vec!["Value 'x' cannot be converted to Int32"]
+    );
+    assert_eq!(
+        column_values(&records, "file"),
+        vec![format!("{stage_url}/bad.csv")]
+    );
+    assert_eq!(column_values(&records, "line"), vec!["2"]);
+    assert_eq!(column_values(&records, "column_name"), vec!["id"]);
+
+    let QueryResult { records, .. } = execution_svc
+        .query(
+            session_id,
+            "COPY INTO t FROM @s VALIDATION_MODE = RETURN_1_ROWS",
+            QueryContext::default(),
+        )
+        .await
+        .expect("Failed to validate files");
+    assert_batches_eq!(
+        &[
+            "+----+------+",
+            "| id | name |",
+            "+----+------+",
+            "| 3  | c    |",
+            "+----+------+"
+        ],
+        &records
+    );
+    assert!(
+        execution_svc
+            .query(
+                session_id,
+                "COPY INTO t FROM @s VALIDATION_MODE = RETURN_2_ROWS",
+                QueryContext::default(),
+            )
+            .await
+            .is_err()
+    );
+    assert_eq!(count_rows().await, vec!["0"]);
+
+    let error = execution_svc
+        .query(session_id, "COPY INTO t FROM @s", QueryContext::default())
+        .await
+        .expect_err("Copy should abort on the bad file");
+    assert!(error.to_string().contains("File '"));
+    assert_eq!(count_rows().await, vec!["0"]);
+
+    let QueryResult { records, .. } = execution_svc
+        .query(
+            session_id,
+            "COPY INTO t FROM @s ON_ERROR = SKIP_FILE",
+            QueryContext::default(),
+        )
+        .await
+        .expect("Failed to copy from stage");
+    assert_eq!(
+        column_values(&records, "status"),
+        vec!["LOAD_FAILED", "LOADED"]
+    );
+    assert_eq!(column_values(&records, "rows_parsed"), vec!["3", "2"]);
+    assert_eq!(column_values(&records, "rows_loaded"), vec!["0", "2"]);
+    assert_eq!(column_values(&records, "first_error_line"), vec!["2", ""]);
+    assert_eq!(count_rows().await, vec!["2"]);
+
+    let QueryResult { records, .. } = execution_svc
+        .query(
+            session_id,
+            "COPY INTO t FROM @s/bad.csv ON_ERROR = CONTINUE",
+            QueryContext::default(),
         )
         .await
-        .expect("Failed to create execution service"),
+        .expect("Failed to copy from stage");
+    assert_eq!(column_values(&records, "status"), vec!["PARTIALLY_LOADED"]);
+    assert_eq!(column_values(&records, "rows_loaded"), vec!["2"]);
+    assert_eq!(column_values(&records, "errors_seen"), vec!["1"]);
+    assert_eq!(count_rows().await, vec!["4"]);
+    let _ = std::fs::remove_dir_all(&temp_dir);
+}
+
+#[tokio::test]
+#[allow(clippy::expect_used)]
+async fn test_service_copy_into_abort_loads_no_file() {
+    let temp_dir = std::env::temp_dir().join("test_copy_abort_files");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    std::fs::create_dir_all(&temp_dir).expect("Failed to create stage directory");
+    std::fs::write(temp_dir.join("1_good.csv"), "1,a\n2,b\n").expect("Failed to write file");
+    std::fs::write(temp_dir.join("2_bad.csv"), "3,c\nx,d\n").expect("Failed to write file");
+    let stage_url = format!("file://{}", temp_dir.display());
+
+    let (execution_svc, session_id) = test_service().await;
+    for sql in [
+        "CREATE TABLE embucket.public.t (id INT NOT NULL, name STRING)".to_string(),
+        format!("CREATE STAGE embucket.public.s URL = '{stage_url}' FILE_FORMAT = (TYPE = CSV)"),
+    ] {
+        execution_svc
+            .query(session_id, &sql, QueryContext::default())
+            .await
+            .expect("Failed to run setup query");
+    }
+
+    // Files are inserted one at a time, the good file is only committed with the rest
+    execution_svc
+        .query(session_id, "COPY INTO t FROM @s", QueryContext::default())
+        .await
+        .expect_err("Copy should abort on the bad file");
+    let QueryResult { records, .. } = execution_svc
+        .query(
+            session_id,
+            "SELECT COUNT(*) AS n FROM t",
+            QueryContext::default(),
+        )
+        .await
+        .expect("Failed to count rows");
+    assert_eq!(column_values(&records, "n"), vec!["0"]);
+
+    let QueryResult { records, .. } = execution_svc
+        .query(
+            session_id,
+            "COPY INTO t FROM @s ON_ERROR = CONTINUE",
+            QueryContext::default(),
+        )
+        .await
+        .expect("Failed to load files");
+    assert_eq!(column_values(&records, "rows_parsed"), vec!["2", "2"]);
+    assert_eq!(column_values(&records, "rows_loaded"), vec!["2", "1"]);
+}
+
+#[tokio::test]
+#[allow(clippy::expect_used, clippy::too_many_lines)]
+async fn test_service_copy_into_load_history() {
+    let temp_dir = std::env::temp_dir().join("test_copy_load_history_files");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    std::fs::create_dir_all(&temp_dir).expect("Failed to create stage directory");
+    std::fs::write(temp_dir.join("a.csv"), "1,a\n").expect("Failed to write file");
+    std::fs::write(temp_dir.join("b.csv"), "2,b\n").expect("Failed to write file");
+    let stage_url = format!("file://{}", temp_dir.display());
+
+    let (execution_svc, session_id) = test_service().await;
+
+    for sql in [
+        "CREATE TABLE embucket.public.t (id INT, name STRING)".to_string(),
+        format!("CREATE STAGE embucket.public.s URL = '{stage_url}' FILE_FORMAT = (TYPE = CSV)"),
+    ] {
+        execution_svc
+            .query(session_id, &sql, QueryContext::default())
+            .await
+            .expect("Failed to run setup query");
+    }
+    let query_values = async |sql: &str, column: &str| {
+        let QueryResult { records, .. } = execution_svc
+            .query(session_id, sql, QueryContext::default())
+            .await
+            .expect("Failed to run query");
+        column_values(&records, column)
+    };
+    let count_rows = async || query_values("SELECT COUNT(*) AS n FROM t", "n").await;
+
+    assert_eq!(
+        query_values("COPY INTO t FROM @s FILES = ('a.csv')", "file").await,
+        vec![format!("{stage_url}/a.csv")]
+    );
+    // Files loaded before are skipped
+    assert_eq!(
+        query_values("COPY INTO t FROM @s", "file").await,
+        vec![format!("{stage_url}/b.csv")]
     );
+    assert_eq!(
+        query_values("COPY INTO t FROM @s", "count").await,
+        vec!["0"]
+    );
+    assert_eq!(count_rows().await, vec!["2"]);
 
-    let _session = execution_svc
-        .create_session("test_session_id")
+    // A file that changed since it was loaded is loaded again
+    std::fs::write(temp_dir.join("a.csv"), "1,a\n3,c\n").expect("Failed to write file");
+    assert_eq!(
+        query_values("COPY INTO t FROM @s", "rows_loaded").await,
+        vec!["2"]
+    );
+    assert_eq!(
+        query_values("COPY INTO t FROM @s FORCE = TRUE", "rows_loaded").await,
+        vec!["2", "1"]
+    );
+    assert_eq!(count_rows().await, vec!["7"]);
+
+    // External locations are filtered by PATTERN and share the load history with stages
+    assert_eq!(
+        query_values(
+            &format!(
+                "COPY INTO t FROM '{stage_url}/' PATTERN = '.*b[.]csv' FILE_FORMAT = (TYPE = CSV)"
+            ),
+            "count",
+        )
+        .await,
+        vec!["0"]
+    );
+    assert_eq!(
+        query_values(
+            &format!(
+                "COPY INTO t FROM '{stage_url}/' PATTERN = '.*b[.]csv' \
+                 FILE_FORMAT = (TYPE = CSV) FORCE = TRUE"
+            ),
+            "file",
+        )
+        .await,
+        vec![format!("{stage_url}/b.csv")]
+    );
+
+    assert_eq!(
+        query_values(
+            "SELECT COUNT(*) AS n FROM information_schema.load_history WHERE table_name = 't'",
+            "n",
+        )
+        .await,
+        vec!["6"]
+    );
+    assert_eq!(
+        query_values(
+            "SELECT COUNT(*) AS n FROM TABLE(information_schema.copy_history(\
+             TABLE_NAME => 't', START_TIME => '2000-01-01'::TIMESTAMP)) WHERE file_name LIKE '%a.csv'",
+            "n",
+        )
+        .await,
+        vec!["3"]
+    );
+    assert_eq!(
+        query_values(
+            "SELECT COUNT(*) AS n FROM TABLE(information_schema.copy_history(\
+             TABLE_NAME => 'public.t', END_TIME => '2000-01-01'::TIMESTAMP))",
+            "n",
+        )
+        .await,
+        vec!["0"]
+    );
+
+    // The load history is dropped with the table and not restored by UNDROP
+    for sql in ["DROP TABLE t", "UNDROP TABLE t"] {
+        execution_svc
+            .query(session_id, sql, QueryContext::default())
+            .await
+            .expect("Failed to run query");
+    }
+    assert_eq!(
+        query_values("COPY INTO t FROM @s FILES = ('b.csv')", "rows_loaded").await,
+        vec!["1"]
+    );
+    let _ = std::fs::remove_dir_all(&temp_dir);
+}
+
+#[tokio::test]
+#[allow(clippy::expect_used, clippy::too_many_lines)]
+async fn test_service_copy_into_location_unload() {
+    let temp_dir = std::env::temp_dir().join("test_copy_into_location_unload");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    std::fs::create_dir_all(&temp_dir).expect("Failed to create stage directory");
+    let stage_url = format!("file://{}", temp_dir.display());
+
+    let (execution_svc, session_id) = test_service().await;
+
+    for sql in [
+        "CREATE TABLE embucket.public.t (id INT, name STRING)".to_string(),
+        "CREATE TABLE embucket.public.t2 (id INT, name STRING)".to_string(),
+        "INSERT INTO embucket.public.t VALUES (1, 'a'), (2, 'b'), (3, NULL)".to_string(),
+        format!("CREATE STAGE embucket.public.s URL = '{stage_url}'"),
+    ] {
+        execution_svc
+            .query(session_id, &sql, QueryContext::default())
+            .await
+            .expect("Failed to run setup query");
+    }
+    let query_values = async |sql: &str, column: &str| {
+        let QueryResult { records, .. } = execution_svc
+            .query(session_id, sql, QueryContext::default())
+            .await
+            .expect("Failed to run query");
+        column_values(&records, column)
+    };
+    let read_file = |path: &str| std::fs::read(temp_dir.join(path)).expect("Failed to read file");
+
+    let unload_csv = "COPY INTO @s/out/ FROM (SELECT id, name FROM t ORDER BY id) \
+                      FILE_FORMAT = (TYPE = CSV COMPRESSION = NONE) HEADER = TRUE";
+    assert_eq!(query_values(unload_csv, "rows_unloaded").await, vec!["3"]);
+    assert_eq!(
+        String::from_utf8(read_file("out/data_0_0_0.csv")).expect("Invalid file"),
+        "id,name\n1,a\n2,b\n3,\n"
+    );
+    // Existing files are only replaced with OVERWRITE
+    assert!(
+        execution_svc
+            .query(session_id, unload_csv, QueryContext::default())
+            .await
+            .is_err()
+    );
+    assert_eq!(
+        query_values(&format!("{unload_csv} OVERWRITE = TRUE"), "input_bytes").await,
+        vec!["19"]
+    );
+
+    // CSV files are gzipped by default and can be loaded again
+    assert_eq!(
+        query_values("COPY INTO @s/gz/ FROM t", "rows_unloaded").await,
+        vec!["3"]
+    );
+    assert!(temp_dir.join("gz/data_0_0_0.csv.gz").exists());
+    assert_eq!(
+        query_values("COPY INTO t2 FROM @s/gz/", "rows_loaded").await,
+        vec!["3"]
+    );
+
+    // Rows continue in a new file once a file reaches MAX_FILE_SIZE
+    assert_eq!(
+        query_values(
+            "COPY INTO @s/small/part FROM t FILE_FORMAT = (TYPE = CSV COMPRESSION = NONE) \
+             MAX_FILE_SIZE = 4",
+            "output_bytes",
+        )
+        .await,
+        vec!["11"]
+    );
+    assert_eq!(
+        std::fs::read_dir(temp_dir.join("small"))
+            .expect("Failed to list files")
+            .count(),
+        3
+    );
+    assert!(temp_dir.join("small/part_0_0_2.csv").exists());
+
+    // Partitions are directories, rows without a partition go to _NULL_
+    assert_eq!(
+        query_values(
+            "COPY INTO @s/parts/ FROM t PARTITION BY (name) \
+             FILE_FORMAT = (TYPE = JSON COMPRESSION = NONE)",
+            "rows_unloaded",
+        )
+        .await,
+        vec!["3"]
+    );
+    assert_eq!(
+        String::from_utf8(read_file("parts/a/data_0_0_0.json")).expect("Invalid file"),
+        "{\"id\":1,\"name\":\"a\"}\n"
+    );
+    assert!(temp_dir.join("parts/_NULL_/data_0_0_0.json").exists());
+
+    // A single file is named by the path
+    assert_eq!(
+        query_values(
+            "COPY INTO @s/single.parquet FROM t FILE_FORMAT = (TYPE = PARQUET) SINGLE = TRUE",
+            "rows_unloaded",
+        )
+        .await,
+        vec!["3"]
+    );
+    assert!(read_file("single.parquet").starts_with(b"PAR1"));
+
+    // External locations have to be locations of volumes, local files are refused
+    assert!(
+        execution_svc
+            .query(
+                session_id,
+                &format!("COPY INTO '{stage_url}/ext/' FROM (SELECT 1 AS x)"),
+                QueryContext::default(),
+            )
+            .await
+            .is_err()
+    );
+    assert!(!temp_dir.join("ext").exists());
+    assert_eq!(
+        query_values(
+            "COPY INTO 'memory:///unload/' FROM t FILE_FORMAT = (TYPE = CSV COMPRESSION = NONE)",
+            "output_bytes",
+        )
+        .await,
+        vec!["11"]
+    );
+    execution_svc
+        .query(
+            session_id,
+            "CREATE STAGE embucket.public.v URL = 'memory:///unload' STORAGE_INTEGRATION = test_volume",
+            QueryContext::default(),
+        )
         .await
-        .expect("Failed to create session");
+        .expect("Failed to create volume stage");
+    assert_eq!(
+        query_values(
+            "COPY INTO t2 FROM @v FILE_FORMAT = (TYPE = CSV)",
+            "rows_loaded"
+        )
+        .await,
+        vec!["3"]
+    );
+    let _ = std::fs::remove_dir_all(&temp_dir);
+}
 
-    let res = execution_svc
+// Avro object container file of `{"id": long, "name": string}` records, without a codec
+fn avro_file(rows: &[(i64, &str)]) -> Vec<u8> {
+    fn long(value: i64, output: &mut Vec<u8>) {
+        let mut value = u64::from_ne_bytes(((value << 1) ^ (value >> 63)).to_ne_bytes());
+        while value >= 0x80 {
+            output.push(u8::try_from(value & 0x7f).unwrap_or_default() | 0x80);
+            value >>= 7;
+        }
+        output.push(u8::try_from(value).unwrap_or_default());
+    }
+    fn bytes(value: &[u8], output: &mut Vec<u8>) {
+        long(i64::try_from(value.len()).unwrap_or_default(), output);
+        output.extend_from_slice(value);
+    }
+    let schema = r#"{"type": "record", "name": "row", "fields": [
+        {"name": "id", "type": "long"}, {"name": "name", "type": "string"}]}"#;
+    let sync = [7u8; 16];
+    let mut file = b"Obj\x01".to_vec();
+    long(1, &mut file);
+    bytes(b"avro.schema", &mut file);
+    bytes(schema.as_bytes(), &mut file);
+    long(0, &mut file);
+    file.extend_from_slice(&sync);
+    let mut block = Vec::new();
+    for (id, name) in rows {
+        long(*id, &mut block);
+        bytes(name.as_bytes(), &mut block);
+    }
+    long(i64::try_from(rows.len()).unwrap_or_default(), &mut file);
+    bytes(&block, &mut file);
+    file.extend_from_slice(&sync);
+    file
+}
+
+#[tokio::test]
+#[allow(clippy::expect_used)]
+async fn test_service_avro_files() {
+    let temp_dir = std::env::temp_dir().join("test_service_avro_files");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    std::fs::create_dir_all(&temp_dir).expect("Failed to create stage directory");
+    let stage_url = format!("file://{}", temp_dir.display());
+    let data = avro_file(&[(1, "a"), (2, "b")]);
+    std::fs::write(temp_dir.join("rows.avro"), &data).expect("Failed to write file");
+
+    let (execution_svc, session_id) = test_service().await;
+
+    for sql in [
+        "CREATE TABLE embucket.public.t (id INT, name STRING)".to_string(),
+        format!("CREATE STAGE embucket.public.s URL = '{stage_url}'"),
+        "CREATE FILE FORMAT embucket.public.avro_format TYPE = AVRO".to_string(),
+    ] {
+        execution_svc
+            .query(session_id, &sql, QueryContext::default())
+            .await
+            .expect("Failed to run setup query");
+    }
+    let query_values = async |sql: &str, column: &str| {
+        let QueryResult { records, .. } = execution_svc
+            .query(session_id, sql, QueryContext::default())
+            .await
+            .expect("Failed to run query");
+        column_values(&records, column)
+    };
+
+    assert_eq!(
+        query_values(
+            "COPY INTO t FROM @s/rows.avro FILE_FORMAT = (FORMAT_NAME = 'avro_format')",
+            "rows_loaded",
+        )
+        .await,
+        vec!["2"]
+    );
+    assert_eq!(
+        query_values("SELECT name FROM t ORDER BY id", "name").await,
+        vec!["a", "b"]
+    );
+    // Avro files can be loaded but not unloaded
+    assert!(
+        execution_svc
+            .query(
+                session_id,
+                "COPY INTO @s/out/ FROM t FILE_FORMAT = (TYPE = AVRO)",
+                QueryContext::default(),
+            )
+            .await
+            .is_err()
+    );
+
+    // Uploads create the table from the schema of the file
+    let table_ident = MetastoreTableIdent {
+        database: "embucket".to_string(),
+        schema: "public".to_string(),
+        table: "uploaded".to_string(),
+    };
+    let rows_loaded = execution_svc
+        .upload_data_to_table(
+            session_id,
+            &table_ident,
+            data.into(),
+            "rows.avro",
+            UploadFormat::Avro,
+            QueryContext::default(),
+        )
+        .await
+        .expect("Failed to upload file");
+    assert_eq!(rows_loaded, 2);
+    assert_eq!(
+        query_values("SELECT id FROM embucket.public.uploaded ORDER BY id", "id").await,
+        vec!["1", "2"]
+    );
+    let _ = std::fs::remove_dir_all(&temp_dir);
+}
+
+#[tokio::test]
+#[allow(clippy::expect_used, clippy::too_many_lines)]
+async fn test_service_infer_schema_using_template() {
+    let temp_dir = std::env::temp_dir().join("test_infer_schema_using_template");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    std::fs::create_dir_all(&temp_dir).expect("Failed to create stage directory");
+    let stage_url = format!("file://{}", temp_dir.display());
+
+    let (execution_svc, session_id) = test_service().await;
+
+    for sql in [
+        "CREATE TABLE embucket.public.t (id INT, name STRING)".to_string(),
+        "INSERT INTO embucket.public.t VALUES (1, 'a'), (2, 'b')".to_string(),
+        format!("CREATE STAGE embucket.public.s URL = '{stage_url}'"),
+        "CREATE FILE FORMAT embucket.public.fmt TYPE = PARQUET".to_string(),
+        "COPY INTO @s/data/ FROM t FILE_FORMAT = (TYPE = PARQUET)".to_string(),
+    ] {
+        execution_svc
+            .query(session_id, &sql, QueryContext::default())
+            .await
+            .expect("Failed to run setup query");
+    }
+    let query_values = async |sql: &str, column: &str| {
+        let QueryResult { records, .. } = execution_svc
+            .query(session_id, sql, QueryContext::default())
+            .await
+            .expect("Failed to run query");
+        column_values(&records, column)
+    };
+
+    let infer_schema = "SELECT * FROM TABLE(INFER_SCHEMA(\
+                        LOCATION => '@s/data/', FILE_FORMAT => 'fmt'))";
+    assert_eq!(
+        query_values(infer_schema, "column_name").await,
+        vec!["id", "name"]
+    );
+    assert_eq!(
+        query_values(infer_schema, "type").await,
+        vec!["NUMBER(38, 0)", "TEXT"]
+    );
+    assert_eq!(
+        query_values(infer_schema, "expression").await,
+        vec!["$1:id::NUMBER(38, 0)", "$1:name::TEXT"]
+    );
+    assert_eq!(
+        query_values(infer_schema, "filenames").await,
+        vec!["data/data_0_0_0.snappy.parquet"; 2]
+    );
+    assert_eq!(
+        query_values(
+            "SELECT column_name FROM TABLE(INFER_SCHEMA(LOCATION => '@s/data/', \
+             FILE_FORMAT => 'fmt', IGNORE_CASE => TRUE)) ORDER BY order_id",
+            "column_name",
+        )
+        .await,
+        vec!["ID", "NAME"]
+    );
+    // Locations without files have no columns
+    assert!(
+        query_values(
+            "SELECT * FROM TABLE(INFER_SCHEMA(LOCATION => '@s/none/', FILE_FORMAT => 'fmt'))",
+            "column_name",
+        )
+        .await
+        .is_empty()
+    );
+    // The location and the file format are required
+    assert!(
+        execution_svc
+            .query(
+                session_id,
+                "SELECT * FROM TABLE(INFER_SCHEMA(LOCATION => '@s/data/'))",
+                QueryContext::default(),
+            )
+            .await
+            .is_err()
+    );
+
+    execution_svc
         .query(
-            "test_session_id",
-            "SELECT sleep(3)",
+            session_id,
+            "CREATE TABLE embucket.public.t2 USING TEMPLATE (\
+             SELECT ARRAY_AGG(OBJECT_CONSTRUCT(*)) \
+             FROM TABLE(INFER_SCHEMA(LOCATION => '@s/data/', FILE_FORMAT => 'fmt')))",
             QueryContext::default(),
         )
-        .await;
+        .await
+        .expect("Failed to create table from template");
+    assert_eq!(
+        query_values(
+            "COPY INTO t2 FROM @s/data/ FILE_FORMAT = (FORMAT_NAME = 'fmt')",
+            "rows_loaded",
+        )
+        .await,
+        vec!["2"]
+    );
+    assert_eq!(
+        query_values("SELECT name FROM t2 ORDER BY id", "name").await,
+        vec!["a", "b"]
+    );
+    let _ = std::fs::remove_dir_all(&temp_dir);
+}
+
+#[tokio::test]
+#[allow(clippy::expect_used, clippy::too_many_lines)]
+async fn test_service_external_tables() {
+    let temp_dir = std::env::temp_dir().join("test_service_external_tables");
+    let _ = std::fs::remove_dir_all(&temp_dir);
+    for part in ["a", "b"] {
+        std::fs::create_dir_all(temp_dir.join("data").join(part))
+            .expect("Failed to create stage directory");
+    }
+    std::fs::write(temp_dir.join("data/a/1.csv"), "1,x\n2,y\n").expect("Failed to write file");
+    std::fs::write(temp_dir.join("data/b/1.csv"), "3,z\n").expect("Failed to write file");
+    let stage_url = format!("file://{}", temp_dir.display());
+
+    let (execution_svc, session_id) = test_service().await;
+
+    for sql in [
+        format!("CREATE STAGE embucket.public.s URL = '{stage_url}'"),
+        "CREATE EXTERNAL TABLE embucket.public.ext (\
+         part VARCHAR AS SPLIT_PART(METADATA$FILENAME, '/', 2), \
+         id NUMBER AS (value:c1::NUMBER), \
+         name VARCHAR AS (value:c2::VARCHAR)) \
+         PARTITION BY (part) LOCATION = @s/data/ \
+         FILE_FORMAT = (TYPE = CSV) AUTO_REFRESH = FALSE"
+            .to_string(),
+    ] {
+        execution_svc
+            .query(session_id, &sql, QueryContext::default())
+            .await
+            .expect("Failed to run setup query");
+    }
+    let query_values = async |sql: &str, column: &str| {
+        let QueryResult { records, .. } = execution_svc
+            .query(session_id, sql, QueryContext::default())
+            .await
+            .expect("Failed to run query");
+        column_values(&records, column)
+    };
+
+    assert_eq!(
+        query_values("SELECT name FROM ext ORDER BY id", "name").await,
+        vec!["x", "y", "z"]
+    );
+    assert_eq!(
+        query_values("SELECT id FROM ext WHERE part = 'b'", "id").await,
+        vec!["3"]
+    );
+    assert_eq!(
+        query_values(
+            "SELECT metadata$filename AS file FROM ext WHERE id = 2",
+            "file"
+        )
+        .await,
+        vec!["data/a/1.csv"]
+    );
+    assert_eq!(
+        query_values(
+            "SELECT metadata$file_row_number AS row_number FROM ext WHERE id = 2",
+            "row_number",
+        )
+        .await,
+        vec!["2"]
+    );
+
+    // New files are only read once the table is refreshed
+    std::fs::write(temp_dir.join("data/b/2.csv"), "4,w\n").expect("Failed to write file");
+    assert_eq!(
+        query_values("SELECT COUNT(*) AS rows FROM ext", "rows").await,
+        vec!["3"]
+    );
+    assert_eq!(
+        query_values("ALTER EXTERNAL TABLE ext REFRESH", "status").await,
+        vec!["REGISTERED_NEW"]
+    );
+    assert_eq!(
+        query_values("SELECT COUNT(*) AS rows FROM ext", "rows").await,
+        vec!["4"]
+    );
+    assert_eq!(
+        query_values(
+            "SELECT file_name FROM TABLE(information_schema.external_table_files(\
+             table_name => 'ext')) ORDER BY file_name",
+            "file_name",
+        )
+        .await,
+        vec!["data/a/1.csv", "data/b/1.csv", "data/b/2.csv"]
+    );
+
+    // Tables with AUTO_REFRESH are refreshed in the background, not when they are read
+    execution_svc
+        .query(
+            session_id,
+            "ALTER EXTERNAL TABLE ext SET AUTO_REFRESH = TRUE",
+            QueryContext::default(),
+        )
+        .await
+        .expect("Failed to alter external table");
+    std::fs::write(temp_dir.join("data/a/2.csv"), "5,v\n").expect("Failed to write file");
+    assert_eq!(
+        query_values("SELECT COUNT(*) AS rows FROM ext", "rows").await,
+        vec!["4"]
+    );
+    assert_eq!(
+        execution_svc
+            .refresh_external_tables()
+            .await
+            .expect("Failed to refresh external tables"),
+        1
+    );
+    assert_eq!(
+        query_values("SELECT COUNT(*) AS rows FROM ext", "rows").await,
+        vec!["5"]
+    );
+
+    // Files ruled out by the partition columns are never opened
+    std::fs::remove_dir_all(temp_dir.join("data/b")).expect("Failed to remove files");
+    assert_eq!(
+        query_values("SELECT id FROM ext WHERE part = 'a' ORDER BY id", "id").await,
+        vec!["1", "2", "5"]
+    );
+
+    // Partition columns can only be computed from the file path
     assert!(
-        res.is_err(),
-        "Expected query execution exceeded timeout error but got {res:?}"
+        execution_svc
+            .query(
+                session_id,
+                "CREATE EXTERNAL TABLE ext2 (id NUMBER AS (value:c1::NUMBER)) \
+                 PARTITION BY (id) LOCATION = @s/data/ FILE_FORMAT = (TYPE = CSV)",
+                QueryContext::default(),
+            )
+            .await
+            .is_err()
+    );
+    // External tables are read-only
+    assert!(
+        execution_svc
+            .query(
+                session_id,
+                "INSERT INTO ext (id) VALUES (5)",
+                QueryContext::default()
+            )
+            .await
+            .is_err()
+    );
+
+    execution_svc
+        .query(
+            session_id,
+            "DROP EXTERNAL TABLE ext",
+            QueryContext::default(),
+        )
+        .await
+        .expect("Failed to drop external table");
+    assert!(
+        execution_svc
+            .query(session_id, "SELECT * FROM ext", QueryContext::default())
+            .await
+            .is_err()
     );
+    let _ = std::fs::remove_dir_all(&temp_dir);
 }
@@ -0,0 +1,68 @@
+use crate::test_query;
+
+test_query!(
+    create_file_format,
+    "CREATE FILE FORMAT embucket.public.f TYPE = CSV FIELD_DELIMITER = '|' SKIP_HEADER = 1 NULL_IF = ('', 'NULL')",
+    snapshot_path = "file_format"
+);
+
+test_query!(
+    create_file_format_already_exists,
+    "CREATE FILE FORMAT f TYPE = JSON",
+    setup_queries = ["CREATE FILE FORMAT f TYPE = CSV"],
+    snapshot_path = "file_format"
+);
+
+test_query!(
+    create_file_format_if_not_exists,
+    "CREATE FILE FORMAT IF NOT EXISTS f TYPE = JSON",
+    setup_queries = ["CREATE FILE FORMAT f TYPE = CSV"],
+    snapshot_path = "file_format"
+);
+
+test_query!(
+    create_or_replace_file_format,
+    "CREATE OR REPLACE FILE FORMAT f TYPE = JSON STRIP_OUTER_ARRAY = TRUE",
+    setup_queries = ["CREATE FILE FORMAT f TYPE = CSV"],
+    snapshot_path = "file_format"
+);
+
+test_query!(
+    create_file_format_unsupported_option,
+    "CREATE FILE FORMAT f TYPE = JSON SKIP_HEADER = 1",
+    snapshot_path = "file_format"
+);
+
+test_query!(
+    create_file_format_invalid_option,
+    "CREATE FILE FORMAT f TYPE = CSV SKIP_HEADER = 'first'",
+    snapshot_path = "file_format"
+);
+
+test_query!(
+    drop_file_format,
+    "DROP FILE FORMAT f",
+    setup_queries = ["CREATE FILE FORMAT f TYPE = CSV"],
+    snapshot_path = "file_format"
+);
+
+test_query!(
+    drop_file_format_if_exists,
+    "DROP FILE FORMAT IF EXISTS missing",
+    snapshot_path = "file_format"
+);
+
+test_query!(
+    drop_missing_file_format,
+    "DROP FILE FORMAT missing",
+    snapshot_path = "file_format",
+    snowflake_error = true
+);
+
+test_query!(
+    copy_into_with_missing_file_format,
+    "COPY INTO t FROM @s FILE_FORMAT = (FORMAT_NAME = 'missing')",
+    setup_queries = ["CREATE TABLE embucket.public.t (id INT)", "CREATE STAGE s"],
+    snapshot_path = "file_format",
+    snowflake_error = true
+);
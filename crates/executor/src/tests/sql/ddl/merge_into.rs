@@ -344,3 +344,48 @@ test_query!(
     ],
     snapshot_path = "merge_into"
 );
+
+// Bounds of bucket, truncate and time partition fields are transformed values, so
+// they must not prune the target files.
+test_query!(
+    merge_into_bucket_partitioned,
+    "SELECT id, description FROM embucket.public.merge_target ORDER BY id",
+    setup_queries = [
+        "CREATE TABLE embucket.public.merge_target (id INTEGER, description VARCHAR) PARTITION BY (bucket(4, id))",
+        "CREATE TABLE embucket.public.merge_source (id INTEGER, description VARCHAR) PARTITION BY (bucket(4, id))",
+        "INSERT INTO embucket.public.merge_target VALUES (1, 'original row'), (2, 'original row'), (10, 'original row')",
+        "INSERT INTO embucket.public.merge_source VALUES (10, 'updated row'), (11, 'new row')",
+        "MERGE INTO merge_target t USING merge_source s ON t.id = s.id WHEN MATCHED THEN UPDATE SET t.description = s.description WHEN NOT MATCHED THEN INSERT (id, description) VALUES (s.id, s.description)",
+    ],
+    snapshot_path = "merge_into"
+);
+
+test_query!(
+    merge_into_day_partitioned,
+    "SELECT id, description FROM embucket.public.merge_target ORDER BY id",
+    setup_queries = [
+        "CREATE TABLE embucket.public.merge_target (id INTEGER, ts TIMESTAMP, description VARCHAR) PARTITION BY (day(ts))",
+        "CREATE TABLE embucket.public.merge_source (id INTEGER, ts TIMESTAMP, description VARCHAR) PARTITION BY (day(ts))",
+        "INSERT INTO embucket.public.merge_target VALUES (1, '2024-01-01 10:00:00', 'original row'), (2, '2024-03-01 10:00:00', 'original row')",
+        "INSERT INTO embucket.public.merge_source VALUES (2, '2024-03-01 10:00:00', 'updated row')",
+        "MERGE INTO merge_target t USING merge_source s ON t.ts = s.ts AND t.id = s.id WHEN MATCHED THEN UPDATE SET t.description = s.description",
+    ],
+    snapshot_path = "merge_into"
+);
+
+// The source files written before the spec change keep the older spec, where the
+// identity field of the region column has another position.
+test_query!(
+    merge_into_after_partition_spec_change,
+    "SELECT id, region, description FROM embucket.public.merge_target ORDER BY id",
+    setup_queries = [
+        "CREATE TABLE embucket.public.merge_target (id INTEGER, region VARCHAR, description VARCHAR) PARTITION BY (region)",
+        "CREATE TABLE embucket.public.merge_source (id INTEGER, region VARCHAR, description VARCHAR) PARTITION BY (region)",
+        "INSERT INTO embucket.public.merge_target VALUES (1, 'eu', 'original row'), (2, 'us', 'original row'), (3, 'us', 'original row')",
+        "INSERT INTO embucket.public.merge_source VALUES (1, 'eu', 'updated row')",
+        "ALTER TABLE merge_source SET PARTITION SPEC (bucket(4, id), region)",
+        "INSERT INTO embucket.public.merge_source VALUES (2, 'us', 'updated row')",
+        "MERGE INTO merge_target t USING merge_source s ON t.region = s.region AND t.id = s.id WHEN MATCHED THEN UPDATE SET t.description = s.description",
+    ],
+    snapshot_path = "merge_into"
+);
@@ -1,6 +1,10 @@
 mod database;
+mod file_format;
 mod merge_into;
+mod roles;
 mod schema;
+mod stage;
 mod table;
+mod users;
 mod view;
 mod volume;
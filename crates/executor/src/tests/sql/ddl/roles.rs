@@ -0,0 +1,191 @@
+use crate::test_query;
+
+test_query!(
+    grant_select,
+    "SELECT id FROM t",
+    setup_queries = [
+        "CREATE TABLE embucket.public.t (id INT)",
+        "INSERT INTO embucket.public.t VALUES (1)",
+        "CREATE ROLE analyst",
+        "GRANT USAGE ON DATABASE embucket TO ROLE analyst",
+        "GRANT USAGE ON SCHEMA embucket.public TO ROLE analyst",
+        "GRANT SELECT ON TABLE embucket.public.t TO ROLE analyst",
+        "USE ROLE analyst",
+    ],
+    snapshot_path = "roles"
+);
+
+test_query!(
+    select_without_privilege,
+    "SELECT id FROM t",
+    setup_queries = [
+        "CREATE TABLE embucket.public.t (id INT)",
+        "CREATE ROLE analyst",
+        "GRANT USAGE ON DATABASE embucket TO ROLE analyst",
+        "GRANT USAGE ON SCHEMA embucket.public TO ROLE analyst",
+        "USE ROLE analyst",
+    ],
+    snapshot_path = "roles",
+    snowflake_error = true
+);
+
+test_query!(
+    insert_without_privilege,
+    "INSERT INTO t VALUES (2)",
+    setup_queries = [
+        "CREATE TABLE embucket.public.t (id INT)",
+        "CREATE ROLE analyst",
+        "GRANT USAGE ON DATABASE embucket TO ROLE analyst",
+        "GRANT USAGE ON SCHEMA embucket.public TO ROLE analyst",
+        "GRANT SELECT ON TABLE embucket.public.t TO ROLE analyst",
+        "USE ROLE analyst",
+    ],
+    snapshot_path = "roles"
+);
+
+test_query!(
+    revoke_select,
+    "SELECT id FROM t",
+    setup_queries = [
+        "CREATE TABLE embucket.public.t (id INT)",
+        "CREATE ROLE analyst",
+        "GRANT USAGE ON DATABASE embucket TO ROLE analyst",
+        "GRANT USAGE ON SCHEMA embucket.public TO ROLE analyst",
+        "GRANT ALL PRIVILEGES ON TABLE embucket.public.t TO ROLE analyst",
+        "REVOKE SELECT ON TABLE embucket.public.t FROM ROLE analyst",
+        "USE ROLE analyst",
+    ],
+    snapshot_path = "roles"
+);
+
+test_query!(
+    create_table_grants_ownership,
+    "SELECT id FROM t",
+    setup_queries = [
+        "CREATE ROLE analyst",
+        "GRANT USAGE ON DATABASE embucket TO ROLE analyst",
+        "GRANT USAGE, CREATE TABLE ON SCHEMA embucket.public TO ROLE analyst",
+        "USE ROLE analyst",
+        "CREATE TABLE t (id INT)",
+        "INSERT INTO t VALUES (1)",
+    ],
+    snapshot_path = "roles"
+);
+
+test_query!(
+    drop_table_without_ownership,
+    "DROP TABLE t",
+    setup_queries = [
+        "CREATE TABLE embucket.public.t (id INT)",
+        "CREATE ROLE analyst",
+        "GRANT USAGE ON DATABASE embucket TO ROLE analyst",
+        "GRANT USAGE ON SCHEMA embucket.public TO ROLE analyst",
+        "GRANT ALL ON TABLE embucket.public.t TO ROLE analyst",
+        "USE ROLE analyst",
+    ],
+    snapshot_path = "roles"
+);
+
+test_query!(
+    create_database_without_accountadmin,
+    "CREATE DATABASE analytics EXTERNAL_VOLUME = 'test_volume'",
+    setup_queries = ["CREATE ROLE analyst", "USE ROLE analyst"],
+    snapshot_path = "roles"
+);
+
+test_query!(
+    set_role_variable,
+    "SET role = 'ACCOUNTADMIN'",
+    setup_queries = ["CREATE ROLE analyst", "USE ROLE analyst"],
+    snapshot_path = "roles"
+);
+
+test_query!(
+    alter_session_set_role,
+    "ALTER SESSION SET role = 'ACCOUNTADMIN'",
+    setup_queries = ["CREATE ROLE analyst", "USE ROLE analyst"],
+    snapshot_path = "roles"
+);
+
+test_query!(
+    use_role_not_found,
+    "USE ROLE analyst",
+    snapshot_path = "roles"
+);
+
+test_query!(
+    grant_on_missing_table,
+    "GRANT SELECT ON TABLE embucket.public.missing TO ROLE analyst",
+    setup_queries = ["CREATE ROLE analyst"],
+    snapshot_path = "roles"
+);
+
+test_query!(
+    drop_role_if_exists,
+    "DROP ROLE IF EXISTS analyst",
+    snapshot_path = "roles"
+);
+
+test_query!(
+    create_stage_without_privilege,
+    "CREATE STAGE s",
+    setup_queries = [
+        "CREATE ROLE analyst",
+        "GRANT USAGE ON DATABASE embucket TO ROLE analyst",
+        "GRANT USAGE ON SCHEMA embucket.public TO ROLE analyst",
+        "USE ROLE analyst",
+    ],
+    snapshot_path = "roles"
+);
+
+test_query!(
+    create_volume_stage_without_accountadmin,
+    "CREATE STAGE s URL = 'memory:///files' STORAGE_INTEGRATION = test_volume",
+    setup_queries = [
+        "CREATE ROLE analyst",
+        "GRANT USAGE ON DATABASE embucket TO ROLE analyst",
+        "GRANT USAGE, CREATE STAGE ON SCHEMA embucket.public TO ROLE analyst",
+        "USE ROLE analyst",
+    ],
+    snapshot_path = "roles"
+);
+
+test_query!(
+    list_stage_with_read,
+    "LIST @s",
+    setup_queries = [
+        "CREATE STAGE s",
+        "CREATE ROLE analyst",
+        "GRANT USAGE ON DATABASE embucket TO ROLE analyst",
+        "GRANT USAGE ON SCHEMA embucket.public TO ROLE analyst",
+        "GRANT READ ON STAGE s TO ROLE analyst",
+        "USE ROLE analyst",
+    ],
+    snapshot_path = "roles"
+);
+
+test_query!(
+    remove_without_write,
+    "REMOVE @s",
+    setup_queries = [
+        "CREATE STAGE s",
+        "CREATE ROLE analyst",
+        "GRANT USAGE ON DATABASE embucket TO ROLE analyst",
+        "GRANT USAGE ON SCHEMA embucket.public TO ROLE analyst",
+        "GRANT READ ON STAGE s TO ROLE analyst",
+        "USE ROLE analyst",
+    ],
+    snapshot_path = "roles"
+);
+
+test_query!(
+    copy_into_volume_location_without_accountadmin,
+    "COPY INTO 'memory:///unload/' FROM (SELECT 1 AS x)",
+    setup_queries = [
+        "CREATE ROLE analyst",
+        "GRANT USAGE ON DATABASE embucket TO ROLE analyst",
+        "GRANT USAGE ON SCHEMA embucket.public TO ROLE analyst",
+        "USE ROLE analyst",
+    ],
+    snapshot_path = "roles"
+);
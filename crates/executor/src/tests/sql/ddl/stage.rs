@@ -0,0 +1,162 @@
+use crate::test_query;
+
+test_query!(
+    create_stage,
+    "CREATE STAGE embucket.public.s URL = 's3://bucket/data' FILE_FORMAT = (TYPE = CSV)",
+    snapshot_path = "stage"
+);
+
+test_query!(
+    create_stage_already_exists,
+    "CREATE STAGE s URL = 's3://bucket/other'",
+    setup_queries = ["CREATE STAGE s URL = 's3://bucket/data'"],
+    snapshot_path = "stage"
+);
+
+test_query!(
+    create_stage_if_not_exists,
+    "CREATE STAGE IF NOT EXISTS s URL = 's3://bucket/other'",
+    setup_queries = ["CREATE STAGE s URL = 's3://bucket/data'"],
+    snapshot_path = "stage"
+);
+
+test_query!(
+    create_or_replace_stage,
+    "CREATE OR REPLACE STAGE s URL = 's3://bucket/other'",
+    setup_queries = ["CREATE STAGE s URL = 's3://bucket/data'"],
+    snapshot_path = "stage"
+);
+
+test_query!(
+    create_internal_stage,
+    "CREATE STAGE s",
+    snapshot_path = "stage"
+);
+
+test_query!(
+    create_stage_missing_volume,
+    "CREATE STAGE s URL = 'memory:///files' STORAGE_INTEGRATION = missing_volume",
+    snapshot_path = "stage"
+);
+
+test_query!(
+    list_empty_volume_stage,
+    "LIST @s",
+    setup_queries = ["CREATE STAGE s URL = 'memory:///files' STORAGE_INTEGRATION = test_volume"],
+    snapshot_path = "stage"
+);
+
+test_query!(
+    drop_stage,
+    "DROP STAGE s",
+    setup_queries = ["CREATE STAGE s URL = 's3://bucket/data'"],
+    snapshot_path = "stage"
+);
+
+test_query!(
+    drop_stage_if_exists,
+    "DROP STAGE IF EXISTS s",
+    snapshot_path = "stage"
+);
+
+test_query!(
+    list_dropped_stage,
+    "LIST @s",
+    setup_queries = [
+        "CREATE STAGE s URL = 'memory:///files' STORAGE_INTEGRATION = test_volume",
+        "DROP STAGE s",
+    ],
+    snapshot_path = "stage",
+    snowflake_error = true
+);
+
+test_query!(
+    drop_missing_stage,
+    "DROP STAGE missing",
+    snapshot_path = "stage",
+    snowflake_error = true
+);
+
+test_query!(
+    copy_into_from_missing_stage,
+    "COPY INTO t FROM @missing/data.csv",
+    setup_queries = ["CREATE TABLE embucket.public.t (id INT)"],
+    snapshot_path = "stage",
+    snowflake_error = true
+);
+
+test_query!(
+    list_empty_internal_stage,
+    "LIST @s",
+    setup_queries = ["CREATE STAGE s"],
+    snapshot_path = "stage"
+);
+
+test_query!(
+    put_internal_stage,
+    "PUT file:///tmp/data.csv @s/path AUTO_COMPRESS = FALSE OVERWRITE = TRUE",
+    setup_queries = ["CREATE STAGE s"],
+    snapshot_path = "stage"
+);
+
+test_query!(
+    get_internal_stage,
+    "GET @s 'file:///tmp/out dir/' PATTERN = '.*[.]csv'",
+    setup_queries = ["CREATE STAGE s"],
+    snapshot_path = "stage"
+);
+
+test_query!(
+    put_external_stage,
+    "PUT file:///tmp/data.csv @s",
+    setup_queries = ["CREATE STAGE s URL = 's3://bucket/data'"],
+    snapshot_path = "stage"
+);
+
+test_query!(
+    put_invalid_parallel,
+    "PUT file:///tmp/data.csv @s PARALLEL = 100",
+    setup_queries = ["CREATE STAGE s"],
+    snapshot_path = "stage"
+);
+
+test_query!(
+    get_missing_stage,
+    "GET @missing file:///tmp/out",
+    snapshot_path = "stage",
+    snowflake_error = true
+);
+
+test_query!(
+    copy_into_invalid_on_error,
+    "COPY INTO t FROM @s ON_ERROR = SKIP_FILE_X",
+    setup_queries = ["CREATE TABLE embucket.public.t (id INT)", "CREATE STAGE s"],
+    snapshot_path = "stage"
+);
+
+test_query!(
+    copy_into_invalid_validation_mode,
+    "COPY INTO t FROM @s VALIDATION_MODE = RETURN_ROWS",
+    setup_queries = ["CREATE TABLE embucket.public.t (id INT)", "CREATE STAGE s"],
+    snapshot_path = "stage"
+);
+
+test_query!(
+    create_stage_overlapping_table,
+    "CREATE STAGE s URL = 'memory:///embucket/public' STORAGE_INTEGRATION = test_volume",
+    setup_queries = ["CREATE TABLE embucket.public.t (id INT)"],
+    snapshot_path = "stage"
+);
+
+test_query!(
+    copy_into_file_location,
+    "COPY INTO 'file:///tmp/unload/' FROM (SELECT 1 AS x)",
+    snapshot_path = "stage"
+);
+
+test_query!(
+    copy_into_location_over_table,
+    "COPY INTO 'memory:///embucket/public/' FROM (SELECT 1 AS x)",
+    setup_queries = ["CREATE TABLE embucket.public.t (id INT)"],
+    snapshot_path = "stage"
+);
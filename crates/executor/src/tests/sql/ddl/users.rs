@@ -0,0 +1,75 @@
+use crate::test_query;
+
+test_query!(
+    create_user,
+    "CREATE USER alice PASSWORD = 'secret' COMMENT = 'analyst'",
+    snapshot_path = "users"
+);
+
+test_query!(
+    create_user_already_exists,
+    "CREATE USER Alice PASSWORD = 'secret'",
+    setup_queries = ["CREATE USER alice"],
+    snapshot_path = "users"
+);
+
+test_query!(
+    create_user_if_not_exists,
+    "CREATE USER IF NOT EXISTS alice",
+    setup_queries = ["CREATE USER alice"],
+    snapshot_path = "users"
+);
+
+test_query!(
+    alter_user_set,
+    "ALTER USER alice SET PASSWORD = 'changed' DISABLED = TRUE",
+    setup_queries = ["CREATE USER alice PASSWORD = 'secret'"],
+    snapshot_path = "users"
+);
+
+test_query!(
+    alter_user_rename,
+    "DROP USER bob",
+    setup_queries = ["CREATE USER alice", "ALTER USER alice RENAME TO bob"],
+    snapshot_path = "users"
+);
+
+test_query!(
+    alter_user_not_found,
+    "ALTER USER alice UNSET COMMENT",
+    snapshot_path = "users"
+);
+
+test_query!(
+    drop_user_not_found,
+    "DROP USER alice",
+    snapshot_path = "users"
+);
+
+test_query!(
+    drop_user_if_exists,
+    "DROP USER IF EXISTS alice",
+    snapshot_path = "users"
+);
+
+test_query!(
+    alter_user_without_accountadmin,
+    "ALTER USER alice SET PASSWORD = 'changed'",
+    setup_queries = [
+        "CREATE USER alice PASSWORD = 'secret'",
+        "CREATE ROLE analyst",
+        "USE ROLE analyst",
+    ],
+    snapshot_path = "users"
+);
+
+test_query!(
+    drop_user_without_accountadmin,
+    "DROP USER alice",
+    setup_queries = [
+        "CREATE USER alice PASSWORD = 'secret'",
+        "CREATE ROLE analyst",
+        "USE ROLE analyst",
+    ],
+    snapshot_path = "users"
+);
@@ -0,0 +1,105 @@
+use crate::test_query;
+
+test_query!(
+    delete_rowcount,
+    "DELETE FROM delete_target WHERE id = 2",
+    setup_queries = [
+        "CREATE TABLE embucket.public.delete_target (id INTEGER, description VARCHAR)",
+        "INSERT INTO embucket.public.delete_target VALUES (1, 'row one'), (2, 'row two'), (3, 'row three')",
+    ],
+    snapshot_path = "delete"
+);
+
+test_query!(
+    delete_where,
+    "SELECT id, description FROM embucket.public.delete_target ORDER BY id",
+    setup_queries = [
+        "CREATE TABLE embucket.public.delete_target (id INTEGER, description VARCHAR)",
+        "INSERT INTO embucket.public.delete_target VALUES (1, 'row one'), (2, 'row two'), (3, 'row three')",
+        "DELETE FROM delete_target WHERE id = 2",
+    ],
+    snapshot_path = "delete"
+);
+
+test_query!(
+    delete_all_rows_rowcount,
+    "DELETE FROM delete_target",
+    setup_queries = [
+        "CREATE TABLE embucket.public.delete_target (id INTEGER, description VARCHAR)",
+        "INSERT INTO embucket.public.delete_target VALUES (1, 'row one'), (2, 'row two'), (3, 'row three')",
+    ],
+    snapshot_path = "delete"
+);
+
+test_query!(
+    delete_all_rows,
+    "SELECT COUNT(*) AS cnt FROM embucket.public.delete_target",
+    setup_queries = [
+        "CREATE TABLE embucket.public.delete_target (id INTEGER, description VARCHAR)",
+        "INSERT INTO embucket.public.delete_target VALUES (1, 'row one'), (2, 'row two'), (3, 'row three')",
+        "DELETE FROM delete_target",
+    ],
+    snapshot_path = "delete"
+);
+
+test_query!(
+    delete_no_matching_rows,
+    "DELETE FROM delete_target WHERE id = 10",
+    setup_queries = [
+        "CREATE TABLE embucket.public.delete_target (id INTEGER, description VARCHAR)",
+        "INSERT INTO embucket.public.delete_target VALUES (1, 'row one'), (2, 'row two'), (3, 'row three')",
+    ],
+    snapshot_path = "delete"
+);
+
+test_query!(
+    delete_using_rowcount,
+    "DELETE FROM delete_target USING delete_source WHERE delete_target.id = delete_source.id",
+    setup_queries = [
+        "CREATE TABLE embucket.public.delete_target (id INTEGER, description VARCHAR)",
+        "INSERT INTO embucket.public.delete_target VALUES (1, 'row one'), (2, 'row two'), (3, 'row three')",
+        "CREATE TABLE embucket.public.delete_source (id INTEGER)",
+        "INSERT INTO embucket.public.delete_source VALUES (1), (3), (3)",
+    ],
+    snapshot_path = "delete"
+);
+
+test_query!(
+    delete_using,
+    "SELECT id, description FROM embucket.public.delete_target ORDER BY id",
+    setup_queries = [
+        "CREATE TABLE embucket.public.delete_target (id INTEGER, description VARCHAR)",
+        "INSERT INTO embucket.public.delete_target VALUES (1, 'row one'), (2, 'row two'), (3, 'row three')",
+        "CREATE TABLE embucket.public.delete_source (id INTEGER)",
+        "INSERT INTO embucket.public.delete_source VALUES (1), (3), (3)",
+        "DELETE FROM delete_target t USING delete_source s WHERE t.id = s.id",
+    ],
+    snapshot_path = "delete"
+);
+
+// The target rows matching the source lie in partitions the source has no rows in
+test_query!(
+    delete_using_other_partitions,
+    "SELECT id, region FROM embucket.public.delete_target ORDER BY id",
+    setup_queries = [
+        "CREATE TABLE embucket.public.delete_target (id INTEGER, region VARCHAR) PARTITION BY (region)",
+        "INSERT INTO embucket.public.delete_target VALUES (1, 'eu'), (2, 'us'), (3, 'us')",
+        "CREATE TABLE embucket.public.delete_source (id INTEGER, region VARCHAR) PARTITION BY (region)",
+        "INSERT INTO embucket.public.delete_source VALUES (2, 'eu'), (3, 'eu')",
+        "DELETE FROM delete_target t USING delete_source s WHERE t.id = s.id",
+    ],
+    snapshot_path = "delete"
+);
+
+test_query!(
+    delete_using_same_partitions,
+    "SELECT id, region FROM embucket.public.delete_target ORDER BY id",
+    setup_queries = [
+        "CREATE TABLE embucket.public.delete_target (id INTEGER, region VARCHAR) PARTITION BY (region)",
+        "INSERT INTO embucket.public.delete_target VALUES (1, 'eu'), (2, 'us'), (3, 'us')",
+        "CREATE TABLE embucket.public.delete_source (id INTEGER, region VARCHAR) PARTITION BY (region)",
+        "INSERT INTO embucket.public.delete_source VALUES (2, 'eu'), (3, 'eu')",
+        "DELETE FROM delete_target t USING delete_source s WHERE t.region = s.region AND t.id = s.id",
+    ],
+    snapshot_path = "delete"
+);
@@ -1 +1,3 @@
+mod delete;
 mod select;
+mod update;
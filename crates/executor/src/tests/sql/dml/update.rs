@@ -0,0 +1,102 @@
+use crate::test_query;
+
+test_query!(
+    update_rowcount,
+    "UPDATE update_target SET description = 'updated row' WHERE id = 2",
+    setup_queries = [
+        "CREATE TABLE embucket.public.update_target (id INTEGER, description VARCHAR)",
+        "INSERT INTO embucket.public.update_target VALUES (1, 'row one'), (2, 'row two'), (3, 'row three')",
+    ],
+    snapshot_path = "update"
+);
+
+test_query!(
+    update_where,
+    "SELECT id, description FROM embucket.public.update_target ORDER BY id",
+    setup_queries = [
+        "CREATE TABLE embucket.public.update_target (id INTEGER, description VARCHAR)",
+        "INSERT INTO embucket.public.update_target VALUES (1, 'row one'), (2, 'row two'), (3, 'row three')",
+        "UPDATE update_target SET description = 'updated row' WHERE id = 2",
+    ],
+    snapshot_path = "update"
+);
+
+test_query!(
+    update_all_rows_rowcount,
+    "UPDATE update_target SET description = 'updated row'",
+    setup_queries = [
+        "CREATE TABLE embucket.public.update_target (id INTEGER, description VARCHAR)",
+        "INSERT INTO embucket.public.update_target VALUES (1, 'row one'), (2, 'row two'), (3, 'row three')",
+    ],
+    snapshot_path = "update"
+);
+
+test_query!(
+    update_no_matching_rows,
+    "UPDATE update_target SET description = 'updated row' WHERE id = 10",
+    setup_queries = [
+        "CREATE TABLE embucket.public.update_target (id INTEGER, description VARCHAR)",
+        "INSERT INTO embucket.public.update_target VALUES (1, 'row one'), (2, 'row two'), (3, 'row three')",
+    ],
+    snapshot_path = "update"
+);
+
+test_query!(
+    update_from_rowcount,
+    "UPDATE update_target t SET description = s.description FROM update_source s WHERE t.id = s.id",
+    setup_queries = [
+        "CREATE TABLE embucket.public.update_target (id INTEGER, description VARCHAR)",
+        "CREATE TABLE embucket.public.update_source (id INTEGER, description VARCHAR)",
+        "INSERT INTO embucket.public.update_target VALUES (1, 'row one'), (2, 'row two'), (3, 'row three')",
+        "INSERT INTO embucket.public.update_source VALUES (2, 'from source'), (3, 'from source')",
+    ],
+    snapshot_path = "update"
+);
+
+test_query!(
+    update_from,
+    "SELECT id, description FROM embucket.public.update_target ORDER BY id",
+    setup_queries = [
+        "CREATE TABLE embucket.public.update_target (id INTEGER, description VARCHAR)",
+        "CREATE TABLE embucket.public.update_source (id INTEGER, description VARCHAR)",
+        "INSERT INTO embucket.public.update_target VALUES (1, 'row one'), (2, 'row two'), (3, 'row three')",
+        "INSERT INTO embucket.public.update_source VALUES (2, 'from source'), (3, 'from source')",
+        "UPDATE update_target t SET description = s.description FROM update_source s WHERE t.id = s.id",
+    ],
+    snapshot_path = "update"
+);
+
+test_query!(
+    update_from_duplicate_source_rows_rowcount,
+    "UPDATE update_target t SET description = s.description FROM update_source s WHERE t.id = s.id",
+    setup_queries = [
+        "CREATE TABLE embucket.public.update_target (id INTEGER, description VARCHAR)",
+        "CREATE TABLE embucket.public.update_source (id INTEGER, description VARCHAR)",
+        "INSERT INTO embucket.public.update_target VALUES (1, 'row one'), (2, 'row two'), (3, 'row three')",
+        "INSERT INTO embucket.public.update_source VALUES (2, 'from source'), (2, 'from source'), (2, 'from source')",
+    ],
+    snapshot_path = "update"
+);
+
+test_query!(
+    update_from_duplicate_source_rows,
+    "SELECT id, description FROM embucket.public.update_target ORDER BY id",
+    setup_queries = [
+        "CREATE TABLE embucket.public.update_target (id INTEGER, description VARCHAR)",
+        "CREATE TABLE embucket.public.update_source (id INTEGER, description VARCHAR)",
+        "INSERT INTO embucket.public.update_target VALUES (1, 'row one'), (2, 'row two'), (3, 'row three')",
+        "INSERT INTO embucket.public.update_source VALUES (2, 'from source'), (2, 'from source'), (2, 'from source')",
+        "UPDATE update_target t SET description = s.description FROM update_source s WHERE t.id = s.id",
+    ],
+    snapshot_path = "update"
+);
+
+test_query!(
+    update_unknown_column,
+    "UPDATE update_target SET missing = 'updated row'",
+    setup_queries = [
+        "CREATE TABLE embucket.public.update_target (id INTEGER, description VARCHAR)",
+        "INSERT INTO embucket.public.update_target VALUES (1, 'row one'), (2, 'row two'), (3, 'row three')",
+    ],
+    snapshot_path = "update"
+);
@@ -1,3 +1,4 @@
 mod alter;
 mod explain;
 mod set;
+mod transaction;
@@ -0,0 +1,81 @@
+use crate::test_query;
+
+test_query!(transaction_begin, "BEGIN", snapshot_path = "transaction");
+
+test_query!(
+    transaction_commit,
+    "SELECT id, description FROM embucket.public.tx_target ORDER BY id",
+    setup_queries = [
+        "CREATE TABLE embucket.public.tx_target (id INTEGER, description VARCHAR)",
+        "INSERT INTO embucket.public.tx_target VALUES (1, 'row one'), (2, 'row two')",
+        "BEGIN",
+        "INSERT INTO embucket.public.tx_target VALUES (3, 'row three')",
+        "UPDATE embucket.public.tx_target SET description = 'updated' WHERE id = 1",
+        "COMMIT",
+    ],
+    snapshot_path = "transaction"
+);
+
+test_query!(
+    transaction_rollback,
+    "SELECT id, description FROM embucket.public.tx_target ORDER BY id",
+    setup_queries = [
+        "CREATE TABLE embucket.public.tx_target (id INTEGER, description VARCHAR)",
+        "INSERT INTO embucket.public.tx_target VALUES (1, 'row one'), (2, 'row two')",
+        "BEGIN TRANSACTION",
+        "INSERT INTO embucket.public.tx_target VALUES (3, 'row three')",
+        "DELETE FROM embucket.public.tx_target WHERE id = 1",
+        "ROLLBACK",
+    ],
+    snapshot_path = "transaction"
+);
+
+test_query!(
+    transaction_read_own_writes,
+    "SELECT id, description FROM embucket.public.tx_target ORDER BY id",
+    setup_queries = [
+        "CREATE TABLE embucket.public.tx_target (id INTEGER, description VARCHAR)",
+        "INSERT INTO embucket.public.tx_target VALUES (1, 'row one')",
+        "START TRANSACTION",
+        "INSERT INTO embucket.public.tx_target VALUES (2, 'row two')",
+        "DELETE FROM embucket.public.tx_target WHERE id = 1",
+    ],
+    snapshot_path = "transaction"
+);
+
+// DDL statements implicitly commit the open transaction
+test_query!(
+    transaction_ddl_implicit_commit,
+    "SELECT id, description FROM embucket.public.tx_target ORDER BY id",
+    setup_queries = [
+        "CREATE TABLE embucket.public.tx_target (id INTEGER, description VARCHAR)",
+        "BEGIN",
+        "INSERT INTO embucket.public.tx_target VALUES (1, 'row one')",
+        "CREATE TABLE embucket.public.tx_other (id INTEGER)",
+        "ROLLBACK",
+    ],
+    snapshot_path = "transaction"
+);
+
+test_query!(
+    transaction_current_transaction,
+    "SELECT CURRENT_TRANSACTION() IS NOT NULL AS in_transaction,
+        LAST_TRANSACTION() IS NULL AS no_last_transaction",
+    setup_queries = ["BEGIN"],
+    snapshot_path = "transaction"
+);
+
+test_query!(
+    transaction_last_transaction,
+    "SELECT CURRENT_TRANSACTION() IS NULL AS no_transaction,
+        LAST_TRANSACTION() IS NOT NULL AS last_transaction",
+    setup_queries = ["BEGIN", "COMMIT"],
+    snapshot_path = "transaction"
+);
+
+test_query!(
+    transaction_rollback_to_savepoint,
+    "ROLLBACK TO SAVEPOINT sp1",
+    setup_queries = ["BEGIN"],
+    snapshot_path = "transaction"
+);
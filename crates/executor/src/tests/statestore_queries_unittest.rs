@@ -6,9 +6,11 @@ use crate::utils::Config;
 use crate::{QueryResult, SessionMetadata};
 use catalog_metastore::InMemoryMetastore;
 use catalog_metastore::metastore_bootstrap_config::MetastoreBootstrapConfig;
+use chrono::Utc;
+use datafusion::arrow::array::Int64Array;
 use insta::assert_json_snapshot;
 use state_store::{MockStateStore, Query, SessionRecord};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use tokio::time::{Duration, timeout};
 use uuid::Uuid;
 
@@ -91,7 +93,7 @@ impl Mocker {
     ) -> Result<Arc<UserSession>> {
         timeout(
             MOCK_RELATED_TIMEOUT_DURATION,
-            executor.create_session(session_id),
+            executor.create_internal_session(session_id),
         )
         .await
         .expect("Create session timed out")
@@ -461,6 +463,7 @@ async fn test_query_lifecycle_ok_update() {
                   "execution_status": "Success",
                   "start_time": "2026-01-01T01:01:01.000000001Z",
                   "end_time": "2026-01-01T01:01:01.000000001Z",
+                  "rows_updated": 4,
                   "execution_time": "1",
                   "release_version": "test-version",
                   "query_hash": "16763742305627145642",
@@ -525,7 +528,7 @@ async fn test_query_lifecycle_ok_update() {
 
 #[allow(clippy::expect_used)]
 #[tokio::test]
-async fn test_query_lifecycle_delete_failed() {
+async fn test_query_lifecycle_ok_delete() {
     let mut state_store_mock = MockStateStore::new();
     Mocker::apply_create_session_mock(&mut state_store_mock, |_| {
         Ok(SessionRecord::new(TEST_SESSION_ID))
@@ -538,7 +541,7 @@ async fn test_query_lifecycle_delete_failed() {
         .times(1)
         .returning(|_| Ok(()))
         .withf(move |query: &Query| {
-            insta_settings("ok_truncate_update").bind(|| {
+            insta_settings("ok_delete_update").bind(|| {
                 assert_json_snapshot!(query, @r#"
                 {
                   "query_id": "00000000-0000-0000-0000-000000000000",
@@ -549,15 +552,15 @@ async fn test_query_lifecycle_delete_failed() {
                   "schema_name": "public",
                   "query_type": "DELETE",
                   "warehouse_type": "DEFAULT",
-                  "execution_status": "Fail",
-                  "error_code": "010001",
-                  "error_message": "00000000-0000-0000-0000-000000000000: Query execution error: DataFusion error: This feature is not implemented: Unsupported logical plan: Dml(Delete)",
+                  "execution_status": "Success",
                   "start_time": "2026-01-01T01:01:01.000000001Z",
                   "end_time": "2026-01-01T01:01:01.000000001Z",
+                  "rows_deleted": 4,
                   "execution_time": "1",
                   "release_version": "test-version",
                   "query_hash": "13652442282618196356",
-                  "query_hash_version": 1
+                  "query_hash_version": 1,
+                  "query_metrics": "[query_metrics]"
                 }
                 "#);
             });
@@ -612,7 +615,7 @@ async fn test_query_lifecycle_delete_failed() {
         "DELETE FROM embucket.public.table",
     )
     .await
-    .expect_err("Query expected to fail");
+    .expect("Query execution failed");
 }
 
 #[allow(clippy::expect_used)]
@@ -1077,3 +1080,87 @@ async fn test_query_lifecycle_query_status_cancelled() {
         .expect("Query timed out")
         .expect("Failed to cancel query");
 }
+
+#[allow(clippy::expect_used)]
+#[tokio::test]
+async fn test_time_travel_statement_from_query_history() {
+    let mut state_store_mock = MockStateStore::new();
+    Mocker::apply_create_session_mock(&mut state_store_mock, |_| {
+        Ok(SessionRecord::new(TEST_SESSION_ID))
+    });
+    Mocker::apply_bypass_queries_mock(&mut state_store_mock, 4);
+    // The statement is only known to the query history, which has it end between the
+    // two inserts
+    let statement_id = Uuid::new_v4();
+    let statement_end = Arc::new(Mutex::new(Utc::now()));
+    let history_end = statement_end.clone();
+    state_store_mock
+        .expect_get_query()
+        .withf(move |query_id: &str| query_id == statement_id.to_string())
+        .returning(move |_| {
+            let mut query = Query::new(
+                "INSERT INTO t VALUES (1)",
+                statement_id,
+                TEST_SESSION_ID,
+                None,
+            );
+            query.end_time = Some(*history_end.lock().expect("Failed to lock end time"));
+            Ok(query)
+        });
+
+    let ctx = QueryContext::default();
+    let metastore = Arc::new(InMemoryMetastore::new());
+    MetastoreBootstrapConfig::bootstrap()
+        .apply(metastore.clone())
+        .await
+        .expect("Failed to bootstrap metastore");
+    let ex: Arc<dyn ExecutionService> = Arc::new(
+        CoreExecutionService::new_test_executor(
+            metastore,
+            Arc::new(state_store_mock),
+            Arc::new(Config::default()),
+        )
+        .await
+        .expect("Failed to create execution service"),
+    );
+    Mocker::create_session(ex.clone(), TEST_SESSION_ID)
+        .await
+        .expect("Failed to create session");
+
+    for sql in ["CREATE TABLE t (a INT)", "INSERT INTO t VALUES (1)"] {
+        Mocker::query(ex.clone(), TEST_SESSION_ID, ctx.clone(), sql)
+            .await
+            .expect("Query execution failed");
+    }
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    *statement_end.lock().expect("Failed to lock end time") = Utc::now();
+    tokio::time::sleep(Duration::from_millis(5)).await;
+    Mocker::query(
+        ex.clone(),
+        TEST_SESSION_ID,
+        ctx.clone(),
+        "INSERT INTO t VALUES (2)",
+    )
+    .await
+    .expect("Query execution failed");
+
+    let QueryResult { records, .. } = Mocker::query(
+        ex.clone(),
+        TEST_SESSION_ID,
+        ctx,
+        &format!("SELECT COUNT(*) AS rows FROM t AT(STATEMENT => '{statement_id}')"),
+    )
+    .await
+    .expect("Query execution failed");
+    assert_eq!(
+        records
+            .first()
+            .expect("Missing result")
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .expect("Unexpected column type")
+            .value(0),
+        1
+    );
+}
@@ -0,0 +1,202 @@
+use crate::error::{self as ex_error, Result};
+use datafusion::sql::sqlparser::ast::{
+    Expr, Function, FunctionArg, FunctionArgExpr, FunctionArgumentList, FunctionArguments, Ident,
+    ObjectName, ObjectNamePart, Statement, TableAlias, TableFactor, TableVersion, VisitMut,
+    VisitorMut,
+};
+use iceberg_rust::spec::table_metadata::TableMetadata;
+use std::ops::ControlFlow;
+
+/// Point in the history of a table a time travel clause refers to
+#[derive(Debug, Clone, PartialEq)]
+pub enum TimeTravelPoint {
+    /// `TIMESTAMP => <expr>`, a point in time
+    Timestamp(Expr),
+    /// `OFFSET => <expr>`, seconds relative to the current time, usually negative
+    Offset(Expr),
+    /// `STATEMENT => <query_id>`, the start or end of a statement
+    Statement(Expr),
+}
+
+/// `AT(...)` or `BEFORE(...)` clause of a table in the FROM clause of a query.
+///
+/// `AT` reads the table as of the point, including changes committed at that point
+/// or by the statement, `BEFORE` reads it right before the point.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeTravel {
+    pub before: bool,
+    pub point: TimeTravelPoint,
+}
+
+impl TimeTravel {
+    pub fn parse(table: &str, version: &TableVersion) -> Result<Self> {
+        let TableVersion::Function(Expr::Function(Function {
+            name,
+            args: FunctionArguments::List(FunctionArgumentList { args, .. }),
+            ..
+        })) = version
+        else {
+            return Err(invalid_time_travel(
+                table,
+                format!("unsupported clause {version}"),
+            ));
+        };
+        let before = match name.to_string().to_ascii_uppercase().as_str() {
+            "AT" => false,
+            "BEFORE" => true,
+            other => {
+                return Err(invalid_time_travel(
+                    table,
+                    format!("unsupported clause {other}, expected AT or BEFORE"),
+                ));
+            }
+        };
+        let [arg] = args.as_slice() else {
+            return Err(invalid_time_travel(
+                table,
+                format!("{name} expects exactly one of TIMESTAMP, OFFSET or STATEMENT"),
+            ));
+        };
+        let (kind, expr) = match arg {
+            FunctionArg::Named {
+                name,
+                arg: FunctionArgExpr::Expr(expr),
+                ..
+            }
+            | FunctionArg::ExprNamed {
+                name: Expr::Identifier(name),
+                arg: FunctionArgExpr::Expr(expr),
+                ..
+            } => (name.value.to_ascii_uppercase(), expr.clone()),
+            _ => {
+                return Err(invalid_time_travel(
+                    table,
+                    format!("invalid argument {arg} of {name}"),
+                ));
+            }
+        };
+        let point = match kind.as_str() {
+            "TIMESTAMP" => TimeTravelPoint::Timestamp(expr),
+            "OFFSET" => TimeTravelPoint::Offset(expr),
+            "STATEMENT" => TimeTravelPoint::Statement(expr),
+            _ => {
+                return Err(invalid_time_travel(
+                    table,
+                    format!("unsupported argument {kind}, expected TIMESTAMP, OFFSET or STATEMENT"),
+                ));
+            }
+        };
+        Ok(Self { before, point })
+    }
+}
+
+/// Table of a statement read with a time travel clause
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeTravelTable {
+    /// Name the table is referenced by in the rewritten statement
+    pub placeholder: ObjectName,
+    /// Name of the table the clause applies to
+    pub table: ObjectName,
+    pub time_travel: TimeTravel,
+}
+
+/// Removes the `AT` and `BEFORE` clauses from the tables of a statement.
+///
+/// Each table with a clause is renamed to a placeholder, so the planner can resolve it
+/// to a provider reading the snapshot the clause points to, while the same table without
+/// a clause still reads the current snapshot. A table without an alias is aliased to its
+/// own name so columns qualified with the table name keep resolving.
+pub fn take_time_travel_tables(statement: &mut Statement) -> Result<Vec<TimeTravelTable>> {
+    let mut visitor = TimeTravelVisitor::default();
+    match statement.visit(&mut visitor) {
+        ControlFlow::Break(err) => Err(err),
+        ControlFlow::Continue(()) => Ok(visitor.tables),
+    }
+}
+
+#[derive(Debug, Default)]
+struct TimeTravelVisitor {
+    tables: Vec<TimeTravelTable>,
+}
+
+impl VisitorMut for TimeTravelVisitor {
+    type Break = ex_error::Error;
+
+    fn pre_visit_table_factor(
+        &mut self,
+        table_factor: &mut TableFactor,
+    ) -> ControlFlow<Self::Break> {
+        let TableFactor::Table {
+            name,
+            alias,
+            version,
+            ..
+        } = table_factor
+        else {
+            return ControlFlow::Continue(());
+        };
+        let Some(clause) = version.take() else {
+            return ControlFlow::Continue(());
+        };
+        let time_travel = match TimeTravel::parse(&name.to_string(), &clause) {
+            Ok(time_travel) => time_travel,
+            Err(err) => return ControlFlow::Break(err),
+        };
+        let Some(ObjectNamePart::Identifier(table)) = name.0.last().cloned() else {
+            return ControlFlow::Continue(());
+        };
+        let mut placeholder = name.clone();
+        if let Some(last) = placeholder.0.last_mut() {
+            *last = ObjectNamePart::Identifier(Ident::with_quote(
+                '"',
+                format!("{}@{}", table.value.to_ascii_lowercase(), self.tables.len()),
+            ));
+        }
+        if alias.is_none() {
+            *alias = Some(TableAlias {
+                name: table,
+                columns: vec![],
+            });
+        }
+        self.tables.push(TimeTravelTable {
+            placeholder: placeholder.clone(),
+            table: std::mem::replace(name, placeholder),
+            time_travel,
+        });
+        ControlFlow::Continue(())
+    }
+}
+
+/// Id of the snapshot of the main branch the table was at `timestamp_ms`, that is the
+/// latest snapshot committed at that time or, with `before`, strictly before it.
+/// Returns `None` if the table had no snapshot yet.
+#[must_use]
+pub fn snapshot_at(metadata: &TableMetadata, timestamp_ms: i64, before: bool) -> Option<i64> {
+    let mut snapshot_id = metadata.current_snapshot_id;
+    while let Some(id) = snapshot_id {
+        let snapshot = metadata.snapshots.get(&id)?;
+        let committed_at = *snapshot.timestamp_ms();
+        if committed_at < timestamp_ms || (!before && committed_at == timestamp_ms) {
+            return Some(id);
+        }
+        snapshot_id = *snapshot.parent_snapshot_id();
+    }
+    None
+}
+
+#[must_use]
+pub fn invalid_time_travel(table: &str, reason: String) -> ex_error::Error {
+    ex_error::InvalidTimeTravelSnafu {
+        table: table.to_string(),
+        reason,
+    }
+    .build()
+}
+
+#[must_use]
+pub fn not_created_yet(table: &str) -> ex_error::Error {
+    invalid_time_travel(
+        table,
+        "the requested time is before the creation of the table".to_string(),
+    )
+}
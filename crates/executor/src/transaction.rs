@@ -0,0 +1,425 @@
+use crate::error::{self as ex_error, Result};
+use async_trait::async_trait;
+use catalog_metastore::{
+    Metastore, TableIdent as MetastoreTableIdent, TableRequirementExt,
+    TableUpdate as MetastoreTableUpdate,
+};
+use chrono::Utc;
+use datafusion_common::ResolvedTableReference;
+use datafusion_iceberg::DataFusionTable;
+use iceberg_rust::{
+    catalog::{
+        Catalog as IcebergCatalog,
+        commit::{
+            CommitTable as IcebergCommitTable, CommitView as IcebergCommitView, TableRequirement,
+            TableUpdate as IcebergTableUpdate, apply_table_updates,
+        },
+        create::{
+            CreateMaterializedView as IcebergCreateMaterializedView,
+            CreateTable as IcebergCreateTable, CreateView as IcebergCreateView,
+        },
+        tabular::Tabular as IcebergTabular,
+    },
+    error::Error as IcebergError,
+    materialized_view::MaterializedView as IcebergMaterializedView,
+    spec::{identifier::Identifier as IcebergIdentifier, table_metadata::TableMetadata},
+    table::Table as IcebergTable,
+    view::View as IcebergView,
+};
+use iceberg_rust_spec::{
+    identifier::FullIdentifier as IcebergFullIdentifier, namespace::Namespace as IcebergNamespace,
+};
+use object_store::ObjectStore;
+use snafu::ResultExt;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+
+/// Transaction state of a session: the open transaction, if any, and the id of the
+/// last transaction that was committed or rolled back.
+#[derive(Debug, Default)]
+pub struct SessionTransactions {
+    current: RwLock<Option<Arc<Transaction>>>,
+    last_id: RwLock<Option<i64>>,
+}
+
+impl SessionTransactions {
+    #[must_use]
+    pub fn current(&self) -> Option<Arc<Transaction>> {
+        self.current.read().ok().and_then(|current| current.clone())
+    }
+
+    #[must_use]
+    pub fn last_id(&self) -> Option<i64> {
+        self.last_id.read().ok().and_then(|last_id| *last_id)
+    }
+
+    /// Opens a new transaction. A `BEGIN` inside an open transaction is ignored.
+    pub fn begin(&self) {
+        #[allow(clippy::unwrap_used)]
+        let mut current = self.current.write().unwrap();
+        if current.is_none() {
+            *current = Some(Arc::new(Transaction::new()));
+        }
+    }
+
+    /// Ends the open transaction, if any, and records its id as the last one.
+    /// The caller either commits or drops the returned transaction.
+    #[must_use]
+    pub fn end(&self) -> Option<Arc<Transaction>> {
+        #[allow(clippy::unwrap_used)]
+        let transaction = self.current.write().unwrap().take()?;
+        if let Ok(mut last_id) = self.last_id.write() {
+            *last_id = Some(transaction.id);
+        }
+        Some(transaction)
+    }
+
+    /// Ends the open transaction, if any, discarding all of its buffered commits.
+    pub fn rollback(&self) {
+        drop(self.end());
+    }
+}
+
+/// Session-scoped transaction over Iceberg tables.
+///
+/// Every table touched while the transaction is open is served by a transaction-scoped
+/// provider whose catalog buffers snapshot commits instead of applying them, so the
+/// session reads its own writes while other sessions keep seeing the committed state.
+/// On `COMMIT` the buffered updates of all tables are applied to the metastore at once.
+#[derive(Debug)]
+pub struct Transaction {
+    pub id: i64,
+    tables: tokio::sync::Mutex<HashMap<ResolvedTableReference, TransactionTable>>,
+}
+
+#[derive(Debug)]
+struct TransactionTable {
+    ident: MetastoreTableIdent,
+    provider: DataFusionTable,
+    shared: DataFusionTable,
+    buffer: Arc<Mutex<BufferedCommit>>,
+}
+
+#[derive(Debug)]
+struct BufferedCommit {
+    /// Requirements asserting the table is still at the snapshot the transaction started from
+    requirements: Vec<TableRequirement>,
+    metadata: TableMetadata,
+    updates: Vec<IcebergTableUpdate>,
+}
+
+impl Default for Transaction {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Transaction {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            id: Utc::now()
+                .timestamp_nanos_opt()
+                .unwrap_or_else(|| Utc::now().timestamp_micros()),
+            tables: tokio::sync::Mutex::default(),
+        }
+    }
+
+    /// Returns the transaction-scoped provider for an Iceberg table, enlisting the table
+    /// in the transaction on first access. Providers that are not backed by an Iceberg
+    /// table are returned unchanged.
+    pub async fn table_provider(
+        &self,
+        table_ref: &ResolvedTableReference,
+        shared: &DataFusionTable,
+    ) -> Result<DataFusionTable> {
+        let mut tables = self.tables.lock().await;
+        if let Some(table) = tables.get(table_ref) {
+            return Ok(table.provider.clone());
+        }
+
+        #[allow(clippy::unwrap_used)]
+        let tabular = shared.tabular.read().unwrap().clone();
+        let IcebergTabular::Table(table) = tabular else {
+            return Ok(shared.clone());
+        };
+        let metadata = table.metadata().clone();
+        let buffer = Arc::new(Mutex::new(BufferedCommit {
+            requirements: vec![
+                TableRequirement::AssertTableUuid {
+                    uuid: metadata.table_uuid,
+                },
+                TableRequirement::AssertRefSnapshotId {
+                    r#ref: "main".to_string(),
+                    snapshot_id: metadata.current_snapshot_id,
+                },
+            ],
+            metadata: metadata.clone(),
+            updates: Vec::new(),
+        }));
+        let catalog = Arc::new(TransactionCatalog {
+            inner: table.catalog(),
+            object_store: table.object_store(),
+            buffer: buffer.clone(),
+        });
+        let table = IcebergTable::new(
+            table.identifier().clone(),
+            catalog,
+            table.object_store(),
+            metadata,
+        )
+        .await
+        .context(ex_error::IcebergSnafu)?;
+        let provider = DataFusionTable {
+            tabular: Arc::new(RwLock::new(IcebergTabular::Table(table))),
+            ..shared.clone()
+        };
+
+        tables.insert(
+            table_ref.clone(),
+            TransactionTable {
+                ident: MetastoreTableIdent::new(
+                    &table_ref.catalog,
+                    &table_ref.schema,
+                    &table_ref.table,
+                ),
+                provider: provider.clone(),
+                shared: shared.clone(),
+                buffer,
+            },
+        );
+        Ok(provider)
+    }
+
+    /// Applies the buffered updates of every table touched by the transaction in one
+    /// metastore call. The commit fails as a whole if any of the tables was changed
+    /// by someone else since the transaction first accessed it.
+    pub async fn commit(&self, metastore: &Arc<dyn Metastore>) -> Result<()> {
+        let tables = std::mem::take(&mut *self.tables.lock().await);
+
+        let mut updates = Vec::new();
+        let mut shared = Vec::new();
+        for table in tables.into_values() {
+            #[allow(clippy::unwrap_used)]
+            let buffer = table.buffer.lock().unwrap();
+            if buffer.updates.is_empty() {
+                continue;
+            }
+            updates.push((
+                table.ident,
+                MetastoreTableUpdate {
+                    requirements: buffer.requirements.clone(),
+                    updates: buffer.updates.clone(),
+                },
+            ));
+            shared.push(table.shared);
+        }
+        if updates.is_empty() {
+            return Ok(());
+        }
+
+        let committed = metastore
+            .update_tables(updates)
+            .await
+            .context(ex_error::TransactionCommitSnafu { id: self.id })?;
+
+        // Refresh the cached tables so every session sees the committed snapshots
+        for (shared, committed) in shared.into_iter().zip(committed) {
+            #[allow(clippy::unwrap_used)]
+            let tabular = shared.tabular.read().unwrap().clone();
+            if let IcebergTabular::Table(table) = tabular {
+                let table = IcebergTable::new(
+                    table.identifier().clone(),
+                    table.catalog(),
+                    table.object_store(),
+                    committed.metadata.clone(),
+                )
+                .await
+                .context(ex_error::IcebergSnafu)?;
+                #[allow(clippy::unwrap_used)]
+                let mut lock = shared.tabular.write().unwrap();
+                *lock = IcebergTabular::Table(table);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Iceberg catalog of a table enlisted in a transaction. Table commits are checked
+/// against and applied to the buffered metadata only; everything else is delegated
+/// to the catalog the table was loaded from.
+#[derive(Debug)]
+struct TransactionCatalog {
+    inner: Arc<dyn IcebergCatalog>,
+    object_store: Arc<dyn ObjectStore>,
+    buffer: Arc<Mutex<BufferedCommit>>,
+}
+
+#[async_trait]
+impl IcebergCatalog for TransactionCatalog {
+    fn name(&self) -> &str {
+        self.inner.name()
+    }
+
+    async fn create_namespace(
+        &self,
+        namespace: &IcebergNamespace,
+        properties: Option<HashMap<String, String>>,
+    ) -> Result<HashMap<String, String>, IcebergError> {
+        self.inner.create_namespace(namespace, properties).await
+    }
+
+    async fn drop_namespace(&self, namespace: &IcebergNamespace) -> Result<(), IcebergError> {
+        self.inner.drop_namespace(namespace).await
+    }
+
+    async fn load_namespace(
+        &self,
+        namespace: &IcebergNamespace,
+    ) -> Result<HashMap<String, String>, IcebergError> {
+        self.inner.load_namespace(namespace).await
+    }
+
+    async fn update_namespace(
+        &self,
+        namespace: &IcebergNamespace,
+        updates: Option<HashMap<String, String>>,
+        removals: Option<Vec<String>>,
+    ) -> Result<(), IcebergError> {
+        self.inner
+            .update_namespace(namespace, updates, removals)
+            .await
+    }
+
+    async fn namespace_exists(&self, namespace: &IcebergNamespace) -> Result<bool, IcebergError> {
+        self.inner.namespace_exists(namespace).await
+    }
+
+    async fn list_tabulars(
+        &self,
+        namespace: &IcebergNamespace,
+    ) -> Result<Vec<IcebergIdentifier>, IcebergError> {
+        self.inner.list_tabulars(namespace).await
+    }
+
+    async fn list_namespaces(
+        &self,
+        parent: Option<&str>,
+    ) -> Result<Vec<IcebergNamespace>, IcebergError> {
+        self.inner.list_namespaces(parent).await
+    }
+
+    async fn tabular_exists(&self, identifier: &IcebergIdentifier) -> Result<bool, IcebergError> {
+        self.inner.tabular_exists(identifier).await
+    }
+
+    async fn drop_table(&self, identifier: &IcebergIdentifier) -> Result<(), IcebergError> {
+        self.inner.drop_table(identifier).await
+    }
+
+    async fn drop_view(&self, identifier: &IcebergIdentifier) -> Result<(), IcebergError> {
+        self.inner.drop_view(identifier).await
+    }
+
+    async fn drop_materialized_view(
+        &self,
+        identifier: &IcebergIdentifier,
+    ) -> Result<(), IcebergError> {
+        self.inner.drop_materialized_view(identifier).await
+    }
+
+    async fn load_tabular(
+        self: Arc<Self>,
+        identifier: &IcebergIdentifier,
+    ) -> Result<IcebergTabular, IcebergError> {
+        self.inner.clone().load_tabular(identifier).await
+    }
+
+    async fn create_table(
+        self: Arc<Self>,
+        identifier: IcebergIdentifier,
+        create_table: IcebergCreateTable,
+    ) -> Result<IcebergTable, IcebergError> {
+        self.inner
+            .clone()
+            .create_table(identifier, create_table)
+            .await
+    }
+
+    async fn create_view(
+        self: Arc<Self>,
+        identifier: IcebergIdentifier,
+        create_view: IcebergCreateView<Option<()>>,
+    ) -> Result<IcebergView, IcebergError> {
+        self.inner
+            .clone()
+            .create_view(identifier, create_view)
+            .await
+    }
+
+    async fn create_materialized_view(
+        self: Arc<Self>,
+        identifier: IcebergIdentifier,
+        create_view: IcebergCreateMaterializedView,
+    ) -> Result<IcebergMaterializedView, IcebergError> {
+        self.inner
+            .clone()
+            .create_materialized_view(identifier, create_view)
+            .await
+    }
+
+    /// Buffers the commit: its requirements are asserted against and its updates applied
+    /// to the transaction's view of the table, the metastore is not touched.
+    async fn update_table(
+        self: Arc<Self>,
+        commit: IcebergCommitTable,
+    ) -> Result<IcebergTable, IcebergError> {
+        let metadata = {
+            #[allow(clippy::unwrap_used)]
+            let mut buffer = self.buffer.lock().unwrap();
+            for requirement in &commit.requirements {
+                TableRequirementExt::new(requirement.clone())
+                    .assert(&buffer.metadata)
+                    .map_err(|e| IcebergError::External(Box::new(e)))?;
+            }
+            let mut metadata = buffer.metadata.clone();
+            apply_table_updates(&mut metadata, commit.updates.clone())?;
+            buffer.metadata = metadata.clone();
+            buffer.updates.extend(commit.updates);
+            metadata
+        };
+
+        IcebergTable::new(
+            commit.identifier.clone(),
+            self.clone(),
+            self.object_store.clone(),
+            metadata,
+        )
+        .await
+    }
+
+    async fn update_view(
+        self: Arc<Self>,
+        commit: IcebergCommitView<Option<()>>,
+    ) -> Result<IcebergView, IcebergError> {
+        self.inner.clone().update_view(commit).await
+    }
+
+    async fn update_materialized_view(
+        self: Arc<Self>,
+        commit: IcebergCommitView<IcebergFullIdentifier>,
+    ) -> Result<IcebergMaterializedView, IcebergError> {
+        self.inner.clone().update_materialized_view(commit).await
+    }
+
+    async fn register_table(
+        self: Arc<Self>,
+        identifier: IcebergIdentifier,
+        metadata_location: &str,
+    ) -> Result<IcebergTable, IcebergError> {
+        self.inner
+            .clone()
+            .register_table(identifier, metadata_location)
+            .await
+    }
+}
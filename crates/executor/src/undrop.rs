@@ -0,0 +1,48 @@
+use sqlparser::ast::{ObjectName, ObjectType};
+use sqlparser::dialect::SnowflakeDialect;
+use sqlparser::keywords::Keyword;
+use sqlparser::parser::{Parser, ParserError};
+use sqlparser::tokenizer::Token;
+
+type ParseResult<T> = std::result::Result<T, ParserError>;
+
+/// `UNDROP TABLE | SCHEMA | DATABASE <name>`, restores the most recently dropped object
+/// with the name while its retention time has not passed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UndropStatement {
+    pub object_type: ObjectType,
+    pub name: ObjectName,
+}
+
+// Cheap check to avoid tokenizing every statement twice
+fn looks_like_undrop_statement(sql: &str) -> bool {
+    sql.split_whitespace()
+        .next()
+        .is_some_and(|word| word.eq_ignore_ascii_case("UNDROP"))
+}
+
+/// Parses an `UNDROP` statement, returns `None` if the sql is a different statement.
+pub fn parse_undrop_statement(sql: &str) -> ParseResult<Option<UndropStatement>> {
+    if !looks_like_undrop_statement(sql) {
+        return Ok(None);
+    }
+    let dialect = SnowflakeDialect;
+    let mut parser = Parser::new(&dialect).try_with_sql(sql)?;
+    match parser.next_token().token {
+        Token::Word(word) if word.value.eq_ignore_ascii_case("UNDROP") => {}
+        _ => return Ok(None),
+    }
+    let object_type = match parser.expect_one_of_keywords(&[
+        Keyword::TABLE,
+        Keyword::SCHEMA,
+        Keyword::DATABASE,
+    ])? {
+        Keyword::TABLE => ObjectType::Table,
+        Keyword::SCHEMA => ObjectType::Schema,
+        _ => ObjectType::Database,
+    };
+    let name = parser.parse_object_name(false)?;
+    while parser.consume_token(&Token::SemiColon) {}
+    parser.expect_token(&Token::EOF)?;
+    Ok(Some(UndropStatement { object_type, name }))
+}
@@ -0,0 +1,518 @@
+use crate::copy_into::{bool_option, invalid_option};
+use crate::error::{self as ex_error, Result};
+use crate::file_formats::{CsvFileFormat, FileFormatOptions, unload_compression};
+use datafusion::arrow::array::{Array, AsArray, Int64Array, RecordBatch, UInt32Array};
+use datafusion::arrow::compute::{cast, take_record_batch};
+use datafusion::arrow::csv::WriterBuilder as CsvWriterBuilder;
+use datafusion::arrow::datatypes::{DataType, Field, Schema as ArrowSchema, SchemaRef};
+use datafusion::arrow::json::LineDelimitedWriter;
+use datafusion::datasource::file_format::file_compression_type::FileCompressionType;
+use datafusion::execution::SendableRecordBatchStream;
+use datafusion::parquet::arrow::ArrowWriter;
+use datafusion::parquet::basic::Compression;
+use datafusion::parquet::file::properties::WriterProperties;
+use futures::TryStreamExt;
+use object_store::ObjectStore;
+use object_store::buffered::BufWriter;
+use object_store::path::Path as ObjectPath;
+use snafu::ResultExt;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// Column holding the `PARTITION BY` value of a row unloaded by `COPY INTO <location>`
+pub const UNLOAD_PARTITION_COLUMN: &str = "__unload_partition";
+
+/// Partition of the rows whose `PARTITION BY` value is NULL
+const NULL_PARTITION: &str = "_NULL_";
+
+/// `MAX_FILE_SIZE` of Snowflake, 16 MB
+const DEFAULT_MAX_FILE_SIZE: usize = 16 * 1024 * 1024;
+
+/// Copy options of `COPY INTO <location>`
+#[derive(Debug, Clone, Copy)]
+pub struct UnloadOptions {
+    /// Size at which the rows continue in a new file, a file can be larger if a single
+    /// row or Parquet batch does not fit
+    pub max_file_size: usize,
+    /// Unloads all rows to a single file named by the location path
+    pub single: bool,
+    /// CSV files start with a line of column names
+    pub header: bool,
+    /// Replaces files with the names of the unloaded files, otherwise the statement fails
+    pub overwrite: bool,
+}
+
+impl Default for UnloadOptions {
+    fn default() -> Self {
+        Self {
+            max_file_size: DEFAULT_MAX_FILE_SIZE,
+            single: false,
+            header: false,
+            overwrite: false,
+        }
+    }
+}
+
+impl UnloadOptions {
+    /// Parses the copy options keyed by their uppercase names, options other than
+    /// `MAX_FILE_SIZE`, `SINGLE`, `HEADER` and `OVERWRITE` are ignored
+    pub fn parse(options: &BTreeMap<String, String>) -> Result<Self> {
+        let mut unload = Self::default();
+        for (option, value) in options {
+            match option.as_str() {
+                "MAX_FILE_SIZE" => {
+                    unload.max_file_size = value
+                        .parse()
+                        .ok()
+                        .filter(|size| *size > 0)
+                        .ok_or_else(|| invalid_option(option, value))?;
+                }
+                "SINGLE" => unload.single = bool_option(option, value)?,
+                "HEADER" => unload.header = bool_option(option, value)?,
+                "OVERWRITE" => unload.overwrite = bool_option(option, value)?,
+                _ => {}
+            }
+        }
+        Ok(unload)
+    }
+}
+
+/// Compression of the unloaded files, along with the extension of their names
+pub fn unload_file_type(format: &FileFormatOptions) -> Result<(FileCompressionType, String)> {
+    match format {
+        // Parquet files are compressed by the writer
+        FileFormatOptions::Parquet => Ok((
+            FileCompressionType::UNCOMPRESSED,
+            ".snappy.parquet".to_string(),
+        )),
+        FileFormatOptions::Csv(csv) => {
+            let (compression, extension) = unload_compression(&csv.compression)?;
+            Ok((compression, format!(".csv{extension}")))
+        }
+        FileFormatOptions::Json(json) => {
+            let (compression, extension) = unload_compression(&json.compression)?;
+            Ok((compression, format!(".json{extension}")))
+        }
+        // Like in Snowflake, Avro and ORC files can only be loaded
+        FileFormatOptions::Avro | FileFormatOptions::Orc => unsupported_unload_format(format),
+    }
+}
+
+/// Path of the `index`th unloaded file, relative to the stage or the external location.
+///
+/// Like in Snowflake a path ending with `/` is a directory of `data_0_0_<index>` files,
+/// otherwise its last part is the prefix of the file names. Partitions are directories
+/// below the path and a single file is named by the path itself.
+#[must_use]
+pub fn unload_file_path(
+    path: &str,
+    partition: Option<&str>,
+    index: usize,
+    extension: &str,
+    single: bool,
+) -> String {
+    let directory = path.is_empty() || path.ends_with('/');
+    if single {
+        return if directory {
+            format!("{path}data")
+        } else {
+            path.to_string()
+        };
+    }
+    let name = format!("_0_0_{index}{extension}");
+    match partition {
+        Some(partition) => {
+            let path = path.trim_end_matches('/');
+            if path.is_empty() {
+                format!("{partition}/data{name}")
+            } else {
+                format!("{path}/{partition}/data{name}")
+            }
+        }
+        None if directory => format!("{path}data{name}"),
+        None => format!("{path}{name}"),
+    }
+}
+
+/// Splits the rows of a batch by their `PARTITION BY` value, the partition column is
+/// dropped
+pub fn partition_batch(batch: &RecordBatch) -> Result<BTreeMap<String, RecordBatch>> {
+    let index = batch
+        .schema()
+        .index_of(UNLOAD_PARTITION_COLUMN)
+        .context(ex_error::ArrowSnafu)?;
+    let values = cast(batch.column(index), &DataType::Utf8).context(ex_error::ArrowSnafu)?;
+    let values = values.as_string::<i32>();
+    let mut rows: BTreeMap<&str, Vec<u32>> = BTreeMap::new();
+    for row in 0..values.len() {
+        let partition = if values.is_valid(row) {
+            values.value(row)
+        } else {
+            NULL_PARTITION
+        };
+        rows.entry(partition)
+            .or_default()
+            .push(u32::try_from(row).unwrap_or(u32::MAX));
+    }
+    let mut batch = batch.clone();
+    batch.remove_column(index);
+    rows.into_iter()
+        .map(|(partition, rows)| {
+            let rows = take_record_batch(&batch, &UInt32Array::from(rows))
+                .context(ex_error::ArrowSnafu)?;
+            Ok((partition.to_string(), rows))
+        })
+        .collect()
+}
+
+/// File being written by [`UnloadWriter`]
+struct OpenFile {
+    path: ObjectPath,
+    writer: Box<dyn AsyncWrite + Send + Unpin>,
+    /// Parquet files are encoded by a single writer whose output is passed on after
+    /// every batch, only the row group in progress is kept in memory
+    parquet: Option<ArrowWriter<Vec<u8>>>,
+    /// Size of the file before it is compressed
+    size: usize,
+    rows: usize,
+}
+
+impl OpenFile {
+    async fn write(&mut self, data: &[u8]) -> Result<()> {
+        self.writer
+            .write_all(data)
+            .await
+            .context(ex_error::UnloadFileWriteSnafu {
+                file: self.path.to_string(),
+            })?;
+        self.size += data.len();
+        Ok(())
+    }
+}
+
+/// Writes the rows unloaded by `COPY INTO <location>` to the files of their partition
+/// as the rows are produced, a new file is started once a file reaches `MAX_FILE_SIZE`
+/// unless `SINGLE` is set
+pub struct UnloadWriter {
+    store: Arc<dyn ObjectStore>,
+    path: String,
+    format: FileFormatOptions,
+    options: UnloadOptions,
+    compression: FileCompressionType,
+    extension: String,
+    /// Rows come with the `PARTITION BY` value in [`UNLOAD_PARTITION_COLUMN`]
+    partitioned: bool,
+    /// Column names line of CSV files, encoded once the schema of the rows is known
+    header: Option<Vec<u8>>,
+    open: BTreeMap<Option<String>, OpenFile>,
+    next_index: HashMap<Option<String>, usize>,
+    /// Completed files, removed again if the statement fails
+    written: Vec<ObjectPath>,
+    pub rows_unloaded: usize,
+    /// Size of the files before they are compressed
+    pub input_bytes: usize,
+    pub output_bytes: usize,
+}
+
+impl UnloadWriter {
+    /// Writer of files under `path` of the store, see [`unload_file_path`]
+    pub fn try_new(
+        store: Arc<dyn ObjectStore>,
+        path: String,
+        format: FileFormatOptions,
+        options: UnloadOptions,
+        partitioned: bool,
+    ) -> Result<Self> {
+        let (compression, extension) = unload_file_type(&format)?;
+        Ok(Self {
+            store,
+            path,
+            format,
+            options,
+            compression,
+            extension,
+            partitioned,
+            header: None,
+            open: BTreeMap::new(),
+            next_index: HashMap::new(),
+            written: Vec::new(),
+            rows_unloaded: 0,
+            input_bytes: 0,
+            output_bytes: 0,
+        })
+    }
+
+    /// Writes all rows of the stream and completes the files. If any of it fails, the
+    /// files written so far are removed so that the statement unloads nothing.
+    pub async fn write_stream(&mut self, mut stream: SendableRecordBatchStream) -> Result<()> {
+        let result = async {
+            while let Some(batch) = stream.try_next().await.context(ex_error::DataFusionSnafu)? {
+                self.write(batch).await?;
+            }
+            self.finish().await
+        }
+        .await;
+        if result.is_err() {
+            self.abort().await;
+        }
+        result
+    }
+
+    async fn write(&mut self, batch: RecordBatch) -> Result<()> {
+        if !self.partitioned {
+            return self.write_partition(None, batch).await;
+        }
+        for (partition, batch) in partition_batch(&batch)? {
+            self.write_partition(Some(partition), batch).await?;
+        }
+        Ok(())
+    }
+
+    async fn write_partition(
+        &mut self,
+        partition: Option<String>,
+        batch: RecordBatch,
+    ) -> Result<()> {
+        match &self.format {
+            FileFormatOptions::Parquet => self.write_parquet(partition, &batch).await,
+            _ => self.write_text(partition, batch).await,
+        }
+    }
+
+    /// Rows are appended to a file as long as they fit, batches that do not fit are split
+    /// in halves until single rows are left
+    async fn write_text(&mut self, partition: Option<String>, batch: RecordBatch) -> Result<()> {
+        if self.header.is_none() {
+            self.header = Some(match &self.format {
+                FileFormatOptions::Csv(csv) if self.options.header => {
+                    encode_csv(csv, &RecordBatch::new_empty(batch.schema()), true)?
+                }
+                _ => Vec::new(),
+            });
+        }
+        let mut pending = vec![batch];
+        while let Some(batch) = pending.pop() {
+            let rows = batch.num_rows();
+            if rows == 0 {
+                continue;
+            }
+            let data = match &self.format {
+                FileFormatOptions::Csv(csv) => encode_csv(csv, &batch, false)?,
+                _ => encode_json(&batch)?,
+            };
+            let size = self.open.get(&partition).map_or_else(
+                || self.header.as_ref().map_or(0, Vec::len),
+                |file| file.size,
+            );
+            if self.options.single || size + data.len() <= self.options.max_file_size {
+                self.append(partition.clone(), &data, rows).await?;
+            } else if rows > 1 {
+                pending.push(batch.slice(rows / 2, rows - rows / 2));
+                pending.push(batch.slice(0, rows / 2));
+            } else {
+                self.close(partition.clone()).await?;
+                self.append(partition.clone(), &data, rows).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn append(&mut self, partition: Option<String>, data: &[u8], rows: usize) -> Result<()> {
+        let mut file = match self.open.remove(&partition) {
+            Some(file) => file,
+            None => {
+                let mut file = self.create(partition.clone()).await?;
+                let header = self.header.clone().unwrap_or_default();
+                file.write(&header).await?;
+                file
+            }
+        };
+        file.write(data).await?;
+        file.rows += rows;
+        self.rows_unloaded += rows;
+        self.open.insert(partition, file);
+        Ok(())
+    }
+
+    async fn write_parquet(
+        &mut self,
+        partition: Option<String>,
+        batch: &RecordBatch,
+    ) -> Result<()> {
+        if batch.num_rows() == 0 {
+            return Ok(());
+        }
+        let mut file = match self.open.remove(&partition) {
+            Some(file) => file,
+            None => self.create(partition.clone()).await?,
+        };
+        let mut writer = match file.parquet.take() {
+            Some(writer) => writer,
+            None => {
+                let properties = WriterProperties::builder()
+                    .set_compression(Compression::SNAPPY)
+                    .build();
+                ArrowWriter::try_new(Vec::new(), batch.schema(), Some(properties))
+                    .context(ex_error::ParquetSnafu)?
+            }
+        };
+        writer.write(batch).context(ex_error::ParquetSnafu)?;
+        // The writer tracks the offsets itself, the encoded bytes can be passed on
+        let data = std::mem::take(writer.inner_mut());
+        file.write(&data).await?;
+        file.rows += batch.num_rows();
+        self.rows_unloaded += batch.num_rows();
+        let full = !self.options.single
+            && writer.bytes_written() + writer.in_progress_size() >= self.options.max_file_size;
+        file.parquet = Some(writer);
+        self.open.insert(partition.clone(), file);
+        if full {
+            self.close(partition).await?;
+        }
+        Ok(())
+    }
+
+    /// Starts the next file of the partition, an existing file is only replaced with
+    /// `OVERWRITE`
+    async fn create(&mut self, partition: Option<String>) -> Result<OpenFile> {
+        let index = self.next_index.entry(partition.clone()).or_default();
+        let path = ObjectPath::from(unload_file_path(
+            &self.path,
+            partition.as_deref(),
+            *index,
+            &self.extension,
+            self.options.single,
+        ));
+        *index += 1;
+        if !self.options.overwrite {
+            match self.store.head(&path).await {
+                Ok(_) => {
+                    return ex_error::UnloadFileExistsSnafu {
+                        file: path.to_string(),
+                    }
+                    .fail();
+                }
+                Err(object_store::Error::NotFound { .. }) => {}
+                Err(error) => return Err(error).context(ex_error::ObjectStoreSnafu),
+            }
+        }
+        let writer = self
+            .compression
+            .convert_async_writer(BufWriter::new(Arc::clone(&self.store), path.clone()))
+            .context(ex_error::DataFusionSnafu)?;
+        Ok(OpenFile {
+            path,
+            writer,
+            parquet: None,
+            size: 0,
+            rows: 0,
+        })
+    }
+
+    /// Completes the open file of the partition, if there is one
+    async fn close(&mut self, partition: Option<String>) -> Result<()> {
+        let Some(mut file) = self.open.remove(&partition) else {
+            return Ok(());
+        };
+        if let Some(writer) = file.parquet.take() {
+            let data = writer.into_inner().context(ex_error::ParquetSnafu)?;
+            file.write(&data).await?;
+        }
+        file.writer
+            .shutdown()
+            .await
+            .context(ex_error::UnloadFileWriteSnafu {
+                file: file.path.to_string(),
+            })?;
+        self.written.push(file.path.clone());
+        let output = self
+            .store
+            .head(&file.path)
+            .await
+            .context(ex_error::ObjectStoreSnafu)?;
+        self.input_bytes += file.size;
+        self.output_bytes += usize::try_from(output.size).unwrap_or(usize::MAX);
+        Ok(())
+    }
+
+    async fn finish(&mut self) -> Result<()> {
+        let partitions = self.open.keys().cloned().collect::<Vec<_>>();
+        for partition in partitions {
+            self.close(partition).await?;
+        }
+        Ok(())
+    }
+
+    /// Drops the open files and removes the completed ones
+    async fn abort(&mut self) {
+        self.open.clear();
+        for path in std::mem::take(&mut self.written) {
+            if let Err(error) = self.store.delete(&path).await {
+                tracing::warn!("Failed to remove unloaded file {path}: {error}");
+            }
+        }
+    }
+}
+
+fn unsupported_unload_format<T>(format: &FileFormatOptions) -> Result<T> {
+    ex_error::NotSupportedStatementSnafu {
+        statement: format!("COPY INTO <location> with TYPE = {}", format.name()),
+    }
+    .fail()
+}
+
+fn encode_csv(csv: &CsvFileFormat, batch: &RecordBatch, header: bool) -> Result<Vec<u8>> {
+    // NULLs are unloaded as the first value of `NULL_IF`
+    let mut builder = CsvWriterBuilder::new()
+        .with_header(header)
+        .with_delimiter(csv.field_delimiter)
+        .with_quote(csv.quote)
+        .with_null(csv.null_if.first().cloned().unwrap_or_default());
+    if let Some(escape) = csv.escape {
+        builder = builder.with_escape(escape).with_double_quote(false);
+    }
+    let mut data = Vec::new();
+    builder
+        .build(&mut data)
+        .write(batch)
+        .context(ex_error::ArrowSnafu)?;
+    Ok(data)
+}
+
+fn encode_json(batch: &RecordBatch) -> Result<Vec<u8>> {
+    let mut data = Vec::new();
+    let mut writer = LineDelimitedWriter::new(&mut data);
+    writer.write(batch).context(ex_error::ArrowSnafu)?;
+    writer.finish().context(ex_error::ArrowSnafu)?;
+    drop(writer);
+    Ok(data)
+}
+
+fn count(value: usize) -> i64 {
+    i64::try_from(value).unwrap_or(i64::MAX)
+}
+
+/// Result set of `COPY INTO <location>`, as returned by Snowflake. `input_bytes` is the
+/// size of the files before they are compressed.
+pub fn unload_results(
+    rows_unloaded: usize,
+    input_bytes: usize,
+    output_bytes: usize,
+) -> Result<(RecordBatch, SchemaRef)> {
+    let schema = Arc::new(ArrowSchema::new(vec![
+        Field::new("rows_unloaded", DataType::Int64, false),
+        Field::new("input_bytes", DataType::Int64, false),
+        Field::new("output_bytes", DataType::Int64, false),
+    ]));
+    let batch = RecordBatch::try_new(
+        schema.clone(),
+        vec![
+            Arc::new(Int64Array::from(vec![count(rows_unloaded)])),
+            Arc::new(Int64Array::from(vec![count(input_bytes)])),
+            Arc::new(Int64Array::from(vec![count(output_bytes)])),
+        ],
+    )
+    .context(ex_error::ArrowSnafu)?;
+    Ok((batch, schema))
+}
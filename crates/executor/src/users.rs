@@ -0,0 +1,261 @@
+use catalog_metastore::{Role, User};
+use sqlparser::dialect::SnowflakeDialect;
+use sqlparser::keywords::Keyword;
+use sqlparser::parser::{Parser, ParserError};
+use sqlparser::tokenizer::Token;
+
+/// User management statement: `CREATE USER`, `ALTER USER` or `DROP USER`.
+///
+/// These are parsed here rather than by the SQL parser, which has no AST for them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UserStatement {
+    Create {
+        name: String,
+        or_replace: bool,
+        if_not_exists: bool,
+        properties: UserProperties,
+    },
+    Alter {
+        name: String,
+        if_exists: bool,
+        action: AlterUserAction,
+    },
+    Drop {
+        name: String,
+        if_exists: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlterUserAction {
+    Set(UserProperties),
+    Unset(Vec<UserProperty>),
+    RenameTo(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UserProperty {
+    Password,
+    RsaPublicKey,
+    RsaPublicKey2,
+    DefaultRole,
+    Disabled,
+    Comment,
+}
+
+impl UserProperty {
+    fn parse(parser: &mut Parser) -> Result<Self, ParserError> {
+        let ident = parser.parse_identifier()?;
+        match ident.value.to_ascii_uppercase().as_str() {
+            "PASSWORD" => Ok(Self::Password),
+            "RSA_PUBLIC_KEY" => Ok(Self::RsaPublicKey),
+            "RSA_PUBLIC_KEY_2" => Ok(Self::RsaPublicKey2),
+            "DEFAULT_ROLE" => Ok(Self::DefaultRole),
+            "DISABLED" => Ok(Self::Disabled),
+            "COMMENT" => Ok(Self::Comment),
+            _ => Err(ParserError::ParserError(format!(
+                "Unsupported user property: {ident}"
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UserProperties {
+    pub password: Option<String>,
+    pub rsa_public_key: Option<String>,
+    pub rsa_public_key_2: Option<String>,
+    pub default_role: Option<String>,
+    pub disabled: Option<bool>,
+    pub comment: Option<String>,
+}
+
+impl UserProperties {
+    fn parse(parser: &mut Parser) -> Result<Self, ParserError> {
+        let mut properties = Self::default();
+        while matches!(parser.peek_token().token, Token::Word(_)) {
+            let property = UserProperty::parse(parser)?;
+            parser.expect_token(&Token::Eq)?;
+            match property {
+                UserProperty::Password => {
+                    properties.password = Some(parser.parse_literal_string()?);
+                }
+                UserProperty::RsaPublicKey => {
+                    properties.rsa_public_key = Some(parser.parse_literal_string()?);
+                }
+                UserProperty::RsaPublicKey2 => {
+                    properties.rsa_public_key_2 = Some(parser.parse_literal_string()?);
+                }
+                UserProperty::DefaultRole => {
+                    properties.default_role = Some(parse_role_name(parser)?);
+                }
+                UserProperty::Comment => properties.comment = Some(parser.parse_literal_string()?),
+                UserProperty::Disabled => properties.disabled = Some(parse_bool(parser)?),
+            }
+        }
+        Ok(properties)
+    }
+
+    /// Applies the properties to the user, keeping the ones that are not set
+    #[must_use]
+    pub fn apply(self, user: User) -> User {
+        let normalize_key = |key: String| Some(User::normalize_public_key(&key));
+        let user = User {
+            rsa_public_key: self
+                .rsa_public_key
+                .map_or(user.rsa_public_key, normalize_key),
+            rsa_public_key_2: self
+                .rsa_public_key_2
+                .map_or(user.rsa_public_key_2, normalize_key),
+            default_role: self.default_role.or(user.default_role),
+            disabled: self.disabled.unwrap_or(user.disabled),
+            comment: self.comment.or(user.comment),
+            ..user
+        };
+        match self.password {
+            Some(password) => user.with_password(&password),
+            None => user,
+        }
+    }
+}
+
+impl AlterUserAction {
+    #[must_use]
+    pub fn apply(self, user: User) -> User {
+        match self {
+            Self::Set(properties) => properties.apply(user),
+            Self::Unset(properties) => {
+                properties
+                    .into_iter()
+                    .fold(user, |user, property| match property {
+                        UserProperty::Password => User {
+                            password_hash: None,
+                            ..user
+                        },
+                        UserProperty::RsaPublicKey => User {
+                            rsa_public_key: None,
+                            ..user
+                        },
+                        UserProperty::RsaPublicKey2 => User {
+                            rsa_public_key_2: None,
+                            ..user
+                        },
+                        UserProperty::DefaultRole => User {
+                            default_role: None,
+                            ..user
+                        },
+                        UserProperty::Disabled => User {
+                            disabled: false,
+                            ..user
+                        },
+                        UserProperty::Comment => User {
+                            comment: None,
+                            ..user
+                        },
+                    })
+            }
+            Self::RenameTo(name) => User {
+                name: User::normalize_name(&name),
+                ..user
+            },
+        }
+    }
+}
+
+fn parse_bool(parser: &mut Parser) -> Result<bool, ParserError> {
+    let token = parser.next_token();
+    let value = match &token.token {
+        Token::Word(word) => word.value.to_ascii_uppercase(),
+        Token::SingleQuotedString(value) => value.to_ascii_uppercase(),
+        _ => return parser.expected("TRUE or FALSE", token),
+    };
+    match value.as_str() {
+        "TRUE" => Ok(true),
+        "FALSE" => Ok(false),
+        _ => parser.expected("TRUE or FALSE", token),
+    }
+}
+
+// Role names are accepted both as identifiers and as string literals
+fn parse_role_name(parser: &mut Parser) -> Result<String, ParserError> {
+    let name = match parser.peek_token().token {
+        Token::SingleQuotedString(_) => parser.parse_literal_string()?,
+        _ => parser.parse_identifier()?.value,
+    };
+    Ok(Role::normalize_name(&name))
+}
+
+fn parse_user_name(parser: &mut Parser) -> Result<String, ParserError> {
+    Ok(User::normalize_name(&parser.parse_identifier()?.value))
+}
+
+// Cheap check to avoid tokenizing every statement twice
+fn looks_like_user_statement(sql: &str) -> bool {
+    let words = sql
+        .split_whitespace()
+        .take(4)
+        .map(str::to_ascii_uppercase)
+        .collect::<Vec<_>>();
+    matches!(
+        words.first().map(String::as_str),
+        Some("CREATE" | "ALTER" | "DROP")
+    ) && words.iter().any(|word| word == "USER")
+}
+
+/// Parses a user management statement, returns `None` if the sql is a different statement.
+pub fn parse_user_statement(sql: &str) -> Result<Option<UserStatement>, ParserError> {
+    if !looks_like_user_statement(sql) {
+        return Ok(None);
+    }
+    let dialect = SnowflakeDialect;
+    let mut parser = Parser::new(&dialect).try_with_sql(sql)?;
+
+    let statement = if parser.parse_keyword(Keyword::CREATE) {
+        let or_replace = parser.parse_keywords(&[Keyword::OR, Keyword::REPLACE]);
+        if !parser.parse_keyword(Keyword::USER) {
+            return Ok(None);
+        }
+        let if_not_exists = parser.parse_keywords(&[Keyword::IF, Keyword::NOT, Keyword::EXISTS]);
+        UserStatement::Create {
+            name: parse_user_name(&mut parser)?,
+            or_replace,
+            if_not_exists,
+            properties: UserProperties::parse(&mut parser)?,
+        }
+    } else if parser.parse_keyword(Keyword::ALTER) {
+        if !parser.parse_keyword(Keyword::USER) {
+            return Ok(None);
+        }
+        let if_exists = parser.parse_keywords(&[Keyword::IF, Keyword::EXISTS]);
+        let name = parse_user_name(&mut parser)?;
+        let action = if parser.parse_keyword(Keyword::SET) {
+            AlterUserAction::Set(UserProperties::parse(&mut parser)?)
+        } else if parser.parse_keyword(Keyword::UNSET) {
+            AlterUserAction::Unset(parser.parse_comma_separated(UserProperty::parse)?)
+        } else if parser.parse_keywords(&[Keyword::RENAME, Keyword::TO]) {
+            AlterUserAction::RenameTo(parse_user_name(&mut parser)?)
+        } else {
+            return parser.expected("SET, UNSET or RENAME TO", parser.peek_token());
+        };
+        UserStatement::Alter {
+            name,
+            if_exists,
+            action,
+        }
+    } else if parser.parse_keyword(Keyword::DROP) {
+        if !parser.parse_keyword(Keyword::USER) {
+            return Ok(None);
+        }
+        let if_exists = parser.parse_keywords(&[Keyword::IF, Keyword::EXISTS]);
+        UserStatement::Drop {
+            name: parse_user_name(&mut parser)?,
+            if_exists,
+        }
+    } else {
+        return Ok(None);
+    };
+
+    while parser.consume_token(&Token::SemiColon) {}
+    parser.expect_token(&Token::EOF)?;
+    Ok(Some(statement))
+}
@@ -27,6 +27,7 @@ use datafusion_expr::{Expr, LogicalPlan};
 use functions::conversion::to_timestamp::parse_timezone;
 use snafu::{OptionExt, ResultExt};
 use sqlparser::ast::{Ident, ObjectName};
+use state_store::StateStoreBackend;
 use std::collections::HashMap;
 use std::sync::Arc;
 use strum::{Display, EnumString};
@@ -52,6 +53,8 @@ pub struct Config {
     pub aws_sdk_operation_attempt_timeout_secs: u64,
     pub iceberg_table_timeout_secs: u64,
     pub iceberg_catalog_timeout_secs: u64,
+    /// Where sessions and query history are persisted when built with `state-store`
+    pub state_store_backend: StateStoreBackend,
 }
 
 impl From<&Config> for CatalogListConfig {
@@ -97,6 +100,7 @@ impl Default for Config {
             aws_sdk_operation_attempt_timeout_secs: 10,
             iceberg_table_timeout_secs: 30,
             iceberg_catalog_timeout_secs: 10,
+            state_store_backend: StateStoreBackend::default(),
         }
     }
 }
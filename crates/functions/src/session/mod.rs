@@ -81,11 +81,34 @@ fn current_session_udf() -> ScalarUDF {
     create_session_context_udf!("current_session", "default")
 }
 
+/// Returns the name of the user currently logged into the system.
+fn current_user_udf() -> ScalarUDF {
+    create_session_context_udf!("current_user", "")
+}
+
 /// Returns the IP address of the client that submitted the request.
 fn current_ip_address_udf() -> ScalarUDF {
     create_session_context_udf!("current_ip_address", "")
 }
 
+/// Session context function that returns NULL unless the session provides a value.
+fn nullable_session_context_udf(name: &str) -> ScalarUDF {
+    let fun: ScalarFunctionImplementation =
+        Arc::new(move |_args| Ok(ColumnarValue::Scalar(ScalarValue::Utf8(None))));
+    create_udf(name, vec![], DataType::Utf8, Volatility::Volatile, fun)
+}
+
+/// Returns the transaction id of an open transaction in the current session.
+fn current_transaction_udf() -> ScalarUDF {
+    nullable_session_context_udf("current_transaction")
+}
+
+/// Returns the transaction id of the last transaction that was either committed
+/// or rolled back in the current session.
+fn last_transaction_udf() -> ScalarUDF {
+    nullable_session_context_udf("last_transaction")
+}
+
 pub fn register_session_context_udfs(registry: &mut dyn FunctionRegistry) -> Result<()> {
     let udfs = [
         current_client_udf(),
@@ -96,8 +119,11 @@ pub fn register_session_context_udfs(registry: &mut dyn FunctionRegistry) -> Res
         current_schema_udf(),
         current_schemas_udf(),
         current_session_udf(),
+        current_transaction_udf(),
+        current_user_udf(),
         current_version_udf(),
         current_warehouse_udf(),
+        last_transaction_udf(),
     ];
 
     for udf in udfs {
@@ -31,6 +31,16 @@ test_query!(
     "SELECT LAST_QUERY_ID(), LAST_QUERY_ID(-1), LAST_QUERY_ID(2)",
     snapshot_path = "session"
 );
+test_query!(
+    session_transaction,
+    "SELECT CURRENT_TRANSACTION(), LAST_TRANSACTION()",
+    snapshot_path = "session"
+);
+test_query!(
+    session_current_user,
+    "SELECT CURRENT_USER()",
+    snapshot_path = "session"
+);
 test_query!(
     session_current_ip_address,
     "SELECT CURRENT_IP_ADDRESS()",
@@ -1,9 +1,11 @@
 use crate::visitors::{
-    fetch_to_limit, functions_rewriter, inline_aliases_in_query, json_element, like_ilike_any,
-    rlike_regexp_expr_rewriter, select_expr_aliases, table_functions, table_functions_cte_relation,
+    copy_into_identifiers, fetch_to_limit, functions_rewriter, inline_aliases_in_query,
+    json_element, like_ilike_any, rlike_regexp_expr_rewriter, select_expr_aliases, table_functions,
+    table_functions_cte_relation,
 };
 use datafusion::prelude::SessionContext;
 use datafusion::sql::parser::Statement as DFStatement;
+use datafusion::sql::sqlparser::ast::Statement;
 use datafusion_common::Result as DFResult;
 
 #[test]
@@ -465,6 +467,18 @@ fn test_table_function_result_scan() -> DFResult<()> {
             "SELECT * FROM TABLE(FLATTEN(input => parse_json('[1, 77]')))",
             "SELECT * FROM FLATTEN(input => parse_json('[1, 77]'))",
         ),
+        (
+            "SELECT * FROM TABLE(information_schema.copy_history(table_name => 'mytable'))",
+            "SELECT * FROM information_schema.copy_history(table_name => 'mytable')",
+        ),
+        (
+            "SELECT * FROM TABLE(information_schema.external_table_files(table_name => 'ext'))",
+            "SELECT * FROM information_schema.external_table_files(table_name => 'ext')",
+        ),
+        (
+            "SELECT * FROM TABLE(INFER_SCHEMA(LOCATION => '@my_stage/data/', FILE_FORMAT => 'my_format'))",
+            "SELECT * FROM INFER_SCHEMA(LOCATION => '@my_stage/data/', FILE_FORMAT => 'my_format')",
+        ),
     ];
 
     for (input, expected) in cases {
@@ -477,6 +491,31 @@ fn test_table_function_result_scan() -> DFResult<()> {
     Ok(())
 }
 
+#[test]
+fn test_copy_into_identifiers() -> DFResult<()> {
+    let state = SessionContext::new().state();
+    let cases = vec![
+        ("COPY INTO mytable FROM @my_stage/data/", "mytable"),
+        (
+            "COPY INTO @my_stage/unload/ FROM (SELECT 1)",
+            "@my_stage/unload/",
+        ),
+    ];
+
+    for (input, expected) in cases {
+        let mut statement = state.sql_to_statement(input, "snowflake")?;
+        if let DFStatement::Statement(ref mut stmt) = statement {
+            copy_into_identifiers::visit(stmt);
+            if let Statement::CopyIntoSnowflake { into, .. } = stmt.as_ref() {
+                assert_eq!(into.to_string(), expected);
+                continue;
+            }
+        }
+        panic!("Expected a COPY INTO statement: {input}");
+    }
+    Ok(())
+}
+
 #[test]
 fn test_fetch_to_limit_error_on_missing_quantity() -> DFResult<()> {
     let state = SessionContext::new().state();
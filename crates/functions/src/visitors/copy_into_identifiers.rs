@@ -1,5 +1,5 @@
 use datafusion_expr::sqlparser::ast::VisitMut;
-use datafusion_expr::sqlparser::ast::{ObjectName, ObjectNamePart};
+use datafusion_expr::sqlparser::ast::{CopyIntoSnowflakeKind, ObjectName, ObjectNamePart};
 use datafusion_expr::sqlparser::ast::{Statement, VisitorMut};
 use std::ops::ControlFlow;
 
@@ -17,7 +17,9 @@ use std::ops::ControlFlow;
 /// - `@<namespace>.<ext_stage_name>/<path>`
 ///
 /// Since those prefixes are not valid table names in many downstream systems,
-/// this visitor ensures that `into` and `from_obj` identifiers are cleaned accordingly.
+/// this visitor ensures that the `into` identifier is cleaned accordingly. The `from_obj`
+/// stage reference and the `into` stage reference of `COPY INTO <location>` are kept as
+/// is, they are resolved to named stages by the executor.
 #[derive(Debug, Default)]
 pub struct CopyIntoStatementIdentifiers {}
 
@@ -26,8 +28,8 @@ impl VisitorMut for CopyIntoStatementIdentifiers {
 
     fn pre_visit_statement(&mut self, statement: &mut Statement) -> ControlFlow<Self::Break> {
         if let Statement::CopyIntoSnowflake {
+            kind,
             into,
-            from_obj,
             stage_params,
             ..
         } = statement
@@ -39,9 +41,9 @@ impl VisitorMut for CopyIntoStatementIdentifiers {
                     ident.value = sanitized;
                 }
             }
-            sanitize_identifier(into);
-            if let Some(from_obj) = from_obj {
-                sanitize_identifier(from_obj);
+            // The stage of `COPY INTO <location>` is resolved by the executor
+            if *kind == CopyIntoSnowflakeKind::Table {
+                sanitize_identifier(into);
             }
             if let Some(storage_integration) = &mut stage_params.storage_integration {
                 *storage_integration = storage_integration
@@ -54,8 +54,16 @@ impl VisitorMut for TableFunctionVisitor {
                     alias,
                 } = &mut item.relation
                 {
-                    let func_name = name.to_string();
-                    if matches!(func_name.to_lowercase().as_str(), "result_scan" | "flatten") {
+                    // Functions of the information schema such as `COPY_HISTORY` may be qualified
+                    let func_name = name.0.last().map(ToString::to_string).unwrap_or_default();
+                    if matches!(
+                        func_name.to_lowercase().as_str(),
+                        "result_scan"
+                            | "flatten"
+                            | "copy_history"
+                            | "external_table_files"
+                            | "infer_schema"
+                    ) {
                         item.relation = TableFactor::Function {
                             name: name.clone(),
                             args: args.args.clone(),
@@ -411,18 +411,6 @@ pub const CONTEXT_FUNCTIONS: &[(&str, FunctionInfo)] = &[
     )
     .with_docs("https://docs.snowflake.com/en/sql-reference/functions/current_statement")
     ),
-    ("CURRENT_TRANSACTION", FunctionInfo::new(
-        "CURRENT_TRANSACTION",
-        "Returns the transaction id of an open transaction in the current session."
-    )
-    .with_docs("https://docs.snowflake.com/en/sql-reference/functions/current_transaction")
-    ),
-    ("CURRENT_USER", FunctionInfo::new(
-        "CURRENT_USER",
-        "Returns the name of the user currently logged into the system."
-    )
-    .with_docs("https://docs.snowflake.com/en/sql-reference/functions/current_user")
-    ),
     ("GETDATE", FunctionInfo::new(
         "GETDATE",
         "Returns the current timestamp for the system in the local time zone."
@@ -483,12 +471,6 @@ pub const CONTEXT_FUNCTIONS: &[(&str, FunctionInfo)] = &[
     )
     .with_docs("https://docs.snowflake.com/en/sql-reference/functions/is_role_in_session")
     ),
-    ("LAST_TRANSACTION", FunctionInfo::new(
-        "LAST_TRANSACTION",
-        "Returns the transaction ID of the last transaction that was either committed or rolled back in the current session."
-    )
-    .with_docs("https://docs.snowflake.com/en/sql-reference/functions/last_transaction")
-    ),
     ("LOCALTIME", FunctionInfo::new(
         "LOCALTIME",
         "Returns the current time for the system."
@@ -1465,12 +1447,6 @@ pub const INFORMATION_SCHEMA_FUNCTIONS: &[(&str, FunctionInfo)] = &[
     )
     .with_docs("https://docs.snowflake.com/en/sql-reference/functions/complete_task_graphs")
     ),
-    ("COPY_HISTORY", FunctionInfo::new(
-        "COPY_HISTORY",
-        "This table function can be used to query Snowflake data loading history along various dimensions within the last 14 days."
-    )
-    .with_docs("https://docs.snowflake.com/en/sql-reference/functions/copy_history")
-    ),
     ("CURRENT_TASK_GRAPHS", FunctionInfo::new(
         "CURRENT_TASK_GRAPHS",
         "Returns the status of a graph run that is currently scheduled or is executing."
@@ -1543,12 +1519,6 @@ pub const INFORMATION_SCHEMA_FUNCTIONS: &[(&str, FunctionInfo)] = &[
     )
     .with_docs("https://docs.snowflake.com/en/sql-reference/functions/external_functions_history")
     ),
-    ("EXTERNAL_TABLE_FILES", FunctionInfo::new(
-        "EXTERNAL_TABLE_FILES",
-        "This table function can be used to query information about the staged data files included in the metadata for a specified external table."
-    )
-    .with_docs("https://docs.snowflake.com/en/sql-reference/functions/external_table_files")
-    ),
     ("EXTERNAL_TABLE_FILE_REGISTRATION_HISTORY", FunctionInfo::new(
         "EXTERNAL_TABLE_FILE_REGISTRATION_HISTORY",
         "This table function can be used to query information about the metadata history for an external table."
@@ -3089,12 +3059,6 @@ pub const TABLE_FUNCTIONS: &[(&str, FunctionInfo)] = &[
     )
     .with_docs("https://docs.snowflake.com/en/sql-reference/functions/get_object_references")
     ),
-    ("INFER_SCHEMA", FunctionInfo::new(
-        "INFER_SCHEMA",
-        "Automatically detects the file metadata schema in a set of staged data files that contain semi-structured data and retrieves the column definitions."
-    )
-    .with_docs("https://docs.snowflake.com/en/sql-reference/functions/infer_schema")
-    ),
     ("REST_EVENT_HISTORY", FunctionInfo::new(
         "REST_EVENT_HISTORY",
         "Returns a list of SCIM REST API requests made to Snowflake over a specified time interval."
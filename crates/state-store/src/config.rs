@@ -1,4 +1,5 @@
 use crate::error::{DynamoDbCredentialsSnafu, Error, Result};
+use crate::{DynamoDbStateStore, FileStateStore, StateStore};
 use aws_config::Region;
 use aws_config::meta::credentials::CredentialsProviderChain;
 use aws_config::{BehaviorVersion, defaults};
@@ -9,6 +10,28 @@ use aws_sdk_dynamodb::config::Builder as DynamoConfigBuilder;
 use aws_sdk_dynamodb::config::retry::RetryConfig;
 use snafu::ResultExt;
 use std::env;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Where sessions and query history are persisted.
+#[derive(Debug, Clone, Default)]
+pub enum StateStoreBackend {
+    /// `DynamoDB` table configured from the environment, see [`DynamoDbConfig::from_env`]
+    #[default]
+    DynamoDb,
+    /// JSON documents in the given local directory
+    File(PathBuf),
+}
+
+impl StateStoreBackend {
+    /// Opens the state store of this backend.
+    pub async fn open(&self) -> Result<Arc<dyn StateStore>> {
+        match self {
+            Self::DynamoDb => Ok(Arc::new(DynamoDbStateStore::new_from_env().await?)),
+            Self::File(path) => Ok(Arc::new(FileStateStore::new(path.clone()).await?)),
+        }
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct DynamoDbConfig {
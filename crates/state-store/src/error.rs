@@ -24,6 +24,14 @@ pub enum Error {
         #[snafu(implicit)]
         location: Location,
     },
+    #[snafu(display("State store file {path} error: {error}"))]
+    FileIo {
+        path: String,
+        #[snafu(source)]
+        error: std::io::Error,
+        #[snafu(implicit)]
+        location: Location,
+    },
     #[snafu(display("Dynamodb get item error: {error}"))]
     DynamoDbGetItem {
         #[snafu(source(from(SdkError<GetItemError, HttpResponse>, Box::new)))]
@@ -377,6 +377,10 @@ impl Query {
         self.rows_updated = Some(rows_count);
     }
 
+    pub const fn set_rows_unloaded(&mut self, rows_count: u64) {
+        self.rows_unloaded = Some(rows_count);
+    }
+
     pub fn set_query_type(&mut self, query_type: String) {
         self.query_type = Some(query_type);
     }
@@ -0,0 +1,359 @@
+use crate::StateStore;
+use crate::error::{Error, FailedToParseJsonSnafu, FileIoSnafu, Result};
+use crate::models::{Query, SessionRecord};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use snafu::ResultExt;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Write;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use tokio::fs;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+const SESSIONS_DIR: &str = "sessions";
+const QUERIES_DIR: &str = "queries";
+
+/// Lookup tables for the secondary keys of query records, rebuilt from the query
+/// documents when the store is opened.
+#[derive(Debug, Default)]
+struct QueryIndex {
+    by_request_id: HashMap<Uuid, Uuid>,
+    by_session_id: HashMap<String, BTreeSet<Uuid>>,
+    session_by_query_id: HashMap<Uuid, String>,
+}
+
+impl QueryIndex {
+    fn insert(&mut self, query: &Query) {
+        self.remove(&query.query_id);
+        if let Some(request_id) = query.request_id {
+            self.by_request_id.insert(request_id, query.query_id);
+        }
+        self.by_session_id
+            .entry(query.session_id.clone())
+            .or_default()
+            .insert(query.query_id);
+        self.session_by_query_id
+            .insert(query.query_id, query.session_id.clone());
+    }
+
+    fn remove(&mut self, query_id: &Uuid) {
+        if let Some(session_id) = self.session_by_query_id.remove(query_id)
+            && let Some(queries) = self.by_session_id.get_mut(&session_id)
+        {
+            queries.remove(query_id);
+            if queries.is_empty() {
+                self.by_session_id.remove(&session_id);
+            }
+        }
+        self.by_request_id.retain(|_, id| id != query_id);
+    }
+}
+
+/// State store keeping sessions and query history as JSON documents in a local
+/// directory, for deployments without `DynamoDB`.
+///
+/// Every record is a file of its own (`sessions/<id>.json`, `queries/<id>.json`) that is
+/// replaced atomically on update, so a crash never leaves a partially written record.
+#[derive(Debug)]
+pub struct FileStateStore {
+    root: PathBuf,
+    index: RwLock<QueryIndex>,
+}
+
+impl FileStateStore {
+    /// Opens the state store kept in `root`, creating the directory if needed.
+    pub async fn new(root: impl Into<PathBuf>) -> Result<Self> {
+        let root = root.into();
+        for dir in [SESSIONS_DIR, QUERIES_DIR] {
+            let path = root.join(dir);
+            fs::create_dir_all(&path).await.context(FileIoSnafu {
+                path: path.display().to_string(),
+            })?;
+        }
+        let store = Self {
+            root,
+            index: RwLock::new(QueryIndex::default()),
+        };
+        store.load_index().await?;
+        Ok(store)
+    }
+
+    async fn load_index(&self) -> Result<()> {
+        let dir = self.root.join(QUERIES_DIR);
+        let io_context = || FileIoSnafu {
+            path: dir.display().to_string(),
+        };
+        let mut index = self.index.write().await;
+        let mut entries = fs::read_dir(&dir).await.context(io_context())?;
+        while let Some(entry) = entries.next_entry().await.context(io_context())? {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                let query: Query = read_document(&path).await?;
+                index.insert(&query);
+            }
+        }
+        Ok(())
+    }
+
+    fn session_path(&self, session_id: &str) -> PathBuf {
+        self.root
+            .join(SESSIONS_DIR)
+            .join(format!("{}.json", file_stem(session_id)))
+    }
+
+    fn query_path(&self, query_id: &Uuid) -> PathBuf {
+        self.root.join(QUERIES_DIR).join(format!("{query_id}.json"))
+    }
+
+    async fn read_query(&self, query_id: &Uuid) -> Result<Query> {
+        read_document(&self.query_path(query_id)).await
+    }
+}
+
+#[async_trait::async_trait]
+impl StateStore for FileStateStore {
+    async fn put_new_session(&self, session_id: &str) -> Result<()> {
+        self.put_session(SessionRecord::new(session_id)).await
+    }
+
+    async fn put_session(&self, session: SessionRecord) -> Result<()> {
+        write_document(&self.session_path(&session.session_id), &session).await
+    }
+
+    async fn get_session(&self, session_id: &str) -> Result<SessionRecord> {
+        read_document(&self.session_path(session_id)).await
+    }
+
+    async fn delete_session(&self, session_id: &str) -> Result<()> {
+        remove_document(&self.session_path(session_id)).await
+    }
+
+    async fn update_session(&self, session: SessionRecord) -> Result<()> {
+        self.put_session(session).await
+    }
+
+    async fn put_query(&self, query: &Query) -> Result<()> {
+        // Hold the index while writing so that it never points at a missing document
+        let mut index = self.index.write().await;
+        write_document(&self.query_path(&query.query_id), query).await?;
+        index.insert(query);
+        Ok(())
+    }
+
+    async fn get_query(&self, query_id: &str) -> Result<Query> {
+        let query_id = Uuid::parse_str(query_id).map_err(|_| Error::NotFound)?;
+        self.read_query(&query_id).await
+    }
+
+    async fn get_query_by_request_id(&self, request_id: &str) -> Result<Query> {
+        let request_id = Uuid::parse_str(request_id).map_err(|_| Error::NotFound)?;
+        let index = self.index.read().await;
+        let query_id = index
+            .by_request_id
+            .get(&request_id)
+            .ok_or(Error::NotFound)?;
+        self.read_query(query_id).await
+    }
+
+    async fn get_queries_by_session_id(&self, session_id: &str) -> Result<Vec<Query>> {
+        let index = self.index.read().await;
+        let Some(query_ids) = index.by_session_id.get(session_id) else {
+            return Ok(Vec::new());
+        };
+        let mut queries = Vec::with_capacity(query_ids.len());
+        for query_id in query_ids {
+            queries.push(self.read_query(query_id).await?);
+        }
+        queries.sort_by_key(|query| query.start_time);
+        Ok(queries)
+    }
+
+    async fn delete_query(&self, query_id: &str) -> Result<()> {
+        let query_id = Uuid::parse_str(query_id).map_err(|_| Error::NotFound)?;
+        let mut index = self.index.write().await;
+        if !index.session_by_query_id.contains_key(&query_id) {
+            return Err(Error::NotFound);
+        }
+        remove_document(&self.query_path(&query_id)).await?;
+        index.remove(&query_id);
+        Ok(())
+    }
+
+    async fn update_query(&self, query: &Query) -> Result<()> {
+        self.put_query(query).await
+    }
+}
+
+/// Turns a key into a file name, escaping everything except ascii alphanumerics,
+/// `-` and `_` so that keys can't reach outside of their directory.
+fn file_stem(key: &str) -> String {
+    let mut stem = String::with_capacity(key.len());
+    for byte in key.bytes() {
+        if byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_' {
+            stem.push(char::from(byte));
+        } else {
+            let _ = write!(stem, "%{byte:02X}");
+        }
+    }
+    stem
+}
+
+async fn read_document<T: DeserializeOwned>(path: &Path) -> Result<T> {
+    let bytes = match fs::read(path).await {
+        Ok(bytes) => bytes,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Err(Error::NotFound),
+        Err(error) => {
+            return Err(error).context(FileIoSnafu {
+                path: path.display().to_string(),
+            });
+        }
+    };
+    serde_json::from_slice(&bytes).context(FailedToParseJsonSnafu)
+}
+
+async fn write_document<T: Serialize + Sync>(path: &Path, value: &T) -> Result<()> {
+    let bytes = serde_json::to_vec(value).context(FailedToParseJsonSnafu)?;
+    // Write next to the target and rename over it, renames are atomic within a directory
+    let tmp_path = path.with_extension(format!("json.{}.tmp", Uuid::new_v4()));
+    fs::write(&tmp_path, bytes).await.context(FileIoSnafu {
+        path: tmp_path.display().to_string(),
+    })?;
+    fs::rename(&tmp_path, path).await.context(FileIoSnafu {
+        path: path.display().to_string(),
+    })
+}
+
+async fn remove_document(path: &Path) -> Result<()> {
+    match fs::remove_file(path).await {
+        Ok(()) => Ok(()),
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(()),
+        Err(error) => Err(error).context(FileIoSnafu {
+            path: path.display().to_string(),
+        }),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::models::Variable;
+
+    fn temp_root(name: &str) -> PathBuf {
+        let root =
+            std::env::temp_dir().join(format!("embucket-state-store-{name}-{}", Uuid::new_v4()));
+        let _ = std::fs::remove_dir_all(&root);
+        root
+    }
+
+    #[tokio::test]
+    async fn test_sessions_survive_reopen() {
+        let root = temp_root("sessions");
+        let store = FileStateStore::new(&root)
+            .await
+            .expect("Failed to open state store");
+
+        let mut session = SessionRecord::new("../session/1");
+        session.variables.insert(
+            "var".to_string(),
+            Variable {
+                name: "var".to_string(),
+                value: "1".to_string(),
+                value_type: "fixed".to_string(),
+                comment: None,
+                created_at: session.created_at,
+                updated_at: None,
+            },
+        );
+        store
+            .put_session(session)
+            .await
+            .expect("Failed to put session");
+        assert!(matches!(
+            store.get_session("missing").await,
+            Err(Error::NotFound)
+        ));
+
+        let store = FileStateStore::new(&root)
+            .await
+            .expect("Failed to reopen state store");
+        let session = store
+            .get_session("../session/1")
+            .await
+            .expect("Failed to get session");
+        assert_eq!(session.variables["var"].value, "1");
+
+        store
+            .delete_session("../session/1")
+            .await
+            .expect("Failed to delete session");
+        assert!(matches!(
+            store.get_session("../session/1").await,
+            Err(Error::NotFound)
+        ));
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[tokio::test]
+    async fn test_queries_by_session_and_request_id() {
+        let root = temp_root("queries");
+        let store = FileStateStore::new(&root)
+            .await
+            .expect("Failed to open state store");
+
+        let request_id = Uuid::new_v4();
+        let first = Query::new("SELECT 1", Uuid::new_v4(), "session", Some(request_id));
+        let mut second = Query::new("SELECT 2", Uuid::new_v4(), "session", None);
+        let other = Query::new("SELECT 3", Uuid::new_v4(), "other", None);
+        for query in [&first, &second, &other] {
+            store.put_query(query).await.expect("Failed to put query");
+        }
+        second.set_rows_produced(1);
+        store
+            .update_query(&second)
+            .await
+            .expect("Failed to update query");
+
+        let store = FileStateStore::new(&root)
+            .await
+            .expect("Failed to reopen state store");
+        let queries = store
+            .get_queries_by_session_id("session")
+            .await
+            .expect("Failed to get queries");
+        assert_eq!(
+            queries.iter().map(|q| q.query_id).collect::<Vec<_>>(),
+            vec![first.query_id, second.query_id]
+        );
+        assert_eq!(queries[1].rows_produced, Some(1));
+        let by_request = store
+            .get_query_by_request_id(&request_id.to_string())
+            .await
+            .expect("Failed to get query by request id");
+        assert_eq!(by_request.query_id, first.query_id);
+
+        store
+            .delete_query(&first.query_id.to_string())
+            .await
+            .expect("Failed to delete query");
+        assert!(matches!(
+            store.get_query(&first.query_id.to_string()).await,
+            Err(Error::NotFound)
+        ));
+        assert!(matches!(
+            store.get_query_by_request_id(&request_id.to_string()).await,
+            Err(Error::NotFound)
+        ));
+        assert_eq!(
+            store
+                .get_queries_by_session_id("session")
+                .await
+                .expect("Failed to get queries")
+                .len(),
+            1
+        );
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}